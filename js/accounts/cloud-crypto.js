@@ -0,0 +1,350 @@
+// js/accounts/cloud-crypto.js
+// Optional end-to-end encryption of the synced library. A key derived from the user's passphrase (PBKDF2, then
+// HKDF into an AES-GCM key and an HMAC key) encrypts every library item, history entry, playlist, folder, smart
+// playlist and the synced settings before they leave the device. Record ids are replaced by HMACs of the real
+// ids so they stay stable between saves without revealing what they point to. Public playlists are left
+// readable, as publishing them means sharing them. Only the salt and a key check are stored next to the data.
+
+import { db } from '../db.js';
+
+const ENCRYPTION_VERSION = 1;
+const PBKDF2_ITERATIONS = 600000;
+const KEYS_SETTING = 'cloud-encryption-keys';
+const SYNC_FIELDS = ['library', 'history', 'userPlaylists', 'userFolders', 'smartPlaylists', 'settings'];
+
+// What the server still sees of an encrypted record
+const PLACEHOLDERS = {
+    playlist: (value) => ({
+        name: 'Encrypted playlist',
+        tracks: [],
+        numberOfTracks: 0,
+        isPublic: false,
+        createdAt: value.createdAt,
+        updatedAt: value.updatedAt,
+    }),
+    folder: () => ({ name: 'Encrypted folder', playlists: [] }),
+    smart_playlist: () => ({ name: 'Encrypted smart playlist', match: 'all', rules: [] }),
+};
+
+export class EncryptionLockedError extends Error {
+    constructor(message = 'The cloud library is encrypted. Enter your passphrase to sync.') {
+        super(message);
+        this.name = 'EncryptionLockedError';
+    }
+}
+
+/** The cloud copy holds encrypted records but not the salt and key check needed to read them */
+export class EncryptionInfoMissingError extends Error {
+    constructor(message = 'The cloud library holds encrypted records, but its encryption settings are missing.') {
+        super(message);
+        this.name = 'EncryptionInfoMissingError';
+    }
+}
+
+const encoder = new TextEncoder();
+const decoder = new TextDecoder();
+
+function toBase64(bytes) {
+    let binary = '';
+    bytes.forEach((byte) => {
+        binary += String.fromCharCode(byte);
+    });
+    return btoa(binary).replace(/\+/g, '-').replace(/\//g, '_').replace(/=+$/, '');
+}
+
+function fromBase64(text) {
+    const binary = atob(text.replace(/-/g, '+').replace(/_/g, '/'));
+    return Uint8Array.from(binary, (char) => char.charCodeAt(0));
+}
+
+async function hashId(keys, value) {
+    const signature = await crypto.subtle.sign('HMAC', keys.ids, encoder.encode(value));
+    return toBase64(new Uint8Array(signature).slice(0, 18));
+}
+
+async function deriveKeys(passphrase, salt, iterations) {
+    const secret = encoder.encode(passphrase.normalize('NFC'));
+    const material = await crypto.subtle.importKey('raw', secret, 'PBKDF2', false, ['deriveBits']);
+    const bits = await crypto.subtle.deriveBits({ name: 'PBKDF2', hash: 'SHA-256', salt, iterations }, material, 256);
+    const root = await crypto.subtle.importKey('raw', bits, 'HKDF', false, ['deriveKey']);
+    const hkdf = (info) => ({ name: 'HKDF', hash: 'SHA-256', salt: new Uint8Array(32), info: encoder.encode(info) });
+
+    const keys = {
+        data: await crypto.subtle.deriveKey(
+            hkdf('monochrome library data'),
+            root,
+            { name: 'AES-GCM', length: 256 },
+            false,
+            ['encrypt', 'decrypt']
+        ),
+        ids: await crypto.subtle.deriveKey(
+            hkdf('monochrome library ids'),
+            root,
+            { name: 'HMAC', hash: 'SHA-256', length: 256 },
+            false,
+            ['sign']
+        ),
+    };
+    keys.keyId = await hashId(keys, 'key check');
+    return keys;
+}
+
+/**
+ * Set up encryption with a new passphrase
+ * @returns {Promise<{info: Object, keys: Object}>} `info` is stored with the cloud data, `keys` on the device
+ */
+export async function createEncryption(passphrase, iterations = PBKDF2_ITERATIONS) {
+    const salt = crypto.getRandomValues(new Uint8Array(16));
+    const keys = await deriveKeys(passphrase, salt, iterations);
+    return { info: { version: ENCRYPTION_VERSION, salt: toBase64(salt), iterations, keyId: keys.keyId }, keys };
+}
+
+/** Derive the keys of an encrypted library, throwing if the passphrase is wrong */
+export async function unlockEncryption(passphrase, info) {
+    if (info.version > ENCRYPTION_VERSION) {
+        throw new Error('The cloud library was encrypted by a newer version of Monochrome.');
+    }
+    const keys = await deriveKeys(passphrase, fromBase64(info.salt), info.iterations);
+    if (keys.keyId !== info.keyId) throw new Error('Wrong passphrase');
+    return keys;
+}
+
+async function encryptValue(keys, value, context) {
+    const iv = crypto.getRandomValues(new Uint8Array(12));
+    const data = await crypto.subtle.encrypt(
+        { name: 'AES-GCM', iv, additionalData: encoder.encode(context) },
+        keys.data,
+        encoder.encode(JSON.stringify(value))
+    );
+    return `${toBase64(iv)}.${toBase64(new Uint8Array(data))}`;
+}
+
+async function decryptValue(keys, text, context) {
+    const [iv, data] = text.split('.');
+    const plain = await crypto.subtle.decrypt(
+        { name: 'AES-GCM', iv: fromBase64(iv), additionalData: encoder.encode(context) },
+        keys.data,
+        fromBase64(data)
+    );
+    return JSON.parse(decoder.decode(plain));
+}
+
+// Unchanged values keep their ciphertext, so saving doesn't rewrite every record
+async function encryptCached(keys, value, context, cache) {
+    const cacheKey = `${context}\n${JSON.stringify(value)}`;
+    if (!cache.has(cacheKey)) cache.set(cacheKey, await encryptValue(keys, value, context));
+    return cache.get(cacheKey);
+}
+
+async function decryptCached(keys, text, context, cache) {
+    const value = await decryptValue(keys, text, context);
+    cache.set(`${context}\n${JSON.stringify(value)}`, text);
+    return value;
+}
+
+async function encryptMap(map, context, keys, cache) {
+    const entries = await Promise.all(
+        Object.entries(map || {}).map(async ([key, value]) => {
+            if (!value || typeof value !== 'object' || (context === 'playlist' && value.isPublic)) return [key, value];
+            const id = await hashId(keys, `${context}:${key}`);
+            const encrypted = await encryptCached(keys, { key, value }, context, cache);
+            return [id, { ...PLACEHOLDERS[context]?.(value), id, encrypted }];
+        })
+    );
+    return Object.fromEntries(entries);
+}
+
+async function decryptMap(map, context, keys, cache) {
+    const entries = await Promise.all(
+        Object.entries(map || {}).map(async ([id, record]) => {
+            if (!record?.encrypted) return [id, record];
+            const { key, value } = await decryptCached(keys, record.encrypted, context, cache);
+            return [key, value];
+        })
+    );
+    return Object.fromEntries(entries);
+}
+
+async function mapSections(library, transform) {
+    const sections = await Promise.all(
+        Object.entries(library).map(async ([section, items]) => [section, await transform(items, `library:${section}`)])
+    );
+    return Object.fromEntries(sections);
+}
+
+/**
+ * Encrypt the fields of a sync payload. Fields that aren't synced data are passed through.
+ * @param {Object} fields - Same shape as /api/sync
+ * @param {Map} [cache] - Ciphertexts of values seen before
+ */
+export async function encryptSyncData(fields, keys, cache = new Map()) {
+    const result = { ...fields };
+    if (fields.library) {
+        result.library = await mapSections(fields.library, (items, context) => encryptMap(items, context, keys, cache));
+    }
+    if (Array.isArray(fields.history)) {
+        result.history = await Promise.all(
+            fields.history.map(async (entry) => ({
+                timestamp: entry.timestamp,
+                encrypted: await encryptCached(keys, entry, 'history', cache),
+            }))
+        );
+    }
+    if (fields.userPlaylists) result.userPlaylists = await encryptMap(fields.userPlaylists, 'playlist', keys, cache);
+    if (fields.userFolders) result.userFolders = await encryptMap(fields.userFolders, 'folder', keys, cache);
+    if (fields.smartPlaylists) {
+        result.smartPlaylists = await encryptMap(fields.smartPlaylists, 'smart_playlist', keys, cache);
+    }
+    if (fields.settings) {
+        const encrypted = await encryptCached(keys, fields.settings.entries, 'settings', cache);
+        result.settings = { ...fields.settings, entries: { encrypted } };
+    }
+    return result;
+}
+
+/** Reverse of encryptSyncData. Records saved before encryption was turned on are passed through. */
+export async function decryptSyncData(data, keys, cache = new Map()) {
+    const result = { ...data };
+    if (data.library) {
+        result.library = await mapSections(data.library, (items, context) => decryptMap(items, context, keys, cache));
+    }
+    if (Array.isArray(data.history)) {
+        result.history = await Promise.all(
+            data.history.map((entry) =>
+                entry?.encrypted ? decryptCached(keys, entry.encrypted, 'history', cache) : entry
+            )
+        );
+    }
+    if (data.userPlaylists) result.userPlaylists = await decryptMap(data.userPlaylists, 'playlist', keys, cache);
+    if (data.userFolders) result.userFolders = await decryptMap(data.userFolders, 'folder', keys, cache);
+    if (data.smartPlaylists) {
+        result.smartPlaylists = await decryptMap(data.smartPlaylists, 'smart_playlist', keys, cache);
+    }
+    if (data.settings?.entries?.encrypted) {
+        const entries = await decryptCached(keys, data.settings.entries.encrypted, 'settings', cache);
+        result.settings = { ...data.settings, entries };
+    }
+    return result;
+}
+
+/** Whether any record of a sync payload is still encrypted */
+export function hasEncryptedRecords(data) {
+    const isEncrypted = (record) => !!record?.encrypted;
+    return (
+        Object.values(data.library || {}).some((items) => Object.values(items || {}).some(isEncrypted)) ||
+        (Array.isArray(data.history) && data.history.some(isEncrypted)) ||
+        ['userPlaylists', 'userFolders', 'smartPlaylists'].some((field) =>
+            Object.values(data[field] || {}).some(isEncrypted)
+        ) ||
+        isEncrypted(data.settings?.entries)
+    );
+}
+
+function isSameEncryption(stored, created) {
+    return ['version', 'salt', 'iterations', 'keyId'].every((key) => stored?.[key] === created[key]);
+}
+
+/** Keeps the derived keys in IndexedDB, so the passphrase is asked for once per device */
+export const deviceKeyStore = {
+    load: () => db.getSetting(KEYS_SETTING),
+    save: (keys) => db.saveSetting(KEYS_SETTING, keys),
+    clear: () => db.saveSetting(KEYS_SETTING, null),
+};
+
+/**
+ * Wrap a sync transport so data is decrypted when loaded and encrypted when saved, whenever the cloud copy is
+ * encrypted. Loading throws EncryptionLockedError until the passphrase was entered on this device.
+ * @param {{load: Function, save: Function}} transport
+ * @param {{load: Function, save: Function, clear: Function}} [keyStore]
+ */
+export function createEncryptedTransport(transport, keyStore = deviceKeyStore) {
+    const cache = new Map();
+    let info = null;
+    let locked = false;
+    let infoMissing = false;
+
+    const getKeys = async () => {
+        const keys = await keyStore.load();
+        return keys && info && keys.keyId === info.keyId ? keys : null;
+    };
+
+    const load = async () => {
+        const data = await transport.load();
+        info = data.encryption || null;
+        locked = false;
+        infoMissing = !info && hasEncryptedRecords(data);
+        // Keep the keys, they are the only way back to these records
+        if (infoMissing) throw new EncryptionInfoMissingError();
+        if (!info) {
+            // Turned off on another device
+            if (await keyStore.load()) await keyStore.clear();
+            return data;
+        }
+        const keys = await getKeys();
+        locked = !keys;
+        if (locked) throw new EncryptionLockedError();
+        return await decryptSyncData(data, keys, cache);
+    };
+
+    const save = async (fields) => {
+        const keys = await getKeys();
+        if (info && !keys) throw new EncryptionLockedError();
+        if (infoMissing) throw new EncryptionInfoMissingError();
+        const updated = await transport.save(keys ? await encryptSyncData(fields, keys, cache) : fields);
+        return keys ? await decryptSyncData(updated, keys, cache) : updated;
+    };
+
+    const pickSyncFields = (data) => Object.fromEntries(SYNC_FIELDS.filter((f) => f in data).map((f) => [f, data[f]]));
+
+    return {
+        load,
+        save,
+
+        /** Whether the cloud copy was encrypted when last loaded */
+        isEncrypted: () => !!info,
+        /** Whether it was encrypted with a passphrase not entered on this device yet */
+        isLocked: () => locked,
+
+        async enable(passphrase) {
+            const data = await load();
+            if (info) throw new Error('The cloud library is already encrypted');
+            const { info: created, keys } = await createEncryption(passphrase);
+            const fields = pickSyncFields(data);
+            const encrypted = await encryptSyncData(fields, keys, cache);
+            const updated = await transport.save({ ...encrypted, encryption: created });
+            const stored = updated && 'encryption' in updated ? updated : await transport.load();
+            if (!isSameEncryption(stored?.encryption, created)) {
+                // Without the salt and key check the records could never be read again, so store them readable
+                await transport.save({ ...fields, encryption: null });
+                throw new Error('The sync server does not support encryption. The library was left unencrypted.');
+            }
+            await keyStore.save(keys);
+            info = created;
+        },
+
+        async unlock(passphrase) {
+            const data = await transport.load();
+            if (!data.encryption) throw new Error('The cloud library is not encrypted');
+            await keyStore.save(await unlockEncryption(passphrase, data.encryption));
+            info = data.encryption;
+            locked = false;
+        },
+
+        /** Replace the cloud copy with unencrypted fields, also when locked, e.g. after forgetting the passphrase */
+        async clear(fields) {
+            await transport.save({ ...fields, encryption: null });
+            await keyStore.clear();
+            info = null;
+            locked = false;
+            infoMissing = false;
+        },
+
+        async disable() {
+            const data = await load();
+            if (!info) return;
+            await transport.save({ ...pickSyncFields(data), encryption: null });
+            await keyStore.clear();
+            info = null;
+        },
+    };
+}
@@ -0,0 +1,219 @@
+// js/accounts/pocketbase-transport.js
+// Loads and saves the synced library and playlists straight from the PocketBase collections described in
+// database/pb_schema.json, in the same shape as /api/sync. Wrapped in createEncryptedTransport it can stand in
+// for syncManager.transport, which is how cloud sync is tested against a local PocketBase. Synced settings live in
+// user_settings and the encryption salt on app_users. History and folders aren't covered.
+
+import { getEntryKey } from '../playlist-history.js';
+
+const LIBRARY_TYPES = ['track', 'video', 'album', 'artist', 'playlist', 'mix'];
+const TRACK_TYPES = ['track', 'video', 'podcast'];
+
+const sectionName = (type) => (type === 'mix' ? 'mixes' : `${type}s`);
+const entryKey = (type, id) => `${type}:${id}`;
+
+function toDate(timestamp) {
+    return timestamp ? new Date(timestamp).toISOString() : '';
+}
+
+function toUrl(value) {
+    return typeof value === 'string' && /^https?:\/\//.test(value) ? value : '';
+}
+
+/**
+ * @param {import('pocketbase').default} pb - Signed in with access to the collections
+ * @param {string} ownerId - Id of the app_users record the data belongs to
+ */
+export function createPocketBaseTransport(pb, ownerId) {
+    const ownerFilter = pb.filter('owner = {:owner}', { owner: ownerId });
+
+    const loadRows = async () => {
+        const [owner, libraryRows, playlistRows, trackRows, smartPlaylistRows, settingsRows] = await Promise.all([
+            pb.collection('app_users').getOne(ownerId),
+            pb.collection('library_items').getFullList({ filter: ownerFilter }),
+            pb.collection('playlists').getFullList({ filter: ownerFilter, sort: 'created' }),
+            pb.collection('playlist_tracks').getFullList({
+                filter: pb.filter('playlist.owner = {:owner}', { owner: ownerId }),
+                sort: 'position',
+            }),
+            pb.collection('smart_playlists').getFullList({ filter: ownerFilter, sort: 'created' }),
+            pb.collection('user_settings').getFullList({ filter: ownerFilter }),
+        ]);
+        const settingsRow = settingsRows[0] || null;
+        return { owner, libraryRows, playlistRows, trackRows, smartPlaylistRows, settingsRow };
+    };
+
+    const load = async () => {
+        const { owner, libraryRows, playlistRows, trackRows, smartPlaylistRows, settingsRow } = await loadRows();
+
+        const library = Object.fromEntries(LIBRARY_TYPES.map((type) => [sectionName(type), {}]));
+        libraryRows.forEach((row) => {
+            library[sectionName(row.item_type)][row.item_id] = row.metadata;
+        });
+
+        const userPlaylists = {};
+        playlistRows.forEach((row) => {
+            const id = row.client_id || row.id;
+            const tracks = trackRows.filter((track) => track.playlist === row.id).map((track) => track.metadata);
+            userPlaylists[id] = {
+                id,
+                name: row.name,
+                description: row.description || '',
+                cover: row.cover_url || null,
+                isPublic: !!row.is_public,
+                tracks,
+                numberOfTracks: tracks.length,
+                createdAt: Date.parse(row.created) || Date.now(),
+                updatedAt: Date.parse(row.updated) || Date.now(),
+            };
+        });
+
+        const smartPlaylists = {};
+        smartPlaylistRows.forEach((row) => {
+            const id = row.client_id || row.id;
+            smartPlaylists[id] = {
+                id,
+                name: row.name,
+                description: row.description || '',
+                match: row.match || 'all',
+                rules: row.rules || [],
+                sort: row.sort || null,
+                limit: row.track_limit || null,
+                createdAt: Date.parse(row.created) || Date.now(),
+                updatedAt: Date.parse(row.updated) || Date.now(),
+            };
+        });
+
+        const settings = settingsRow
+            ? {
+                  version: settingsRow.schema_version,
+                  revision: settingsRow.revision,
+                  entries: settingsRow.entries || {},
+              }
+            : null;
+
+        return { library, userPlaylists, smartPlaylists, settings, encryption: owner.encryption || null };
+    };
+
+    const saveLibrary = async (library, libraryRows) => {
+        const wanted = new Map();
+        LIBRARY_TYPES.forEach((type) => {
+            Object.entries(library[sectionName(type)] || {}).forEach(([id, item]) => {
+                if (item && typeof item === 'object') wanted.set(entryKey(type, id), { type, id, item });
+            });
+        });
+
+        for (const row of libraryRows) {
+            const key = entryKey(row.item_type, row.item_id);
+            if (wanted.has(key)) wanted.delete(key);
+            else await pb.collection('library_items').delete(row.id);
+        }
+        for (const { type, id, item } of wanted.values()) {
+            await pb.collection('library_items').create({
+                owner: ownerId,
+                item_type: type,
+                item_id: String(id),
+                metadata: item,
+                added_at: toDate(item.addedAt),
+            });
+        }
+    };
+
+    const savePlaylists = async (userPlaylists, playlistRows, trackRows) => {
+        const rowsByClientId = new Map(playlistRows.map((row) => [row.client_id || row.id, row]));
+
+        for (const playlist of Object.values(userPlaylists)) {
+            if (!playlist || typeof playlist !== 'object') continue;
+            const fields = {
+                owner: ownerId,
+                client_id: String(playlist.id),
+                name: playlist.name || 'Untitled playlist',
+                description: playlist.description || '',
+                cover_url: toUrl(playlist.cover),
+                is_public: !!playlist.isPublic,
+            };
+            const existing = rowsByClientId.get(String(playlist.id));
+            rowsByClientId.delete(String(playlist.id));
+            const row = existing
+                ? await pb.collection('playlists').update(existing.id, fields)
+                : await pb.collection('playlists').create(fields);
+
+            const tracks = playlist.tracks || [];
+            const oldTracks = trackRows.filter((track) => track.playlist === row.id);
+            const unchanged =
+                oldTracks.length === tracks.length &&
+                oldTracks.every((row, i) => entryKey(row.item_type, row.item_id) === getEntryKey(tracks[i]));
+            if (unchanged) continue;
+
+            // Positions are unique per playlist, so the old rows go before the new ones are written
+            for (const track of oldTracks) await pb.collection('playlist_tracks').delete(track.id);
+            for (const [position, track] of tracks.entries()) {
+                await pb.collection('playlist_tracks').create({
+                    playlist: row.id,
+                    item_type: TRACK_TYPES.includes(track.type) ? track.type : 'track',
+                    item_id: String(track.id),
+                    metadata: track,
+                    position,
+                    added_at: toDate(track.addedAt),
+                });
+            }
+        }
+
+        for (const row of rowsByClientId.values()) {
+            await pb.collection('playlists').delete(row.id);
+        }
+    };
+
+    const saveSmartPlaylists = async (smartPlaylists, smartPlaylistRows) => {
+        const rowsByClientId = new Map(smartPlaylistRows.map((row) => [row.client_id || row.id, row]));
+
+        for (const playlist of Object.values(smartPlaylists)) {
+            if (!playlist || typeof playlist !== 'object') continue;
+            const fields = {
+                owner: ownerId,
+                client_id: String(playlist.id),
+                name: playlist.name || 'Untitled smart playlist',
+                description: playlist.description || '',
+                match: playlist.match === 'any' ? 'any' : 'all',
+                rules: playlist.rules || [],
+                sort: playlist.sort || '',
+                track_limit: playlist.limit > 0 ? playlist.limit : null,
+            };
+            const existing = rowsByClientId.get(String(playlist.id));
+            rowsByClientId.delete(String(playlist.id));
+            if (existing) await pb.collection('smart_playlists').update(existing.id, fields);
+            else await pb.collection('smart_playlists').create(fields);
+        }
+
+        for (const row of rowsByClientId.values()) {
+            await pb.collection('smart_playlists').delete(row.id);
+        }
+    };
+
+    const saveSettings = async (settings, settingsRow) => {
+        if (!settings) {
+            if (settingsRow) await pb.collection('user_settings').delete(settingsRow.id);
+            return;
+        }
+        const fields = {
+            owner: ownerId,
+            schema_version: settings.version,
+            revision: settings.revision || 0,
+            entries: settings.entries || {},
+        };
+        if (settingsRow) await pb.collection('user_settings').update(settingsRow.id, fields);
+        else await pb.collection('user_settings').create(fields);
+    };
+
+    const save = async (fields) => {
+        const { libraryRows, playlistRows, trackRows, smartPlaylistRows, settingsRow } = await loadRows();
+        if (fields.library) await saveLibrary(fields.library, libraryRows);
+        if (fields.userPlaylists) await savePlaylists(fields.userPlaylists, playlistRows, trackRows);
+        if (fields.smartPlaylists) await saveSmartPlaylists(fields.smartPlaylists, smartPlaylistRows);
+        if ('settings' in fields) await saveSettings(fields.settings, settingsRow);
+        if ('encryption' in fields) await pb.collection('app_users').update(ownerId, { encryption: fields.encryption });
+        return await load();
+    };
+
+    return { load, save };
+}
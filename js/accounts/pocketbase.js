@@ -3,6 +3,9 @@ import PocketBase from 'pocketbase';
 import { db } from '../db.js';
 import { authManager } from './auth.js';
 import { authApi } from './authApi.js';
+import { playlistTarget, pushOps } from '../sync-oplog.js';
+import { createEncryptedTransport, EncryptionInfoMissingError, EncryptionLockedError } from './cloud-crypto.js';
+import { getSyncBackend, SyncConflictError } from './sync-backend.js';
 
 const DEFAULT_POCKETBASE_URL = 'https://data.samidy.xyz';
 const POCKETBASE_URL =
@@ -18,6 +21,30 @@ const syncManager = {
     _userRecordCache: null,
     _getUserRecordPromise: null,
     _isSyncing: false,
+    _pushingOps: Promise.resolve(),
+
+    // Where the cloud copy is kept, the Monochrome account or a WebDAV folder (see sync-backend.js), and the
+    // transport that loads and saves it, encrypted if the user turned that on
+    backend: null,
+    transport: null,
+
+    /** Switch where the library is synced to, see getSyncBackend() */
+    useBackend(backend) {
+        this.backend = backend;
+        this.transport = createEncryptedTransport(backend);
+        this._userRecordCache = null;
+        this._getUserRecordPromise = null;
+    },
+
+    /** Who the synced data belongs to: the signed-in account, the WebDAV folder, or null when not syncing */
+    getSyncUser() {
+        return this.backend.getUser();
+    },
+
+    /** Whether the backend keeps the given field of the cloud copy; the others stay on the device */
+    supports(field) {
+        return this.backend.fields.includes(field);
+    },
 
     async _getUserRecord(uid) {
         if (!uid) return null;
@@ -32,7 +59,7 @@ const syncManager = {
 
         const promise = (async () => {
             try {
-                const data = await authApi('/api/sync');
+                const data = await this.transport.load();
                 const record = {
                     id: data.appUserId,
                     firebase_id: uid,
@@ -51,10 +78,21 @@ const syncManager = {
                     history: data.history || [],
                     user_playlists: data.userPlaylists || {},
                     user_folders: data.userFolders || {},
+                    smart_playlists: data.smartPlaylists || {},
+                    settings: data.settings || null,
                 };
                 this._userRecordCache = record;
                 return record;
             } catch (error) {
+                if (error instanceof EncryptionLockedError) {
+                    console.warn('[CloudSync] Cloud library is encrypted, waiting for the passphrase');
+                    window.dispatchEvent(new CustomEvent('cloud-encryption-locked'));
+                    return null;
+                }
+                if (error instanceof EncryptionInfoMissingError) {
+                    console.error('[CloudSync] Cloud library is encrypted but its encryption settings are missing');
+                    return null;
+                }
                 console.error('[CloudSync] Failed to get user sync data:', error);
                 return null;
             } finally {
@@ -67,7 +105,7 @@ const syncManager = {
     },
 
     async getUserData() {
-        const user = authManager.user;
+        const user = this.getSyncUser();
         if (!user) return null;
 
         const record = await this._getUserRecord(user.$id);
@@ -83,6 +121,10 @@ const syncManager = {
             this.safeParseInternal(record.user_folders, 'user_folders', {}),
             'folder'
         );
+        const smartPlaylists = this._dedupeRecordMap(
+            this.safeParseInternal(record.smart_playlists, 'smart_playlists', {}),
+            'smart_playlist'
+        );
         const favoriteAlbums = this.safeParseInternal(record.favorite_albums, 'favorite_albums', []);
 
         const profile = {
@@ -98,7 +140,7 @@ const syncManager = {
             favorite_albums: favoriteAlbums,
         };
 
-        return { library, history, userPlaylists, userFolders, profile };
+        return { library, history, userPlaylists, userFolders, smartPlaylists, profile };
     },
 
     async _updateUserJSON(uid, field, data) {
@@ -114,25 +156,31 @@ const syncManager = {
                 history: 'history',
                 user_playlists: 'userPlaylists',
                 user_folders: 'userFolders',
+                smart_playlists: 'smartPlaylists',
+                settings: 'settings',
             };
             const syncField = syncFieldMap[field];
-            if (!syncField) return;
+            if (!syncField || !this.supports(syncField)) return;
             let payload = data;
             if (field === 'user_playlists') payload = this._dedupeRecordMap(data, 'playlist');
             if (field === 'user_folders') payload = this._dedupeRecordMap(data, 'folder');
-            const updated = await authApi('/api/sync', {
-                method: 'PATCH',
-                body: JSON.stringify({ [syncField]: payload }),
-            });
+            if (field === 'smart_playlists') payload = this._dedupeRecordMap(data, 'smart_playlist');
+            const updated = await this.transport.save({ [syncField]: payload });
             this._userRecordCache = {
                 ...record,
                 library: updated.library || record.library,
                 history: updated.history || record.history,
                 user_playlists: updated.userPlaylists || record.user_playlists,
                 user_folders: updated.userFolders || record.user_folders,
+                smart_playlists: updated.smartPlaylists || record.smart_playlists,
+                settings: updated.settings || record.settings,
             };
+            return true;
         } catch (error) {
-            console.error(`Failed to sync ${field} to auth server:`, error);
+            console.error(`Failed to sync ${field} to the cloud:`, error);
+            // Changed by another device in between; load it again next time
+            if (error instanceof SyncConflictError) this._userRecordCache = null;
+            return false;
         }
     },
 
@@ -239,29 +287,9 @@ const syncManager = {
         return deduped;
     },
 
-    async syncLibraryItem(type, item, added) {
-        const user = authManager.user;
-        if (!user) return;
-
-        const record = await this._getUserRecord(user.$id);
-        if (!record) return;
-
-        let library = this.safeParseInternal(record.library, 'library', {});
-
-        const pluralType = type === 'mix' ? 'mixes' : `${type}s`;
-        const key = type === 'playlist' ? item.uuid : item.id;
-
-        if (!library[pluralType]) {
-            library[pluralType] = {};
-        }
-
-        if (added) {
-            library[pluralType][key] = this._minifyItem(type, item);
-        } else {
-            delete library[pluralType][key];
-        }
-
-        await this._updateUserJSON(user.$id, 'library', library);
+    // The change itself was queued by db.toggleFavorite
+    async syncLibraryItem(_type, _item, _added) {
+        await this.pushQueuedOps();
     },
 
     _minifyItem(type, item) {
@@ -368,7 +396,7 @@ const syncManager = {
     },
 
     async syncHistoryItem(historyEntry) {
-        const user = authManager.user;
+        const user = this.getSyncUser();
         if (!user) return;
 
         const record = await this._getUserRecord(user.$id);
@@ -381,16 +409,33 @@ const syncManager = {
     },
 
     async clearHistory() {
-        const user = authManager.user;
+        const user = this.getSyncUser();
         if (!user) return;
 
         await this._updateUserJSON(user.$id, 'history', []);
     },
 
+    _playlistRecord(playlist) {
+        return {
+            id: playlist.id,
+            name: playlist.name,
+            cover: playlist.cover || null,
+            tracks: playlist.tracks ? playlist.tracks.map((t) => this._minifyItem(t.type || 'track', t)) : [],
+            createdAt: playlist.createdAt || Date.now(),
+            updatedAt: playlist.updatedAt || Date.now(),
+            numberOfTracks: playlist.tracks ? playlist.tracks.length : 0,
+            images: playlist.images || [],
+            isPublic: playlist.isPublic || false,
+        };
+    },
+
     async syncUserPlaylist(playlist, action) {
-        const user = authManager.user;
+        const user = this.getSyncUser();
         if (!user) return;
 
+        // Track changes are merged with the cloud copy first, so only the details are written below
+        if (action !== 'delete') await this.pushQueuedOps();
+
         const record = await this._getUserRecord(user.$id);
         if (!record) return;
 
@@ -400,17 +445,14 @@ const syncManager = {
             delete userPlaylists[playlist.id];
             await this.unpublishPlaylist(playlist.id);
         } else {
-            userPlaylists[playlist.id] = {
-                id: playlist.id,
-                name: playlist.name,
-                cover: playlist.cover || null,
-                tracks: playlist.tracks ? playlist.tracks.map((t) => this._minifyItem(t.type || 'track', t)) : [],
-                createdAt: playlist.createdAt || Date.now(),
-                updatedAt: playlist.updatedAt || Date.now(),
-                numberOfTracks: playlist.tracks ? playlist.tracks.length : 0,
-                images: playlist.images || [],
-                isPublic: playlist.isPublic || false,
-            };
+            const cloud = userPlaylists[playlist.id];
+            const updated = this._playlistRecord(playlist);
+            if (Array.isArray(cloud?.tracks)) {
+                updated.tracks = cloud.tracks;
+                updated.numberOfTracks = cloud.tracks.length;
+            }
+            if (JSON.stringify(cloud) === JSON.stringify(updated)) return;
+            userPlaylists[playlist.id] = updated;
 
             if (playlist.isPublic) {
                 await this.publishPlaylist(playlist);
@@ -420,8 +462,136 @@ const syncManager = {
         await this._updateUserJSON(user.$id, 'user_playlists', userPlaylists);
     },
 
+    /**
+     * Replay the edits queued by db.js on a fresh copy of the cloud data and save the result. Edits stay
+     * queued while offline or when saving fails, and are pushed on the next call.
+     * @returns {Promise<boolean>} Whether the queue is empty afterwards
+     */
+    async pushQueuedOps() {
+        const push = this._pushingOps.then(() => this._pushQueuedOps());
+        this._pushingOps = push.catch(() => false);
+        return push;
+    },
+
+    async _pushQueuedOps() {
+        const user = this.getSyncUser();
+        if (!user || !navigator.onLine) return false;
+
+        if (await db.hasSyncQueueOverflowed()) return await this._pushLibrarySnapshot();
+
+        const ops = await db.getQueuedSyncOps();
+        if (ops.length === 0) return true;
+
+        const playlists = await db.getPlaylists(true);
+        const localPlaylists = Object.fromEntries(playlists.map((p) => [p.id, this._playlistRecord(p)]));
+
+        let merged;
+        for (let attempt = 0; !merged; attempt++) {
+            try {
+                merged = await pushOps(this.transport, ops, localPlaylists);
+            } catch (error) {
+                // Another device saved in between, replay the edits on its copy
+                if (error instanceof SyncConflictError && attempt < 2) continue;
+                console.error('[CloudSync] Failed to push queued changes:', error);
+                return false;
+            }
+        }
+        this._userRecordCache = null;
+        await db.removeQueuedSyncOps(ops.map((op) => op.id));
+
+        // Bring in what other devices changed in the same playlists
+        const targets = new Set(ops.map((op) => op.target));
+        for (const playlist of playlists) {
+            const tracks = merged.userPlaylists[playlist.id]?.tracks;
+            if (!targets.has(playlistTarget(playlist.id)) || !tracks) continue;
+            if (JSON.stringify(tracks) === JSON.stringify(playlist.tracks || [])) continue;
+            await db.updatePlaylistTracks(playlist.id, tracks, 'sync');
+            window.dispatchEvent(new CustomEvent('playlist-tracks-changed'));
+        }
+        return true;
+    },
+
+    /**
+     * Save the whole local library as the cloud copy, for when more edits were made than db.js keeps queued.
+     * Changes other devices pushed in the meantime are overwritten.
+     */
+    async _pushLibrarySnapshot() {
+        const queued = await db.getQueuedSyncOps();
+        // Edits made from here on are queued again and replayed on top
+        await db.setSyncQueueOverflowed(false);
+
+        try {
+            const tracks = (await db.getAll('favorites_tracks')) || [];
+            const albums = (await db.getAll('favorites_albums')) || [];
+            const artists = (await db.getAll('favorites_artists')) || [];
+            const playlists = (await db.getAll('favorites_playlists')) || [];
+            const mixes = (await db.getAll('favorites_mixes')) || [];
+            const history = (await db.getAll('history_tracks')) || [];
+            const userPlaylists = (await db.getAll('user_playlists')) || [];
+            const userFolders = (await db.getAll('user_folders')) || [];
+            const smartPlaylists = (await db.getAll('smart_playlists')) || [];
+
+            const toMap = (items, type) =>
+                Object.fromEntries(
+                    items.map((item) => [
+                        type === 'playlist' ? item.uuid || item.id : item.id,
+                        this._minifyItem(type, item),
+                    ])
+                );
+            const fields = {
+                library: {
+                    tracks: toMap(tracks, 'track'),
+                    albums: toMap(albums, 'album'),
+                    artists: toMap(artists, 'artist'),
+                    playlists: toMap(playlists, 'playlist'),
+                    mixes: toMap(mixes, 'mix'),
+                },
+                history: history
+                    .filter((item) => item.timestamp)
+                    .sort((a, b) => b.timestamp - a.timestamp)
+                    .slice(0, 100),
+                userPlaylists: this._dedupeRecordMap(
+                    Object.fromEntries(userPlaylists.map((playlist) => [playlist.id, this._playlistRecord(playlist)])),
+                    'playlist'
+                ),
+                userFolders: this._dedupeRecordMap(
+                    Object.fromEntries(userFolders.map((folder) => [folder.id, this._folderRecord(folder)])),
+                    'folder'
+                ),
+                smartPlaylists: Object.fromEntries(
+                    smartPlaylists.map((playlist) => [playlist.id, this._smartPlaylistRecord(playlist)])
+                ),
+            };
+
+            // Load first so a WebDAV folder replaces the latest version instead of failing on a stale one
+            await this.transport.load();
+            await this.transport.save(
+                Object.fromEntries(Object.entries(fields).filter(([field]) => this.supports(field)))
+            );
+        } catch (error) {
+            console.error('[CloudSync] Failed to push the library:', error);
+            await db.setSyncQueueOverflowed(true);
+            return false;
+        }
+
+        this._userRecordCache = null;
+        await db.removeQueuedSyncOps(queued.map((op) => op.id));
+        return true;
+    },
+
+    _folderRecord(folder) {
+        return {
+            id: folder.id,
+            name: folder.name,
+            cover: folder.cover || null,
+            playlists: folder.playlists || [],
+            createdAt: folder.createdAt || Date.now(),
+            updatedAt: folder.updatedAt || Date.now(),
+        };
+    },
+
     async syncUserFolder(folder, action) {
-        const user = authManager.user;
+        const user = this.getSyncUser();
         if (!user) return;
 
         const record = await this._getUserRecord(user.$id);
@@ -432,19 +602,90 @@ const syncManager = {
         if (action === 'delete') {
             delete userFolders[folder.id];
         } else {
-            userFolders[folder.id] = {
-                id: folder.id,
-                name: folder.name,
-                cover: folder.cover || null,
-                playlists: folder.playlists || [],
-                createdAt: folder.createdAt || Date.now(),
-                updatedAt: folder.updatedAt || Date.now(),
-            };
+            userFolders[folder.id] = this._folderRecord(folder);
         }
 
         await this._updateUserJSON(user.$id, 'user_folders', userFolders);
     },
 
+    _smartPlaylistRecord(playlist) {
+        return {
+            id: playlist.id,
+            name: playlist.name,
+            description: playlist.description || '',
+            match: playlist.match === 'any' ? 'any' : 'all',
+            rules: playlist.rules || [],
+            sort: playlist.sort || null,
+            limit: playlist.limit || null,
+            createdAt: playlist.createdAt || Date.now(),
+            updatedAt: playlist.updatedAt || Date.now(),
+        };
+    },
+
+    async syncSmartPlaylist(playlist, action) {
+        const user = this.getSyncUser();
+        if (!user || !this.supports('smartPlaylists')) return;
+
+        const record = await this._getUserRecord(user.$id);
+        if (!record) return;
+
+        let smartPlaylists = this.safeParseInternal(record.smart_playlists, 'smart_playlists', {});
+
+        if (action === 'delete') {
+            delete smartPlaylists[playlist.id];
+        } else {
+            smartPlaylists[playlist.id] = this._smartPlaylistRecord(playlist);
+        }
+
+        await this._updateUserJSON(user.$id, 'smart_playlists', smartPlaylists);
+    },
+
+    async getSettings() {
+        const user = this.getSyncUser();
+        if (!user) return null;
+
+        // Another device may have changed them since the record was cached
+        this._userRecordCache = null;
+        const record = await this._getUserRecord(user.$id);
+        if (!record) throw new Error('Failed to load synced settings');
+
+        return this.safeParseInternal(record.settings, 'settings', null);
+    },
+
+    async saveSettings(settings) {
+        const user = this.getSyncUser();
+        if (!user) return false;
+
+        return await this._updateUserJSON(user.$id, 'settings', settings);
+    },
+
+    isEncrypted() {
+        return this.transport.isEncrypted();
+    },
+
+    isEncryptionLocked() {
+        return this.transport.isLocked();
+    },
+
+    /** Encrypt the cloud library with a new passphrase */
+    async enableEncryption(passphrase) {
+        await this.transport.enable(passphrase);
+        this._userRecordCache = null;
+    },
+
+    /** Enter the passphrase of a library encrypted on another device, then sync */
+    async unlockEncryption(passphrase) {
+        await this.transport.unlock(passphrase);
+        this._userRecordCache = null;
+        await this.onAuthStateChanged(this.getSyncUser());
+    },
+
+    /** Store the cloud library unencrypted again */
+    async disableEncryption() {
+        await this.transport.disable();
+        this._userRecordCache = null;
+    },
+
     async getPublicPlaylist(uuid) {
         try {
             const record = await authApi(`/api/public/playlists/${encodeURIComponent(uuid)}`);
@@ -502,7 +743,8 @@ const syncManager = {
         if (!playlist || !playlist.id) return;
         const uid = authManager.user?.$id;
         if (!uid) return;
-        // Public state is now stored on the normalized playlist row by syncUserPlaylist().
+        // Public state is now stored on the normalized playlist row by syncUserPlaylist(). An encrypted library
+        // keeps public playlists readable, so publishing works the same.
     },
 
     async unpublishPlaylist(_uuid) {
@@ -572,18 +814,18 @@ const syncManager = {
     },
 
     async clearCloudData() {
-        const user = authManager.user;
+        const user = this.getSyncUser();
         if (!user) return;
 
         try {
-            await authApi('/api/sync', {
-                method: 'PATCH',
-                body: JSON.stringify({
-                    library: {},
-                    history: [],
-                    userPlaylists: {},
-                    userFolders: {},
-                }),
+            // Also works on an encrypted library whose passphrase was forgotten
+            await this.transport.clear({
+                library: {},
+                history: [],
+                userPlaylists: {},
+                userFolders: {},
+                smartPlaylists: {},
+                settings: null,
             });
             this._userRecordCache = null;
             alert('Cloud data cleared successfully.');
@@ -600,6 +842,8 @@ const syncManager = {
             this._isSyncing = true;
 
             try {
+                // Edits made while offline go in first, so the merge below can't drop them
+                await this.pushQueuedOps();
                 const cloudData = await this.getUserData();
 
                 if (cloudData) {
@@ -614,9 +858,12 @@ const syncManager = {
                         history: (await database.getAll('history_tracks')) || [],
                         userPlaylists: (await database.getAll('user_playlists')) || [],
                         userFolders: (await database.getAll('user_folders')) || [],
+                        smartPlaylists: this.supports('smartPlaylists')
+                            ? (await database.getAll('smart_playlists')) || []
+                            : [],
                     };
 
-                    let { library, history, userPlaylists, userFolders } = cloudData;
+                    let { library, history, userPlaylists, userFolders, smartPlaylists } = cloudData;
                     let needsUpdate = false;
 
                     if (!library) library = {};
@@ -627,6 +874,7 @@ const syncManager = {
                     if (!library.mixes) library.mixes = {};
                     if (!userPlaylists) userPlaylists = {};
                     if (!userFolders) userFolders = {};
+                    if (!smartPlaylists) smartPlaylists = {};
                     if (!history) history = [];
                     userPlaylists = this._dedupeRecordMap(userPlaylists, 'playlist');
                     userFolders = this._dedupeRecordMap(userFolders, 'folder');
@@ -647,19 +895,7 @@ const syncManager = {
 
                     localData.userPlaylists.forEach((playlist) => {
                         if (!userPlaylists[playlist.id]) {
-                            userPlaylists[playlist.id] = {
-                                id: playlist.id,
-                                name: playlist.name,
-                                cover: playlist.cover || null,
-                                tracks: playlist.tracks
-                                    ? playlist.tracks.map((t) => this._minifyItem(t.type || 'track', t))
-                                    : [],
-                                createdAt: playlist.createdAt || Date.now(),
-                                updatedAt: playlist.updatedAt || Date.now(),
-                                numberOfTracks: playlist.tracks ? playlist.tracks.length : 0,
-                                images: playlist.images || [],
-                                isPublic: playlist.isPublic || false,
-                            };
+                            userPlaylists[playlist.id] = this._playlistRecord(playlist);
                             needsUpdate = true;
                         }
                     });
@@ -667,19 +903,21 @@ const syncManager = {
 
                     localData.userFolders.forEach((folder) => {
                         if (!userFolders[folder.id]) {
-                            userFolders[folder.id] = {
-                                id: folder.id,
-                                name: folder.name,
-                                cover: folder.cover || null,
-                                playlists: folder.playlists || [],
-                                createdAt: folder.createdAt || Date.now(),
-                                updatedAt: folder.updatedAt || Date.now(),
-                            };
+                            userFolders[folder.id] = this._folderRecord(folder);
                             needsUpdate = true;
                         }
                     });
                     userFolders = this._dedupeRecordMap(userFolders, 'folder');
 
+                    // Rules edited on this device while signed out win over an older cloud copy
+                    localData.smartPlaylists.forEach((playlist) => {
+                        const cloud = smartPlaylists[playlist.id];
+                        if (!cloud || this._recordTimestamp(cloud.updatedAt) < (playlist.updatedAt || 0)) {
+                            smartPlaylists[playlist.id] = this._smartPlaylistRecord(playlist);
+                            needsUpdate = true;
+                        }
+                    });
+
                     const combinedHistory = [...history, ...localData.history];
                     combinedHistory.sort((a, b) => (b.timestamp || 0) - (a.timestamp || 0));
 
@@ -704,6 +942,7 @@ const syncManager = {
                         await this._updateUserJSON(user.$id, 'library', library);
                         await this._updateUserJSON(user.$id, 'user_playlists', userPlaylists);
                         await this._updateUserJSON(user.$id, 'user_folders', userFolders);
+                        await this._updateUserJSON(user.$id, 'smart_playlists', smartPlaylists);
                         await this._updateUserJSON(user.$id, 'history', history);
                     }
 
@@ -716,6 +955,7 @@ const syncManager = {
                         history_tracks: history,
                         user_playlists: Object.values(userPlaylists).filter((p) => p && typeof p === 'object'),
                         user_folders: Object.values(userFolders).filter((f) => f && typeof f === 'object'),
+                        smart_playlists: Object.values(smartPlaylists).filter((p) => p && typeof p === 'object'),
                     };
 
                     // Safety check: if we had local data but merged result is completely empty, something went wrong.
@@ -728,7 +968,8 @@ const syncManager = {
                         localData.mixes.length > 0 ||
                         localData.history.length > 0 ||
                         localData.userPlaylists.length > 0 ||
-                        localData.userFolders.length > 0;
+                        localData.userFolders.length > 0 ||
+                        localData.smartPlaylists.length > 0;
 
                     const isConvertedEmpty =
                         convertedData.favorites_tracks.length === 0 &&
@@ -738,19 +979,23 @@ const syncManager = {
                         convertedData.favorites_mixes.length === 0 &&
                         convertedData.history_tracks.length === 0 &&
                         convertedData.user_playlists.length === 0 &&
-                        convertedData.user_folders.length === 0;
+                        convertedData.user_folders.length === 0 &&
+                        convertedData.smart_playlists.length === 0;
 
                     if (hadLocalData && isConvertedEmpty) {
                         console.warn(
                             '[PocketBase] Sync aborted: local data exists but merged result is empty. Preserving local data to prevent accidental wipe.'
                         );
                     } else {
+                        // Not in the cloud copy, so the empty list must not replace the local one
+                        if (!this.supports('smartPlaylists')) delete convertedData.smart_playlists;
                         await database.importData(convertedData, true);
                     }
                     await new Promise((resolve) => setTimeout(resolve, 300));
 
                     window.dispatchEvent(new CustomEvent('library-changed'));
                     window.dispatchEvent(new CustomEvent('history-changed'));
+                    window.dispatchEvent(new CustomEvent('smart-playlists-changed'));
                     window.dispatchEvent(new HashChangeEvent('hashchange'));
 
                     console.log('[PocketBase] ✓ Sync completed');
@@ -767,8 +1012,15 @@ const syncManager = {
     },
 };
 
+syncManager.useBackend(getSyncBackend());
+db.isSyncActive = () => !!syncManager.getSyncUser();
+
 if (pb) {
-    authManager.onAuthStateChanged(syncManager.onAuthStateChanged.bind(syncManager));
+    authManager.onAuthStateChanged((user) => {
+        // A WebDAV folder syncs whether or not anyone is signed in
+        if (syncManager.backend.id === 'account') syncManager.onAuthStateChanged(user);
+    });
+    window.addEventListener('online', () => syncManager.pushQueuedOps());
 }
 
 export { pb, syncManager };
@@ -0,0 +1,139 @@
+// js/accounts/sync-backend.js
+// Where syncManager keeps the cloud copy. A backend loads and saves the synced fields in the shape of /api/sync
+// ({library, history, userPlaylists, userFolders, smartPlaylists, settings, encryption}), lists which of them it
+// keeps and names the user the data belongs to. Merging stays in syncManager and sync-oplog.js, so every backend
+// syncs the same way:
+// - the Monochrome account, through the auth server and PocketBase (see also pocketbase-transport.js)
+// - a WebDAV folder (Nextcloud, rclone serve webdav, ...), one JSON document per field, without an account
+
+import { authManager } from './auth.js';
+import { authApi } from './authApi.js';
+import { syncBackendSettings } from '../storage.js';
+
+const WEBDAV_FILES = {
+    library: 'library.json',
+    history: 'history.json',
+    userPlaylists: 'playlists.json',
+    userFolders: 'folders.json',
+    smartPlaylists: 'smart-playlists.json',
+    settings: 'settings.json',
+    encryption: 'encryption.json',
+};
+
+// What /api/sync stores; it has no smart playlists or settings yet, so those stay on the device with an account
+const ACCOUNT_SYNC_FIELDS = ['library', 'history', 'userPlaylists', 'userFolders', 'encryption'];
+
+const WEBDAV_DEFAULTS = {
+    library: {},
+    history: [],
+    userPlaylists: {},
+    userFolders: {},
+    smartPlaylists: {},
+    settings: null,
+    encryption: null,
+};
+
+// The server sent no ETag for the document, so saving it could overwrite another device's changes
+const UNKNOWN_VERSION = Symbol('unknown version');
+
+/** Another device saved in between; load again and retry */
+export class SyncConflictError extends Error {
+    constructor(message = 'The cloud copy was changed by another device') {
+        super(message);
+        this.name = 'SyncConflictError';
+    }
+}
+
+export function createAccountBackend() {
+    return {
+        id: 'account',
+        fields: ACCOUNT_SYNC_FIELDS,
+        getUser: () => authManager.user,
+        load: () => authApi('/api/sync'),
+        save: (fields) => authApi('/api/sync', { method: 'PATCH', body: JSON.stringify(fields) }),
+    };
+}
+
+function encodeCredentials(username, password) {
+    const bytes = new TextEncoder().encode(`${username}:${password}`);
+    return btoa(String.fromCharCode(...bytes));
+}
+
+/**
+ * @param {{url: string, username?: string, password?: string}} config - `url` is the folder the documents are
+ *   kept in; it is created on the first save if missing
+ * @param {typeof fetch} [fetchImpl]
+ */
+export function createWebDavBackend({ url, username = '', password = '' }, fetchImpl = (...args) => fetch(...args)) {
+    const base = url.endsWith('/') ? url : `${url}/`;
+    const authorization =
+        username || password ? { Authorization: `Basic ${encodeCredentials(username, password)}` } : {};
+    // ETag of every document as last loaded, null for missing ones and UNKNOWN_VERSION when the server sent none
+    const etags = new Map();
+
+    const request = (path, options = {}) =>
+        fetchImpl(base + path, { ...options, headers: { ...authorization, ...options.headers } });
+
+    const loadFile = async (field) => {
+        const response = await request(WEBDAV_FILES[field], { cache: 'no-store' });
+        if (response.status === 404) {
+            etags.set(field, null);
+            return WEBDAV_DEFAULTS[field];
+        }
+        if (!response.ok) throw new Error(`WebDAV server answered ${response.status} for ${WEBDAV_FILES[field]}`);
+        etags.set(field, response.headers.get('ETag') || UNKNOWN_VERSION);
+        const text = await response.text();
+        return text ? JSON.parse(text) : WEBDAV_DEFAULTS[field];
+    };
+
+    const saveFile = async (field, value) => {
+        // Only replace the version that was loaded, so two devices saving at once can't drop each other's changes
+        const etag = etags.get(field);
+        if (etag === UNKNOWN_VERSION) throw new SyncConflictError(`Load ${WEBDAV_FILES[field]} again before saving it`);
+        const condition = etag ? { 'If-Match': etag } : etag === null ? { 'If-None-Match': '*' } : {};
+        const put = () =>
+            request(WEBDAV_FILES[field], {
+                method: 'PUT',
+                headers: { 'Content-Type': 'application/json', ...condition },
+                body: JSON.stringify(value),
+            });
+
+        let response = await put();
+        if (response.status === 409 || response.status === 404) {
+            await request('', { method: 'MKCOL' });
+            response = await put();
+        }
+        if (response.status === 412) throw new SyncConflictError();
+        if (!response.ok) throw new Error(`WebDAV server answered ${response.status} for ${WEBDAV_FILES[field]}`);
+        // Without an ETag the next save waits for a load, which save() does right after
+        etags.set(field, response.headers.get('ETag') || UNKNOWN_VERSION);
+    };
+
+    const load = async () => {
+        const fields = Object.keys(WEBDAV_FILES);
+        const values = await Promise.all(fields.map(loadFile));
+        return Object.fromEntries(fields.map((field, i) => [field, values[i]]));
+    };
+
+    const save = async (fields) => {
+        for (const field of Object.keys(WEBDAV_FILES)) {
+            if (field in fields) await saveFile(field, fields[field]);
+        }
+        return await load();
+    };
+
+    return {
+        id: 'webdav',
+        fields: Object.keys(WEBDAV_FILES),
+        // There is no account, the folder stands in for the user
+        getUser: () => ({ $id: `webdav:${username}@${base}` }),
+        load,
+        save,
+    };
+}
+
+/** The backend chosen in settings */
+export function getSyncBackend() {
+    const webDav = syncBackendSettings.getWebDavConfig();
+    return webDav ? createWebDavBackend(webDav) : createAccountBackend();
+}
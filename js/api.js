@@ -27,6 +27,7 @@ import { isCustomFormat } from './ffmpegFormats.ts';
 import { DownloadProgress } from './progressEvents.js';
 import { resolveDownloadTotalBytes } from './downloadProgressUtils.js';
 import { readableStreamIterator } from './readableStreamIterator.js';
+import { downloadWithRanges } from './resumable-download.js';
 import { HiFiClient, TidalResponse } from './HiFi.ts';
 import { isIos, isSafari, isChrome, canUseNativeAmazonCenc } from './platform-detection.js';
 import {
@@ -3227,6 +3228,14 @@ export class LosslessAPI {
                 }
             }
 
+            // Stream URLs are signed per request, so partial downloads are keyed by what is being fetched
+            const checkpointKey = [
+                isVideo ? 'video' : 'track',
+                enriched.externalProvider || 'tidal',
+                id,
+                postProcessingQuality || downloadQuality,
+            ].join('_');
+
             if (enriched.externalProvider === 'amazon' && enriched.externalStreamType?.includes('cenc')) {
                 const response = await fetch(enriched.externalSourceUrl || streamUrl, {
                     cache: 'no-store',
@@ -3260,6 +3269,7 @@ export class LosslessAPI {
                         signal: options.signal,
                         onProgress,
                         calculateDashBytes: calculateDashBytes ?? true,
+                        checkpointKey,
                     });
                 } catch (dashError) {
                     console.error('DASH download failed:', dashError);
@@ -3278,58 +3288,69 @@ export class LosslessAPI {
                     blob = await downloader.downloadHlsStream(getProxyUrl(streamUrl), {
                         signal: options.signal,
                         onProgress,
+                        checkpointKey,
                     });
                 } catch (hlsError) {
                     console.error('HLS download failed:', hlsError);
                     throw hlsError;
                 }
             } else {
-                // Try HEAD first to get Content-Length when GET uses chunked encoding (fixes #278)
-                let headContentLength = null;
-                try {
-                    const headResponse = await fetch(streamUrl, {
-                        method: 'HEAD',
+                // Byte ranges first, so an interrupted file resumes from its last checkpointed chunk
+                blob = await downloadWithRanges(getProxyUrl(streamUrl), {
+                    checkpointKey,
+                    signal: options.signal,
+                    onProgress,
+                    defaultMime: isVideo ? 'video/mp4' : 'audio/flac',
+                });
+
+                if (!blob) {
+                    // Try HEAD first to get Content-Length when GET uses chunked encoding (fixes #278)
+                    let headContentLength = null;
+                    try {
+                        const headResponse = await fetch(streamUrl, {
+                            method: 'HEAD',
+                            cache: 'no-store',
+                            signal: options.signal,
+                        });
+                        if (headResponse.ok) {
+                            const cl = headResponse.headers.get('Content-Length');
+                            if (cl) headContentLength = parseInt(cl, 10);
+                        }
+                    } catch (_) {
+                        /* ignore HEAD failure; proceed with GET */
+                    }
+
+                    const response = await fetch(getProxyUrl(streamUrl), {
                         cache: 'no-store',
                         signal: options.signal,
                     });
-                    if (headResponse.ok) {
-                        const cl = headResponse.headers.get('Content-Length');
-                        if (cl) headContentLength = parseInt(cl, 10);
-                    }
-                } catch (_) {
-                    /* ignore HEAD failure; proceed with GET */
-                }
 
-                const response = await fetch(getProxyUrl(streamUrl), {
-                    cache: 'no-store',
-                    signal: options.signal,
-                });
+                    if (!response.ok) {
+                        throw new Error(`Fetch failed: ${response.status}`);
+                    }
 
-                if (!response.ok) {
-                    throw new Error(`Fetch failed: ${response.status}`);
-                }
+                    const contentLengthHeader = response.headers.get('Content-Length');
+                    const totalBytes = resolveDownloadTotalBytes(contentLengthHeader, headContentLength);
 
-                const contentLengthHeader = response.headers.get('Content-Length');
-                const totalBytes = resolveDownloadTotalBytes(contentLengthHeader, headContentLength);
+                    let receivedBytes = 0;
 
-                let receivedBytes = 0;
+                    if (response.body) {
+                        const chunks = [];
 
-                if (response.body) {
-                    const chunks = [];
+                        for await (const chunk of readableStreamIterator(response.body)) {
+                            chunks.push(chunk);
+                            receivedBytes += chunk.byteLength;
 
-                    for await (const chunk of readableStreamIterator(response.body)) {
-                        chunks.push(chunk);
-                        receivedBytes += chunk.byteLength;
+                            onProgress?.(new DownloadProgress(receivedBytes, totalBytes || undefined));
+                        }
 
-                        onProgress?.(new DownloadProgress(receivedBytes, totalBytes || undefined));
+                        const defaultMime = isVideo ? 'video/mp4' : 'audio/flac';
+                        blob = new Blob(chunks, { type: response.headers.get('Content-Type') || defaultMime });
+                    } else {
+                        onProgress?.(new DownloadProgress(0, undefined));
+                        blob = await response.blob();
+                        onProgress?.(new DownloadProgress(blob.size, blob.size));
                     }
-
-                    const defaultMime = isVideo ? 'video/mp4' : 'audio/flac';
-                    blob = new Blob(chunks, { type: response.headers.get('Content-Type') || defaultMime });
-                } else {
-                    onProgress?.(new DownloadProgress(0, undefined));
-                    blob = await response.blob();
-                    onProgress?.(new DownloadProgress(blob.size, blob.size));
                 }
             }
 
@@ -16,6 +16,7 @@ import {
     modalSettings,
     keyboardShortcuts,
     amazonMusicSettings,
+    localMatchSettings,
 } from './storage.js';
 import { UIRenderer } from './ui.js';
 import { Player } from './player.js';
@@ -27,8 +28,20 @@ import { initializeUIInteractions, updateLocalFilesSupportUI } from './ui-intera
 import { debounce, getShareUrl, sanitizeForFilename } from './utils.js';
 import { sidePanelManager } from './side-panel.js';
 import { db } from './db.js';
+import { offlineLibrary } from './offline-library.js';
+import {
+    MAIN_ROOT_ID,
+    addLocalRoot,
+    indexLocalFile,
+    removeLocalRoot,
+    scanLocalLibrary,
+    setMainRoot,
+    sortLocalTracks,
+} from './local-library.js';
+import { getCatalogTrack, matchLocalLibrary } from './local-matcher.js';
 import { showNotification } from './downloads.js';
 import { syncManager } from './accounts/pocketbase.js';
+import { initSettingsSync } from './settings-sync.js';
 import { authManager } from './accounts/auth.js';
 import { registerSW } from 'virtual:pwa-register';
 import { openEditProfile } from './profile.js';
@@ -45,7 +58,11 @@ import {
     parseDynamicCSV,
     importToLibrary,
 } from './playlist-importer.js';
-import { generateFullCSV, generateFullJSON } from './playlist-generator.js';
+import { generateFullCSV, generateFullJSON, generateM3U8 } from './playlist-generator.js';
+import { getSmartPlaylistTracks } from './smart-playlists.js';
+import { openSmartPlaylistEditor, deleteSmartPlaylist } from './smart-playlist-editor.js';
+import { reviewImportMatches } from './import-review.js';
+import { openPlaylistHistory } from './playlist-history-modal.js';
 import { modernSettings } from './ModernSettings.js';
 import {
     SVG_OFFLINE,
@@ -342,6 +359,13 @@ function initializeKeyboardShortcuts(player, _audioPlayer) {
     });
 }
 
+/**
+ * The track to show lyrics for: matched local files use their catalog track
+ */
+function getLyricsTrack() {
+    return getCatalogTrack(Player.instance.currentTrack) || Player.instance.currentTrack;
+}
+
 async function closeFullscreenOverlay() {
     if (UIRenderer.instance?.dismissFullscreenCover) {
         await UIRenderer.instance.dismissFullscreenCover({ animate: false });
@@ -578,6 +602,22 @@ document.addEventListener('DOMContentLoaded', async () => {
     // i love ios and macos!!!! webkit fucking SUCKS BULLSHIT sorry ios/macos heads yall getting lossless only playback
     const currentQuality = localStorage.getItem('playback-quality') || 'HI_RES_LOSSLESS';
     await Player.initialize(audioPlayer, MusicAPI.instance, currentQuality);
+    offlineLibrary.init(MusicAPI.instance);
+    // A WebDAV folder syncs without an account, so no sign-in starts it
+    if (syncManager.backend.id !== 'account') syncManager.onAuthStateChanged(syncManager.getSyncUser());
+    initSettingsSync();
+    window.addEventListener(
+        'cloud-encryption-locked',
+        () => showNotification('Your cloud library is encrypted. Enter your passphrase in Settings to sync.'),
+        { once: true }
+    );
+
+    // Pick up bulk downloads left unfinished by a previous session
+    db.getDownloadJobs()
+        .then((jobs) => {
+            if (jobs.some((job) => job.status !== 'done')) return loadDownloadsModule();
+        })
+        .catch((e) => console.warn('Failed to restore download queue:', e));
 
     // Initialize tracker
     initTracker().catch(console.error);
@@ -588,7 +628,7 @@ document.addEventListener('DOMContentLoaded', async () => {
     await UIRenderer.initialize(MusicAPI.instance, Player.instance);
 
     /**
-     * Scans the configured local media folder and refreshes `window.localFilesCache`.
+     * Scans the local library folders and refreshes `window.localFilesCache`.
      * Called by the folder-select button handler and by downloads.js after a
      * successful write to the local media folder.
      *
@@ -605,49 +645,30 @@ document.addEventListener('DOMContentLoaded', async () => {
         if (window.localFilesScanInProgress) return;
         window.localFilesScanInProgress = true;
 
-        try {
-            const handle = await db.getSetting('local_folder_handle');
-            if (!handle) return;
-
-            const tracks = (window.localFilesCache = []);
-            let idCounter = 0;
-            const { readTrackMetadata } = await loadMetadataModule();
-
-            // Request read permission before iterating. When the browser has
-            // already granted it (e.g. within the same session or via a
-            // persistent grant) this succeeds without a user gesture.
-            if (typeof handle.requestPermission === 'function') {
-                const permission = await handle.requestPermission({ mode: 'read' });
-                if (permission !== 'granted') return;
-            }
+        const renderLocalFiles = () =>
+            UIRenderer.instance.renderLocalFiles(document.getElementById('library-local-container'));
+        // Large libraries report many updates while scanning; redraw at most once a second
+        const renderSoon = debounce(renderLocalFiles, 1000);
 
-            async function scanBrowser(dirHandle) {
-                for await (const entry of dirHandle.values()) {
-                    if (entry.kind === 'file') {
-                        const name = entry.name.toLowerCase();
-                        if (
-                            name.endsWith('.flac') ||
-                            name.endsWith('.mp3') ||
-                            name.endsWith('.m4a') ||
-                            name.endsWith('.wav') ||
-                            name.endsWith('.ogg')
-                        ) {
-                            const file = await entry.getFile();
-                            const metadata = await readTrackMetadata(file);
-                            metadata.id = `local-${idCounter++}-${file.name}`;
-                            tracks.push(metadata);
-                            UIRenderer.instance.renderLocalFiles(document.getElementById('library-local-container'));
-                        }
-                    } else if (entry.kind === 'directory') {
-                        await scanBrowser(entry);
-                    }
-                }
-            }
-            await scanBrowser(handle);
+        try {
+            const { readEditableTags } = await loadMetadataModule();
+
+            // Tracks already in the index show up straight away; only new or changed files are parsed.
+            const tracks = await scanLocalLibrary({
+                readTags: readEditableTags,
+                onUpdate: (tracks) => {
+                    window.localFilesCache = tracks;
+                    renderSoon();
+                },
+            });
+            if (!tracks) return;
 
-            tracks.sort((a, b) => (a.artist.name || '').localeCompare(b.artist.name || ''));
+            window.localFilesCache = tracks;
             // Update only the local-files section without navigating to the library page.
-            UIRenderer.instance.renderLocalFiles(document.getElementById('library-local-container'));
+            renderLocalFiles();
+
+            // Link new files to the catalog in the background
+            matchLocalTracks(tracks);
         } finally {
             window.localFilesScanInProgress = false;
         }
@@ -655,32 +676,71 @@ document.addEventListener('DOMContentLoaded', async () => {
         return window.localFilesCache;
     }
 
+    /**
+     * Resolves a path relative to the local media folder (as written by downloads.js) to its file handle.
+     */
+    async function getLocalFileHandle(path) {
+        let dir = await db.getSetting('local_folder_handle');
+        if (!dir) return null;
+        const parts = path.split('/').filter(Boolean);
+        const name = parts.pop();
+        for (const part of parts) {
+            dir = await dir.getDirectoryHandle(part);
+        }
+        return await dir.getFileHandle(name);
+    }
+
+    function matchLocalTracks(tracks) {
+        if (tracks.length > 0 && !localMatchSettings.isDecided()) {
+            localMatchSettings.setEnabled(
+                confirm(
+                    'Look up your local files in the catalog? Their titles, artists and ISRCs are sent as searches, ' +
+                        'so their plays count toward history and recommendations and they can be added to playlists. ' +
+                        'This can be changed in Settings.'
+                )
+            );
+            const toggle = document.getElementById('local-catalog-matching-toggle');
+            if (toggle) toggle.checked = localMatchSettings.isEnabled();
+        }
+        matchLocalLibrary(tracks, MusicAPI.instance, (track) => {
+            if (Player.instance.currentTrack?.id === track.id) {
+                Player.instance.currentTrack.catalogMatch = track.catalogMatch;
+                UIRenderer.instance.setCurrentTrack(Player.instance.currentTrack);
+            }
+        }).catch((e) => console.warn('[LocalMatcher] Matching failed:', e));
+    }
+
+    window.addEventListener('local-catalog-matching-enabled', () => {
+        matchLocalTracks(window.localFilesCache || []);
+    });
+
+    window.addEventListener('local-files-retagged', () => {
+        UIRenderer.instance.renderLocalFiles(document.getElementById('library-local-container'));
+        matchLocalTracks(window.localFilesCache || []);
+    });
+
     /**
      * Called by downloads.js (via window) after a successful write to the local
      * media folder so the track appears in Library > Local without the user
      * having to manually re-scan.
      *
      * When called with a `blob` and `filename` (single-track download case) it
-     * performs a cheap partial update - reading metadata only from that one file
-     * and inserting it into the existing cache - so the full folder does not need
-     * to be re-walked.  When called with no arguments (bulk download case, or when
+     * performs a cheap partial update - indexing only that one file and inserting
+     * it into the existing cache - so the full folder does not need to be
+     * re-walked.  When called with no arguments (bulk download case, or when
      * `localFilesCache` has never been populated) it falls back to a full rescan.
      */
     window.refreshLocalMediaFolder = async (blob = null, filename = null) => {
         if (blob && filename) {
             try {
                 /** @type {import("./metadata.js")} */
-                const { readTrackMetadata } = await loadMetadataModule();
-                const baseName = filename.split('/').pop();
-                const metadata = await readTrackMetadata(new Uint8Array(await blob.arrayBuffer()), {
-                    filename: baseName,
-                });
-                const existing = window.localFilesCache || [];
-                metadata.id = `local-${existing.length}-${baseName}`;
-                window.localFilesCache = [...existing, metadata].sort((a, b) =>
-                    (a.artist.name || '').localeCompare(b.artist.name || '')
-                );
+                const { readEditableTags } = await loadMetadataModule();
+                const fileHandle = await getLocalFileHandle(filename);
+                const track = await indexLocalFile(MAIN_ROOT_ID, filename, fileHandle, readEditableTags);
+                const existing = (window.localFilesCache || []).filter((t) => t.id !== track.id);
+                window.localFilesCache = sortLocalTracks([...existing, track]);
                 UIRenderer.instance.renderLocalFiles(document.getElementById('library-local-container'));
+                matchLocalTracks(window.localFilesCache);
             } catch {
                 // Fall back to a full rescan if metadata extraction fails.
                 await scanLocalMediaFolder(true);
@@ -716,6 +776,7 @@ document.addEventListener('DOMContentLoaded', async () => {
 
     // Render pinned items
     await UIRenderer.instance.renderPinnedItems();
+    await UIRenderer.instance.renderSmartPlaylistsNav();
 
     // Load settings module and initialize
     const { initializeSettings } = await loadSettingsModule();
@@ -775,7 +836,7 @@ document.addEventListener('DOMContentLoaded', async () => {
                 sidePanelManager.close();
                 clearLyricsPanelSync(Player.instance.activeElement, sidePanelManager.panel);
             } else {
-                openLyricsPanel(Player.instance.currentTrack, Player.instance.activeElement, lyricsManager);
+                openLyricsPanel(getLyricsTrack(), Player.instance.activeElement, lyricsManager);
             }
         } else if (mode === 'cover') {
             const overlay = document.getElementById('fullscreen-cover-overlay');
@@ -1063,7 +1124,7 @@ document.addEventListener('DOMContentLoaded', async () => {
             sidePanelManager.close();
             clearLyricsPanelSync(Player.instance.activeElement, sidePanelManager.panel);
         } else {
-            openLyricsPanel(Player.instance.currentTrack, Player.instance.activeElement, lyricsManager);
+            openLyricsPanel(getLyricsTrack(), Player.instance.activeElement, lyricsManager);
         }
     });
 
@@ -1099,7 +1160,7 @@ document.addEventListener('DOMContentLoaded', async () => {
         // Update lyrics panel if it's open
         if (sidePanelManager.isActive('lyrics')) {
             // Re-open forces update/refresh of content and sync
-            openLyricsPanel(Player.instance.currentTrack, Player.instance.activeElement, lyricsManager, true);
+            openLyricsPanel(getLyricsTrack(), Player.instance.activeElement, lyricsManager, true);
         }
 
         // Update Fullscreen if it's open
@@ -1446,6 +1507,28 @@ document.addEventListener('DOMContentLoaded', async () => {
             }
         }
 
+        if (e.target.closest('#create-smart-playlist-btn')) {
+            e.preventDefault();
+            await openSmartPlaylistEditor();
+        }
+
+        if (e.target.closest('#edit-smart-playlist-btn')) {
+            const playlist = await db.getSmartPlaylist(window.location.pathname.split('/')[2]);
+            if (playlist) await openSmartPlaylistEditor(playlist);
+        }
+
+        if (e.target.closest('#delete-smart-playlist-btn')) {
+            if (await deleteSmartPlaylist(window.location.pathname.split('/')[2])) {
+                navigate('/library');
+            }
+        }
+
+        const smartExportBtn = e.target.closest('#export-smart-playlist-btn');
+        if (smartExportBtn) {
+            e.stopPropagation();
+            showExportPlaylistMenu(smartExportBtn, window.location.pathname.split('/')[2], 'smart');
+        }
+
         if (e.target.closest('#delete-folder-btn')) {
             const folderId = window.location.pathname.split('/')[2];
             if (folderId && confirm('Are you sure you want to delete this folder?')) {
@@ -1588,7 +1671,7 @@ document.addEventListener('DOMContentLoaded', async () => {
                             const totalTracks = songs.length;
                             progressTotal.textContent = totalTracks.toString();
 
-                            const result = await parseCSV(
+                            const parsed = await parseCSV(
                                 csvText,
                                 MusicAPI.instance,
                                 (progress) => {
@@ -1602,6 +1685,10 @@ document.addEventListener('DOMContentLoaded', async () => {
                                 importOptions
                             );
 
+                            // Let the user check and correct the matches before anything is added
+                            const result = await reviewImportMatches(parsed, MusicAPI.instance);
+                            if (!result) return;
+
                             tracks = result.tracks;
                             const missingTracks = result.missingTracks;
 
@@ -1650,7 +1737,7 @@ document.addEventListener('DOMContentLoaded', async () => {
 
                             const jspfText = await file.text();
 
-                            const result = await parseJSPF(jspfText, MusicAPI.instance, (progress) => {
+                            const parsed = await parseJSPF(jspfText, MusicAPI.instance, (progress) => {
                                 const percentage = progress.total > 0 ? (progress.current / progress.total) * 100 : 0;
                                 progressFill.style.width = `${Math.min(percentage, 100)}%`;
                                 progressCurrent.textContent = progress.current.toString();
@@ -1660,6 +1747,9 @@ document.addEventListener('DOMContentLoaded', async () => {
                                     currentArtistElement.textContent = progress.currentArtist || '';
                             });
 
+                            const result = await reviewImportMatches(parsed, MusicAPI.instance);
+                            if (!result) return;
+
                             tracks = result.tracks;
                             const missingTracks = result.missingTracks;
 
@@ -1730,7 +1820,7 @@ document.addEventListener('DOMContentLoaded', async () => {
                             const totalItems = Math.max(0, lines.length - 1);
                             progressTotal.textContent = totalItems.toString();
 
-                            const result = await parseDynamicCSV(
+                            const parsed = await parseDynamicCSV(
                                 csvText,
                                 MusicAPI.instance,
                                 (progress) => {
@@ -1747,6 +1837,9 @@ document.addEventListener('DOMContentLoaded', async () => {
                                 importOptions
                             );
 
+                            const result = await reviewImportMatches(parsed, MusicAPI.instance);
+                            if (!result) return;
+
                             const isLibraryImport =
                                 result.albums.length > 0 ||
                                 result.artists.length > 0 ||
@@ -1841,7 +1934,7 @@ document.addEventListener('DOMContentLoaded', async () => {
 
                             const xspfText = await file.text();
 
-                            const result = await parseXSPF(xspfText, MusicAPI.instance, (progress) => {
+                            const parsed = await parseXSPF(xspfText, MusicAPI.instance, (progress) => {
                                 const percentage = progress.total > 0 ? (progress.current / progress.total) * 100 : 0;
                                 progressFill.style.width = `${Math.min(percentage, 100)}%`;
                                 progressCurrent.textContent = progress.current.toString();
@@ -1851,6 +1944,9 @@ document.addEventListener('DOMContentLoaded', async () => {
                                     currentArtistElement.textContent = progress.currentArtist || '';
                             });
 
+                            const result = await reviewImportMatches(parsed, MusicAPI.instance);
+                            if (!result) return;
+
                             tracks = result.tracks;
                             const missingTracks = result.missingTracks;
 
@@ -1898,7 +1994,7 @@ document.addEventListener('DOMContentLoaded', async () => {
 
                             const xmlText = await file.text();
 
-                            const result = await parseXML(xmlText, MusicAPI.instance, (progress) => {
+                            const parsed = await parseXML(xmlText, MusicAPI.instance, (progress) => {
                                 const percentage = progress.total > 0 ? (progress.current / progress.total) * 100 : 0;
                                 progressFill.style.width = `${Math.min(percentage, 100)}%`;
                                 progressCurrent.textContent = progress.current.toString();
@@ -1908,6 +2004,9 @@ document.addEventListener('DOMContentLoaded', async () => {
                                     currentArtistElement.textContent = progress.currentArtist || '';
                             });
 
+                            const result = await reviewImportMatches(parsed, MusicAPI.instance);
+                            if (!result) return;
+
                             tracks = result.tracks;
                             const missingTracks = result.missingTracks;
 
@@ -1955,7 +2054,7 @@ document.addEventListener('DOMContentLoaded', async () => {
 
                             const m3uText = await file.text();
 
-                            const result = await parseM3U(m3uText, MusicAPI.instance, (progress) => {
+                            const parsed = await parseM3U(m3uText, MusicAPI.instance, (progress) => {
                                 const percentage = progress.total > 0 ? (progress.current / progress.total) * 100 : 0;
                                 progressFill.style.width = `${Math.min(percentage, 100)}%`;
                                 progressCurrent.textContent = progress.current.toString();
@@ -1965,6 +2064,9 @@ document.addEventListener('DOMContentLoaded', async () => {
                                     currentArtistElement.textContent = progress.currentArtist || '';
                             });
 
+                            const result = await reviewImportMatches(parsed, MusicAPI.instance);
+                            if (!result) return;
+
                             tracks = result.tracks;
                             const missingTracks = result.missingTracks;
 
@@ -2169,6 +2271,15 @@ document.addEventListener('DOMContentLoaded', async () => {
             }
         }
 
+        if (e.target.closest('#history-playlist-btn')) {
+            const playlistId = window.location.pathname.split('/')[2];
+            if (playlistId) {
+                await openPlaylistHistory(playlistId, {
+                    onRestore: () => UIRenderer.instance.renderPlaylistPage(playlistId, 'user'),
+                });
+            }
+        }
+
         const detailExportBtn = e.target.closest('#export-playlist-btn');
         if (detailExportBtn) {
             e.stopPropagation();
@@ -2520,6 +2631,28 @@ document.addEventListener('DOMContentLoaded', async () => {
         }
 
         // Local Files Logic lollll
+        if (e.target.closest('#add-local-folder-btn')) {
+            try {
+                const handle = await window.showDirectoryPicker({ id: 'music-folder-extra', mode: 'read' });
+                if (await addLocalRoot(handle)) {
+                    await scanLocalMediaFolder();
+                } else {
+                    showNotification(`"${handle.name}" is already in your library`);
+                }
+            } catch (err) {
+                if (err.name !== 'AbortError') console.error('Error adding folder:', err);
+            }
+        }
+
+        const removeRootBtn = e.target.closest('.local-root-remove');
+        if (removeRootBtn) {
+            await removeLocalRoot(removeRootBtn.dataset.rootId);
+            window.localFilesCache = (window.localFilesCache || []).filter(
+                (track) => track.localRootId !== removeRootBtn.dataset.rootId
+            );
+            UIRenderer.instance.renderLocalFiles(document.getElementById('library-local-container'));
+        }
+
         if (e.target.closest('#select-local-folder-btn') || e.target.closest('#change-local-folder-btn')) {
             const isChange = e.target.closest('#change-local-folder-btn') !== null;
             try {
@@ -2528,7 +2661,8 @@ document.addEventListener('DOMContentLoaded', async () => {
                     mode: 'read',
                 });
 
-                await db.saveSetting('local_folder_handle', handle);
+                await setMainRoot(handle);
+                window.localFilesCache = [];
 
                 const btn = document.getElementById('select-local-folder-btn');
                 const btnText = document.getElementById('select-local-folder-text');
@@ -2820,6 +2954,21 @@ document.addEventListener('DOMContentLoaded', async () => {
         }
     });
 
+    // Smart playlists are evaluated against the library, so the open one is refreshed when it changes
+    const refreshSmartPlaylistPage = debounce(() => {
+        const path = window.location.pathname;
+        if (!path.startsWith('/smartplaylist/')) return;
+        const content = document.querySelector('.main-content');
+        const scroll = content ? content.scrollTop : 0;
+        UIRenderer.instance.renderSmartPlaylistPage(path.split('/')[2]).then(() => {
+            if (content) content.scrollTop = scroll;
+        });
+    }, 500);
+    ['favorites-changed', 'history-changed', 'playlist-tracks-changed', 'smart-playlists-changed'].forEach((event) =>
+        window.addEventListener(event, refreshSmartPlaylistPage)
+    );
+    window.addEventListener('smart-playlists-changed', () => UIRenderer.instance.renderSmartPlaylistsNav());
+
     const contextMenu = document.getElementById('context-menu');
     if (contextMenu) {
         const observer = new MutationObserver((mutations) => {
@@ -3051,13 +3200,14 @@ async function enrichTrack(track, api) {
     }
 }
 
-async function exportUserPlaylist(playlistId, format) {
-    const playlist = await db.getPlaylist(playlistId);
+async function exportUserPlaylist(playlistId, format, source = 'user') {
+    const playlist = source === 'smart' ? await db.getSmartPlaylist(playlistId) : await db.getPlaylist(playlistId);
     if (!playlist) {
         showNotification('Playlist not found.');
         return;
     }
-    const rawTracks = Array.isArray(playlist.tracks) ? playlist.tracks : [];
+    const playlistTracks = source === 'smart' ? await getSmartPlaylistTracks(playlist) : playlist.tracks;
+    const rawTracks = Array.isArray(playlistTracks) ? playlistTracks : [];
     const safeName = sanitizeForFilename(playlist.name || playlist.title || 'playlist');
 
     const api = MusicAPI.instance;
@@ -3076,6 +3226,10 @@ async function exportUserPlaylist(playlistId, format) {
         content = generateFullJSON(playlist, tracks);
         mime = 'application/json;charset=utf-8;';
         extension = 'json';
+    } else if (format === 'm3u8') {
+        content = generateM3U8({ title: playlist.name || playlist.title }, tracks);
+        mime = 'audio/x-mpegurl;charset=utf-8;';
+        extension = 'm3u8';
     } else {
         content = generateFullCSV(playlist, tracks);
         mime = 'text/csv;charset=utf-8;';
@@ -3094,7 +3248,7 @@ async function exportUserPlaylist(playlistId, format) {
     setTimeout(() => URL.revokeObjectURL(url), 1000);
 }
 
-function showExportPlaylistMenu(anchorEl, playlistId) {
+function showExportPlaylistMenu(anchorEl, playlistId, source = 'user') {
     const menu = document.getElementById('export-playlist-menu');
     if (!menu) return;
     const rect = anchorEl.getBoundingClientRect();
@@ -3114,7 +3268,7 @@ function showExportPlaylistMenu(anchorEl, playlistId) {
             ev.stopPropagation();
             closeMenu();
             try {
-                await exportUserPlaylist(playlistId, li.dataset.exportFormat);
+                await exportUserPlaylist(playlistId, li.dataset.exportFormat, source);
             } catch (err) {
                 console.error('Failed to export playlist:', err);
                 showNotification('Failed to export playlist.');
@@ -3,8 +3,32 @@
 // Supports 3-32 parametric EQ bands
 
 import { isIos } from './platform-detection.js';
-import { equalizerSettings, monoAudioSettings, binauralDspSettings } from './storage.js';
+import {
+    equalizerSettings,
+    monoAudioSettings,
+    binauralDspSettings,
+    eqDeviceProfileSettings,
+    dynamicsSettings,
+    convolutionSettings,
+} from './storage.js';
 import { BinauralDSP } from './binaural-dsp.js';
+import { sanitizeDynamicsSettings } from './dynamics-processor.js';
+import dynamicsWorkletUrl from './dynamics.worklet.js?worker&url';
+import { ConvolutionStage, decodeImpulseResponse, sanitizeConvolutionSettings } from './convolution.js';
+import {
+    isOutputSelectionSupported,
+    listOutputDevices,
+    resolveOutputDevice,
+    findBoundProfileId,
+} from './output-devices.js';
+import {
+    getFilterStages,
+    toBiquadNodeQ,
+    biquadStageResponseDb,
+    normalizeSlope,
+    parseEqualizerAPO,
+    formatEqualizerAPO,
+} from './parametric-eq.js';
 
 // Generate frequency array for given number of bands using logarithmic spacing
 function generateFrequencies(bandCount, minFreq = 20, maxFreq = 20000) {
@@ -131,8 +155,23 @@ class AudioContextManager {
         this.msRMix = null;
         this.msMerger = null;
         this.msOutputNode = null;
+
+        // L/R (per-channel) processing state
+        this.lrEnabled = false;
+        this.lrInput = null;
+        this.lrSplitter = null;
+        this.lrMerger = null;
+        this.lrOutputNode = null;
+        this.leftFilters = [];
+        this.rightFilters = [];
         this.currentVolume = 1.0;
 
+        // Source mixing bus: each media element feeds the graph through its own gain node,
+        // so the crossfade engine can overlap two tracks ahead of the EQ/DSP chain
+        this.inputNode = null;
+        this.sourceGains = new Map();
+        this.crossfadeElement = null;
+
         // Band configuration
         this.bandCount = equalizerSettings.getBandCount();
         this.freqRange = equalizerSettings.getFreqRange();
@@ -158,8 +197,27 @@ class AudioContextManager {
         this.geqGains = equalizerSettings.getGraphicEqGains(this.geqBandCount);
         this.geqPreamp = equalizerSettings.getGraphicEqPreamp();
 
+        // Compressor / look-ahead limiter (AudioWorklet, loaded on first use)
+        this.dynamicsNode = null;
+        this._dynamicsLoading = null;
+        this.dynamicsSettings = sanitizeDynamicsSettings(dynamicsSettings.get());
+        this.gainReduction = { compressor: 0, limiter: 0 };
+
+        // Convolution stage for user impulse responses (only in the graph once an IR is decoded)
+        this.convolution = null;
+        this.convolutionSettings = sanitizeConvolutionSettings(convolutionSettings.get());
+        this._convolutionIrId = null;
+        this.convolutionError = null;
+
+        // Output device and per-device EQ profiles
+        this.outputDeviceId = eqDeviceProfileSettings.getOutputDevice();
+        this.currentOutputDevice = null;
+        this._outputSync = Promise.resolve();
+        this._deviceChangeHandler = null;
+
         // Load saved settings
         this._loadSettings();
+        this.watchOutputDevices();
     }
 
     /**
@@ -186,13 +244,7 @@ class AudioContextManager {
         equalizerSettings.setGains(newGains);
 
         // Reinitialize EQ if already initialized
-        if (this.isInitialized && this.audioContext) {
-            this._destroyMSFilters();
-            this._destroyEQ();
-            this._createEQ();
-            if (this.msEnabled) this._createMSFilters();
-            this._connectGraph();
-        }
+        this._rebuildFilterChains();
 
         // Dispatch event for UI update
         window.dispatchEvent(
@@ -224,13 +276,7 @@ class AudioContextManager {
         this.frequencies = generateFrequencies(this.bandCount, newMin, newMax);
 
         // Reinitialize EQ if already initialized
-        if (this.isInitialized && this.audioContext) {
-            this._destroyMSFilters();
-            this._destroyEQ();
-            this._createEQ();
-            if (this.msEnabled) this._createMSFilters();
-            this._connectGraph();
-        }
+        this._rebuildFilterChains();
 
         // Dispatch event for UI update
         window.dispatchEvent(
@@ -247,7 +293,7 @@ class AudioContextManager {
      */
     _destroyEQ() {
         if (this.filters) {
-            this.filters.forEach((filter) => {
+            this.filters.flat().forEach((filter) => {
                 try {
                     filter.disconnect();
                 } catch {
@@ -322,39 +368,116 @@ class AudioContextManager {
     }
 
     /**
-     * Create parallel M/S filter chains based on current band settings.
-     * Mid filters process the center image, Side filters process stereo width.
+     * Create L/R split/merge nodes. The input is forced to a stereo pair so mono sources feed both chains.
      */
-    _createMSFilters() {
+    _createLRNodes() {
         if (!this.audioContext) return;
 
-        this.midFilters = this.frequencies.map((freq, i) => {
-            const type = (this.currentTypes && this.currentTypes[i]) || 'peaking';
-            const q = this.currentQs && this.currentQs[i] > 0 ? this.currentQs[i] : this._calculateQ(i);
-            const ch = (this.currentChannels && this.currentChannels[i]) || 'stereo';
-            const gain = ch === 'side' ? 0 : this.currentGains[i] || 0;
-            const filter = this.audioContext.createBiquadFilter();
-            filter.type = type;
-            filter.frequency.value = freq;
-            filter.Q.value = q;
-            filter.gain.value = gain;
-            return filter;
-        });
+        this.lrInput = this.audioContext.createGain();
+        this.lrInput.channelCount = 2;
+        this.lrInput.channelCountMode = 'explicit';
+        this.lrInput.channelInterpretation = 'speakers';
+        this.lrSplitter = this.audioContext.createChannelSplitter(2);
+        this.lrMerger = this.audioContext.createChannelMerger(2);
+        this.lrOutputNode = this.audioContext.createGain();
+    }
 
-        this.sideFilters = this.frequencies.map((freq, i) => {
-            const type = (this.currentTypes && this.currentTypes[i]) || 'peaking';
-            const q = this.currentQs && this.currentQs[i] > 0 ? this.currentQs[i] : this._calculateQ(i);
-            const ch = (this.currentChannels && this.currentChannels[i]) || 'stereo';
-            const gain = ch === 'mid' ? 0 : this.currentGains[i] || 0;
+    /**
+     * Whether a band routed to `bandChannel` is processed by the given chain.
+     * Stereo bands run in the L/R stage when one exists, otherwise on both M/S chains.
+     */
+    _isBandActiveOn(chain, bandChannel) {
+        if (chain === 'stereo' || bandChannel === chain) return true;
+        if (bandChannel !== 'stereo') return false;
+        return chain === 'left' || chain === 'right' || !this.lrEnabled;
+    }
+
+    /**
+     * Biquad stage parameters (node units) for one band on one chain.
+     * Bands that don't apply to the chain become a single transparent 0 dB peaking stage.
+     */
+    _getBandStages(index, chain = 'stereo') {
+        const freq = this.frequencies[index];
+        const bandChannel = (this.currentChannels && this.currentChannels[index]) || 'stereo';
+        if (!this._isBandActiveOn(chain, bandChannel)) {
+            return [{ type: 'peaking', freq, gain: 0, q: 1 }];
+        }
+
+        const type = (this.currentTypes && this.currentTypes[index]) || 'peaking';
+        const q = this.currentQs && this.currentQs[index] > 0 ? this.currentQs[index] : this._calculateQ(index);
+        const gain = this.currentGains[index] || 0;
+        const slope = this.currentSlopes && this.currentSlopes[index];
+        return getFilterStages({ type, freq, q, gain, slope }).map((stage) => ({
+            ...stage,
+            q: toBiquadNodeQ(stage.type, stage.q),
+        }));
+    }
+
+    /**
+     * Create the BiquadFilterNodes implementing one band on one chain
+     */
+    _createBandStages(index, chain = 'stereo') {
+        return this._getBandStages(index, chain).map((stage) => {
             const filter = this.audioContext.createBiquadFilter();
-            filter.type = type;
-            filter.frequency.value = freq;
-            filter.Q.value = q;
-            filter.gain.value = gain;
+            filter.type = stage.type;
+            filter.frequency.value = stage.freq;
+            filter.Q.value = stage.q;
+            filter.gain.value = stage.gain;
             return filter;
         });
     }
 
+    /**
+     * Connect input -> every stage of every band in the chain -> output
+     */
+    _connectFilterChain(input, chain, output, inputIndex = 0, outputIndex = 0) {
+        const stages = chain.flat();
+        input.connect(stages[0], inputIndex);
+        for (let i = 0; i < stages.length - 1; i++) {
+            stages[i].connect(stages[i + 1]);
+        }
+        stages[stages.length - 1].connect(output, 0, outputIndex);
+    }
+
+    /**
+     * Recompute which routing stages the current band channels need
+     * @returns {boolean} True if the M/S or L/R stage was switched on or off
+     */
+    _updateRoutingFlags() {
+        const channels = this.currentChannels || [];
+        const needsMS = channels.some((ch) => ch === 'mid' || ch === 'side');
+        const needsLR = channels.some((ch) => ch === 'left' || ch === 'right');
+        const changed = needsMS !== this.msEnabled || needsLR !== this.lrEnabled;
+        this.msEnabled = needsMS;
+        this.lrEnabled = needsLR;
+        return changed;
+    }
+
+    /**
+     * Recreate every filter chain from the current band settings and reconnect the graph
+     */
+    _rebuildFilterChains() {
+        if (!this.isInitialized || !this.audioContext) return;
+        this._destroyMSFilters();
+        this._destroyLRFilters();
+        this._destroyEQ();
+        this._createEQ();
+        if (this.msEnabled) this._createMSFilters();
+        if (this.lrEnabled) this._createLRFilters();
+        this._connectGraph();
+    }
+
+    /**
+     * Create parallel M/S filter chains based on current band settings.
+     * Mid filters process the center image, Side filters process stereo width.
+     */
+    _createMSFilters() {
+        if (!this.audioContext) return;
+
+        this.midFilters = this.frequencies.map((_, i) => this._createBandStages(i, 'mid'));
+        this.sideFilters = this.frequencies.map((_, i) => this._createBandStages(i, 'side'));
+    }
+
     /**
      * Destroy M/S parallel filter chains
      */
@@ -366,31 +489,81 @@ class AudioContextManager {
                 /* */
             }
         };
-        this.midFilters.forEach(sd);
-        this.sideFilters.forEach(sd);
+        this.midFilters.flat().forEach(sd);
+        this.sideFilters.flat().forEach(sd);
         this.midFilters = [];
         this.sideFilters = [];
     }
 
     /**
-     * Update an existing filter chain in place.
-     * @param {Array} chain - Filter array to update (this.filters, this.midFilters, or this.sideFilters)
-     * @param {Array} freqs - New frequencies
-     * @param {Array} types - New filter types
-     * @param {Array} qs - New Q values
-     * @param {Array} gains - New gain values
+     * Create parallel left/right filter chains for per-channel correction
+     */
+    _createLRFilters() {
+        if (!this.audioContext) return;
+
+        this.leftFilters = this.frequencies.map((_, i) => this._createBandStages(i, 'left'));
+        this.rightFilters = this.frequencies.map((_, i) => this._createBandStages(i, 'right'));
+    }
+
+    /**
+     * Destroy left/right filter chains
+     */
+    _destroyLRFilters() {
+        const sd = (node) => {
+            try {
+                node?.disconnect();
+            } catch {
+                /* */
+            }
+        };
+        this.leftFilters.flat().forEach(sd);
+        this.rightFilters.flat().forEach(sd);
+        this.leftFilters = [];
+        this.rightFilters = [];
+    }
+
+    /**
+     * Update an existing filter chain in place from the current band settings.
+     * @param {Array} chain - Filter chain to update (this.filters, this.midFilters, ...)
+     * @param {string} channel - Chain name: 'stereo', 'mid', 'side', 'left' or 'right'
      * @param {number} now - Current audio context time
+     * @returns {boolean} False if the chain's stage layout no longer matches and it must be rebuilt
      */
-    _updateFilterChain(chain, freqs, types, qs, gains, now) {
-        chain.forEach((filter, i) => {
-            const type = types[i] || 'peaking';
-            const q = qs[i] > 0 ? qs[i] : this._calculateQ(i);
-            const gain = gains[i];
-            filter.type = type;
-            filter.frequency.setTargetAtTime(freqs[i], now, 0.005);
-            filter.gain.setTargetAtTime(gain, now, 0.005);
-            filter.Q.setTargetAtTime(q, now, 0.005);
+    _updateFilterChain(chain, channel, now) {
+        if (chain.length !== this.frequencies.length) return false;
+        const bandStages = this.frequencies.map((_, i) => this._getBandStages(i, channel));
+        if (chain.some((nodes, i) => nodes.length !== bandStages[i].length)) return false;
+
+        chain.forEach((nodes, i) => {
+            nodes.forEach((filter, k) => {
+                const stage = bandStages[i][k];
+                filter.type = stage.type;
+                filter.frequency.setTargetAtTime(stage.freq, now, 0.005);
+                filter.gain.setTargetAtTime(stage.gain, now, 0.005);
+                filter.Q.setTargetAtTime(stage.q, now, 0.005);
+            });
         });
+        return true;
+    }
+
+    /**
+     * Push one band's current gain to every chain that carries it
+     */
+    _applyBandGain(index, now) {
+        const chains = {
+            stereo: this.filters,
+            mid: this.midFilters,
+            side: this.sideFilters,
+            left: this.leftFilters,
+            right: this.rightFilters,
+        };
+        for (const [channel, chain] of Object.entries(chains)) {
+            const nodes = chain[index];
+            if (!nodes) continue;
+            const stages = this._getBandStages(index, channel);
+            if (stages.length !== nodes.length) continue;
+            nodes.forEach((filter, k) => filter.gain.setTargetAtTime(stages[k].gain, now, 0.01));
+        }
     }
 
     /**
@@ -408,18 +581,8 @@ class AudioContextManager {
         const gainValue = Math.pow(10, preampValue / 20);
         this.preampNode.gain.value = gainValue;
 
-        // Create filters for each frequency band
-        this.filters = this.frequencies.map((freq, index) => {
-            const type = (this.currentTypes && this.currentTypes[index]) || 'peaking';
-            const q = this.currentQs && this.currentQs[index] > 0 ? this.currentQs[index] : this._calculateQ(index);
-            const gain = this.currentGains[index] || 0;
-            const filter = this.audioContext.createBiquadFilter();
-            filter.type = type;
-            filter.frequency.value = freq;
-            filter.Q.value = q;
-            filter.gain.value = gain;
-            return filter;
-        });
+        // Create filter stages for each frequency band
+        this.filters = this.frequencies.map((_, index) => this._createBandStages(index));
 
         // Create volume node if not exists
         if (!this.volumeNode) {
@@ -492,21 +655,14 @@ class AudioContextManager {
                 }
                 this.source = this.sources.get(audioElement);
 
-                try {
-                    this.audioContext.destination.channelCount = Math.min(
-                        this.audioContext.destination.maxChannelCount,
-                        8
-                    );
-                    this.audioContext.destination.channelCountMode = 'explicit';
-                    this.audioContext.destination.channelInterpretation = 'discrete';
-                } catch {
-                    // Some browsers may not support changing destination channel count
-                }
+                this._configureDestination();
 
                 this.binauralDsp = new BinauralDSP(this.audioContext);
                 void this._loadBinauralSettings();
             }
 
+            this.inputNode = this.audioContext.createGain();
+
             this.analyser = this.audioContext.createAnalyser();
             this.analyser.fftSize = 1024;
             this.analyser.smoothingTimeConstant = 0.7;
@@ -514,9 +670,13 @@ class AudioContextManager {
             this._createEQ();
             this._createGraphicEQ();
             this._createMSNodes();
+            this._createLRNodes();
             if (this.msEnabled) {
                 this._createMSFilters();
             }
+            if (this.lrEnabled) {
+                this._createLRFilters();
+            }
 
             this.outputNode = this.audioContext.createGain();
             this.outputNode.gain.value = 1;
@@ -526,7 +686,22 @@ class AudioContextManager {
 
             this.monoMergerNode = this.audioContext.createChannelMerger(2);
 
+            this.convolution = new ConvolutionStage(this.audioContext);
+
             this._connectGraph();
+            if (this._isDynamicsActive()) {
+                void this._ensureDynamicsNode();
+            }
+            void this._applyConvolutionSettings();
+
+            if (this.outputDeviceId) {
+                void this._applySinkId();
+            }
+            // Fires when the output is switched, or when the browser falls back after the device disappears
+            this.audioContext.addEventListener('sinkchange', () => {
+                this._configureDestination();
+                this._scheduleOutputSync();
+            });
 
             // Auto-recover from unexpected suspensions (e.g. background throttling)
             this.audioContext.addEventListener('statechange', () => {
@@ -596,17 +771,28 @@ class AudioContextManager {
         }
 
         const connectTail = (lastNode) => {
+            let tail = lastNode;
             if (this.isGraphicEQEnabled && this.geqFilters.length > 0) {
-                lastNode.connect(this.geqPreampNode);
+                tail.connect(this.geqPreampNode);
                 this.geqPreampNode.connect(this.geqFilters[0]);
                 for (let i = 0; i < this.geqFilters.length - 1; i++) {
                     this.geqFilters[i].connect(this.geqFilters[i + 1]);
                 }
                 this.geqFilters[this.geqFilters.length - 1].connect(this.geqOutputNode);
-                this.geqOutputNode.connect(this.analyser);
-            } else {
-                lastNode.connect(this.analyser);
+                tail = this.geqOutputNode;
+            }
+            if (this._isConvolutionActive()) {
+                const { input, output } = this.convolution.getNodes();
+                tail.connect(input);
+                this.convolution.reconnect();
+                tail = output;
             }
+            // Compressor / limiter goes after every EQ stage so it catches band boosts and preamp gain
+            if (this.dynamicsNode && this._isDynamicsActive()) {
+                tail.connect(this.dynamicsNode);
+                tail = this.dynamicsNode;
+            }
+            tail.connect(this.analyser);
             this.analyser.connect(this.volumeNode);
             this.volumeNode.connect(this.audioContext.destination);
         };
@@ -622,7 +808,9 @@ class AudioContextManager {
                     node?.disconnect();
                 } catch {}
             };
-            safeDisconnect(this.source);
+            this.sources.forEach(safeDisconnect);
+            this.sourceGains.forEach(safeDisconnect);
+            safeDisconnect(this.inputNode);
             safeDisconnect(this.monoGainNode);
             safeDisconnect(this.monoMergerNode);
             if (this.binauralDsp) {
@@ -631,7 +819,13 @@ class AudioContextManager {
                 safeDisconnect(output);
             }
             safeDisconnect(this.preampNode);
-            this.filters.forEach(safeDisconnect);
+            this.filters.flat().forEach(safeDisconnect);
+            safeDisconnect(this.lrInput);
+            safeDisconnect(this.lrSplitter);
+            this.leftFilters.flat().forEach(safeDisconnect);
+            this.rightFilters.flat().forEach(safeDisconnect);
+            safeDisconnect(this.lrMerger);
+            safeDisconnect(this.lrOutputNode);
             safeDisconnect(this.outputNode);
             safeDisconnect(this.msSplitter);
             safeDisconnect(this.msEncoderMidL);
@@ -640,8 +834,8 @@ class AudioContextManager {
             safeDisconnect(this.msEncoderSideR);
             safeDisconnect(this.msMidInput);
             safeDisconnect(this.msSideInput);
-            this.midFilters.forEach(safeDisconnect);
-            this.sideFilters.forEach(safeDisconnect);
+            this.midFilters.flat().forEach(safeDisconnect);
+            this.sideFilters.flat().forEach(safeDisconnect);
             safeDisconnect(this.midOutputNode);
             safeDisconnect(this.sideOutputNode);
             safeDisconnect(this.msDecoderMidToL);
@@ -655,13 +849,29 @@ class AudioContextManager {
             safeDisconnect(this.geqPreampNode);
             this.geqFilters.forEach(safeDisconnect);
             safeDisconnect(this.geqOutputNode);
+            if (this.convolution) {
+                const { input, output } = this.convolution.getNodes();
+                safeDisconnect(input);
+                safeDisconnect(output);
+            }
+            safeDisconnect(this.dynamicsNode);
             safeDisconnect(this.analyser);
             safeDisconnect(this.volumeNode);
 
-            let lastNode = this.source;
+            const primaryGain = this._getSourceGain(this.audio);
+            this.source.connect(primaryGain);
+            primaryGain.connect(this.inputNode);
+
+            if (this.crossfadeElement && this.crossfadeElement !== this.audio) {
+                const crossfadeGain = this._getSourceGain(this.crossfadeElement);
+                this.sources.get(this.crossfadeElement)?.connect(crossfadeGain);
+                crossfadeGain.connect(this.inputNode);
+            }
+
+            let lastNode = this.inputNode;
 
             if (this.isMonoAudioEnabled && this.monoMergerNode) {
-                this.source.connect(this.monoGainNode);
+                lastNode.connect(this.monoGainNode);
                 this.monoGainNode.connect(this.monoMergerNode, 0, 0);
                 this.monoGainNode.connect(this.monoMergerNode, 0, 1);
                 lastNode = this.monoMergerNode;
@@ -675,6 +885,7 @@ class AudioContextManager {
             }
 
             if (this.isEQEnabled && this.filters.length > 0) {
+                const useLR = this.lrEnabled && this.leftFilters.length > 0 && this.rightFilters.length > 0;
                 const useMS = this.msEnabled && this.midFilters.length > 0 && this.sideFilters.length > 0;
 
                 if (this.preampNode) {
@@ -682,6 +893,20 @@ class AudioContextManager {
                     lastNode = this.preampNode;
                 }
 
+                if (!useLR && !useMS) {
+                    this._connectFilterChain(lastNode, this.filters, this.outputNode);
+                    lastNode = this.outputNode;
+                }
+
+                if (useLR) {
+                    lastNode.connect(this.lrInput);
+                    this.lrInput.connect(this.lrSplitter);
+                    this._connectFilterChain(this.lrSplitter, this.leftFilters, this.lrMerger, 0, 0);
+                    this._connectFilterChain(this.lrSplitter, this.rightFilters, this.lrMerger, 1, 1);
+                    this.lrMerger.connect(this.lrOutputNode);
+                    lastNode = this.lrOutputNode;
+                }
+
                 if (useMS) {
                     lastNode.connect(this.msSplitter);
 
@@ -695,17 +920,8 @@ class AudioContextManager {
                     this.msEncoderSideL.connect(this.msSideInput);
                     this.msEncoderSideR.connect(this.msSideInput);
 
-                    this.msMidInput.connect(this.midFilters[0]);
-                    for (let i = 0; i < this.midFilters.length - 1; i++) {
-                        this.midFilters[i].connect(this.midFilters[i + 1]);
-                    }
-                    this.midFilters[this.midFilters.length - 1].connect(this.midOutputNode);
-
-                    this.msSideInput.connect(this.sideFilters[0]);
-                    for (let i = 0; i < this.sideFilters.length - 1; i++) {
-                        this.sideFilters[i].connect(this.sideFilters[i + 1]);
-                    }
-                    this.sideFilters[this.sideFilters.length - 1].connect(this.sideOutputNode);
+                    this._connectFilterChain(this.msMidInput, this.midFilters, this.midOutputNode);
+                    this._connectFilterChain(this.msSideInput, this.sideFilters, this.sideOutputNode);
 
                     this.midOutputNode.connect(this.msDecoderMidToL);
                     this.sideOutputNode.connect(this.msDecoderSideToL);
@@ -720,16 +936,10 @@ class AudioContextManager {
                     this.msLMix.connect(this.msMerger, 0, 0);
                     this.msRMix.connect(this.msMerger, 0, 1);
                     this.msMerger.connect(this.msOutputNode);
-
-                    connectTail(this.msOutputNode);
-                } else {
-                    lastNode.connect(this.filters[0]);
-                    for (let i = 0; i < this.filters.length - 1; i++) {
-                        this.filters[i].connect(this.filters[i + 1]);
-                    }
-                    this.filters[this.filters.length - 1].connect(this.outputNode);
-                    connectTail(this.outputNode);
+                    lastNode = this.msOutputNode;
                 }
+
+                connectTail(lastNode);
             } else {
                 connectTail(lastNode);
             }
@@ -743,6 +953,99 @@ class AudioContextManager {
         }
     }
 
+    /**
+     * Get (or lazily create) the gain node that feeds a media element into the input bus
+     */
+    _getSourceGain(element) {
+        let gainNode = this.sourceGains.get(element);
+        if (!gainNode) {
+            gainNode = this.audioContext.createGain();
+            gainNode.gain.value = 1;
+            this.sourceGains.set(element, gainNode);
+        }
+        return gainNode;
+    }
+
+    /**
+     * Route a secondary media element into the graph alongside the current source.
+     * The crossfade engine plays the incoming track through it, so EQ and binaural
+     * processing apply to both tracks while they overlap.
+     * @param {HTMLMediaElement} element
+     * @returns {boolean} True if the element is connected
+     */
+    attachCrossfadeSource(element) {
+        if (!this.isInitialized || !this.audioContext || !element) return false;
+
+        try {
+            if (!this.sources.has(element)) {
+                this.sources.set(element, this.audioContext.createMediaElementSource(element));
+            }
+        } catch (e) {
+            console.warn('[AudioContext] Failed to create crossfade source:', e);
+            return false;
+        }
+
+        const gain = this._getSourceGain(element).gain;
+        gain.cancelScheduledValues(this.audioContext.currentTime);
+        gain.value = 0;
+
+        this.crossfadeElement = element;
+        this._connectGraph();
+        return true;
+    }
+
+    /**
+     * Remove the secondary crossfade element from the graph
+     */
+    detachCrossfadeSource() {
+        const element = this.crossfadeElement;
+        if (!element) return;
+        this.crossfadeElement = null;
+
+        try {
+            this.sources.get(element)?.disconnect();
+            this.sourceGains.get(element)?.disconnect();
+        } catch {
+            // node may already be disconnected
+        }
+    }
+
+    /**
+     * Run a gain automation curve on a media element's source gain
+     * @param {HTMLMediaElement} element
+     * @param {Float32Array} curve - Gain values spread evenly across the duration
+     * @param {number} duration - Curve length in seconds
+     */
+    fadeSource(element, curve, duration) {
+        if (!this.audioContext || !element) return;
+
+        const param = this._getSourceGain(element).gain;
+        const now = this.audioContext.currentTime;
+        param.cancelScheduledValues(now);
+        if (duration <= 0) {
+            param.setValueAtTime(curve[curve.length - 1], now);
+            return;
+        }
+        param.setValueCurveAtTime(curve, now, duration);
+    }
+
+    /**
+     * Ramp a media element's source gain to a value, starting from wherever it currently is
+     * @param {HTMLMediaElement} element
+     * @param {number} value - Target gain (linear)
+     * @param {number} duration - Ramp length in seconds
+     */
+    rampSourceGain(element, value, duration = 0.03) {
+        if (!this.audioContext || !element) return;
+
+        const param = this._getSourceGain(element).gain;
+        const now = this.audioContext.currentTime;
+        const current = param.value;
+        param.cancelScheduledValues(now);
+        param.setValueAtTime(current, now);
+        param.linearRampToValueAtTime(value, now + Math.max(0.001, duration));
+    }
+
     /**
      * Resume audio context (required after user interaction)
      * @returns {Promise<boolean>} - Returns true if context is running
@@ -977,6 +1280,362 @@ class AudioContextManager {
         }
     }
 
+    // ==========================================
+    // Dynamics (compressor / look-ahead limiter)
+    // ==========================================
+
+    _isDynamicsActive() {
+        return this.dynamicsSettings.compressorEnabled || this.dynamicsSettings.limiterEnabled;
+    }
+
+    /**
+     * Load the dynamics worklet and create its node, then wire it into the graph
+     * @returns {Promise<AudioWorkletNode|null>}
+     */
+    _ensureDynamicsNode() {
+        if (this.dynamicsNode) return Promise.resolve(this.dynamicsNode);
+        if (!this.audioContext?.audioWorklet) return Promise.resolve(null);
+
+        if (!this._dynamicsLoading) {
+            this._dynamicsLoading = this.audioContext.audioWorklet
+                .addModule(dynamicsWorkletUrl)
+                .then(() => {
+                    const node = new AudioWorkletNode(this.audioContext, 'dynamics-processor', {
+                        processorOptions: { settings: this.dynamicsSettings },
+                    });
+                    node.port.onmessage = (e) => {
+                        if (e.data?.type === 'meter') {
+                            this.gainReduction = { compressor: e.data.compressor, limiter: e.data.limiter };
+                        }
+                    };
+                    this.dynamicsNode = node;
+                    this._connectGraph();
+                    return node;
+                })
+                .catch((e) => {
+                    console.warn('[AudioContext] Failed to load dynamics processor:', e);
+                    this._dynamicsLoading = null;
+                    return null;
+                });
+        }
+        return this._dynamicsLoading;
+    }
+
+    /**
+     * Current compressor / limiter settings
+     */
+    getDynamicsSettings() {
+        return { ...this.dynamicsSettings };
+    }
+
+    /**
+     * Update compressor / limiter settings. The stage is only in the graph while one of them is enabled.
+     * @param {object} settings - Partial settings, see DYNAMICS_DEFAULTS
+     */
+    setDynamicsSettings(settings) {
+        const wasActive = this._isDynamicsActive();
+        this.dynamicsSettings = sanitizeDynamicsSettings({ ...this.dynamicsSettings, ...settings });
+        dynamicsSettings.set(this.dynamicsSettings);
+        this._applyDynamicsSettings(wasActive);
+    }
+
+    _applyDynamicsSettings(wasActive) {
+        const active = this._isDynamicsActive();
+        if (!active) this.gainReduction = { compressor: 0, limiter: 0 };
+        if (this.dynamicsNode) {
+            this.dynamicsNode.port.postMessage({ type: 'configure', settings: this.dynamicsSettings });
+        }
+        if (!this.isInitialized) return;
+
+        if (active && !this.dynamicsNode) {
+            void this._ensureDynamicsNode();
+        } else if (active !== wasActive) {
+            this._connectGraph();
+        }
+    }
+
+    /**
+     * Latest gain reduction in dB (positive values) for the compressor and limiter
+     */
+    getGainReduction() {
+        return { ...this.gainReduction };
+    }
+
+    // ==========================================
+    // Convolution (user impulse responses)
+    // ==========================================
+
+    _isConvolutionActive() {
+        return this.convolutionSettings.enabled && !!this.convolution?.hasImpulseResponse();
+    }
+
+    /**
+     * Current convolution settings
+     */
+    getConvolutionSettings() {
+        return { ...this.convolutionSettings };
+    }
+
+    /**
+     * Update convolution settings. Selecting a different IR decodes it from the database before it's swapped in.
+     * @param {object} settings - Partial settings, see CONVOLUTION_DEFAULTS
+     */
+    setConvolutionSettings(settings) {
+        this.convolutionSettings = sanitizeConvolutionSettings({ ...this.convolutionSettings, ...settings });
+        convolutionSettings.set(this.convolutionSettings);
+        return this._applyConvolutionSettings();
+    }
+
+    async _applyConvolutionSettings() {
+        if (!this.convolution) return;
+        const { enabled, irId, mix, gain, normalize } = this.convolutionSettings;
+        this.convolution.setMix(mix);
+        this.convolution.setGain(gain);
+        this.convolution.setNormalize(normalize);
+
+        // The decoded IR is kept while disabled so re-enabling is instant
+        if (enabled && irId !== this._convolutionIrId) {
+            await this._loadImpulseResponse(irId);
+        } else {
+            this._connectGraph();
+        }
+    }
+
+    async _loadImpulseResponse(id) {
+        this._convolutionIrId = id;
+        this.convolutionError = null;
+
+        let buffer = null;
+        if (id) {
+            try {
+                buffer = await decodeImpulseResponse(this.audioContext, id);
+            } catch (e) {
+                console.warn('[AudioContext] Failed to load impulse response:', e);
+                this.convolutionError = e.message;
+            }
+        }
+        // A newer selection may have come in while decoding
+        if (this._convolutionIrId !== id || !this.convolution) return;
+
+        this.convolution.setImpulseResponse(buffer);
+        this._connectGraph();
+        window.dispatchEvent(new CustomEvent('convolution-changed', { detail: this.getConvolutionStatus() }));
+    }
+
+    /**
+     * Loaded IR details for display
+     */
+    getConvolutionStatus() {
+        const buffer = this.convolution?.buffer;
+        return {
+            active: this._isConvolutionActive(),
+            irId: buffer ? this._convolutionIrId : null,
+            layout: this.convolution?.layout || null,
+            duration: buffer ? buffer.duration : 0,
+            error: this.convolutionError,
+        };
+    }
+
+    // ==========================================
+    // Output device & per-device EQ profiles
+    // ==========================================
+
+    /**
+     * Use as many destination channels as the current output offers (up to 7.1)
+     */
+    _configureDestination() {
+        if (!this.audioContext) return;
+        try {
+            this.audioContext.destination.channelCount = Math.min(this.audioContext.destination.maxChannelCount, 8);
+            this.audioContext.destination.channelCountMode = 'explicit';
+            this.audioContext.destination.channelInterpretation = 'discrete';
+        } catch {
+            // Some browsers may not support changing destination channel count
+        }
+    }
+
+    /**
+     * Start following output device changes (headphones plugged in, default device switched)
+     */
+    watchOutputDevices() {
+        if (this._deviceChangeHandler || typeof navigator === 'undefined') return;
+        if (!navigator.mediaDevices?.addEventListener) return;
+        this._deviceChangeHandler = () => this._scheduleOutputSync();
+        navigator.mediaDevices.addEventListener('devicechange', this._deviceChangeHandler);
+        this._scheduleOutputSync();
+    }
+
+    /**
+     * List available audio outputs
+     */
+    getOutputDevices() {
+        return listOutputDevices();
+    }
+
+    /**
+     * The physical device audio is currently playing on, if known
+     */
+    getCurrentOutputDevice() {
+        return this.currentOutputDevice;
+    }
+
+    /**
+     * Route playback to an output device
+     * @param {string} deviceId - Sink ID from getOutputDevices, '' or 'default' for the system default
+     */
+    async setOutputDevice(deviceId) {
+        this.outputDeviceId = deviceId && deviceId !== 'default' ? deviceId : '';
+        eqDeviceProfileSettings.setOutputDevice(this.outputDeviceId);
+        await this._applySinkId();
+        await this._scheduleOutputSync();
+    }
+
+    async _applySinkId() {
+        if (!this.audioContext || !isOutputSelectionSupported()) return;
+        if (this.audioContext.sinkId === this.outputDeviceId) return;
+        try {
+            await this.audioContext.setSinkId(this.outputDeviceId);
+            this._configureDestination();
+        } catch (e) {
+            console.warn('[AudioContext] Failed to switch output device:', e);
+        }
+    }
+
+    /**
+     * Queue a re-check of the output device; devicechange tends to fire in bursts
+     */
+    _scheduleOutputSync() {
+        this._outputSync = this._outputSync.then(() => this._syncOutputDevice()).catch((e) => {
+            console.warn('[AudioContext] Output device sync failed:', e);
+        });
+        return this._outputSync;
+    }
+
+    /**
+     * Work out which device is playing and swap in the EQ profile bound to it
+     */
+    async _syncOutputDevice() {
+        const devices = await listOutputDevices();
+        const device = resolveOutputDevice(devices, this.outputDeviceId);
+        const previous = this.currentOutputDevice;
+        this.currentOutputDevice = device;
+
+        if (device?.deviceId !== previous?.deviceId) {
+            window.dispatchEvent(new CustomEvent('audio-output-changed', { detail: { device } }));
+        }
+
+        if (!device || !eqDeviceProfileSettings.isAutoSwitchEnabled()) return;
+        this.switchEQProfile(findBoundProfileId(eqDeviceProfileSettings.getBindings(), device));
+    }
+
+    /**
+     * Save the current EQ state as a named profile and bind it to the current output
+     * @returns {string|false} Profile ID
+     */
+    createEQProfile(name) {
+        const id = eqDeviceProfileSettings.saveProfile(name, eqDeviceProfileSettings.captureSnapshot());
+        if (!id) return false;
+
+        const previousId = eqDeviceProfileSettings.getActiveProfile();
+        if (!previousId) {
+            // Unbound outputs keep this state instead of following later edits to the new profile
+            eqDeviceProfileSettings.setFallbackSnapshot(eqDeviceProfileSettings.captureSnapshot());
+        }
+        eqDeviceProfileSettings.bindDevice(this.currentOutputDevice, id);
+        eqDeviceProfileSettings.setActiveProfile(id);
+        window.dispatchEvent(
+            new CustomEvent('eq-profile-changed', { detail: { profileId: id, device: this.currentOutputDevice } })
+        );
+        return id;
+    }
+
+    /**
+     * Bind a profile to the current output (or unbind with null) and switch to it
+     */
+    bindEQProfile(profileId) {
+        eqDeviceProfileSettings.bindDevice(this.currentOutputDevice, profileId);
+        return this.switchEQProfile(profileId);
+    }
+
+    /**
+     * Activate a device EQ profile, or the unbound-output state when profileId is null.
+     * The outgoing profile keeps any edits made while it was active.
+     * @returns {boolean} True if the EQ state was swapped
+     */
+    switchEQProfile(profileId) {
+        const profiles = eqDeviceProfileSettings.getProfiles();
+        const targetId = profileId && profiles[profileId] ? profileId : null;
+        const activeId = eqDeviceProfileSettings.getActiveProfile();
+        if (targetId === activeId) return false;
+
+        const current = eqDeviceProfileSettings.captureSnapshot();
+        if (activeId && profiles[activeId]) {
+            eqDeviceProfileSettings.updateProfileSnapshot(activeId, current);
+        } else {
+            eqDeviceProfileSettings.setFallbackSnapshot(current);
+        }
+
+        const snapshot = targetId ? profiles[targetId].snapshot : eqDeviceProfileSettings.getFallbackSnapshot();
+        eqDeviceProfileSettings.setActiveProfile(targetId);
+        if (snapshot) {
+            this._applyEQSnapshot(snapshot);
+        }
+
+        console.log(`[AudioContext] EQ profile: ${targetId ? profiles[targetId].name : 'default'}`);
+        window.dispatchEvent(
+            new CustomEvent('eq-profile-changed', { detail: { profileId: targetId, device: this.currentOutputDevice } })
+        );
+        return true;
+    }
+
+    /**
+     * Write a profile snapshot to storage and rebuild the EQ, graphic EQ, dynamics, convolution and binaural state
+     */
+    _applyEQSnapshot(snapshot) {
+        eqDeviceProfileSettings.applySnapshot(snapshot);
+        this._loadSettings();
+
+        this.isGraphicEQEnabled = equalizerSettings.isGraphicEqEnabled();
+        this.geqBandCount = equalizerSettings.getGraphicEqBandCount();
+        this.geqFreqRange = equalizerSettings.getGraphicEqFreqRange();
+        this.geqFrequencies = generateFrequencies(this.geqBandCount, this.geqFreqRange.min, this.geqFreqRange.max);
+        this.geqGains = equalizerSettings.getGraphicEqGains(this.geqBandCount);
+        this.geqPreamp = equalizerSettings.getGraphicEqPreamp();
+
+        if (this.isInitialized && this.audioContext) {
+            this._destroyGraphicEQ();
+            this._createGraphicEQ();
+            this._rebuildFilterChains();
+        }
+
+        const wasDynamicsActive = this._isDynamicsActive();
+        this.dynamicsSettings = sanitizeDynamicsSettings(dynamicsSettings.get());
+        this._applyDynamicsSettings(wasDynamicsActive);
+        this.convolutionSettings = sanitizeConvolutionSettings(convolutionSettings.get());
+        void this._applyConvolutionSettings();
+        void this._reloadBinauralSettings();
+    }
+
+    /**
+     * Push stored binaural settings into a running DSP instance
+     */
+    async _reloadBinauralSettings() {
+        if (!this.binauralDsp) return;
+        const dsp = this.binauralDsp;
+
+        dsp.setCrossfeedLevel(binauralDspSettings.getCrossfeedLevel());
+        dsp.setWideningAmount(binauralDspSettings.getWideningAmount());
+        dsp.crossfeedEnabled = binauralDspSettings.getCrossfeedEnabled();
+        dsp.wideningEnabled = binauralDspSettings.getWideningEnabled();
+        const hrtfPreset = binauralDspSettings.getHrtfPreset();
+        if (hrtfPreset !== dsp.hrtfPreset) {
+            await dsp.setHrtfPreset(hrtfPreset);
+        }
+        await dsp.setEnabled(this.isBinauralEnabled);
+
+        if (this.isInitialized) this._connectGraph();
+    }
+
     /**
      * Get current gain range
      */
@@ -985,54 +1644,12 @@ class AudioContextManager {
     }
 
     /**
-     * Calculate biquad filter magnitude response in dB at a given frequency
+     * Calculate a band's magnitude response in dB at a given frequency.
+     * Covers every filter type, including the cascaded stages of steep high/low-pass bands.
      */
-    _biquadResponseDb(f, band, sr) {
+    _biquadResponseDb(f, band, sr = this.audioContext?.sampleRate ?? 48000) {
         if (!band.enabled || !band.type) return 0;
-        const w = (2 * Math.PI * band.freq) / sr;
-        const p = (2 * Math.PI * f) / sr;
-        const s = Math.sin(w) / (2 * band.q);
-        const A = Math.pow(10, band.gain / 40);
-        const c = Math.cos(w);
-        let b0, b1, b2, a0, a1, a2;
-        const t = band.type[0];
-        if (t === 'p') {
-            b0 = 1 + s * A;
-            b1 = -2 * c;
-            b2 = 1 - s * A;
-            a0 = 1 + s / A;
-            a1 = -2 * c;
-            a2 = 1 - s / A;
-        } else if (t === 'l') {
-            const sq = 2 * Math.sqrt(A) * s;
-            b0 = A * (A + 1 - (A - 1) * c + sq);
-            b1 = 2 * A * (A - 1 - (A + 1) * c);
-            b2 = A * (A + 1 - (A - 1) * c - sq);
-            a0 = A + 1 + (A - 1) * c + sq;
-            a1 = -2 * (A - 1 + (A + 1) * c);
-            a2 = A + 1 + (A - 1) * c - sq;
-        } else if (t === 'h') {
-            const sq = 2 * Math.sqrt(A) * s;
-            b0 = A * (A + 1 + (A - 1) * c + sq);
-            b1 = -2 * A * (A - 1 + (A + 1) * c);
-            b2 = A * (A + 1 + (A - 1) * c - sq);
-            a0 = A + 1 - (A - 1) * c + sq;
-            a1 = 2 * (A - 1 - (A + 1) * c);
-            a2 = A + 1 - (A - 1) * c - sq;
-        } else {
-            return 0;
-        }
-        const _a0 = 1 / a0;
-        const b0n = b0 * _a0,
-            b1n = b1 * _a0,
-            b2n = b2 * _a0;
-        const a1n = a1 * _a0,
-            a2n = a2 * _a0;
-        const cp = Math.cos(p),
-            c2p = Math.cos(2 * p);
-        const n = b0n * b0n + b1n * b1n + b2n * b2n + 2 * (b0n * b1n + b1n * b2n) * cp + 2 * b0n * b2n * c2p;
-        const d = 1 + a1n * a1n + a2n * a2n + 2 * (a1n + a1n * a2n) * cp + 2 * a2n * c2p;
-        return 10 * Math.log10(n / d);
+        return getFilterStages(band).reduce((sum, stage) => sum + biquadStageResponseDb(f, stage, sr), 0);
     }
 
     /**
@@ -1052,9 +1669,8 @@ class AudioContextManager {
         const clampedGain = this._clampGain(gainDb);
         this.currentGains[bandIndex] = clampedGain;
 
-        if (this.filters[bandIndex] && this.audioContext) {
-            const now = this.audioContext.currentTime;
-            this.filters[bandIndex].gain.setTargetAtTime(clampedGain, now, 0.01);
+        if (this.audioContext) {
+            this._applyBandGain(bandIndex, this.audioContext.currentTime);
         }
 
         equalizerSettings.setGains(this.currentGains);
@@ -1078,9 +1694,7 @@ class AudioContextManager {
             const clampedGain = this._clampGain(gain);
             this.currentGains[index] = clampedGain;
 
-            if (this.filters[index]) {
-                this.filters[index].gain.setTargetAtTime(clampedGain, now, 0.01);
-            }
+            this._applyBandGain(index, now);
         });
 
         equalizerSettings.setGains(this.currentGains);
@@ -1133,7 +1747,8 @@ class AudioContextManager {
         this.currentTypes = equalizerSettings.getBandTypes(this.bandCount);
         this.currentQs = equalizerSettings.getBandQs(this.bandCount);
         this.currentChannels = equalizerSettings.getBandChannels(this.bandCount);
-        this.msEnabled = this.currentChannels.some((ch) => ch === 'mid' || ch === 'side');
+        this.currentSlopes = equalizerSettings.getBandSlopes(this.bandCount);
+        this._updateRoutingFlags();
         this.isMonoAudioEnabled = monoAudioSettings.isEnabled();
         this.preamp = equalizerSettings.getPreamp();
         this.isBinauralEnabled = binauralDspSettings.isEnabled();
@@ -1167,7 +1782,7 @@ class AudioContextManager {
     /**
      * Apply AutoEQ-generated bands to the equalizer
      * Unlike regular presets, AutoEQ bands have specific frequencies, gains, and Q values
-     * @param {Array<{id: number, type: string, freq: number, gain: number, q: number, enabled: boolean}>} bands
+     * @param {Array<{id: number, type: string, freq: number, gain: number, q: number, slope?: number, channel?: string, enabled: boolean}>} bands
      * @returns {string} Exported text representation of the applied EQ
      */
     applyAutoEQBands(bands, skipPreamp = false) {
@@ -1207,6 +1822,7 @@ class AudioContextManager {
         const newQs = slicedBands.map((b) => b.q);
         const newGains = slicedBands.map((b) => this._clampGain(b.gain));
         const newChannels = slicedBands.map((b) => b.channel || 'stereo');
+        const newSlopes = slicedBands.map((b) => normalizeSlope(b.slope));
         while (newFrequencies.length < count) {
             const lastFreq = newFrequencies[newFrequencies.length - 1] || 1000;
             newFrequencies.push(Math.round(Math.min(lastFreq * 2, maxFreq)));
@@ -1214,6 +1830,7 @@ class AudioContextManager {
             newQs.push(1.0);
             newGains.push(0);
             newChannels.push('stereo');
+            newSlopes.push(12);
         }
 
         // Update band count via class setter to trigger equalizer-band-count-changed event
@@ -1221,56 +1838,32 @@ class AudioContextManager {
             this.setBandCount(count);
         }
 
-        // Override frequencies, types, Qs, and channels with band-specific values
+        // Override frequencies, types, Qs, slopes, and channels with band-specific values
         this.frequencies = newFrequencies;
         this.currentTypes = newTypes;
         this.currentQs = newQs;
         this.currentGains = newGains;
         this.currentChannels = newChannels;
+        this.currentSlopes = newSlopes;
 
-        // Determine if M/S processing is needed
-        const needsMS = newChannels.some((ch) => ch === 'mid' || ch === 'side');
-        const msChanged = needsMS !== this.msEnabled;
-        this.msEnabled = needsMS;
+        // Determine which routing stages (M/S, L/R) are needed
+        const routingChanged = this._updateRoutingFlags();
 
         if (this.isInitialized && this.audioContext) {
-            const needsRebuild =
-                msChanged || this.filters.length !== count || (needsMS && this.midFilters.length !== count);
-
-            if (needsRebuild) {
-                // M/S state changed or band count changed - full rebuild
-                this._destroyMSFilters();
-                this._destroyEQ();
-                this._createEQ();
-                if (needsMS) {
-                    this._createMSFilters();
-                }
-                this._connectGraph();
-            } else if (needsMS) {
-                // M/S active - update both parallel chains in-place
-                const now = this.audioContext.currentTime;
-
-                // Update main filters (not connected in M/S mode, kept in sync for stereo fallback)
-                this._updateFilterChain(this.filters, newFrequencies, newTypes, newQs, newGains, now);
-
-                // Update mid filters (gain = 0 for side-only bands)
-                const midGains = newGains.map((g, i) => (newChannels[i] === 'side' ? 0 : g));
-                this._updateFilterChain(this.midFilters, newFrequencies, newTypes, newQs, midGains, now);
-
-                // Update side filters (gain = 0 for mid-only bands)
-                const sideGains = newGains.map((g, i) => (newChannels[i] === 'mid' ? 0 : g));
-                this._updateFilterChain(this.sideFilters, newFrequencies, newTypes, newQs, sideGains, now);
-            } else if (this.filters.length === count) {
-                // Normal stereo - update in-place
-                const now = this.audioContext.currentTime;
-                this._updateFilterChain(this.filters, newFrequencies, newTypes, newQs, newGains, now);
-            } else {
-                // Band count changed - must rebuild
-                this._destroyMSFilters();
-                this._destroyEQ();
-                this._createEQ();
-                if (this.msEnabled) this._createMSFilters();
-                this._connectGraph();
+            // Update every active chain in place; rebuild when routing or any band's stage layout changed
+            const now = this.audioContext.currentTime;
+            const updatedInPlace =
+                !routingChanged &&
+                this._updateFilterChain(this.filters, 'stereo', now) &&
+                (!this.msEnabled ||
+                    (this._updateFilterChain(this.midFilters, 'mid', now) &&
+                        this._updateFilterChain(this.sideFilters, 'side', now))) &&
+                (!this.lrEnabled ||
+                    (this._updateFilterChain(this.leftFilters, 'left', now) &&
+                        this._updateFilterChain(this.rightFilters, 'right', now)));
+
+            if (!updatedInPlace) {
+                this._rebuildFilterChains();
             }
         }
 
@@ -1285,150 +1878,53 @@ class AudioContextManager {
         equalizerSettings.setBandTypes(this.currentTypes);
         equalizerSettings.setBandQs(this.currentQs);
         equalizerSettings.setBandChannels(this.currentChannels);
+        equalizerSettings.setBandSlopes(this.currentSlopes);
 
         // Generate export text using the actual applied preamp value
-        const lines = [`Preamp: ${this.preamp.toFixed(1)} dB`];
-        sortedBands.forEach((band, index) => {
-            if (index >= count) return;
-            const filterType = band.type === 'lowshelf' ? 'LSC' : band.type === 'highshelf' ? 'HSC' : 'PK';
-            lines.push(
-                `Filter ${index + 1}: ON ${filterType} Fc ${newFrequencies[index]} Hz Gain ${newGains[index].toFixed(1)} dB Q ${newQs[index].toFixed(2)}`
-            );
-        });
+        return formatEqualizerAPO(this.preamp, this._getCurrentBands().slice(0, slicedBands.length));
+    }
 
-        return lines.join('\n');
+    /**
+     * Current band settings as band descriptors
+     */
+    _getCurrentBands() {
+        return this.frequencies.map((freq, index) => ({
+            id: index,
+            type: (this.currentTypes && this.currentTypes[index]) || 'peaking',
+            freq,
+            gain: this.currentGains[index] || 0,
+            q: this.currentQs && this.currentQs[index] > 0 ? this.currentQs[index] : this._calculateQ(index),
+            slope: normalizeSlope(this.currentSlopes && this.currentSlopes[index]),
+            channel: (this.currentChannels && this.currentChannels[index]) || 'stereo',
+            enabled: true,
+        }));
     }
 
     /**
-     * Export equalizer settings to text format
+     * Export equalizer settings to Equalizer APO text format
      * @returns {string} Exported settings in text format
      */
     exportEQToText() {
-        const lines = [];
-        const preampValue = this.getPreamp();
-        lines.push(`Preamp: ${preampValue.toFixed(1)} dB`);
-
-        this.frequencies.forEach((freq, index) => {
-            const gain = this.currentGains[index] || 0;
-            const type = (this.currentTypes && this.currentTypes[index]) || 'peaking';
-            const filterType = type === 'lowshelf' ? 'LSC' : type === 'highshelf' ? 'HSC' : 'PK';
-            const q = this.currentQs && this.currentQs[index] > 0 ? this.currentQs[index] : this._calculateQ(index);
-            const filterNum = index + 1;
-            lines.push(
-                `Filter ${filterNum}: ON ${filterType} Fc ${freq} Hz Gain ${gain.toFixed(1)} dB Q ${q.toFixed(2)}`
-            );
-        });
-
-        return lines.join('\n');
+        return formatEqualizerAPO(this.getPreamp(), this._getCurrentBands());
     }
 
     /**
-     * Import equalizer settings from text format
+     * Import equalizer settings from Equalizer APO text format, including filter types,
+     * slopes and Channel: directives
      * @param {string} text - Text format settings
      * @returns {boolean} True if import was successful
      */
     importEQFromText(text) {
         try {
-            const lines = text
-                .split('\n')
-                .map((line) => line.trim())
-                .filter((line) => line);
-            const filters = [];
-            let preamp = 0;
-
-            for (const line of lines) {
-                // Parse preamp
-                const preampMatch = line.match(/^Preamp:\s*([+-]?\d+\.?\d*)\s*dB$/i);
-                if (preampMatch) {
-                    preamp = parseFloat(preampMatch[1]);
-                    continue;
-                }
+            const { preamp, bands } = parseEqualizerAPO(text);
 
-                // Parse filter lines (handle "Filter:" and "Filter X:" formats)
-                const filterMatch = line.match(
-                    /^Filter\s*\d*:\s*ON\s+(\w+)\s+Fc\s+(\d+)\s+Hz\s+Gain\s*([+-]?\d+\.?\d*)\s*dB(?:\s+Q\s+(\d+\.?\d*))?/i
-                );
-                if (filterMatch) {
-                    const type = filterMatch[1].toUpperCase();
-                    const freq = parseInt(filterMatch[2], 10);
-                    const gain = parseFloat(filterMatch[3]);
-                    const q = filterMatch[4] ? parseFloat(filterMatch[4]) : Math.SQRT1_2;
-                    filters.push({ type, freq, gain, q });
-                }
-            }
-
-            if (filters.length === 0) {
+            if (bands.length === 0) {
                 console.warn('[AudioContext] No valid filters found in import text');
                 return false;
             }
 
-            // Apply preamp
+            this.applyAutoEQBands(bands, true);
             this.setPreamp(preamp);
-
-            // If different number of bands, adjust
-            const newCount = Math.max(
-                equalizerSettings.MIN_BANDS,
-                Math.min(equalizerSettings.MAX_BANDS, filters.length)
-            );
-            if (newCount !== this.bandCount) {
-                this.setBandCount(newCount);
-            }
-
-            // Apply per-band frequencies, types, Qs, and gains from import
-            const sliced = filters.slice(0, this.bandCount);
-            const typeMap = {
-                PK: 'peaking',
-                LS: 'lowshelf',
-                LSC: 'lowshelf',
-                LSF: 'lowshelf',
-                HS: 'highshelf',
-                HSC: 'highshelf',
-                HSF: 'highshelf',
-            };
-
-            // Pad arrays to bandCount if import has fewer filters than minimum
-            const padCount = this.bandCount - sliced.length;
-            const freqs = sliced.map((f) => f.freq);
-            const types = sliced.map((f) => typeMap[f.type] || 'peaking');
-            const qs = sliced.map((f) => f.q);
-            const gains = sliced.map((f) => this._clampGain(f.gain));
-            if (padCount > 0) {
-                const lastFreq = freqs[freqs.length - 1] || 1000;
-                const maxFreq = (this.audioContext?.sampleRate ?? 48000) / 2 - 1;
-                for (let p = 0; p < padCount; p++) {
-                    const padFreq = Math.min(lastFreq * Math.pow(2, p + 1), maxFreq);
-                    freqs.push(Math.round(padFreq));
-                    types.push('peaking');
-                    qs.push(this._calculateQ(freqs.length - 1));
-                    gains.push(0);
-                }
-            }
-
-            this.frequencies = freqs;
-            this.currentTypes = types;
-            this.currentQs = qs;
-            this.currentGains = gains;
-
-            // Reset M/S channel assignments - imported config has no channel info
-            this.currentChannels = new Array(this.bandCount).fill('stereo');
-            this.msEnabled = false;
-
-            // Rebuild EQ chain to apply new frequencies, types, and Qs
-            if (this.isInitialized && this.audioContext) {
-                this._destroyMSFilters();
-                this._destroyEQ();
-                this._createEQ();
-                if (this.msEnabled) this._createMSFilters();
-                this._connectGraph();
-            }
-
-            // Persist all band settings including custom frequencies
-            equalizerSettings.setCustomFrequencies(this.frequencies);
-            equalizerSettings.setGains(this.currentGains);
-            equalizerSettings.setBandTypes(this.currentTypes);
-            equalizerSettings.setBandQs(this.currentQs);
-            equalizerSettings.setBandChannels(this.currentChannels);
-
             return true;
         } catch (e) {
             console.warn('[AudioContext] Failed to import EQ settings:', e);
@@ -0,0 +1,402 @@
+// js/backup.js
+// Full backups as a single .monochrome-backup file: a ZIP holding manifest.json and one JSON file per section.
+// Besides the library it keeps settings, themes, EQ presets, blocklists, listening stats, lyrics offsets and the
+// configured instances, so a backup also moves everything to another (self-hosted) instance. Restoring can be
+// limited to some sections and previewed as a diff first. Tokens and sessions are never written to a backup.
+
+import { db } from './db.js';
+import {
+    apiSettings,
+    contentBlockingSettings,
+    equalizerSettings,
+    settingsSyncSettings,
+    syncBackendSettings,
+    themeManager,
+} from './storage.js';
+import { isSecretKey } from './settings-sync.js';
+import { triggerDownload } from './download-utils.ts';
+import { escapeHtml } from './utils.js';
+
+export const BACKUP_FORMAT = 'monochrome-backup';
+export const BACKUP_VERSION = 1;
+export const BACKUP_EXTENSION = '.monochrome-backup';
+
+const LIBRARY_STORES = {
+    favorites_tracks: 'id',
+    favorites_albums: 'id',
+    favorites_artists: 'id',
+    favorites_playlists: 'uuid',
+    favorites_mixes: 'id',
+    history_tracks: 'timestamp',
+    user_playlists: 'id',
+    user_folders: 'id',
+    smart_playlists: 'id',
+};
+
+// Settings kept in IndexedDB instead of localStorage, stored in their section as JSON like the other keys
+const DB_SETTING_KEYS = ['speaker-eq-profiles'];
+const EQUALIZER_KEYS = [
+    ...Object.values(equalizerSettings).filter((value) => typeof value === 'string'),
+    ...DB_SETTING_KEYS,
+    'monochrome-legacy-geq-custom-presets',
+];
+const THEME_KEYS = [themeManager.STORAGE_KEY, themeManager.CUSTOM_THEME_KEY, 'community-theme', 'custom_theme_css'];
+const BLOCKLIST_KEYS = [
+    contentBlockingSettings.BLOCKED_ARTISTS_KEY,
+    contentBlockingSettings.BLOCKED_ALBUMS_KEY,
+    contentBlockingSettings.BLOCKED_TRACKS_KEY,
+];
+const INSTANCE_KEYS = [
+    'monochrome-user-api-instances-v1',
+    'monochrome-pocketbase-url',
+    'monochrome-appwrite-endpoint',
+    'monochrome-appwrite-project',
+];
+// Caches, per-device state that means nothing on another device, and the WebDAV sync login
+const EXCLUDED_KEYS = [
+    apiSettings.STORAGE_KEY,
+    settingsSyncSettings.STATE_KEY,
+    syncBackendSettings.WEBDAV_URL_KEY,
+    syncBackendSettings.WEBDAV_USERNAME_KEY,
+    syncBackendSettings.WEBDAV_PASSWORD_KEY,
+];
+const EXCLUDED_PATTERN = /cache|turnstile|expiry/i;
+
+/** Sections of a backup, in the order they are listed. Every localStorage key belongs to the first it matches. */
+export const BACKUP_SECTIONS = [
+    { id: 'library', label: 'Library, playlists & history' },
+    { id: 'themes', label: 'Themes', matches: (key) => THEME_KEYS.includes(key) },
+    { id: 'equalizer', label: 'Equalizer & presets', matches: (key) => EQUALIZER_KEYS.includes(key) },
+    { id: 'blocklists', label: 'Blocked content', matches: (key) => BLOCKLIST_KEYS.includes(key) },
+    { id: 'listening', label: 'Listening stats', matches: (key) => key === 'monochrome-listening-data' },
+    { id: 'lyricsOffsets', label: 'Lyrics timing offsets', matches: (key) => key.startsWith('lyrics-offset-') },
+    { id: 'instances', label: 'Instances & servers', matches: (key) => INSTANCE_KEYS.includes(key) },
+    { id: 'settings', label: 'Other settings', matches: () => true },
+];
+
+function isBackedUp(key) {
+    return !isSecretKey(key) && !EXCLUDED_KEYS.includes(key) && !EXCLUDED_PATTERN.test(key);
+}
+
+/** Id of the section a localStorage key is saved in, or null if it isn't backed up */
+export function getKeySection(key) {
+    if (!isBackedUp(key)) return null;
+    return BACKUP_SECTIONS.find((section) => section.matches?.(key)).id;
+}
+
+/** Sort localStorage entries into their sections */
+export function splitStorage(entries) {
+    const sections = Object.fromEntries(BACKUP_SECTIONS.filter((s) => s.matches).map((s) => [s.id, {}]));
+    Object.entries(entries).forEach(([key, value]) => {
+        const section = getKeySection(key);
+        if (section) sections[section][key] = value;
+    });
+    return sections;
+}
+
+function readStorage() {
+    const entries = {};
+    for (let i = 0; i < localStorage.length; i++) {
+        const key = localStorage.key(i);
+        if (key !== null) entries[key] = localStorage.getItem(key);
+    }
+    return entries;
+}
+
+async function readDbSettings() {
+    const entries = {};
+    for (const key of DB_SETTING_KEYS) {
+        const value = await db.getSetting(key);
+        if (value !== undefined && value !== null) entries[key] = JSON.stringify(value);
+    }
+    return entries;
+}
+
+/** Everything a backup holds, taken from this device */
+export async function collectBackup() {
+    const settings = { ...readStorage(), ...(await readDbSettings()) };
+    return {
+        version: BACKUP_VERSION,
+        createdAt: new Date().toISOString(),
+        origin: window.location.origin,
+        sections: { library: await db.exportData(), ...splitStorage(settings) },
+    };
+}
+
+// Exports of the old "Export" buttons: the library JSON, or a map of monochrome-* settings
+function migrateLegacyExport(data) {
+    if (Object.keys(LIBRARY_STORES).some((store) => store in data)) {
+        const library = Object.fromEntries(Object.keys(LIBRARY_STORES).map((store) => [store, data[store] || []]));
+        return { version: 1, createdAt: null, origin: null, sections: { library } };
+    }
+    const entries = Object.fromEntries(
+        Object.entries(data).map(([key, value]) => [key, typeof value === 'string' ? value : JSON.stringify(value)])
+    );
+    const sections = Object.entries(splitStorage(entries)).filter(([, values]) => Object.keys(values).length > 0);
+    // Only monochrome-* keys were exported, so keys missing from it are kept on restore
+    return { version: 1, createdAt: null, origin: null, partial: true, sections: Object.fromEntries(sections) };
+}
+
+// MIGRATIONS[n] turns a version n backup into version n + 1
+const MIGRATIONS = [migrateLegacyExport];
+
+/** Bring a backup of any older version up to BACKUP_VERSION */
+export function migrateBackup(backup) {
+    let version = backup.version ?? 0;
+    if (version > BACKUP_VERSION) {
+        throw new Error('This backup was made by a newer version of Monochrome. Update the app to restore it.');
+    }
+    let result = version === 0 ? backup.data : backup;
+    while (version < BACKUP_VERSION) {
+        result = MIGRATIONS[version](result);
+        version += 1;
+    }
+    return result;
+}
+
+function getRecordKey(store, item) {
+    return String(item?.[LIBRARY_STORES[store]] ?? JSON.stringify(item));
+}
+
+// One comparable value per entry of a section
+function flattenSection(id, data) {
+    if (id !== 'library') return data || {};
+    const flat = {};
+    Object.keys(LIBRARY_STORES).forEach((store) => {
+        const items = Array.isArray(data?.[store]) ? data[store] : Object.values(data?.[store] || {});
+        items.forEach((item) => {
+            flat[`${store}/${getRecordKey(store, item)}`] = JSON.stringify(item);
+        });
+    });
+    return flat;
+}
+
+/**
+ * What restoring a backup would change, without changing anything
+ * @param {Object} current - Backup of the current state, see collectBackup
+ * @param {Object} backup - Backup to restore, already migrated
+ * @returns {Array<{id: string, label: string, added: string[], changed: string[], removed: string[]}>} One entry
+ *   per section in the backup
+ */
+export function diffBackup(current, backup) {
+    return BACKUP_SECTIONS.filter((section) => backup.sections[section.id]).map(({ id, label }) => {
+        const before = flattenSection(id, current.sections[id]);
+        const after = flattenSection(id, backup.sections[id]);
+        const diff = { id, label, added: [], changed: [], removed: [] };
+        Object.entries(after).forEach(([key, value]) => {
+            if (!(key in before)) diff.added.push(key);
+            else if (before[key] !== value) diff.changed.push(key);
+        });
+        if (!backup.partial) diff.removed = Object.keys(before).filter((key) => !(key in after));
+        return diff;
+    });
+}
+
+/**
+ * Replace the chosen sections with the backup's copy
+ * @param {Object} backup - Already migrated
+ * @param {string[]} sectionIds
+ */
+export async function restoreBackup(backup, sectionIds) {
+    for (const id of sectionIds) {
+        const data = backup.sections[id];
+        if (!data) continue;
+
+        if (id === 'library') {
+            await db.importData(structuredClone(data), true);
+            continue;
+        }
+        if (!backup.partial) {
+            Object.keys(readStorage()).forEach((key) => {
+                if (getKeySection(key) === id && !(key in data)) localStorage.removeItem(key);
+            });
+            for (const key of DB_SETTING_KEYS) {
+                if (getKeySection(key) === id && !(key in data)) await db.deleteSetting(key);
+            }
+        }
+        for (const [key, value] of Object.entries(data)) {
+            if (getKeySection(key) !== id) continue;
+            if (DB_SETTING_KEYS.includes(key)) await db.saveSetting(key, JSON.parse(value));
+            else localStorage.setItem(key, value);
+        }
+    }
+}
+
+async function loadClientZip() {
+    try {
+        return await import('client-zip');
+    } catch (error) {
+        console.error('Failed to load client-zip:', error);
+        throw new Error('Failed to load ZIP library');
+    }
+}
+
+/** Pack a backup into a .monochrome-backup archive */
+export async function createBackupArchive(backup) {
+    const lastModified = backup.createdAt ? new Date(backup.createdAt) : new Date();
+    const ids = Object.keys(backup.sections);
+    const manifest = {
+        format: BACKUP_FORMAT,
+        version: backup.version,
+        createdAt: backup.createdAt,
+        origin: backup.origin,
+        sections: Object.fromEntries(ids.map((id) => [id, `sections/${id}.json`])),
+    };
+    const files = ids.map((id) => ({
+        name: manifest.sections[id],
+        lastModified,
+        input: JSON.stringify(backup.sections[id]),
+    }));
+    const { downloadZip } = await loadClientZip();
+    return await downloadZip([
+        { name: 'manifest.json', lastModified, input: JSON.stringify(manifest, null, 2) },
+        ...files,
+    ]).blob();
+}
+
+async function inflate(data) {
+    const stream = new Blob([data]).stream().pipeThrough(new DecompressionStream('deflate-raw'));
+    return new Uint8Array(await new Response(stream).arrayBuffer());
+}
+
+/** Files of a ZIP archive by name. Only stored and deflated entries are supported. */
+export async function readZip(buffer) {
+    const view = new DataView(buffer);
+    let end = buffer.byteLength - 22;
+    while (end >= 0 && view.getUint32(end, true) !== 0x06054b50) end--;
+    if (end < 0) throw new Error('Not a ZIP archive');
+
+    const count = view.getUint16(end + 10, true);
+    let offset = view.getUint32(end + 16, true);
+    const decoder = new TextDecoder();
+    const files = {};
+    for (let i = 0; i < count; i++) {
+        if (view.getUint32(offset, true) !== 0x02014b50) throw new Error('Corrupted ZIP archive');
+        const method = view.getUint16(offset + 10, true);
+        const size = view.getUint32(offset + 20, true);
+        const nameLength = view.getUint16(offset + 28, true);
+        const extraLength = view.getUint16(offset + 30, true);
+        const commentLength = view.getUint16(offset + 32, true);
+        const headerOffset = view.getUint32(offset + 42, true);
+        const name = decoder.decode(new Uint8Array(buffer, offset + 46, nameLength));
+
+        const dataOffset =
+            headerOffset + 30 + view.getUint16(headerOffset + 26, true) + view.getUint16(headerOffset + 28, true);
+        const data = new Uint8Array(buffer, dataOffset, size);
+        if (method === 0) files[name] = data;
+        else if (method === 8) files[name] = await inflate(data);
+        else throw new Error(`Unsupported compression in ${name}`);
+
+        offset += 46 + nameLength + extraLength + commentLength;
+    }
+    return files;
+}
+
+/**
+ * Read a .monochrome-backup archive, or a JSON file from the old export buttons
+ * @param {Blob} file
+ * @returns {Promise<Object>} The backup, migrated to BACKUP_VERSION
+ */
+export async function readBackupFile(file) {
+    const buffer = await file.arrayBuffer();
+    const bytes = new Uint8Array(buffer, 0, Math.min(2, buffer.byteLength));
+    const decoder = new TextDecoder();
+
+    if (bytes[0] !== 0x50 || bytes[1] !== 0x4b) {
+        return migrateBackup({ version: 0, data: JSON.parse(decoder.decode(buffer)) });
+    }
+
+    const files = await readZip(buffer);
+    const readJson = (name) => (files[name] ? JSON.parse(decoder.decode(files[name])) : undefined);
+    const manifest = readJson('manifest.json');
+    if (manifest?.format !== BACKUP_FORMAT) throw new Error('Not a Monochrome backup');
+
+    const sections = {};
+    Object.entries(manifest.sections || {}).forEach(([id, name]) => {
+        const data = readJson(name);
+        if (data !== undefined) sections[id] = data;
+    });
+    return migrateBackup({ ...manifest, sections });
+}
+
+export async function exportBackup() {
+    const archive = await createBackupArchive(await collectBackup());
+    triggerDownload(archive, `monochrome-${new Date().toISOString().split('T')[0]}${BACKUP_EXTENSION}`);
+}
+
+function describeDiff({ added, changed, removed }) {
+    const parts = [
+        added.length && `${added.length} added`,
+        changed.length && `${changed.length} changed`,
+        removed.length && `${removed.length} removed`,
+    ].filter(Boolean);
+    return parts.length > 0 ? parts.join(' · ') : 'No changes';
+}
+
+/**
+ * Show what restoring a backup would change and restore the sections the user picks
+ * @returns {Promise<boolean>} Whether anything was restored
+ */
+export async function openBackupRestore(backup) {
+    const modal = document.getElementById('backup-restore-modal');
+    if (!modal) return false;
+
+    const diff = diffBackup(await collectBackup(), backup);
+    const list = modal.querySelector('#backup-restore-sections');
+    const summary = modal.querySelector('#backup-restore-summary');
+    const confirmBtn = modal.querySelector('#backup-restore-confirm');
+
+    const created = backup.createdAt ? new Date(backup.createdAt).toLocaleString() : 'an older version';
+    summary.textContent = backup.origin ? `Backup from ${created} on ${backup.origin}` : `Backup from ${created}`;
+
+    list.innerHTML = diff
+        .map((section) => {
+            const empty = section.added.length + section.changed.length + section.removed.length === 0;
+            return `
+                <label class="backup-restore-section">
+                    <input type="checkbox" value="${section.id}" ${empty ? '' : 'checked'} />
+                    <span class="backup-restore-label">${escapeHtml(section.label)}</span>
+                    <span class="backup-restore-diff">${describeDiff(section)}</span>
+                </label>
+            `;
+        })
+        .join('');
+
+    const getSelected = () => [...list.querySelectorAll('input:checked')].map((input) => input.value);
+    const updateConfirm = () => {
+        confirmBtn.disabled = getSelected().length === 0;
+    };
+    updateConfirm();
+    modal.classList.add('active');
+
+    return new Promise((resolve) => {
+        const close = (value) => {
+            modal.classList.remove('active');
+            modal.removeEventListener('click', handleClick);
+            list.removeEventListener('change', updateConfirm);
+            resolve(value);
+        };
+
+        const handleClick = async (e) => {
+            if (e.target.classList.contains('modal-overlay') || e.target.id === 'backup-restore-cancel') {
+                close(false);
+            } else if (e.target === confirmBtn) {
+                confirmBtn.disabled = true;
+                confirmBtn.textContent = 'Restoring...';
+                try {
+                    await restoreBackup(backup, getSelected());
+                    close(true);
+                } catch (error) {
+                    console.error('Restore failed:', error);
+                    alert('Failed to restore the backup: ' + error.message);
+                    close(false);
+                } finally {
+                    confirmBtn.textContent = 'Restore';
+                }
+            }
+        };
+
+        modal.addEventListener('click', handleClick);
+        list.addEventListener('change', updateConfirm);
+    });
+}
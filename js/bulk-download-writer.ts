@@ -24,6 +24,13 @@ async function loadClientZip() {
  * and throws a DOMException with name 'AbortError' if the user cancels.
  */
 export interface IBulkDownloadWriter {
+    /**
+     * How files can be handed over while a download is still running:
+     * - 'files': each file is written as soon as it arrives and stays written
+     * - 'archive': files are appended to one archive, which is lost if it isn't finished
+     * - undefined: the writer needs every file at once, so they have to be staged first
+     */
+    readonly streaming?: 'files' | 'archive';
     write(files: AsyncIterable<WriterEntry>): Promise<void>;
 }
 
@@ -31,6 +38,8 @@ export interface IBulkDownloadWriter {
  * Triggers individual downloads for each file entry, one after another.
  */
 class SequentialFileWriter implements IBulkDownloadWriter {
+    readonly streaming = 'files';
+
     constructor() {}
 
     async write(files: AsyncIterable<WriterEntry>): Promise<void> {
@@ -66,6 +75,8 @@ export { sequentialFileWriter as SequentialFileWriter };
  * Prompts the user to choose a save location with showSaveFilePicker.
  */
 export class ZipStreamWriter implements IBulkDownloadWriter {
+    readonly streaming = 'archive';
+
     constructor(private readonly suggestedFilename: string) {}
 
     async write(files: AsyncIterable<WriterEntry>): Promise<void> {
@@ -105,6 +116,8 @@ export class ZipBlobWriter implements IBulkDownloadWriter {
  * the constructor is private so the directory handle is always set before use.
  */
 export class FolderPickerWriter implements IBulkDownloadWriter {
+    readonly streaming = 'files';
+
     private constructor(private readonly dirHandle: FileSystemDirectoryHandle) {}
 
     /** Returns the underlying directory handle (e.g. to persist it for later re-use). */
@@ -0,0 +1,98 @@
+// js/cloud-encryption-modal.js
+// Asks for the passphrase of the encrypted cloud library, either to turn encryption on or to unlock it on
+// another device. The modal stays open with the error shown until the passphrase is accepted or it is cancelled.
+
+import { syncManager } from './accounts/pocketbase.js';
+
+const MIN_PASSPHRASE_LENGTH = 8;
+
+const MODES = {
+    enable: {
+        title: 'Encrypt Cloud Library',
+        description:
+            'Your library is encrypted on this device before it is synced. The passphrase is needed on every ' +
+            'device you sign in on and cannot be recovered, so keep it somewhere safe.',
+        submit: 'Encrypt',
+        confirm: true,
+        run: (passphrase) => syncManager.enableEncryption(passphrase),
+    },
+    unlock: {
+        title: 'Unlock Cloud Library',
+        description: 'Your cloud library is encrypted. Enter the passphrase you chose to sync this device.',
+        submit: 'Unlock',
+        confirm: false,
+        run: (passphrase) => syncManager.unlockEncryption(passphrase),
+    },
+};
+
+/**
+ * @param {'enable'|'unlock'} mode
+ * @returns {Promise<boolean>} Whether the passphrase was accepted
+ */
+export function openCloudEncryption(mode) {
+    const modal = document.getElementById('cloud-encryption-modal');
+    if (!modal) return Promise.resolve(false);
+
+    const options = MODES[mode];
+    const form = modal.querySelector('#cloud-encryption-form');
+    const passphraseInput = modal.querySelector('#cloud-encryption-passphrase');
+    const confirmInput = modal.querySelector('#cloud-encryption-confirm');
+    const error = modal.querySelector('#cloud-encryption-error');
+    const submitBtn = modal.querySelector('#cloud-encryption-submit');
+
+    modal.querySelector('#cloud-encryption-title').textContent = options.title;
+    modal.querySelector('#cloud-encryption-description').textContent = options.description;
+    submitBtn.textContent = options.submit;
+    confirmInput.style.display = options.confirm ? '' : 'none';
+    passphraseInput.autocomplete = options.confirm ? 'new-password' : 'current-password';
+    passphraseInput.value = '';
+    confirmInput.value = '';
+    error.textContent = '';
+    modal.classList.add('active');
+    passphraseInput.focus();
+
+    return new Promise((resolve) => {
+        const close = (value) => {
+            modal.classList.remove('active');
+            modal.removeEventListener('click', handleClick);
+            form.removeEventListener('submit', handleSubmit);
+            passphraseInput.value = '';
+            confirmInput.value = '';
+            resolve(value);
+        };
+
+        const handleSubmit = async (e) => {
+            e.preventDefault();
+            const passphrase = passphraseInput.value;
+            if (options.confirm && passphrase.length < MIN_PASSPHRASE_LENGTH) {
+                error.textContent = `Use at least ${MIN_PASSPHRASE_LENGTH} characters.`;
+                return;
+            }
+            if (options.confirm && passphrase !== confirmInput.value) {
+                error.textContent = "The passphrases don't match.";
+                return;
+            }
+
+            submitBtn.disabled = true;
+            error.textContent = '';
+            try {
+                await options.run(passphrase);
+                close(true);
+            } catch (err) {
+                console.error('[CloudSync] Encryption failed:', err);
+                error.textContent = err.message;
+            } finally {
+                submitBtn.disabled = false;
+            }
+        };
+
+        const handleClick = (e) => {
+            if (e.target.classList.contains('modal-overlay') || e.target.id === 'cloud-encryption-cancel') {
+                close(false);
+            }
+        };
+
+        modal.addEventListener('click', handleClick);
+        form.addEventListener('submit', handleSubmit);
+    });
+}
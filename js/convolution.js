@@ -0,0 +1,379 @@
+// js/convolution.js
+// Convolution stage for user-supplied impulse responses: room correction, speaker/headphone
+// emulation or reverb. Placed after the EQ stages in the audio chain.
+// IRs are uploaded as WAV files and kept in MusicDatabase; they are decoded on demand.
+
+import { db } from './db.js';
+
+export const CONVOLUTION_DEFAULTS = {
+    enabled: false,
+    irId: null,
+    mix: 1, // 0 = dry only, 1 = wet only
+    gain: 0, // dB, applied to the stage output
+    normalize: true,
+};
+
+const GAIN_RANGE = [-24, 12];
+
+// Longer IRs cost a lot of CPU for little audible benefit
+export const MAX_IR_SECONDS = 20;
+
+// Channel layouts accepted for impulse responses
+const IR_LAYOUTS = { 1: 'mono', 2: 'stereo', 4: 'true-stereo' };
+
+// True-stereo IR channel order (LL, LR, RL, RR) as [input channel, output channel] pairs
+const TRUE_STEREO_ROUTING = [
+    [0, 0],
+    [0, 1],
+    [1, 0],
+    [1, 1],
+];
+
+// Constants from the Web Audio ConvolverNode normalization algorithm
+const NORMALIZE_GAIN_CALIBRATION = 0.00125;
+const NORMALIZE_CALIBRATION_SAMPLE_RATE = 44100;
+const NORMALIZE_MIN_POWER = 0.000125;
+
+const WAVE_FORMAT_PCM = 1;
+const WAVE_FORMAT_IEEE_FLOAT = 3;
+const WAVE_FORMAT_EXTENSIBLE = 0xfffe;
+
+export class ImpulseResponseError extends Error {
+    constructor(message) {
+        super(message);
+        this.name = 'ImpulseResponseError';
+    }
+}
+
+/**
+ * Fill in defaults and clamp convolution settings
+ */
+export function sanitizeConvolutionSettings(settings = {}) {
+    const gain = parseFloat(settings.gain);
+    const mix = parseFloat(settings.mix);
+    return {
+        enabled: settings.enabled === true,
+        irId: typeof settings.irId === 'string' && settings.irId ? settings.irId : null,
+        mix: Number.isFinite(mix) ? Math.max(0, Math.min(1, mix)) : CONVOLUTION_DEFAULTS.mix,
+        gain: Number.isFinite(gain)
+            ? Math.max(GAIN_RANGE[0], Math.min(GAIN_RANGE[1], gain))
+            : CONVOLUTION_DEFAULTS.gain,
+        normalize: settings.normalize !== false,
+    };
+}
+
+/**
+ * Map an IR channel count to its layout
+ * @param {number} channels
+ * @returns {'mono'|'stereo'|'true-stereo'}
+ */
+export function getImpulseResponseLayout(channels) {
+    const layout = IR_LAYOUTS[channels];
+    if (!layout) {
+        throw new ImpulseResponseError(
+            `Impulse responses must be mono, stereo or 4-channel true stereo (got ${channels} channels)`
+        );
+    }
+    return layout;
+}
+
+/**
+ * Read and validate the header of a WAV file without decoding the samples
+ * @param {ArrayBuffer} buffer
+ * @returns {{channels: number, sampleRate: number, bitsPerSample: number, sampleFormat: 'pcm'|'float',
+ *   frames: number, duration: number, layout: string}}
+ */
+export function parseWavInfo(buffer) {
+    const view = new DataView(buffer);
+    const tag = (offset) =>
+        offset + 4 <= view.byteLength
+            ? String.fromCharCode(
+                  view.getUint8(offset),
+                  view.getUint8(offset + 1),
+                  view.getUint8(offset + 2),
+                  view.getUint8(offset + 3)
+              )
+            : '';
+
+    if (tag(0) !== 'RIFF' || tag(8) !== 'WAVE') {
+        throw new ImpulseResponseError('Not a WAV file');
+    }
+
+    let format = null;
+    let dataSize = null;
+    let offset = 12;
+    while (offset + 8 <= view.byteLength) {
+        const id = tag(offset);
+        const size = view.getUint32(offset + 4, true);
+        const body = offset + 8;
+
+        if (id === 'fmt ' && size >= 16) {
+            let formatTag = view.getUint16(body, true);
+            if (formatTag === WAVE_FORMAT_EXTENSIBLE && size >= 40) {
+                // Sub-format GUID starts with the actual format tag
+                formatTag = view.getUint16(body + 24, true);
+            }
+            format = {
+                formatTag,
+                channels: view.getUint16(body + 2, true),
+                sampleRate: view.getUint32(body + 4, true),
+                blockAlign: view.getUint16(body + 12, true),
+                bitsPerSample: view.getUint16(body + 14, true),
+            };
+        } else if (id === 'data') {
+            // Streamed WAVs may leave the size unset; clamp to what is actually there
+            dataSize = Math.min(size, view.byteLength - body);
+            if (format) break;
+        }
+        offset = body + size + (size % 2);
+    }
+
+    if (!format) throw new ImpulseResponseError('WAV file has no format chunk');
+    if (dataSize === null) throw new ImpulseResponseError('WAV file has no audio data');
+
+    const { formatTag, channels, sampleRate, blockAlign, bitsPerSample } = format;
+    const isPcm = formatTag === WAVE_FORMAT_PCM && [8, 16, 24, 32].includes(bitsPerSample);
+    const isFloat = formatTag === WAVE_FORMAT_IEEE_FLOAT && [32, 64].includes(bitsPerSample);
+    if (!isPcm && !isFloat) {
+        throw new ImpulseResponseError(`Unsupported WAV encoding (format ${formatTag}, ${bitsPerSample}-bit)`);
+    }
+    const layout = getImpulseResponseLayout(channels);
+    if (!sampleRate || !blockAlign) throw new ImpulseResponseError('Invalid WAV format chunk');
+
+    const frames = Math.floor(dataSize / blockAlign);
+    if (frames === 0) throw new ImpulseResponseError('WAV file has no audio data');
+    const duration = frames / sampleRate;
+    if (duration > MAX_IR_SECONDS) {
+        throw new ImpulseResponseError(
+            `Impulse response is too long (${duration.toFixed(1)}s, max ${MAX_IR_SECONDS}s)`
+        );
+    }
+
+    return { channels, sampleRate, bitsPerSample, sampleFormat: isFloat ? 'float' : 'pcm', frames, duration, layout };
+}
+
+/**
+ * Gain the Web Audio ConvolverNode would apply with normalize = true, computed over all IR channels
+ * together so the balance between the four true-stereo paths is preserved.
+ * @param {Float32Array[]} channelData
+ * @param {number} sampleRate
+ */
+export function getNormalizationScale(channelData, sampleRate) {
+    const length = channelData[0]?.length || 0;
+    let power = 0;
+    for (const data of channelData) {
+        for (let i = 0; i < data.length; i++) power += data[i] * data[i];
+    }
+    power = Math.sqrt(power / (channelData.length * length));
+    if (!Number.isFinite(power) || power < NORMALIZE_MIN_POWER) power = NORMALIZE_MIN_POWER;
+
+    let scale = (1 / power) * NORMALIZE_GAIN_CALIBRATION;
+    if (sampleRate) scale *= NORMALIZE_CALIBRATION_SAMPLE_RATE / sampleRate;
+    // True-stereo sums two convolutions into each output
+    if (channelData.length === 4) scale *= 0.5;
+    return scale;
+}
+
+/**
+ * Validate a user-selected WAV file and store it in the database
+ * @param {File} file
+ * @returns {Promise<object>} The stored entry
+ */
+export async function importImpulseResponse(file) {
+    const data = await file.arrayBuffer();
+    const info = parseWavInfo(data);
+    const name = file.name.replace(/\.wav$/i, '');
+    return db.saveImpulseResponse(name, data, info);
+}
+
+/**
+ * Load a stored IR and decode it at the context's sample rate
+ * @param {BaseAudioContext} audioContext
+ * @param {string} id
+ * @returns {Promise<AudioBuffer>}
+ */
+export async function decodeImpulseResponse(audioContext, id) {
+    const entry = await db.getImpulseResponse(id);
+    if (!entry) throw new ImpulseResponseError('Impulse response not found');
+    // decodeAudioData detaches the buffer it is given
+    const buffer = await audioContext.decodeAudioData(entry.data.slice(0));
+    getImpulseResponseLayout(buffer.numberOfChannels);
+    return buffer;
+}
+
+export class ConvolutionStage {
+    /**
+     * @param {AudioContext} audioContext
+     */
+    constructor(audioContext) {
+        this.ctx = audioContext;
+        this.buffer = null;
+        this.layout = null;
+        this.normalize = true;
+        this.mix = CONVOLUTION_DEFAULTS.mix;
+        this.gain = CONVOLUTION_DEFAULTS.gain;
+        this._normalizeScale = 1;
+
+        // Everything is processed as stereo: mono is upmixed, surround is downmixed
+        this.inputNode = this.ctx.createGain();
+        this.inputNode.channelCount = 2;
+        this.inputNode.channelCountMode = 'explicit';
+        this.inputNode.channelInterpretation = 'speakers';
+        this.dryNode = this.ctx.createGain();
+        this.wetNode = this.ctx.createGain();
+        this.outputNode = this.ctx.createGain();
+
+        // Wet path nodes (rebuilt whenever the IR changes)
+        this._convolvers = [];
+        this._splitter = null;
+        this._merger = null;
+
+        this._applyLevels();
+        this._connectInternal();
+    }
+
+    /**
+     * Get the input/output nodes for graph insertion.
+     */
+    getNodes() {
+        return { input: this.inputNode, output: this.outputNode };
+    }
+
+    /**
+     * Reconnect internal graph (public API for external callers).
+     */
+    reconnect() {
+        this._connectInternal();
+    }
+
+    hasImpulseResponse() {
+        return this.buffer !== null;
+    }
+
+    /**
+     * Swap the impulse response. Mono and stereo IRs use a single convolver; 4-channel true-stereo
+     * IRs (LL, LR, RL, RR) use one mono convolver per path, summed into each output channel.
+     * @param {AudioBuffer|null} buffer
+     */
+    setImpulseResponse(buffer) {
+        this._destroyWetPath();
+        this.buffer = buffer;
+        this.layout = buffer ? getImpulseResponseLayout(buffer.numberOfChannels) : null;
+
+        if (buffer) {
+            if (this.layout === 'true-stereo') {
+                this._splitter = this.ctx.createChannelSplitter(2);
+                this._merger = this.ctx.createChannelMerger(2);
+                this._convolvers = TRUE_STEREO_ROUTING.map((_, i) => {
+                    const path = this.ctx.createBuffer(1, buffer.length, buffer.sampleRate);
+                    path.copyToChannel(buffer.getChannelData(i), 0);
+                    return this._createConvolver(path);
+                });
+            } else {
+                this._convolvers = [this._createConvolver(buffer)];
+            }
+
+            const channelData = Array.from({ length: buffer.numberOfChannels }, (_, i) => buffer.getChannelData(i));
+            this._normalizeScale = getNormalizationScale(channelData, buffer.sampleRate);
+        }
+
+        this._applyLevels();
+        this._connectInternal();
+    }
+
+    /**
+     * Wet/dry balance, 0 (dry) to 1 (wet), as an equal-power crossfade
+     */
+    setMix(mix) {
+        this.mix = Math.max(0, Math.min(1, mix));
+        this._applyLevels();
+    }
+
+    /**
+     * Output gain in dB
+     */
+    setGain(db) {
+        this.gain = db;
+        this._applyLevels();
+    }
+
+    /**
+     * Scale the IR to a consistent loudness. Turn off for room-correction filters that are already calibrated.
+     */
+    setNormalize(enabled) {
+        this.normalize = enabled;
+        this._applyLevels();
+    }
+
+    _createConvolver(buffer) {
+        const convolver = this.ctx.createConvolver();
+        // Normalization is applied on the wet gain instead, computed across all channels
+        convolver.normalize = false;
+        convolver.buffer = buffer;
+        return convolver;
+    }
+
+    _applyLevels() {
+        const now = this.ctx.currentTime;
+        const angle = (this.mix * Math.PI) / 2;
+        const wet = this.buffer ? Math.sin(angle) * (this.normalize ? this._normalizeScale : 1) : 0;
+        const dry = this.buffer ? Math.cos(angle) : 1;
+        this.dryNode.gain.setTargetAtTime(dry, now, 0.01);
+        this.wetNode.gain.setTargetAtTime(wet, now, 0.01);
+        this.outputNode.gain.setTargetAtTime(Math.pow(10, this.gain / 20), now, 0.01);
+    }
+
+    _connectInternal() {
+        this._disconnectAll();
+
+        this.inputNode.connect(this.dryNode);
+        this.dryNode.connect(this.outputNode);
+        if (!this.buffer) return;
+
+        if (this.layout === 'true-stereo') {
+            this.inputNode.connect(this._splitter);
+            TRUE_STEREO_ROUTING.forEach(([from, to], i) => {
+                this._splitter.connect(this._convolvers[i], from);
+                this._convolvers[i].connect(this._merger, 0, to);
+            });
+            this._merger.connect(this.wetNode);
+        } else {
+            this.inputNode.connect(this._convolvers[0]);
+            this._convolvers[0].connect(this.wetNode);
+        }
+        this.wetNode.connect(this.outputNode);
+    }
+
+    _disconnectAll() {
+        const safeDisconnect = (node) => {
+            try {
+                node?.disconnect();
+            } catch {
+                // node may already be disconnected
+            }
+        };
+        safeDisconnect(this.inputNode);
+        safeDisconnect(this.dryNode);
+        safeDisconnect(this.wetNode);
+        safeDisconnect(this._splitter);
+        safeDisconnect(this._merger);
+        this._convolvers.forEach(safeDisconnect);
+    }
+
+    _destroyWetPath() {
+        this._disconnectAll();
+        this._convolvers = [];
+        this._splitter = null;
+        this._merger = null;
+        this._normalizeScale = 1;
+    }
+
+    /**
+     * Destroy all nodes and clean up.
+     */
+    destroy() {
+        this._destroyWetPath();
+        this.buffer = null;
+        this.layout = null;
+    }
+}
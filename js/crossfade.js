@@ -0,0 +1,370 @@
+// js/crossfade.js
+// Crossfade engine - overlaps the tail of the current track with the head of the next one.
+// The incoming track plays on a secondary <audio> element routed through the shared
+// AudioContextManager graph; once the outgoing track ends, the main element takes over
+// at the same position and the secondary element is released. Tracks split from a CUE
+// sheet fade out before their cue end and fade in from their cue start.
+// Consecutive tracks of an album that the transition policy keeps gapless use the same handoff:
+// the next track is loaded on the secondary element ahead of time and started at full gain the
+// moment the current one ends. Other gapless transitions are left to the player.
+
+import { contentBlockingSettings, gaplessPlaybackSettings } from './storage.js';
+import { audioContextManager } from './audio-context.js';
+import { getProxyUrl } from './proxy-utils.js';
+import { REPEAT_MODE } from './utils.js';
+import { TRANSITION_MODE } from './transition-policy.js';
+
+// Arm the start timer this many seconds before the fade is due (timeupdate fires ~4x/s)
+const ARM_WINDOW = 1.5;
+// Fades shorter than this are not worth the handoff
+const MIN_FADE = 0.5;
+// Load the next track of a gapless transition this many seconds before the current one ends
+const GAPLESS_PRELOAD = 10;
+// Position drift between the two elements tolerated at handoff before re-seeking
+const HANDOFF_TOLERANCE = 0.08;
+const HANDOFF_RAMP = 0.03;
+const HANDOFF_TIMEOUT_MS = 8000;
+const CURVE_STEPS = 256;
+
+/**
+ * Build an equal-power gain curve (constant perceived loudness across the overlap)
+ * @param {'in'|'out'} direction
+ * @param {number} steps
+ * @returns {Float32Array}
+ */
+export function equalPowerCurve(direction, steps = CURVE_STEPS) {
+    const curve = new Float32Array(steps);
+    for (let i = 0; i < steps; i++) {
+        const angle = (i / (steps - 1)) * (Math.PI / 2);
+        curve[i] = direction === 'in' ? Math.sin(angle) : Math.cos(angle);
+    }
+    return curve;
+}
+
+export class CrossfadeEngine {
+    constructor(player) {
+        this.player = player;
+        this.element = null;
+        this.state = null;
+        this._startTimer = null;
+
+        const audio = player.audio;
+        if (audio) {
+            audio.addEventListener('timeupdate', () => this.check());
+            audio.addEventListener('pause', () => {
+                // Tracks split from a CUE sheet end by being paused at their cue end
+                if (player.hasReachedTrackEnd(audio)) this._onTrackEnded();
+                else if (!audio.ended && this._isPending()) this.cancel();
+            });
+            audio.addEventListener('seeking', () => {
+                if (this._isPending()) this.cancel();
+            });
+            audio.addEventListener('ended', () => this._onTrackEnded());
+        }
+    }
+
+    _onTrackEnded() {
+        const state = this.state;
+        if (!state) return;
+        if (state.phase === 'preloaded') this._startGapless(state);
+        if (state.phase !== 'fading' && state.phase !== 'playing') {
+            this.cancel();
+            return;
+        }
+        // The player normally claims the fade right away; release it if nothing does
+        clearTimeout(state.endedTimer);
+        state.endedTimer = setTimeout(() => {
+            if (this.state === state && (state.phase === 'fading' || state.phase === 'playing')) this.cancel();
+        }, HANDOFF_TIMEOUT_MS);
+    }
+
+    /** Seconds left of the current track, up to its cue end for tracks split from a CUE sheet */
+    _getRemaining(el) {
+        const { end } = this.player.getTrackBounds(el);
+        return (end - el.currentTime) / (el.playbackRate || 1);
+    }
+
+    isActive() {
+        return this.state !== null;
+    }
+
+    _isPending() {
+        return this.state !== null && this.state.phase !== 'handoff';
+    }
+
+    _ensureElement() {
+        if (!this.element) {
+            const el = document.createElement('audio');
+            el.id = 'crossfade-player';
+            el.crossOrigin = 'anonymous';
+            el.preload = 'auto';
+            el.style.display = 'none';
+            document.body.appendChild(el);
+            this.element = el;
+        }
+        return this.element;
+    }
+
+    /**
+     * Resolve a directly playable URL for the next track, or null if it can't be overlapped
+     * (DASH/HLS/DRM streams are owned by Shaka on the main element). `start` is where the
+     * track begins within the file.
+     */
+    _resolveSource(track) {
+        if (!track || track.type === 'video' || track.isUnavailable) return null;
+        if (contentBlockingSettings.shouldHideTrack(track)) return null;
+
+        const isPodcast = track.isPodcast || (track.id && String(track.id).startsWith('podcast_'));
+        if (isPodcast) return null;
+
+        if (track.isLocal) {
+            if (!track.file) return null;
+            const objectUrl = URL.createObjectURL(track.file);
+            return { url: objectUrl, objectUrl, rgInfo: null, start: track.cueStart || 0 };
+        }
+
+        const isTracker = track.isTracker || (track.id && String(track.id).startsWith('tracker-'));
+        if (isTracker || track.audioUrl) {
+            const url = track.audioUrl && !track.audioUrl.startsWith('blob:') ? track.audioUrl : track.remoteUrl;
+            return url ? { url, objectUrl: null, rgInfo: null, start: 0 } : null;
+        }
+
+        const cached = this.player.preloadCache.get(track.id);
+        if (!cached?.url) return null;
+
+        const streamUrl = this.player.getNativeAmazonDecryptionUrl(cached, cached.url) || cached.url;
+        const needsShaka =
+            cached.playbackType?.includes('cenc') ||
+            streamUrl.includes('.mpd') ||
+            streamUrl.includes('.m3u8') ||
+            (streamUrl.startsWith('blob:') && cached.playbackType !== 'direct');
+        if (needsShaka || this.player.isNativeAmazonHlsDecryptionUrl(streamUrl)) return null;
+
+        return {
+            url: getProxyUrl(streamUrl),
+            objectUrl: null,
+            rgInfo: cached.rgInfo || cached.rgInfoFallback || null,
+            start: 0,
+        };
+    }
+
+    /**
+     * Called on every timeupdate of the main element - arms the fade, or preloads the next track of an album run
+     * kept gapless, when the track is close to its end
+     */
+    check() {
+        if (this.state || this._startTimer) return;
+
+        const player = this.player;
+        const el = player.audio;
+        if (player.activeElement !== el || el.paused || el.seeking) return;
+        if (player.repeatMode === REPEAT_MODE.ONE) return;
+        if (!el.duration || !isFinite(el.duration)) return;
+
+        const next = player.getNextTrack();
+        if (!next || next === player.currentTrack) return;
+
+        const transition = player.transitionPolicy.decide(player.currentTrack, next);
+        const remaining = this._getRemaining(el);
+        if (transition.mode === TRANSITION_MODE.GAPLESS) {
+            if (transition.reason !== 'album' || !gaplessPlaybackSettings.isEnabled()) return;
+            if (remaining > GAPLESS_PRELOAD) return;
+            if (!audioContextManager.isReady()) return;
+            const source = this._resolveSource(next);
+            if (source) this._preload({ phase: 'armed', track: next, duration: 0, ...source });
+            return;
+        }
+
+        const duration = transition.crossfadeDuration;
+        if (remaining > duration + ARM_WINDOW || remaining < MIN_FADE) return;
+
+        const source = this._resolveSource(next);
+        if (!source) return;
+
+        const state = { phase: 'armed', track: next, duration, ...source };
+        this.state = state;
+        this._startTimer = setTimeout(
+            () => {
+                this._startTimer = null;
+                void this._start(state);
+            },
+            Math.max(0, remaining - duration) * 1000
+        );
+    }
+
+    async _start(state) {
+        if (this.state !== state) return;
+
+        const primary = this.player.audio;
+        if (primary.paused || this.player.activeElement !== primary || !audioContextManager.isReady()) {
+            this.cancel();
+            return;
+        }
+
+        const el = this._ensureElement();
+        if (!audioContextManager.attachCrossfadeSource(el)) {
+            this.cancel();
+            return;
+        }
+
+        el.src = state.url;
+        // Before metadata is loaded this sets where playback will start
+        if (state.start > 0) el.currentTime = state.start;
+        el.playbackRate = primary.playbackRate;
+        el.preservesPitch = primary.preservesPitch;
+        this.applyVolume();
+        state.phase = 'starting';
+
+        try {
+            await el.play();
+        } catch (e) {
+            if (this.state === state) {
+                console.warn('[Crossfade] Could not start next track:', e);
+                this.cancel();
+            }
+            return;
+        }
+        if (this.state !== state) return;
+
+        const remaining = this._getRemaining(primary);
+        const fadeDuration = Math.max(MIN_FADE, Math.min(state.duration, remaining));
+        audioContextManager.fadeSource(primary, equalPowerCurve('out'), fadeDuration);
+        audioContextManager.fadeSource(el, equalPowerCurve('in'), fadeDuration);
+        state.phase = 'fading';
+    }
+
+    /**
+     * Load the next track of a gapless transition, paused at its start, so it can begin without a gap
+     */
+    _preload(state) {
+        this.state = state;
+        if (!audioContextManager.isReady()) {
+            this.cancel();
+            return;
+        }
+
+        const el = this._ensureElement();
+        if (!audioContextManager.attachCrossfadeSource(el)) {
+            this.cancel();
+            return;
+        }
+
+        el.src = state.url;
+        if (state.start > 0) el.currentTime = state.start;
+        el.playbackRate = this.player.audio.playbackRate;
+        el.preservesPitch = this.player.audio.preservesPitch;
+        this.applyVolume();
+        // No fade in: the track starts at full level, exactly where the current one stops
+        audioContextManager.rampSourceGain(el, 1, 0);
+        state.phase = 'preloaded';
+    }
+
+    _startGapless(state) {
+        if (this.state !== state || state.phase !== 'preloaded') return;
+        state.phase = 'playing';
+        // The main element is about to load this track; keep it silent until the handoff
+        audioContextManager.rampSourceGain(this.player.audio, 0, 0);
+        this.element.play().catch((e) => {
+            if (this.state === state) {
+                console.warn('[Crossfade] Could not start next track:', e);
+                this.cancel();
+            }
+        });
+    }
+
+    /**
+     * Claim a running crossfade or gapless transition for the track the player is about to load.
+     * @param {object} track - Track being loaded
+     * @param {HTMLMediaElement} element - Element it will play on
+     * @returns {number|null} Position (seconds) within the track to start from, or null if there is nothing to
+     *   hand off. Like the player's startTime, it is relative to the cue start of tracks split from a CUE sheet.
+     */
+    takeHandoff(track, element) {
+        const state = this.state;
+        const matches = state && state.track.id === track?.id && element === this.player.audio;
+        // The player can get here before the ended track's events did
+        if (matches) this._startGapless(state);
+        if (!matches || (state.phase !== 'fading' && state.phase !== 'playing')) {
+            this.cancel();
+            return null;
+        }
+
+        state.phase = 'handoff';
+        clearTimeout(state.endedTimer);
+
+        const onPlaying = () => {
+            if (this.state !== state) return;
+            if (Math.abs(this.element.currentTime - element.currentTime) > HANDOFF_TOLERANCE) {
+                element.addEventListener('seeked', () => this._finishHandoff(state, element), { once: true });
+                element.currentTime = this.element.currentTime;
+                return;
+            }
+            this._finishHandoff(state, element);
+        };
+        element.addEventListener('playing', onPlaying, { once: true });
+
+        state.handoffTimer = setTimeout(() => {
+            element.removeEventListener('playing', onPlaying);
+            if (this.state === state) this.cancel();
+        }, HANDOFF_TIMEOUT_MS);
+
+        return Math.max(0, this.element.currentTime - state.start);
+    }
+
+    _finishHandoff(state, element) {
+        if (this.state !== state) return;
+        clearTimeout(state.handoffTimer);
+
+        audioContextManager.rampSourceGain(element, 1, HANDOFF_RAMP);
+        audioContextManager.rampSourceGain(this.element, 0, HANDOFF_RAMP);
+        setTimeout(
+            () => {
+                if (this.state === state) this._teardown();
+            },
+            HANDOFF_RAMP * 1000 + 50
+        );
+    }
+
+    /**
+     * Keep the incoming track's volume in line with the user volume and its own ReplayGain
+     */
+    applyVolume() {
+        if (!this.element || !this.state) return;
+        this.element.volume = this.player.computeEffectiveVolume(this.state.rgInfo);
+    }
+
+    /**
+     * Abort any pending or running crossfade and restore the main element to full gain
+     */
+    cancel() {
+        if (this._startTimer) {
+            clearTimeout(this._startTimer);
+            this._startTimer = null;
+        }
+        if (!this.state) return;
+
+        if (this.state.phase !== 'armed') {
+            audioContextManager.rampSourceGain(this.player.audio, 1, HANDOFF_RAMP);
+        }
+        this._teardown();
+    }
+
+    _teardown() {
+        const state = this.state;
+        this.state = null;
+        if (!state) return;
+
+        clearTimeout(state.endedTimer);
+        clearTimeout(state.handoffTimer);
+
+        if (this.element) {
+            this.element.pause();
+            this.element.removeAttribute('src');
+            this.element.load();
+        }
+        if (state.objectUrl) {
+            URL.revokeObjectURL(state.objectUrl);
+        }
+        audioContextManager.detachCrossfadeSource();
+    }
+}
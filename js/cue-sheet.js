@@ -0,0 +1,158 @@
+// js/cue-sheet.js
+// Reads CUE sheets that describe a whole album stored as one audio file (an "image"), so the local
+// library can list and play its tracks separately. Times in a CUE sheet are mm:ss:ff with 75 frames
+// per second; everything returned here is in seconds.
+
+const CUE_FRAMES_PER_SECOND = 75;
+
+export function isCueSheetFile(name) {
+    return name.toLowerCase().endsWith('.cue');
+}
+
+function unquote(value) {
+    const trimmed = value.trim();
+    return trimmed.startsWith('"') && trimmed.endsWith('"') && trimmed.length >= 2 ? trimmed.slice(1, -1) : trimmed;
+}
+
+function getBaseName(name) {
+    return name
+        .split(/[\\/]/)
+        .pop()
+        .replace(/\.[^.]+$/, '')
+        .toLowerCase();
+}
+
+/** Parse a CUE timestamp (mm:ss:ff) into seconds */
+export function parseCueTime(value) {
+    const match = /^(\d+):(\d{1,2}):(\d{1,2})$/.exec(String(value || '').trim());
+    if (!match) return null;
+    const [, minutes, seconds, frames] = match;
+    return parseInt(minutes, 10) * 60 + parseInt(seconds, 10) + parseInt(frames, 10) / CUE_FRAMES_PER_SECOND;
+}
+
+/**
+ * Decode a CUE sheet. Most are UTF-8, but older rips are often saved in the Windows code page.
+ * @param {ArrayBuffer} buffer
+ */
+export function decodeCueSheet(buffer) {
+    try {
+        return new TextDecoder('utf-8', { fatal: true }).decode(buffer);
+    } catch {
+        return new TextDecoder('windows-1252').decode(buffer);
+    }
+}
+
+/**
+ * @typedef {object} CueTrack
+ * @property {number} number
+ * @property {string|null} title
+ * @property {string|null} performer
+ * @property {string|null} isrc
+ * @property {number|null} start - INDEX 01 in seconds
+ */
+
+/**
+ * @typedef {object} CueSheet
+ * @property {string|null} title
+ * @property {string|null} performer
+ * @property {string|null} date
+ * @property {string|null} genre
+ * @property {{name: string, type: string, tracks: CueTrack[]}[]} files
+ */
+
+/**
+ * Parse the text of a CUE sheet
+ * @param {string} text
+ * @returns {CueSheet}
+ */
+export function parseCueSheet(text) {
+    const cue = { title: null, performer: null, date: null, genre: null, files: [] };
+    let file = null;
+    let track = null;
+
+    for (const rawLine of text.split(/\r?\n/)) {
+        const match = /^\s*(\S+)\s*(.*?)\s*$/.exec(rawLine);
+        if (!match) continue;
+        const [, command, rest] = match;
+
+        switch (command.toUpperCase()) {
+            case 'FILE': {
+                const fileMatch = /^(".*"|\S+)\s*(\S*)$/.exec(rest);
+                file = { name: unquote(fileMatch ? fileMatch[1] : rest), type: fileMatch?.[2] || '', tracks: [] };
+                cue.files.push(file);
+                track = null;
+                break;
+            }
+            case 'TRACK': {
+                if (!file) break;
+                track = { number: parseInt(rest, 10), title: null, performer: null, isrc: null, start: null };
+                file.tracks.push(track);
+                break;
+            }
+            case 'TITLE':
+                (track || cue).title = unquote(rest);
+                break;
+            case 'PERFORMER':
+                (track || cue).performer = unquote(rest);
+                break;
+            case 'ISRC':
+                if (track) track.isrc = unquote(rest);
+                break;
+            case 'INDEX': {
+                const [number, time] = rest.split(/\s+/);
+                if (track && parseInt(number, 10) === 1) track.start = parseCueTime(time);
+                break;
+            }
+            case 'REM': {
+                const remMatch = /^(\S+)\s*(.*)$/.exec(rest);
+                const key = remMatch?.[1].toUpperCase();
+                if (key === 'DATE') cue.date = unquote(remMatch[2]);
+                if (key === 'GENRE') cue.genre = unquote(remMatch[2]);
+                break;
+            }
+        }
+    }
+
+    return cue;
+}
+
+/**
+ * The audio file a CUE sheet splits, when it describes a single-file image. Sheets whose FILE entry
+ * was renamed after ripping (e.g. "Album.wav" re-encoded to "Album.flac") are matched by base name,
+ * and as a last resort the audio file named like the sheet itself is used.
+ * @param {CueSheet} cue
+ * @param {string} cuePath - Path of the sheet relative to the root folder
+ * @param {Set<string>} audioPaths - Audio files in the root folder
+ * @returns {string|null}
+ */
+export function findCueImagePath(cue, cuePath, audioPaths) {
+    if (cue.files.length !== 1) return null;
+
+    const dir = cuePath.includes('/') ? cuePath.slice(0, cuePath.lastIndexOf('/') + 1) : '';
+    const siblings = [...audioPaths].filter((path) => path.startsWith(dir) && !path.slice(dir.length).includes('/'));
+    const fileName = cue.files[0].name.split(/[\\/]/).pop().toLowerCase();
+
+    return (
+        siblings.find((path) => path.slice(dir.length).toLowerCase() === fileName) ||
+        siblings.find((path) => getBaseName(path) === getBaseName(fileName)) ||
+        siblings.find((path) => getBaseName(path) === getBaseName(cuePath)) ||
+        null
+    );
+}
+
+/**
+ * Tracks of a single-file image with their start and end offsets
+ * @param {CueSheet} cue
+ * @param {number} [duration] - Length of the audio file, used as the end of the last track
+ * @returns {(CueTrack & {end: number|null})[]}
+ */
+export function getCueTracks(cue, duration = 0) {
+    const tracks = (cue.files[0]?.tracks || [])
+        .filter((track) => track.start !== null && Number.isFinite(track.number))
+        .sort((a, b) => a.start - b.start);
+
+    return tracks.map((track, index) => ({
+        ...track,
+        end: index + 1 < tracks.length ? tracks[index + 1].start : duration || null,
+    }));
+}
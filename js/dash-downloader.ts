@@ -1,11 +1,13 @@
-import { AbortError } from './errorTypes';
 import { SegmentedDownloadProgress } from './progressEvents';
 import { getProxyUrl } from './proxy-utils';
+import { downloadSegments } from './resumable-download.js';
 
 export interface DashDownloadOptions {
     onProgress?: MonochromeProgressListener<SegmentedDownloadProgress>;
     signal?: AbortSignal;
     calculateDashBytes?: boolean;
+    /** Stable key under which finished segments are kept, so a retry resumes instead of starting over */
+    checkpointKey?: string | null;
 }
 
 interface DashSegment {
@@ -49,7 +51,7 @@ export class DashDownloader {
     }
 
     async downloadDashStream(manifestBlobUrl: string, options: DashDownloadOptions = {}): Promise<Blob> {
-        const { onProgress, signal, calculateDashBytes = true } = options;
+        const { onProgress, signal, calculateDashBytes = true, checkpointKey = null } = options;
 
         // 1. Fetch and Parse Manifest
         const response = await fetch(manifestBlobUrl);
@@ -64,45 +66,17 @@ export class DashDownloader {
         const urls = this.generateSegmentUrls(manifest);
         const mimeType = manifest.mimeType || 'audio/mp4';
 
-        // 3. Download Segments
-        const chunks: ArrayBuffer[] = [];
-        let downloadedBytes = 0;
-
-        const totalSegments = urls.length;
+        // 3. Download Segments (checkpointed, so an interrupted download resumes)
         const totalSize = calculateDashBytes ? await this.getTotalSize(urls, signal) : undefined;
 
-        for (let i = 0; i < urls.length; i++) {
-            if (signal?.aborted) throw new AbortError();
-
-            onProgress?.(new SegmentedDownloadProgress(downloadedBytes, totalSize ?? undefined, i, totalSegments));
-
-            const url = getProxyUrl(urls[i]);
-            const segmentResponse = await fetch(url, { signal });
-
-            if (!segmentResponse.ok) {
-                console.warn(`Failed to fetch segment ${i}, retrying...`);
-                await new Promise((r) => setTimeout(r, 1000));
-
-                const retryResponse = await fetch(url, { signal });
-
-                if (!retryResponse.ok) {
-                    throw new Error(`Failed to fetch segment ${i}: ${retryResponse.status}`);
-                }
-
-                const chunk = await retryResponse.arrayBuffer();
-                chunks.push(chunk);
-                downloadedBytes += chunk.byteLength;
-            } else {
-                const chunk = await segmentResponse.arrayBuffer();
-                chunks.push(chunk);
-                downloadedBytes += chunk.byteLength;
-            }
-
-            onProgress?.(new SegmentedDownloadProgress(downloadedBytes, totalSize ?? undefined, i + 1, totalSegments));
-        }
-
-        // 4. Concatenate
-        return new Blob(chunks, { type: mimeType });
+        return downloadSegments(urls.map((url) => getProxyUrl(url)), {
+            checkpointKey,
+            fingerprint: `dash:${manifest.repId ?? ''}:${mimeType}`,
+            signal,
+            onProgress,
+            totalBytes: totalSize ?? undefined,
+            mimeType,
+        });
     }
 
     parseManifest(manifestText: string): DashManifest {
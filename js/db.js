@@ -1,8 +1,26 @@
+import { haveSameTracks } from './playlist-history.js';
+import {
+    compareOps,
+    createClock,
+    diffToOps,
+    formatStamp,
+    libraryTarget,
+    playlistTarget,
+    tickClock,
+} from './sync-oplog.js';
+
+// Automatic snapshots kept per playlist; named snapshots are never pruned
+const MAX_PLAYLIST_SNAPSHOTS = 50;
+// Edits kept while they can't be pushed, e.g. while offline; past this the whole library is pushed instead
+const MAX_QUEUED_SYNC_OPS = 5000;
+
 export class MusicDatabase {
     constructor() {
         this.dbName = 'MonochromeDB';
-        this.version = 11;
+        this.version = 20;
         this.db = null;
+        // Set by syncManager; edits are only queued while there is a cloud copy to push them to
+        this.isSyncActive = () => false;
     }
 
     async open() {
@@ -73,6 +91,59 @@ export class MusicDatabase {
                     const store = db.createObjectStore('pinned_items', { keyPath: 'id' });
                     store.createIndex('pinnedAt', 'pinnedAt', { unique: false });
                 }
+                if (!db.objectStoreNames.contains('loudness_analysis')) {
+                    const store = db.createObjectStore('loudness_analysis', { keyPath: 'id' });
+                    store.createIndex('analyzedAt', 'analyzedAt', { unique: false });
+                }
+                if (!db.objectStoreNames.contains('impulse_responses')) {
+                    const store = db.createObjectStore('impulse_responses', { keyPath: 'id' });
+                    store.createIndex('addedAt', 'addedAt', { unique: false });
+                }
+                if (!db.objectStoreNames.contains('offline_tracks')) {
+                    const store = db.createObjectStore('offline_tracks', { keyPath: 'key' });
+                    store.createIndex('trackId', 'trackId', { unique: false });
+                    store.createIndex('lastUsedAt', 'lastUsedAt', { unique: false });
+                }
+                if (!db.objectStoreNames.contains('offline_blobs')) {
+                    db.createObjectStore('offline_blobs');
+                }
+                if (!db.objectStoreNames.contains('download_jobs')) {
+                    const store = db.createObjectStore('download_jobs', { keyPath: 'id' });
+                    store.createIndex('createdAt', 'createdAt', { unique: false });
+                }
+                if (!db.objectStoreNames.contains('download_items')) {
+                    const store = db.createObjectStore('download_items', { keyPath: 'id' });
+                    store.createIndex('jobId', 'jobId', { unique: false });
+                }
+                if (!db.objectStoreNames.contains('download_blobs')) {
+                    db.createObjectStore('download_blobs');
+                }
+                if (!db.objectStoreNames.contains('download_checkpoints')) {
+                    const store = db.createObjectStore('download_checkpoints', { keyPath: 'key' });
+                    store.createIndex('updatedAt', 'updatedAt', { unique: false });
+                }
+                if (!db.objectStoreNames.contains('download_chunks')) {
+                    db.createObjectStore('download_chunks');
+                }
+                if (!db.objectStoreNames.contains('local_library')) {
+                    const store = db.createObjectStore('local_library', { keyPath: 'key' });
+                    store.createIndex('rootId', 'rootId', { unique: false });
+                }
+                if (!db.objectStoreNames.contains('local_covers')) {
+                    db.createObjectStore('local_covers');
+                }
+                if (!db.objectStoreNames.contains('smart_playlists')) {
+                    const store = db.createObjectStore('smart_playlists', { keyPath: 'id' });
+                    store.createIndex('createdAt', 'createdAt', { unique: false });
+                }
+                if (!db.objectStoreNames.contains('playlist_snapshots')) {
+                    const store = db.createObjectStore('playlist_snapshots', { keyPath: 'id' });
+                    store.createIndex('playlistId', 'playlistId', { unique: false });
+                }
+                if (!db.objectStoreNames.contains('sync_queue')) {
+                    const store = db.createObjectStore('sync_queue', { keyPath: 'id' });
+                    store.createIndex('target', 'target', { unique: false });
+                }
             };
         });
     }
@@ -188,12 +259,14 @@ export class MusicDatabase {
 
         if (exists) {
             await this.performTransaction(storeName, 'readwrite', (store) => store.delete(key));
+            await this._queueLibraryOp(type, key, null);
             window.dispatchEvent(new CustomEvent('favorites-changed'));
             return false; // Removed
         } else {
             const minified = this._minifyItem(type, item);
             const entry = { ...minified, addedAt: Date.now() };
             await this.performTransaction(storeName, 'readwrite', (store) => store.put(entry));
+            await this._queueLibraryOp(type, key, entry);
             window.dispatchEvent(new CustomEvent('favorites-changed'));
             return true; // Added
         }
@@ -233,6 +306,11 @@ export class MusicDatabase {
         });
     }
 
+    /** Copy of a catalog item with just the fields the library keeps */
+    minifyItem(type, item) {
+        return this._minifyItem(type, item);
+    }
+
     _minifyItem(type, item) {
         if (!item) return item;
         const normalizedType = (type || '').toLowerCase();
@@ -439,6 +517,7 @@ export class MusicDatabase {
 
         const userPlaylists = await this.getPlaylists(true);
         const userFolders = await this.getFolders();
+        const smartPlaylists = await this.getSmartPlaylists();
         const data = {
             favorites_tracks: tracks.map((t) => this._minifyItem('track', t)),
             favorites_albums: albums.map((a) => this._minifyItem('album', a)),
@@ -448,6 +527,7 @@ export class MusicDatabase {
             history_tracks: history.map((t) => this._minifyItem('track', t)),
             user_playlists: userPlaylists,
             user_folders: userFolders,
+            smart_playlists: smartPlaylists,
         };
         return data;
     }
@@ -466,6 +546,7 @@ export class MusicDatabase {
                 data.history_tracks,
                 data.user_playlists,
                 data.user_folders,
+                data.smart_playlists,
             ].every((arr) => !arr || (Array.isArray(arr) ? arr.length === 0 : Object.keys(arr).length === 0));
 
             if (allEmpty) {
@@ -563,8 +644,12 @@ export class MusicDatabase {
             history: data.history_tracks?.length || 0,
             userPlaylists: data.user_playlists?.length || 0,
             user_folders: data.user_folders?.length || 0,
+            smart_playlists: data.smart_playlists?.length || 0,
         });
 
+        // Playlists replaced by the import keep their previous tracks in their history
+        const previousPlaylists = data.user_playlists ? await this.getPlaylists(true) : [];
+
         const results = await Promise.all([
             importStore('favorites_tracks', data.favorites_tracks),
             importStore('favorites_albums', data.favorites_albums),
@@ -574,9 +659,23 @@ export class MusicDatabase {
             importStore('history_tracks', data.history_tracks),
             data.user_playlists ? importStore('user_playlists', data.user_playlists) : Promise.resolve(false),
             data.user_folders ? importStore('user_folders', data.user_folders) : Promise.resolve(false),
+            data.smart_playlists ? importStore('smart_playlists', data.smart_playlists) : Promise.resolve(false),
         ]);
 
         console.log('Import results:', results);
+
+        if (previousPlaylists.length > 0) {
+            const imported = Array.isArray(data.user_playlists)
+                ? data.user_playlists
+                : Object.values(data.user_playlists);
+            const previousById = new Map(previousPlaylists.map((playlist) => [String(playlist.id), playlist]));
+            for (const playlist of imported) {
+                const previous = previousById.get(String(playlist.id));
+                if (!previous || haveSameTracks(previous.tracks || [], playlist.tracks || [])) continue;
+                await this._recordPlaylistSnapshot(playlist, 'sync', previous.tracks || []);
+            }
+        }
+
         return results.some((r) => r);
     }
 
@@ -624,6 +723,8 @@ export class MusicDatabase {
         };
         this._updatePlaylistMetadata(playlist);
         await this.performTransaction('user_playlists', 'readwrite', (store) => store.put(playlist));
+        await this._recordPlaylistSnapshot(playlist, 'create');
+        await this._queuePlaylistOps(playlist.id, [], playlist.tracks);
 
         // TRIGGER SYNC
         this._dispatchPlaylistSync('create', playlist);
@@ -639,10 +740,13 @@ export class MusicDatabase {
         const trackWithDate = { ...track, addedAt: Date.now() };
         const minifiedTrack = this._minifyItem(track.type || 'track', trackWithDate);
         if (playlist.tracks.some((t) => t.id === track.id)) return;
+        const previousTracks = [...playlist.tracks];
         playlist.tracks.push(minifiedTrack);
         playlist.updatedAt = Date.now();
         this._updatePlaylistMetadata(playlist);
         await this.performTransaction('user_playlists', 'readwrite', (store) => store.put(playlist));
+        await this._recordPlaylistSnapshot(playlist, 'add', previousTracks);
+        await this._queuePlaylistOps(playlist.id, previousTracks, playlist.tracks);
 
         this._dispatchPlaylistSync('update', playlist);
         window.dispatchEvent(new CustomEvent('playlist-tracks-changed'));
@@ -654,6 +758,7 @@ export class MusicDatabase {
         const playlist = await this.performTransaction('user_playlists', 'readonly', (store) => store.get(playlistId));
         if (!playlist) throw new Error('Playlist not found');
         playlist.tracks = playlist.tracks || [];
+        const previousTracks = [...playlist.tracks];
 
         let addedCount = 0;
         for (const track of tracks) {
@@ -668,6 +773,8 @@ export class MusicDatabase {
             playlist.updatedAt = Date.now();
             this._updatePlaylistMetadata(playlist);
             await this.performTransaction('user_playlists', 'readwrite', (store) => store.put(playlist));
+            await this._recordPlaylistSnapshot(playlist, 'add', previousTracks);
+            await this._queuePlaylistOps(playlist.id, previousTracks, playlist.tracks);
             this._dispatchPlaylistSync('update', playlist);
             window.dispatchEvent(new CustomEvent('playlist-tracks-changed'));
         }
@@ -678,8 +785,8 @@ export class MusicDatabase {
     async removeTrackFromPlaylist(playlistId, trackId, trackType = null) {
         const playlist = await this.performTransaction('user_playlists', 'readonly', (store) => store.get(playlistId));
         if (!playlist) throw new Error('Playlist not found');
-        playlist.tracks = playlist.tracks || [];
-        playlist.tracks = playlist.tracks.filter((t) => {
+        const previousTracks = playlist.tracks || [];
+        playlist.tracks = previousTracks.filter((t) => {
             if (trackType) {
                 return !(t.id == trackId && (t.type || 'track') === trackType);
             }
@@ -688,6 +795,8 @@ export class MusicDatabase {
         playlist.updatedAt = Date.now();
         this._updatePlaylistMetadata(playlist);
         await this.performTransaction('user_playlists', 'readwrite', (store) => store.put(playlist));
+        await this._recordPlaylistSnapshot(playlist, 'remove', previousTracks);
+        await this._queuePlaylistOps(playlist.id, previousTracks, playlist.tracks);
 
         this._dispatchPlaylistSync('update', playlist);
         window.dispatchEvent(new CustomEvent('playlist-tracks-changed'));
@@ -697,6 +806,8 @@ export class MusicDatabase {
 
     async deletePlaylist(playlistId) {
         await this.performTransaction('user_playlists', 'readwrite', (store) => store.delete(playlistId));
+        await this.deletePlaylistSnapshots(playlistId);
+        await this.removeQueuedSyncOps((await this.getQueuedSyncOps(playlistTarget(playlistId))).map((op) => op.id));
 
         // TRIGGER SYNC (but for deleting)
         this._dispatchPlaylistSync('delete', { id: playlistId });
@@ -764,6 +875,45 @@ export class MusicDatabase {
         await this.performTransaction('user_folders', 'readwrite', (store) => store.delete(id));
     }
 
+    // Smart Playlists API: only the rules are stored, see smart-playlists.js
+    async createSmartPlaylist({ name, description = '', match = 'all', rules = [], sort = null, limit = null }) {
+        const playlist = {
+            id: crypto.randomUUID(),
+            name,
+            description,
+            match,
+            rules,
+            sort,
+            limit,
+            createdAt: Date.now(),
+            updatedAt: Date.now(),
+        };
+        await this.performTransaction('smart_playlists', 'readwrite', (store) => store.put(playlist));
+        window.dispatchEvent(new CustomEvent('smart-playlists-changed'));
+        return playlist;
+    }
+
+    async updateSmartPlaylist(playlist) {
+        playlist.updatedAt = Date.now();
+        await this.performTransaction('smart_playlists', 'readwrite', (store) => store.put(playlist));
+        window.dispatchEvent(new CustomEvent('smart-playlists-changed'));
+        return playlist;
+    }
+
+    async deleteSmartPlaylist(id) {
+        await this.performTransaction('smart_playlists', 'readwrite', (store) => store.delete(id));
+        window.dispatchEvent(new CustomEvent('smart-playlists-changed'));
+    }
+
+    async getSmartPlaylist(id) {
+        return await this.performTransaction('smart_playlists', 'readonly', (store) => store.get(id));
+    }
+
+    async getSmartPlaylists() {
+        const playlists = await this.getAll('smart_playlists');
+        return playlists.sort((a, b) => a.createdAt - b.createdAt);
+    }
+
     async getPinned() {
         const storeName = 'pinned_items';
         const db = await this.open();
@@ -849,9 +999,10 @@ export class MusicDatabase {
         return playlist;
     }
 
-    async updatePlaylistTracks(playlistId, tracks) {
+    async updatePlaylistTracks(playlistId, tracks, action = 'edit') {
         const db = await this.open();
-        return new Promise((resolve, reject) => {
+        let previousTracks = [];
+        const playlist = await new Promise((resolve, reject) => {
             const transaction = db.transaction('user_playlists', 'readwrite');
             const store = transaction.objectStore('user_playlists');
 
@@ -862,6 +1013,7 @@ export class MusicDatabase {
                     reject(new Error('Playlist not found'));
                     return;
                 }
+                previousTracks = playlist.tracks || [];
                 playlist.tracks = tracks;
                 playlist.updatedAt = Date.now();
                 this._updatePlaylistMetadata(playlist);
@@ -881,6 +1033,175 @@ export class MusicDatabase {
                 reject(event.target.error);
             };
         });
+        await this._recordPlaylistSnapshot(playlist, action, previousTracks);
+        // Tracks written by cloud sync are already in the cloud
+        if (action !== 'sync') await this._queuePlaylistOps(playlist.id, previousTracks, playlist.tracks);
+        return playlist;
+    }
+
+    // Playlist history: a snapshot of the tracks is kept after every change, see playlist-history.js
+    async _recordPlaylistSnapshot(playlist, action, previousTracks = null, name = null) {
+        try {
+            const snapshots = await this.getPlaylistSnapshots(playlist.id);
+            const latest = snapshots[0];
+            // Playlists from before history existed get their previous state as the first snapshot
+            if (!latest && previousTracks) {
+                const before = (playlist.updatedAt || Date.now()) - 1;
+                await this._putPlaylistSnapshot(playlist.id, previousTracks, 'initial', null, before);
+            } else if (!name && latest && haveSameTracks(latest.tracks, playlist.tracks || [])) {
+                return null;
+            }
+
+            // Changes within the same millisecond still need a stable order
+            const createdAt = Math.max(Date.now(), (latest?.createdAt || 0) + 1);
+            const tracks = playlist.tracks || [];
+            const snapshot = await this._putPlaylistSnapshot(playlist.id, tracks, action, name, createdAt);
+
+            const automatic = snapshots.filter((s) => !s.name);
+            if (!name && automatic.length >= MAX_PLAYLIST_SNAPSHOTS) {
+                const expired = automatic.slice(MAX_PLAYLIST_SNAPSHOTS - 1).map((s) => s.id);
+                await this.performTransaction('playlist_snapshots', 'readwrite', (store) => {
+                    expired.forEach((id) => store.delete(id));
+                });
+            }
+            window.dispatchEvent(new CustomEvent('playlist-history-changed', { detail: { playlistId: playlist.id } }));
+            return snapshot;
+        } catch (error) {
+            console.warn('Failed to save playlist snapshot:', error);
+            return null;
+        }
+    }
+
+    async _putPlaylistSnapshot(playlistId, tracks, action, name = null, createdAt = Date.now()) {
+        const snapshot = {
+            id: crypto.randomUUID(),
+            playlistId,
+            createdAt,
+            action,
+            name,
+            tracks: tracks.map((t) => ({ ...t })),
+        };
+        await this.performTransaction('playlist_snapshots', 'readwrite', (store) => store.put(snapshot));
+        return snapshot;
+    }
+
+    /** Snapshots of a playlist, newest first */
+    async getPlaylistSnapshots(playlistId) {
+        const snapshots = await this.performTransaction('playlist_snapshots', 'readonly', (store) =>
+            store.index('playlistId').getAll(playlistId)
+        );
+        return snapshots.sort((a, b) => b.createdAt - a.createdAt);
+    }
+
+    async savePlaylistSnapshot(playlistId, name) {
+        const playlist = await this.getPlaylist(playlistId);
+        if (!playlist) throw new Error('Playlist not found');
+        return await this._recordPlaylistSnapshot(playlist, 'manual', null, name);
+    }
+
+    async deletePlaylistSnapshot(snapshotId) {
+        await this.performTransaction('playlist_snapshots', 'readwrite', (store) => store.delete(snapshotId));
+    }
+
+    async deletePlaylistSnapshots(playlistId) {
+        const snapshots = await this.getPlaylistSnapshots(playlistId);
+        if (!snapshots.length) return;
+        await this.performTransaction('playlist_snapshots', 'readwrite', (store) => {
+            snapshots.forEach((snapshot) => store.delete(snapshot.id));
+        });
+    }
+
+    /** Put a playlist's tracks back to a snapshot. The restore is itself recorded, so it can be undone. */
+    async restorePlaylistSnapshot(snapshotId) {
+        const snapshot = await this.performTransaction('playlist_snapshots', 'readonly', (store) =>
+            store.get(snapshotId)
+        );
+        if (!snapshot) throw new Error('Snapshot not found');
+
+        const playlist = await this.updatePlaylistTracks(
+            snapshot.playlistId,
+            snapshot.tracks.map((t) => ({ ...t })),
+            'restore'
+        );
+        this._dispatchPlaylistSync('update', playlist);
+        window.dispatchEvent(new CustomEvent('playlist-tracks-changed'));
+        return playlist;
+    }
+
+    // Sync queue: edits waiting to be replayed on the cloud copy, see sync-oplog.js
+    async _loadSyncClock() {
+        if (!this._savedSyncClock) this._savedSyncClock = this.getSetting('sync-clock');
+        const saved = await this._savedSyncClock;
+        if (!this._syncClock) this._syncClock = saved || createClock(crypto.randomUUID().slice(0, 8));
+    }
+
+    async _queueSyncOps(createOps) {
+        // Signing in or choosing a WebDAV folder merges the whole library, nothing to replay
+        if (!this.isSyncActive()) return;
+        try {
+            await this._loadSyncClock();
+            // No await until the clock is stored again, so concurrent edits never share a stamp
+            let clock = this._syncClock;
+            const ops = createOps(() => {
+                clock = tickClock(clock);
+                return formatStamp(clock);
+            });
+            this._syncClock = clock;
+            if (!ops.length) return;
+
+            await this.saveSetting('sync-clock', clock);
+            const queued = await this.performTransaction('sync_queue', 'readwrite', (store) => {
+                ops.forEach((op) => store.put(op));
+                return store.count();
+            });
+
+            // Too many edits to replay: drop them and push the whole library next time, see syncManager
+            if (queued > MAX_QUEUED_SYNC_OPS) {
+                await this.performTransaction('sync_queue', 'readwrite', (store) => store.clear());
+                await this.setSyncQueueOverflowed(true);
+            }
+        } catch (error) {
+            console.warn('Failed to queue sync operations:', error);
+        }
+    }
+
+    async _queuePlaylistOps(playlistId, previousTracks, tracks) {
+        await this._queueSyncOps((nextStamp) =>
+            diffToOps(playlistTarget(playlistId), previousTracks || [], tracks || [], nextStamp)
+        );
+    }
+
+    async _queueLibraryOp(type, key, entry) {
+        await this._queueSyncOps((nextStamp) => {
+            const stamp = nextStamp();
+            const op = entry ? { type: 'add', item: entry, after: null } : { type: 'remove' };
+            return [{ id: `${stamp}:${op.type}:${key}`, target: libraryTarget(type), key: String(key), stamp, ...op }];
+        });
+    }
+
+    /** Queued edits in the order they were made, optionally only those for one target */
+    async getQueuedSyncOps(target = null) {
+        const ops = await this.performTransaction('sync_queue', 'readonly', (store) =>
+            target ? store.index('target').getAll(target) : store.getAll()
+        );
+        return ops.sort(compareOps);
+    }
+
+    /** Whether edits were dropped from the queue, so the next push must save the whole library */
+    async hasSyncQueueOverflowed() {
+        return (await this.getSetting('sync-queue-overflowed')) === true;
+    }
+
+    async setSyncQueueOverflowed(overflowed) {
+        if (overflowed) await this.saveSetting('sync-queue-overflowed', true);
+        else await this.deleteSetting('sync-queue-overflowed');
+    }
+
+    async removeQueuedSyncOps(ids) {
+        if (!ids.length) return;
+        await this.performTransaction('sync_queue', 'readwrite', (store) => {
+            ids.forEach((id) => store.delete(id));
+        });
     }
 
     async saveSetting(key, value) {
@@ -890,6 +1211,214 @@ export class MusicDatabase {
     async getSetting(key) {
         return await this.performTransaction('settings', 'readonly', (store) => store.get(key));
     }
+
+    async deleteSetting(key) {
+        await this.performTransaction('settings', 'readwrite', (store) => store.delete(key));
+    }
+
+    // Loudness analysis cache (EBU R128 results for tracks without ReplayGain)
+    async getLoudnessAnalysis(trackId) {
+        return await this.performTransaction('loudness_analysis', 'readonly', (store) => store.get(String(trackId)));
+    }
+
+    async saveLoudnessAnalysis(trackId, analysis) {
+        const entry = { ...analysis, id: String(trackId), analyzedAt: Date.now() };
+        await this.performTransaction('loudness_analysis', 'readwrite', (store) => store.put(entry));
+        return entry;
+    }
+
+    async clearLoudnessAnalysis() {
+        await this.performTransaction('loudness_analysis', 'readwrite', (store) => store.clear());
+    }
+
+    // Impulse responses for the convolution stage (raw WAV bytes plus parsed format info)
+    async getImpulseResponses() {
+        const entries = await this.getAll('impulse_responses');
+        return entries.sort((a, b) => a.addedAt - b.addedAt);
+    }
+
+    async getImpulseResponse(id) {
+        return await this.performTransaction('impulse_responses', 'readonly', (store) => store.get(id));
+    }
+
+    async saveImpulseResponse(name, data, info = {}) {
+        const entry = {
+            ...info,
+            id: 'ir_' + Date.now() + '_' + Math.random().toString(36).slice(2, 8),
+            name,
+            data,
+            addedAt: Date.now(),
+        };
+        await this.performTransaction('impulse_responses', 'readwrite', (store) => store.put(entry));
+        return entry;
+    }
+
+    async deleteImpulseResponse(id) {
+        await this.performTransaction('impulse_responses', 'readwrite', (store) => store.delete(id));
+    }
+
+    // Offline library index (one entry per track + quality). Audio lives in OPFS or in offline_blobs.
+    async getOfflineTracks() {
+        return await this.getAll('offline_tracks');
+    }
+
+    async getOfflineTracksForTrack(trackId) {
+        return await this.performTransaction('offline_tracks', 'readonly', (store) =>
+            store.index('trackId').getAll(String(trackId))
+        );
+    }
+
+    async saveOfflineTrack(entry) {
+        await this.performTransaction('offline_tracks', 'readwrite', (store) => store.put(entry));
+        return entry;
+    }
+
+    async deleteOfflineTrack(key) {
+        await this.performTransaction('offline_tracks', 'readwrite', (store) => store.delete(key));
+    }
+
+    async getOfflineBlob(key) {
+        return await this.performTransaction('offline_blobs', 'readonly', (store) => store.get(key));
+    }
+
+    async saveOfflineBlob(key, blob) {
+        await this.performTransaction('offline_blobs', 'readwrite', (store) => store.put(blob, key));
+    }
+
+    async deleteOfflineBlob(key) {
+        await this.performTransaction('offline_blobs', 'readwrite', (store) => store.delete(key));
+    }
+
+    async clearOfflineTracks() {
+        await this.performTransaction('offline_tracks', 'readwrite', (store) => store.clear());
+        await this.performTransaction('offline_blobs', 'readwrite', (store) => store.clear());
+    }
+
+    // Download queue. Tracks for writers that need every file at once are staged in download_blobs until their
+    // job is written out; other writers get them as they finish.
+    async getDownloadJobs() {
+        const jobs = await this.getAll('download_jobs');
+        return jobs.sort((a, b) => (a.createdAt || 0) - (b.createdAt || 0));
+    }
+
+    async saveDownloadJob(job) {
+        await this.performTransaction('download_jobs', 'readwrite', (store) => store.put(job));
+        return job;
+    }
+
+    async getDownloadItems(jobId) {
+        const items = await this.performTransaction('download_items', 'readonly', (store) =>
+            store.index('jobId').getAll(jobId)
+        );
+        return items.sort((a, b) => a.order - b.order);
+    }
+
+    async saveDownloadItems(items) {
+        const db = await this.open();
+        return new Promise((resolve, reject) => {
+            const transaction = db.transaction('download_items', 'readwrite');
+            const store = transaction.objectStore('download_items');
+            items.forEach((item) => store.put(item));
+            transaction.oncomplete = () => resolve(items);
+            transaction.onerror = () => reject(transaction.error);
+        });
+    }
+
+    async getDownloadBlob(itemId) {
+        return await this.performTransaction('download_blobs', 'readonly', (store) => store.get(itemId));
+    }
+
+    async saveDownloadBlob(itemId, blob) {
+        await this.performTransaction('download_blobs', 'readwrite', (store) => store.put(blob, itemId));
+    }
+
+    async deleteDownloadBlobs(itemIds) {
+        if (!itemIds.length) return;
+        await this.performTransaction('download_blobs', 'readwrite', (store) => {
+            itemIds.forEach((id) => store.delete(id));
+        });
+    }
+
+    async deleteDownloadJob(jobId) {
+        const items = await this.getDownloadItems(jobId);
+        const db = await this.open();
+        return new Promise((resolve, reject) => {
+            const transaction = db.transaction(['download_jobs', 'download_items', 'download_blobs'], 'readwrite');
+            transaction.objectStore('download_jobs').delete(jobId);
+            const itemStore = transaction.objectStore('download_items');
+            const blobStore = transaction.objectStore('download_blobs');
+            items.forEach((item) => {
+                itemStore.delete(item.id);
+                blobStore.delete(item.id);
+            });
+            transaction.oncomplete = () => resolve();
+            transaction.onerror = () => reject(transaction.error);
+        });
+    }
+
+    // Resumable download checkpoints. Chunks are keyed `${checkpointKey}#${index}`.
+    async getDownloadCheckpoints() {
+        return await this.getAll('download_checkpoints');
+    }
+
+    async getDownloadCheckpoint(key) {
+        return await this.performTransaction('download_checkpoints', 'readonly', (store) => store.get(key));
+    }
+
+    async saveDownloadCheckpoint(checkpoint) {
+        await this.performTransaction('download_checkpoints', 'readwrite', (store) => store.put(checkpoint));
+        return checkpoint;
+    }
+
+    async getDownloadChunk(id) {
+        return await this.performTransaction('download_chunks', 'readonly', (store) => store.get(id));
+    }
+
+    async saveDownloadChunk(id, blob) {
+        await this.performTransaction('download_chunks', 'readwrite', (store) => store.put(blob, id));
+    }
+
+    async deleteDownloadCheckpoint(key) {
+        const db = await this.open();
+        return new Promise((resolve, reject) => {
+            const transaction = db.transaction(['download_checkpoints', 'download_chunks'], 'readwrite');
+            transaction.objectStore('download_checkpoints').delete(key);
+            transaction.objectStore('download_chunks').delete(IDBKeyRange.bound(`${key}#`, `${key}#\uffff`));
+            transaction.oncomplete = () => resolve();
+            transaction.onerror = () => reject(transaction.error);
+        });
+    }
+
+    // Local library index: one entry per audio file, keyed `${rootId}:${path}`. Album covers are
+    // stored once per album in local_covers.
+    async getLocalLibraryEntries(rootId = null) {
+        if (!rootId) return await this.getAll('local_library');
+        return await this.performTransaction('local_library', 'readonly', (store) =>
+            store.index('rootId').getAll(rootId)
+        );
+    }
+
+    async saveLocalLibraryEntries(entries) {
+        if (!entries.length) return;
+        await this.performTransaction('local_library', 'readwrite', (store) => {
+            entries.forEach((entry) => store.put(entry));
+        });
+    }
+
+    async deleteLocalLibraryEntries(keys) {
+        if (!keys.length) return;
+        await this.performTransaction('local_library', 'readwrite', (store) => {
+            keys.forEach((key) => store.delete(key));
+        });
+    }
+
+    async getLocalCover(albumKey) {
+        return await this.performTransaction('local_covers', 'readonly', (store) => store.get(albumKey));
+    }
+
+    async saveLocalCover(albumKey, blob) {
+        await this.performTransaction('local_covers', 'readwrite', (store) => store.put(blob, albumKey));
+    }
 }
 
 export const db = new MusicDatabase();
@@ -0,0 +1,750 @@
+// js/download-queue.js
+// Persistent download queue. Bulk downloads (albums, playlists, discographies, ...) become jobs whose
+// tracks are stored as items in MusicDatabase, so a reload or a crash picks up where it stopped
+// instead of starting over. Tracks run a few at a time, failures are retried with exponential
+// backoff, and finished audio goes straight to the folder or ZIP the user picked. Only writers
+// that need every file at once get it staged in IndexedDB until the whole job is done.
+
+import { db } from './db.js';
+import { downloadQueueSettings } from './storage.js';
+import { RATE_LIMIT_ERROR_MESSAGE } from './utils.js';
+
+const RETRY_BASE_MS = 5000;
+const RETRY_MAX_MS = 5 * 60 * 1000;
+// Rate limits clear slowly; retrying sooner only burns attempts
+const RATE_LIMIT_RETRY_MS = 60 * 1000;
+const PROGRESS_EMIT_INTERVAL_MS = 250;
+// Errors of save pickers opened without a recent click, or dismissed
+const OUTPUT_PROMPT_ERRORS = ['AbortError', 'SecurityError', 'NotAllowedError'];
+
+/**
+ * Delay before retry number `attempt` (1-based): 5 s, 10 s, 20 s, ... capped at 5 minutes
+ * @param {number} attempt
+ * @param {boolean} [rateLimited=false]
+ */
+export function getRetryDelay(attempt, rateLimited = false) {
+    const delay = Math.min(RETRY_BASE_MS * 2 ** Math.max(0, attempt - 1), RETRY_MAX_MS);
+    return rateLimited ? Math.max(delay, RATE_LIMIT_RETRY_MS) : delay;
+}
+
+export function isItemRunnable(item, now = Date.now()) {
+    if (item.status === 'queued') return true;
+    return item.status === 'retrying' && (item.nextAttemptAt || 0) <= now;
+}
+
+/**
+ * Pick the items to start next: oldest job first, tracks in order within a job
+ * @param {object[]} jobs - Jobs sorted by creation time
+ * @param {Map<string, object[]>} itemsByJob
+ * @param {number} slots - Free download slots
+ * @param {number} [now]
+ */
+export function selectRunnableItems(jobs, itemsByJob, slots, now = Date.now()) {
+    const selected = [];
+    for (const job of jobs) {
+        if (selected.length >= slots) break;
+        if (job.status !== 'queued') continue;
+        for (const item of itemsByJob.get(job.id) || []) {
+            if (selected.length >= slots) break;
+            if (isItemRunnable(item, now)) selected.push(item);
+        }
+    }
+    return selected;
+}
+
+/**
+ * When the queue next has a backed-off item or release to retry
+ * @returns {number|null} Timestamp, or null if nothing is waiting
+ */
+export function getNextRetryAt(jobs, itemsByJob) {
+    let next = null;
+    const consider = (at) => {
+        if (at && (next === null || at < next)) next = at;
+    };
+    for (const job of jobs) {
+        if (job.status !== 'queued') continue;
+        consider(job.pendingReleases?.[0]?.nextAttemptAt);
+        for (const item of itemsByJob.get(job.id) || []) {
+            if (item.status === 'retrying') consider(item.nextAttemptAt);
+        }
+    }
+    return next;
+}
+
+export function summarizeItems(items) {
+    const summary = { total: items.length, done: 0, failed: 0, active: 0, paused: 0, waiting: 0, bytes: 0 };
+    for (const item of items) {
+        if (item.status === 'done') {
+            summary.done += 1;
+            summary.bytes += item.size || 0;
+        } else if (item.status === 'failed') {
+            summary.failed += 1;
+        } else if (item.status === 'active') {
+            summary.active += 1;
+        } else if (item.status === 'paused') {
+            summary.paused += 1;
+        } else {
+            summary.waiting += 1;
+        }
+    }
+    return summary;
+}
+
+/**
+ * A job can be written out once every release is expanded and every track finished or gave up
+ */
+export function isJobComplete(job, items) {
+    if (job.pendingReleases?.length > 0) return false;
+    return items.every((item) => item.status === 'done' || item.status === 'failed');
+}
+
+/**
+ * Where a job's files go: 'files' and 'archive' for writers that take them as tracks finish (see
+ * IBulkDownloadWriter.streaming), 'staged' for writers that need them all at once
+ */
+export function getJobOutput(writer) {
+    return writer?.streaming || 'staged';
+}
+
+/**
+ * Files handed to a writer while its job runs. `push` resolves once the writer has taken the files,
+ * so a track only counts as done when it reached the output.
+ */
+export function createFileChannel() {
+    const pending = [];
+    let current = null;
+    let wake = null;
+    let closed = false;
+    let failure = null;
+
+    const notify = () => {
+        wake?.();
+        wake = null;
+    };
+
+    return {
+        push(files) {
+            if (failure) return Promise.reject(failure);
+            if (closed) return Promise.reject(new Error('Output is already closed'));
+            return new Promise((resolve, reject) => {
+                pending.push({ files, resolve, reject });
+                notify();
+            });
+        },
+
+        close() {
+            closed = true;
+            notify();
+        },
+
+        fail(error) {
+            failure = error;
+            closed = true;
+            [current, ...pending.splice(0)].forEach((entry) => entry?.reject(error));
+            current = null;
+            notify();
+        },
+
+        async *[Symbol.asyncIterator]() {
+            // Throwing makes the writer abort, so a failed or cancelled job doesn't leave a truncated archive
+            while (true) {
+                if (failure) throw failure;
+                current = pending.shift();
+                if (current) {
+                    yield* current.files;
+                    current?.resolve();
+                    current = null;
+                } else if (closed) {
+                    return;
+                } else {
+                    await new Promise((resolve) => (wake = resolve));
+                }
+            }
+        },
+    };
+}
+
+/** Whether a job's tracks go to its writer as they finish, see getJobOutput() */
+export function isStreamingJob(job) {
+    return job.output === 'files' || job.output === 'archive';
+}
+
+function createJobId() {
+    return `dl_${Date.now()}_${Math.random().toString(36).slice(2, 8)}`;
+}
+
+class DownloadQueue {
+    constructor() {
+        this.jobs = [];
+        this.items = new Map(); // jobId -> items in track order
+        this.active = new Map(); // itemId -> { controller, progress, reason }
+        this.writers = new Map(); // jobId -> output picked when the job was queued (this session only)
+        this.outputs = new Map(); // jobId -> open output of a streaming job (this session only)
+        this.processor = null;
+        this._initPromise = null;
+        this._expanding = new Set();
+        this._retryTimer = null;
+        this._emitTimer = null;
+    }
+
+    /**
+     * @param {object} processor - Does the actual work:
+     *   `expandRelease(job, release)` → `{group, seeds}` for discography jobs,
+     *   `download(job, item, {signal, onProgress})` → `{blob, extension, lrc}`,
+     *   `open(job, writer)` → `{add(item, blob), close(items), abort(error), done}` hands tracks to a
+     *   streaming writer as they finish,
+     *   `save(job, items, writer)` writes the staged files out,
+     *   `createWriter(job)` asks for an output again after a reload.
+     */
+    init(processor) {
+        this.processor = processor;
+        if (!this._initPromise) {
+            this._initPromise = this._restore();
+        }
+        return this._initPromise;
+    }
+
+    async _restore() {
+        try {
+            const jobs = await db.getDownloadJobs();
+            for (const job of jobs) {
+                const items = await db.getDownloadItems(job.id);
+                // Tracks that were mid-flight when the page went away start over, and so do the ones
+                // written into an archive that was never finished
+                const lost = job.output === 'archive' && job.status !== 'done' ? ['active', 'done'] : ['active'];
+                const interrupted = items.filter((item) => lost.includes(item.status));
+                interrupted.forEach((item) => (item.status = 'queued'));
+                if (interrupted.length) await db.saveDownloadItems(interrupted);
+                this.jobs.push(job);
+                this.items.set(job.id, items);
+                if (job.status === 'saving') job.status = 'ready';
+                // Streaming jobs wait for the user to pick where the rest goes
+                if (['queued', 'ready'].includes(job.status) && this._needsOutput(job)) job.status = 'needs-output';
+                await db.saveDownloadJob(job);
+            }
+        } catch (e) {
+            console.warn('[DownloadQueue] Could not restore queue:', e);
+        }
+
+        window.addEventListener('online', () => this._pump());
+        window.addEventListener('download-queue-settings-changed', () => this._pump());
+        this._emit();
+        for (const job of this.jobs) {
+            void this._checkJob(job);
+        }
+        this._pump();
+    }
+
+    getJobs() {
+        return this.jobs;
+    }
+
+    getJob(jobId) {
+        return this.jobs.find((job) => job.id === jobId) || null;
+    }
+
+    getItems(jobId) {
+        return this.items.get(jobId) || [];
+    }
+
+    /** Fraction (0-1) of an item currently downloading, or null */
+    getItemProgress(itemId) {
+        return this.active.get(itemId)?.progress ?? null;
+    }
+
+    /** Overall fraction of a job, counting partial progress of running tracks */
+    getJobProgress(jobId) {
+        const items = this.getItems(jobId);
+        if (!items.length) return 0;
+        let done = 0;
+        for (const item of items) {
+            if (item.status === 'done' || item.status === 'failed') done += 1;
+            else if (item.status === 'active') done += this.getItemProgress(item.id) || 0;
+        }
+        return done / items.length;
+    }
+
+    isPaused() {
+        return downloadQueueSettings.isPaused();
+    }
+
+    /**
+     * Add a job to the queue
+     * @param {object} spec - `{type, name, folderName, quality, groups, pendingReleases}`
+     * @param {object[]} seeds - `{track, groupKey, discNumber}` per track; discography jobs pass
+     *   `pendingReleases` instead and are expanded album by album as the queue works through them
+     * @param {object} [writer] - Output the user picked for this job
+     */
+    async enqueue(spec, seeds = [], writer = null) {
+        await this._initPromise;
+        const job = {
+            id: createJobId(),
+            type: spec.type,
+            name: spec.name,
+            folderName: spec.folderName,
+            quality: spec.quality,
+            groups: spec.groups || [],
+            pendingReleases: spec.pendingReleases || [],
+            failedReleases: 0,
+            output: getJobOutput(writer),
+            status: 'queued',
+            error: null,
+            createdAt: Date.now(),
+        };
+        this.jobs.push(job);
+        this.items.set(job.id, []);
+        if (writer) this.writers.set(job.id, writer);
+
+        // Opened right away, while the click that started the download still allows a save picker
+        if (isStreamingJob(job)) this._openOutput(job, writer);
+        await db.saveDownloadJob(job);
+        await this._addItems(job, seeds);
+        this._emit();
+        this._pump();
+        return job;
+    }
+
+    async _addItems(job, seeds) {
+        const items = this.items.get(job.id);
+        const added = seeds.map((seed, i) => ({
+            id: `${job.id}_${items.length + i}`,
+            jobId: job.id,
+            order: items.length + i,
+            groupKey: seed.groupKey,
+            discNumber: seed.discNumber || 1,
+            track: seed.track,
+            status: 'queued',
+            attempts: 0,
+            nextAttemptAt: 0,
+            error: null,
+            size: 0,
+            extension: null,
+            lrc: null,
+        }));
+        items.push(...added);
+        if (added.length) await db.saveDownloadItems(added);
+    }
+
+    _pump() {
+        if (!this.processor || this.isPaused()) return;
+        if (navigator.onLine === false) return;
+
+        const now = Date.now();
+        for (const job of this.jobs) {
+            const release = job.pendingReleases?.[0];
+            if (job.status !== 'queued' || !release || this._expanding.has(job.id)) continue;
+            if ((release.nextAttemptAt || 0) <= now) void this._expandNext(job);
+        }
+
+        const slots = downloadQueueSettings.getConcurrency() - this.active.size;
+        if (slots > 0) {
+            selectRunnableItems(this.jobs, this.items, slots, now).forEach((item) => void this._runItem(item));
+        }
+        this._scheduleRetry();
+    }
+
+    _scheduleRetry() {
+        clearTimeout(this._retryTimer);
+        const next = getNextRetryAt(this.jobs, this.items);
+        if (next === null) return;
+        this._retryTimer = setTimeout(() => this._pump(), Math.max(0, next - Date.now()) + 50);
+    }
+
+    async _expandNext(job) {
+        this._expanding.add(job.id);
+        const release = job.pendingReleases[0];
+        try {
+            const { group, seeds } = await this.processor.expandRelease(job, release);
+            if (!this.jobs.includes(job)) return;
+            job.groups.push(group);
+            job.pendingReleases = job.pendingReleases.slice(1);
+            await this._addItems(job, seeds);
+        } catch (error) {
+            if (!this.jobs.includes(job)) return;
+            release.attempts = (release.attempts || 0) + 1;
+            if (release.attempts > downloadQueueSettings.getMaxRetries()) {
+                console.error(`[DownloadQueue] Giving up on ${release.title}:`, error);
+                job.failedReleases += 1;
+                if (!job.error) job.error = error?.message || String(error);
+                job.pendingReleases = job.pendingReleases.slice(1);
+            } else {
+                release.nextAttemptAt = Date.now() + getRetryDelay(release.attempts);
+            }
+        } finally {
+            this._expanding.delete(job.id);
+        }
+
+        await db.saveDownloadJob(job);
+        this._emit();
+        await this._checkJob(job);
+        this._pump();
+    }
+
+    async _runItem(item) {
+        const job = this.getJob(item.jobId);
+        const entry = { controller: new AbortController(), progress: 0, reason: null };
+        this.active.set(item.id, entry);
+        item.status = 'active';
+        this._saveItem(item);
+        this._emit();
+
+        try {
+            const result = await this.processor.download(job, item, {
+                signal: entry.controller.signal,
+                onProgress: (fraction) => {
+                    entry.progress = fraction;
+                    this._emitSoon();
+                },
+            });
+            Object.assign(item, {
+                size: result.blob.size,
+                extension: result.extension,
+                lrc: result.lrc || null,
+                finishedAt: Date.now(),
+            });
+            if (await this._deliver(job, item, result.blob)) {
+                Object.assign(item, { status: 'done', error: null });
+            } else {
+                // The output went away; the track is fetched again once there is a new one
+                item.status = 'queued';
+            }
+        } catch (error) {
+            if (entry.controller.signal.aborted) {
+                // Paused or cancelled; whoever aborted decides where the item goes
+                item.status = entry.reason || 'queued';
+            } else if (navigator.onLine === false) {
+                // Dropped connection; the 'online' listener restarts it without using up a retry
+                item.status = 'queued';
+            } else {
+                item.attempts += 1;
+                item.error = error?.message || String(error);
+                if (item.attempts > downloadQueueSettings.getMaxRetries()) {
+                    item.status = 'failed';
+                } else {
+                    item.status = 'retrying';
+                    item.nextAttemptAt =
+                        Date.now() + getRetryDelay(item.attempts, item.error === RATE_LIMIT_ERROR_MESSAGE);
+                }
+            }
+        } finally {
+            this.active.delete(item.id);
+        }
+
+        if (!this.jobs.includes(job)) return;
+        this._saveItem(item);
+        this._emit();
+        await this._checkJob(job);
+        this._pump();
+    }
+
+    /**
+     * Hand a finished track to the job's output, or stage it for writers that need every file at once
+     * @returns {Promise<boolean>} Whether the track was kept
+     */
+    async _deliver(job, item, blob) {
+        if (!isStreamingJob(job)) {
+            await db.saveDownloadBlob(item.id, blob);
+            return true;
+        }
+        const output = this.outputs.get(job.id);
+        if (!output) return false;
+        try {
+            await output.add(item, blob);
+            return true;
+        } catch (error) {
+            this._outputFailed(job, output, error);
+            return false;
+        }
+    }
+
+    _needsOutput(job) {
+        return isStreamingJob(job) && !this.outputs.has(job.id);
+    }
+
+    _openOutput(job, writer) {
+        const output = this.processor.open(job, writer);
+        this.outputs.set(job.id, output);
+        output.done.catch((error) => this._outputFailed(job, output, error));
+    }
+
+    _outputFailed(job, output, error) {
+        if (this.outputs.get(job.id) !== output) return;
+        this.outputs.delete(job.id);
+        this.writers.delete(job.id);
+        output.abort(error);
+        if (!this.jobs.includes(job)) return;
+
+        const items = this.getItems(job.id);
+        items.forEach((item) => this._abortItem(item, 'queued'));
+        // Whatever went into an unfinished archive is gone with it
+        if (job.output === 'archive') {
+            const lost = items.filter((item) => item.status === 'done');
+            lost.forEach((item) => (item.status = 'queued'));
+            if (lost.length) this._saveItems(lost);
+        }
+        job.status = 'needs-output';
+        job.error = null;
+        // Save pickers need a click once the original one has expired; wait for the user
+        if (!OUTPUT_PROMPT_ERRORS.includes(error?.name)) {
+            console.error('[DownloadQueue] Writing failed:', error);
+            job.error = error?.message || String(error);
+        }
+        this._saveJob(job);
+        this._emit();
+    }
+
+    /** Back to downloading, unless the job first needs a new output */
+    _requeue(job) {
+        job.status = this._needsOutput(job) ? 'needs-output' : 'queued';
+        job.error = null;
+        this._saveJob(job);
+    }
+
+    _saveItem(item) {
+        db.saveDownloadItems([item]).catch((e) => console.warn('[DownloadQueue] Could not save item:', e));
+    }
+
+    _saveItems(items) {
+        db.saveDownloadItems(items).catch((e) => console.warn('[DownloadQueue] Could not save items:', e));
+    }
+
+    _saveJob(job) {
+        db.saveDownloadJob(job).catch((e) => console.warn('[DownloadQueue] Could not save job:', e));
+    }
+
+    async _checkJob(job) {
+        if (job.status !== 'queued') return;
+        const items = this.getItems(job.id);
+        if (!isJobComplete(job, items)) return;
+
+        if (!items.some((item) => item.status === 'done')) {
+            job.status = 'error';
+            job.error = job.error || 'All tracks failed to download';
+            this._saveJob(job);
+            this._emit();
+            return;
+        }
+
+        const writer = this.writers.get(job.id);
+        if (isStreamingJob(job)) {
+            await this._closeOutput(job);
+        } else if (writer) {
+            await this._finalize(job, writer);
+        } else {
+            job.status = 'ready';
+            this._saveJob(job);
+            this._emit();
+        }
+    }
+
+    async _finalize(job, writer) {
+        const items = this.getItems(job.id);
+        job.status = 'saving';
+        job.error = null;
+        this._saveJob(job);
+        this._emit();
+
+        try {
+            await this.processor.save(job, items, writer);
+            job.status = 'done';
+            job.finishedAt = Date.now();
+            this.writers.delete(job.id);
+            await db.deleteDownloadBlobs(items.map((item) => item.id));
+        } catch (error) {
+            // Save pickers need a click once the original one has expired; wait for the user
+            job.status = 'ready';
+            if (!OUTPUT_PROMPT_ERRORS.includes(error?.name)) {
+                console.error('[DownloadQueue] Saving failed:', error);
+                job.error = error?.message || String(error);
+            }
+        }
+        this._saveJob(job);
+        this._emit();
+    }
+
+    /** Write the playlist files of a streaming job once all its tracks are in, and finish the output */
+    async _closeOutput(job) {
+        const output = this.outputs.get(job.id);
+        if (!output) return;
+        job.status = 'saving';
+        job.error = null;
+        this._saveJob(job);
+        this._emit();
+
+        try {
+            await output.close(this.getItems(job.id));
+        } catch (error) {
+            this._outputFailed(job, output, error);
+            return;
+        }
+        this.outputs.delete(job.id);
+        this.writers.delete(job.id);
+        if (!this.jobs.includes(job)) return;
+        job.status = 'done';
+        job.finishedAt = Date.now();
+        this._saveJob(job);
+        this._emit();
+    }
+
+    /** Reset the tracks a new output doesn't have yet; only separate files survive a change of output */
+    _switchOutput(job, writer) {
+        const output = getJobOutput(writer);
+        if (job.output !== 'files' || output !== 'files') {
+            const lost = this.getItems(job.id).filter((item) => item.status === 'done');
+            lost.forEach((item) => (item.status = 'queued'));
+            if (lost.length) this._saveItems(lost);
+        }
+        job.output = output;
+        if (isStreamingJob(job)) this._openOutput(job, writer);
+    }
+
+    /**
+     * Write out a finished job, or pick a new output for a streaming job so it can carry on. Must be
+     * called from a user gesture if it needs a new save location.
+     */
+    async saveJob(jobId) {
+        const job = this.getJob(jobId);
+        if (!job || !['ready', 'needs-output'].includes(job.status)) return;
+        let writer = this.writers.get(jobId);
+        if (!writer) {
+            try {
+                writer = await this.processor.createWriter(job);
+            } catch (error) {
+                if (error?.name !== 'AbortError') throw error;
+                return;
+            }
+            if (!writer) return;
+            this.writers.set(jobId, writer);
+        }
+
+        if (job.status === 'ready' && !isStreamingJob(job)) {
+            await this._finalize(job, writer);
+            return;
+        }
+        this._switchOutput(job, writer);
+        this._requeue(job);
+        this._emit();
+        await this._checkJob(job);
+        this._pump();
+    }
+
+    _abortItem(item, reason) {
+        const entry = this.active.get(item.id);
+        if (!entry) return;
+        entry.reason = reason;
+        entry.controller.abort();
+    }
+
+    _findItem(itemId) {
+        for (const items of this.items.values()) {
+            const item = items.find((candidate) => candidate.id === itemId);
+            if (item) return item;
+        }
+        return null;
+    }
+
+    pauseItem(itemId) {
+        const item = this._findItem(itemId);
+        if (!item || !['queued', 'retrying', 'active'].includes(item.status)) return;
+        this._abortItem(item, 'paused');
+        item.status = 'paused';
+        this._saveItem(item);
+        this._emit();
+        this._pump();
+    }
+
+    /** Resume a paused track, or try a failed one again from scratch */
+    resumeItem(itemId) {
+        const item = this._findItem(itemId);
+        if (!item || !['paused', 'failed', 'retrying'].includes(item.status)) return;
+        const job = this.getJob(item.jobId);
+        if (item.status === 'failed') item.attempts = 0;
+        item.status = 'queued';
+        item.nextAttemptAt = 0;
+        if (job && (job.status === 'error' || job.status === 'ready')) this._requeue(job);
+        this._saveItem(item);
+        this._emit();
+        this._pump();
+    }
+
+    pauseJob(jobId) {
+        const job = this.getJob(jobId);
+        if (!job || job.status !== 'queued') return;
+        job.status = 'paused';
+        this.getItems(jobId).forEach((item) => this._abortItem(item, 'queued'));
+        this._saveJob(job);
+        this._emit();
+        this._pump();
+    }
+
+    resumeJob(jobId) {
+        const job = this.getJob(jobId);
+        if (!job || job.status !== 'paused') return;
+        this._requeue(job);
+        this._emit();
+        void this._checkJob(job);
+        this._pump();
+    }
+
+    /** Queue every failed track of a job again */
+    retryFailed(jobId) {
+        const job = this.getJob(jobId);
+        if (!job || job.status === 'done' || job.status === 'saving') return;
+        const failed = this.getItems(jobId).filter((item) => item.status === 'failed');
+        if (!failed.length) return;
+        failed.forEach((item) => Object.assign(item, { status: 'queued', attempts: 0, nextAttemptAt: 0 }));
+        this._saveItems(failed);
+        if (job.status !== 'paused') this._requeue(job);
+        this._emit();
+        this._pump();
+    }
+
+    pauseAll() {
+        downloadQueueSettings.setPaused(true);
+        for (const items of this.items.values()) {
+            items.forEach((item) => this._abortItem(item, 'queued'));
+        }
+        this._emit();
+    }
+
+    resumeAll() {
+        downloadQueueSettings.setPaused(false);
+        this._emit();
+        this._pump();
+    }
+
+    async cancelJob(jobId) {
+        const job = this.getJob(jobId);
+        if (!job) return;
+        this.getItems(jobId).forEach((item) => this._abortItem(item, 'cancelled'));
+        this.jobs = this.jobs.filter((candidate) => candidate !== job);
+        this.items.delete(jobId);
+        this.writers.delete(jobId);
+        const output = this.outputs.get(jobId);
+        this.outputs.delete(jobId);
+        output?.abort(new DOMException('Download cancelled', 'AbortError'));
+        this._emit();
+        await db.deleteDownloadJob(jobId);
+        this._pump();
+    }
+
+    async clearFinished() {
+        const finished = this.jobs.filter((job) => job.status === 'done');
+        for (const job of finished) {
+            await this.cancelJob(job.id);
+        }
+    }
+
+    _emit() {
+        clearTimeout(this._emitTimer);
+        this._emitTimer = null;
+        window.dispatchEvent(new CustomEvent('download-queue-changed'));
+    }
+
+    _emitSoon() {
+        if (this._emitTimer) return;
+        this._emitTimer = setTimeout(() => this._emit(), PROGRESS_EMIT_INTERVAL_MS);
+    }
+}
+
+export const downloadQueue = new DownloadQueue();
@@ -10,15 +10,20 @@ import {
     getExtensionFromBlob,
     escapeHtml,
     getTrackDiscNumber,
+    computeDiscInfo,
 } from './utils.js';
-import { lyricsSettings, playlistSettings } from './storage.js';
+import { lyricsSettings, playlistSettings, offlineSettings, downloadQualitySettings } from './storage.js';
 import { generateM3U, generateM3U8, generateCUE, generateNFO, generateJSON } from './playlist-generator.js';
 import { ZipStreamWriter, ZipBlobWriter, FolderPickerWriter, SequentialFileWriter } from './bulk-download-writer.ts';
 import { FfmpegProgress } from './ffmpeg.types.js';
+import { getCustomFormat, transcodingProfiles } from './ffmpegFormats.ts';
 import { DownloadProgress, ProgressMessage, SegmentedDownloadProgress } from './progressEvents.js';
 import { db } from './db.js';
+import { offlineLibrary } from './offline-library.js';
+import { createFileChannel, downloadQueue, summarizeItems } from './download-queue.js';
+import { sidePanelManager } from './side-panel.js';
 import { BulkDownloadMethod, modernSettings } from './ModernSettings.js';
-import { SVG_CLOSE } from './icons.ts';
+import { SVG_CLOSE, SVG_DOWNLOAD, SVG_PAUSE, SVG_PLAY, SVG_RESET, SVG_TRASH } from './icons.ts';
 import { MusicAPI } from './music-api.js';
 import { LyricsManager } from './lyrics.js';
 
@@ -71,48 +76,6 @@ async function createDiscLayoutContext(tracks, api) {
     return { separateByDisc: false, resolveDiscNumber: () => 1 };
 }
 
-async function computeDiscInfo(tracks, api = null) {
-    // First pass: collect explicit disc numbers from the raw track objects.
-    const explicitDiscNumbers = tracks.map((track) => getTrackDiscNumber(track));
-    const explicitDistinct = new Set(explicitDiscNumbers.filter(Boolean));
-
-    let resolvedDiscNumbers = explicitDiscNumbers;
-
-    // Some providers omit disc fields in the album payload. When we can't
-    // distinguish discs from the raw data and an API instance is provided,
-    // hydrate missing disc numbers via full-track metadata (mirrors the logic
-    // in createDiscLayoutContext).
-    if (explicitDistinct.size <= 1 && api) {
-        const hydratedDiscNumbers = await Promise.all(
-            tracks.map(async (track, index) => {
-                if (explicitDiscNumbers[index]) return explicitDiscNumbers[index];
-                try {
-                    const fullTrack = await api.getTrackMetadata(track.id);
-                    return getTrackDiscNumber(fullTrack);
-                } catch {
-                    return null;
-                }
-            })
-        );
-        const hydratedDistinct = new Set(hydratedDiscNumbers.filter(Boolean));
-        if (hydratedDistinct.size > 1) {
-            resolvedDiscNumbers = hydratedDiscNumbers;
-        }
-    }
-
-    const tracksPerDisc = new Map();
-    let maxDiscNumber = 0;
-    for (let i = 0; i < tracks.length; i++) {
-        const discNumber = resolvedDiscNumbers[i] || 1;
-        tracksPerDisc.set(discNumber, (tracksPerDisc.get(discNumber) || 0) + 1);
-        if (discNumber > maxDiscNumber) {
-            maxDiscNumber = discNumber;
-        }
-    }
-
-    return { totalDiscs: maxDiscNumber || 1, tracksPerDisc, resolvedDiscNumbers };
-}
-
 async function annotateTracksWithDiscInfo(tracks, api = null) {
     const { totalDiscs, tracksPerDisc, resolvedDiscNumbers } = await computeDiscInfo(tracks, api);
     return tracks.map((track, index) => {
@@ -314,6 +277,14 @@ function removeBulkDownloadTask(notifEl) {
     }, 300);
 }
 
+/** Keep a finished download in the offline library when the user opted in. Never blocks the download. */
+function keepDownloadOffline(track, quality, blob) {
+    if (!offlineSettings.shouldSaveDownloads()) return;
+    offlineLibrary.saveBlob(track, quality, blob).catch((e) => {
+        console.warn('[Offline] Could not keep download offline:', e);
+    });
+}
+
 async function downloadTrackBlob(track, quality, api, signal = null, onProgress = null) {
     const blob = await api.downloadTrack(track.id, quality, undefined, {
         track,
@@ -322,6 +293,7 @@ async function downloadTrackBlob(track, quality, api, signal = null, onProgress
         triggerDownload: false,
         calculateDashBytes: false,
     });
+    keepDownloadOffline(track, quality, blob);
 
     // Detect actual format from blob signature BEFORE adding metadata
     const extension = await getExtensionFromBlob(blob);
@@ -329,180 +301,279 @@ async function downloadTrackBlob(track, quality, api, signal = null, onProgress
     return { blob, extension };
 }
 
-async function bulkDownload({
-    tracks,
-    folderName,
-    api,
-    quality,
-    lyricsManager,
-    notification,
-    writer,
-    coverBlob = null,
-    type = 'playlist',
-    metadata = null,
-}) {
-    const { abortController } = bulkDownloadTasks.get(notification);
-    const signal = abortController.signal;
-    const stats = { failed: 0, total: tracks.length, firstError: null };
-
-    async function* yieldFiles() {
-        // Add cover if available and enabled
-        if (coverBlob && playlistSettings.shouldIncludeCover()) {
-            yield { name: `${folderName}/cover.jpg`, lastModified: new Date(), input: coverBlob };
-        }
-
-        const useRelativePaths = playlistSettings.shouldUseRelativePaths();
-        const discLayout = await createDiscLayoutContext(tracks, api);
-        const separateByDisc = discLayout.separateByDisc;
-
-        // Download tracks, yielding each immediately and collecting actual paths for playlist generation
-        const trackPaths = [];
-        for (let i = 0; i < tracks.length; i++) {
-            if (signal.aborted) break;
-            const track = tracks[i];
-            const trackTitle = getTrackTitle(track);
-            let fileFraction = 0;
+/**
+ * Playlist and info files written next to the audio of one album or playlist folder.
+ * `trackPaths` holds the path of each track relative to the folder, or null if it failed.
+ */
+function* yieldPlaylistFiles(group, tracks, trackPaths) {
+    const { folder, fileBase, type, metadata } = group;
+    const useRelativePaths = playlistSettings.shouldUseRelativePaths();
+
+    if (playlistSettings.shouldGenerateNFO()) {
+        yield {
+            name: `${folder}/${fileBase}.nfo`,
+            lastModified: new Date(),
+            input: generateNFO(metadata, tracks, type),
+        };
+    }
 
-            updateBulkDownloadProgress(notification, i, tracks.length, trackTitle);
+    if (playlistSettings.shouldGenerateJSON()) {
+        yield {
+            name: `${folder}/${fileBase}.json`,
+            lastModified: new Date(),
+            input: generateJSON(metadata, tracks, type),
+        };
+    }
 
-            try {
-                const { blob, extension } = await downloadTrackBlob(track, quality, api, signal, (p) => {
-                    if (p instanceof DownloadProgress && p.totalBytes && p.receivedBytes) {
-                        fileFraction = p.receivedBytes / p.totalBytes;
-                    } else if (p instanceof SegmentedDownloadProgress && p.currentSegment && p.totalSegments) {
-                        fileFraction = p.currentSegment / p.totalSegments;
-                    }
+    // For albums, generate CUE file (one per disc if multi-disc)
+    if (type === 'album' && playlistSettings.shouldGenerateCUE()) {
+        const tracksByVolume = tracks.reduce((acc, track, index) => {
+            const discNumber = String(getTrackDiscNumber(track) || 1);
+            if (!acc[discNumber]) acc[discNumber] = [];
+            acc[discNumber].push({ ...track, trackPath: trackPaths[index] });
+            return acc;
+        }, {});
 
-                    fileFraction = Math.min(fileFraction, 0.99); // Cap at 99% to avoid showing 100% before finalization
-                    updateBulkDownloadProgress(notification, i + fileFraction, tracks.length, trackTitle, p);
-                });
-                const filename = buildTrackFilename(track, quality, extension);
-                const discNumber = discLayout.resolveDiscNumber(i);
-                const discPath = separateByDisc ? `${getDiscFolderName(discNumber)}/${filename}` : filename;
-
-                trackPaths.push(discPath);
-
-                yield {
-                    name: buildZipTrackPath(folderName, filename, separateByDisc, discNumber),
-                    lastModified: new Date(),
-                    input: blob,
-                };
-
-                if (lyricsManager && lyricsSettings.shouldDownloadLyrics()) {
-                    try {
-                        const lyricsData = await lyricsManager.fetchLyrics(track.id, track);
-                        if (lyricsData) {
-                            const lrcContent = lyricsManager.generateLRCContent(lyricsData, track);
-                            if (lrcContent) {
-                                const lrcFilename = filename.replace(/\.[^.]+$/, '.lrc');
-                                yield {
-                                    name: buildZipTrackPath(folderName, lrcFilename, separateByDisc, discNumber),
-                                    lastModified: new Date(),
-                                    input: lrcContent,
-                                };
-                            }
-                        }
-                    } catch {
-                        /* ignore */
-                    }
-                }
-            } catch (err) {
-                if (err?.name === 'AbortError' || signal.aborted) throw err;
-                stats.failed += 1;
-                if (!stats.firstError) stats.firstError = err?.message || String(err);
-                console.error(`Failed to download track ${trackTitle}:`, err);
-                const statusEl = notification.querySelector('.download-status');
-                if (statusEl) {
-                    statusEl.textContent = `${Math.floor(i + 1)}/${tracks.length} · ${stats.failed} failed`;
-                    statusEl.style.color = '#f59e0b';
-                }
-                trackPaths.push(null);
-            }
-        }
+        const multiDisc = Object.keys(tracksByVolume).length > 1;
 
-        if (playlistSettings.shouldGenerateNFO()) {
-            const nfoContent = generateNFO(metadata || { title: folderName }, tracks, type);
+        for (const [volumeNumber, volumeTracks] of Object.entries(tracksByVolume)) {
+            const volumeTrackPaths = volumeTracks.map((track) => track.trackPath);
             yield {
-                name: `${folderName}/${sanitizeForFilename(folderName)}.nfo`,
+                name: `${folder}/${fileBase}${multiDisc ? ` - Disc ${volumeNumber}` : ''}.cue`,
                 lastModified: new Date(),
-                input: nfoContent,
+                input: generateCUE(metadata, volumeTracks, fileBase, volumeTrackPaths),
             };
         }
+    }
 
-        if (playlistSettings.shouldGenerateJSON()) {
-            const jsonContent = generateJSON(metadata || { title: folderName }, tracks, type);
-            yield {
-                name: `${folderName}/${sanitizeForFilename(folderName)}.json`,
-                lastModified: new Date(),
-                input: jsonContent,
-            };
-        }
+    // Generate m3u/m3u8 last, using actual track paths collected during download
+    if (playlistSettings.shouldGenerateM3U()) {
+        yield {
+            name: `${folder}/${fileBase}.m3u`,
+            lastModified: new Date(),
+            input: generateM3U(metadata, tracks, useRelativePaths, null, 'flac', trackPaths),
+        };
+    }
 
-        // For albums, generate CUE file (one per disc if multi-disc)
-        if (type === 'album' && playlistSettings.shouldGenerateCUE()) {
-            const tracksByVolume = tracks.reduce((acc, track, index) => {
-                const discNumber = String(getTrackDiscNumber(track) || 1);
-                if (!acc[discNumber]) acc[discNumber] = [];
-                acc[discNumber].push({ ...track, trackPath: trackPaths[index] });
-                return acc;
-            }, {});
-
-            const multiDisc = Object.keys(tracksByVolume).length > 1;
-
-            for (const [volumeNumber, volumeTracks] of Object.entries(tracksByVolume)) {
-                const volumeTrackPaths = volumeTracks.map((track) => track.trackPath);
-                const cueContent = generateCUE(
-                    metadata,
-                    volumeTracks,
-                    sanitizeForFilename(folderName),
-                    volumeTrackPaths
-                );
-                yield {
-                    name: `${folderName}/${sanitizeForFilename(folderName)}${multiDisc ? ` - Disc ${volumeNumber}` : ''}.cue`,
-                    lastModified: new Date(),
-                    input: cueContent,
-                };
-            }
-        }
+    if (playlistSettings.shouldGenerateM3U8()) {
+        yield {
+            name: `${folder}/${fileBase}.m3u8`,
+            lastModified: new Date(),
+            input: generateM3U8(metadata, tracks, useRelativePaths, null, 'flac', trackPaths),
+        };
+    }
+}
 
-        // Generate m3u/m3u8 last, using actual track paths collected during download
-        if (playlistSettings.shouldGenerateM3U()) {
-            const m3uContent = generateM3U(
-                metadata || { title: folderName },
-                tracks,
-                useRelativePaths,
-                null,
-                'flac',
-                trackPaths
-            );
-            yield {
-                name: `${folderName}/${sanitizeForFilename(folderName)}.m3u`,
-                lastModified: new Date(),
-                input: m3uContent,
-            };
-        }
+// Queue jobs live in IndexedDB, so only plain data can go in them
+function toStorable(value) {
+    return value ? JSON.parse(JSON.stringify(value)) : null;
+}
 
-        if (playlistSettings.shouldGenerateM3U8()) {
-            const m3u8Content = generateM3U8(
-                metadata || { title: folderName },
-                tracks,
-                useRelativePaths,
-                null,
-                'flac',
-                trackPaths
-            );
-            yield {
-                name: `${folderName}/${sanitizeForFilename(folderName)}.m3u8`,
-                lastModified: new Date(),
-                input: m3u8Content,
-            };
+/**
+ * Describe one output folder of a queued job (an album, a playlist, one album of a discography)
+ * along with the tracks that go into it.
+ * @returns {Promise<{group: object, seeds: object[]}>}
+ */
+async function createQueueGroup({ key, folder, fileBase, type, metadata, coverId }, tracks, api) {
+    const discLayout = await createDiscLayoutContext(tracks, api);
+    const group = {
+        key,
+        folder,
+        fileBase,
+        type,
+        metadata: toStorable(metadata),
+        coverId: coverId || null,
+        separateByDisc: discLayout.separateByDisc,
+    };
+    const seeds = tracks.map((track, index) => ({
+        track: toStorable(track),
+        groupKey: key,
+        discNumber: discLayout.resolveDiscNumber(index),
+    }));
+    return { group, seeds };
+}
+
+async function* yieldGroupCover(group) {
+    if (!group.coverId || !playlistSettings.shouldIncludeCover()) return;
+    const coverBlob = await getCoverBlob(MusicAPI.instance, group.coverId).catch(() => null);
+    if (coverBlob) {
+        yield { name: `${group.folder}/cover.jpg`, lastModified: new Date(), input: coverBlob };
+    }
+}
+
+/** Path of a finished track relative to its group folder */
+function getQueuedTrackPath(job, group, item) {
+    const filename = buildTrackFilename(item.track, job.quality, item.extension);
+    return group.separateByDisc ? `${getDiscFolderName(item.discNumber)}/${filename}` : filename;
+}
+
+/** The audio file of a finished track and its lyrics */
+function* yieldQueuedTrackFiles(job, group, item, blob) {
+    const filename = buildTrackFilename(item.track, job.quality, item.extension);
+    yield {
+        name: buildZipTrackPath(group.folder, filename, group.separateByDisc, item.discNumber),
+        lastModified: new Date(item.finishedAt || Date.now()),
+        input: blob,
+    };
+
+    if (item.lrc) {
+        yield {
+            name: buildZipTrackPath(
+                group.folder,
+                filename.replace(/\.[^.]+$/, '.lrc'),
+                group.separateByDisc,
+                item.discNumber
+            ),
+            lastModified: new Date(),
+            input: item.lrc,
+        };
+    }
+}
+
+function* yieldQueuedPlaylistFiles(job, group, groupItems) {
+    yield* yieldPlaylistFiles(
+        group,
+        groupItems.map((item) => item.track),
+        groupItems.map((item) => (item.status === 'done' ? getQueuedTrackPath(job, group, item) : null))
+    );
+}
+
+/** Everything a finished staged job writes: per folder the cover, the tracks and lyrics, then playlist files. */
+async function* yieldQueuedJobFiles(job, items) {
+    for (const group of job.groups) {
+        const groupItems = items.filter((item) => item.groupKey === group.key);
+        if (!groupItems.some((item) => item.status === 'done')) continue;
+
+        yield* yieldGroupCover(group);
+        for (const item of groupItems) {
+            const blob = item.status === 'done' ? await db.getDownloadBlob(item.id) : null;
+            if (blob) yield* yieldQueuedTrackFiles(job, group, item, blob);
         }
+        yield* yieldQueuedPlaylistFiles(job, group, groupItems);
     }
+}
 
-    await writer.write(yieldFiles());
-    return stats;
+// If the download went to the local media folder, refresh the local library.
+function refreshLocalMediaAfterDownload() {
+    if (modernSettings.bulkDownloadMethod === BulkDownloadMethod.LocalMedia) {
+        window.refreshLocalMediaFolder?.();
+    }
 }
 
+/** Does the actual work for {@link downloadQueue}; see DownloadQueue.init(). */
+const queueProcessor = {
+    async expandRelease(job, release) {
+        const api = MusicAPI.instance;
+        const { album: fullAlbum, tracks: rawTracks } = await api.getAlbum(release.id);
+        const tracks = await annotateTracksWithDiscInfo(rawTracks, api);
+        const releaseDateStr =
+            fullAlbum.releaseDate || (tracks[0]?.streamStartDate ? tracks[0].streamStartDate.split('T')[0] : '');
+        const releaseDate = releaseDateStr ? new Date(releaseDateStr) : null;
+        const year = releaseDate && !isNaN(releaseDate.getTime()) ? releaseDate.getFullYear() : '';
+
+        const albumFolder = formatPathTemplate(modernSettings.folderTemplate, {
+            albumTitle: fullAlbum.title,
+            albumArtist: fullAlbum.artist?.name,
+            year: year,
+        });
+
+        return createQueueGroup(
+            {
+                key: String(release.id),
+                folder: `${job.folderName}/${albumFolder}`,
+                fileBase: sanitizeForFilename(fullAlbum.title),
+                type: 'album',
+                metadata: fullAlbum,
+                coverId: fullAlbum.cover || release.cover,
+            },
+            tracks,
+            api
+        );
+    },
+
+    async download(job, item, { signal, onProgress }) {
+        const { track } = item;
+        // Leave room on the bar for the encode when a transcoding profile is used
+        const downloadShare = getCustomFormat(job.quality) ? 0.8 : 0.99;
+        const { blob, extension } = await downloadTrackBlob(track, job.quality, MusicAPI.instance, signal, (p) => {
+            if (p instanceof DownloadProgress && p.totalBytes && p.receivedBytes) {
+                onProgress(Math.min(p.receivedBytes / p.totalBytes, 1) * downloadShare);
+            } else if (p instanceof SegmentedDownloadProgress && p.currentSegment && p.totalSegments) {
+                onProgress(Math.min(p.currentSegment / p.totalSegments, 1) * downloadShare);
+            } else if (p instanceof FfmpegProgress && p.stage === 'encoding' && downloadShare < 0.99) {
+                onProgress(downloadShare + (Math.min(p.progress, 100) / 100) * (0.99 - downloadShare));
+            }
+        });
+
+        let lrc = null;
+        if (lyricsSettings.shouldDownloadLyrics()) {
+            try {
+                const lyricsManager = LyricsManager.instance;
+                const lyricsData = await lyricsManager.fetchLyrics(track.id, track);
+                if (lyricsData) lrc = lyricsManager.generateLRCContent(lyricsData, track) || null;
+            } catch {
+                /* ignore */
+            }
+        }
+
+        return { blob, extension, lrc };
+    },
+
+    /** Streams each track to the writer as soon as it finishes; playlist files follow once the job is done */
+    open(job, writer) {
+        const channel = createFileChannel();
+        const coversWritten = new Set();
+
+        return {
+            done: writer.write(channel),
+
+            async add(item, blob) {
+                const group = job.groups.find((candidate) => candidate.key === item.groupKey);
+                const files = [];
+                if (!coversWritten.has(group.key)) {
+                    coversWritten.add(group.key);
+                    for await (const file of yieldGroupCover(group)) files.push(file);
+                }
+                files.push(...yieldQueuedTrackFiles(job, group, item, blob));
+                await channel.push(files);
+            },
+
+            async close(items) {
+                const files = job.groups.flatMap((group) => {
+                    const groupItems = items.filter((item) => item.groupKey === group.key);
+                    if (!groupItems.some((item) => item.status === 'done')) return [];
+                    return [...yieldQueuedPlaylistFiles(job, group, groupItems)];
+                });
+                if (files.length) await channel.push(files);
+                channel.close();
+                await this.done;
+                refreshLocalMediaAfterDownload();
+            },
+
+            abort(error) {
+                channel.fail(error);
+            },
+        };
+    },
+
+    async save(job, items, writer) {
+        await writer.write(yieldQueuedJobFiles(job, items));
+        refreshLocalMediaAfterDownload();
+    },
+
+    createWriter(job) {
+        return createBulkWriter(job.folderName);
+    },
+};
+
+// Jobs restored from a previous session get their notification back (and a way to save them)
+downloadQueue.init(queueProcessor).then(() => {
+    downloadQueue
+        .getJobs()
+        .filter((job) => job.status !== 'done')
+        .forEach((job) => trackJobNotification(job));
+});
+
 /**
  * Returns a writer that can be used to save a single-track download directly
  * to the configured folder (Local Media Folder or saved Folder Picker handle),
@@ -633,56 +704,118 @@ async function createBulkWriter(folderName) {
     return new ZipBlobWriter(`${folderName}.zip`);
 }
 
+const QUALITY_PROFILE_OPTIONS = {
+    HI_RES_LOSSLESS: { name: 'Hi-Res Lossless', description: 'Up to 24-bit FLAC, largest files' },
+    LOSSLESS: { name: 'Lossless', description: '16-bit FLAC, CD quality' },
+    HIGH: { name: 'AAC 320kbps', description: 'Streamed as-is, no transcoding' },
+    LOW: { name: 'AAC 96kbps', description: 'Streamed as-is, no transcoding' },
+};
+
+let closeDownloadProfilePicker = null;
+
+/**
+ * Asks which quality or transcoding profile to use for this download, when enabled in settings.
+ * Resolves to the chosen quality, or null if the picker was dismissed.
+ * @param {string} quality - Default download quality
+ * @returns {Promise<string|null>}
+ */
+export function chooseDownloadQuality(quality) {
+    const modal = document.getElementById('download-profile-modal');
+    const list = document.getElementById('download-profile-list');
+    if (!downloadQualitySettings.shouldAsk() || !modal || !list) return Promise.resolve(quality);
+
+    const defaultName = QUALITY_PROFILE_OPTIONS[quality]?.name || getCustomFormat(quality)?.displayName || quality;
+    const options = [
+        { value: quality, name: defaultName, description: 'Default download quality' },
+        ...['HI_RES_LOSSLESS', 'LOSSLESS'].map((value) => ({ value, ...QUALITY_PROFILE_OPTIONS[value] })),
+        ...transcodingProfiles.map(({ format, name, description }) => ({ value: format, name, description })),
+    ].filter((option, index, all) => all.findIndex((o) => o.value === option.value) === index);
+
+    list.innerHTML = options
+        .map(
+            (option) => `
+            <div class="modal-option download-profile-option" data-quality="${escapeHtml(option.value)}">
+                <div class="info">
+                    <span>${escapeHtml(option.name)}</span>
+                    <span class="description">${escapeHtml(option.description)}</span>
+                </div>
+            </div>
+        `
+        )
+        .join('');
+
+    // A picker left open (e.g. closed with Escape) must not start its download later
+    closeDownloadProfilePicker?.(null);
+
+    return new Promise((resolve) => {
+        const close = (result) => {
+            modal.classList.remove('active');
+            modal.removeEventListener('click', handleClick);
+            closeDownloadProfilePicker = null;
+            resolve(result);
+        };
+
+        const handleClick = (e) => {
+            const option = e.target.closest('.download-profile-option');
+            if (option) {
+                close(option.dataset.quality);
+            } else if (e.target.classList.contains('modal-overlay') || e.target.id === 'download-profile-cancel') {
+                close(null);
+            }
+        };
+
+        closeDownloadProfilePicker = close;
+        modal.addEventListener('click', handleClick);
+        modal.classList.add('active');
+    });
+}
+
+/**
+ * Ask for the output of a bulk download while the click that started it is still active.
+ * Returns null when the user cancelled or nothing should be written.
+ */
+async function pickBulkWriter(type, name, folderName, single = false) {
+    try {
+        return single ? await createSingleTrackFolderWriter() : await createBulkWriter(folderName);
+    } catch (error) {
+        if (error?.name === 'AbortError') return null;
+        console.error('Bulk download failed:', error);
+        completeBulkDownload(createBulkDownloadNotification(type, name), false, error?.message);
+        return null;
+    }
+}
+
 async function startBulkDownload({
     tracks,
     folderName = '',
     api,
     quality,
-    lyricsManager = LyricsManager.instance,
     type,
     name,
-    coverBlob = null,
+    coverId = null,
     metadata = null,
     single = false,
 }) {
-    const notification = createBulkDownloadNotification(type, name, tracks.length);
-
-    try {
-        const writer = single ? await createSingleTrackFolderWriter() : await createBulkWriter(folderName);
-
-        let stats = null;
-        if (writer) {
-            stats = await bulkDownload({
-                tracks,
-                folderName,
-                api,
-                quality,
-                lyricsManager,
-                notification,
-                writer,
-                coverBlob,
-                type,
-                metadata,
-            });
-        }
-
-        completeBulkDownload(notification, true, null, {
-            failedTracks: stats?.failed || 0,
-            totalTracks: stats?.total || 0,
-        });
-
-        // If the download went to the local media folder, refresh the local library.
-        if (modernSettings.bulkDownloadMethod === BulkDownloadMethod.LocalMedia) {
-            window.refreshLocalMediaFolder?.();
-        }
-    } catch (error) {
-        if (error?.name === 'AbortError') {
-            removeBulkDownloadTask(notification);
-            return;
-        }
-        console.error('Bulk download failed:', error);
-        completeBulkDownload(notification, false, error?.message);
-    }
+    quality = await chooseDownloadQuality(quality);
+    if (!quality) return;
+
+    const writer = await pickBulkWriter(type, name, folderName, single);
+    if (!writer) return;
+
+    const { group, seeds } = await createQueueGroup(
+        {
+            key: 'main',
+            folder: folderName,
+            fileBase: sanitizeForFilename(folderName),
+            type,
+            metadata: metadata || { title: folderName },
+            coverId,
+        },
+        tracks,
+        api
+    );
+    const job = await downloadQueue.enqueue({ type, name, folderName, quality, groups: [group] }, seeds, writer);
+    trackJobNotification(job);
 }
 
 export async function downloadTracks(tracks, api, quality, _lyricsManager = null) {
@@ -712,14 +845,13 @@ export async function downloadAlbum(album, tracks, api, quality, _lyricsManager
         year: year,
     });
 
-    const coverBlob = await getCoverBlob(api, album.cover || album.album?.cover || album.coverId);
     await startBulkDownload({
         tracks: await annotateTracksWithDiscInfo(tracks, api),
         folderName,
         quality,
         type: 'album',
         name: album.title,
-        coverBlob,
+        coverId: album.cover || album.album?.cover || album.coverId,
         metadata: album,
         api,
     });
@@ -733,188 +865,63 @@ export async function downloadPlaylist(playlist, tracks, api, quality, _lyricsMa
     });
 
     const representativeTrack = tracks.find((t) => t.album?.cover);
-    const coverBlob = await getCoverBlob(api, representativeTrack?.album?.cover);
     await startBulkDownload({
         tracks,
         folderName,
         quality,
         type: 'playlist',
         name: playlist.title,
-        coverBlob,
+        coverId: representativeTrack?.album?.cover,
         metadata: playlist,
         api,
     });
 }
 
-export async function downloadDiscography(artist, selectedReleases, api, quality, lyricsManager = null) {
-    const rootFolder = `${sanitizeForFilename(artist.name)} discography`;
-    const notification = createBulkDownloadNotification('discography', artist.name, selectedReleases.length);
-    const { abortController } = bulkDownloadTasks.get(notification);
-    const signal = abortController.signal;
-    const stats = { failed: 0, total: 0, firstError: null };
-
-    async function* yieldDiscography() {
-        for (let albumIndex = 0; albumIndex < selectedReleases.length; albumIndex++) {
-            if (signal.aborted) break;
-            const album = selectedReleases[albumIndex];
-            updateBulkDownloadProgress(notification, albumIndex, selectedReleases.length, album.title);
-
-            try {
-                const { album: fullAlbum, tracks: rawTracks } = await api.getAlbum(album.id);
-                const tracks = await annotateTracksWithDiscInfo(rawTracks, api);
-                const coverBlob = await getCoverBlob(api, fullAlbum.cover || album.cover);
-                const releaseDateStr =
-                    fullAlbum.releaseDate ||
-                    (tracks[0]?.streamStartDate ? tracks[0].streamStartDate.split('T')[0] : '');
-                const releaseDate = releaseDateStr ? new Date(releaseDateStr) : null;
-                const year = releaseDate && !isNaN(releaseDate.getTime()) ? releaseDate.getFullYear() : '';
-
-                const albumFolder = formatPathTemplate(modernSettings.folderTemplate, {
-                    albumTitle: fullAlbum.title,
-                    albumArtist: fullAlbum.artist?.name,
-                    year: year,
-                });
+export async function downloadDiscography(artist, selectedReleases, _api, quality, _lyricsManager = null) {
+    quality = await chooseDownloadQuality(quality);
+    if (!quality) return;
 
-                const fullFolderPath = `${rootFolder}/${albumFolder}`;
-                if (coverBlob && playlistSettings.shouldIncludeCover())
-                    yield { name: `${fullFolderPath}/cover.jpg`, lastModified: new Date(), input: coverBlob };
-
-                // Generate playlist files for each album
-                const useRelativePaths = playlistSettings.shouldUseRelativePaths();
-                const discLayout = await createDiscLayoutContext(tracks, api);
-                const separateByDisc = discLayout.separateByDisc;
-
-                // Download tracks, yielding each immediately and collecting actual paths for playlist generation
-                const trackPaths = [];
-                stats.total += tracks.length;
-                for (let i = 0; i < tracks.length; i++) {
-                    const track = tracks[i];
-                    if (signal.aborted) break;
-                    try {
-                        const { blob, extension } = await downloadTrackBlob(track, quality, api, signal, null);
-                        const filename = buildTrackFilename(track, quality, extension);
-                        const discNumber = discLayout.resolveDiscNumber(i);
-                        const discPath = separateByDisc ? `${getDiscFolderName(discNumber)}/${filename}` : filename;
-
-                        trackPaths.push(discPath);
-
-                        yield {
-                            name: buildZipTrackPath(fullFolderPath, filename, separateByDisc, discNumber),
-                            lastModified: new Date(),
-                            input: blob,
-                        };
-
-                        if (lyricsManager && lyricsSettings.shouldDownloadLyrics()) {
-                            try {
-                                const lyricsData = await lyricsManager.fetchLyrics(track.id, track);
-                                if (lyricsData) {
-                                    const lrcContent = lyricsManager.generateLRCContent(lyricsData, track);
-                                    if (lrcContent) {
-                                        const lrcFilename = filename.replace(/\.[^.]+$/, '.lrc');
-                                        yield {
-                                            name: buildZipTrackPath(
-                                                fullFolderPath,
-                                                lrcFilename,
-                                                separateByDisc,
-                                                discNumber
-                                            ),
-                                            lastModified: new Date(),
-                                            input: lrcContent,
-                                        };
-                                    }
-                                }
-                            } catch {
-                                /* ignore */
-                            }
-                        }
-                    } catch (err) {
-                        if (err?.name === 'AbortError' || signal.aborted) throw err;
-                        stats.failed += 1;
-                        if (!stats.firstError) stats.firstError = err?.message || String(err);
-                        console.error(`Failed to download track ${track.title}:`, err);
-                        const statusEl = notification.querySelector('.download-status');
-                        if (statusEl) {
-                            statusEl.textContent = `${album.title} · ${stats.failed} failed`;
-                            statusEl.style.color = '#f59e0b';
-                        }
-                        trackPaths.push(null);
-                    }
-                }
-
-                if (playlistSettings.shouldGenerateNFO()) {
-                    const nfoContent = generateNFO(fullAlbum, tracks, 'album');
-                    yield {
-                        name: `${fullFolderPath}/${sanitizeForFilename(fullAlbum.title)}.nfo`,
-                        lastModified: new Date(),
-                        input: nfoContent,
-                    };
-                }
-
-                if (playlistSettings.shouldGenerateJSON()) {
-                    const jsonContent = generateJSON(fullAlbum, tracks, 'album');
-                    yield {
-                        name: `${fullFolderPath}/${sanitizeForFilename(fullAlbum.title)}.json`,
-                        lastModified: new Date(),
-                        input: jsonContent,
-                    };
-                }
-
-                if (playlistSettings.shouldGenerateCUE()) {
-                    const cueContent = generateCUE(fullAlbum, tracks, sanitizeForFilename(fullAlbum.title), trackPaths);
-                    yield {
-                        name: `${fullFolderPath}/${sanitizeForFilename(fullAlbum.title)}.cue`,
-                        lastModified: new Date(),
-                        input: cueContent,
-                    };
-                }
-
-                // Generate m3u/m3u8 last, using actual track paths collected during download
-                if (playlistSettings.shouldGenerateM3U()) {
-                    const m3uContent = generateM3U(fullAlbum, tracks, useRelativePaths, null, 'flac', trackPaths);
-                    yield {
-                        name: `${fullFolderPath}/${sanitizeForFilename(fullAlbum.title)}.m3u`,
-                        lastModified: new Date(),
-                        input: m3uContent,
-                    };
-                }
-
-                if (playlistSettings.shouldGenerateM3U8()) {
-                    const m3u8Content = generateM3U8(fullAlbum, tracks, useRelativePaths, null, 'flac', trackPaths);
-                    yield {
-                        name: `${fullFolderPath}/${sanitizeForFilename(fullAlbum.title)}.m3u8`,
-                        lastModified: new Date(),
-                        input: m3u8Content,
-                    };
-                }
-            } catch (error) {
-                if (error?.name === 'AbortError' || signal.aborted) throw error;
-                console.error(`Failed to download album ${album.title}:`, error);
-                if (!stats.firstError) stats.firstError = error?.message || String(error);
-            }
-        }
-    }
-
-    try {
-        const writer = await createBulkWriter(rootFolder);
-
-        if (writer) {
-            await writer.write(yieldDiscography());
-        }
+    const rootFolder = `${sanitizeForFilename(artist.name)} discography`;
+    const writer = await pickBulkWriter('discography', artist.name, rootFolder);
+    if (!writer) return;
+
+    // Albums are fetched one at a time by the queue, so a reload mid-way only refetches what's left
+    const job = await downloadQueue.enqueue(
+        {
+            type: 'discography',
+            name: artist.name,
+            folderName: rootFolder,
+            quality,
+            pendingReleases: selectedReleases.map((album) => ({
+                id: album.id,
+                title: album.title,
+                cover: album.cover || null,
+            })),
+        },
+        [],
+        writer
+    );
+    trackJobNotification(job);
+}
 
-        completeBulkDownload(notification, true, null, {
-            failedTracks: stats.failed,
-            totalTracks: stats.total,
-        });
-    } catch (error) {
-        if (error?.name === 'AbortError' || signal.aborted) {
-            removeBulkDownloadTask(notification);
-            return;
-        }
-        completeBulkDownload(notification, false, error?.message);
+function getBulkTypeLabel(type) {
+    switch (type) {
+        case 'album':
+            return 'Album';
+        case 'playlist':
+            return 'Playlist';
+        case 'liked':
+            return 'Liked Tracks';
+        case 'queue':
+            return 'Queue';
+        case 'discography':
+            return 'Discography';
+        default:
+            return '';
     }
 }
 
-function createBulkDownloadNotification(type, name, _totalItems) {
+function createBulkDownloadNotification(type, name, onCancel = null) {
     const container = createDownloadNotification();
 
     const notifEl = document.createElement('div');
@@ -922,22 +929,7 @@ function createBulkDownloadNotification(type, name, _totalItems) {
     notifEl.dataset.bulkType = type;
     notifEl.dataset.bulkName = name;
 
-    const typeLabel = (() => {
-        switch (type) {
-            case 'album':
-                return 'Album';
-            case 'playlist':
-                return 'Playlist';
-            case 'liked':
-                return 'Liked Tracks';
-            case 'queue':
-                return 'Queue';
-            case 'discography':
-                return 'Discography';
-            default:
-                return '';
-        }
-    })();
+    const typeLabel = getBulkTypeLabel(type);
 
     notifEl.innerHTML = `
         <div style="display: flex; align-items: start; gap: 0.75rem;">
@@ -958,18 +950,278 @@ function createBulkDownloadNotification(type, name, _totalItems) {
     `;
 
     container.appendChild(notifEl);
+    bulkDownloadTasks.set(notifEl, { onCancel });
 
-    const abortController = new AbortController();
-    bulkDownloadTasks.set(notifEl, { abortController });
-
-    notifEl.querySelector('.download-cancel').addEventListener('click', () => {
-        abortController.abort();
+    notifEl.querySelector('.download-cancel').addEventListener('click', (e) => {
+        e.stopPropagation();
+        onCancel?.();
         removeBulkDownloadTask(notifEl);
     });
 
     return notifEl;
 }
 
+/**
+ * Mirror a queued job in a floating notification until it has been written out. Clicking it
+ * opens the download queue, or saves the job when it is waiting for a save location.
+ */
+function trackJobNotification(job) {
+    const notification = createBulkDownloadNotification(job.type, job.name, () => downloadQueue.cancelJob(job.id));
+    notification.style.cursor = 'pointer';
+    notification.title = 'Show download queue';
+
+    const update = () => {
+        const current = downloadQueue.getJob(job.id);
+        if (!current) {
+            window.removeEventListener('download-queue-changed', update);
+            removeBulkDownloadTask(notification);
+            return;
+        }
+
+        const items = downloadQueue.getItems(job.id);
+        const summary = summarizeItems(items);
+        const statusEl = notification.querySelector('.download-status');
+        statusEl.style.color = '';
+
+        if (current.status === 'done' || current.status === 'error') {
+            window.removeEventListener('download-queue-changed', update);
+            completeBulkDownload(notification, current.status === 'done', current.error, {
+                failedTracks: summary.failed,
+                totalTracks: summary.total,
+            });
+        } else if (current.status === 'ready') {
+            statusEl.textContent = current.error ? 'Saving failed - click to retry' : 'Finished - click to save';
+            statusEl.style.color = current.error ? '#ef4444' : 'var(--highlight)';
+        } else if (current.status === 'needs-output') {
+            statusEl.textContent = current.error ? 'Saving failed - click to retry' : 'Click to choose where to save';
+            statusEl.style.color = current.error ? '#ef4444' : 'var(--highlight)';
+        } else if (current.status === 'saving') {
+            statusEl.textContent = 'Saving...';
+        } else if (current.status === 'paused' || downloadQueue.isPaused()) {
+            statusEl.textContent = 'Paused';
+        } else if (!summary.total) {
+            statusEl.textContent = 'Loading releases...';
+        } else {
+            const activeItem = items.find((item) => item.status === 'active');
+            const currentItem = activeItem ? getTrackTitle(activeItem.track) : 'Waiting...';
+            const done = downloadQueue.getJobProgress(job.id) * summary.total;
+            updateBulkDownloadProgress(notification, Math.min(done, summary.total - 1), summary.total, currentItem);
+            if (summary.failed) {
+                statusEl.textContent += ` · ${summary.failed} failed`;
+                statusEl.style.color = '#f59e0b';
+            }
+        }
+    };
+
+    notification.addEventListener('click', () => {
+        if (['ready', 'needs-output'].includes(downloadQueue.getJob(job.id)?.status)) {
+            downloadQueue.saveJob(job.id).catch((e) => console.error('Saving download failed:', e));
+        } else {
+            openDownloadQueuePanel();
+        }
+    });
+
+    window.addEventListener('download-queue-changed', update);
+    update();
+}
+
+const DOWNLOAD_ITEM_STATUS_LABELS = {
+    queued: 'Waiting',
+    active: 'Downloading',
+    retrying: 'Retrying soon',
+    paused: 'Paused',
+    done: 'Downloaded',
+    failed: 'Failed',
+};
+
+const DOWNLOAD_JOB_STATUS_LABELS = {
+    paused: 'Paused',
+    saving: 'Saving...',
+    ready: 'Ready to save',
+    'needs-output': 'Choose where to save to continue',
+    done: 'Saved',
+    error: 'Failed',
+};
+
+// Jobs whose track list is unfolded in the panel; kept across re-renders
+const expandedDownloadJobs = new Set();
+let downloadQueueRenderFrame = null;
+
+function renderDownloadItemHTML(item) {
+    const progress = downloadQueue.getItemProgress(item.id);
+    let status = DOWNLOAD_ITEM_STATUS_LABELS[item.status] || item.status;
+    if (item.status === 'active' && progress) status += ` ${Math.round(progress * 100)}%`;
+    if (item.status === 'retrying' && item.attempts) status += ` (attempt ${item.attempts + 1})`;
+
+    let action = '';
+    if (['queued', 'active', 'retrying'].includes(item.status)) {
+        action = `<button class="btn-icon" data-queue-action="pause-item" title="Pause">${SVG_PAUSE(16)}</button>`;
+    } else if (item.status === 'paused') {
+        action = `<button class="btn-icon" data-queue-action="resume-item" title="Resume">${SVG_PLAY(16)}</button>`;
+    } else if (item.status === 'failed') {
+        action = `<button class="btn-icon" data-queue-action="resume-item" title="Retry">${SVG_RESET(16)}</button>`;
+    }
+
+    return `
+        <div class="download-queue-item ${item.status}" data-item-id="${item.id}">
+            <div class="download-queue-item-info">
+                <div class="download-queue-item-title">${escapeHtml(getTrackTitle(item.track))}</div>
+                <div class="download-queue-item-status" title="${escapeHtml(item.error || '')}">${status}</div>
+            </div>
+            ${action}
+        </div>
+    `;
+}
+
+function renderDownloadJobHTML(job) {
+    const items = downloadQueue.getItems(job.id);
+    const summary = summarizeItems(items);
+    const percent = Math.round(downloadQueue.getJobProgress(job.id) * 100);
+    const expanded = expandedDownloadJobs.has(job.id);
+
+    const details = [getBulkTypeLabel(job.type), `${summary.done}/${summary.total}`];
+    const profile = getCustomFormat(job.quality);
+    if (profile) details.push(profile.displayName);
+    if (job.pendingReleases?.length) details.push(`${job.pendingReleases.length} releases left`);
+    if (summary.failed || job.failedReleases) details.push(`${summary.failed + job.failedReleases} failed`);
+    if (DOWNLOAD_JOB_STATUS_LABELS[job.status]) details.push(DOWNLOAD_JOB_STATUS_LABELS[job.status]);
+
+    const actions = [];
+    if (job.status === 'queued') {
+        actions.push(`<button class="btn-icon" data-queue-action="pause-job" title="Pause">${SVG_PAUSE(18)}</button>`);
+    } else if (job.status === 'paused') {
+        actions.push(`<button class="btn-icon" data-queue-action="resume-job" title="Resume">${SVG_PLAY(18)}</button>`);
+    } else if (job.status === 'ready' || job.status === 'needs-output') {
+        actions.push(`<button class="btn-icon" data-queue-action="save-job" title="Save">${SVG_DOWNLOAD(18)}</button>`);
+    }
+    if (summary.failed && !['done', 'saving'].includes(job.status)) {
+        actions.push(
+            `<button class="btn-icon" data-queue-action="retry-job" title="Retry failed">${SVG_RESET(18)}</button>`
+        );
+    }
+    const cancelTitle = job.status === 'done' ? 'Remove' : 'Cancel';
+    actions.push(
+        `<button class="btn-icon" data-queue-action="cancel-job" title="${cancelTitle}">${SVG_CLOSE(18)}</button>`
+    );
+
+    return `
+        <div class="download-queue-job ${job.status}" data-job-id="${job.id}">
+            <div class="download-queue-job-header">
+                <div class="download-queue-job-info">
+                    <div class="download-queue-job-title">${escapeHtml(job.name)}</div>
+                    <div class="download-queue-job-status">${escapeHtml(details.join(' · '))}</div>
+                    ${job.error ? `<div class="download-queue-job-error">${escapeHtml(job.error)}</div>` : ''}
+                </div>
+                <div class="download-queue-job-actions">${actions.join('')}</div>
+            </div>
+            <div class="download-progress-bar">
+                <div class="download-progress-fill" style="width: ${percent}%"></div>
+            </div>
+            ${
+                items.length
+                    ? `<button class="download-queue-toggle" data-queue-action="toggle-items">
+                        ${expanded ? 'Hide tracks' : `Show tracks (${items.length})`}
+                    </button>`
+                    : ''
+            }
+            ${expanded ? `<div class="download-queue-items">${items.map(renderDownloadItemHTML).join('')}</div>` : ''}
+        </div>
+    `;
+}
+
+function renderDownloadQueueControls(container) {
+    const paused = downloadQueue.isPaused();
+    container.innerHTML = `
+        <button id="download-manager-toggle-btn" class="btn-icon" title="${paused ? 'Resume all' : 'Pause all'}">
+            ${paused ? SVG_PLAY(20) : SVG_PAUSE(20)}
+        </button>
+        <button id="download-manager-clear-btn" class="btn-icon" title="Clear finished">
+            ${SVG_TRASH(20)}
+        </button>
+        <button id="close-side-panel-btn" class="btn-icon" title="Close">
+            ${SVG_CLOSE(20)}
+        </button>
+    `;
+
+    container.querySelector('#download-manager-toggle-btn').addEventListener('click', () => {
+        if (downloadQueue.isPaused()) downloadQueue.resumeAll();
+        else downloadQueue.pauseAll();
+    });
+    container.querySelector('#download-manager-clear-btn').addEventListener('click', () => {
+        void downloadQueue.clearFinished();
+    });
+    container.querySelector('#close-side-panel-btn').addEventListener('click', () => {
+        sidePanelManager.close();
+    });
+}
+
+function renderDownloadQueueContent(container) {
+    if (!container._downloadQueueListenersAttached) {
+        container.addEventListener('click', (e) => {
+            const button = e.target.closest('[data-queue-action]');
+            const jobEl = button?.closest('.download-queue-job');
+            if (!jobEl) return;
+            const jobId = jobEl.dataset.jobId;
+            const itemId = button.closest('.download-queue-item')?.dataset.itemId;
+
+            switch (button.dataset.queueAction) {
+                case 'pause-job':
+                    downloadQueue.pauseJob(jobId);
+                    break;
+                case 'resume-job':
+                    downloadQueue.resumeJob(jobId);
+                    break;
+                case 'save-job':
+                    downloadQueue.saveJob(jobId).catch((err) => console.error('Saving download failed:', err));
+                    break;
+                case 'retry-job':
+                    downloadQueue.retryFailed(jobId);
+                    break;
+                case 'cancel-job':
+                    void downloadQueue.cancelJob(jobId);
+                    break;
+                case 'pause-item':
+                    downloadQueue.pauseItem(itemId);
+                    break;
+                case 'resume-item':
+                    downloadQueue.resumeItem(itemId);
+                    break;
+                case 'toggle-items':
+                    if (!expandedDownloadJobs.delete(jobId)) expandedDownloadJobs.add(jobId);
+                    renderDownloadQueueContent(container);
+                    break;
+            }
+        });
+        container._downloadQueueListenersAttached = true;
+    }
+
+    const jobs = downloadQueue.getJobs();
+    if (jobs.length === 0) {
+        container.innerHTML = '<div class="placeholder-text">No downloads in the queue.</div>';
+        return;
+    }
+    // Newest first
+    container.innerHTML = `<div class="download-queue-list">${jobs
+        .slice()
+        .reverse()
+        .map(renderDownloadJobHTML)
+        .join('')}</div>`;
+}
+
+export function openDownloadQueuePanel() {
+    sidePanelManager.open('downloads', 'Downloads', renderDownloadQueueControls, renderDownloadQueueContent, true);
+}
+
+window.addEventListener('download-queue-changed', () => {
+    if (downloadQueueRenderFrame || !sidePanelManager.isActive('downloads')) return;
+    downloadQueueRenderFrame = requestAnimationFrame(() => {
+        downloadQueueRenderFrame = null;
+        sidePanelManager.refresh('downloads', renderDownloadQueueControls, renderDownloadQueueContent, {
+            noClear: true,
+        });
+    });
+});
+
 /**
  *
  * @param {HTMLElement} notifEl
@@ -1094,6 +1346,9 @@ export async function downloadTrackWithMetadata(
         return;
     }
 
+    quality = await chooseDownloadQuality(quality);
+    if (!quality) return;
+
     const { enrichedTrack } = await tidalAPI.enrichTrack(track, { downloadQuality: quality });
     const filename = buildTrackFilename(enrichedTrack, quality);
 
@@ -1138,6 +1393,7 @@ export async function downloadTrackWithMetadata(
 
         // Write to folder using IBulkDownloadWriter.write() via singleWriterEntry().
         await folderWriter.write(singleWriterEntry({ name: entryName, lastModified: new Date(), input: blob }));
+        keepDownloadOffline(enrichedTrack, quality, blob);
 
         if (lyricsManager && lyricsSettings.shouldDownloadLyrics()) {
             try {
@@ -1157,10 +1413,10 @@ export async function downloadTrackWithMetadata(
         }
 
         // If the target is the local media folder, do a cheap partial update:
-        // pass the downloaded blob and base filename so only this one track's metadata
+        // pass the downloaded blob and its path in the folder so only this one track's metadata
         // is read and inserted into localFilesCache instead of re-walking the whole folder.
         if (modernSettings.bulkDownloadMethod === BulkDownloadMethod.LocalMedia) {
-            window.refreshLocalMediaFolder?.(blob, finalFilename);
+            window.refreshLocalMediaFolder?.(blob, entryName);
         }
 
         completeDownloadTask(track.id, true);
@@ -0,0 +1,225 @@
+// js/dynamics-processor.js
+// Compressor and look-ahead limiter DSP used by the dynamics AudioWorklet.
+// Operates on planar Float32Array channels, one render quantum at a time; kept free of
+// Web Audio globals so it can run (and be tested) outside the worklet scope.
+
+export const DYNAMICS_DEFAULTS = {
+    compressorEnabled: false,
+    threshold: -24, // dBFS
+    ratio: 3,
+    knee: 6, // dB
+    attack: 10, // ms
+    release: 200, // ms
+    makeupGain: 0, // dB
+    limiterEnabled: false,
+    ceiling: -1, // dBFS
+    lookahead: 5, // ms
+    limiterRelease: 80, // ms
+};
+
+const LIMITS = {
+    threshold: [-60, 0],
+    ratio: [1, 20],
+    knee: [0, 24],
+    attack: [0.1, 200],
+    release: [10, 2000],
+    makeupGain: [0, 24],
+    ceiling: [-12, 0],
+    lookahead: [1, 20],
+    limiterRelease: [10, 1000],
+};
+
+const dbToGain = (db) => Math.pow(10, db / 20);
+const gainToDb = (gain) => 20 * Math.log10(Math.max(gain, 1e-9));
+const timeCoef = (ms, sampleRate) => Math.exp(-1 / ((ms / 1000) * sampleRate));
+
+/**
+ * Fill in defaults and clamp every parameter to its supported range
+ */
+export function sanitizeDynamicsSettings(settings = {}) {
+    const result = { ...DYNAMICS_DEFAULTS };
+    result.compressorEnabled = settings.compressorEnabled === true;
+    result.limiterEnabled = settings.limiterEnabled === true;
+    for (const [key, [min, max]] of Object.entries(LIMITS)) {
+        const value = parseFloat(settings[key]);
+        if (Number.isFinite(value)) result[key] = Math.max(min, Math.min(max, value));
+    }
+    return result;
+}
+
+/**
+ * Static compressor curve: gain change in dB (<= 0) for a detector level, with a quadratic soft knee
+ */
+export function compressorGainDb(levelDb, threshold, ratio, knee) {
+    const over = levelDb - threshold;
+    const slope = 1 / ratio - 1;
+    if (knee > 0 && Math.abs(over) <= knee / 2) {
+        return (slope * Math.pow(over + knee / 2, 2)) / (2 * knee);
+    }
+    return over > 0 ? slope * over : 0;
+}
+
+export class DynamicsProcessor {
+    /**
+     * @param {number} sampleRate
+     * @param {object} [settings] - See DYNAMICS_DEFAULTS
+     */
+    constructor(sampleRate, settings = {}) {
+        this.sampleRate = sampleRate;
+        this.channels = 0;
+        this.settings = null;
+        this.compEnvDb = 0;
+        this.configure(settings);
+        this.takeMeter();
+    }
+
+    configure(settings) {
+        const previous = this.settings;
+        this.settings = sanitizeDynamicsSettings({ ...previous, ...settings });
+        const s = this.settings;
+
+        this.attackCoef = timeCoef(s.attack, this.sampleRate);
+        this.releaseCoef = timeCoef(s.release, this.sampleRate);
+        this.limiterReleaseCoef = timeCoef(s.limiterRelease, this.sampleRate);
+        this.ceilingGain = dbToGain(s.ceiling);
+        this.makeup = dbToGain(s.makeupGain);
+
+        const lookahead = Math.max(1, Math.round((s.lookahead / 1000) * this.sampleRate));
+        if (!previous || lookahead !== this.lookahead || s.limiterEnabled !== previous.limiterEnabled) {
+            this.lookahead = lookahead;
+            this._resetLimiter(this.channels);
+        }
+        if (!s.compressorEnabled) this.compEnvDb = 0;
+    }
+
+    /**
+     * Latency added by the limiter, in samples
+     */
+    getLatency() {
+        return this.settings.limiterEnabled ? this.lookahead : 0;
+    }
+
+    /**
+     * Largest gain reduction (positive dB) per stage since the last call
+     * @returns {{compressor: number, limiter: number}}
+     */
+    takeMeter() {
+        const meter = {
+            compressor: -this.meterCompDb || 0,
+            limiter: -gainToDb(this.meterLimGain ?? 1) || 0,
+        };
+        this.meterCompDb = 0;
+        this.meterLimGain = 1;
+        return meter;
+    }
+
+    _resetLimiter(channels) {
+        const size = this.lookahead;
+        this.channels = channels;
+        this.delay = Array.from({ length: channels }, () => new Float32Array(size));
+        this.delayIndex = 0;
+        // Sliding-window minimum of the target gain over lookahead + 1 samples (monotonic deque)
+        this.minValues = new Float32Array(size + 1);
+        this.minStamps = new Float64Array(size + 1);
+        this.minHead = 0;
+        this.minLength = 0;
+        this.sampleIndex = 0;
+        // Moving average over the lookahead, so gain ramps down in time for the peak
+        this.smoothBuffer = new Float32Array(size).fill(1);
+        this.smoothIndex = 0;
+        this.smoothSum = size;
+        this.releaseGain = 1;
+    }
+
+    _pushMin(value) {
+        const capacity = this.minValues.length;
+        // Expire the front once it leaves the window
+        if (this.minLength > 0 && this.minStamps[this.minHead] <= this.sampleIndex - capacity) {
+            this.minHead = (this.minHead + 1) % capacity;
+            this.minLength--;
+        }
+        // Drop values at the back that can never be the minimum again
+        while (this.minLength > 0) {
+            const back = (this.minHead + this.minLength - 1) % capacity;
+            if (this.minValues[back] < value) break;
+            this.minLength--;
+        }
+        const slot = (this.minHead + this.minLength) % capacity;
+        this.minValues[slot] = value;
+        this.minStamps[slot] = this.sampleIndex;
+        this.minLength++;
+        this.sampleIndex++;
+        return this.minValues[this.minHead];
+    }
+
+    /**
+     * Process one block. Input and output may be the same arrays.
+     * @param {Float32Array[]} input
+     * @param {Float32Array[]} output
+     */
+    process(input, output) {
+        const channels = Math.min(input.length, output.length);
+        if (channels === 0) return;
+        const frames = output[0].length;
+        const s = this.settings;
+
+        if (!s.compressorEnabled && !s.limiterEnabled) {
+            for (let ch = 0; ch < channels; ch++) {
+                if (output[ch] !== input[ch]) output[ch].set(input[ch]);
+            }
+            return;
+        }
+        if (s.limiterEnabled && channels !== this.channels) {
+            this._resetLimiter(channels);
+        }
+
+        for (let i = 0; i < frames; i++) {
+            let peak = 0;
+            for (let ch = 0; ch < channels; ch++) {
+                const abs = Math.abs(input[ch][i]);
+                if (abs > peak) peak = abs;
+            }
+
+            // Compressor: linked-channel peak detector with attack/release smoothing in dB
+            let gain = 1;
+            if (s.compressorEnabled) {
+                const target = compressorGainDb(gainToDb(peak), s.threshold, s.ratio, s.knee);
+                const coef = target < this.compEnvDb ? this.attackCoef : this.releaseCoef;
+                this.compEnvDb = coef * this.compEnvDb + (1 - coef) * target;
+                if (this.compEnvDb < this.meterCompDb) this.meterCompDb = this.compEnvDb;
+                gain = dbToGain(this.compEnvDb) * this.makeup;
+            }
+
+            if (!s.limiterEnabled) {
+                for (let ch = 0; ch < channels; ch++) {
+                    output[ch][i] = input[ch][i] * gain;
+                }
+                continue;
+            }
+
+            // Limiter: gain needed for this sample, held across the lookahead window, released slowly,
+            // then averaged over the window so it reaches the target exactly when the delayed peak arrives
+            const level = peak * gain;
+            const needed = level > this.ceilingGain ? this.ceilingGain / level : 1;
+            const held = this._pushMin(needed);
+            this.releaseGain =
+                held < this.releaseGain
+                    ? held
+                    : held + this.limiterReleaseCoef * (this.releaseGain - held);
+
+            this.smoothSum += this.releaseGain - this.smoothBuffer[this.smoothIndex];
+            this.smoothBuffer[this.smoothIndex] = this.releaseGain;
+            this.smoothIndex = (this.smoothIndex + 1) % this.lookahead;
+            const limGain = Math.min(1, this.smoothSum / this.lookahead);
+            if (limGain < this.meterLimGain) this.meterLimGain = limGain;
+
+            for (let ch = 0; ch < channels; ch++) {
+                const line = this.delay[ch];
+                const delayed = line[this.delayIndex];
+                line[this.delayIndex] = input[ch][i] * gain;
+                output[ch][i] = delayed * limGain;
+            }
+            this.delayIndex = (this.delayIndex + 1) % this.lookahead;
+        }
+    }
+}
@@ -0,0 +1,36 @@
+import { DynamicsProcessor } from './dynamics-processor.js';
+
+// Gain-reduction readings are posted to the main thread at roughly this rate
+const METER_INTERVAL_SECONDS = 0.05;
+
+class DynamicsWorkletProcessor extends AudioWorkletProcessor {
+    constructor(options) {
+        super();
+        this.dsp = new DynamicsProcessor(sampleRate, options.processorOptions?.settings);
+        this.meterInterval = Math.round(sampleRate * METER_INTERVAL_SECONDS);
+        this.framesSinceMeter = 0;
+
+        this.port.onmessage = (e) => {
+            if (e.data?.type === 'configure') {
+                this.dsp.configure(e.data.settings);
+            }
+        };
+    }
+
+    process(inputs, outputs) {
+        const input = inputs[0];
+        const output = outputs[0];
+        if (!input || input.length === 0) return true;
+
+        this.dsp.process(input, output);
+
+        this.framesSinceMeter += output[0].length;
+        if (this.framesSinceMeter >= this.meterInterval) {
+            this.framesSinceMeter = 0;
+            this.port.postMessage({ type: 'meter', ...this.dsp.takeMeter() });
+        }
+        return true;
+    }
+}
+
+registerProcessor('dynamics-processor', DynamicsWorkletProcessor);
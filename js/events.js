@@ -28,6 +28,7 @@ import { partyManager } from './listening-party.js';
 import { MusicAPI } from './music-api.js';
 import { LyricsManager } from './lyrics.js';
 import { Player } from './player.js';
+import { getCatalogTrack } from './local-matcher.js';
 
 let currentTrackIdForWaveform = null;
 
@@ -223,6 +224,7 @@ function toggleTrackSelection(trackItem, ctrlHeld, shiftHeld) {
 
     trackSelection.isSelecting = trackSelection.selectedIds.size > 0;
     document.body.classList.toggle('multi-select-mode', trackSelection.isSelecting);
+    updateSelectionBar();
 }
 
 async function showMultiSelectPlaylistModal(tracks) {
@@ -350,6 +352,7 @@ function updateSelectionBar() {
                 <button data-action="add-to-playlist-selected">Add to playlist</button>
                 <button data-action="download-selected">Download</button>
                 <button data-action="like-selected">Like</button>
+                <button data-action="edit-tags-selected">Edit tags</button>
             </div>
             <button data-action="clear-selection" style="margin-left: 8px;">Clear</button>
             `;
@@ -363,6 +366,16 @@ function updateSelectionBar() {
     const count = trackSelection.selectedIds.size;
     bar.querySelector('.selection-count').textContent = `${count} selected`;
     bar.classList.toggle('visible', count > 0);
+
+    // Local files can only be tagged, not liked or downloaded
+    const selectedItems = Array.from(document.querySelectorAll('#main-content .track-item.selected'));
+    const allLocal = selectedItems.length > 0 && selectedItems.every((item) => item.dataset.isLocal === 'true');
+    const remoteOnly = ['download-selected', 'like-selected'];
+    bar.querySelectorAll('.selection-actions button').forEach((btn) => {
+        const { action } = btn.dataset;
+        const hidden = action === 'edit-tags-selected' ? !allLocal : allLocal && remoteOnly.includes(action);
+        btn.style.display = hidden ? 'none' : '';
+    });
 }
 
 async function handleSelectionAction(action) {
@@ -418,6 +431,12 @@ async function handleSelectionAction(action) {
             }
             showNotification(`Liked ${selectedTracks.length} tracks`);
             break;
+        case 'edit-tags-selected':
+            if (selectedTracks.length > 0) {
+                const { openTagEditor } = await import('./tag-editor.js');
+                await openTagEditor(selectedTracks);
+            }
+            break;
         case 'clear-selection':
             clearSelection();
             break;
@@ -480,7 +499,8 @@ export async function initializePlayerEvents(player, audioPlayer, scrobbler, ui)
                 }
 
                 if (scrobbler.isAuthenticated()) {
-                    scrobbler.updateNowPlaying(player.currentTrack);
+                    // Matched local files scrobble with the catalog's metadata
+                    scrobbler.updateNowPlaying(getCatalogTrack(player.currentTrack) || player.currentTrack);
                 }
 
                 await updateWaveform();
@@ -507,8 +527,7 @@ export async function initializePlayerEvents(player, audioPlayer, scrobbler, ui)
             player.updateMediaSessionPositionState();
         });
 
-        element.addEventListener('ended', () => {
-            if (player.activeElement !== element) return;
+        const handleTrackEnded = () => {
             const elapsedPlayTime = listeningTracker.getSessionSignals().accumulatedPlayTime || 0;
             const trackDur = listeningTracker.getSessionSignals().trackDuration || 0;
             listeningTracker.onTrackEnd();
@@ -519,12 +538,24 @@ export async function initializePlayerEvents(player, audioPlayer, scrobbler, ui)
             listeningTracker.forceFlush();
             _previousTrackId = null;
             void player.playNext(0, { preserveGestureToken: true });
+        };
+
+        element.addEventListener('ended', () => {
+            if (player.activeElement !== element) return;
+            handleTrackEnded();
         });
 
         element.addEventListener('timeupdate', async () => {
             if (player.activeElement !== element) return;
 
-            const { currentTime, duration } = element;
+            // Tracks split from a CUE sheet end before their file does
+            if (player.hasReachedTrackEnd(element) && !element.paused) {
+                element.pause();
+                handleTrackEnded();
+                return;
+            }
+
+            const { currentTime, duration } = player.getPosition(element);
             if (duration) {
                 const progressFill = document.getElementById('progress-fill');
                 const currentTimeEl = document.getElementById('current-time');
@@ -535,8 +566,11 @@ export async function initializePlayerEvents(player, audioPlayer, scrobbler, ui)
 
                 if (currentTime >= 10 && player.currentTrack && player.currentTrack.id !== historyLoggedTrackId) {
                     historyLoggedTrackId = player.currentTrack.id;
-                    const historyEntry = await db.addToHistory(player.currentTrack);
+                    const historyEntry = await db.addToHistory(
+                        getCatalogTrack(player.currentTrack) || player.currentTrack
+                    );
                     await syncManager.syncHistoryItem(historyEntry);
+                    window.dispatchEvent(new CustomEvent('history-changed'));
 
                     if (window.location.hash === '#recent') {
                         ui.renderRecentPage();
@@ -548,7 +582,7 @@ export async function initializePlayerEvents(player, audioPlayer, scrobbler, ui)
         element.addEventListener('loadedmetadata', () => {
             if (player.activeElement !== element) return;
             const totalDurationEl = document.getElementById('total-duration');
-            totalDurationEl.textContent = formatTime(element.duration);
+            totalDurationEl.textContent = formatTime(player.getPosition(element).duration);
             player.updateMediaSessionPositionState();
         });
 
@@ -821,7 +855,7 @@ function initializeSmoothSliders(player) {
         if (!isNaN(activeEl.duration)) {
             progressFill.style.width = `${position * 100}%`;
             if (currentTimeEl) {
-                currentTimeEl.textContent = formatTime(position * activeEl.duration);
+                currentTimeEl.textContent = formatTime(position * player.getPosition().duration);
             }
         }
     };
@@ -913,7 +947,7 @@ function initializeSmoothSliders(player) {
             const activeEl = player.activeElement;
             // Commit the seek
             if (!isNaN(activeEl.duration)) {
-                activeEl.currentTime = lastSeekPosition * activeEl.duration;
+                player.seekTo(lastSeekPosition * player.getPosition().duration);
                 player.updateMediaSessionPositionState();
                 if (wasPlaying) activeEl.play();
             }
@@ -929,7 +963,7 @@ function initializeSmoothSliders(player) {
         if (isSeeking) {
             const activeEl = player.activeElement;
             if (!isNaN(activeEl.duration)) {
-                activeEl.currentTime = lastSeekPosition * activeEl.duration;
+                player.seekTo(lastSeekPosition * player.getPosition().duration);
                 player.updateMediaSessionPositionState();
                 if (wasPlaying) activeEl.play();
             }
@@ -947,7 +981,7 @@ function initializeSmoothSliders(player) {
             // Only handle click if not result of a drag release
             seek(progressBar, e, (position) => {
                 if (!isNaN(activeEl.duration) && activeEl.duration > 0 && activeEl.duration !== Infinity) {
-                    activeEl.currentTime = position * activeEl.duration;
+                    player.seekTo(position * player.getPosition().duration);
                     player.updateMediaSessionPositionState();
                 } else if (player.currentTrack && player.currentTrack.duration) {
                     const targetTime = position * player.currentTrack.duration;
@@ -2661,10 +2695,10 @@ export function initializeTrackInteractions(player, api, mainContent, contextMen
     if (nowPlayingAddPlaylistBtn) {
         nowPlayingAddPlaylistBtn.addEventListener('click', async (e) => {
             e.stopPropagation();
-            if (player.currentTrack) {
+            if (getCatalogTrack(player.currentTrack)) {
                 await handleTrackAction(
                     'add-to-playlist',
-                    player.currentTrack,
+                    getCatalogTrack(player.currentTrack),
                     player,
                     api,
                     lyricsManager,
@@ -2682,10 +2716,10 @@ export function initializeTrackInteractions(player, api, mainContent, contextMen
     if (mobileAddPlaylistBtn) {
         mobileAddPlaylistBtn.addEventListener('click', async (e) => {
             e.stopPropagation();
-            if (player.currentTrack) {
+            if (getCatalogTrack(player.currentTrack)) {
                 await handleTrackAction(
                     'add-to-playlist',
-                    player.currentTrack,
+                    getCatalogTrack(player.currentTrack),
                     player,
                     api,
                     lyricsManager,
@@ -1,5 +1,33 @@
 import { expect, test } from 'vitest';
-import { ffmpeg } from './ffmpeg';
+import { ffmpeg, loadFfmpeg } from './ffmpeg';
+import { getCustomFormat, transcodeWithCustomFormat, transcodingProfiles } from './ffmpegFormats';
+import FfmpegWorker from './ffmpeg.worker.js?worker';
+
+/** A 16-bit mono 440 Hz sine wave as a WAV file */
+function createWav(seconds: number, sampleRate = 44100): Blob {
+    const samples = Math.round(seconds * sampleRate);
+    const view = new DataView(new ArrayBuffer(44 + samples * 2));
+    const writeString = (offset: number, text: string) =>
+        [...text].forEach((char, i) => view.setUint8(offset + i, char.charCodeAt(0)));
+
+    writeString(0, 'RIFF');
+    view.setUint32(4, 36 + samples * 2, true);
+    writeString(8, 'WAVE');
+    writeString(12, 'fmt ');
+    view.setUint32(16, 16, true);
+    view.setUint16(20, 1, true);
+    view.setUint16(22, 1, true);
+    view.setUint32(24, sampleRate, true);
+    view.setUint32(28, sampleRate * 2, true);
+    view.setUint16(32, 2, true);
+    view.setUint16(34, 16, true);
+    writeString(36, 'data');
+    view.setUint32(40, samples * 2, true);
+    for (let i = 0; i < samples; i++) {
+        view.setInt16(44 + i * 2, Math.round(Math.sin((2 * Math.PI * 440 * i) / sampleRate) * 16000), true);
+    }
+    return new Blob([view.buffer], { type: 'audio/wav' });
+}
 
 test('Run `ffmpeg --help`', async () => {
     const lines: string[] = [];
@@ -17,3 +45,55 @@ test('Run `ffmpeg --help`', async () => {
     expect(lines).length.greaterThan(0);
     expect(lines[0]).matches(/ffmpeg version/i);
 });
+
+test.each([
+    ['FFMPEG_OPUS_160', /Audio: opus/],
+    ['FFMPEG_MP3_V0', /Audio: mp3/],
+    ['FFMPEG_AAC_256', /Audio: aac/],
+])('transcoding profile %s encodes with its codec', async (format, codec) => {
+    expect(transcodingProfiles.map((profile) => profile.format)).toContain(format);
+
+    const customFormat = getCustomFormat(format)!;
+    const lines: string[] = [];
+    const blob = await transcodeWithCustomFormat(createWav(1), customFormat, (progress) => {
+        if (progress.stage == 'stdout') lines.push(progress.message);
+    });
+
+    expect(blob.type).toBe(customFormat.outputMime);
+    expect(blob.size).toBeGreaterThan(0);
+    const output = lines.slice(lines.findIndex((line) => line.startsWith('Output #0')));
+    expect(output.some((line) => codec.test(line))).toBe(true);
+});
+
+test('the worker tracks the progress of every run on its own', async () => {
+    const worker = new FfmpegWorker();
+    const loadOptions = await loadFfmpeg();
+    const { ffmpegArgs, outputFilename, outputMime } = getCustomFormat('FFMPEG_MP3_V0')!;
+
+    const run = async (seconds: number) => {
+        const audioData = await createWav(seconds).arrayBuffer();
+        const messages: { type: string; stage?: string; message?: string }[] = [];
+        await new Promise<void>((resolve, reject) => {
+            worker.onmessage = (e) => {
+                messages.push(e.data);
+                if (e.data.type === 'complete') resolve();
+                if (e.data.type === 'error') reject(new Error(e.data.message));
+            };
+            worker.postMessage({
+                audioData,
+                args: ffmpegArgs,
+                output: { name: outputFilename, mime: outputMime },
+                loadOptions,
+            });
+        });
+        return messages.filter((m) => m.stage === 'parsing').map((m) => m.message);
+    };
+
+    try {
+        expect(await run(1)).toEqual(['Detected duration: 1s']);
+        // Without a reset the second run would keep the first run's duration
+        expect(await run(2)).toEqual(['Detected duration: 2s']);
+    } finally {
+        worker.terminate();
+    }
+});
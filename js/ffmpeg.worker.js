@@ -87,9 +87,6 @@ async function loadFFmpeg(loadOptions = {}) {
         self.postMessage({ type: 'progress', stage: 'loading', message: 'Loading FFmpeg...' });
 
         await ffmpeg.load(loadOptions);
-        // Reset progress state for each run
-        totalDurationSeconds = null;
-        lastProgress = 0;
     })();
 
     return loadingPromise;
@@ -113,6 +110,10 @@ self.onmessage = async (e) => {
     try {
         await loadFFmpeg(loadOptions);
 
+        // Reset progress state for each run
+        totalDurationSeconds = null;
+        lastProgress = 0;
+
         self.postMessage({ type: 'progress', stage: 'encoding', message: encodeStartMessage, progress: 0.0 });
 
         try {
@@ -55,6 +55,14 @@ export const customFormats: Record<string, CustomFormat> = {
         extension: 'mp3',
         category: 'MP3',
     },
+    FFMPEG_MP3_V0: {
+        displayName: 'MP3 V0 (~245kbps VBR)',
+        ffmpegArgs: ['-map_metadata', '-1', '-c:a', 'libmp3lame', '-q:a', '0', '-ar', '44100'],
+        outputFilename: 'output.mp3',
+        outputMime: 'audio/mpeg',
+        extension: 'mp3',
+        category: 'MP3',
+    },
     FFMPEG_MP3_128: {
         displayName: 'MP3 128kbps',
         ffmpegArgs: ['-map_metadata', '-1', '-c:a', 'libmp3lame', '-b:a', '128k', '-ar', '44100'],
@@ -182,6 +190,25 @@ if (import.meta.env.DEV) {
     };
 }
 
+export interface TranscodingProfile {
+    /** Short name shown in the per-download picker */
+    name: string;
+    /** One-line hint about where the profile makes sense */
+    description: string;
+    /** Internal name of the custom format used for encoding */
+    format: string;
+}
+
+/**
+ * Named transcoding profiles for portable players and phones with limited storage.
+ * Offered in the per-download picker alongside the lossless qualities.
+ */
+export const transcodingProfiles: TranscodingProfile[] = [
+    { name: 'Opus 160k', description: 'Smallest files, transparent for most listeners', format: 'FFMPEG_OPUS_160' },
+    { name: 'MP3 V0', description: 'Plays on virtually any device', format: 'FFMPEG_MP3_V0' },
+    { name: 'AAC 256k', description: 'Apple devices and car stereos', format: 'FFMPEG_AAC_256' },
+];
+
 /**
  * Container format definitions for lossless re-muxing.  Each entry describes
  * the ffmpeg arguments needed to produce that container and provides a
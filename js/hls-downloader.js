@@ -1,18 +1,18 @@
-import { SegmentedDownloadProgress } from './progressEvents';
 import { getProxyUrl } from './proxy-utils';
+import { downloadSegments, fetchWithRetry } from './resumable-download.js';
 
 export class HlsDownloader {
     constructor() {}
 
     async downloadHlsStream(masterUrl, options = {}) {
-        const { onProgress, signal } = options;
+        const { onProgress, signal, checkpointKey = null } = options;
 
-        const response = await fetch(getProxyUrl(masterUrl), { signal });
+        const response = await fetchWithRetry(getProxyUrl(masterUrl), { signal });
         const masterText = await response.text();
 
         const variantUrl = this.getBestVariantUrl(masterUrl, masterText);
 
-        const mediaResponse = await fetch(getProxyUrl(variantUrl), { signal });
+        const mediaResponse = await fetchWithRetry(getProxyUrl(variantUrl), { signal });
         const mediaText = await mediaResponse.text();
 
         const segments = this.parseMediaPlaylist(variantUrl, mediaText);
@@ -20,31 +20,15 @@ export class HlsDownloader {
             throw new Error('No segments found in HLS playlist');
         }
 
-        const chunks = [];
-        let downloadedBytes = 0;
-        const totalSegments = segments.length;
-
-        for (let i = 0; i < totalSegments; i++) {
-            if (signal?.aborted) throw new Error('AbortError');
-
-            onProgress?.(new SegmentedDownloadProgress(downloadedBytes, undefined, i, totalSegments));
-
-            const segmentUrl = segments[i];
-            const segmentResponse = await fetch(getProxyUrl(segmentUrl), { signal });
-
-            if (!segmentResponse.ok) {
-                throw new Error(`Failed to fetch segment ${i}: ${segmentResponse.status}`);
-            }
-
-            const chunk = await segmentResponse.arrayBuffer();
-            chunks.push(chunk);
-            downloadedBytes += chunk.byteLength;
-
-            onProgress?.(new SegmentedDownloadProgress(downloadedBytes, undefined, i + 1, totalSegments));
-        }
-
         const mimeType = segments[0].endsWith('.m4s') || segments[0].includes('mp4') ? 'video/mp4' : 'video/mp2t';
-        return new Blob(chunks, { type: mimeType });
+
+        return downloadSegments(segments.map((segmentUrl) => getProxyUrl(segmentUrl)), {
+            checkpointKey,
+            fingerprint: `hls:${mimeType}`,
+            signal,
+            onProgress,
+            mimeType,
+        });
     }
 
     getBestVariantUrl(masterUrl, masterText) {
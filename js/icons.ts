@@ -22,6 +22,7 @@ export { default as SVG_GLOBE } from '!lucide/globe.svg?svg&icon';
 export { default as SVG_HAND_HEART } from '!lucide/hand-heart.svg?svg&icon';
 export { default as SVG_HEART } from '!lucide/heart.svg?svg&icon&class=heart-icon';
 export { default as SVG_HEART_FILLED } from '!lucide/heart.svg?svg&icon&class=heart-icon+filled';
+export { default as SVG_HISTORY } from '!lucide/history.svg?svg&icon';
 export { default as SVG_HOUSE } from '!lucide/house.svg?svg&icon';
 export { default as SVG_INFO } from '!lucide/info.svg?svg&icon';
 export { default as SVG_INSTAGRAM } from '../images/instagram.svg?svg&icon';
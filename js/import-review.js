@@ -0,0 +1,223 @@
+// js/import-review.js
+// Review step between parsing an imported playlist and adding it. Every source row is listed with the
+// track that was picked for it, its match score and the other search results, so wrong picks can be
+// swapped, searched for manually or left out before anything is written to the library.
+
+import { applyImportReview, rankCandidates, scoreCandidate } from './playlist-importer.js';
+import { escapeHtml, getTrackArtists } from './utils.js';
+
+/** Matches scoring below this are listed under "Needs review" */
+export const REVIEW_THRESHOLD = 0.8;
+
+let closeImportReview = null;
+
+/** Whether a row still needs a look from the user */
+export function needsReview(entry) {
+    return !entry.reviewed && (!entry.match || entry.score < REVIEW_THRESHOLD);
+}
+
+/**
+ * Pick another track for a row, or none to leave it out
+ * @param {Object} entry - Entry of a parse result's `matches`
+ * @param {Object|null} track
+ */
+export function chooseMatch(entry, track) {
+    entry.match = track;
+    entry.score = track ? scoreCandidate(track, entry.source) : 0;
+    entry.reviewed = true;
+}
+
+/**
+ * Add manual search results to a row's candidates and pick the best of them
+ * @param {Object} entry - Entry of a parse result's `matches`
+ * @param {Array} items - Search results
+ */
+export function addSearchResults(entry, items) {
+    const found = rankCandidates(items, entry.source);
+    if (found.length === 0) return false;
+
+    const ids = new Set(found.map((candidate) => candidate.track.id));
+    entry.candidates = [...found, ...entry.candidates.filter((candidate) => !ids.has(candidate.track.id))];
+    chooseMatch(entry, found[0].track);
+    return true;
+}
+
+function getScoreClass(entry) {
+    if (!entry.match) return 'missing';
+    if (entry.score >= REVIEW_THRESHOLD) return 'high';
+    return entry.score >= 0.55 ? 'medium' : 'low';
+}
+
+function describeSource({ title, artist, album }) {
+    return `${escapeHtml(title || 'Unknown track')}<span>${escapeHtml(
+        [artist, album].filter(Boolean).join(' · ')
+    )}</span>`;
+}
+
+function createCandidateOptions(entry) {
+    const options = entry.candidates.map(({ track, score }) => {
+        const label = [`${track.title} — ${getTrackArtists(track)}`, track.album?.title].filter(Boolean).join(' · ');
+        const selected = entry.match && entry.match.id === track.id ? 'selected' : '';
+        return `<option value="${escapeHtml(String(track.id))}" ${selected}>${escapeHtml(label)} (${Math.round(
+            score * 100
+        )}%)</option>`;
+    });
+    options.push(`<option value="" ${entry.match ? '' : 'selected'}>Don't import</option>`);
+    return options.join('');
+}
+
+function createRow(entry, index) {
+    const scoreClass = getScoreClass(entry);
+    const row = document.createElement('div');
+    row.className = `import-review-row ${scoreClass}`;
+    row.dataset.index = index;
+    row.innerHTML = `
+        <div class="import-review-source">${describeSource(entry.source)}</div>
+        <div class="import-review-choice">
+            <select data-review-choice>${createCandidateOptions(entry)}</select>
+            <span class="import-review-score ${scoreClass}">${
+                entry.match ? `${Math.round(entry.score * 100)}%` : 'Not found'
+            }</span>
+            <button type="button" class="btn-icon import-review-accept" title="Accept" ${
+                entry.match && needsReview(entry) ? '' : 'hidden'
+            }>&#10003;</button>
+            <button type="button" class="btn-icon import-review-search-btn" title="Search manually">&#128269;</button>
+        </div>
+        <form class="import-review-search" hidden>
+            <input type="text" class="template-input" data-review-query
+                value="${escapeHtml([entry.source.title, entry.source.artist].filter(Boolean).join(' '))}" />
+            <button type="submit" class="btn-secondary">Search</button>
+        </form>
+    `;
+    return row;
+}
+
+/**
+ * Let the user review the matches of a parse result before it is imported
+ * @param {Object} result - Result of one of the playlist-importer parsers
+ * @param {import('./music-api.js').MusicAPI} api - Used for manual searches
+ * @returns {Promise<Object|null>} The result following the reviewed matches, or null if cancelled
+ */
+export function reviewImportMatches(result, api) {
+    const modal = document.getElementById('import-review-modal');
+    if (!modal || !Array.isArray(result.matches) || result.matches.length === 0) return Promise.resolve(result);
+
+    closeImportReview?.();
+
+    const entries = result.matches;
+    const list = modal.querySelector('#import-review-list');
+    const filterSelect = modal.querySelector('#import-review-filter');
+    const summary = modal.querySelector('#import-review-summary');
+    const confirmBtn = modal.querySelector('#import-review-confirm');
+
+    const updateSummary = () => {
+        const matched = entries.filter((entry) => entry.match).length;
+        const review = entries.filter((entry) => entry.match && needsReview(entry)).length;
+        const missing = entries.length - matched;
+        summary.textContent = `${matched} of ${entries.length} matched · ${review} to review · ${missing} not found`;
+        confirmBtn.textContent = `Import ${matched} track${matched === 1 ? '' : 's'}`;
+    };
+
+    const render = () => {
+        const filter = filterSelect.value;
+        list.innerHTML = '';
+        const fragment = document.createDocumentFragment();
+        entries.forEach((entry, index) => {
+            if (filter === 'review' && !needsReview(entry)) return;
+            if (filter === 'missing' && entry.match) return;
+            fragment.appendChild(createRow(entry, index));
+        });
+        list.appendChild(fragment);
+        if (!list.children.length) {
+            list.innerHTML = '<div class="placeholder-text">Nothing left to review.</div>';
+        }
+        updateSummary();
+    };
+
+    // Rows stay in place after a change so the list doesn't jump while working through it
+    const rerenderRow = (row) => {
+        const index = Number(row.dataset.index);
+        row.replaceWith(createRow(entries[index], index));
+        updateSummary();
+    };
+
+    filterSelect.value = entries.some(needsReview) ? 'review' : 'all';
+    render();
+
+    return new Promise((resolve) => {
+        const close = (value) => {
+            modal.classList.remove('active');
+            modal.removeEventListener('click', handleClick);
+            modal.removeEventListener('change', handleChange);
+            modal.removeEventListener('submit', handleSubmit);
+            closeImportReview = null;
+            resolve(value);
+        };
+
+        const handleChange = (e) => {
+            if (e.target === filterSelect) {
+                render();
+                return;
+            }
+            const row = e.target.closest('.import-review-row');
+            if (!row || !e.target.matches('[data-review-choice]')) return;
+            const entry = entries[Number(row.dataset.index)];
+            const candidate = entry.candidates.find(({ track }) => String(track.id) === e.target.value);
+            chooseMatch(entry, candidate?.track || null);
+            rerenderRow(row);
+        };
+
+        const handleSubmit = async (e) => {
+            const form = e.target.closest('.import-review-search');
+            if (!form) return;
+            e.preventDefault();
+
+            const row = form.closest('.import-review-row');
+            const query = form.querySelector('[data-review-query]').value.trim();
+            if (!query) return;
+
+            const button = form.querySelector('button');
+            button.disabled = true;
+            button.textContent = 'Searching...';
+            try {
+                const { items } = await api.searchTracks(query);
+                if (addSearchResults(entries[Number(row.dataset.index)], items)) {
+                    rerenderRow(row);
+                    return;
+                }
+                button.textContent = 'No results';
+            } catch (error) {
+                console.error('Import review search failed:', error);
+                button.textContent = 'Search failed';
+            }
+            button.disabled = false;
+        };
+
+        const handleClick = (e) => {
+            const row = e.target.closest('.import-review-row');
+            if (e.target.classList.contains('modal-overlay') || e.target.id === 'import-review-cancel') {
+                close(null);
+            } else if (e.target.id === 'import-review-confirm') {
+                close(applyImportReview(result));
+            } else if (e.target.id === 'import-review-accept-all') {
+                entries.forEach((entry) => {
+                    if (entry.match) entry.reviewed = true;
+                });
+                render();
+            } else if (row && e.target.closest('.import-review-accept')) {
+                entries[Number(row.dataset.index)].reviewed = true;
+                rerenderRow(row);
+            } else if (row && e.target.closest('.import-review-search-btn')) {
+                const form = row.querySelector('.import-review-search');
+                form.hidden = !form.hidden;
+                if (!form.hidden) form.querySelector('input').select();
+            }
+        };
+
+        closeImportReview = () => close(null);
+        modal.addEventListener('click', handleClick);
+        modal.addEventListener('change', handleChange);
+        modal.addEventListener('submit', handleSubmit);
+        modal.classList.add('active');
+    });
+}
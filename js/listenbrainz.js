@@ -1,150 +1,242 @@
 import { listenBrainzSettings, lastFMStorage } from './storage.js';
 
-export class ListenBrainzScrobbler {
-    constructor() {
-        this.DEFAULT_API_URL = 'https://api.listenbrainz.org';
-        this.currentTrack = null;
-        this.scrobbleTimer = null;
-        this.scrobbleThreshold = 0;
-        this.hasScrobbled = false;
-        this.isScrobbling = false;
-        this.lovingTracks = new Set();
+const DEFAULT_API_URL = 'https://api.listenbrainz.org';
+// Lookups made for downloads are spaced out, so a large job doesn't run into the rate limit
+const MBID_LOOKUP_INTERVAL_MS = 1000;
+const MBID_CACHE_SIZE = 500;
+const MAX_RATE_LIMIT_RETRIES = 3;
+const DEFAULT_RETRY_AFTER_S = 5;
+
+export function getListenBrainzApiUrl() {
+    const customUrl = listenBrainzSettings.getCustomUrl();
+    const base = customUrl || DEFAULT_API_URL;
+    return base.replace(/\/1\/?$/, '');
+}
+
+export function getListenMetadata(track) {
+    if (!track) return null;
+
+    let artistName = 'Unknown Artist';
+
+    if (track.artist?.name) {
+        artistName = track.artist.name;
+    } else if (typeof track.artist === 'string') {
+        artistName = track.artist;
+    } else if (track.artists && track.artists.length > 0) {
+        const first = track.artists[0];
+        artistName = typeof first === 'string' ? first : first.name || 'Unknown Artist';
     }
 
-    getApiUrl() {
-        const customUrl = listenBrainzSettings.getCustomUrl();
-        const base = customUrl || this.DEFAULT_API_URL;
-        return base.replace(/\/1\/?$/, '');
+    if (typeof artistName === 'string') {
+        artistName = artistName
+            .split(/\s*[&]\s*|\s+feat\.?\s*|\s+ft\.?\s*|\s+featuring\s+|\s+with\s+|\s+x\s+/i)[0]
+            .trim();
     }
 
-    isEnabled() {
-        return listenBrainzSettings.isEnabled() && !!listenBrainzSettings.getToken();
+    const payload = {
+        artist_name: artistName,
+        track_name: track.cleanTitle || track.title,
+        additional_info: {
+            submission_client: 'Monochrome',
+            submission_client_version: '1.0.0',
+        },
+    };
+
+    if (track.album?.title) {
+        payload.release_name = track.album.title;
     }
 
-    getToken() {
-        return listenBrainzSettings.getToken();
+    if (track.duration) {
+        payload.additional_info.duration = Math.floor(track.duration);
     }
 
-    _getMetadata(track) {
-        if (!track) return null;
+    if (track.trackNumber) {
+        payload.additional_info.track_number = track.trackNumber;
+    }
 
-        let artistName = 'Unknown Artist';
+    if (track.isLocal) {
+        payload.additional_info.is_local = true;
+    }
 
-        if (track.artist?.name) {
-            artistName = track.artist.name;
-        } else if (typeof track.artist === 'string') {
-            artistName = track.artist;
-        } else if (track.artists && track.artists.length > 0) {
-            const first = track.artists[0];
-            artistName = typeof first === 'string' ? first : first.name || 'Unknown Artist';
+    if (track.mbids) {
+        if (track.mbids.recording_mbid) {
+            payload.additional_info.recording_mbid = track.mbids.recording_mbid;
+        }
+        if (track.mbids.release_mbid) {
+            payload.additional_info.release_mbid = track.mbids.release_mbid;
+        }
+        if (track.mbids.artist_mbids) {
+            payload.additional_info.artist_mbids = track.mbids.artist_mbids;
         }
+    }
+
+    return payload;
+}
 
-        if (typeof artistName === 'string') {
-            artistName = artistName
-                .split(/\s*[&]\s*|\s+feat\.?\s*|\s+ft\.?\s*|\s+featuring\s+|\s+with\s+|\s+x\s+/i)[0]
-                .trim();
+/**
+ * Resolve MusicBrainz recording, release and artist IDs for a track through the ListenBrainz metadata
+ * lookup. The result is cached on `track.mbids`.
+ * @returns {Promise<{recording_mbid: string, release_mbid?: string, artist_mbids?: string[]}|null>}
+ */
+export async function lookupMbids(
+    track,
+    { apiUrl = getListenBrainzApiUrl(), token = listenBrainzSettings.getToken() } = {}
+) {
+    if (track.mbids?.recording_mbid) return track.mbids;
+    let with_album = true;
+    const metadata = getListenMetadata(track);
+    if (!metadata || !metadata.artist_name || !metadata.track_name) return null;
+
+    // The lookup endpoint also answers without a token, so downloads can use it with ListenBrainz disabled
+    const headers = token ? { Authorization: `Token ${token}` } : {};
+
+    const request = async (params) => {
+        for (let attempt = 0; ; attempt++) {
+            const response = await fetch(`${apiUrl}/1/metadata/lookup/?${params}`, { method: 'GET', headers });
+            if (response.status !== 429 || attempt >= MAX_RATE_LIMIT_RETRIES) return response;
+
+            const header = response.headers.get('Retry-After') ?? response.headers.get('X-RateLimit-Reset-In');
+            const retryAfter = parseFloat(header);
+            const wait = Number.isFinite(retryAfter) && retryAfter >= 0 ? retryAfter : DEFAULT_RETRY_AFTER_S;
+            console.warn(`[ListenBrainz] Rate limited, retrying MBID lookup in ${wait}s`);
+            await new Promise((resolve) => setTimeout(resolve, wait * 1000));
         }
+    };
 
-        const payload = {
-            artist_name: artistName,
-            track_name: track.cleanTitle || track.title,
-            additional_info: {
-                submission_client: 'Monochrome',
-                submission_client_version: '1.0.0',
-            },
-        };
+    try {
+        const params = new URLSearchParams({
+            recording_name: metadata.track_name,
+            artist_name: metadata.artist_name,
+        });
 
         if (track.album?.title) {
-            payload.release_name = track.album.title;
+            params.append('release_name', track.album.title);
         }
 
-        if (track.duration) {
-            payload.additional_info.duration = Math.floor(track.duration);
+        let response = await request(params);
+        if (response.status === 429) {
+            console.warn('[ListenBrainz] MBID lookup still rate limited, giving up');
+            return null;
         }
 
-        if (track.trackNumber) {
-            payload.additional_info.track_number = track.trackNumber;
+        if (!response.ok) {
+            console.warn(`[ListenBrainz] MBID lookup failed, trying without album`);
+            with_album = false;
+            const params = new URLSearchParams({
+                recording_name: metadata.track_name,
+                artist_name: metadata.artist_name,
+            });
+            response = await request(params);
+            if (!response.ok) {
+                console.warn(`[ListenBrainz] MBID lookup failed: ${response.status}`);
+                return null;
+            }
         }
 
-        if (track.isLocal) {
-            payload.additional_info.is_local = true;
+        const data = await response.json();
+        if (data?.recording_mbid) {
+            track.mbids = {
+                recording_mbid: data.recording_mbid,
+                artist_mbids: data.artist_mbids,
+            };
+            if (with_album) {
+                track.mbids.release_mbid = data.release_mbid;
+            }
+            console.log(`[ListenBrainz] Found MBID: ${data.recording_mbid}`);
+            return track.mbids;
         }
+        console.warn('[ListenBrainz] No recording_mbid found in lookup response');
+    } catch (error) {
+        console.error('[ListenBrainz] MBID lookup error:', error);
+    }
+    return null;
+}
 
-        if (track.mbids) {
-            if (track.mbids.recording_mbid) {
-                payload.additional_info.recording_mbid = track.mbids.recording_mbid;
+/**
+ * Queue of MBID lookups that runs one lookup at a time, at most one per `interval`, and shares the result
+ * between tracks of the same recording and release
+ * @param {{interval?: number, lookup?: typeof lookupMbids}} [options]
+ */
+export function createMbidLookupQueue({ interval = MBID_LOOKUP_INTERVAL_MS, lookup = lookupMbids } = {}) {
+    // Recording and release -> pending or resolved lookup
+    const cache = new Map();
+    let queue = Promise.resolve();
+    let lastLookupAt = -Infinity;
+
+    const run = (track) => {
+        const result = queue.then(async () => {
+            const wait = lastLookupAt + interval - Date.now();
+            if (wait > 0) await new Promise((resolve) => setTimeout(resolve, wait));
+            try {
+                return await lookup({ ...track });
+            } finally {
+                lastLookupAt = Date.now();
             }
-            if (track.mbids.release_mbid) {
-                payload.additional_info.release_mbid = track.mbids.release_mbid;
+        });
+        queue = result.catch(() => {});
+        return result;
+    };
+
+    return {
+        /**
+         * Same result as lookupMbids, also cached on `track.mbids`
+         * @returns {Promise<{recording_mbid: string, release_mbid?: string, artist_mbids?: string[]}|null>}
+         */
+        async lookup(track) {
+            if (track.mbids?.recording_mbid) return track.mbids;
+            const metadata = getListenMetadata(track);
+            if (!metadata?.artist_name || !metadata.track_name) return null;
+
+            const key = [metadata.artist_name, metadata.track_name, metadata.release_name || '']
+                .join('\n')
+                .toLowerCase();
+            if (!cache.has(key)) {
+                const pending = run(track);
+                cache.set(key, pending);
+                if (cache.size > MBID_CACHE_SIZE) cache.delete(cache.keys().next().value);
+                // Failed lookups and misses are tried again next time
+                pending.then((mbids) => mbids || cache.delete(key), () => cache.delete(key));
             }
-            if (track.mbids.artist_mbids) {
-                payload.additional_info.artist_mbids = track.mbids.artist_mbids;
-            }
-        }
 
-        return payload;
-    }
+            const mbids = await cache.get(key);
+            if (!mbids) return null;
+            track.mbids = { ...mbids };
+            return track.mbids;
+        },
+    };
+}
 
-    async _lookupMbids(track) {
-        if (track.mbids?.recording_mbid) return track.mbids;
-        let with_album = true;
-        const metadata = this._getMetadata(track);
-        if (!metadata || !metadata.artist_name || !metadata.track_name) return null;
+/** Shared by all downloads */
+export const mbidLookupQueue = createMbidLookupQueue();
 
-        try {
-            const apiUrl = this.getApiUrl();
-            const params = new URLSearchParams({
-                recording_name: metadata.track_name,
-                artist_name: metadata.artist_name,
-            });
+export class ListenBrainzScrobbler {
+    constructor() {
+        this.currentTrack = null;
+        this.scrobbleTimer = null;
+        this.scrobbleThreshold = 0;
+        this.hasScrobbled = false;
+        this.isScrobbling = false;
+        this.lovingTracks = new Set();
+    }
 
-            if (track.album?.title) {
-                params.append('release_name', track.album.title);
-            }
+    getApiUrl() {
+        return getListenBrainzApiUrl();
+    }
 
-            let response = await fetch(`${apiUrl}/1/metadata/lookup/?${params}`, {
-                method: 'GET',
-                headers: {
-                    Authorization: `Token ${this.getToken()}`,
-                },
-            });
+    isEnabled() {
+        return listenBrainzSettings.isEnabled() && !!listenBrainzSettings.getToken();
+    }
 
-            if (!response.ok) {
-                console.warn(`[ListenBrainz] MBID lookup failed, trying without album`);
-                with_album = false;
-                const params = new URLSearchParams({
-                    recording_name: metadata.track_name,
-                    artist_name: metadata.artist_name,
-                });
-                response = await fetch(`${apiUrl}/1/metadata/lookup/?${params}`, {
-                    method: 'GET',
-                    headers: {
-                        Authorization: `Token ${this.getToken()}`,
-                    },
-                });
-                if (!response.ok) {
-                    console.warn(`[ListenBrainz] MBID lookup failed: ${response.status}`);
-                    return null;
-                }
-            }
+    getToken() {
+        return listenBrainzSettings.getToken();
+    }
 
-            const data = await response.json();
-            if (data?.recording_mbid) {
-                track.mbids = {
-                    recording_mbid: data.recording_mbid,
-                    artist_mbids: data.artist_mbids,
-                };
-                if (with_album) {
-                    track.mbids.release_mbid = data.release_mbid;
-                }
-                console.log(`[ListenBrainz] Found MBID: ${data.recording_mbid}`);
-                return track.mbids;
-            }
-            console.warn('[ListenBrainz] No recording_mbid found in lookup response');
-        } catch (error) {
-            console.error('[ListenBrainz] MBID lookup error:', error);
-        }
-        return null;
+    _getMetadata(track) {
+        return getListenMetadata(track);
+    }
+
+    async _lookupMbids(track) {
+        return await lookupMbids(track, { apiUrl: this.getApiUrl(), token: this.getToken() });
     }
 
     async submitListen(listenType, track, timestamp = null) {
@@ -0,0 +1,487 @@
+// js/local-library.js
+// Persistent index of the local media folders. Every audio file is recorded in IndexedDB together
+// with its size and modification time, so a rescan only parses the tags of new or changed files and
+// everything else comes straight from the index. Several root folders can be indexed side by side;
+// the "main" root is the folder picked in Library > Local, which downloads and the tag editor use.
+
+import { db } from './db.js';
+import { decodeCueSheet, findCueImagePath, getCueTracks, isCueSheetFile, parseCueSheet } from './cue-sheet.js';
+
+export const LOCAL_AUDIO_EXTENSIONS = ['.flac', '.mp3', '.m4a', '.wav', '.ogg', '.opus', '.aif', '.aiff'];
+export const MAIN_ROOT_ID = 'main';
+
+const ROOTS_SETTING = 'local_library_roots';
+const SAVE_BATCH_SIZE = 200;
+const UNKNOWN_ARTIST = 'Unknown Artist';
+const UNKNOWN_ALBUM = 'Unknown Album';
+const DEFAULT_COVER = 'assets/appicon.png';
+
+// Object URLs of album covers, created once per session
+const coverUrls = new Map();
+
+export function isLocalAudioFile(name) {
+    const lower = name.toLowerCase();
+    return LOCAL_AUDIO_EXTENSIONS.some((ext) => lower.endsWith(ext));
+}
+
+export function getLocalLibraryKey(rootId, path) {
+    return `${rootId}:${path}`;
+}
+
+export function getAlbumKey(albumArtist, album) {
+    return `${(albumArtist || '').toLowerCase()}\u0000${(album || '').toLowerCase()}`;
+}
+
+/**
+ * Whether an index entry still describes the file on disk
+ * @param {object} entry
+ * @param {File} file
+ * @param {{lastModified: number}|null} [sheet] - CUE sheet next to the file
+ */
+export function isEntryCurrent(entry, file, sheet = null) {
+    return (
+        !!entry &&
+        entry.size === file.size &&
+        entry.lastModified === file.lastModified &&
+        (entry.cue?.sheetModified ?? null) === (sheet?.lastModified ?? null)
+    );
+}
+
+function splitArtists(artist) {
+    return (Array.isArray(artist) ? artist : String(artist || '').split(';'))
+        .map((name) => name.trim())
+        .filter(Boolean);
+}
+
+/**
+ * Index entry for a parsed file
+ * @param {string} rootId
+ * @param {string} path - Path relative to the root folder
+ * @param {File} file
+ * @param {import('./taglib.types.ts').TagLibReadMetadata|null} tags
+ */
+export function createLocalLibraryEntry(rootId, path, file, tags) {
+    const artists = splitArtists(tags?.artist);
+    const albumArtist = tags?.albumArtist || artists[0] || '';
+    const album = tags?.albumTitle || '';
+
+    return {
+        key: getLocalLibraryKey(rootId, path),
+        rootId,
+        path,
+        name: file.name,
+        size: file.size,
+        lastModified: file.lastModified,
+        title: tags?.title || file.name.replace(/\.[^/.]+$/, ''),
+        artists,
+        album,
+        albumArtist,
+        albumKey: getAlbumKey(albumArtist, album),
+        trackNumber: tags?.trackNumber || null,
+        discNumber: tags?.discNumber || null,
+        duration: tags?.duration || 0,
+        releaseDate: tags?.releaseDate || null,
+        isrc: tags?.isrc || null,
+        copyright: tags?.copyright || null,
+        explicit: !!tags?.explicit,
+        musicBrainz: tags?.musicBrainz || null,
+        hasCover: !!tags?.cover?.data?.length,
+        indexedAt: Date.now(),
+    };
+}
+
+/**
+ * Index entry for one track of a file split by a CUE sheet
+ * @param {string} rootId
+ * @param {string} path - Path of the audio file relative to the root folder
+ * @param {File} file
+ * @param {object|null} tags - Tags of the audio file
+ * @param {import('./cue-sheet.js').CueSheet} cue
+ * @param {ReturnType<typeof getCueTracks>[number]} cueTrack
+ * @param {number|null} [sheetModified] - Modification time of the sheet, null if it is embedded in the file
+ */
+export function createCueTrackEntry(rootId, path, file, tags, cue, cueTrack, sheetModified = null) {
+    const entry = createLocalLibraryEntry(rootId, path, file, tags);
+    const albumArtist = cue.performer || entry.albumArtist;
+    const album = cue.title || entry.album;
+    const number = String(cueTrack.number).padStart(2, '0');
+
+    return {
+        ...entry,
+        key: getLocalLibraryKey(rootId, `${path}#${number}`),
+        title: cueTrack.title || `Track ${number}`,
+        artists: splitArtists(cueTrack.performer || albumArtist),
+        album,
+        albumArtist,
+        albumKey: getAlbumKey(albumArtist, album),
+        trackNumber: cueTrack.number,
+        duration: cueTrack.end ? cueTrack.end - cueTrack.start : 0,
+        releaseDate: cue.date || entry.releaseDate,
+        isrc: cueTrack.isrc || null,
+        musicBrainz: null,
+        cue: { start: cueTrack.start, end: cueTrack.end, sheetModified },
+    };
+}
+
+/**
+ * Track object for an index entry, in the shape readTrackMetadata() returns
+ * @param {object} entry
+ * @param {{file?: File|null, fileHandle?: FileSystemFileHandle|null, cover?: string|null}} [options]
+ */
+export function toLocalTrack(entry, { file = null, fileHandle = null, cover = null } = {}) {
+    const artists = entry.artists.map((name) => ({ name }));
+    const artist = artists[0] || { name: UNKNOWN_ARTIST };
+
+    const track = {
+        id: `local-${entry.key}`,
+        title: entry.title,
+        artists,
+        artist,
+        album: {
+            title: entry.album || UNKNOWN_ALBUM,
+            cover: cover || DEFAULT_COVER,
+            releaseDate: entry.releaseDate,
+        },
+        duration: entry.duration,
+        trackNumber: entry.trackNumber,
+        discNumber: entry.discNumber,
+        isrc: entry.isrc,
+        copyright: entry.copyright,
+        explicit: entry.explicit,
+        isLocal: true,
+        file,
+        fileHandle,
+        localRootId: entry.rootId,
+        localPath: entry.path,
+        localKey: entry.key,
+    };
+
+    // Tracks of a CUE-split image only play their part of the file
+    if (entry.cue) {
+        track.cueStart = entry.cue.start;
+        track.cueEnd = entry.cue.end;
+    }
+
+    if (entry.albumArtist || artists.length) {
+        track.album.artist = { name: entry.albumArtist || artist.name };
+    }
+    if (entry.match?.id) {
+        track.catalogMatch = entry.match;
+    }
+    if (entry.musicBrainz?.recordingId) {
+        track.mbids = {
+            recording_mbid: entry.musicBrainz.recordingId,
+            release_mbid: entry.musicBrainz.releaseId,
+            artist_mbids: entry.musicBrainz.artistIds,
+        };
+    }
+    return track;
+}
+
+export function sortLocalTracks(tracks) {
+    return tracks.sort(
+        (a, b) =>
+            (a.artist.name || '').localeCompare(b.artist.name || '') ||
+            (a.album.title || '').localeCompare(b.album.title || '') ||
+            (a.discNumber || 1) - (b.discNumber || 1) ||
+            (a.trackNumber || 0) - (b.trackNumber || 0)
+    );
+}
+
+/**
+ * Group tracks by album or by album artist
+ * @param {object[]} tracks
+ * @param {'album'|'artist'} by
+ * @returns {{key: string, title: string, subtitle: string, cover: string, tracks: object[]}[]}
+ */
+export function groupLocalTracks(tracks, by) {
+    const groups = new Map();
+    for (const track of tracks) {
+        const artistName = track.album.artist?.name || track.artist.name || UNKNOWN_ARTIST;
+        const key = by === 'artist' ? artistName.toLowerCase() : getAlbumKey(artistName, track.album.title);
+        let group = groups.get(key);
+        if (!group) {
+            group = {
+                key,
+                title: by === 'artist' ? artistName : track.album.title,
+                subtitle: by === 'artist' ? '' : artistName,
+                cover: track.album.cover,
+                tracks: [],
+            };
+            groups.set(key, group);
+        }
+        if (group.cover === DEFAULT_COVER) group.cover = track.album.cover;
+        group.tracks.push(track);
+    }
+
+    const result = [...groups.values()].sort((a, b) => a.title.localeCompare(b.title));
+    for (const group of result) {
+        sortLocalTracks(group.tracks);
+        if (by === 'artist') {
+            const albums = new Set(group.tracks.map((track) => track.album.title));
+            group.subtitle = `${albums.size} album${albums.size === 1 ? '' : 's'}`;
+        }
+    }
+    return result;
+}
+
+/**
+ * Tracks whose title, artists or album contain every word of the query
+ */
+export function searchLocalTracks(tracks, query) {
+    const words = (query || '').toLowerCase().split(/\s+/).filter(Boolean);
+    if (words.length === 0) return tracks;
+
+    return tracks.filter((track) => {
+        const haystack = [track.title, track.album.title, track.album.artist?.name, ...track.artists.map((a) => a.name)]
+            .join('\n')
+            .toLowerCase();
+        return words.every((word) => haystack.includes(word));
+    });
+}
+
+/**
+ * Root folders of the local library. The first call after upgrading adopts the folder picked before
+ * multiple roots existed.
+ * @returns {Promise<{id: string, name: string, handle: FileSystemDirectoryHandle}[]>}
+ */
+export async function getLocalRoots() {
+    const roots = await db.getSetting(ROOTS_SETTING);
+    if (Array.isArray(roots)) return roots;
+
+    const handle = await db.getSetting('local_folder_handle');
+    if (!handle) return [];
+    const migrated = [{ id: MAIN_ROOT_ID, name: handle.name, handle }];
+    await db.saveSetting(ROOTS_SETTING, migrated);
+    return migrated;
+}
+
+async function forgetRoot(rootId) {
+    const entries = await db.getLocalLibraryEntries(rootId);
+    await db.deleteLocalLibraryEntries(entries.map((entry) => entry.key));
+}
+
+/**
+ * Use `handle` as the main folder, dropping the index of the folder it replaces
+ */
+export async function setMainRoot(handle) {
+    const roots = (await getLocalRoots()).filter((root) => root.id !== MAIN_ROOT_ID);
+    await forgetRoot(MAIN_ROOT_ID);
+    await db.saveSetting('local_folder_handle', handle);
+    await db.saveSetting(ROOTS_SETTING, [{ id: MAIN_ROOT_ID, name: handle.name, handle }, ...roots]);
+}
+
+/**
+ * Index another folder alongside the main one
+ * @returns {Promise<boolean>} false if the folder is already part of the library
+ */
+export async function addLocalRoot(handle) {
+    const roots = await getLocalRoots();
+    for (const root of roots) {
+        if (await root.handle.isSameEntry?.(handle)) return false;
+    }
+    roots.push({ id: `root-${Date.now().toString(36)}`, name: handle.name, handle });
+    await db.saveSetting(ROOTS_SETTING, roots);
+    return true;
+}
+
+export async function removeLocalRoot(rootId) {
+    if (rootId === MAIN_ROOT_ID) return;
+    const roots = await getLocalRoots();
+    await db.saveSetting(ROOTS_SETTING, roots.filter((root) => root.id !== rootId));
+    await forgetRoot(rootId);
+}
+
+async function getCoverUrl(albumKey) {
+    if (coverUrls.has(albumKey)) return coverUrls.get(albumKey);
+    const blob = await db.getLocalCover(albumKey).catch(() => null);
+    const url = blob ? URL.createObjectURL(blob) : null;
+    coverUrls.set(albumKey, url);
+    return url;
+}
+
+async function saveCover(albumKey, cover) {
+    if (coverUrls.get(albumKey)) return coverUrls.get(albumKey);
+    const blob = new Blob([cover.data], { type: cover.type || 'image/jpeg' });
+    await db.saveLocalCover(albumKey, blob).catch((e) => console.warn('[LocalLibrary] Could not save cover:', e));
+    const url = URL.createObjectURL(blob);
+    coverUrls.set(albumKey, url);
+    return url;
+}
+
+async function hasReadPermission(handle) {
+    if (typeof handle.queryPermission !== 'function') return true;
+    if ((await handle.queryPermission({ mode: 'read' })) === 'granted') return true;
+    try {
+        // Only succeeds with a user gesture or a persistent grant
+        return (await handle.requestPermission({ mode: 'read' })) === 'granted';
+    } catch {
+        return false;
+    }
+}
+
+async function* walkLibraryFiles(dirHandle, prefix = '') {
+    for await (const entry of dirHandle.values()) {
+        const path = prefix + entry.name;
+        if (entry.kind === 'file' && (isLocalAudioFile(entry.name) || isCueSheetFile(entry.name))) {
+            yield { path, handle: entry };
+        } else if (entry.kind === 'directory') {
+            yield* walkLibraryFiles(entry, `${path}/`);
+        }
+    }
+}
+
+/**
+ * Read the CUE sheets of a root folder
+ * @returns {Promise<Map<string, {cue: import('./cue-sheet.js').CueSheet, lastModified: number}>>} Sheets by
+ *     the path of the audio file they split
+ */
+async function readCueSheets(cueFiles, audioFiles) {
+    const audioPaths = new Set(audioFiles.map(({ path }) => path));
+    const sheets = new Map();
+    for (const { path, handle } of cueFiles) {
+        try {
+            const file = await handle.getFile();
+            const cue = parseCueSheet(decodeCueSheet(await file.arrayBuffer()));
+            const imagePath = findCueImagePath(cue, path, audioPaths);
+            if (imagePath) sheets.set(imagePath, { cue, lastModified: file.lastModified });
+        } catch (e) {
+            console.warn('[LocalLibrary] Could not read cue sheet', path, e);
+        }
+    }
+    return sheets;
+}
+
+/**
+ * Index entries for an audio file: one per track when a CUE sheet next to the file or embedded in it
+ * splits it, otherwise a single entry
+ */
+async function createFileEntries(rootId, path, file, tags, sheet) {
+    let cue = sheet?.cue || null;
+    if (!cue && file.name.toLowerCase().endsWith('.flac')) {
+        const { readFlacCueSheet } = await import('./metadata.flac.js');
+        cue = await readFlacCueSheet(file).catch(() => null);
+    }
+
+    const cueTracks = cue ? getCueTracks(cue, tags?.duration) : [];
+    if (cueTracks.length < 2) return [createLocalLibraryEntry(rootId, path, file, tags)];
+    return cueTracks.map((cueTrack) =>
+        createCueTrackEntry(rootId, path, file, tags, cue, cueTrack, sheet?.lastModified ?? null)
+    );
+}
+
+/**
+ * Index one file and return its track, e.g. right after a download was written into a root folder
+ * @param {string} rootId
+ * @param {string} path
+ * @param {FileSystemFileHandle} fileHandle
+ * @param {(file: File) => Promise<object|null>} readTags
+ */
+export async function indexLocalFile(rootId, path, fileHandle, readTags) {
+    const file = await fileHandle.getFile();
+    const tags = await readTags(file).catch(() => null);
+    const entry = createLocalLibraryEntry(rootId, path, file, tags);
+    const cover = entry.hasCover ? await saveCover(entry.albumKey, tags.cover) : await getCoverUrl(entry.albumKey);
+    await db.saveLocalLibraryEntries([entry]);
+    return toLocalTrack(entry, { file, fileHandle, cover });
+}
+
+/**
+ * Bring the index up to date with the root folders and return the library's tracks.
+ * `onUpdate` first receives the tracks known from the index, then the list as it changes; tracks only
+ * get their `file` once the walk reaches them.
+ * @param {object} options
+ * @param {(file: File) => Promise<object|null>} options.readTags
+ * @param {(tracks: object[]) => void} [options.onUpdate]
+ * @returns {Promise<object[]|null>} null when no folder has been picked yet
+ */
+export async function scanLocalLibrary({ readTags, onUpdate }) {
+    const roots = await getLocalRoots();
+    if (roots.length === 0) return null;
+
+    const rootIds = new Set(roots.map((root) => root.id));
+    const entries = new Map();
+    const tracks = new Map();
+    // Entries by the file they come from; a file split by a CUE sheet has one entry per track
+    const entriesByFile = new Map();
+    for (const entry of await db.getLocalLibraryEntries()) {
+        if (!rootIds.has(entry.rootId)) continue;
+        entries.set(entry.key, entry);
+        const fileKey = getLocalLibraryKey(entry.rootId, entry.path);
+        entriesByFile.set(fileKey, [...(entriesByFile.get(fileKey) || []), entry]);
+        const cover = entry.hasCover ? await getCoverUrl(entry.albumKey) : null;
+        tracks.set(entry.key, toLocalTrack(entry, { cover }));
+    }
+    const list = () => sortLocalTracks([...tracks.values()]);
+    onUpdate?.(list());
+
+    for (const root of roots) {
+        if (!(await hasReadPermission(root.handle))) continue;
+
+        const seen = new Set();
+        let pending = [];
+        let changed = false;
+        try {
+            const files = [];
+            for await (const file of walkLibraryFiles(root.handle)) files.push(file);
+            const audioFiles = files.filter(({ path }) => isLocalAudioFile(path));
+            const cueSheets = await readCueSheets(files.filter(({ path }) => isCueSheetFile(path)), audioFiles);
+
+            for (const { path, handle } of audioFiles) {
+                const file = await handle.getFile();
+                const sheet = cueSheets.get(path) || null;
+                const known = entriesByFile.get(getLocalLibraryKey(root.id, path)) || [];
+
+                if (known.length > 0 && known.every((entry) => isEntryCurrent(entry, file, sheet))) {
+                    for (const entry of known) {
+                        seen.add(entry.key);
+                        const track = tracks.get(entry.key);
+                        track.file = file;
+                        track.fileHandle = handle;
+                    }
+                    continue;
+                }
+
+                const tags = await readTags(file).catch((e) => {
+                    console.warn('[LocalLibrary] Could not read tags of', path, e);
+                    return null;
+                });
+                for (const entry of await createFileEntries(root.id, path, file, tags, sheet)) {
+                    const cover = entry.hasCover
+                        ? await saveCover(entry.albumKey, tags.cover)
+                        : await getCoverUrl(entry.albumKey);
+                    seen.add(entry.key);
+                    entries.set(entry.key, entry);
+                    tracks.set(entry.key, toLocalTrack(entry, { file, fileHandle: handle, cover }));
+                    pending.push(entry);
+                }
+                changed = true;
+
+                if (pending.length >= SAVE_BATCH_SIZE) {
+                    await db.saveLocalLibraryEntries(pending);
+                    pending = [];
+                    onUpdate?.(list());
+                }
+            }
+        } catch (e) {
+            // A folder that went away mid-walk keeps its index; the next scan tries again
+            console.warn(`[LocalLibrary] Scanning "${root.name}" failed:`, e);
+            await db.saveLocalLibraryEntries(pending);
+            continue;
+        }
+        await db.saveLocalLibraryEntries(pending);
+
+        const removed = [...entries.values()]
+            .filter((entry) => entry.rootId === root.id && !seen.has(entry.key))
+            .map((entry) => entry.key);
+        removed.forEach((key) => {
+            entries.delete(key);
+            tracks.delete(key);
+        });
+        await db.deleteLocalLibraryEntries(removed);
+
+        if (changed || removed.length) onUpdate?.(list());
+    }
+
+    return list();
+}
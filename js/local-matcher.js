@@ -0,0 +1,140 @@
+// js/local-matcher.js
+// Links files of the local library to catalog tracks. Files with an embedded ISRC are looked up
+// directly; everything else goes through the same fuzzy search the playlist importer uses. The result
+// is stored on the file's index entry together with a confidence score, and confident matches let
+// local plays count toward history and recommendations, scrobble with catalog metadata, be added to
+// cloud playlists and fetch lyrics.
+
+import { db } from './db.js';
+import { findBestMatch, isIsrcMatch, scoreTrack } from './playlist-importer.js';
+import { localMatchSettings } from './storage.js';
+
+/** Matches below this confidence are recorded but not used */
+export const MIN_MATCH_CONFIDENCE = 0.75;
+
+// Files without a match are looked up again after this long, in case the catalog has them by then
+const RETRY_UNMATCHED_MS = 30 * 24 * 60 * 60 * 1000;
+const LOOKUP_DELAY_MS = 500;
+const SAVE_BATCH_SIZE = 20;
+
+let currentRun = 0;
+
+function getArtistNames(track) {
+    return (track.artists || []).map((artist) => artist.name).filter(Boolean);
+}
+
+/**
+ * How likely `candidate` is the recording in the local file, from 0 to 1
+ * @param {object} track - Local track
+ * @param {object} candidate - Catalog track
+ */
+export function scoreCatalogMatch(track, candidate) {
+    if (isIsrcMatch(candidate.isrc, track.isrc)) return 1;
+
+    const artist = getArtistNames(track).join(', ');
+    const album = track.album?.title === 'Unknown Album' ? null : track.album?.title;
+    let { score } = scoreTrack(candidate, track.title, artist, album);
+    // Without an album to compare the best possible score would be 0.9
+    if (!album) score /= 0.9;
+
+    // Durations a few seconds apart are normal between masters; beyond that it is likely another version
+    if (track.duration && candidate.duration) {
+        const difference = Math.abs(track.duration - candidate.duration);
+        if (difference > 3) score *= Math.max(0.5, 1 - (difference - 3) / 60);
+    }
+    return Math.round(Math.min(score, 1) * 100) / 100;
+}
+
+/**
+ * Find the catalog track for a local track
+ * @param {object} track - Local track
+ * @param {import('./music-api.js').MusicAPI} api
+ * @returns {Promise<{id: string|number|null, confidence: number, method: 'isrc'|'search'|null,
+ *     track: object|null, matchedAt: number}>}
+ */
+export async function matchLocalTrack(track, api) {
+    const result = { id: null, confidence: 0, method: null, track: null, matchedAt: Date.now() };
+
+    let found = null;
+    if (track.isrc) {
+        const { items } = await api.searchTracksByIsrc(track.isrc);
+        found = items?.find((item) => isIsrcMatch(item.isrc, track.isrc)) || null;
+        if (found) result.method = 'isrc';
+    }
+
+    const artist = getArtistNames(track).join(', ');
+    if (!found && track.title && artist) {
+        const { items } = await api.searchTracks(`"${track.title}" ${artist}`);
+        found = findBestMatch(items, artist, track.album?.title, null, track.title, track.isrc);
+        if (found) result.method = 'search';
+    }
+
+    if (!found) return result;
+    result.id = found.id;
+    result.confidence = result.method === 'isrc' ? 1 : scoreCatalogMatch(track, found);
+    result.track = db.minifyItem('track', found);
+    return result;
+}
+
+/** Whether an index entry should be (re)matched */
+export function needsMatch(entry, now = Date.now()) {
+    if (!entry.match) return true;
+    return !entry.match.id && now - entry.match.matchedAt > RETRY_UNMATCHED_MS;
+}
+
+/**
+ * The catalog track to use in place of a local one: `track` itself for catalog tracks, the matched
+ * track for confidently matched local files and null otherwise
+ */
+export function getCatalogTrack(track) {
+    if (!track?.isLocal) return track || null;
+    const match = track.catalogMatch;
+    if (!match?.track || match.confidence < MIN_MATCH_CONFIDENCE) return null;
+    return { ...match.track };
+}
+
+/**
+ * Match the files of the library that have not been matched yet, one lookup at a time. Starting a new
+ * run stops the previous one.
+ * @param {object[]} tracks - `window.localFilesCache`
+ * @param {import('./music-api.js').MusicAPI} api
+ * @param {(track: object) => void} [onMatch] - Called for every track that got a match
+ */
+export async function matchLocalLibrary(tracks, api, onMatch) {
+    if (!localMatchSettings.isEnabled()) return;
+    const run = ++currentRun;
+
+    const byKey = new Map(tracks.map((track) => [track.localKey, track]));
+    const entries = (await db.getLocalLibraryEntries()).filter((entry) => byKey.has(entry.key) && needsMatch(entry));
+
+    let pending = [];
+    const flush = async () => {
+        if (pending.length === 0) return;
+        await db.saveLocalLibraryEntries(pending);
+        pending = [];
+    };
+
+    try {
+        for (const entry of entries) {
+            if (run !== currentRun || !navigator.onLine) break;
+
+            const track = byKey.get(entry.key);
+            try {
+                entry.match = await matchLocalTrack(track, api);
+            } catch (e) {
+                console.warn('[LocalMatcher] Lookup failed for', entry.path, e);
+                continue;
+            }
+            pending.push(entry);
+            if (entry.match.id) {
+                track.catalogMatch = entry.match;
+                onMatch?.(track);
+            }
+
+            if (pending.length >= SAVE_BATCH_SIZE) await flush();
+            await new Promise((resolve) => setTimeout(resolve, LOOKUP_DELAY_MS));
+        }
+    } finally {
+        await flush();
+    }
+}
@@ -0,0 +1,185 @@
+// js/loudness-analyzer.js
+// Measures EBU R128 loudness for tracks that arrive without ReplayGain data (local files,
+// fallback providers) and caches the result in MusicDatabase, keyed by track ID.
+// Decoding happens on the main thread via Web Audio; the metering itself runs in a worker.
+
+import LoudnessWorker from './loudness.worker.js?worker';
+import { loudnessToReplayGain } from './loudness-meter.js';
+import { db } from './db.js';
+import { getProxyUrl } from './proxy-utils.js';
+
+// Decode at a fixed rate so results don't depend on the output device
+const ANALYSIS_SAMPLE_RATE = 48000;
+// The whole track is decoded at once: 20 minutes of 48 kHz stereo is about 460 MB of float PCM
+const MAX_ANALYSIS_SECONDS = 20 * 60;
+// Compressed sources are read into memory before decoding
+const MAX_SOURCE_BYTES = 300 * 1024 * 1024;
+// PCM is handed to the worker this many seconds at a time
+const SLICE_SECONDS = 10;
+
+export class LoudnessAnalysisError extends Error {
+    constructor(message) {
+        super(message);
+        this.name = 'LoudnessAnalysisError';
+    }
+}
+
+/**
+ * Work out what can be analyzed for a track: the local file, or a directly fetchable stream.
+ * DASH/HLS manifests and DRM-protected streams are skipped.
+ * @param {object} track
+ * @param {object} [streamInfo] - Resolved stream info from the API, if any
+ * @returns {{file?: File, url?: string}|null}
+ */
+export function getAnalysisSource(track, streamInfo = null) {
+    if (!track) return null;
+    if (track.isLocal) {
+        return track.file ? { file: track.file } : null;
+    }
+
+    const url = streamInfo?.url || track.remoteUrl || track.audioUrl;
+    if (!url) return null;
+    if (streamInfo?.playbackType?.includes('cenc')) return null;
+    if (url.includes('.mpd') || url.includes('.m3u8') || url.includes('codec=flac-hls')) return null;
+    if (url.startsWith('blob:')) {
+        return !streamInfo || streamInfo.playbackType === 'direct' ? { url } : null;
+    }
+    return { url: getProxyUrl(url) };
+}
+
+async function readSource(source, signal) {
+    if (source.file) {
+        if (source.file.size > MAX_SOURCE_BYTES) {
+            throw new LoudnessAnalysisError('File too large to analyze');
+        }
+        return await source.file.arrayBuffer();
+    }
+
+    const response = await fetch(source.url, { signal });
+    if (!response.ok) {
+        throw new LoudnessAnalysisError(`Failed to fetch audio: ${response.status}`);
+    }
+    const length = parseInt(response.headers.get('content-length'), 10);
+    if (length > MAX_SOURCE_BYTES) {
+        throw new LoudnessAnalysisError('Stream too large to analyze');
+    }
+    return await response.arrayBuffer();
+}
+
+function measure(buffer, signal) {
+    return new Promise((resolve, reject) => {
+        const worker = new LoudnessWorker();
+
+        const abortHandler = () => {
+            worker.terminate();
+            reject(new LoudnessAnalysisError('Loudness analysis aborted'));
+        };
+
+        if (signal) {
+            if (signal.aborted) {
+                abortHandler();
+                return;
+            }
+            signal.addEventListener('abort', abortHandler);
+        }
+
+        const finish = () => {
+            if (signal) signal.removeEventListener('abort', abortHandler);
+            worker.terminate();
+        };
+
+        worker.onmessage = (e) => {
+            const { type, result, message } = e.data;
+            if (type === 'complete') {
+                finish();
+                resolve(result);
+            } else if (type === 'error') {
+                finish();
+                reject(new LoudnessAnalysisError(message));
+            }
+        };
+
+        worker.onerror = (error) => {
+            finish();
+            reject(new LoudnessAnalysisError('Worker failed: ' + error.message));
+        };
+
+        const { sampleRate, numberOfChannels, length } = buffer;
+        worker.postMessage({ type: 'start', sampleRate, channelCount: numberOfChannels, frames: length });
+        const slice = sampleRate * SLICE_SECONDS;
+        for (let start = 0; start < length; start += slice) {
+            const channels = [];
+            for (let ch = 0; ch < numberOfChannels; ch++) {
+                // Copy so the transfer doesn't detach the AudioBuffer's own storage
+                const data = new Float32Array(Math.min(slice, length - start));
+                buffer.copyFromChannel(data, ch, start);
+                channels.push(data);
+            }
+            worker.postMessage({ type: 'chunk', channels }, channels.map((data) => data.buffer));
+        }
+        worker.postMessage({ type: 'end' });
+    });
+}
+
+class LoudnessAnalyzer {
+    constructor() {
+        this.pending = new Map();
+        // Analyses run one at a time; decoded PCM for a single album track is already large
+        this.queue = Promise.resolve();
+    }
+
+    /**
+     * Cached ReplayGain values for a track, or null if it hasn't been analyzed
+     */
+    async getReplayGain(trackId) {
+        if (trackId === undefined || trackId === null) return null;
+        try {
+            const entry = await db.getLoudnessAnalysis(trackId);
+            return entry?.trackReplayGain !== undefined ? entry : null;
+        } catch (e) {
+            console.warn('[Loudness] Failed to read cache:', e);
+            return null;
+        }
+    }
+
+    /**
+     * Analyze a track (or return the cached result). Concurrent calls for the same track share one run.
+     * @param {object} track
+     * @param {{file?: File, url?: string}} source - From getAnalysisSource
+     * @param {AbortSignal} [signal]
+     * @returns {Promise<object|null>} Cached entry with integratedLoudness, truePeak and ReplayGain values, or
+     *   null for tracks too quiet to give one (e.g. silence)
+     */
+    analyze(track, source, signal = null) {
+        const id = String(track.id);
+        if (this.pending.has(id)) return this.pending.get(id);
+
+        const job = this.queue.then(() => this._run(id, source, track.duration, signal));
+        this.pending.set(id, job);
+        this.queue = job.catch(() => {});
+        job.finally(() => this.pending.delete(id)).catch(() => {});
+        return job;
+    }
+
+    async _run(id, source, duration, signal) {
+        const cached = await db.getLoudnessAnalysis(id);
+        if (cached) return cached.noGain ? null : cached;
+
+        // The duration has to be known up front, decoding is what takes the memory
+        if (!(duration > 0) || duration > MAX_ANALYSIS_SECONDS) {
+            throw new LoudnessAnalysisError('Track too long, or of unknown length, to analyze');
+        }
+
+        const data = await readSource(source, signal);
+        const context = new OfflineAudioContext(1, 1, ANALYSIS_SAMPLE_RATE);
+        const buffer = await context.decodeAudioData(data);
+
+        const result = await measure(buffer, signal);
+        // Remembered as well, so silent tracks aren't decoded again on every play
+        const gain = loudnessToReplayGain(result) || { noGain: true };
+        const entry = await db.saveLoudnessAnalysis(id, { ...result, ...gain });
+        return entry.noGain ? null : entry;
+    }
+}
+
+export const loudnessAnalyzer = new LoudnessAnalyzer();
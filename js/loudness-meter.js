@@ -0,0 +1,240 @@
+// js/loudness-meter.js
+// EBU R128 / ITU-R BS.1770-4 loudness meter: K-weighted, gated integrated loudness and true peak.
+// Pure computation with no DOM access, so it runs unchanged inside the loudness worker.
+
+const BLOCK_SECONDS = 0.4;
+const STEP_SECONDS = 0.1; // 75% block overlap
+const ABSOLUTE_GATE_LUFS = -70;
+const RELATIVE_GATE_LU = -10;
+const TRUE_PEAK_TAPS_PER_PHASE = 12;
+
+// ReplayGain 2.0 reference level
+export const REPLAYGAIN_REFERENCE_LUFS = -18;
+
+/**
+ * K-weighting pre-filter (high shelf) and RLB high-pass coefficients for any sample rate
+ * (BS.1770 analog prototypes, bilinear transformed as in libebur128)
+ */
+function kWeightingCoefficients(sampleRate) {
+    let f0 = 1681.974450955533;
+    const G = 3.999843853973347;
+    let Q = 0.7071752369554196;
+    let K = Math.tan((Math.PI * f0) / sampleRate);
+    const Vh = Math.pow(10, G / 20);
+    const Vb = Math.pow(Vh, 0.4996667741545416);
+    let a0 = 1 + K / Q + K * K;
+    const shelf = {
+        b0: (Vh + (Vb * K) / Q + K * K) / a0,
+        b1: (2 * (K * K - Vh)) / a0,
+        b2: (Vh - (Vb * K) / Q + K * K) / a0,
+        a1: (2 * (K * K - 1)) / a0,
+        a2: (1 - K / Q + K * K) / a0,
+    };
+
+    f0 = 38.13547087602444;
+    Q = 0.5003270373238773;
+    K = Math.tan((Math.PI * f0) / sampleRate);
+    a0 = 1 + K / Q + K * K;
+    const highpass = {
+        b0: 1,
+        b1: -2,
+        b2: 1,
+        a1: (2 * (K * K - 1)) / a0,
+        a2: (1 - K / Q + K * K) / a0,
+    };
+
+    return [shelf, highpass];
+}
+
+/**
+ * BS.1770 channel weights. 5.1 layouts use L R C LFE Ls Rs ordering; LFE is excluded.
+ */
+function channelWeights(channelCount) {
+    if (channelCount === 6) return [1, 1, 1, 0, 1.41, 1.41];
+    if (channelCount === 5) return [1, 1, 1, 1.41, 1.41];
+    return new Array(channelCount).fill(1);
+}
+
+/**
+ * Polyphase windowed-sinc interpolator used for true-peak estimation
+ */
+function createInterpolator(factor) {
+    const length = factor * TRUE_PEAK_TAPS_PER_PHASE;
+    const center = (length - 1) / 2;
+    const phases = [];
+    for (let p = 0; p < factor; p++) {
+        phases.push(new Float64Array(TRUE_PEAK_TAPS_PER_PHASE));
+    }
+    for (let i = 0; i < length; i++) {
+        const x = (i - center) / factor;
+        const sinc = x === 0 ? 1 : Math.sin(Math.PI * x) / (Math.PI * x);
+        const window = 0.5 * (1 - Math.cos((2 * Math.PI * (i + 0.5)) / length));
+        phases[i % factor][Math.floor(i / factor)] = sinc * window;
+    }
+    return phases;
+}
+
+export function loudnessFromPower(power) {
+    return power > 0 ? -0.691 + 10 * Math.log10(power) : -Infinity;
+}
+
+export class LoudnessMeter {
+    /**
+     * @param {number} sampleRate
+     * @param {number} channelCount
+     */
+    constructor(sampleRate, channelCount) {
+        this.sampleRate = sampleRate;
+        this.channelCount = channelCount;
+        this.weights = channelWeights(channelCount);
+        this.filters = kWeightingCoefficients(sampleRate);
+        // Per channel: [x1, x2, y1, y2] for each of the two biquad stages
+        this.filterState = Array.from({ length: channelCount }, () => [new Float64Array(4), new Float64Array(4)]);
+
+        this.stepSize = Math.round(sampleRate * STEP_SECONDS);
+        this.stepsPerBlock = Math.round(BLOCK_SECONDS / STEP_SECONDS);
+        this.stepEnergy = new Float64Array(channelCount);
+        this.stepFill = 0;
+        this.stepPowers = [];
+
+        this.oversampling = sampleRate < 96000 ? 4 : sampleRate < 192000 ? 2 : 1;
+        this.interpolator = this.oversampling > 1 ? createInterpolator(this.oversampling) : null;
+        this.history = Array.from({ length: channelCount }, () => new Float64Array(TRUE_PEAK_TAPS_PER_PHASE));
+        this.historyPos = 0;
+
+        this.samplePeak = 0;
+        this.truePeak = 0;
+        this.sampleCount = 0;
+    }
+
+    /**
+     * Feed planar PCM. All channel arrays must have the same length.
+     * @param {Float32Array[]} channels
+     */
+    process(channels) {
+        const frames = channels[0]?.length || 0;
+        const [shelf, hp] = this.filters;
+
+        for (let i = 0; i < frames; i++) {
+            for (let ch = 0; ch < this.channelCount; ch++) {
+                const x = channels[ch][i];
+
+                const abs = Math.abs(x);
+                if (abs > this.samplePeak) this.samplePeak = abs;
+
+                const s1 = this.filterState[ch][0];
+                const y1 = shelf.b0 * x + shelf.b1 * s1[0] + shelf.b2 * s1[1] - shelf.a1 * s1[2] - shelf.a2 * s1[3];
+                s1[1] = s1[0];
+                s1[0] = x;
+                s1[3] = s1[2];
+                s1[2] = y1;
+
+                const s2 = this.filterState[ch][1];
+                const y2 = hp.b0 * y1 + hp.b1 * s2[0] + hp.b2 * s2[1] - hp.a1 * s2[2] - hp.a2 * s2[3];
+                s2[1] = s2[0];
+                s2[0] = y1;
+                s2[3] = s2[2];
+                s2[2] = y2;
+
+                this.stepEnergy[ch] += y2 * y2;
+
+                if (this.interpolator) {
+                    this.history[ch][this.historyPos] = x;
+                }
+            }
+
+            if (this.interpolator) {
+                this._updateTruePeak();
+                this.historyPos = (this.historyPos + 1) % TRUE_PEAK_TAPS_PER_PHASE;
+            }
+
+            this.stepFill++;
+            if (this.stepFill === this.stepSize) {
+                this._closeStep();
+            }
+        }
+
+        this.sampleCount += frames;
+    }
+
+    _updateTruePeak() {
+        const taps = TRUE_PEAK_TAPS_PER_PHASE;
+        for (let ch = 0; ch < this.channelCount; ch++) {
+            const hist = this.history[ch];
+            for (let p = 0; p < this.interpolator.length; p++) {
+                const phase = this.interpolator[p];
+                let acc = 0;
+                for (let k = 0; k < taps; k++) {
+                    acc += phase[k] * hist[(this.historyPos - k + taps) % taps];
+                }
+                const abs = Math.abs(acc);
+                if (abs > this.truePeak) this.truePeak = abs;
+            }
+        }
+    }
+
+    _closeStep() {
+        let power = 0;
+        for (let ch = 0; ch < this.channelCount; ch++) {
+            power += this.weights[ch] * (this.stepEnergy[ch] / this.stepSize);
+            this.stepEnergy[ch] = 0;
+        }
+        this.stepPowers.push(power);
+        this.stepFill = 0;
+    }
+
+    /**
+     * Mean-square power of every complete 400 ms gating block
+     */
+    _blockPowers() {
+        const blocks = [];
+        for (let i = 0; i + this.stepsPerBlock <= this.stepPowers.length; i++) {
+            let sum = 0;
+            for (let j = 0; j < this.stepsPerBlock; j++) {
+                sum += this.stepPowers[i + j];
+            }
+            blocks.push(sum / this.stepsPerBlock);
+        }
+        return blocks;
+    }
+
+    /**
+     * Gated integrated loudness in LUFS (-Infinity for silence or input shorter than one block)
+     */
+    getIntegratedLoudness() {
+        const blocks = this._blockPowers().filter((p) => loudnessFromPower(p) > ABSOLUTE_GATE_LUFS);
+        if (blocks.length === 0) return -Infinity;
+
+        const ungatedMean = blocks.reduce((a, b) => a + b, 0) / blocks.length;
+        const relativeGate = loudnessFromPower(ungatedMean) + RELATIVE_GATE_LU;
+
+        const gated = blocks.filter((p) => loudnessFromPower(p) > relativeGate);
+        if (gated.length === 0) return -Infinity;
+        return loudnessFromPower(gated.reduce((a, b) => a + b, 0) / gated.length);
+    }
+
+    /**
+     * @returns {{integratedLoudness: number, truePeak: number, samplePeak: number, duration: number}}
+     */
+    getResult() {
+        return {
+            integratedLoudness: this.getIntegratedLoudness(),
+            truePeak: Math.max(this.truePeak, this.samplePeak),
+            samplePeak: this.samplePeak,
+            duration: this.sampleCount / this.sampleRate,
+        };
+    }
+}
+
+/**
+ * Convert a loudness measurement to the ReplayGain values consumed by Player.applyReplayGain
+ * @param {{integratedLoudness: number, truePeak: number}} result
+ */
+export function loudnessToReplayGain(result) {
+    if (!result || !isFinite(result.integratedLoudness)) return null;
+    return {
+        trackReplayGain: Math.round((REPLAYGAIN_REFERENCE_LUFS - result.integratedLoudness) * 100) / 100,
+        trackPeakAmplitude: result.truePeak || 1.0,
+        source: 'ebur128',
+    };
+}
@@ -0,0 +1,33 @@
+import { LoudnessMeter } from './loudness-meter.js';
+
+// The PCM arrives in slices ('start', then 'chunk's, then 'end'), so the main thread never holds a
+// second copy of the decoded track
+let meter = null;
+let totalFrames = 0;
+let processedFrames = 0;
+
+self.onmessage = (e) => {
+    const { type } = e.data;
+
+    try {
+        if (type === 'start') {
+            const { sampleRate, channelCount, frames } = e.data;
+            if (!channelCount || !sampleRate || !frames) {
+                throw new Error('No audio data');
+            }
+            meter = new LoudnessMeter(sampleRate, channelCount);
+            totalFrames = frames;
+            processedFrames = 0;
+        } else if (type === 'chunk') {
+            const { channels } = e.data;
+            meter.process(channels);
+            processedFrames += channels[0].length;
+            self.postMessage({ type: 'progress', progress: processedFrames / totalFrames });
+        } else if (type === 'end') {
+            self.postMessage({ type: 'complete', result: meter.getResult() });
+            meter = null;
+        }
+    } catch (err) {
+        self.postMessage({ type: 'error', message: err.message });
+    }
+};
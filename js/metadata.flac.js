@@ -1,6 +1,7 @@
 import { getCoverBlob, getTrackTitle } from './utils.js';
 import { getFullArtistString } from './utils.js';
 import { METADATA_STRINGS } from './METADATA_STRINGS.js';
+import { parseCueSheet } from './cue-sheet.js';
 
 export const FLAC_MIME_TYPE = 'audio/flac';
 const FLAC_BLOCK_TYPES = {
@@ -617,3 +618,94 @@ export async function rebuildFlacWithoutMetadata(flacBlob) {
         return flacBlob;
     }
 }
+
+function readUint64(dataView, offset) {
+    return dataView.getUint32(offset, false) * 0x100000000 + dataView.getUint32(offset + 4, false);
+}
+
+/**
+ * Parses a CUESHEET metadata block. The block only carries track numbers, ISRCs and sample offsets;
+ * titles are left empty.
+ * @param {DataView} dataView - The block's data
+ * @param {number} sampleRate
+ * @param {string} fileName
+ * @returns {import('./cue-sheet.js').CueSheet}
+ */
+export function parseFlacCueSheetBlock(dataView, sampleRate, fileName) {
+    // 128 byte catalog number, 8 byte lead-in, 1 byte CD flag and 258 reserved bytes come first
+    const trackCount = dataView.getUint8(395);
+    const tracks = [];
+    let pos = 396;
+
+    for (let i = 0; i < trackCount && pos + 36 <= dataView.byteLength; i++) {
+        const trackOffset = readUint64(dataView, pos);
+        const number = dataView.getUint8(pos + 8);
+        const isrc = new TextDecoder().decode(new Uint8Array(dataView.buffer, dataView.byteOffset + pos + 9, 12));
+        const indexCount = dataView.getUint8(pos + 35);
+        pos += 36;
+
+        let start = null;
+        for (let j = 0; j < indexCount; j++) {
+            if (dataView.getUint8(pos + 8) === 1) start = (trackOffset + readUint64(dataView, pos)) / sampleRate;
+            pos += 12;
+        }
+
+        // 170 (CD-DA) and 255 are the lead-out track
+        if (number === 170 || number === 255) continue;
+        tracks.push({ number, title: null, performer: null, isrc: isrc.replace(/\0/g, '') || null, start });
+    }
+
+    return { title: null, performer: null, date: null, genre: null, files: [{ name: fileName, type: 'WAVE', tracks }] };
+}
+
+/**
+ * Reads the cue sheet embedded in a FLAC file, either as a CUESHEET Vorbis comment (which keeps
+ * titles and performers) or as a CUESHEET metadata block. Only the metadata blocks are read.
+ *
+ * @param {File} file
+ * @returns {Promise<import('./cue-sheet.js').CueSheet|null>}
+ */
+export async function readFlacCueSheet(file) {
+    const read = async (start, end) => new DataView(await file.slice(start, end).arrayBuffer());
+
+    if (!isFlacFile(await read(0, 4))) return null;
+
+    let offset = 4;
+    let sampleRate = 0;
+    let cueSheetBlock = null;
+    while (offset + 4 <= file.size) {
+        const header = await read(offset, offset + 4);
+        const isLast = (header.getUint8(0) & 0x80) !== 0;
+        const blockType = header.getUint8(0) & 0x7f;
+        const blockSize = (header.getUint8(1) << 16) | (header.getUint8(2) << 8) | header.getUint8(3);
+        if (blockType === 127) break;
+
+        if (blockType === FLAC_BLOCK_TYPES.StreamInfo) {
+            const streamInfo = await read(offset + 4, offset + 4 + blockSize);
+            sampleRate =
+                (streamInfo.getUint8(10) << 12) | (streamInfo.getUint8(11) << 4) | (streamInfo.getUint8(12) >> 4);
+        } else if (blockType === FLAC_BLOCK_TYPES.CueSheet) {
+            cueSheetBlock = await read(offset + 4, offset + 4 + blockSize);
+        } else if (blockType === FLAC_BLOCK_TYPES.VorbisComment) {
+            const block = await read(offset + 4, offset + 4 + blockSize);
+            let pos = 4 + block.getUint32(0, true);
+            const commentCount = block.getUint32(pos, true);
+            pos += 4;
+            for (let i = 0; i < commentCount && pos + 4 <= block.byteLength; i++) {
+                const len = block.getUint32(pos, true);
+                pos += 4;
+                const comment = new TextDecoder().decode(new Uint8Array(block.buffer, pos, len));
+                pos += len;
+                if (comment.slice(0, 9).toUpperCase() === 'CUESHEET=') {
+                    return parseCueSheet(comment.slice(9));
+                }
+            }
+        }
+
+        offset += 4 + blockSize;
+        if (isLast) break;
+    }
+
+    if (!cueSheetBlock || !sampleRate) return null;
+    return parseFlacCueSheetBlock(cueSheetBlock, sampleRate, file.name);
+}
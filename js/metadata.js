@@ -10,6 +10,11 @@ import { addMetadataWithTagLib, getMetadataWithTagLib } from './taglib.ts';
 import { LyricsManager } from './lyrics.js';
 import { Mp4Stik } from './taglib.types.ts';
 import { modernSettings } from './ModernSettings.js';
+import { lyricsSettings, musicBrainzSettings } from './storage.js';
+import { mbidLookupQueue } from './listenbrainz.js';
+import { addID3v2Frames, createSYLTFrame } from './metadata.mp3.js';
+import { isOggFile, readOggMetadata, writeOggMetadata } from './metadata.ogg.js';
+import { getRiffFormat, readRiffMetadata, writeRiffMetadata } from './metadata.riff.js';
 
 /**
  * @typedef {import('./container-classes.ts').Track} Track
@@ -17,6 +22,85 @@ import { modernSettings } from './ModernSettings.js';
  * @typedef {import("./taglib.types.ts").TagLibMetadata} TagLibMetadata
  */
 
+/**
+ * Containers whose tags are read and written here rather than by TagLib: Ogg (Vorbis/Opus), WAV and AIFF
+ * @param {Blob} blob
+ * @returns {Promise<'ogg'|'wav'|'aiff'|null>}
+ */
+async function detectTagContainer(blob) {
+    const view = new DataView(await blob.slice(0, 12).arrayBuffer());
+    if (isOggFile(view)) return 'ogg';
+    return getRiffFormat(view);
+}
+
+async function detectID3v2Tag(blob) {
+    if (!(blob instanceof Blob)) return false;
+    const header = new Uint8Array(await blob.slice(0, 3).arrayBuffer());
+    return header[0] === 0x49 && header[1] === 0x44 && header[2] === 0x33;
+}
+
+async function getAudioFile(file) {
+    if (file instanceof Blob) return file;
+    if (file instanceof Uint8Array) return new Blob([file]);
+    if (typeof FileSystemFileHandle !== 'undefined' && file instanceof FileSystemFileHandle) {
+        return await file.getFile();
+    }
+    if (typeof FileSystemFileEntry !== 'undefined' && file instanceof FileSystemFileEntry) {
+        return await new Promise((resolve, reject) => file.file(resolve, reject));
+    }
+    return null;
+}
+
+/**
+ * Read tags from containers TagLib is not used for. Resolves to null for everything else, or if the
+ * file could not be parsed, so the caller can fall back to TagLib.
+ */
+async function readContainerMetadata(file) {
+    try {
+        const blob = await getAudioFile(file);
+        if (!blob) return null;
+        const container = await detectTagContainer(blob);
+        if (container === 'ogg') return await readOggMetadata(blob);
+        if (container === 'wav' || container === 'aiff') return await readRiffMetadata(blob);
+    } catch (e) {
+        console.warn('Error reading Ogg/RIFF metadata, falling back to TagLib:', e);
+    }
+    return null;
+}
+
+/**
+ * Write tags into containers TagLib is not used for. Resolves to null for everything else.
+ */
+async function writeContainerMetadata(blob, data) {
+    const container = await detectTagContainer(blob);
+    if (container === 'ogg') return await writeOggMetadata(blob, data);
+    if (container === 'wav' || container === 'aiff') return await writeRiffMetadata(blob, data);
+    return null;
+}
+
+/**
+ * Read the raw tags of a local file, as shown in the tag editor
+ * @param {Blob | File} file
+ * @returns {Promise<TagLibMetadata | null>}
+ */
+export async function readEditableTags(file) {
+    return (await readContainerMetadata(file)) || (await getMetadataWithTagLib(file, file?.name, true));
+}
+
+/**
+ * Write tags into a local file. Only the fields present in `tags` are changed; everything else
+ * already in the file is kept. Editable fields set to null are removed.
+ * @param {Blob | File} file
+ * @param {TagLibMetadata} tags
+ * @returns {Promise<Blob>} The retagged file
+ */
+export async function writeEditableTags(file, tags) {
+    const written = await writeContainerMetadata(file, tags);
+    if (written) return written;
+
+    return await addMetadataWithTagLib(file, { ...tags }, file?.name, true, true);
+}
+
 export function prefetchMetadataObjects(track, api, coverBlob = null) {
     const coverId = getTrackCoverId(track);
     const coverFetch = coverBlob
@@ -25,8 +109,11 @@ export function prefetchMetadataObjects(track, api, coverBlob = null) {
           ? getCoverBlob(api, coverId).catch(console.error)
           : Promise.resolve(null);
     const lyricsFetch = LyricsManager.instance.fetchLyrics?.(track.id, track)?.catch(console.error);
+    const mbidFetch = musicBrainzSettings.shouldEmbedIds()
+        ? mbidLookupQueue.lookup(track).catch(console.error)
+        : Promise.resolve(null);
 
-    return { coverFetch, lyricsFetch };
+    return { coverFetch, lyricsFetch, mbidFetch };
 }
 
 /**
@@ -38,7 +125,8 @@ export function prefetchMetadataObjects(track, api, coverBlob = null) {
  * @returns {Promise<Blob>} - Audio blob with embedded metadata
  */
 export async function addMetadataToAudio(audioBlob, track, _api, _quality, prefetchPromises) {
-    const { coverFetch, lyricsFetch } = prefetchPromises;
+    const { coverFetch, lyricsFetch, mbidFetch } = prefetchPromises;
+    let syncedLyrics = [];
 
     /**
      * @type {TagLibMetadata}
@@ -118,12 +206,36 @@ export async function addMetadataToAudio(audioBlob, track, _api, _quality, prefe
 
         try {
             const lyrics = await lyricsFetch;
-            data.lyrics = lyrics?.subtitles || lyrics?.plainLyrics;
+            if (lyrics?.subtitles) {
+                syncedLyrics = LyricsManager.instance?.parseSyncedLyrics(lyrics.subtitles) ?? [];
+            }
+            if (lyrics?.subtitles && lyricsSettings.shouldEmbedSyncedLyrics()) {
+                data.lyrics = lyrics.subtitles;
+            } else {
+                data.lyrics = lyrics?.plainLyrics || syncedLyrics.map((line) => line.text).join('\n') || undefined;
+                syncedLyrics = [];
+            }
         } catch (e) {
             console.warn('Error setting lyrics metadata', track, e);
         }
 
-        return await addMetadataWithTagLib(
+        try {
+            const mbids = await mbidFetch;
+            if (mbids?.recording_mbid) {
+                data.musicBrainz = {
+                    recordingId: mbids.recording_mbid,
+                    releaseId: mbids.release_mbid || undefined,
+                    artistIds: mbids.artist_mbids?.length ? mbids.artist_mbids : undefined,
+                };
+            }
+        } catch (e) {
+            console.warn('Error setting MusicBrainz metadata', track, e);
+        }
+
+        const written = await writeContainerMetadata(audioBlob, data);
+        if (written) return written;
+
+        const tagged = await addMetadataWithTagLib(
             audioBlob,
             {
                 ...data,
@@ -132,6 +244,12 @@ export async function addMetadataToAudio(audioBlob, track, _api, _quality, prefe
             true,
             true
         );
+
+        // TagLib writes lyrics as USLT only; players that scroll lyrics in MP3s want a SYLT frame
+        if (syncedLyrics.length > 0 && (await detectID3v2Tag(tagged))) {
+            return await addID3v2Frames(tagged, (version) => [createSYLTFrame(syncedLyrics, version)]);
+        }
+        return tagged;
     } catch (err) {
         console.error(err);
     }
@@ -160,7 +278,7 @@ export async function readTrackMetadata(file, { filename = file?.name || 'Unknow
     };
 
     try {
-        const data = await getMetadataWithTagLib(file, filename, true);
+        const data = (await readContainerMetadata(file)) || (await getMetadataWithTagLib(file, filename, true));
 
         if (data) {
             metadata.title = data.title || metadata.title;
@@ -193,6 +311,15 @@ export async function readTrackMetadata(file, { filename = file?.name || 'Unknow
             metadata.copyright = data.copyright || metadata.copyright;
             metadata.explicit = !!data.explicit;
 
+            // Lets scrobblers skip the MBID lookup for files that already carry them
+            if (data.musicBrainz?.recordingId) {
+                metadata.mbids = {
+                    recording_mbid: data.musicBrainz.recordingId,
+                    release_mbid: data.musicBrainz.releaseId,
+                    artist_mbids: data.musicBrainz.artistIds,
+                };
+            }
+
             // parse TIDAL_DATA if present
             if (data.extra?.TIDAL_DATA) {
                 try {
@@ -136,6 +136,103 @@ export function buildID3v2Tag(mp3Blob, frames) {
     return new Blob([header, framesData, mp3Blob], { type: 'audio/mpeg' });
 }
 
+function writeSynchsafeInteger32(bytes, offset, value) {
+    bytes[offset] = (value >> 21) & 0x7f;
+    bytes[offset + 1] = (value >> 14) & 0x7f;
+    bytes[offset + 2] = (value >> 7) & 0x7f;
+    bytes[offset + 3] = value & 0x7f;
+}
+
+function encodeID3Text(text, version) {
+    // ID3v2.4 allows UTF-8; ID3v2.3 only knows UTF-16 with BOM
+    if (version === 4) return [...new TextEncoder().encode(text), 0x00];
+
+    const bytes = [0xff, 0xfe];
+    for (let i = 0; i < text.length; i++) {
+        const charCode = text.charCodeAt(i);
+        bytes.push(charCode & 0xff, (charCode >> 8) & 0xff);
+    }
+    bytes.push(0x00, 0x00);
+    return bytes;
+}
+
+function createID3Frame(frameId, body, version) {
+    const frame = new Uint8Array(10 + body.length);
+    for (let i = 0; i < 4; i++) {
+        frame[i] = frameId.charCodeAt(i);
+    }
+    if (version === 4) {
+        writeSynchsafeInteger32(frame, 4, body.length);
+    } else {
+        new DataView(frame.buffer).setUint32(4, body.length, false);
+    }
+    frame.set(body, 10);
+    return frame;
+}
+
+/**
+ * SYLT frame with millisecond timestamps
+ * @param {{time: number, text: string}[]} lines - Times in seconds, as returned by LyricsManager.parseSyncedLyrics
+ * @param {number} [version=4] - ID3v2 major version of the tag the frame goes into
+ * @param {string} [language='XXX']
+ */
+export function createSYLTFrame(lines, version = 4, language = 'XXX') {
+    const body = [version === 4 ? 0x03 : 0x01, ...new TextEncoder().encode(language.padEnd(3).slice(0, 3))];
+    body.push(0x02); // Timestamps in milliseconds
+    body.push(0x01); // Content type: lyrics
+    body.push(...encodeID3Text('', version)); // Empty content descriptor
+
+    for (const line of lines) {
+        const time = Math.max(0, Math.round(line.time * 1000));
+        body.push(...encodeID3Text(line.text, version));
+        body.push((time >>> 24) & 0xff, (time >>> 16) & 0xff, (time >>> 8) & 0xff, time & 0xff);
+    }
+
+    return createID3Frame('SYLT', Uint8Array.from(body), version);
+}
+
+/**
+ * Add frames to the ID3v2 tag at the start of an MP3, creating an ID3v2.3 tag if there is none.
+ * Frames are built per tag version because v2.3 and v2.4 encode text and frame sizes differently.
+ * @param {Blob} mp3Blob
+ * @param {(version: number) => Uint8Array[]} createFrames
+ * @returns {Promise<Blob>}
+ */
+export async function addID3v2Frames(mp3Blob, createFrames) {
+    const header = new Uint8Array(await mp3Blob.slice(0, 10).arrayBuffer());
+    const hasTag = header[0] === 0x49 && header[1] === 0x44 && header[2] === 0x33;
+    const version = header[3];
+    // Unsynchronised tags and tags with a footer would need rewriting; leave those alone
+    if (!hasTag || (version !== 3 && version !== 4) || (header[5] & 0x90) !== 0) {
+        if (hasTag) return mp3Blob;
+        return buildID3v2Tag(mp3Blob, createFrames(3));
+    }
+
+    const frames = createFrames(version);
+    const framesLength = frames.reduce((acc, f) => acc + f.length, 0);
+    const tagSize = readSynchsafeInteger32(new DataView(header.buffer), 6);
+    const tag = new Uint8Array(await mp3Blob.slice(0, 10 + tagSize).arrayBuffer());
+    const view = new DataView(tag.buffer);
+
+    // Find where the existing frames end so the new ones go before the padding
+    let offset = 10;
+    if ((header[5] & 0x40) !== 0) {
+        offset += version === 4 ? readSynchsafeInteger32(view, offset) : view.getUint32(offset, false) + 4;
+    }
+    while (offset + 10 <= tag.length && tag[offset] !== 0) {
+        const frameSize = version === 4 ? readSynchsafeInteger32(view, offset + 4) : view.getUint32(offset + 4, false);
+        offset += 10 + frameSize;
+    }
+    offset = Math.min(offset, tag.length);
+
+    const newHeader = tag.slice(0, 10);
+    writeSynchsafeInteger32(newHeader, 6, tagSize + framesLength);
+
+    return new Blob([newHeader, tag.slice(10, offset), ...frames, tag.slice(offset), mp3Blob.slice(10 + tagSize)], {
+        type: mp3Blob.type || 'audio/mpeg',
+    });
+}
+
 export async function addMp3Metadata(mp3Blob, track, api, coverBlob = null) {
     try {
         if (!coverBlob) {
@@ -0,0 +1,586 @@
+import { METADATA_STRINGS } from './METADATA_STRINGS.js';
+import { REMOVABLE_TAG_KEYS } from './taglib.types.ts';
+
+// Ogg Vorbis / Ogg Opus tags. Both codecs keep their tags in a Vorbis comment packet (the second
+// header packet of the stream); cover art is a base64 FLAC picture block in METADATA_BLOCK_PICTURE.
+// Rewriting the comment packet re-paginates the header pages, so every page after them may need a
+// new sequence number and CRC.
+
+const OGG_CAPTURE_PATTERN = 0x4f676753; // 'OggS'
+const OGG_HEADER_SIZE = 27;
+const OGG_FLAG_CONTINUED = 0x01;
+const OGG_MAX_SEGMENTS = 255;
+
+const VORBIS_COMMENT_PREFIX = [0x03, 0x76, 0x6f, 0x72, 0x62, 0x69, 0x73]; // '\x03vorbis'
+const OPUS_TAGS_PREFIX = [0x4f, 0x70, 0x75, 0x73, 0x54, 0x61, 0x67, 0x73]; // 'OpusTags'
+
+// Header reads start small and grow until the comment packet (which may carry a large picture) fits
+const HEADER_READ_SIZES = [256 * 1024, 4 * 1024 * 1024];
+const TAIL_READ_SIZE = 64 * 1024;
+
+const OGG_CRC_TABLE = (() => {
+    const table = new Uint32Array(256);
+    for (let i = 0; i < 256; i++) {
+        let r = i << 24;
+        for (let j = 0; j < 8; j++) {
+            r = r & 0x80000000 ? (r << 1) ^ 0x04c11db7 : r << 1;
+        }
+        table[i] = r >>> 0;
+    }
+    return table;
+})();
+
+/**
+ * Ogg page checksum (CRC-32, polynomial 0x04c11db7, no reflection), computed with the CRC field zeroed
+ * @param {Uint8Array} bytes
+ * @returns {number}
+ */
+export function oggCrc32(bytes) {
+    let crc = 0;
+    for (let i = 0; i < bytes.length; i++) {
+        crc = ((crc << 8) ^ OGG_CRC_TABLE[((crc >>> 24) ^ bytes[i]) & 0xff]) >>> 0;
+    }
+    return crc;
+}
+
+export function isOggFile(dataView) {
+    return dataView.byteLength >= 4 && dataView.getUint32(0, false) === OGG_CAPTURE_PATTERN;
+}
+
+/**
+ * Parse the page starting at `offset`. Returns null when the page is cut off by the end of `bytes`.
+ */
+export function parseOggPage(bytes, offset) {
+    if (offset + OGG_HEADER_SIZE > bytes.length) return null;
+    const view = new DataView(bytes.buffer, bytes.byteOffset + offset);
+    if (view.getUint32(0, false) !== OGG_CAPTURE_PATTERN) {
+        throw new Error(`Invalid Ogg page at offset ${offset}`);
+    }
+
+    const segmentCount = view.getUint8(26);
+    const headerSize = OGG_HEADER_SIZE + segmentCount;
+    if (offset + headerSize > bytes.length) return null;
+
+    const segments = bytes.subarray(offset + OGG_HEADER_SIZE, offset + headerSize);
+    const dataSize = segments.reduce((sum, lace) => sum + lace, 0);
+    if (offset + headerSize + dataSize > bytes.length) return null;
+
+    return {
+        offset,
+        size: headerSize + dataSize,
+        flags: view.getUint8(5),
+        granule: view.getUint32(10, true) * 0x100000000 + view.getUint32(6, true),
+        serial: view.getUint32(14, true),
+        sequence: view.getUint32(18, true),
+        segments,
+        dataOffset: offset + headerSize,
+    };
+}
+
+function concatBytes(parts) {
+    const out = new Uint8Array(parts.reduce((sum, part) => sum + part.length, 0));
+    let offset = 0;
+    for (const part of parts) {
+        out.set(part, offset);
+        offset += part.length;
+    }
+    return out;
+}
+
+function startsWith(bytes, prefix) {
+    return bytes.length >= prefix.length && prefix.every((byte, i) => bytes[i] === byte);
+}
+
+function getCodec(identificationPacket) {
+    const magic = new TextDecoder().decode(identificationPacket.subarray(0, 8));
+    if (magic === 'OpusHead') return 'opus';
+    if (magic.startsWith('\x01vorbis')) return 'vorbis';
+    return null;
+}
+
+/**
+ * Collect the header packets of the first logical stream.
+ * Returns null if `bytes` ends before the headers do; throws for streams we cannot rewrite.
+ * @param {Uint8Array} bytes
+ */
+export function readOggHeaders(bytes) {
+    const pages = [];
+    const packets = [];
+    let pending = [];
+    let codec = null;
+    let serial = null;
+    let offset = 0;
+
+    while (codec === null || packets.length < (codec === 'vorbis' ? 3 : 2)) {
+        const page = parseOggPage(bytes, offset);
+        if (!page) return null;
+        if (serial === null) serial = page.serial;
+        if (page.serial !== serial) throw new Error('Multiplexed Ogg streams are not supported');
+
+        pages.push(page);
+        let pos = page.dataOffset;
+        for (const lace of page.segments) {
+            pending.push(bytes.subarray(pos, pos + lace));
+            pos += lace;
+            if (lace < 255) {
+                packets.push(concatBytes(pending));
+                pending = [];
+            }
+        }
+        offset += page.size;
+
+        if (codec === null && packets.length > 0) {
+            codec = getCodec(packets[0]);
+            if (!codec) throw new Error('Unsupported Ogg codec');
+            if (pages.length !== 1 || packets.length !== 1) {
+                throw new Error('Ogg identification header must be alone on the first page');
+            }
+        }
+    }
+
+    // Audio data always starts on a fresh page, right after the last header packet
+    if (pending.length > 0 || packets.length !== (codec === 'vorbis' ? 3 : 2)) {
+        throw new Error('Unexpected Ogg header layout');
+    }
+
+    return { codec, serial, pages, packets, end: offset };
+}
+
+/**
+ * Split packets into Ogg pages (lacing values, continuation flags, CRCs).
+ * Granule positions are 0 on pages where a packet ends and -1 otherwise, as for header pages.
+ * @param {Uint8Array[]} packets
+ * @param {number} serial
+ * @param {number} firstSequence
+ * @returns {Uint8Array[]}
+ */
+export function createOggPages(packets, serial, firstSequence) {
+    const lacing = [];
+    for (const packet of packets) {
+        let remaining = packet.length;
+        while (remaining >= 255) {
+            lacing.push(255);
+            remaining -= 255;
+        }
+        lacing.push(remaining);
+    }
+
+    const data = concatBytes(packets);
+    const pages = [];
+    let dataOffset = 0;
+    let continued = false;
+
+    for (let i = 0; i < lacing.length; i += OGG_MAX_SEGMENTS) {
+        const segments = lacing.slice(i, i + OGG_MAX_SEGMENTS);
+        const dataSize = segments.reduce((sum, lace) => sum + lace, 0);
+        const endsPacket = segments.some((lace) => lace < 255);
+
+        const page = new Uint8Array(OGG_HEADER_SIZE + segments.length + dataSize);
+        const view = new DataView(page.buffer);
+        view.setUint32(0, OGG_CAPTURE_PATTERN, false);
+        view.setUint8(4, 0); // version
+        view.setUint8(5, continued ? OGG_FLAG_CONTINUED : 0);
+        view.setUint32(6, endsPacket ? 0 : 0xffffffff, true);
+        view.setUint32(10, endsPacket ? 0 : 0xffffffff, true);
+        view.setUint32(14, serial, true);
+        view.setUint32(18, firstSequence + pages.length, true);
+        view.setUint8(26, segments.length);
+        page.set(segments, OGG_HEADER_SIZE);
+        page.set(data.subarray(dataOffset, dataOffset + dataSize), OGG_HEADER_SIZE + segments.length);
+        view.setUint32(22, oggCrc32(page), true);
+
+        pages.push(page);
+        dataOffset += dataSize;
+        continued = segments[segments.length - 1] === 255;
+    }
+
+    return pages;
+}
+
+/**
+ * Shift the sequence number of every page of `serial` by `delta` and fix their CRCs, in place
+ * @param {Uint8Array} bytes - Pages following the headers
+ */
+export function renumberOggPages(bytes, serial, delta) {
+    let offset = 0;
+    while (offset < bytes.length) {
+        let page;
+        try {
+            page = parseOggPage(bytes, offset);
+        } catch {
+            break; // Trailing garbage, leave it alone
+        }
+        if (!page) break;
+
+        if (page.serial === serial) {
+            const pageBytes = bytes.subarray(offset, offset + page.size);
+            const view = new DataView(pageBytes.buffer, pageBytes.byteOffset, pageBytes.byteLength);
+            view.setUint32(18, page.sequence + delta, true);
+            view.setUint32(22, 0, true);
+            view.setUint32(22, oggCrc32(pageBytes), true);
+        }
+        offset += page.size;
+    }
+}
+
+/**
+ * Parse a Vorbis comment packet (Vorbis or Opus flavour)
+ * @param {Uint8Array} packet
+ * @returns {{vendor: string, comments: Array<[string, string]>}}
+ */
+export function parseVorbisCommentPacket(packet) {
+    let pos;
+    if (startsWith(packet, VORBIS_COMMENT_PREFIX)) pos = VORBIS_COMMENT_PREFIX.length;
+    else if (startsWith(packet, OPUS_TAGS_PREFIX)) pos = OPUS_TAGS_PREFIX.length;
+    else throw new Error('Not a Vorbis comment packet');
+
+    const view = new DataView(packet.buffer, packet.byteOffset, packet.byteLength);
+    const decoder = new TextDecoder();
+
+    const vendorLength = view.getUint32(pos, true);
+    pos += 4;
+    const vendor = decoder.decode(packet.subarray(pos, pos + vendorLength));
+    pos += vendorLength;
+
+    const count = view.getUint32(pos, true);
+    pos += 4;
+
+    const comments = [];
+    for (let i = 0; i < count && pos + 4 <= packet.length; i++) {
+        const length = view.getUint32(pos, true);
+        pos += 4;
+        const comment = decoder.decode(packet.subarray(pos, pos + length));
+        pos += length;
+
+        const eqIdx = comment.indexOf('=');
+        if (eqIdx > 0) comments.push([comment.substring(0, eqIdx).toUpperCase(), comment.substring(eqIdx + 1)]);
+    }
+
+    return { vendor, comments };
+}
+
+/**
+ * Build a Vorbis comment packet. Vorbis packets end with a framing bit, Opus packets do not.
+ * @param {'vorbis'|'opus'} codec
+ * @param {Array<[string, string]>} comments
+ * @param {string} [vendor]
+ */
+export function createVorbisCommentPacket(codec, comments, vendor = METADATA_STRINGS.VENDOR_STRING) {
+    const encoder = new TextEncoder();
+    const prefix = codec === 'opus' ? OPUS_TAGS_PREFIX : VORBIS_COMMENT_PREFIX;
+    const vendorBytes = encoder.encode(vendor);
+    const encoded = comments.map(([key, value]) => encoder.encode(`${key}=${value}`));
+
+    const size =
+        prefix.length +
+        4 +
+        vendorBytes.length +
+        4 +
+        encoded.reduce((sum, bytes) => sum + 4 + bytes.length, 0) +
+        (codec === 'vorbis' ? 1 : 0);
+
+    const packet = new Uint8Array(size);
+    const view = new DataView(packet.buffer);
+    let offset = 0;
+
+    packet.set(prefix, offset);
+    offset += prefix.length;
+    view.setUint32(offset, vendorBytes.length, true);
+    offset += 4;
+    packet.set(vendorBytes, offset);
+    offset += vendorBytes.length;
+    view.setUint32(offset, encoded.length, true);
+    offset += 4;
+
+    for (const bytes of encoded) {
+        view.setUint32(offset, bytes.length, true);
+        offset += 4;
+        packet.set(bytes, offset);
+        offset += bytes.length;
+    }
+
+    if (codec === 'vorbis') packet[offset] = 1; // framing bit
+
+    return packet;
+}
+
+function bytesToBase64(bytes) {
+    let binary = '';
+    for (let i = 0; i < bytes.length; i += 0x8000) {
+        binary += String.fromCharCode.apply(null, bytes.subarray(i, i + 0x8000));
+    }
+    return btoa(binary);
+}
+
+function base64ToBytes(base64) {
+    const binary = atob(base64.replace(/\s/g, ''));
+    const bytes = new Uint8Array(binary.length);
+    for (let i = 0; i < binary.length; i++) bytes[i] = binary.charCodeAt(i);
+    return bytes;
+}
+
+/**
+ * FLAC picture block (as used by METADATA_BLOCK_PICTURE) for a front cover
+ * @param {{data: Uint8Array, type: string}} cover
+ */
+export function createPictureBlock(cover) {
+    const mimeBytes = new TextEncoder().encode(cover.type || 'image/jpeg');
+    const block = new Uint8Array(32 + mimeBytes.length + cover.data.length);
+    const view = new DataView(block.buffer);
+    let offset = 0;
+
+    view.setUint32(offset, 3, false); // front cover
+    offset += 4;
+    view.setUint32(offset, mimeBytes.length, false);
+    offset += 4;
+    block.set(mimeBytes, offset);
+    offset += mimeBytes.length;
+    view.setUint32(offset, 0, false); // empty description
+    offset += 4;
+    offset += 16; // width, height, colour depth, indexed colours: unknown
+    view.setUint32(offset, cover.data.length, false);
+    offset += 4;
+    block.set(cover.data, offset);
+
+    return block;
+}
+
+/**
+ * @param {Uint8Array} block
+ * @returns {{data: Uint8Array, type: string}|null}
+ */
+export function parsePictureBlock(block) {
+    const view = new DataView(block.buffer, block.byteOffset, block.byteLength);
+    let pos = 4;
+    const mimeLength = view.getUint32(pos, false);
+    pos += 4;
+    const type = new TextDecoder().decode(block.subarray(pos, pos + mimeLength));
+    pos += mimeLength;
+    const descriptionLength = view.getUint32(pos, false);
+    pos += 4 + descriptionLength + 16;
+    const dataLength = view.getUint32(pos, false);
+    pos += 4;
+    if (pos + dataLength > block.length) return null;
+    return { data: block.slice(pos, pos + dataLength), type };
+}
+
+/**
+ * Comments for the given tags, in the same shape TagLib writes for FLAC
+ * @param {import('./taglib.types.ts').TagLibMetadata} data
+ * @returns {Array<[string, string]>}
+ */
+export function createVorbisCommentsFromTags(data) {
+    const comments = [];
+    const add = (key, value) => {
+        if (value !== undefined && value !== null && value !== '') comments.push([key, String(value)]);
+    };
+
+    const artists = Array.isArray(data.artist) ? data.artist : data.artist ? [data.artist] : [];
+
+    add('TITLE', data.title);
+    if (data.writeArtistsSeparately) {
+        artists.forEach((artist) => add('ARTIST', artist));
+    } else {
+        add('ARTIST', artists.join('; '));
+    }
+    add('ALBUM', data.albumTitle);
+    add('ALBUMARTIST', data.albumArtist || artists.join('; '));
+    add('TRACKNUMBER', data.trackNumber);
+    add('TRACKTOTAL', data.totalTracks);
+    add('DISCNUMBER', data.discNumber);
+    add('DISCTOTAL', data.totalDiscs);
+    if (data.bpm != null && Number.isFinite(Number(data.bpm))) add('BPM', Math.round(Number(data.bpm)));
+
+    if (data.replayGain) {
+        const { albumReplayGain, albumPeakAmplitude, trackReplayGain, trackPeakAmplitude } = data.replayGain;
+        add('REPLAYGAIN_ALBUM_GAIN', albumReplayGain);
+        add('REPLAYGAIN_ALBUM_PEAK', albumPeakAmplitude);
+        add('REPLAYGAIN_TRACK_GAIN', trackReplayGain);
+        add('REPLAYGAIN_TRACK_PEAK', trackPeakAmplitude);
+    }
+
+    if (data.releaseDate) {
+        const year = Number(String(data.releaseDate).split('-')[0]);
+        if (!isNaN(year)) add('DATE', year);
+    }
+
+    add('COPYRIGHT', data.copyright);
+    add('ISRC', data.isrc);
+    add('UPC', data.upc);
+    if (data.lyrics) add('LYRICS', data.lyrics.replace(/\r/g, '').replace(/\n/g, '\r\n'));
+    if (data.explicit !== undefined) add('ITUNESADVISORY', data.explicit ? '1' : '0');
+    add('MUSICBRAINZ_TRACKID', data.musicBrainz?.recordingId);
+    add('MUSICBRAINZ_ALBUMID', data.musicBrainz?.releaseId);
+    (data.musicBrainz?.artistIds || []).forEach((id) => add('MUSICBRAINZ_ARTISTID', id));
+
+    for (const [key, value] of Object.entries(data.extra || {})) {
+        add(key.toUpperCase(), value);
+    }
+
+    if (data.cover?.data?.length) {
+        add('METADATA_BLOCK_PICTURE', bytesToBase64(createPictureBlock(data.cover)));
+    }
+
+    return comments;
+}
+
+/**
+ * Tags from parsed comments, in the shape returned by getMetadataWithTagLib
+ * @param {Array<[string, string]>} comments
+ */
+export function getTagsFromVorbisComments(comments) {
+    const values = (key) => comments.filter(([k]) => k === key).map(([, value]) => value);
+    const get = (key) => values(key)[0] || undefined;
+    const number = (value) => Number(String(value ?? '').trim() || 0) || undefined;
+
+    const data = {};
+    data.title = get('TITLE');
+    const artists = values('ARTIST');
+    data.artist = artists.length ? artists.join('; ') : undefined;
+    data.albumTitle = get('ALBUM');
+    data.albumArtist = get('ALBUMARTIST');
+
+    const [trackNumber, trackTotal] = (get('TRACKNUMBER') ?? '').split('/');
+    data.trackNumber = number(trackNumber);
+    data.totalTracks = number(trackTotal) ?? number(get('TRACKTOTAL') ?? get('TOTALTRACKS'));
+    const [discNumber, discTotal] = (get('DISCNUMBER') ?? '').split('/');
+    data.discNumber = number(discNumber);
+    data.totalDiscs = number(discTotal) ?? number(get('DISCTOTAL') ?? get('TOTALDISCS'));
+
+    data.bpm = number(get('BPM') ?? get('TEMPO'));
+    data.copyright = get('COPYRIGHT');
+    data.lyrics = get('LYRICS') ?? get('UNSYNCEDLYRICS');
+    data.releaseDate = get('DATE');
+    data.isrc = get('ISRC');
+    data.upc = get('UPC');
+    data.explicit = get('ITUNESADVISORY') === '1';
+    if (get('MUSICBRAINZ_TRACKID')) {
+        data.musicBrainz = {
+            recordingId: get('MUSICBRAINZ_TRACKID'),
+            releaseId: get('MUSICBRAINZ_ALBUMID'),
+            artistIds: values('MUSICBRAINZ_ARTISTID'),
+        };
+    }
+
+    const replayGain = {};
+    if (get('REPLAYGAIN_ALBUM_GAIN')) replayGain.albumReplayGain = get('REPLAYGAIN_ALBUM_GAIN');
+    if (get('REPLAYGAIN_ALBUM_PEAK')) replayGain.albumPeakAmplitude = Number(get('REPLAYGAIN_ALBUM_PEAK'));
+    if (get('REPLAYGAIN_TRACK_GAIN')) replayGain.trackReplayGain = get('REPLAYGAIN_TRACK_GAIN');
+    if (get('REPLAYGAIN_TRACK_PEAK')) replayGain.trackPeakAmplitude = Number(get('REPLAYGAIN_TRACK_PEAK'));
+    if (Object.keys(replayGain).length > 0) data.replayGain = replayGain;
+
+    const picture = get('METADATA_BLOCK_PICTURE');
+    const legacyCover = get('COVERART');
+    try {
+        if (picture) {
+            data.cover = parsePictureBlock(base64ToBytes(picture)) || undefined;
+        } else if (legacyCover) {
+            data.cover = { data: base64ToBytes(legacyCover), type: get('COVERARTMIME') || 'image/jpeg' };
+        }
+    } catch (e) {
+        console.warn('Error parsing Ogg cover art:', e);
+    }
+
+    const extra = {};
+    for (const key of ['TIDAL_TRACK_ID', 'TIDAL_ALBUM_ID', 'TIDAL_TRACK_URL', 'TIDAL_ALBUM_URL', 'TIDAL_DATA']) {
+        if (get(key)) extra[key] = get(key);
+    }
+    if (get('ALBUM_RELEASE_DATE')) extra.ALBUM_RELEASE_DATE = get('ALBUM_RELEASE_DATE');
+    if (Object.keys(extra).length > 0) data.extra = extra;
+
+    return data;
+}
+
+async function readHeadersFromBlob(blob) {
+    for (const size of [...HEADER_READ_SIZES, blob.size]) {
+        const bytes = new Uint8Array(await blob.slice(0, Math.min(size, blob.size)).arrayBuffer());
+        const headers = readOggHeaders(bytes);
+        if (headers) return { headers, bytes };
+        if (size >= blob.size) break;
+    }
+    throw new Error('Ogg headers are truncated');
+}
+
+/**
+ * Granule position of the last page of `serial`, found by scanning the end of the file
+ */
+async function readLastGranule(blob, serial) {
+    const start = Math.max(0, blob.size - TAIL_READ_SIZE);
+    const tail = new Uint8Array(await blob.slice(start).arrayBuffer());
+    const view = new DataView(tail.buffer);
+
+    for (let i = tail.length - OGG_HEADER_SIZE; i >= 0; i--) {
+        if (view.getUint32(i, false) !== OGG_CAPTURE_PATTERN) continue;
+        if (view.getUint32(i + 14, true) !== serial) continue;
+        const granule = view.getUint32(i + 10, true) * 0x100000000 + view.getUint32(i + 6, true);
+        if (granule < Number.MAX_SAFE_INTEGER) return granule;
+    }
+    return 0;
+}
+
+/**
+ * Read tags and duration from an Ogg Vorbis or Ogg Opus file
+ * @param {Blob} blob
+ * @returns {Promise<import('./taglib.types.ts').TagLibReadMetadata>}
+ */
+export async function readOggMetadata(blob) {
+    const { headers } = await readHeadersFromBlob(blob);
+    const [identification, commentPacket] = headers.packets;
+    const { comments } = parseVorbisCommentPacket(commentPacket);
+    const data = getTagsFromVorbisComments(comments);
+
+    const idView = new DataView(identification.buffer, identification.byteOffset, identification.byteLength);
+    const granule = await readLastGranule(blob, headers.serial);
+    if (headers.codec === 'opus') {
+        // Opus granules always count 48 kHz samples, including the encoder pre-skip
+        const preSkip = idView.getUint16(10, true);
+        data.duration = Math.max(0, granule - preSkip) / 48000;
+    } else {
+        const sampleRate = idView.getUint32(12, true);
+        data.duration = sampleRate > 0 ? granule / sampleRate : 0;
+    }
+
+    return data;
+}
+
+/**
+ * Write tags into an Ogg Vorbis or Ogg Opus file. Tags that are set replace existing values with the
+ * same key, tags set to null are removed; other existing comments are kept.
+ * @param {Blob} blob
+ * @param {import('./taglib.types.ts').TagLibMetadata} data
+ * @returns {Promise<Blob>}
+ */
+export async function writeOggMetadata(blob, data) {
+    const { headers, bytes } = await readHeadersFromBlob(blob);
+    const [, commentPacket, ...otherPackets] = headers.packets;
+    const { vendor, comments } = parseVorbisCommentPacket(commentPacket);
+
+    const updated = createVorbisCommentsFromTags(data);
+    const replacedKeys = new Set(updated.map(([key]) => key));
+    for (const [field, key] of Object.entries(REMOVABLE_TAG_KEYS)) {
+        if (data[field] === null) replacedKeys.add(key);
+    }
+    if (replacedKeys.has('METADATA_BLOCK_PICTURE')) {
+        replacedKeys.add('COVERART');
+        replacedKeys.add('COVERARTMIME');
+    }
+    const merged = [...comments.filter(([key]) => !replacedKeys.has(key)), ...updated];
+
+    // The identification page is kept byte for byte
+    const firstPage = bytes.subarray(0, headers.pages[0].size);
+    const headerPages = createOggPages(
+        [createVorbisCommentPacket(headers.codec, merged, vendor || undefined), ...otherPackets],
+        headers.serial,
+        headers.pages[0].sequence + 1
+    );
+
+    const delta = 1 + headerPages.length - headers.pages.length;
+    let rest = blob.slice(headers.end);
+    if (delta !== 0) {
+        const restBytes = new Uint8Array(await rest.arrayBuffer());
+        renumberOggPages(restBytes, headers.serial, delta);
+        rest = restBytes;
+    }
+
+    return new Blob([firstPage, ...headerPages, rest], { type: blob.type || 'audio/ogg' });
+}
@@ -0,0 +1,357 @@
+import {
+    buildID3v2Tag,
+    createAPICFrame,
+    createTextFrame,
+    readID3Text,
+    readSynchsafeInteger32,
+} from './metadata.mp3.js';
+
+// WAV and AIFF tags. WAV keeps text in a LIST/INFO chunk and AIFF in NAME/AUTH/(c) chunks; both may
+// also carry a full ID3v2 tag in an "id3 " chunk, which is where cover art lives. Chunk sizes are
+// little-endian in RIFF and big-endian in AIFF, and every chunk is padded to an even length.
+
+const RIFF_INFO_TAGS = {
+    INAM: 'title',
+    IART: 'artist',
+    IPRD: 'albumTitle',
+    ICRD: 'releaseDate',
+    ICOP: 'copyright',
+    ITRK: 'trackNumber',
+    IPRT: 'trackNumber',
+    ISRC: 'isrc',
+};
+
+const AIFF_TEXT_TAGS = {
+    NAME: 'title',
+    AUTH: 'artist',
+    '(c) ': 'copyright',
+};
+
+const ID3_TEXT_TAGS = {
+    TIT2: 'title',
+    TPE1: 'artist',
+    TALB: 'albumTitle',
+    TPE2: 'albumArtist',
+    TSRC: 'isrc',
+    TCOP: 'copyright',
+    TBPM: 'bpm',
+    TYER: 'releaseDate',
+    TDRC: 'releaseDate',
+};
+
+/**
+ * 'wav' for RIFF/WAVE, 'aiff' for FORM/AIFF or AIFC, otherwise null
+ * @param {DataView} dataView - At least the first 12 bytes of the file
+ */
+export function getRiffFormat(dataView) {
+    if (dataView.byteLength < 12) return null;
+    const id = fourCC(dataView, 0);
+    const form = fourCC(dataView, 8);
+    if (id === 'RIFF' && form === 'WAVE') return 'wav';
+    if (id === 'FORM' && (form === 'AIFF' || form === 'AIFC')) return 'aiff';
+    return null;
+}
+
+function fourCC(view, offset) {
+    return String.fromCharCode(
+        view.getUint8(offset),
+        view.getUint8(offset + 1),
+        view.getUint8(offset + 2),
+        view.getUint8(offset + 3)
+    );
+}
+
+/**
+ * List the top-level chunks without reading their contents
+ * @param {Blob} blob
+ * @param {boolean} littleEndian - true for RIFF, false for AIFF
+ * @returns {Promise<{id: string, offset: number, size: number}[]>} offset points at the chunk data
+ */
+export async function listRiffChunks(blob, littleEndian) {
+    const chunks = [];
+    let offset = 12;
+    while (offset + 8 <= blob.size) {
+        const view = new DataView(await blob.slice(offset, offset + 8).arrayBuffer());
+        const id = fourCC(view, 0);
+        // Streamed WAVs sometimes leave a placeholder size on the data chunk
+        const size = Math.min(view.getUint32(4, littleEndian), blob.size - offset - 8);
+        chunks.push({ id, offset: offset + 8, size });
+        offset += 8 + size + (size % 2);
+    }
+    return chunks;
+}
+
+function readString(bytes) {
+    return new TextDecoder()
+        .decode(bytes)
+        .replace(/\0+$/, '')
+        .trim();
+}
+
+function createChunk(id, data, littleEndian) {
+    const chunk = new Uint8Array(8 + data.length + (data.length % 2));
+    const view = new DataView(chunk.buffer);
+    for (let i = 0; i < 4; i++) chunk[i] = id.charCodeAt(i);
+    view.setUint32(4, data.length, littleEndian);
+    chunk.set(data, 8);
+    return chunk;
+}
+
+function createStringChunk(id, value, littleEndian) {
+    const text = new TextEncoder().encode(String(value));
+    const data = new Uint8Array(text.length + 1); // null-terminated
+    data.set(text);
+    return createChunk(id, data, littleEndian);
+}
+
+/**
+ * Parse the text frames and front cover of an ID3v2.3/2.4 tag
+ * @param {Uint8Array} bytes
+ */
+export function parseID3v2Tag(bytes) {
+    const data = {};
+    if (bytes.length < 10 || bytes[0] !== 0x49 || bytes[1] !== 0x44 || bytes[2] !== 0x33) return data;
+
+    const view = new DataView(bytes.buffer, bytes.byteOffset, bytes.byteLength);
+    const majorVer = view.getUint8(3);
+    if (majorVer !== 3 && majorVer !== 4) return data;
+
+    const end = Math.min(bytes.length, 10 + readSynchsafeInteger32(view, 6));
+    let offset = 10;
+    if ((view.getUint8(5) & 0x40) !== 0) {
+        offset += majorVer === 4 ? readSynchsafeInteger32(view, offset) : view.getUint32(offset, false) + 4;
+    }
+
+    while (offset + 10 <= end) {
+        const frameId = fourCC(view, offset);
+        const frameSize = majorVer === 4 ? readSynchsafeInteger32(view, offset + 4) : view.getUint32(offset + 4, false);
+        offset += 10;
+        if (frameId.charCodeAt(0) === 0 || offset + frameSize > end) break;
+
+        const frame = new DataView(bytes.buffer, bytes.byteOffset + offset, frameSize);
+        const key = ID3_TEXT_TAGS[frameId];
+        if (key) {
+            data[key] = readID3Text(frame);
+        } else if (frameId === 'TRCK' || frameId === 'TPOS') {
+            const [number, total] = readID3Text(frame).split('/');
+            const isTrack = frameId === 'TRCK';
+            data[isTrack ? 'trackNumber' : 'discNumber'] = Number(number) || undefined;
+            if (Number(total)) data[isTrack ? 'totalTracks' : 'totalDiscs'] = Number(total);
+        } else if (frameId === 'APIC' && !data.cover) {
+            const cover = parseAPICFrame(bytes.subarray(offset, offset + frameSize));
+            if (cover) data.cover = cover;
+        }
+
+        offset += frameSize;
+    }
+
+    if (data.bpm) data.bpm = Number(data.bpm) || undefined;
+    return data;
+}
+
+function parseAPICFrame(frame) {
+    const encoding = frame[0];
+    let pos = 1;
+    let type = '';
+    while (pos < frame.length && frame[pos] !== 0) type += String.fromCharCode(frame[pos++]);
+    pos += 2; // terminator + picture type
+
+    // Skip the description, whose terminator width depends on the text encoding
+    const wide = encoding === 1 || encoding === 2;
+    while (pos < frame.length) {
+        if (!wide && frame[pos] === 0) {
+            pos += 1;
+            break;
+        }
+        if (wide && frame[pos] === 0 && frame[pos + 1] === 0) {
+            pos += 2;
+            break;
+        }
+        pos += wide ? 2 : 1;
+    }
+
+    if (pos >= frame.length) return null;
+    return { data: frame.slice(pos), type: type || 'image/jpeg' };
+}
+
+/**
+ * An ID3v2.3 tag with the given tags
+ * @param {import('./taglib.types.ts').TagLibMetadata} data
+ * @returns {Promise<Uint8Array>}
+ */
+export async function createID3v2Tag(data) {
+    const frames = [];
+    const artist = Array.isArray(data.artist) ? data.artist.join('; ') : data.artist;
+
+    if (data.title) frames.push(createTextFrame('TIT2', data.title));
+    if (artist) frames.push(createTextFrame('TPE1', artist));
+    if (data.albumTitle) frames.push(createTextFrame('TALB', data.albumTitle));
+    if (data.albumArtist || artist) frames.push(createTextFrame('TPE2', data.albumArtist || artist));
+    if (data.trackNumber) {
+        const track = data.totalTracks ? `${data.trackNumber}/${data.totalTracks}` : String(data.trackNumber);
+        frames.push(createTextFrame('TRCK', track));
+    }
+    if (data.discNumber) {
+        const disc = data.totalDiscs ? `${data.discNumber}/${data.totalDiscs}` : String(data.discNumber);
+        frames.push(createTextFrame('TPOS', disc));
+    }
+    if (data.releaseDate) {
+        const year = Number(String(data.releaseDate).split('-')[0]);
+        if (!isNaN(year)) frames.push(createTextFrame('TYER', String(year)));
+    }
+    if (data.bpm != null && Number.isFinite(Number(data.bpm))) {
+        frames.push(createTextFrame('TBPM', String(Math.round(Number(data.bpm)))));
+    }
+    if (data.isrc) frames.push(createTextFrame('TSRC', data.isrc));
+    if (data.copyright) frames.push(createTextFrame('TCOP', data.copyright));
+    if (data.cover?.data?.length) {
+        frames.push(await createAPICFrame(new Blob([data.cover.data], { type: data.cover.type })));
+    }
+
+    return new Uint8Array(await buildID3v2Tag(new Blob([]), frames).arrayBuffer());
+}
+
+/**
+ * 80-bit IEEE extended float, as used for the AIFF sample rate
+ */
+function readExtended(view, offset) {
+    const exponent = view.getUint16(offset, false) & 0x7fff;
+    const mantissa = view.getUint32(offset + 2, false) * 0x100000000 + view.getUint32(offset + 6, false);
+    if (exponent === 0 && mantissa === 0) return 0;
+    return mantissa * Math.pow(2, exponent - 16383 - 63);
+}
+
+async function readChunk(blob, chunk) {
+    return new Uint8Array(await blob.slice(chunk.offset, chunk.offset + chunk.size).arrayBuffer());
+}
+
+/**
+ * Read tags and duration from a WAV or AIFF file. ID3 values win over INFO/text chunks.
+ * @param {Blob} blob
+ * @returns {Promise<import('./taglib.types.ts').TagLibReadMetadata>}
+ */
+export async function readRiffMetadata(blob) {
+    const format = getRiffFormat(new DataView(await blob.slice(0, 12).arrayBuffer()));
+    if (!format) throw new Error('Not a WAV or AIFF file');
+
+    const littleEndian = format === 'wav';
+    const chunks = await listRiffChunks(blob, littleEndian);
+    const data = { duration: 0 };
+    let id3 = {};
+    let byteRate = 0;
+
+    for (const chunk of chunks) {
+        if (format === 'wav' && chunk.id === 'fmt ') {
+            const view = new DataView((await readChunk(blob, chunk)).buffer);
+            byteRate = view.getUint32(8, true);
+        } else if (format === 'wav' && chunk.id === 'data' && byteRate > 0) {
+            data.duration = chunk.size / byteRate;
+        } else if (format === 'aiff' && chunk.id === 'COMM') {
+            const view = new DataView((await readChunk(blob, chunk)).buffer);
+            const sampleRate = readExtended(view, 8);
+            if (sampleRate > 0) data.duration = view.getUint32(2, false) / sampleRate;
+        } else if (format === 'wav' && chunk.id === 'LIST') {
+            const bytes = await readChunk(blob, chunk);
+            if (readString(bytes.subarray(0, 4)) !== 'INFO') continue;
+            const view = new DataView(bytes.buffer);
+            let pos = 4;
+            while (pos + 8 <= bytes.length) {
+                const id = fourCC(view, pos);
+                const size = view.getUint32(pos + 4, true);
+                const key = RIFF_INFO_TAGS[id];
+                if (key) data[key] = readString(bytes.subarray(pos + 8, pos + 8 + size)) || data[key];
+                pos += 8 + size + (size % 2);
+            }
+        } else if (format === 'aiff' && AIFF_TEXT_TAGS[chunk.id]) {
+            data[AIFF_TEXT_TAGS[chunk.id]] = readString(await readChunk(blob, chunk)) || undefined;
+        } else if (chunk.id.toLowerCase() === 'id3 ') {
+            id3 = parseID3v2Tag(await readChunk(blob, chunk));
+        }
+    }
+
+    if (data.trackNumber) data.trackNumber = Number(String(data.trackNumber).split('/')[0]) || undefined;
+
+    for (const [key, value] of Object.entries(id3)) {
+        if (value !== undefined && value !== '') data[key] = value;
+    }
+
+    return data;
+}
+
+/**
+ * Write tags into a WAV or AIFF file, replacing its INFO/text and ID3 chunks. Values that are not set
+ * keep what the file already had, values set to null are removed. Audio chunks are copied as Blob slices, never loaded.
+ * @param {Blob} blob
+ * @param {import('./taglib.types.ts').TagLibMetadata} data
+ * @returns {Promise<Blob>}
+ */
+export async function writeRiffMetadata(blob, data) {
+    const header = new Uint8Array(await blob.slice(0, 12).arrayBuffer());
+    const format = getRiffFormat(new DataView(header.buffer));
+    if (!format) throw new Error('Not a WAV or AIFF file');
+
+    const littleEndian = format === 'wav';
+    const existing = await readRiffMetadata(blob);
+    const tags = { ...existing };
+    for (const [key, value] of Object.entries(data)) {
+        if (value === null) delete tags[key];
+        else if (value !== undefined && value !== '') tags[key] = value;
+    }
+    const artist = Array.isArray(tags.artist) ? tags.artist.join('; ') : tags.artist;
+
+    const kept = [];
+    for (const chunk of await listRiffChunks(blob, littleEndian)) {
+        if (chunk.id.toLowerCase() === 'id3 ') continue;
+        if (format === 'aiff' && AIFF_TEXT_TAGS[chunk.id]) continue;
+        // Other LIST chunks (e.g. adtl cue labels) are kept
+        if (format === 'wav' && chunk.id === 'LIST') {
+            const listType = await blob.slice(chunk.offset, chunk.offset + 4).text();
+            if (listType === 'INFO') continue;
+        }
+        kept.push(chunk);
+    }
+
+    const tagChunks = [];
+    if (format === 'wav') {
+        const info = [];
+        const add = (id, value) => value && info.push(createStringChunk(id, value, true));
+        add('INAM', tags.title);
+        add('IART', artist);
+        add('IPRD', tags.albumTitle);
+        add('ICRD', tags.releaseDate && String(tags.releaseDate).split('-')[0]);
+        add('ITRK', tags.trackNumber);
+        add('ICOP', tags.copyright);
+        add('ISRC', tags.isrc);
+        if (info.length) {
+            const body = new Uint8Array(4 + info.reduce((sum, chunk) => sum + chunk.length, 0));
+            body.set(new TextEncoder().encode('INFO'));
+            let offset = 4;
+            for (const chunk of info) {
+                body.set(chunk, offset);
+                offset += chunk.length;
+            }
+            tagChunks.push(createChunk('LIST', body, true));
+        }
+    } else {
+        if (tags.title) tagChunks.push(createChunk('NAME', new TextEncoder().encode(tags.title), false));
+        if (artist) tagChunks.push(createChunk('AUTH', new TextEncoder().encode(artist), false));
+        if (tags.copyright) tagChunks.push(createChunk('(c) ', new TextEncoder().encode(tags.copyright), false));
+    }
+    tagChunks.push(createChunk(format === 'wav' ? 'id3 ' : 'ID3 ', await createID3v2Tag(tags), littleEndian));
+
+    const parts = [];
+    for (const chunk of kept) {
+        const chunkHeader = new Uint8Array(8);
+        const view = new DataView(chunkHeader.buffer);
+        for (let i = 0; i < 4; i++) chunkHeader[i] = chunk.id.charCodeAt(i);
+        view.setUint32(4, chunk.size, littleEndian);
+        parts.push(chunkHeader, blob.slice(chunk.offset, chunk.offset + chunk.size));
+        if (chunk.size % 2) parts.push(new Uint8Array(1));
+    }
+    parts.push(...tagChunks);
+
+    const bodySize = parts.reduce((sum, part) => sum + (part.size ?? part.length), 0);
+    new DataView(header.buffer).setUint32(4, 4 + bodySize, littleEndian);
+
+    return new Blob([header, ...parts], { type: blob.type || (format === 'wav' ? 'audio/wav' : 'audio/aiff') });
+}
@@ -105,6 +105,10 @@ export class MusicAPI {
         return this.getAPI().searchTracks(query, options);
     }
 
+    async searchTracksByIsrc(isrc, options = {}) {
+        return this.getAPI().searchTracksByIsrc(isrc, options);
+    }
+
     async searchArtists(query, options = {}) {
         return this.getAPI().searchArtists(query, options);
     }
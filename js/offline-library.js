@@ -0,0 +1,505 @@
+// js/offline-library.js
+// Offline library: keeps downloaded tracks as blobs (OPFS when available, IndexedDB otherwise) so they
+// can be played without the network. Indexed in MusicDatabase by track ID + quality; user playlists
+// marked "keep offline" are re-synced whenever they change, and unpinned tracks are evicted LRU-first
+// when the storage budget or browser quota runs out.
+
+import { db } from './db.js';
+import { offlineSettings } from './storage.js';
+
+const OPFS_DIRECTORY = 'offline-tracks';
+// Space left free for the rest of the app when the browser quota is the limit
+const QUOTA_HEADROOM_BYTES = 200 * 1024 * 1024;
+// Coalesces bursts of playlist edits into a single sync
+const SYNC_DEBOUNCE_MS = 2000;
+
+// Higher is better; unknown (e.g. transcoded download) qualities rank lowest
+export const OFFLINE_QUALITY_RANK = {
+    HI_RES_LOSSLESS: 4,
+    LOSSLESS: 3,
+    HIGH: 2,
+    LOW: 1,
+};
+
+export class OfflineStorageError extends Error {
+    constructor(message) {
+        super(message);
+        this.name = 'OfflineStorageError';
+    }
+}
+
+export function getOfflineKey(trackId, quality) {
+    return `${trackId}_${quality}`;
+}
+
+/**
+ * Whether a queue/playlist item can be stored offline (streamed catalog audio only)
+ */
+export function isOfflineCapable(track) {
+    if (!track?.id || track.isLocal || track.type === 'video') return false;
+    const id = String(track.id);
+    return !track.isTracker && !track.isPodcast && !id.startsWith('tracker-') && !id.startsWith('podcast_');
+}
+
+/**
+ * Pick the copy to play: the requested quality if stored, otherwise the best one available
+ * @param {object[]} entries - Offline entries for one track
+ * @param {string} quality
+ */
+export function pickOfflineEntry(entries, quality) {
+    if (!entries?.length) return null;
+    const exact = entries.find((entry) => entry.quality === quality);
+    if (exact) return exact;
+    const rank = (entry) => OFFLINE_QUALITY_RANK[entry.quality] || 0;
+    return [...entries].sort((a, b) => rank(b) - rank(a))[0];
+}
+
+/**
+ * Choose least-recently-used entries to delete until `bytesNeeded` is freed. Entries pinned by a
+ * kept playlist (or listed in `protectedKeys`) are never chosen.
+ * @returns {{entries: object[], bytes: number}} Possibly less than needed if not enough is evictable
+ */
+export function selectEvictions(entries, bytesNeeded, protectedKeys = new Set()) {
+    const candidates = entries
+        .filter((entry) => !protectedKeys.has(entry.key) && !(entry.playlists?.length > 0))
+        .sort((a, b) => (a.lastUsedAt || a.addedAt || 0) - (b.lastUsedAt || b.addedAt || 0));
+
+    const selected = [];
+    let bytes = 0;
+    for (const entry of candidates) {
+        if (bytes >= bytesNeeded) break;
+        selected.push(entry);
+        bytes += entry.size || 0;
+    }
+    return { entries: selected, bytes };
+}
+
+/**
+ * Work out what a playlist sync has to do. Any stored copy of a track counts, whatever its quality.
+ * @param {object[]} entries - All offline entries
+ * @param {string} playlistId
+ * @param {object[]} tracks - Current playlist tracks
+ * @returns {{download: object[], attach: object[], release: object[]}} Tracks to fetch, entries to pin
+ *   to the playlist, and entries the playlist no longer needs
+ */
+export function planPlaylistSync(entries, playlistId, tracks) {
+    const wanted = new Map();
+    for (const track of tracks) {
+        if (isOfflineCapable(track)) wanted.set(String(track.id), track);
+    }
+
+    const stored = new Set();
+    const attach = [];
+    const release = [];
+    for (const entry of entries) {
+        const pinned = entry.playlists?.includes(playlistId);
+        if (wanted.has(entry.trackId)) {
+            stored.add(entry.trackId);
+            if (!pinned) attach.push(entry);
+        } else if (pinned) {
+            release.push(entry);
+        }
+    }
+
+    const download = [...wanted.entries()].filter(([id]) => !stored.has(id)).map(([, track]) => track);
+    return { download, attach, release };
+}
+
+function supportsOpfs() {
+    return (
+        typeof navigator !== 'undefined' &&
+        typeof navigator.storage?.getDirectory === 'function' &&
+        typeof FileSystemFileHandle !== 'undefined' &&
+        'createWritable' in FileSystemFileHandle.prototype
+    );
+}
+
+const opfsFileName = (key) => key.replace(/[^a-zA-Z0-9_-]/g, '_');
+
+const opfsBlobStore = {
+    async _directory() {
+        const root = await navigator.storage.getDirectory();
+        return root.getDirectoryHandle(OPFS_DIRECTORY, { create: true });
+    },
+
+    async put(key, blob) {
+        const directory = await this._directory();
+        const handle = await directory.getFileHandle(opfsFileName(key), { create: true });
+        const writable = await handle.createWritable();
+        try {
+            await writable.write(blob);
+            await writable.close();
+        } catch (e) {
+            await writable.abort().catch(() => {});
+            throw e;
+        }
+    },
+
+    async get(key) {
+        try {
+            const directory = await this._directory();
+            const handle = await directory.getFileHandle(opfsFileName(key));
+            return await handle.getFile();
+        } catch {
+            return null;
+        }
+    },
+
+    async delete(key) {
+        try {
+            const directory = await this._directory();
+            await directory.removeEntry(opfsFileName(key));
+        } catch {
+            // already gone
+        }
+    },
+
+    async clear() {
+        try {
+            const root = await navigator.storage.getDirectory();
+            await root.removeEntry(OPFS_DIRECTORY, { recursive: true });
+        } catch {
+            // never created
+        }
+    },
+};
+
+const idbBlobStore = {
+    put: (key, blob) => db.saveOfflineBlob(key, blob),
+    get: async (key) => (await db.getOfflineBlob(key)) || null,
+    delete: (key) => db.deleteOfflineBlob(key),
+};
+
+const blobStores = { opfs: opfsBlobStore, idb: idbBlobStore };
+
+export class OfflineLibrary {
+    constructor() {
+        this.api = null;
+        this.syncStatus = new Map(); // playlistId -> { done, total, failed, error, syncing }
+        this._syncChain = Promise.resolve();
+        this._syncTimers = new Map();
+        this._syncControllers = new Map();
+    }
+
+    /**
+     * Start background syncing of kept playlists
+     * @param {object} api - MusicAPI instance used for downloads
+     */
+    init(api) {
+        if (this.api) return;
+        this.api = api;
+
+        window.addEventListener('sync-playlist-change', (e) => {
+            const { action, playlist } = e.detail || {};
+            if (!playlist?.id || !offlineSettings.isPlaylistKept(playlist.id)) return;
+            if (action === 'delete') {
+                void this.setPlaylistKeepOffline(playlist.id, false);
+            } else {
+                this.scheduleSync(playlist.id);
+            }
+        });
+        // Cloud sync replaced local playlists; reconnecting lets pending downloads continue
+        window.addEventListener('library-changed', () => this.syncAll());
+        window.addEventListener('online', () => this.syncAll());
+
+        this.syncAll();
+    }
+
+    // ==========================================
+    // Lookup / playback
+    // ==========================================
+
+    async find(trackId, quality) {
+        try {
+            return pickOfflineEntry(await db.getOfflineTracksForTrack(trackId), quality);
+        } catch (e) {
+            console.warn('[Offline] Lookup failed:', e);
+            return null;
+        }
+    }
+
+    async has(trackId) {
+        return (await this.find(trackId, null)) !== null;
+    }
+
+    /**
+     * Stored audio for a track, preferring the given quality. Marks the copy as recently used.
+     * @returns {Promise<{entry: object, blob: Blob}|null>}
+     */
+    async getPlayable(trackId, quality) {
+        if (!trackId) return null;
+        const entry = await this.find(trackId, quality);
+        if (!entry) return null;
+
+        const stored = await blobStores[entry.storage]?.get(entry.key);
+        if (!stored) {
+            // The browser cleared the blob behind our back; drop the stale index entry
+            await db.deleteOfflineTrack(entry.key).catch(() => {});
+            this._notifyChanged();
+            return null;
+        }
+
+        entry.lastUsedAt = Date.now();
+        void db.saveOfflineTrack(entry).catch(() => {});
+        const blob = stored.type ? stored : new Blob([stored], { type: entry.mimeType || 'audio/flac' });
+        return { entry, blob };
+    }
+
+    // ==========================================
+    // Saving / removing
+    // ==========================================
+
+    /**
+     * Store an already-downloaded blob
+     * @param {object} track
+     * @param {string} quality
+     * @param {Blob} blob
+     * @param {{playlistId?: string, source?: 'playlist'|'download'}} [options]
+     */
+    async saveBlob(track, quality, blob, { playlistId = null, source = 'download' } = {}) {
+        const key = getOfflineKey(track.id, quality);
+        const existing = (await db.getOfflineTracksForTrack(track.id)).find((entry) => entry.key === key);
+        if (existing) {
+            if (playlistId && !existing.playlists.includes(playlistId)) {
+                existing.playlists.push(playlistId);
+                await db.saveOfflineTrack(existing);
+            }
+            return existing;
+        }
+
+        await this._ensureSpace(blob.size);
+
+        const storage = supportsOpfs() ? 'opfs' : 'idb';
+        try {
+            await blobStores[storage].put(key, blob);
+        } catch (e) {
+            if (e?.name === 'QuotaExceededError') {
+                throw new OfflineStorageError('Browser storage is full');
+            }
+            throw e;
+        }
+
+        const now = Date.now();
+        const entry = {
+            key,
+            trackId: String(track.id),
+            quality,
+            track: db.minifyItem('track', track),
+            size: blob.size,
+            mimeType: blob.type || 'audio/flac',
+            storage,
+            source,
+            playlists: playlistId ? [playlistId] : [],
+            addedAt: now,
+            lastUsedAt: now,
+        };
+        await db.saveOfflineTrack(entry);
+        void navigator.storage?.persist?.().catch(() => {});
+        this._notifyChanged();
+        return entry;
+    }
+
+    /**
+     * Download a track at the offline quality and store it
+     */
+    async saveTrack(track, { playlistId = null, signal = null, onProgress = null } = {}) {
+        if (!this.api) throw new OfflineStorageError('Offline library is not initialized');
+        const quality = offlineSettings.getQuality();
+        const blob = await this.api.downloadTrack(track.id, quality, undefined, {
+            track,
+            signal,
+            onProgress,
+            triggerDownload: false,
+            calculateDashBytes: false,
+        });
+        return this.saveBlob(track, quality, blob, { playlistId, source: playlistId ? 'playlist' : 'download' });
+    }
+
+    async removeEntry(entry) {
+        await blobStores[entry.storage]?.delete(entry.key);
+        await db.deleteOfflineTrack(entry.key);
+        this._notifyChanged();
+    }
+
+    async clear() {
+        for (const controller of this._syncControllers.values()) controller.abort();
+        this._syncControllers.clear();
+        this.syncStatus.clear();
+        offlineSettings.clearKeptPlaylists();
+
+        await db.clearOfflineTracks();
+        await opfsBlobStore.clear();
+        this._notifyChanged();
+    }
+
+    // ==========================================
+    // Quota accounting / eviction
+    // ==========================================
+
+    /**
+     * @returns {Promise<{bytes: number, count: number, limit: number, quota: number|null, usage: number|null}>}
+     */
+    async getUsage() {
+        const entries = await db.getOfflineTracks();
+        const estimate = await navigator.storage?.estimate?.().catch(() => null);
+        return {
+            bytes: entries.reduce((sum, entry) => sum + (entry.size || 0), 0),
+            count: entries.length,
+            limit: offlineSettings.getMaxBytes(),
+            quota: estimate?.quota ?? null,
+            usage: estimate?.usage ?? null,
+        };
+    }
+
+    /**
+     * Make room for `bytes` more, evicting unpinned tracks if needed
+     */
+    async _ensureSpace(bytes) {
+        const entries = await db.getOfflineTracks();
+        const used = entries.reduce((sum, entry) => sum + (entry.size || 0), 0);
+        const limit = offlineSettings.getMaxBytes();
+
+        let needed = limit > 0 ? used + bytes - limit : 0;
+        const estimate = await navigator.storage?.estimate?.().catch(() => null);
+        if (estimate?.quota) {
+            const free = estimate.quota - (estimate.usage || 0) - QUOTA_HEADROOM_BYTES;
+            needed = Math.max(needed, bytes - free);
+        }
+        if (needed <= 0) return;
+
+        const evictions = selectEvictions(entries, needed);
+        if (evictions.bytes < needed) {
+            throw new OfflineStorageError('Not enough offline storage space');
+        }
+        for (const entry of evictions.entries) {
+            console.log(`[Offline] Evicting ${entry.track?.title || entry.key}`);
+            await blobStores[entry.storage]?.delete(entry.key);
+            await db.deleteOfflineTrack(entry.key);
+        }
+    }
+
+    // ==========================================
+    // Keep-offline playlists
+    // ==========================================
+
+    isPlaylistKeptOffline(playlistId) {
+        return offlineSettings.isPlaylistKept(playlistId);
+    }
+
+    getSyncStatus(playlistId) {
+        return this.syncStatus.get(playlistId) || null;
+    }
+
+    async setPlaylistKeepOffline(playlistId, keep) {
+        offlineSettings.setPlaylistKept(playlistId, keep);
+        if (keep) {
+            this.scheduleSync(playlistId, 0);
+            return;
+        }
+
+        this._syncControllers.get(playlistId)?.abort();
+        clearTimeout(this._syncTimers.get(playlistId));
+        this._syncTimers.delete(playlistId);
+        this.syncStatus.delete(playlistId);
+
+        const entries = await db.getOfflineTracks();
+        for (const entry of entries.filter((e) => e.playlists?.includes(playlistId))) {
+            await this._releaseEntry(entry, playlistId);
+        }
+        this._notifyChanged(playlistId);
+    }
+
+    /**
+     * Unpin an entry from a playlist; tracks only kept for playlists are deleted once nothing needs them
+     */
+    async _releaseEntry(entry, playlistId) {
+        entry.playlists = entry.playlists.filter((id) => id !== playlistId);
+        if (entry.playlists.length === 0 && entry.source !== 'download') {
+            await blobStores[entry.storage]?.delete(entry.key);
+            await db.deleteOfflineTrack(entry.key);
+        } else {
+            await db.saveOfflineTrack(entry);
+        }
+    }
+
+    syncAll() {
+        for (const playlistId of offlineSettings.getKeptPlaylists()) {
+            this.scheduleSync(playlistId);
+        }
+    }
+
+    scheduleSync(playlistId, delay = SYNC_DEBOUNCE_MS) {
+        clearTimeout(this._syncTimers.get(playlistId));
+        this._syncTimers.set(
+            playlistId,
+            setTimeout(() => {
+                this._syncTimers.delete(playlistId);
+                this._syncChain = this._syncChain.then(() => this._syncPlaylist(playlistId)).catch(console.error);
+            }, delay)
+        );
+    }
+
+    async _syncPlaylist(playlistId) {
+        if (!offlineSettings.isPlaylistKept(playlistId) || !this.api) return;
+
+        const playlist = await db.getPlaylist(playlistId);
+        if (!playlist) {
+            await this.setPlaylistKeepOffline(playlistId, false);
+            return;
+        }
+
+        const plan = planPlaylistSync(await db.getOfflineTracks(), playlistId, playlist.tracks || []);
+        for (const entry of plan.attach) {
+            entry.playlists = [...(entry.playlists || []), playlistId];
+            await db.saveOfflineTrack(entry);
+        }
+        for (const entry of plan.release) {
+            await this._releaseEntry(entry, playlistId);
+        }
+
+        const status = { done: 0, total: plan.download.length, failed: 0, error: null, syncing: true };
+        this.syncStatus.set(playlistId, status);
+        this._notifyChanged(playlistId);
+        if (plan.download.length > 0 && typeof navigator !== 'undefined' && navigator.onLine === false) {
+            status.syncing = false;
+            status.error = 'Waiting for a connection';
+            this._notifyChanged(playlistId);
+            return;
+        }
+
+        const controller = new AbortController();
+        this._syncControllers.set(playlistId, controller);
+        try {
+            for (const track of plan.download) {
+                if (controller.signal.aborted) return;
+                try {
+                    await this.saveTrack(track, { playlistId, signal: controller.signal });
+                    status.done++;
+                } catch (e) {
+                    if (e?.name === 'AbortError') return;
+                    if (e instanceof OfflineStorageError) {
+                        status.error = e.message;
+                        break;
+                    }
+                    console.warn(`[Offline] Failed to save ${track.title}:`, e);
+                    status.failed++;
+                }
+                this._notifyChanged(playlistId);
+            }
+        } finally {
+            status.syncing = false;
+            this._syncControllers.delete(playlistId);
+            this._notifyChanged(playlistId);
+        }
+    }
+
+    _notifyChanged(playlistId = null) {
+        window.dispatchEvent(
+            new CustomEvent('offline-library-changed', {
+                detail: { playlistId, status: playlistId ? this.getSyncStatus(playlistId) : null },
+            })
+        );
+    }
+}
+
+export const offlineLibrary = new OfflineLibrary();
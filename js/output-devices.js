@@ -0,0 +1,85 @@
+// js/output-devices.js
+// Audio output device discovery for sink selection and per-device EQ profiles
+
+// Sink IDs that name a role rather than a physical device
+const ALIAS_IDS = new Set(['', 'default', 'communications']);
+
+/**
+ * Whether the Web Audio output can be routed to a specific device
+ */
+export function isOutputSelectionSupported() {
+    return typeof AudioContext !== 'undefined' && 'setSinkId' in AudioContext.prototype;
+}
+
+/**
+ * @returns {Promise<Array<{deviceId: string, groupId: string, label: string}>>}
+ */
+export async function listOutputDevices() {
+    if (typeof navigator === 'undefined' || !navigator.mediaDevices?.enumerateDevices) return [];
+    try {
+        const devices = await navigator.mediaDevices.enumerateDevices();
+        return devices
+            .filter((d) => d.kind === 'audiooutput')
+            .map(({ deviceId, groupId, label }) => ({ deviceId, groupId, label }));
+    } catch (e) {
+        console.warn('[OutputDevices] Failed to enumerate devices:', e);
+        return [];
+    }
+}
+
+/**
+ * Output device IDs and labels stay hidden until the page has media permission.
+ * Firefox grants it per device through selectAudioOutput; elsewhere a short-lived microphone grant unlocks them.
+ * @returns {Promise<string|null>} The device the user picked, when the browser asked for one
+ */
+export async function requestOutputDeviceAccess() {
+    const media = typeof navigator !== 'undefined' ? navigator.mediaDevices : null;
+    if (!media) return null;
+    if (media.selectAudioOutput) {
+        const device = await media.selectAudioOutput();
+        return device.deviceId;
+    }
+    const stream = await media.getUserMedia({ audio: true });
+    stream.getTracks().forEach((track) => track.stop());
+    return null;
+}
+
+/**
+ * Resolve a sink ID to the physical device it plays on. The "default" entry is an alias that shares
+ * its groupId with the real device, so plugging in headphones changes what it resolves to.
+ * A selected device that has disappeared resolves to the default, as the browser falls back to it.
+ * @param {Array<{deviceId: string, groupId: string, label: string}>} devices - From listOutputDevices
+ * @param {string} sinkId - Selected sink, '' for the system default
+ * @returns {{deviceId: string, groupId: string, label: string}|null}
+ */
+export function resolveOutputDevice(devices, sinkId) {
+    if (sinkId && !ALIAS_IDS.has(sinkId)) {
+        const selected = devices.find((d) => d.deviceId === sinkId);
+        if (selected) return selected;
+    }
+
+    const physical = devices.filter((d) => !ALIAS_IDS.has(d.deviceId));
+    const alias = devices.find((d) => d.deviceId === 'default');
+    if (!alias) {
+        // Browsers without a default alias list the default device first
+        return physical[0] || null;
+    }
+    return physical.find((d) => d.groupId === alias.groupId) || alias;
+}
+
+/**
+ * Find the profile bound to a device. Device IDs are regenerated when site data is cleared,
+ * so a binding with the same label is accepted too.
+ * @param {Object<string, {profileId: string, label: string}>} bindings
+ * @param {{deviceId: string, label: string}|null} device
+ * @returns {string|null}
+ */
+export function findBoundProfileId(bindings, device) {
+    if (!device) return null;
+    if (bindings[device.deviceId]) return bindings[device.deviceId].profileId;
+    if (device.label) {
+        const match = Object.values(bindings).find((binding) => binding.label === device.label);
+        if (match) return match.profileId;
+    }
+    return null;
+}
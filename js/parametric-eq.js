@@ -0,0 +1,344 @@
+// js/parametric-eq.js
+// Parametric EQ band model shared by the audio engine and the EQ panel:
+// filter stages per band, magnitude response, and Equalizer APO text import/export.
+
+export const EQ_FILTER_TYPES = [
+    'peaking',
+    'lowshelf',
+    'highshelf',
+    'notch',
+    'bandpass',
+    'highpass',
+    'lowpass',
+    'allpass',
+];
+export const EQ_CHANNELS = ['stereo', 'mid', 'side', 'left', 'right'];
+export const EQ_SLOPES = [12, 24, 48];
+
+const GAIN_TYPES = new Set(['peaking', 'lowshelf', 'highshelf']);
+const SLOPE_TYPES = new Set(['highpass', 'lowpass']);
+
+const APO_TYPE_MAP = {
+    PK: 'peaking',
+    PEQ: 'peaking',
+    LS: 'lowshelf',
+    LSC: 'lowshelf',
+    LSF: 'lowshelf',
+    HS: 'highshelf',
+    HSC: 'highshelf',
+    HSF: 'highshelf',
+    NO: 'notch',
+    BP: 'bandpass',
+    HP: 'highpass',
+    HPQ: 'highpass',
+    LP: 'lowpass',
+    LPQ: 'lowpass',
+    AP: 'allpass',
+};
+
+const APO_TYPE_NAMES = {
+    peaking: 'PK',
+    lowshelf: 'LSC',
+    highshelf: 'HSC',
+    notch: 'NO',
+    bandpass: 'BP',
+    highpass: 'HPQ',
+    lowpass: 'LPQ',
+    allpass: 'AP',
+};
+
+// Tolerance when recognising exported Butterworth cascades (Q is written with 2 decimals)
+const CASCADE_Q_TOLERANCE = 0.01;
+
+export function filterTypeHasGain(type) {
+    return GAIN_TYPES.has(type);
+}
+
+export function filterTypeHasSlope(type) {
+    return SLOPE_TYPES.has(type);
+}
+
+export function normalizeSlope(slope) {
+    const parsed = parseInt(slope, 10);
+    return EQ_SLOPES.includes(parsed) ? parsed : 12;
+}
+
+/**
+ * Stage Qs of an even-order Butterworth filter built from cascaded biquads
+ * @param {number} slope - 12, 24 or 48 dB/octave
+ */
+export function butterworthQs(slope) {
+    const order = normalizeSlope(slope) / 6;
+    const qs = [];
+    for (let k = 1; k <= order / 2; k++) {
+        qs.push(1 / (2 * Math.cos(((2 * k - 1) * Math.PI) / (2 * order))));
+    }
+    return qs;
+}
+
+/**
+ * Expand a band into the biquad stages that implement it.
+ * High/low-pass bands steeper than 12 dB/oct become Butterworth cascades; a 12 dB band keeps its own Q.
+ * @param {{type: string, freq: number, gain: number, q: number, slope?: number}} band
+ * @returns {Array<{type: string, freq: number, gain: number, q: number}>}
+ */
+export function getFilterStages(band) {
+    const type = EQ_FILTER_TYPES.includes(band.type) ? band.type : 'peaking';
+    const gain = filterTypeHasGain(type) ? band.gain || 0 : 0;
+    const q = band.q > 0 ? band.q : Math.SQRT1_2;
+
+    if (filterTypeHasSlope(type) && normalizeSlope(band.slope) > 12) {
+        return butterworthQs(band.slope).map((stageQ) => ({ type, freq: band.freq, gain: 0, q: stageQ }));
+    }
+    return [{ type, freq: band.freq, gain, q }];
+}
+
+/**
+ * Web Audio interprets Q of lowpass/highpass BiquadFilterNodes in dB; every other type takes it linearly
+ */
+export function toBiquadNodeQ(type, q) {
+    return filterTypeHasSlope(type) ? 20 * Math.log10(q) : q;
+}
+
+/**
+ * Magnitude response in dB of a single biquad stage (RBJ cookbook, as used by BiquadFilterNode)
+ */
+export function biquadStageResponseDb(f, stage, sr) {
+    const w = (2 * Math.PI * stage.freq) / sr;
+    const p = (2 * Math.PI * f) / sr;
+    const s = Math.sin(w) / (2 * stage.q);
+    const A = Math.pow(10, (stage.gain || 0) / 40);
+    const c = Math.cos(w);
+    let b0, b1, b2, a0, a1, a2;
+
+    switch (stage.type) {
+        case 'peaking':
+            b0 = 1 + s * A;
+            b1 = -2 * c;
+            b2 = 1 - s * A;
+            a0 = 1 + s / A;
+            a1 = -2 * c;
+            a2 = 1 - s / A;
+            break;
+        case 'lowshelf': {
+            const sq = 2 * Math.sqrt(A) * s;
+            b0 = A * (A + 1 - (A - 1) * c + sq);
+            b1 = 2 * A * (A - 1 - (A + 1) * c);
+            b2 = A * (A + 1 - (A - 1) * c - sq);
+            a0 = A + 1 + (A - 1) * c + sq;
+            a1 = -2 * (A - 1 + (A + 1) * c);
+            a2 = A + 1 + (A - 1) * c - sq;
+            break;
+        }
+        case 'highshelf': {
+            const sq = 2 * Math.sqrt(A) * s;
+            b0 = A * (A + 1 + (A - 1) * c + sq);
+            b1 = -2 * A * (A - 1 + (A + 1) * c);
+            b2 = A * (A + 1 + (A - 1) * c - sq);
+            a0 = A + 1 - (A - 1) * c + sq;
+            a1 = 2 * (A - 1 - (A + 1) * c);
+            a2 = A + 1 - (A - 1) * c - sq;
+            break;
+        }
+        case 'lowpass':
+            b0 = (1 - c) / 2;
+            b1 = 1 - c;
+            b2 = (1 - c) / 2;
+            a0 = 1 + s;
+            a1 = -2 * c;
+            a2 = 1 - s;
+            break;
+        case 'highpass':
+            b0 = (1 + c) / 2;
+            b1 = -(1 + c);
+            b2 = (1 + c) / 2;
+            a0 = 1 + s;
+            a1 = -2 * c;
+            a2 = 1 - s;
+            break;
+        case 'bandpass':
+            b0 = s;
+            b1 = 0;
+            b2 = -s;
+            a0 = 1 + s;
+            a1 = -2 * c;
+            a2 = 1 - s;
+            break;
+        case 'notch':
+            b0 = 1;
+            b1 = -2 * c;
+            b2 = 1;
+            a0 = 1 + s;
+            a1 = -2 * c;
+            a2 = 1 - s;
+            break;
+        default:
+            // allpass and unknown types leave the magnitude untouched
+            return 0;
+    }
+
+    const _a0 = 1 / a0;
+    const b0n = b0 * _a0,
+        b1n = b1 * _a0,
+        b2n = b2 * _a0;
+    const a1n = a1 * _a0,
+        a2n = a2 * _a0;
+    const cp = Math.cos(p),
+        c2p = Math.cos(2 * p);
+    const n = b0n * b0n + b1n * b1n + b2n * b2n + 2 * (b0n * b1n + b1n * b2n) * cp + 2 * b0n * b2n * c2p;
+    const d = 1 + a1n * a1n + a2n * a2n + 2 * (a1n + a1n * a2n) * cp + 2 * a2n * c2p;
+    // Clamp so notch/pass-band zeros don't produce -Infinity on the graph
+    return 10 * Math.log10(Math.max(n / d, 1e-12));
+}
+
+/**
+ * Convert an APO "BW Oct" bandwidth to Q
+ */
+function bandwidthToQ(octaves) {
+    const factor = Math.pow(2, octaves);
+    return Math.sqrt(factor) / (factor - 1);
+}
+
+/**
+ * Map an APO "Channel:" directive to a band channel.
+ * Returns null when none of the selected channels exist on a stereo output.
+ */
+function parseChannelDirective(value) {
+    const tokens = value
+        .toUpperCase()
+        .split(/[\s,]+/)
+        .filter(Boolean);
+    if (tokens.includes('ALL')) return 'stereo';
+    const left = tokens.includes('L') || tokens.includes('1');
+    const right = tokens.includes('R') || tokens.includes('2');
+    if (left && right) return 'stereo';
+    if (left) return 'left';
+    if (right) return 'right';
+    return null;
+}
+
+/**
+ * Fold consecutive identical high/low-pass stages with Butterworth Qs back into one steep band
+ */
+function mergeButterworthCascades(bands) {
+    const merged = [];
+    for (let i = 0; i < bands.length; i++) {
+        const band = bands[i];
+        let folded = false;
+
+        if (filterTypeHasSlope(band.type)) {
+            for (const slope of [48, 24]) {
+                const qs = butterworthQs(slope);
+                const run = bands.slice(i, i + qs.length);
+                const matches =
+                    run.length === qs.length &&
+                    run.every(
+                        (b, k) =>
+                            b.type === band.type &&
+                            b.freq === band.freq &&
+                            b.channel === band.channel &&
+                            Math.abs(b.q - qs[k]) <= CASCADE_Q_TOLERANCE
+                    );
+                if (matches) {
+                    merged.push({ ...band, q: Math.SQRT1_2, slope });
+                    i += qs.length - 1;
+                    folded = true;
+                    break;
+                }
+            }
+        }
+
+        if (!folded) merged.push(band);
+    }
+    return merged.map((band, id) => ({ ...band, id }));
+}
+
+/**
+ * Parse an Equalizer APO / Peace configuration
+ * @param {string} text
+ * @returns {{preamp: number, bands: Array<object>}}
+ */
+export function parseEqualizerAPO(text) {
+    let preamp = 0;
+    let channel = 'stereo';
+    const bands = [];
+
+    const lines = text
+        .split('\n')
+        .map((line) => line.trim())
+        .filter((line) => line && !line.startsWith('#'));
+
+    for (const line of lines) {
+        const preampMatch = line.match(/^Preamp:\s*([+-]?\d*\.?\d+)\s*dB/i);
+        if (preampMatch) {
+            preamp = parseFloat(preampMatch[1]);
+            continue;
+        }
+
+        const channelMatch = line.match(/^Channel:\s*(.+)$/i);
+        if (channelMatch) {
+            channel = parseChannelDirective(channelMatch[1]);
+            continue;
+        }
+
+        // Handles "Filter:" and "Filter N:", optional Gain/Q/BW, and shelf slopes such as "LS 12dB"
+        const filterMatch = line.match(
+            /^Filter\s*\d*:\s*ON\s+([A-Z]+)(?:\s+\d+\s*dB)?\s+Fc\s+(\d*\.?\d+)\s*Hz(?:\s+Gain\s*([+-]?\d*\.?\d+)\s*dB)?(?:\s+Q\s+(\d*\.?\d+))?(?:\s+BW\s+Oct\s+(\d*\.?\d+))?/i
+        );
+        if (!filterMatch || channel === null) continue;
+
+        const type = APO_TYPE_MAP[filterMatch[1].toUpperCase()];
+        if (!type) continue;
+
+        let q = Math.SQRT1_2;
+        if (filterMatch[4]) q = parseFloat(filterMatch[4]);
+        else if (filterMatch[5]) q = bandwidthToQ(parseFloat(filterMatch[5]));
+
+        bands.push({
+            id: bands.length,
+            type,
+            freq: parseFloat(filterMatch[2]),
+            gain: filterTypeHasGain(type) && filterMatch[3] ? parseFloat(filterMatch[3]) : 0,
+            q,
+            slope: 12,
+            enabled: true,
+            channel,
+        });
+    }
+
+    return { preamp, bands: mergeButterworthCascades(bands) };
+}
+
+/**
+ * Serialize bands to Equalizer APO text. Left/right bands are wrapped in "Channel:" directives;
+ * steep high/low-pass bands are written as their Butterworth stages so APO reproduces them exactly.
+ * Mid/side routing has no APO equivalent and is written as a stereo filter.
+ * @param {number} preamp
+ * @param {Array<object>} bands
+ * @returns {string}
+ */
+export function formatEqualizerAPO(preamp, bands) {
+    const lines = [`Preamp: ${(preamp || 0).toFixed(1)} dB`];
+    let currentChannel = 'all';
+    let filterNum = 0;
+
+    for (const band of bands) {
+        if (band.enabled === false) continue;
+
+        const channel = band.channel === 'left' ? 'L' : band.channel === 'right' ? 'R' : 'all';
+        if (channel !== currentChannel) {
+            lines.push(`Channel: ${channel}`);
+            currentChannel = channel;
+        }
+
+        const name = APO_TYPE_NAMES[band.type] || 'PK';
+        const fc = Math.round(band.freq);
+        for (const stage of getFilterStages(band)) {
+            filterNum++;
+            const gain = filterTypeHasGain(stage.type) ? ` Gain ${stage.gain.toFixed(1)} dB` : '';
+            lines.push(`Filter ${filterNum}: ON ${name} Fc ${fc} Hz${gain} Q ${stage.q.toFixed(2)}`);
+        }
+    }
+
+    return lines.join('\n');
+}
@@ -24,6 +24,10 @@ import { audioContextManager } from './audio-context.js';
 import { isIos, isSafari, canUseNativeAmazonCenc } from './platform-detection.js';
 import { db } from './db.js';
 import { getProxyUrl } from './proxy-utils.js';
+import { CrossfadeEngine } from './crossfade.js';
+import { TransitionPolicy, TRANSITION_MODE } from './transition-policy.js';
+import { loudnessAnalyzer, getAnalysisSource } from './loudness-analyzer.js';
+import { offlineLibrary } from './offline-library.js';
 
 import { SVG_CLOCK, SVG_ATMOS, SVG_TRIANGLE_ALERT, SVG_PLAY, SVG_PAUSE } from './icons.js';
 import { UIRenderer } from './ui.js';
@@ -80,6 +84,8 @@ export class Player {
             isFetching: false,
             hasMore: true,
         };
+        this.transitionPolicy = new TransitionPolicy();
+        this.crossfade = new CrossfadeEngine(this);
     }
 
     static async initialize(audioElement, api, quality) {
@@ -274,13 +280,16 @@ export class Player {
         this.applyReplayGain();
     }
 
-    applyReplayGain() {
+    /**
+     * Element volume for the given ReplayGain values, with pre-amp, peak protection and volume curve applied
+     */
+    computeEffectiveVolume(rgValues) {
         const mode = replayGainSettings.getMode(); // 'off', 'track', 'album'
         let gainDb = 0;
         let peak = 1.0;
 
-        if (mode !== 'off' && this.currentRgValues) {
-            const { trackReplayGain, trackPeakAmplitude, albumReplayGain, albumPeakAmplitude } = this.currentRgValues;
+        if (mode !== 'off' && rgValues) {
+            const { trackReplayGain, trackPeakAmplitude, albumReplayGain, albumPeakAmplitude } = rgValues;
 
             if (mode === 'album' && albumReplayGain !== undefined) {
                 gainDb = albumReplayGain;
@@ -308,9 +317,14 @@ export class Player {
         // Calculate effective volume
         const effectiveVolume = curvedVolume * scale;
 
+        return Math.max(0, Math.min(1, effectiveVolume));
+    }
+
+    applyReplayGain() {
         const el = this.activeElement;
 
-        el.volume = Math.max(0, Math.min(1, effectiveVolume));
+        el.volume = this.computeEffectiveVolume(this.currentRgValues);
+        this.crossfade.applyVolume();
     }
 
     applyAudioEffects() {
@@ -352,6 +366,7 @@ export class Player {
             this.currentQueueIndex = savedState.currentQueueIndex ?? -1;
             this.shuffleActive = savedState.shuffleActive || false;
             this.repeatMode = savedState.repeatMode !== undefined ? savedState.repeatMode : REPEAT_MODE.OFF;
+            void this.transitionPolicy.indexAlbums(this.queue).catch(console.error);
 
             // Restore current track if queue exists and index is valid
             const currentQueue = this.shuffleActive ? this.shuffledQueue : this.queue;
@@ -521,14 +536,14 @@ export class Player {
 
             await MediaSession.setActionHandler({ action: 'seekto' }, (details) => {
                 if (details.seekTime !== undefined) {
-                    this.activeElement.currentTime = Math.max(0, details.seekTime);
+                    this.seekTo(details.seekTime);
                     this.updateMediaSessionPositionState();
                 }
             });
 
             await MediaSession.setActionHandler({ action: 'stop' }, () => {
                 this.activeElement.pause();
-                this.activeElement.currentTime = 0;
+                this.seekTo(0);
                 this.updateMediaSessionPlaybackState();
             });
         };
@@ -560,8 +575,7 @@ export class Player {
     async checkPreloadConditions() {
         if (!this._pendingPreload || !this.activeElement || this.activeElement.paused) return;
 
-        const currentTime = this.activeElement.currentTime || 0;
-        const duration = this.activeElement.duration || 0;
+        const { currentTime, duration } = this.getPosition();
         const timeRemaining = duration - currentTime;
 
         // Preload if we are in last 30 seconds of song
@@ -595,6 +609,7 @@ export class Player {
             const isTracker = track.isTracker || (track.id && String(track.id).startsWith('tracker-'));
             const isPodcast = track.isPodcast || (track.id && String(track.id).startsWith('podcast_'));
             if (track.isLocal || isTracker || isPodcast || (track.audioUrl && !track.isLocal)) continue;
+            if (track.type !== 'video' && (await offlineLibrary.has(track.id))) continue;
             try {
                 const streamInfo =
                     track.type == 'video'
@@ -606,6 +621,10 @@ export class Player {
                 this.preloadCache.set(track.id, streamInfo);
                 const streamUrl = streamInfo.url;
 
+                if (!streamInfo.rgInfo && !streamInfo.rgInfoFallback) {
+                    streamInfo.rgInfoFallback = await loudnessAnalyzer.getReplayGain(track.id);
+                }
+
                 if (streamInfo.playbackType?.includes('cenc')) continue;
                 if (this.isNativeAmazonHlsDecryptionUrl(streamUrl)) continue;
 
@@ -696,7 +715,36 @@ export class Player {
         }
     }
 
-    backfillReplayGainFromTrack(_track, _currentSequence) {}
+    /**
+     * Fill in ReplayGain for a track that has none, from the loudness cache or a fresh
+     * EBU R128 analysis. Applied only if the same playback is still current when it resolves.
+     */
+    async backfillReplayGainFromTrack(track, currentSequence, streamInfo = null) {
+        if (!track || replayGainSettings.getMode() === 'off') return;
+
+        const isCurrent = () => this.playbackSequence === currentSequence && this.currentTrack?.id === track.id;
+        const apply = (rgValues) => {
+            if (!rgValues || !isCurrent() || this.currentRgValues) return;
+            this.currentRgValues = rgValues;
+            this.applyReplayGain();
+        };
+
+        const cached = await loudnessAnalyzer.getReplayGain(track.id);
+        if (cached) {
+            apply(cached);
+            return;
+        }
+
+        if (!replayGainSettings.isAnalysisEnabled() || !isCurrent()) return;
+        const source = getAnalysisSource(track, streamInfo ?? this.preloadCache.get(track.id));
+        if (!source) return;
+
+        try {
+            apply(await loudnessAnalyzer.analyze(track, source));
+        } catch (e) {
+            console.warn(`[Loudness] Could not analyze ${track.title}:`, e);
+        }
+    }
 
     shouldUseNativeAmazonDecrypter() {
         return !canUseNativeAmazonCenc;
@@ -773,7 +821,7 @@ export class Player {
         } else {
             this.currentRgValues = null;
             this.applyReplayGain();
-            this.backfillReplayGainFromTrack(track, currentSequence);
+            void this.backfillReplayGainFromTrack(track, currentSequence, streamInfo);
         }
 
         const deezerHiResFallback =
@@ -1038,7 +1086,7 @@ export class Player {
 
     async playTrackFromQueue(startTime = 0, recursiveCount = 0, isRetry = false, options = {}) {
         await this.shakaReady;
-        const { preserveGestureToken = false } = options;
+        const { preserveGestureToken = false, transition = null } = options;
         if (!isRetry) {
             this.isFallbackRetry = false;
         }
@@ -1062,6 +1110,18 @@ export class Player {
             return;
         }
 
+        // If the next track is already playing on the crossfade element (fading in, or the next track of an album
+        // started gaplessly), pick up where it is. Other gapless transitions just load the next track.
+        if (transition?.mode === TRANSITION_MODE.GAPLESS && transition.reason !== 'album') {
+            this.crossfade.cancel();
+        } else {
+            const activeFor = track.type === 'video' ? this.video : this.audio;
+            const crossfadePosition = this.crossfade.takeHandoff(track, activeFor);
+            if (crossfadePosition !== null) {
+                startTime = crossfadePosition;
+            }
+        }
+
         this.setLoadingState(true);
 
         const previousActiveElement = this.activeElement;
@@ -1313,6 +1373,7 @@ export class Player {
 
                 this.currentRgValues = null;
                 this.applyReplayGain();
+                void this.backfillReplayGainFromTrack(track, currentSequence);
 
                 activeElement.src = streamUrl;
                 this.applyAudioEffects();
@@ -1330,8 +1391,9 @@ export class Player {
                 streamUrl = URL.createObjectURL(track.file);
                 if (this.playbackSequence !== currentSequence) return;
 
-                this.currentRgValues = null; // No replaygain for local files yet
+                this.currentRgValues = null;
                 this.applyReplayGain();
+                void this.backfillReplayGainFromTrack(track, currentSequence);
 
                 activeElement.src = streamUrl;
                 this.applyAudioEffects();
@@ -1340,8 +1402,9 @@ export class Player {
                 const canPlay = await this.waitForCanPlayOrTimeout(activeElement);
                 if (!canPlay || this.playbackSequence !== currentSequence) return;
 
-                if (startTime > 0) {
-                    activeElement.currentTime = startTime;
+                // Tracks split from a CUE sheet start partway into their file
+                if (startTime > 0 || track.cueStart > 0) {
+                    activeElement.currentTime = (track.cueStart || 0) + startTime;
                 }
                 const played = await this.safePlay(activeElement);
                 if (!played) return;
@@ -1413,6 +1476,42 @@ export class Player {
                     return;
                 }
 
+                // Saved offline copy: play it without asking the API for a stream
+                const offlineCopy = await offlineLibrary.getPlayable(track.id, this.quality);
+                if (this.playbackSequence !== currentSequence) return;
+                if (offlineCopy) {
+                    if (this.shakaInitialized) {
+                        try {
+                            this.shakaPlayer.unload();
+                            this.shakaPlayer.detach();
+                        } catch {}
+                        this.shakaInitialized = false;
+                    }
+                    streamUrl = URL.createObjectURL(offlineCopy.blob);
+
+                    this.currentRgValues = null;
+                    this.applyReplayGain();
+                    void this.backfillReplayGainFromTrack(track, currentSequence, {
+                        url: streamUrl,
+                        playbackType: 'direct',
+                    });
+
+                    activeElement.src = streamUrl;
+                    this.applyAudioEffects();
+                    this.updateAdaptiveQualityBadge();
+
+                    const canPlay = await this.waitForCanPlayOrTimeout(activeElement);
+                    if (!canPlay || this.playbackSequence !== currentSequence) return;
+
+                    if (startTime > 0) {
+                        activeElement.currentTime = startTime;
+                    }
+                    const played = await this.safePlay(activeElement);
+                    if (!played) return;
+                    this.preloadNextTracks();
+                    return;
+                }
+
                 // Tidal: Try to get ReplayGain from manifest first, supplement with track info if needed
                 const streamInfoPromise = this.preloadCache.has(track.id)
                     ? Promise.resolve(this.preloadCache.get(track.id))
@@ -1461,6 +1560,7 @@ export class Player {
                     this.currentRgValues = resolvedStreamInfo.rgInfoFallback;
                 } else {
                     this.currentRgValues = null;
+                    void this.backfillReplayGainFromTrack(track, currentSequence, resolvedStreamInfo);
                 }
                 this.applyReplayGain();
 
@@ -1601,6 +1701,7 @@ export class Player {
 
     async playNext(recursiveCount = 0, options = {}) {
         try {
+            const previousTrack = this.currentTrack;
             const currentQueue = this.getCurrentQueue();
             const isLastTrack = this.currentQueueIndex >= currentQueue.length - 1;
 
@@ -1696,7 +1797,8 @@ export class Player {
                 return;
             }
 
-            await this.playTrackFromQueue(0, recursiveCount, false, options);
+            const transition = this.transitionPolicy.decide(previousTrack, currentQueue[this.currentQueueIndex]);
+            await this.playTrackFromQueue(0, recursiveCount, false, { ...options, transition });
         } catch (error) {
             console.error(error);
         }
@@ -1963,8 +2065,8 @@ export class Player {
 
     playPrev(recursiveCount = 0) {
         const el = this.activeElement;
-        if (el.currentTime > 3) {
-            el.currentTime = 0;
+        if (this.getPosition().currentTime > 3) {
+            this.seekTo(0);
             this.updateMediaSessionPositionState();
         } else if (this.currentQueueIndex > 0) {
             this.currentQueueIndex--;
@@ -2024,20 +2126,45 @@ export class Player {
     }
 
     seekBackward(seconds = 10) {
-        const el = this.activeElement;
-        const newTime = Math.max(0, el.currentTime - seconds);
-        el.currentTime = newTime;
+        this.seekTo(this.getPosition().currentTime - seconds);
         this.updateMediaSessionPositionState();
     }
 
     seekForward(seconds = 10) {
-        const el = this.activeElement;
-        const duration = el.duration || 0;
-        const newTime = Math.min(duration, el.currentTime + seconds);
-        el.currentTime = newTime;
+        this.seekTo(this.getPosition().currentTime + seconds);
         this.updateMediaSessionPositionState();
     }
 
+    /**
+     * Start and end of the current track within the media element. Tracks split from a CUE sheet only
+     * cover part of their file; everything else spans the whole element.
+     */
+    getTrackBounds(el = this.activeElement) {
+        const start = this.currentTrack?.cueStart || 0;
+        const end = this.currentTrack?.cueEnd || el.duration || 0;
+        return { start, end };
+    }
+
+    /** Position and length of the current track, in seconds */
+    getPosition(el = this.activeElement) {
+        const { start, end } = this.getTrackBounds(el);
+        const duration = Number.isFinite(end) ? Math.max(0, end - start) : 0;
+        return { currentTime: Math.max(0, (el.currentTime || 0) - start), duration };
+    }
+
+    /** Seek to a position within the current track */
+    seekTo(seconds, el = this.activeElement) {
+        const { start } = this.getTrackBounds(el);
+        const { duration } = this.getPosition(el);
+        const position = Math.max(0, seconds || 0);
+        el.currentTime = start + (duration > 0 ? Math.min(position, duration) : position);
+    }
+
+    /** Whether a track split from a CUE sheet has played past its end */
+    hasReachedTrackEnd(el = this.activeElement) {
+        return this.currentTrack?.cueEnd > 0 && el.currentTime >= this.currentTrack.cueEnd;
+    }
+
     async toggleShuffle() {
         this.shuffleActive = !this.shuffleActive;
 
@@ -2087,9 +2214,20 @@ export class Player {
         this.currentQueueIndex = startIndex;
         this.shuffleActive = false;
         this.preloadCache.clear();
+        this.setTransitionPolicy(new TransitionPolicy());
         await this.saveQueueState();
     }
 
+    /**
+     * Replace the transition policy for the current queue
+     * @param {TransitionPolicy} policy
+     */
+    setTransitionPolicy(policy) {
+        this.transitionPolicy = policy;
+        this.crossfade.cancel();
+        void policy.indexAlbums(this.queue).catch(console.error);
+    }
+
     setArtistPopularTracksContext(artistId, initialTracks, offset = 15, hasMore = true) {
         this.artistPopularTracksState = {
             artistId,
@@ -2163,6 +2301,8 @@ export class Player {
             this.originalQueueBeforeShuffle.push(...tracks);
         }
 
+        void this.transitionPolicy.indexAlbums(this.queue).catch(console.error);
+
         if (!this.currentTrack || this.currentQueueIndex === -1) {
             this.currentQueueIndex = this.getCurrentQueue().length - tracks.length;
             await this.playTrackFromQueue(0, 0);
@@ -2679,7 +2819,7 @@ export class Player {
 
     updateMediaSessionPositionState() {
         const el = this.activeElement;
-        const duration = el.duration;
+        const { currentTime, duration } = this.getPosition(el);
 
         if (!duration || isNaN(duration) || !isFinite(duration)) {
             return;
@@ -2688,7 +2828,7 @@ export class Player {
         MediaSession.setPositionState({
             duration: duration,
             playbackRate: el.playbackRate || 1,
-            position: Math.min(el.currentTime, duration),
+            position: Math.min(currentTime, duration),
         }).catch((error) => {
             console.log('Failed to update Media Session position:', error);
         });
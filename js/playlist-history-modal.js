@@ -0,0 +1,155 @@
+// js/playlist-history-modal.js
+// Lists the snapshots of a user playlist with what each change added, removed or moved. Any snapshot
+// can be restored in one click, and the current tracks can be saved as a named snapshot.
+
+import { db } from './db.js';
+import { syncManager } from './accounts/pocketbase.js';
+import { showNotification } from './downloads.js';
+import { escapeHtml, getTrackArtists } from './utils.js';
+import { describePlaylistDiff, getSnapshotLabel, withDiffs } from './playlist-history.js';
+
+// Tracks listed per change before the rest is summarized
+const MAX_LISTED_TRACKS = 20;
+
+let closePlaylistHistory = null;
+
+function formatSnapshotDate(timestamp) {
+    return new Date(timestamp).toLocaleString(undefined, { dateStyle: 'medium', timeStyle: 'short' });
+}
+
+function createTrackList(title, tracks, className) {
+    if (!tracks.length) return '';
+    const items = tracks
+        .slice(0, MAX_LISTED_TRACKS)
+        .map((track) => `<li>${escapeHtml(`${track.title || 'Unknown track'} — ${getTrackArtists(track)}`)}</li>`)
+        .join('');
+    const more = tracks.length > MAX_LISTED_TRACKS ? `<li>and ${tracks.length - MAX_LISTED_TRACKS} more</li>` : '';
+    return `<div class="playlist-history-changes ${className}"><h4>${title}</h4><ul>${items}${more}</ul></div>`;
+}
+
+function createEntry({ snapshot, diff }, isCurrent) {
+    const count = snapshot.tracks.length;
+    const summary = diff ? describePlaylistDiff(diff) : `${count} track${count === 1 ? '' : 's'}`;
+    const hasDetails = diff && (diff.added.length || diff.removed.length || diff.moved.length);
+
+    const entry = document.createElement('div');
+    entry.className = `playlist-history-entry${snapshot.name ? ' named' : ''}`;
+    entry.dataset.snapshotId = snapshot.id;
+    entry.innerHTML = `
+        <div class="playlist-history-entry-header">
+            <div class="playlist-history-entry-info">
+                <span class="playlist-history-entry-label">${escapeHtml(getSnapshotLabel(snapshot))}</span>
+                <span class="playlist-history-entry-meta">${formatSnapshotDate(snapshot.createdAt)} · ${summary}</span>
+            </div>
+            ${hasDetails ? '<button type="button" class="btn-secondary playlist-history-toggle">Changes</button>' : ''}
+            ${
+                isCurrent
+                    ? '<span class="playlist-history-current">Current</span>'
+                    : '<button type="button" class="btn-secondary playlist-history-restore">Restore</button>'
+            }
+            ${
+                snapshot.name
+                    ? `<button type="button" class="btn-icon playlist-history-delete" title="Delete snapshot">
+                        &times;
+                    </button>`
+                    : ''
+            }
+        </div>
+        ${
+            hasDetails
+                ? `<div class="playlist-history-details" hidden>
+                    ${createTrackList('Added', diff.added, 'added')}
+                    ${createTrackList('Removed', diff.removed, 'removed')}
+                    ${createTrackList('Moved', diff.moved, 'moved')}
+                </div>`
+                : ''
+        }
+    `;
+    return entry;
+}
+
+/**
+ * Open the history of a user playlist
+ * @param {string} playlistId
+ * @param {{onRestore?: (playlist: object) => void}} [options]
+ */
+export async function openPlaylistHistory(playlistId, { onRestore } = {}) {
+    const modal = document.getElementById('playlist-history-modal');
+    if (!modal) return;
+
+    closePlaylistHistory?.();
+
+    const list = modal.querySelector('#playlist-history-list');
+    const nameInput = modal.querySelector('#playlist-history-name-input');
+    nameInput.value = '';
+
+    const render = async () => {
+        const playlist = await db.getPlaylist(playlistId);
+        const snapshots = await db.getPlaylistSnapshots(playlistId);
+        modal.querySelector('#playlist-history-title').textContent = `History of ${playlist?.name || 'Playlist'}`;
+
+        list.innerHTML = '';
+        if (snapshots.length === 0) {
+            list.innerHTML = '<div class="placeholder-text">No changes recorded yet.</div>';
+            return;
+        }
+
+        // Named snapshots don't change the tracks, so the newest automatic one is the current state
+        const currentId = snapshots.find((snapshot) => !snapshot.name)?.id;
+        const fragment = document.createDocumentFragment();
+        withDiffs(snapshots).forEach((item) => {
+            fragment.appendChild(createEntry(item, item.snapshot.id === currentId));
+        });
+        list.appendChild(fragment);
+    };
+
+    const close = () => {
+        modal.classList.remove('active');
+        modal.removeEventListener('click', handleClick);
+        modal.removeEventListener('submit', handleSubmit);
+        closePlaylistHistory = null;
+    };
+
+    const restore = async (snapshotId) => {
+        const playlist = await db.restorePlaylistSnapshot(snapshotId);
+        await syncManager.syncUserPlaylist(playlist, 'update');
+        showNotification(`Restored ${playlist.tracks.length} tracks`);
+        onRestore?.(playlist);
+        await render();
+    };
+
+    const handleSubmit = async (e) => {
+        if (e.target.id !== 'playlist-history-save-form') return;
+        e.preventDefault();
+        const name = nameInput.value.trim() || `Snapshot ${formatSnapshotDate(Date.now())}`;
+        await db.savePlaylistSnapshot(playlistId, name);
+        nameInput.value = '';
+        await render();
+    };
+
+    const handleClick = async (e) => {
+        const entry = e.target.closest('.playlist-history-entry');
+        if (e.target.classList.contains('modal-overlay') || e.target.id === 'playlist-history-close') {
+            close();
+        } else if (entry && e.target.closest('.playlist-history-toggle')) {
+            const details = entry.querySelector('.playlist-history-details');
+            details.hidden = !details.hidden;
+        } else if (entry && e.target.closest('.playlist-history-restore')) {
+            e.target.closest('.playlist-history-restore').disabled = true;
+            await restore(entry.dataset.snapshotId).catch((error) => {
+                console.error('Failed to restore playlist snapshot:', error);
+                showNotification('Failed to restore playlist.');
+            });
+        } else if (entry && e.target.closest('.playlist-history-delete')) {
+            if (!confirm('Delete this snapshot?')) return;
+            await db.deletePlaylistSnapshot(entry.dataset.snapshotId);
+            await render();
+        }
+    };
+
+    closePlaylistHistory = close;
+    modal.addEventListener('click', handleClick);
+    modal.addEventListener('submit', handleSubmit);
+    await render();
+    modal.classList.add('active');
+}
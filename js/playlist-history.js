@@ -0,0 +1,91 @@
+// js/playlist-history.js
+// Snapshots of user playlists are saved by db.js whenever their tracks change. This module compares
+// snapshots so the history view can show what each change added, removed or moved.
+
+const ACTION_LABELS = {
+    initial: 'Earlier version',
+    create: 'Created',
+    add: 'Added tracks',
+    remove: 'Removed tracks',
+    reorder: 'Reordered',
+    edit: 'Edited',
+    sync: 'Replaced by cloud sync',
+    restore: 'Restored',
+    manual: 'Saved',
+};
+
+/** Identifies a playlist entry across snapshots; tracks and videos can share ids */
+export function getEntryKey(track) {
+    return `${track.type || 'track'}:${track.id}`;
+}
+
+// Indices (into `sequence`) of a longest strictly increasing subsequence
+function longestIncreasingSubsequence(sequence) {
+    const tails = [];
+    const previous = new Array(sequence.length);
+    for (let i = 0; i < sequence.length; i++) {
+        let low = 0;
+        let high = tails.length;
+        while (low < high) {
+            const mid = (low + high) >> 1;
+            if (sequence[tails[mid]] < sequence[i]) low = mid + 1;
+            else high = mid;
+        }
+        previous[i] = low > 0 ? tails[low - 1] : -1;
+        tails[low] = i;
+    }
+
+    const result = new Set();
+    for (let i = tails.length ? tails[tails.length - 1] : -1; i !== -1; i = previous[i]) result.add(i);
+    return result;
+}
+
+/**
+ * What changed between two versions of a playlist's tracks. Moved tracks are the fewest tracks that
+ * have to change place to turn the old order into the new one.
+ * @param {Array} before
+ * @param {Array} after
+ * @returns {{added: Array, removed: Array, moved: Array}}
+ */
+export function diffPlaylistTracks(before = [], after = []) {
+    const beforeIndex = new Map(before.map((track, index) => [getEntryKey(track), index]));
+    const afterKeys = new Set(after.map(getEntryKey));
+
+    const added = after.filter((track) => !beforeIndex.has(getEntryKey(track)));
+    const removed = before.filter((track) => !afterKeys.has(getEntryKey(track)));
+
+    const kept = after.filter((track) => beforeIndex.has(getEntryKey(track)));
+    const inOrder = longestIncreasingSubsequence(kept.map((track) => beforeIndex.get(getEntryKey(track))));
+    const moved = kept.filter((_, index) => !inOrder.has(index));
+
+    return { added, removed, moved };
+}
+
+/** Whether two versions have the same tracks in the same order */
+export function haveSameTracks(before = [], after = []) {
+    return before.length === after.length && before.every((track, i) => getEntryKey(track) === getEntryKey(after[i]));
+}
+
+/** Short summary of a diff, e.g. "+3 · −1 · 2 moved" */
+export function describePlaylistDiff({ added, removed, moved }) {
+    const parts = [];
+    if (added.length) parts.push(`+${added.length}`);
+    if (removed.length) parts.push(`−${removed.length}`);
+    if (moved.length) parts.push(`${moved.length} moved`);
+    return parts.join(' · ') || 'No changes';
+}
+
+export function getSnapshotLabel(snapshot) {
+    return snapshot.name || ACTION_LABELS[snapshot.action] || 'Changed';
+}
+
+/**
+ * Pair every snapshot with the diff from the one before it. The oldest snapshot has no diff.
+ * @param {Array} snapshots - Newest first, as returned by db.getPlaylistSnapshots
+ */
+export function withDiffs(snapshots) {
+    return snapshots.map((snapshot, index) => {
+        const older = snapshots[index + 1];
+        return { snapshot, diff: older ? diffPlaylistTracks(older.tracks, snapshot.tracks) : null };
+    });
+}
@@ -103,6 +103,92 @@ function findBestMatch(items, targetArtist, targetAlbum, importOptions, targetTi
     return best;
 }
 
+// Search results kept per import row for the review step
+const MAX_CANDIDATES = 5;
+
+/**
+ * How well a search result matches an import row, from 0 to 1. ISRC matches score 1.
+ * @param {Object} item - Search result
+ * @param {{title: string, artist: string, album?: string, isrc?: string}} source - Import row
+ */
+function scoreCandidate(item, source) {
+    if (source.isrc && isIsrcMatch(item.isrc, source.isrc)) return 1;
+    const { score } = scoreTrack(item, source.title, source.artist, source.album);
+    return Math.round(score * 100) / 100;
+}
+
+/**
+ * Search results for an import row, scored and best first
+ * @param {Array} items - Search results
+ * @param {Object} source - Import row
+ * @returns {Array<{track: Object, score: number}>}
+ */
+function rankCandidates(items, source) {
+    return (items || [])
+        .filter((item) => item?.id)
+        .map((item) => ({ track: item, score: scoreCandidate(item, source) }))
+        .sort((a, b) => b.score - a.score)
+        .slice(0, MAX_CANDIDATES);
+}
+
+/**
+ * Record what was picked for an import row so it can be reviewed before importing
+ * @param {Object} source - Import row as read from the file
+ * @param {Object|null} match - Track picked automatically
+ * @param {Array} [items] - Search results the match was picked from
+ */
+function createImportMatch(source, match, items = []) {
+    const candidates = rankCandidates(items, source);
+    if (match && !candidates.some((candidate) => candidate.track.id === match.id)) {
+        candidates.push({ track: match, score: scoreCandidate(match, source) });
+        candidates.sort((a, b) => b.score - a.score);
+    }
+    return { source, match, score: match ? scoreCandidate(match, source) : 0, candidates, reviewed: false };
+}
+
+/**
+ * Rebuild a parse result from its reviewed matches
+ * @param {Object} result - Result of one of the parsers, with `matches`
+ * @returns {Object} The result with tracks, missing tracks and playlists following the matches
+ */
+export function applyImportReview(result) {
+    if (!Array.isArray(result.matches)) return result;
+
+    const tracks = [];
+    const missingTracks = [];
+    const playlists = {};
+    for (const { source, match } of result.matches) {
+        if (!match) {
+            missingTracks.push({ type: 'track', title: source.title, artist: source.artist, album: source.album });
+            continue;
+        }
+        const track = source.isFavorite ? { ...match, isFavorite: true } : match;
+        tracks.push(track);
+        if (source.playlistName) {
+            if (!playlists[source.playlistName]) playlists[source.playlistName] = [];
+            playlists[source.playlistName].push(track);
+        }
+    }
+
+    // parseDynamicCSV results also carry albums and artists
+    if (Array.isArray(result.missingItems)) {
+        const missingItems = [...result.missingItems.filter((item) => item.type !== 'track'), ...missingTracks];
+        return {
+            ...result,
+            tracks,
+            playlists,
+            missingItems,
+            stats: result.stats && {
+                ...result.stats,
+                tracksFound: tracks.length,
+                missingCount: missingItems.length,
+                playlistCount: Object.keys(playlists).length,
+            },
+        };
+    }
+    return { ...result, tracks, missingTracks };
+}
+
 /**
  * Helper function to get track artists string
  */
@@ -208,7 +294,7 @@ export function generateXML(playlist, tracks) {
  * @param {string} csvText - CSV content
  * @param {Function} api - API instance for searching tracks
  * @param {Function} onProgress - Progress callback
- * @returns {Promise<{tracks: Array, missingTracks: Array}>}
+ * @returns {Promise<{tracks: Array, missingTracks: Array, matches: Array}>}
  */
 const HEADER_MAPPINGS = {
     track: ['track name', 'title', 'song', 'name', 'track', 'track title'],
@@ -329,6 +415,7 @@ export async function parseDynamicCSV(csvText, api, onProgress, options = {}) {
     const artists = [];
     const missingItems = [];
     const playlists = {};
+    const matches = [];
     const totalItems = rows.length;
 
     const getItemType = (values) => {
@@ -390,6 +477,9 @@ export async function parseDynamicCSV(csvText, api, onProgress, options = {}) {
             await new Promise((resolve) => setTimeout(resolve, rowDelayMs));
         }
 
+        const source = { title: trackName, artist: artistName, album: albumName, isrc, playlistName, isFavorite };
+        let searchItems = [];
+
         try {
             if (itemType === 'track') {
                 let foundTrack = null;
@@ -398,6 +488,7 @@ export async function parseDynamicCSV(csvText, api, onProgress, options = {}) {
                     try {
                         const searchResult = await api.searchTracksByIsrc(isrc);
                         if (searchResult.items && searchResult.items.length > 0) {
+                            searchItems = searchResult.items;
                             foundTrack = searchResult.items.find((t) => isIsrcMatch(t.isrc, isrc)) || null;
                         }
                     } catch {
@@ -409,6 +500,7 @@ export async function parseDynamicCSV(csvText, api, onProgress, options = {}) {
                     const searchQuery = `"${trackName}" ${artistName}`.trim();
                     const searchResult = await api.searchTracks(searchQuery);
                     if (searchResult.items && searchResult.items.length > 0) {
+                        searchItems = [...searchItems, ...searchResult.items];
                         const isrcHit = isrc ? searchResult.items.find((t) => isIsrcMatch(t.isrc, isrc)) : null;
                         foundTrack =
                             isrcHit ||
@@ -416,6 +508,8 @@ export async function parseDynamicCSV(csvText, api, onProgress, options = {}) {
                     }
                 }
 
+                matches.push(createImportMatch(source, foundTrack, searchItems));
+
                 if (foundTrack) {
                     if (isFavorite) foundTrack.isFavorite = true;
                     tracks.push(foundTrack);
@@ -476,6 +570,7 @@ export async function parseDynamicCSV(csvText, api, onProgress, options = {}) {
                 }
             }
         } catch {
+            if (itemType === 'track') matches.push(createImportMatch(source, null, searchItems));
             missingItems.push({
                 type: itemType,
                 title: trackName || albumName,
@@ -491,6 +586,7 @@ export async function parseDynamicCSV(csvText, api, onProgress, options = {}) {
         artists,
         missingItems,
         playlists,
+        matches,
         stats: {
             totalItems,
             tracksFound: tracks.length,
@@ -587,7 +683,7 @@ export async function importToLibrary(csvResult, db, onProgress, options = {}) {
 
 export async function parseCSV(csvText, api, onProgress, importOptions = {}) {
     const lines = csvText.trim().split('\n');
-    if (lines.length < 2) return { tracks: [], missingTracks: [] };
+    if (lines.length < 2) return { tracks: [], missingTracks: [], matches: [] };
 
     const parseLine = (text) => {
         const values = [];
@@ -621,6 +717,7 @@ export async function parseCSV(csvText, api, onProgress, importOptions = {}) {
 
     const tracks = [];
     const missingTracks = [];
+    const matches = [];
     const totalTracks = rows.length;
 
     for (let i = 0; i < rows.length; i++) {
@@ -671,6 +768,7 @@ export async function parseCSV(csvText, api, onProgress, importOptions = {}) {
             if (trackTitle && artistNames) {
                 await new Promise((resolve) => setTimeout(resolve, 300));
 
+                const source = { title: trackTitle, artist: artistNames, album: albumName };
                 try {
                     const searchQuery = `"${trackTitle}" ${artistNames}`.trim();
                     const searchResult = await api.searchTracks(searchQuery);
@@ -683,19 +781,22 @@ export async function parseCSV(csvText, api, onProgress, importOptions = {}) {
                             importOptions,
                             trackTitle
                         );
+                        matches.push(createImportMatch(source, match, searchResult.items));
                         if (match) tracks.push(match);
                         else missingTracks.push({ title: trackTitle, artist: artistNames, album: albumName });
                     } else {
+                        matches.push(createImportMatch(source, null));
                         missingTracks.push({ title: trackTitle, artist: artistNames, album: albumName });
                     }
                 } catch {
+                    matches.push(createImportMatch(source, null));
                     missingTracks.push({ title: trackTitle, artist: artistNames, album: albumName });
                 }
             }
         }
     }
 
-    return { tracks, missingTracks };
+    return { tracks, missingTracks, matches };
 }
 
 /**
@@ -703,7 +804,7 @@ export async function parseCSV(csvText, api, onProgress, importOptions = {}) {
  * @param {string} jspfText - JSPF JSON content
  * @param {Function} api - API instance for searching tracks
  * @param {Function} onProgress - Progress callback
- * @returns {Promise<{tracks: Array, missingTracks: Array}>}
+ * @returns {Promise<{tracks: Array, missingTracks: Array, matches: Array}>}
  */
 export async function parseJSPF(jspfText, api, onProgress) {
     try {
@@ -716,6 +817,7 @@ export async function parseJSPF(jspfText, api, onProgress) {
         const playlist = jspfData.playlist;
         const tracks = [];
         const missingTracks = [];
+        const matches = [];
         const totalTracks = playlist.track.length;
 
         for (let i = 0; i < playlist.track.length; i++) {
@@ -736,22 +838,26 @@ export async function parseJSPF(jspfText, api, onProgress) {
             if (trackTitle && trackCreator) {
                 await new Promise((resolve) => setTimeout(resolve, 300));
 
+                const source = { title: trackTitle, artist: trackCreator, album: trackAlbum };
                 try {
                     const searchQuery = `${trackTitle} ${trackCreator}`;
                     const searchResults = await api.searchTracks(searchQuery);
 
                     if (searchResults.items && searchResults.items.length > 0) {
                         tracks.push(searchResults.items[0]);
+                        matches.push(createImportMatch(source, searchResults.items[0], searchResults.items));
                     } else {
+                        matches.push(createImportMatch(source, null));
                         missingTracks.push({ title: trackTitle, artist: trackCreator, album: trackAlbum });
                     }
                 } catch {
+                    matches.push(createImportMatch(source, null));
                     missingTracks.push({ title: trackTitle, artist: trackCreator, album: trackAlbum });
                 }
             }
         }
 
-        return { tracks, missingTracks };
+        return { tracks, missingTracks, matches };
     } catch (error) {
         throw new Error('Failed to parse JSPF: ' + error.message);
     }
@@ -762,7 +868,7 @@ export async function parseJSPF(jspfText, api, onProgress) {
  * @param {string} xspfText - XSPF XML content
  * @param {Function} api - API instance for searching tracks
  * @param {Function} onProgress - Progress callback
- * @returns {Promise<{tracks: Array, missingTracks: Array}>}
+ * @returns {Promise<{tracks: Array, missingTracks: Array, matches: Array}>}
  */
 export async function parseXSPF(xspfText, api, onProgress) {
     // Validate input to prevent potential XXE attacks
@@ -780,6 +886,7 @@ export async function parseXSPF(xspfText, api, onProgress) {
     const trackList = xmlDoc.getElementsByTagName('track');
     const tracks = [];
     const missingTracks = [];
+    const matches = [];
     const totalTracks = trackList.length;
 
     for (let i = 0; i < trackList.length; i++) {
@@ -800,22 +907,26 @@ export async function parseXSPF(xspfText, api, onProgress) {
         if (title && creator) {
             await new Promise((resolve) => setTimeout(resolve, 300));
 
+            const source = { title, artist: creator, album };
             try {
                 const searchQuery = `${title} ${creator}`;
                 const searchResults = await api.searchTracks(searchQuery);
 
                 if (searchResults.items && searchResults.items.length > 0) {
                     tracks.push(searchResults.items[0]);
+                    matches.push(createImportMatch(source, searchResults.items[0], searchResults.items));
                 } else {
+                    matches.push(createImportMatch(source, null));
                     missingTracks.push({ title, artist: creator, album });
                 }
             } catch {
+                matches.push(createImportMatch(source, null));
                 missingTracks.push({ title, artist: creator, album });
             }
         }
     }
 
-    return { tracks, missingTracks };
+    return { tracks, missingTracks, matches };
 }
 
 /**
@@ -823,7 +934,7 @@ export async function parseXSPF(xspfText, api, onProgress) {
  * @param {string} xmlText - XML content
  * @param {Function} api - API instance for searching tracks
  * @param {Function} onProgress - Progress callback
- * @returns {Promise<{tracks: Array, missingTracks: Array}>}
+ * @returns {Promise<{tracks: Array, missingTracks: Array, matches: Array}>}
  */
 export async function parseXML(xmlText, api, onProgress) {
     // Validate input to prevent potential XXE attacks
@@ -849,6 +960,7 @@ export async function parseXML(xmlText, api, onProgress) {
 
     const tracks = [];
     const missingTracks = [];
+    const matches = [];
     const totalTracks = trackElements.length;
 
     for (let i = 0; i < trackElements.length; i++) {
@@ -878,22 +990,26 @@ export async function parseXML(xmlText, api, onProgress) {
         if (title && artist) {
             await new Promise((resolve) => setTimeout(resolve, 300));
 
+            const source = { title, artist, album };
             try {
                 const searchQuery = `${title} ${artist}`;
                 const searchResults = await api.searchTracks(searchQuery);
 
                 if (searchResults.items && searchResults.items.length > 0) {
                     tracks.push(searchResults.items[0]);
+                    matches.push(createImportMatch(source, searchResults.items[0], searchResults.items));
                 } else {
+                    matches.push(createImportMatch(source, null));
                     missingTracks.push({ title, artist, album });
                 }
             } catch {
+                matches.push(createImportMatch(source, null));
                 missingTracks.push({ title, artist, album });
             }
         }
     }
 
-    return { tracks, missingTracks };
+    return { tracks, missingTracks, matches };
 }
 
 /**
@@ -901,12 +1017,13 @@ export async function parseXML(xmlText, api, onProgress) {
  * @param {string} m3uText - M3U content
  * @param {Function} api - API instance for searching tracks
  * @param {Function} onProgress - Progress callback
- * @returns {Promise<{tracks: Array, missingTracks: Array}>}
+ * @returns {Promise<{tracks: Array, missingTracks: Array, matches: Array}>}
  */
 export async function parseM3U(m3uText, api, onProgress) {
     const lines = m3uText.trim().split('\n');
     const tracks = [];
     const missingTracks = [];
+    const matches = [];
 
     const trackInfo = [];
     let currentInfo = null;
@@ -953,22 +1070,26 @@ export async function parseM3U(m3uText, api, onProgress) {
         if (info.title) {
             await new Promise((resolve) => setTimeout(resolve, 300));
 
+            const source = { title: info.title, artist: info.artist, album: '' };
             try {
                 const searchQuery = info.artist ? `${info.title} ${info.artist}` : info.title;
                 const searchResults = await api.searchTracks(searchQuery);
 
                 if (searchResults.items && searchResults.items.length > 0) {
                     tracks.push(searchResults.items[0]);
+                    matches.push(createImportMatch(source, searchResults.items[0], searchResults.items));
                 } else {
+                    matches.push(createImportMatch(source, null));
                     missingTracks.push({ title: info.title, artist: info.artist, album: '' });
                 }
             } catch {
+                matches.push(createImportMatch(source, null));
                 missingTracks.push({ title: info.title, artist: info.artist, album: '' });
             }
         }
     }
 
-    return { tracks, missingTracks };
+    return { tracks, missingTracks, matches };
 }
 
 /**
@@ -997,4 +1118,13 @@ function escapeXml(text) {
 }
 
 // Export all functions
-export { getTrackArtists };
+export {
+    getTrackArtists,
+    isIsrcMatch,
+    stringSimilarity,
+    scoreTrack,
+    findBestMatch,
+    scoreCandidate,
+    rankCandidates,
+    createImportMatch,
+};
@@ -0,0 +1,284 @@
+// js/resumable-download.js
+// Chunked downloads that survive interruptions. Progressive files are fetched as HTTP byte ranges,
+// DASH/HLS streams segment by segment, and every finished piece is checkpointed in IndexedDB under a
+// stable key (track + quality), so a retry after a dropped connection or a reload only fetches what
+// is still missing. Signed stream URLs change between attempts, so checkpoints are matched by a
+// fingerprint of the content (byte size, segment layout) rather than by URL.
+
+import { db } from './db.js';
+import { AbortError } from './errorTypes.ts';
+import { DownloadProgress, SegmentedDownloadProgress } from './progressEvents.js';
+import { readableStreamIterator } from './readableStreamIterator.js';
+
+export const RANGE_CHUNK_BYTES = 4 * 1024 * 1024;
+const FETCH_ATTEMPTS = 3;
+const FETCH_RETRY_DELAY_MS = 1000;
+// Partial downloads nobody came back for are dropped after a week
+const CHECKPOINT_MAX_AGE_MS = 7 * 24 * 60 * 60 * 1000;
+
+let checkpointsPruned = false;
+
+/**
+ * Parse a `Content-Range: bytes start-end/total` header
+ * @param {string|null} header
+ * @returns {{start: number, end: number, total: number|null}|null}
+ */
+export function parseContentRange(header) {
+    const match = /^bytes\s+(\d+)-(\d+)\/(\d+|\*)$/i.exec(header?.trim() || '');
+    if (!match) return null;
+    return {
+        start: parseInt(match[1], 10),
+        end: parseInt(match[2], 10),
+        total: match[3] === '*' ? null : parseInt(match[3], 10),
+    };
+}
+
+/**
+ * Split a file into inclusive byte ranges of at most `chunkBytes`
+ * @returns {{index: number, start: number, end: number}[]}
+ */
+export function planRanges(totalBytes, chunkBytes = RANGE_CHUNK_BYTES) {
+    const ranges = [];
+    for (let start = 0, index = 0; start < totalBytes; start += chunkBytes, index++) {
+        ranges.push({ index, start, end: Math.min(start + chunkBytes, totalBytes) - 1 });
+    }
+    return ranges;
+}
+
+/** Whether a failed response is worth asking for again */
+export function isRetryableStatus(status) {
+    return status === 408 || status === 429 || status >= 500;
+}
+
+/**
+ * fetch() that retries network errors and transient HTTP errors a few times before giving up
+ */
+export async function fetchWithRetry(url, init = {}, attempts = FETCH_ATTEMPTS) {
+    let lastError = null;
+    for (let attempt = 1; attempt <= attempts; attempt++) {
+        if (init.signal?.aborted) throw new AbortError();
+        try {
+            const response = await fetch(url, init);
+            if (response.ok) return response;
+            lastError = new Error(`Fetch failed: ${response.status}`);
+            if (!isRetryableStatus(response.status)) break;
+        } catch (error) {
+            if (error?.name === 'AbortError' || init.signal?.aborted) throw error;
+            lastError = error;
+        }
+        if (attempt < attempts) {
+            await new Promise((resolve) => setTimeout(resolve, FETCH_RETRY_DELAY_MS * attempt));
+        }
+    }
+    throw lastError;
+}
+
+async function pruneCheckpoints() {
+    if (checkpointsPruned) return;
+    checkpointsPruned = true;
+    try {
+        const cutoff = Date.now() - CHECKPOINT_MAX_AGE_MS;
+        const checkpoints = await db.getDownloadCheckpoints();
+        for (const checkpoint of checkpoints) {
+            if ((checkpoint.updatedAt || 0) < cutoff) await db.deleteDownloadCheckpoint(checkpoint.key);
+        }
+    } catch (e) {
+        console.warn('[Download] Could not prune checkpoints:', e);
+    }
+}
+
+/**
+ * Finished pieces of one download. Without a key (or if IndexedDB refuses the data) pieces are
+ * only kept in memory, which still lets a single attempt retry individual chunks.
+ */
+export class DownloadCheckpoint {
+    constructor(key, fingerprint, record = null) {
+        this.key = key;
+        this.fingerprint = fingerprint;
+        this.completed = new Set(record?.completed || []);
+        this.receivedBytes = record?.receivedBytes || 0;
+        this.memory = new Map();
+        this.persistent = !!key;
+    }
+
+    /**
+     * Load the checkpoint for `key`, discarding it if it was made for different content
+     * @param {string|null} key
+     * @param {string} fingerprint
+     */
+    static async open(key, fingerprint) {
+        if (!key) return new DownloadCheckpoint(null, fingerprint);
+        await pruneCheckpoints();
+        try {
+            const record = await db.getDownloadCheckpoint(key);
+            if (record?.fingerprint === fingerprint) {
+                return new DownloadCheckpoint(key, fingerprint, record);
+            }
+            if (record) await db.deleteDownloadCheckpoint(key);
+        } catch (e) {
+            console.warn('[Download] Could not read checkpoint:', e);
+        }
+        return new DownloadCheckpoint(key, fingerprint);
+    }
+
+    has(index) {
+        return this.completed.has(index);
+    }
+
+    async put(index, blob) {
+        if (this.persistent) {
+            try {
+                await db.saveDownloadChunk(`${this.key}#${index}`, blob);
+            } catch (e) {
+                console.warn('[Download] Checkpointing failed, continuing in memory:', e);
+                this.persistent = false;
+            }
+        }
+        if (!this.persistent) this.memory.set(index, blob);
+
+        this.completed.add(index);
+        this.receivedBytes += blob.size;
+        if (this.persistent) {
+            await db
+                .saveDownloadCheckpoint({
+                    key: this.key,
+                    fingerprint: this.fingerprint,
+                    completed: [...this.completed],
+                    receivedBytes: this.receivedBytes,
+                    updatedAt: Date.now(),
+                })
+                .catch((e) => console.warn('[Download] Could not save checkpoint:', e));
+        }
+    }
+
+    /**
+     * Join all pieces in order. Pieces that went missing from storage drop the checkpoint and throw,
+     * so the next attempt starts clean.
+     */
+    async assemble(count, type) {
+        const parts = [];
+        for (let index = 0; index < count; index++) {
+            let part = this.memory.get(index);
+            if (!part && this.key) part = await db.getDownloadChunk(`${this.key}#${index}`);
+            if (!part) {
+                await this.clear();
+                throw new Error(`Download checkpoint is missing part ${index}`);
+            }
+            parts.push(part);
+        }
+        return new Blob(parts, { type });
+    }
+
+    async clear() {
+        this.memory.clear();
+        this.completed.clear();
+        this.receivedBytes = 0;
+        if (this.key) {
+            await db.deleteDownloadCheckpoint(this.key).catch(() => {});
+        }
+    }
+}
+
+/**
+ * Download a progressive file as byte ranges, resuming from a checkpoint when possible.
+ * Resolves to null if the server does not support ranges; the caller should then fall back to a
+ * plain GET.
+ * @param {string} url - Already proxied URL
+ * @param {object} [options]
+ * @param {string|null} [options.checkpointKey]
+ * @param {AbortSignal} [options.signal]
+ * @param {Function} [options.onProgress]
+ * @param {string} [options.defaultMime]
+ * @returns {Promise<Blob|null>}
+ */
+export async function downloadWithRanges(url, options = {}) {
+    const { checkpointKey = null, signal, onProgress, defaultMime = 'application/octet-stream' } = options;
+
+    // A one-byte probe tells us the size and whether ranges are honoured in one request
+    const probe = await fetchWithRetry(url, { headers: { Range: 'bytes=0-0' }, cache: 'no-store', signal });
+    const probeRange = probe.status === 206 ? parseContentRange(probe.headers.get('Content-Range')) : null;
+    await probe.body?.cancel().catch(() => {});
+    if (!probeRange?.total) return null;
+
+    const totalBytes = probeRange.total;
+    const type = probe.headers.get('Content-Type') || defaultMime;
+    const ranges = planRanges(totalBytes);
+    const checkpoint = await DownloadCheckpoint.open(checkpointKey, `bytes:${totalBytes}`);
+
+    onProgress?.(new DownloadProgress(checkpoint.receivedBytes, totalBytes));
+
+    for (const range of ranges) {
+        if (checkpoint.has(range.index)) continue;
+        if (signal?.aborted) throw new AbortError();
+
+        const response = await fetchWithRetry(url, {
+            headers: { Range: `bytes=${range.start}-${range.end}` },
+            cache: 'no-store',
+            signal,
+        });
+        if (response.status === 200) {
+            // The range was ignored and the whole file sent, which does just as well as the missing pieces
+            const whole = await response.blob();
+            if (whole.size !== totalBytes) {
+                throw new Error(`Range request was not honoured and got ${whole.size} of ${totalBytes} bytes`);
+            }
+            await checkpoint.clear();
+            onProgress?.(new DownloadProgress(totalBytes, totalBytes));
+            return new Blob([whole], { type });
+        }
+        if (response.status !== 206) {
+            throw new Error(`Range request was not honoured (${response.status})`);
+        }
+
+        const chunks = [];
+        let received = 0;
+        for await (const chunk of readableStreamIterator(response.body)) {
+            chunks.push(chunk);
+            received += chunk.byteLength;
+            onProgress?.(new DownloadProgress(checkpoint.receivedBytes + received, totalBytes));
+        }
+        if (received !== range.end - range.start + 1) {
+            throw new Error(`Incomplete range ${range.start}-${range.end}: got ${received} bytes`);
+        }
+
+        await checkpoint.put(range.index, new Blob(chunks));
+    }
+
+    const blob = await checkpoint.assemble(ranges.length, type);
+    await checkpoint.clear();
+    return blob;
+}
+
+/**
+ * Download DASH/HLS segments in order, resuming from a checkpoint when possible
+ * @param {string[]} urls - Already proxied segment URLs (initialization segment first)
+ * @param {object} [options]
+ * @param {string|null} [options.checkpointKey]
+ * @param {string} options.fingerprint - Identifies the stream layout, e.g. representation + segment count
+ * @param {AbortSignal} [options.signal]
+ * @param {Function} [options.onProgress]
+ * @param {number} [options.totalBytes]
+ * @param {string} [options.mimeType]
+ * @returns {Promise<Blob>}
+ */
+export async function downloadSegments(urls, options) {
+    const { checkpointKey = null, fingerprint, signal, onProgress, totalBytes, mimeType } = options;
+    const checkpoint = await DownloadCheckpoint.open(checkpointKey, `${fingerprint}:${urls.length}`);
+
+    for (let i = 0; i < urls.length; i++) {
+        if (checkpoint.has(i)) continue;
+        if (signal?.aborted) throw new AbortError();
+
+        onProgress?.(new SegmentedDownloadProgress(checkpoint.receivedBytes, totalBytes, i, urls.length));
+
+        const response = await fetchWithRetry(urls[i], { signal });
+        const data = await response.arrayBuffer();
+        await checkpoint.put(i, new Blob([data]));
+
+        onProgress?.(new SegmentedDownloadProgress(checkpoint.receivedBytes, totalBytes, i + 1, urls.length));
+    }
+
+    const blob = await checkpoint.assemble(urls.length, mimeType);
+    await checkpoint.clear();
+    return blob;
+}
@@ -70,6 +70,9 @@ export function createRouter(ui) {
             case 'folder':
                 await ui.renderFolderPage(param);
                 break;
+            case 'smartplaylist':
+                await ui.renderSmartPlaylistPage(param);
+                break;
             case 'mix': {
                 const { provider, id } = extractProviderAndId(param);
                 await ui.renderMixPage(id, provider);
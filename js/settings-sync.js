@@ -0,0 +1,267 @@
+// js/settings-sync.js
+// Opt-in sync of app settings between devices, stored in the user_settings collection. Only the keys listed in
+// SYNC_CATEGORIES are synced, and anything that looks like a token, password or API key never leaves the
+// device. Every key remembers when it last changed, and the most recent change wins. Sync backends without a
+// place for settings (the account server, for now) leave them alone, see syncManager.supports().
+
+import {
+    artistBannerSettings,
+    audioEffectsSettings,
+    backgroundSettings,
+    binauralDspSettings,
+    cardSettings,
+    contentBlockingSettings,
+    crossfadeSettings,
+    dynamicColorSettings,
+    dynamicsSettings,
+    equalizerSettings,
+    exponentialVolumeSettings,
+    fontSettings,
+    fullscreenCoverClickSettings,
+    fullscreenCoverNoRoundSettings,
+    fullscreenCoverTiltDistanceSettings,
+    fullscreenCoverTiltSpeedSettings,
+    fullscreenCoverVanillaTiltSettings,
+    gaplessPlaybackSettings,
+    keyboardShortcuts,
+    lastFMStorage,
+    libreFmSettings,
+    listenBrainzSettings,
+    malojaSettings,
+    monoAudioSettings,
+    nowPlayingSettings,
+    qualityBadgeSettings,
+    replayGainSettings,
+    settingsSyncSettings,
+    syncBackendSettings,
+    themeManager,
+    trackDateSettings,
+    visualizerSettings,
+    waveformSettings,
+} from './storage.js';
+import { authManager } from './accounts/auth.js';
+import { syncManager } from './accounts/pocketbase.js';
+import { showNotification } from './downloads.js';
+
+/** Bumped when synced keys are renamed or change format */
+export const SETTINGS_SYNC_VERSION = 1;
+
+// Larger values are files, e.g. uploaded fonts, and stay on the device
+const MAX_VALUE_LENGTH = 64 * 1024;
+// Writes made by this tab don't fire an event, so local changes are looked for on an interval
+const CHECK_INTERVAL = 15000;
+const SECRET_PATTERN = /token|secret|password|session|credential|api-?key/i;
+
+// Every *_KEY constant of the given settings objects
+function keysOf(...settings) {
+    return settings.flatMap((object) =>
+        Object.entries(object)
+            .filter(([name, value]) => /(^|_)KEY(_|$)/.test(name) && typeof value === 'string')
+            .map(([, value]) => value)
+    );
+}
+
+export const SYNC_CATEGORIES = [
+    {
+        id: 'audio',
+        label: 'Equalizer & Audio',
+        keys: keysOf(
+            equalizerSettings,
+            monoAudioSettings,
+            binauralDspSettings,
+            dynamicsSettings,
+            replayGainSettings,
+            crossfadeSettings,
+            gaplessPlaybackSettings,
+            exponentialVolumeSettings,
+            audioEffectsSettings
+        ),
+    },
+    {
+        id: 'visualizer',
+        label: 'Visualizer',
+        keys: [...keysOf(visualizerSettings), 'butterchurn-cycle-enabled', 'butterchurn-randomize-enabled'],
+    },
+    {
+        id: 'appearance',
+        label: 'Theme & Appearance',
+        keys: keysOf(
+            themeManager,
+            fontSettings,
+            cardSettings,
+            backgroundSettings,
+            dynamicColorSettings,
+            nowPlayingSettings,
+            artistBannerSettings,
+            waveformSettings,
+            qualityBadgeSettings,
+            trackDateSettings,
+            fullscreenCoverClickSettings,
+            fullscreenCoverNoRoundSettings,
+            fullscreenCoverVanillaTiltSettings,
+            fullscreenCoverTiltDistanceSettings,
+            fullscreenCoverTiltSpeedSettings
+        ),
+    },
+    { id: 'blocking', label: 'Blocked Content', keys: keysOf(contentBlockingSettings) },
+    { id: 'shortcuts', label: 'Keyboard Shortcuts', keys: keysOf(keyboardShortcuts) },
+    {
+        id: 'scrobbling',
+        label: 'Scrobbling',
+        keys: keysOf(lastFMStorage, listenBrainzSettings, malojaSettings, libreFmSettings),
+    },
+];
+
+// The WebDAV folder and its login belong to this device, whatever the keys are named
+const DEVICE_KEYS = new Set(keysOf(syncBackendSettings));
+
+export function isSecretKey(key) {
+    return SECRET_PATTERN.test(key) || DEVICE_KEYS.has(key);
+}
+
+/** Keys synced for the given categories, without secrets */
+export function getSyncableKeys(categories = SYNC_CATEGORIES.map((c) => c.id)) {
+    return SYNC_CATEGORIES.filter((category) => categories.includes(category.id))
+        .flatMap((category) => category.keys)
+        .filter((key) => !isSecretKey(key));
+}
+
+/**
+ * Stamp the keys whose value changed since the last sync. Keys seen for the first time get time 0, so a
+ * device that just turned sync on takes the settings already in the cloud.
+ * @param {Object<string, {value: string|null, updatedAt: number}>} entries - As of the last sync
+ * @param {Object<string, string|null>} values - Current values
+ */
+export function recordChanges(entries, values, now = Date.now()) {
+    const result = { ...entries };
+    for (const [key, value] of Object.entries(values)) {
+        const known = entries[key];
+        if (!known) result[key] = { value, updatedAt: 0 };
+        else if (known.value !== value) result[key] = { value, updatedAt: now };
+    }
+    return result;
+}
+
+/**
+ * Merge local entries with the cloud's. The latest change of each key wins, the cloud wins ties, and keys
+ * this device doesn't sync are kept as they are.
+ * @returns {{entries: object, apply: Object<string, string|null>, push: boolean}} `apply` holds the values to
+ *   write locally, `push` tells whether the cloud copy is out of date
+ */
+export function mergeSettingsEntries(local, remote = {}) {
+    const entries = { ...remote };
+    const apply = {};
+    let push = false;
+    for (const [key, entry] of Object.entries(local)) {
+        const other = remote[key];
+        if (!other) {
+            if (entry.value === null) continue;
+            entries[key] = entry;
+            push = true;
+        } else if (entry.updatedAt > other.updatedAt) {
+            entries[key] = entry;
+            push = push || entry.value !== other.value;
+        } else if (entry.value !== other.value) {
+            apply[key] = other.value;
+        }
+    }
+    return { entries, apply, push };
+}
+
+function getEnabledKeys() {
+    const categories = SYNC_CATEGORIES.map((c) => c.id).filter((id) => settingsSyncSettings.isCategoryEnabled(id));
+    return getSyncableKeys(categories);
+}
+
+function readValues(keys) {
+    const values = {};
+    keys.forEach((key) => {
+        const value = localStorage.getItem(key);
+        if (value === null || value.length <= MAX_VALUE_LENGTH) values[key] = value;
+    });
+    return values;
+}
+
+/** Stamp settings changed on this device since the last check. Returns whether any changed. */
+export function recordLocalChanges() {
+    const state = settingsSyncSettings.getState();
+    const entries = recordChanges(state.entries, readValues(getEnabledKeys()));
+    const changed = Object.entries(entries).some(([key, entry]) => state.entries[key]?.value !== entry.value);
+    settingsSyncSettings.setState({ ...state, entries });
+    return changed;
+}
+
+let syncing = null;
+
+const canSync = () => !!syncManager.getSyncUser() && syncManager.supports('settings');
+
+/**
+ * Merge this device's settings with the cloud copy
+ * @returns {Promise<{status: 'synced'|'outdated', applied?: string[], pushed?: boolean}|null>} Null when sync
+ *   is off or there is nothing to sync to
+ */
+export function syncSettings() {
+    if (!settingsSyncSettings.isEnabled() || !canSync()) return Promise.resolve(null);
+    if (syncing) return syncing;
+
+    syncing = (async () => {
+        recordLocalChanges();
+        const state = settingsSyncSettings.getState();
+        const remote = await syncManager.getSettings();
+
+        // Written by a newer version of the app; its keys may mean something else by now
+        if (remote && remote.version > SETTINGS_SYNC_VERSION) return { status: 'outdated' };
+
+        const keys = new Set(getEnabledKeys());
+        const local = Object.fromEntries(Object.entries(state.entries).filter(([key]) => keys.has(key)));
+        const { entries, apply, push } = mergeSettingsEntries(local, remote?.entries);
+
+        Object.entries(apply).forEach(([key, value]) => {
+            if (value === null) localStorage.removeItem(key);
+            else localStorage.setItem(key, value);
+        });
+
+        let revision = remote?.revision || 0;
+        if (push) {
+            revision += 1;
+            const saved = await syncManager.saveSettings({ version: SETTINGS_SYNC_VERSION, revision, entries });
+            if (!saved) throw new Error('Failed to save settings');
+        }
+
+        const synced = Object.fromEntries(Object.entries(entries).filter(([key]) => keys.has(key)));
+        settingsSyncSettings.setState({ entries: { ...state.entries, ...synced }, revision, syncedAt: Date.now() });
+
+        const applied = Object.keys(apply);
+        if (applied.length > 0) {
+            window.dispatchEvent(new CustomEvent('settings-synced', { detail: { keys: applied } }));
+        }
+        return { status: 'synced', applied, pushed: push };
+    })().finally(() => {
+        syncing = null;
+    });
+    return syncing;
+}
+
+export function initSettingsSync() {
+    const sync = () => syncSettings().catch((error) => console.warn('[SettingsSync] Sync failed:', error));
+
+    authManager.onAuthStateChanged((user) => {
+        if (user) sync();
+    });
+    // Syncing to a WebDAV folder doesn't wait for a sign-in
+    if (syncManager.backend.id !== 'account') sync();
+    window.addEventListener('online', sync);
+    document.addEventListener('visibilitychange', () => {
+        if (document.visibilityState === 'visible') sync();
+    });
+    setInterval(() => {
+        if (settingsSyncSettings.isEnabled() && canSync() && recordLocalChanges()) sync();
+    }, CHECK_INTERVAL);
+
+    window.addEventListener('settings-synced', (e) => {
+        if (e.detail.keys.includes(themeManager.STORAGE_KEY) || e.detail.keys.includes(themeManager.CUSTOM_THEME_KEY)) {
+            themeManager.setTheme(themeManager.getTheme());
+        }
+        showNotification('Settings updated from another device. Some changes apply after a reload.');
+    });
+}
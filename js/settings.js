@@ -5,6 +5,8 @@ import {
     nowPlayingSettings,
     fullscreenCoverClickSettings,
     lyricsSettings,
+    musicBrainzSettings,
+    localMatchSettings,
     backgroundSettings,
     dynamicColorSettings,
     cardSettings,
@@ -35,24 +37,39 @@ import {
     amazonMusicSettings,
     deezerFallbackSettings,
     gaplessPlaybackSettings,
+    crossfadeSettings,
     analyticsSettings,
     modalSettings,
     preferDolbyAtmosSettings,
     binauralDspSettings,
+    eqDeviceProfileSettings,
+    offlineSettings,
+    downloadQueueSettings,
     fullscreenCoverNoRoundSettings,
     fullscreenCoverVanillaTiltSettings,
     fullscreenCoverTiltDistanceSettings,
     fullscreenCoverTiltSpeedSettings,
     devModeSettings,
     serverDisruptionSettings,
+    settingsSyncSettings,
+    syncBackendSettings,
 } from './storage.js';
 import { audioContextManager, getPresetsForBandCount } from './audio-context.js';
-import { calculateBiquadResponse, interpolate, getNormalizationOffset, runAutoEqAlgorithm } from './autoeq-engine.js';
+import { interpolate, getNormalizationOffset, runAutoEqAlgorithm } from './autoeq-engine.js';
+import { filterTypeHasGain, filterTypeHasSlope, parseEqualizerAPO, formatEqualizerAPO } from './parametric-eq.js';
+import { isOutputSelectionSupported, requestOutputDeviceAccess } from './output-devices.js';
+import { importImpulseResponse } from './convolution.js';
+import { offlineLibrary } from './offline-library.js';
+import { formatBytes } from './utils.js';
 import { parseRawData, TARGETS, SPEAKER_TARGETS } from './autoeq-data.js';
 import { fetchAutoEqIndex, fetchHeadphoneData, searchHeadphones, POPULAR_HEADPHONES } from './autoeq-importer.js';
 import { db } from './db.js';
 import { authManager } from './accounts/auth.js';
 import { syncManager } from './accounts/pocketbase.js';
+import { SYNC_CATEGORIES, syncSettings } from './settings-sync.js';
+import { exportBackup, openBackupRestore, readBackupFile } from './backup.js';
+import { openCloudEncryption } from './cloud-encryption-modal.js';
+import { createWebDavBackend } from './accounts/sync-backend.js';
 import { containerFormats, customFormats } from './ffmpegFormats.ts';
 import { BulkDownloadMethod, modernSettings } from './ModernSettings.js';
 
@@ -989,6 +1006,122 @@ export async function initializeSettings(scrobbler, player, api, ui) {
         });
     }
 
+    const downloadQualityAskToggle = document.getElementById('download-quality-ask-toggle');
+    if (downloadQualityAskToggle) {
+        downloadQualityAskToggle.checked = downloadQualitySettings.shouldAsk();
+        downloadQualityAskToggle.addEventListener('change', (e) => {
+            downloadQualitySettings.setAsk(e.target.checked);
+        });
+    }
+
+    // Offline library
+    const offlineUsageStatus = document.getElementById('offline-usage-status');
+    const offlineClearBtn = document.getElementById('offline-clear-btn');
+    const offlineQualitySetting = document.getElementById('offline-quality-setting');
+    const offlineMaxBytesSetting = document.getElementById('offline-max-bytes-setting');
+    const offlineSaveDownloadsToggle = document.getElementById('offline-save-downloads-toggle');
+
+    const renderOfflineUsage = async () => {
+        if (!offlineUsageStatus) return;
+        try {
+            const usage = await offlineLibrary.getUsage();
+            const limit = usage.limit > 0 ? usage.limit : usage.quota;
+            const tracks = `${usage.count} track${usage.count === 1 ? '' : 's'}`;
+            offlineUsageStatus.textContent = limit
+                ? `${tracks}, ${formatBytes(usage.bytes)} of ${formatBytes(limit)} used`
+                : `${tracks}, ${formatBytes(usage.bytes)} used`;
+        } catch (e) {
+            console.warn('Failed to read offline usage:', e);
+        }
+    };
+
+    if (offlineUsageStatus) {
+        void renderOfflineUsage();
+        window.addEventListener('offline-library-changed', () => void renderOfflineUsage());
+    }
+
+    if (offlineClearBtn) {
+        offlineClearBtn.addEventListener('click', async () => {
+            if (!confirm('Remove all offline tracks and stop keeping playlists offline?')) return;
+            await offlineLibrary.clear();
+        });
+    }
+
+    if (offlineQualitySetting) {
+        offlineQualitySetting.value = offlineSettings.getQuality();
+        offlineQualitySetting.addEventListener('change', (e) => {
+            offlineSettings.setQuality(e.target.value);
+        });
+    }
+
+    if (offlineMaxBytesSetting) {
+        const current = String(offlineSettings.getMaxBytes());
+        if (!offlineMaxBytesSetting.querySelector(`option[value="${current}"]`)) {
+            const option = document.createElement('option');
+            option.value = current;
+            option.textContent = formatBytes(offlineSettings.getMaxBytes());
+            offlineMaxBytesSetting.appendChild(option);
+        }
+        offlineMaxBytesSetting.value = current;
+        offlineMaxBytesSetting.addEventListener('change', (e) => {
+            offlineSettings.setMaxBytes(parseInt(e.target.value, 10));
+            void renderOfflineUsage();
+        });
+    }
+
+    if (offlineSaveDownloadsToggle) {
+        offlineSaveDownloadsToggle.checked = offlineSettings.shouldSaveDownloads();
+        offlineSaveDownloadsToggle.addEventListener('change', (e) => {
+            offlineSettings.setSaveDownloads(e.target.checked);
+        });
+    }
+
+    // Download queue
+    const downloadQueueStatus = document.getElementById('download-queue-status');
+    const downloadQueueOpenBtn = document.getElementById('download-queue-open-btn');
+    const downloadQueueConcurrencySetting = document.getElementById('download-queue-concurrency-setting');
+    const downloadQueueRetriesSetting = document.getElementById('download-queue-retries-setting');
+
+    const renderDownloadQueueStatus = async () => {
+        try {
+            const jobs = await db.getDownloadJobs();
+            const pending = jobs.filter((job) => job.status !== 'done').length;
+            const paused = downloadQueueSettings.isPaused() ? ' (paused)' : '';
+            downloadQueueStatus.textContent = pending
+                ? `${pending} download${pending === 1 ? '' : 's'} in progress${paused}`
+                : 'Bulk downloads resume after a reload and retry failed tracks';
+        } catch (e) {
+            console.warn('Failed to read download queue:', e);
+        }
+    };
+
+    if (downloadQueueStatus) {
+        void renderDownloadQueueStatus();
+        window.addEventListener('download-queue-changed', () => void renderDownloadQueueStatus());
+    }
+
+    if (downloadQueueOpenBtn) {
+        downloadQueueOpenBtn.addEventListener('click', async () => {
+            const { openDownloadQueuePanel } = await import('./downloads.js');
+            openDownloadQueuePanel();
+        });
+    }
+
+    if (downloadQueueConcurrencySetting) {
+        downloadQueueConcurrencySetting.value = String(downloadQueueSettings.getConcurrency());
+        downloadQueueConcurrencySetting.addEventListener('change', (e) => {
+            downloadQueueSettings.setConcurrency(parseInt(e.target.value, 10));
+            window.dispatchEvent(new CustomEvent('download-queue-settings-changed'));
+        });
+    }
+
+    if (downloadQueueRetriesSetting) {
+        downloadQueueRetriesSetting.value = String(downloadQueueSettings.getMaxRetries());
+        downloadQueueRetriesSetting.addEventListener('change', (e) => {
+            downloadQueueSettings.setMaxRetries(parseInt(e.target.value, 10));
+        });
+    }
+
     const losslessContainerSetting = document.getElementById('lossless-container-setting');
     const losslessContainerSettingItem = losslessContainerSetting?.closest('.setting-item');
 
@@ -1211,6 +1344,31 @@ export async function initializeSettings(scrobbler, player, api, ui) {
         gaplessPlaybackToggle.checked = gaplessPlaybackSettings.isEnabled();
         gaplessPlaybackToggle.addEventListener('change', (e) => {
             gaplessPlaybackSettings.setEnabled(e.target.checked);
+            if (!e.target.checked) player.crossfade.cancel();
+        });
+    }
+
+    const crossfadeSlider = document.getElementById('crossfade-duration-slider');
+    const crossfadeValue = document.getElementById('crossfade-duration-value');
+    if (crossfadeSlider) {
+        const updateCrossfadeLabel = (seconds) => {
+            if (crossfadeValue) crossfadeValue.textContent = seconds > 0 ? `${seconds}s` : 'Off';
+        };
+        crossfadeSlider.value = crossfadeSettings.getDuration();
+        updateCrossfadeLabel(crossfadeSettings.getDuration());
+        crossfadeSlider.addEventListener('input', (e) => {
+            const seconds = parseFloat(e.target.value) || 0;
+            crossfadeSettings.setDuration(seconds);
+            updateCrossfadeLabel(seconds);
+            if (seconds === 0) player.crossfade.cancel();
+        });
+    }
+
+    const crossfadeAlbumAwareToggle = document.getElementById('crossfade-album-aware-toggle');
+    if (crossfadeAlbumAwareToggle) {
+        crossfadeAlbumAwareToggle.checked = crossfadeSettings.isAlbumAware();
+        crossfadeAlbumAwareToggle.addEventListener('change', (e) => {
+            crossfadeSettings.setAlbumAware(e.target.checked);
         });
     }
 
@@ -1234,6 +1392,14 @@ export async function initializeSettings(scrobbler, player, api, ui) {
         });
     }
 
+    const replayGainAnalysisToggle = document.getElementById('replay-gain-analysis-toggle');
+    if (replayGainAnalysisToggle) {
+        replayGainAnalysisToggle.checked = replayGainSettings.isAnalysisEnabled();
+        replayGainAnalysisToggle.addEventListener('change', (e) => {
+            replayGainSettings.setAnalysisEnabled(e.target.checked);
+        });
+    }
+
     // Mono Audio Toggle
     const monoAudioToggle = document.getElementById('mono-audio-toggle');
     if (monoAudioToggle) {
@@ -1346,6 +1512,181 @@ export async function initializeSettings(scrobbler, player, api, ui) {
         }
     });
 
+    // Reload binaural controls when a device EQ profile is swapped in
+    window.addEventListener('eq-profile-changed', () => {
+        if (binauralToggle && binauralContainer) {
+            binauralToggle.checked = binauralDspSettings.isEnabled();
+            binauralContainer.style.display = binauralToggle.checked ? 'block' : 'none';
+        }
+        if (binauralAutoSpatialToggle) {
+            binauralAutoSpatialToggle.checked = binauralDspSettings.getAutoEnableForSpatial();
+        }
+        if (binauralCrossfeedToggle) {
+            binauralCrossfeedToggle.checked = binauralDspSettings.getCrossfeedEnabled();
+            if (crossfeedLevelRow) crossfeedLevelRow.style.display = binauralCrossfeedToggle.checked ? 'flex' : 'none';
+        }
+        if (binauralCrossfeedLevel) binauralCrossfeedLevel.value = binauralDspSettings.getCrossfeedLevel();
+        if (binauralHrtfPreset) binauralHrtfPreset.value = binauralDspSettings.getHrtfPreset();
+        if (binauralWideningToggle) {
+            binauralWideningToggle.checked = binauralDspSettings.getWideningEnabled();
+            if (wideningSliderRow) wideningSliderRow.style.display = binauralWideningToggle.checked ? 'flex' : 'none';
+        }
+        if (binauralWideningSlider && binauralWidthValue) {
+            binauralWideningSlider.value = binauralDspSettings.getWideningAmount();
+            binauralWidthValue.textContent = parseFloat(binauralWideningSlider.value).toFixed(2);
+        }
+    });
+
+    // ========================================
+    // Output Device & Device EQ Profiles
+    // ========================================
+    const outputDeviceSelect = document.getElementById('audio-output-device-select');
+    const outputDeviceStatus = document.getElementById('audio-output-device-status');
+    const outputDeviceDetectBtn = document.getElementById('audio-output-device-detect-btn');
+    const eqProfileSelect = document.getElementById('eq-device-profile-select');
+    const eqProfileStatus = document.getElementById('eq-device-profile-status');
+    const eqProfileSaveBtn = document.getElementById('eq-device-profile-save-btn');
+    const eqProfileDeleteBtn = document.getElementById('eq-device-profile-delete-btn');
+    const eqProfileAutoSwitchToggle = document.getElementById('eq-device-auto-switch-toggle');
+
+    const renderOutputDevices = async () => {
+        if (!outputDeviceSelect) return;
+        const devices = await audioContextManager.getOutputDevices();
+        const selected = eqDeviceProfileSettings.getOutputDevice();
+
+        outputDeviceSelect.innerHTML = '';
+        const defaultOpt = document.createElement('option');
+        defaultOpt.value = '';
+        defaultOpt.textContent = 'System Default';
+        outputDeviceSelect.appendChild(defaultOpt);
+
+        devices
+            .filter((d) => d.deviceId && d.deviceId !== 'default' && d.deviceId !== 'communications')
+            .forEach((d, i) => {
+                const opt = document.createElement('option');
+                opt.value = d.deviceId;
+                opt.textContent = d.label || `Output ${i + 1}`;
+                outputDeviceSelect.appendChild(opt);
+            });
+        outputDeviceSelect.value = selected;
+        if (outputDeviceSelect.value !== selected) outputDeviceSelect.value = '';
+        outputDeviceSelect.disabled = !isOutputSelectionSupported();
+
+        if (outputDeviceStatus) {
+            const current = audioContextManager.getCurrentOutputDevice();
+            if (!isOutputSelectionSupported()) {
+                outputDeviceStatus.textContent = 'Output selection is not supported in this browser';
+            } else if (!devices.some((d) => d.label)) {
+                outputDeviceStatus.textContent = 'Press Detect to show device names';
+            } else {
+                outputDeviceStatus.textContent = current?.label
+                    ? `Playing on ${current.label}`
+                    : 'Choose where audio plays';
+            }
+        }
+    };
+
+    const renderEqProfiles = () => {
+        if (!eqProfileSelect) return;
+        const profiles = eqDeviceProfileSettings.getProfiles();
+        const activeId = eqDeviceProfileSettings.getActiveProfile();
+        const device = audioContextManager.getCurrentOutputDevice();
+
+        eqProfileSelect.innerHTML = '';
+        const noneOpt = document.createElement('option');
+        noneOpt.value = '';
+        noneOpt.textContent = 'None';
+        eqProfileSelect.appendChild(noneOpt);
+
+        Object.values(profiles)
+            .sort((a, b) => a.name.localeCompare(b.name))
+            .forEach((profile) => {
+                const opt = document.createElement('option');
+                opt.value = profile.id;
+                opt.textContent = profile.name;
+                eqProfileSelect.appendChild(opt);
+            });
+        eqProfileSelect.value = activeId && profiles[activeId] ? activeId : '';
+
+        if (eqProfileDeleteBtn) eqProfileDeleteBtn.style.display = eqProfileSelect.value ? '' : 'none';
+        if (eqProfileStatus) {
+            const deviceName = device?.label || 'this output';
+            eqProfileStatus.textContent =
+                activeId && profiles[activeId]
+                    ? `Using "${profiles[activeId].name}" on ${deviceName}`
+                    : `EQ, graphic EQ and binaural settings used on ${deviceName}`;
+        }
+    };
+
+    if (outputDeviceSelect) {
+        void renderOutputDevices();
+        outputDeviceSelect.addEventListener('change', async (e) => {
+            await audioContextManager.setOutputDevice(e.target.value);
+            await renderOutputDevices();
+        });
+    }
+
+    if (outputDeviceDetectBtn) {
+        outputDeviceDetectBtn.addEventListener('click', async () => {
+            try {
+                const picked = await requestOutputDeviceAccess();
+                if (picked) await audioContextManager.setOutputDevice(picked);
+            } catch (e) {
+                console.warn('[OutputDevices] Permission request failed:', e);
+            }
+            await renderOutputDevices();
+        });
+    }
+
+    if (eqProfileSelect) {
+        renderEqProfiles();
+        eqProfileSelect.addEventListener('change', (e) => {
+            audioContextManager.bindEQProfile(e.target.value || null);
+            renderEqProfiles();
+        });
+    }
+
+    if (eqProfileSaveBtn) {
+        eqProfileSaveBtn.addEventListener('click', () => {
+            const activeId = eqDeviceProfileSettings.getActiveProfile();
+            const profiles = eqDeviceProfileSettings.getProfiles();
+            if (activeId && profiles[activeId]) {
+                eqDeviceProfileSettings.updateProfileSnapshot(activeId, eqDeviceProfileSettings.captureSnapshot());
+                if (eqProfileStatus) eqProfileStatus.textContent = `Saved "${profiles[activeId].name}"`;
+                return;
+            }
+            const device = audioContextManager.getCurrentOutputDevice();
+            const name = prompt('Profile name:', device?.label || '');
+            if (!name || !name.trim()) return;
+            audioContextManager.createEQProfile(name.trim());
+            renderEqProfiles();
+        });
+    }
+
+    if (eqProfileDeleteBtn) {
+        eqProfileDeleteBtn.addEventListener('click', () => {
+            const profileId = eqProfileSelect?.value;
+            const profile = eqDeviceProfileSettings.getProfiles()[profileId];
+            if (!profile || !confirm(`Delete profile "${profile.name}"?`)) return;
+            eqDeviceProfileSettings.deleteProfile(profileId);
+            renderEqProfiles();
+        });
+    }
+
+    if (eqProfileAutoSwitchToggle) {
+        eqProfileAutoSwitchToggle.checked = eqDeviceProfileSettings.isAutoSwitchEnabled();
+        eqProfileAutoSwitchToggle.addEventListener('change', (e) => {
+            eqDeviceProfileSettings.setAutoSwitchEnabled(e.target.checked);
+            if (e.target.checked) void audioContextManager._scheduleOutputSync();
+        });
+    }
+
+    window.addEventListener('audio-output-changed', () => {
+        void renderOutputDevices();
+        renderEqProfiles();
+    });
+    window.addEventListener('eq-profile-changed', renderEqProfiles);
+
     // Exponential Volume Toggle
     const exponentialVolumeToggle = document.getElementById('exponential-volume-toggle');
     if (exponentialVolumeToggle) {
@@ -1631,6 +1972,21 @@ export async function initializeSettings(scrobbler, player, api, ui) {
         geqFreqMaxInput.addEventListener('change', handleFreqRangeChange);
     }
 
+    // Reload graphic EQ controls when a device EQ profile is swapped in
+    window.addEventListener('eq-profile-changed', () => {
+        geqBandCount = equalizerSettings.getGraphicEqBandCount();
+        geqFreqRange = equalizerSettings.getGraphicEqFreqRange();
+        geqGains = equalizerSettings.getGraphicEqGains(geqBandCount);
+        geqPreamp = equalizerSettings.getGraphicEqPreamp();
+        if (geqBandCountInput) geqBandCountInput.value = geqBandCount;
+        if (geqFreqMinInput) geqFreqMinInput.value = geqFreqRange.min;
+        if (geqFreqMaxInput) geqFreqMaxInput.value = geqFreqRange.max;
+        geqPreampSliders.forEach((slider) => (slider.value = geqPreamp));
+        geqPreampValues.forEach((v) => (v.textContent = `${geqPreamp.toFixed(1)} dB`));
+        geqPresetSelects.forEach((select) => (select.value = ''));
+        rebuildGeq();
+    });
+
     // Legacy EQ Import / Export
     const parseGeqLabelFrequency = (label) => {
         const normalized = String(label).trim().toLowerCase().replace(/\s+/g, '');
@@ -2215,7 +2571,8 @@ export async function initializeSettings(scrobbler, player, api, ui) {
 
                 // Draw individual band bell curves (filled)
                 activeBands.forEach((band, i) => {
-                    if (!band.enabled || Math.abs(band.gain) < 0.1) return;
+                    if (!band.enabled || band.type === 'allpass') return;
+                    if (filterTypeHasGain(band.type || 'peaking') && Math.abs(band.gain) < 0.1) return;
                     const color = nodeColors[i % nodeColors.length];
                     const r = parseInt(color.slice(1, 3), 16);
                     const g2 = parseInt(color.slice(3, 5), 16);
@@ -2226,7 +2583,7 @@ export async function initializeSettings(scrobbler, player, api, ui) {
                     ctx.beginPath();
                     ctx.moveTo(padLeft, gy(0));
                     for (let f = FREQ_MIN; f <= FREQ_MAX; f *= 1.02) {
-                        const resp = calculateBiquadResponse(f, band, sampleRate);
+                        const resp = audioContextManager._biquadResponseDb(f, band, sampleRate);
                         ctx.lineTo(gx(f), gy(resp));
                     }
                     ctx.lineTo(padLeft + w, gy(0));
@@ -2238,7 +2595,7 @@ export async function initializeSettings(scrobbler, player, api, ui) {
                     ctx.beginPath();
                     let started = false;
                     for (let f = FREQ_MIN; f <= FREQ_MAX; f *= 1.02) {
-                        const resp = calculateBiquadResponse(f, band, sampleRate);
+                        const resp = audioContextManager._biquadResponseDb(f, band, sampleRate);
                         const bx = gx(f);
                         const by = gy(resp);
                         if (!started) {
@@ -2257,7 +2614,7 @@ export async function initializeSettings(scrobbler, player, api, ui) {
                 for (let f = FREQ_MIN; f <= FREQ_MAX; f *= 1.02) {
                     let totalGain = 0;
                     for (const band of activeBands) {
-                        if (band.enabled) totalGain += calculateBiquadResponse(f, band, sampleRate);
+                        if (band.enabled) totalGain += audioContextManager._biquadResponseDb(f, band, sampleRate);
                     }
                     eqCurve.push({ freq: f, gain: totalGain });
                 }
@@ -2357,7 +2714,7 @@ export async function initializeSettings(scrobbler, player, api, ui) {
                     nodeGain = band.gain;
                     sumGain = 0;
                     for (const b of activeBands) {
-                        if (b.enabled) sumGain += calculateBiquadResponse(band.freq, b, sampleRate);
+                        if (b.enabled) sumGain += audioContextManager._biquadResponseDb(band.freq, b, sampleRate);
                     }
                 } else {
                     nodeGain = interpolate(band.freq, autoeqCorrectedCurve) + graphShift;
@@ -2412,7 +2769,7 @@ export async function initializeSettings(scrobbler, player, api, ui) {
                 ctx.lineWidth = 1.5;
                 ctx.stroke();
 
-                // Show M/S channel label inside node for non-stereo bands
+                // Show M/S or L/R channel label inside node for non-stereo bands
                 const bandChannel = band.channel || 'stereo';
                 if (bandChannel !== 'stereo') {
                     ctx.save();
@@ -2420,7 +2777,7 @@ export async function initializeSettings(scrobbler, player, api, ui) {
                     ctx.textAlign = 'center';
                     ctx.textBaseline = 'middle';
                     ctx.fillStyle = isDragged ? nodeColor : '#fff';
-                    ctx.fillText(bandChannel === 'mid' ? 'M' : 'S', x, y + 0.5);
+                    ctx.fillText(bandChannel[0].toUpperCase(), x, y + 0.5);
                     ctx.restore();
                 }
 
@@ -2481,7 +2838,7 @@ export async function initializeSettings(scrobbler, player, api, ui) {
         autoeqCorrectedCurve = measurement.map((p) => {
             let correction = 0;
             for (const band of bands) {
-                if (band.enabled) correction += calculateBiquadResponse(p.freq, band, sampleRate);
+                if (band.enabled) correction += audioContextManager._biquadResponseDb(p.freq, band, sampleRate);
             }
             return { freq: p.freq, gain: p.gain + normOff + correction };
         });
@@ -2719,24 +3076,15 @@ export async function initializeSettings(scrobbler, player, api, ui) {
             const band = bands[nodeIdx];
 
             // Update active states for filter type items
+            const bandType = band.type || 'peaking';
             eqCtxMenu.querySelectorAll('.eq-ctx-type').forEach((li) => {
-                const action = li.dataset.action;
-                const isActive =
-                    (action === 'eq-type-lowshelf' && band.type === 'lowshelf') ||
-                    (action === 'eq-type-peaking' && band.type === 'peaking') ||
-                    (action === 'eq-type-highshelf' && band.type === 'highshelf');
-                li.classList.toggle('eq-ctx-active', isActive);
+                li.classList.toggle('eq-ctx-active', li.dataset.action === `eq-type-${bandType}`);
             });
 
-            // Update active states for channel items (per-band M/S mode)
+            // Update active states for channel items (per-band M/S and L/R routing)
             const bandChannel = band.channel || 'stereo';
             eqCtxMenu.querySelectorAll('.eq-ctx-channel').forEach((li) => {
-                const action = li.dataset.action;
-                const isActive =
-                    (action === 'eq-channel-stereo' && bandChannel === 'stereo') ||
-                    (action === 'eq-channel-mid' && bandChannel === 'mid') ||
-                    (action === 'eq-channel-side' && bandChannel === 'side');
-                li.classList.toggle('eq-ctx-active', isActive);
+                li.classList.toggle('eq-ctx-active', li.dataset.action === `eq-channel-${bandChannel}`);
             });
 
             // Position menu at cursor, clamped to viewport
@@ -2768,6 +3116,11 @@ export async function initializeSettings(scrobbler, player, api, ui) {
                         'eq-type-lowshelf': 'lowshelf',
                         'eq-type-peaking': 'peaking',
                         'eq-type-highshelf': 'highshelf',
+                        'eq-type-notch': 'notch',
+                        'eq-type-bandpass': 'bandpass',
+                        'eq-type-highpass': 'highpass',
+                        'eq-type-lowpass': 'lowpass',
+                        'eq-type-allpass': 'allpass',
                     };
                     const newType = typeMap[action];
                     if (newType) {
@@ -2779,7 +3132,7 @@ export async function initializeSettings(scrobbler, player, api, ui) {
                     }
                 }
 
-                // Channel actions (per-band M/S mode)
+                // Channel actions (per-band M/S and L/R routing)
                 if (
                     action.startsWith('eq-channel-') &&
                     contextMenuNodeIdx !== null &&
@@ -2790,6 +3143,8 @@ export async function initializeSettings(scrobbler, player, api, ui) {
                         'eq-channel-stereo': 'stereo',
                         'eq-channel-mid': 'mid',
                         'eq-channel-side': 'side',
+                        'eq-channel-left': 'left',
+                        'eq-channel-right': 'right',
                     };
                     const newChannel = channelMap[action];
                     if (newChannel) {
@@ -3112,6 +3467,7 @@ export async function initializeSettings(scrobbler, player, api, ui) {
             control.dataset.band = i;
             const currentType = band.type || 'peaking';
             const currentChannel = band.channel || 'stereo';
+            const currentSlope = band.slope || 12;
             control.innerHTML = `
                 <div class="autoeq-band-header">
                     <span class="autoeq-band-number">${i + 1}</span>
@@ -3119,11 +3475,23 @@ export async function initializeSettings(scrobbler, player, api, ui) {
                         <option value="peaking"${currentType === 'peaking' ? ' selected' : ''}>PK</option>
                         <option value="lowshelf"${currentType === 'lowshelf' ? ' selected' : ''}>LSF</option>
                         <option value="highshelf"${currentType === 'highshelf' ? ' selected' : ''}>HSF</option>
+                        <option value="notch"${currentType === 'notch' ? ' selected' : ''}>NO</option>
+                        <option value="bandpass"${currentType === 'bandpass' ? ' selected' : ''}>BP</option>
+                        <option value="highpass"${currentType === 'highpass' ? ' selected' : ''}>HP</option>
+                        <option value="lowpass"${currentType === 'lowpass' ? ' selected' : ''}>LP</option>
+                        <option value="allpass"${currentType === 'allpass' ? ' selected' : ''}>AP</option>
+                    </select>
+                    <select class="autoeq-slope-select"${filterTypeHasSlope(currentType) ? '' : ' style="display: none"'}>
+                        <option value="12"${currentSlope === 12 ? ' selected' : ''}>12 dB</option>
+                        <option value="24"${currentSlope === 24 ? ' selected' : ''}>24 dB</option>
+                        <option value="48"${currentSlope === 48 ? ' selected' : ''}>48 dB</option>
                     </select>
                     <select class="autoeq-channel-select">
                         <option value="stereo"${currentChannel === 'stereo' ? ' selected' : ''}>ST</option>
                         <option value="mid"${currentChannel === 'mid' ? ' selected' : ''}>M</option>
                         <option value="side"${currentChannel === 'side' ? ' selected' : ''}>S</option>
+                        <option value="left"${currentChannel === 'left' ? ' selected' : ''}>L</option>
+                        <option value="right"${currentChannel === 'right' ? ' selected' : ''}>R</option>
                     </select>
                     <div class="autoeq-band-param">
                         <span class="autoeq-band-param-label">Freq</span>
@@ -3185,10 +3553,21 @@ export async function initializeSettings(scrobbler, player, api, ui) {
             });
 
             const typeSelect = control.querySelector('.autoeq-type-select');
+            const slopeSelect = control.querySelector('.autoeq-slope-select');
             typeSelect.addEventListener('change', () => {
                 const bands = getActiveBands();
                 if (!bands || !bands[i]) return;
                 bands[i].type = typeSelect.value;
+                slopeSelect.style.display = filterTypeHasSlope(typeSelect.value) ? '' : 'none';
+                computeCorrectedCurve();
+                applyBandsToAudio(bands);
+                scheduleDrawAutoEQGraph();
+            });
+
+            slopeSelect.addEventListener('change', () => {
+                const bands = getActiveBands();
+                if (!bands || !bands[i]) return;
+                bands[i].slope = parseInt(slopeSelect.value, 10);
                 computeCorrectedCurve();
                 applyBandsToAudio(bands);
                 scheduleDrawAutoEQGraph();
@@ -3419,11 +3798,12 @@ export async function initializeSettings(scrobbler, player, api, ui) {
 
         // Draw each band as a filled blob
         bands.forEach((band, bi) => {
-            if (!band.enabled || Math.abs(band.gain) < 0.1) return;
+            if (!band.enabled || band.type === 'allpass') return;
+            if (filterTypeHasGain(band.type || 'peaking') && Math.abs(band.gain) < 0.1) return;
             const color = BAND_PREVIEW_COLORS[bi % BAND_PREVIEW_COLORS.length];
             const pts = [];
             for (let f = 20; f <= 20000; f *= 1.04) {
-                const resp = calculateBiquadResponse(f, band, sr);
+                const resp = audioContextManager._biquadResponseDb(f, band, sr);
                 pts.push({
                     x: freqToX(f, pw),
                     y: mid - (Math.max(-dbRange, Math.min(dbRange, resp)) / dbRange) * mid * 0.9,
@@ -3458,7 +3838,7 @@ export async function initializeSettings(scrobbler, player, api, ui) {
         for (let f = 20; f <= 20000; f *= 1.04) {
             let total = 0;
             for (const b of bands) {
-                if (b.enabled) total += calculateBiquadResponse(f, b, sr);
+                if (b.enabled) total += audioContextManager._biquadResponseDb(f, b, sr);
             }
             const x = freqToX(f, pw);
             const y = mid - (Math.max(-dbRange, Math.min(dbRange, total)) / dbRange) * mid * 0.9;
@@ -4011,15 +4391,7 @@ export async function initializeSettings(scrobbler, player, api, ui) {
                 return;
             }
             // Build EqualizerAPO / Peace format
-            let lines = [`Preamp: ${currentPreamp} dB`];
-            autoeqCurrentBands.forEach((band, i) => {
-                if (!band.enabled) return;
-                const type = band.type === 'peaking' ? 'PK' : band.type === 'lowshelf' ? 'LSC' : 'HSC';
-                lines.push(
-                    `Filter ${i + 1}: ON ${type} Fc ${Math.round(band.freq)} Hz Gain ${band.gain.toFixed(1)} dB Q ${band.q.toFixed(2)}`
-                );
-            });
-            const exportText = lines.join('\n');
+            const exportText = formatEqualizerAPO(currentPreamp, autoeqCurrentBands);
             const blob = new Blob([exportText], { type: 'text/plain' });
             const url = URL.createObjectURL(blob);
             const a = document.createElement('a');
@@ -4595,15 +4967,7 @@ export async function initializeSettings(scrobbler, player, api, ui) {
     if (parametricExportBtn) {
         parametricExportBtn.addEventListener('click', () => {
             if (!parametricBands || parametricBands.length === 0) return;
-            const preamp = equalizerSettings.getPreamp();
-            const lines = [`Preamp: ${preamp.toFixed(1)} dB`];
-            parametricBands.forEach((band, i) => {
-                const ft = band.type === 'lowshelf' ? 'LS' : band.type === 'highshelf' ? 'HS' : 'PK';
-                lines.push(
-                    `Filter ${i + 1}: ON ${ft} Fc ${Math.round(band.freq)} Hz Gain ${band.gain.toFixed(1)} dB Q ${band.q.toFixed(2)}`
-                );
-            });
-            const text = lines.join('\n');
+            const text = formatEqualizerAPO(equalizerSettings.getPreamp(), parametricBands);
             const blob = new Blob([text], { type: 'text/plain' });
             const url = URL.createObjectURL(blob);
             const a = document.createElement('a');
@@ -4622,39 +4986,7 @@ export async function initializeSettings(scrobbler, player, api, ui) {
             const reader = new FileReader();
             reader.onload = (event) => {
                 try {
-                    const text = event.target.result;
-                    const bands = [];
-                    let preamp = 0;
-                    const lines = text.split('\n');
-                    for (const line of lines) {
-                        const preampMatch = line.match(/Preamp:\s*([-\d.]+)\s*dB/i);
-                        if (preampMatch) {
-                            preamp = parseFloat(preampMatch[1]);
-                            continue;
-                        }
-                        const filterMatch = line.match(
-                            /Filter\s+\d+:\s*ON\s+(\w+)\s+Fc\s+([\d.]+)\s*Hz\s+Gain\s+([-\d.]+)\s*dB\s+Q\s+([\d.]+)/i
-                        );
-                        if (filterMatch) {
-                            const typeMap = {
-                                PK: 'peaking',
-                                LS: 'lowshelf',
-                                LSC: 'lowshelf',
-                                LSF: 'lowshelf',
-                                HS: 'highshelf',
-                                HSC: 'highshelf',
-                                HSF: 'highshelf',
-                            };
-                            bands.push({
-                                id: bands.length,
-                                type: typeMap[filterMatch[1].toUpperCase()] || 'peaking',
-                                freq: parseFloat(filterMatch[2]),
-                                gain: parseFloat(filterMatch[3]),
-                                q: parseFloat(filterMatch[4]),
-                                enabled: true,
-                            });
-                        }
-                    }
+                    const { preamp, bands } = parseEqualizerAPO(event.target.result);
                     if (bands.length === 0) return;
                     parametricBands = bands;
                     applyBandsToAudio(parametricBands);
@@ -5100,7 +5432,7 @@ export async function initializeSettings(scrobbler, player, api, ui) {
                     for (let f = 20; f <= 20000; f *= 1.1) {
                         let total = 0;
                         bands.forEach((b) => {
-                            if (b.enabled) total += calculateBiquadResponse(f, b);
+                            if (b.enabled) total += audioContextManager._biquadResponseDb(f, b);
                         });
                         if (total > maxGain) maxGain = total;
                     }
@@ -5210,7 +5542,7 @@ export async function initializeSettings(scrobbler, player, api, ui) {
                 for (let f = 20; f <= 20000; f *= 1.1) {
                     let total = 0;
                     bands.forEach((b) => {
-                        if (b.enabled) total += calculateBiquadResponse(f, b, sampleRate);
+                        if (b.enabled) total += audioContextManager._biquadResponseDb(f, b, sampleRate);
                     });
                     if (total > maxGain) maxGain = total;
                 }
@@ -5605,6 +5937,292 @@ export async function initializeSettings(scrobbler, player, api, ui) {
         });
     }
 
+    // Follow device EQ profile swaps: the engine already holds the profile's bands,
+    // so adopt them as the bands of the profile's mode and redraw
+    window.addEventListener('eq-profile-changed', () => {
+        currentPreamp = equalizerSettings.getPreamp();
+        if (eqPreampSlider) eqPreampSlider.value = currentPreamp;
+        if (autoeqPreampValue) autoeqPreampValue.textContent = `${currentPreamp} dB`;
+        if (eqToggle) {
+            eqToggle.checked = equalizerSettings.isEnabled();
+            updateEQContainerVisibility(eqToggle.checked);
+        }
+
+        const savedMode = localStorage.getItem(EQ_MODE_KEY);
+        const mode = ['autoeq', 'parametric', 'speaker', 'legacy'].includes(savedMode) ? savedMode : currentMode;
+        if (mode !== 'legacy') {
+            currentMode = mode;
+            setActiveBands(audioContextManager._getCurrentBands());
+        }
+        setEQMode(mode);
+    });
+
+    // ========================================
+    // Dynamics (compressor / limiter) + gain-reduction meters
+    // ========================================
+    const dynamicsHeader = document.getElementById('eq-dynamics-toggle');
+    const dynamicsCollapse = document.getElementById('eq-dynamics-collapse');
+    const dynamicsContent = document.getElementById('eq-dynamics-content');
+    const dynamicsCompressorToggle = document.getElementById('dynamics-compressor-toggle');
+    const dynamicsLimiterToggle = document.getElementById('dynamics-limiter-toggle');
+    const dynamicsCompressorParams = document.getElementById('dynamics-compressor-params');
+    const dynamicsLimiterParams = document.getElementById('dynamics-limiter-params');
+    const dynamicsCompMeter = document.getElementById('dynamics-comp-meter');
+    const dynamicsCompMeterValue = document.getElementById('dynamics-comp-meter-value');
+    const dynamicsLimitMeter = document.getElementById('dynamics-limit-meter');
+    const dynamicsLimitMeterValue = document.getElementById('dynamics-limit-meter-value');
+
+    const DYNAMICS_SLIDERS = {
+        threshold: 'dynamics-threshold',
+        ratio: 'dynamics-ratio',
+        knee: 'dynamics-knee',
+        attack: 'dynamics-attack',
+        release: 'dynamics-release',
+        makeupGain: 'dynamics-makeup',
+        ceiling: 'dynamics-ceiling',
+        lookahead: 'dynamics-lookahead',
+        limiterRelease: 'dynamics-limiter-release',
+    };
+    // Gain reduction that fills a meter completely
+    const DYNAMICS_METER_RANGE_DB = 24;
+
+    const formatDynamicsValue = (slider) => {
+        const value = parseFloat(slider.value);
+        return slider.dataset.unit === ':1' ? `${value}:1` : `${value} ${slider.dataset.unit}`;
+    };
+
+    const syncDynamicsControls = () => {
+        const settings = audioContextManager.getDynamicsSettings();
+        if (dynamicsCompressorToggle) dynamicsCompressorToggle.checked = settings.compressorEnabled;
+        if (dynamicsLimiterToggle) dynamicsLimiterToggle.checked = settings.limiterEnabled;
+        if (dynamicsCompressorParams) dynamicsCompressorParams.style.display = settings.compressorEnabled ? '' : 'none';
+        if (dynamicsLimiterParams) dynamicsLimiterParams.style.display = settings.limiterEnabled ? '' : 'none';
+
+        for (const [key, id] of Object.entries(DYNAMICS_SLIDERS)) {
+            const slider = document.getElementById(id);
+            const label = document.getElementById(`${id}-value`);
+            if (!slider) continue;
+            slider.value = settings[key];
+            if (label) label.textContent = formatDynamicsValue(slider);
+        }
+    };
+
+    for (const [key, id] of Object.entries(DYNAMICS_SLIDERS)) {
+        const slider = document.getElementById(id);
+        const label = document.getElementById(`${id}-value`);
+        if (!slider) continue;
+        slider.addEventListener('input', () => {
+            if (label) label.textContent = formatDynamicsValue(slider);
+            audioContextManager.setDynamicsSettings({ [key]: parseFloat(slider.value) });
+        });
+    }
+
+    if (dynamicsCompressorToggle) {
+        dynamicsCompressorToggle.addEventListener('change', (e) => {
+            audioContextManager.setDynamicsSettings({ compressorEnabled: e.target.checked });
+            syncDynamicsControls();
+        });
+    }
+
+    if (dynamicsLimiterToggle) {
+        dynamicsLimiterToggle.addEventListener('change', (e) => {
+            audioContextManager.setDynamicsSettings({ limiterEnabled: e.target.checked });
+            syncDynamicsControls();
+        });
+    }
+
+    if (dynamicsHeader) {
+        dynamicsHeader.addEventListener('click', () => {
+            if (dynamicsCollapse) dynamicsCollapse.classList.toggle('collapsed');
+            if (dynamicsContent)
+                dynamicsContent.style.display = dynamicsContent.style.display === 'none' ? 'flex' : 'none';
+        });
+    }
+
+    const setDynamicsMeter = (fill, valueEl, reductionDb) => {
+        const db = Math.max(0, reductionDb || 0);
+        if (fill) fill.style.width = `${Math.min(100, (db / DYNAMICS_METER_RANGE_DB) * 100)}%`;
+        if (valueEl) valueEl.textContent = `${db > 0.05 ? '-' : ''}${db.toFixed(1)} dB`;
+    };
+
+    // Meters only animate while the dynamics panel is on screen
+    let dynamicsMeterFrame = null;
+    let dynamicsMeterVisible = false;
+    const updateDynamicsMeters = () => {
+        dynamicsMeterFrame = null;
+        if (!dynamicsMeterVisible) return;
+        const { compressor, limiter } = audioContextManager.getGainReduction();
+        setDynamicsMeter(dynamicsCompMeter, dynamicsCompMeterValue, compressor);
+        setDynamicsMeter(dynamicsLimitMeter, dynamicsLimitMeterValue, limiter);
+        dynamicsMeterFrame = requestAnimationFrame(updateDynamicsMeters);
+    };
+
+    if (dynamicsContent) {
+        syncDynamicsControls();
+        const observer = new IntersectionObserver((entries) => {
+            dynamicsMeterVisible = entries.some((entry) => entry.isIntersecting);
+            if (dynamicsMeterVisible && dynamicsMeterFrame === null) {
+                dynamicsMeterFrame = requestAnimationFrame(updateDynamicsMeters);
+            }
+        });
+        observer.observe(dynamicsContent);
+        window.addEventListener('eq-profile-changed', syncDynamicsControls);
+    }
+
+    // ========================================
+    // Convolution (user impulse responses)
+    // ========================================
+    const convolutionHeader = document.getElementById('eq-convolution-toggle');
+    const convolutionCollapse = document.getElementById('eq-convolution-collapse');
+    const convolutionContent = document.getElementById('eq-convolution-content');
+    const convolutionToggle = document.getElementById('convolution-enabled-toggle');
+    const convolutionStatus = document.getElementById('convolution-status');
+    const convolutionIrSelect = document.getElementById('convolution-ir-select');
+    const convolutionUploadBtn = document.getElementById('convolution-ir-upload-btn');
+    const convolutionDeleteBtn = document.getElementById('convolution-ir-delete-btn');
+    const convolutionFileInput = document.getElementById('convolution-ir-file');
+    const convolutionMix = document.getElementById('convolution-mix');
+    const convolutionMixValue = document.getElementById('convolution-mix-value');
+    const convolutionGain = document.getElementById('convolution-gain');
+    const convolutionGainValue = document.getElementById('convolution-gain-value');
+    const convolutionNormalizeToggle = document.getElementById('convolution-normalize-toggle');
+
+    const IR_LAYOUT_LABELS = { mono: 'Mono', stereo: 'Stereo', 'true-stereo': 'True stereo' };
+    let impulseResponses = [];
+
+    const describeImpulseResponse = (ir) => {
+        const layout = IR_LAYOUT_LABELS[ir.layout] || `${ir.channels} ch`;
+        return `${layout}, ${ir.duration.toFixed(2)}s, ${ir.sampleRate / 1000} kHz`;
+    };
+
+    const updateConvolutionStatus = (message = null) => {
+        if (!convolutionStatus) return;
+        if (message) {
+            convolutionStatus.textContent = message;
+            return;
+        }
+        const settings = audioContextManager.getConvolutionSettings();
+        const status = audioContextManager.getConvolutionStatus();
+        const ir = impulseResponses.find((entry) => entry.id === settings.irId);
+        if (status.error && settings.enabled) {
+            convolutionStatus.textContent = `Could not load impulse response: ${status.error}`;
+        } else if (ir) {
+            convolutionStatus.textContent = describeImpulseResponse(ir);
+        } else {
+            convolutionStatus.textContent = 'Mono, stereo or 4-channel true-stereo WAV files';
+        }
+    };
+
+    const syncConvolutionControls = () => {
+        const settings = audioContextManager.getConvolutionSettings();
+        if (convolutionToggle) convolutionToggle.checked = settings.enabled;
+        if (convolutionNormalizeToggle) convolutionNormalizeToggle.checked = settings.normalize;
+        if (convolutionMix) convolutionMix.value = Math.round(settings.mix * 100);
+        if (convolutionMixValue) convolutionMixValue.textContent = `${Math.round(settings.mix * 100)}% wet`;
+        if (convolutionGain) convolutionGain.value = settings.gain;
+        if (convolutionGainValue) convolutionGainValue.textContent = `${settings.gain} dB`;
+        if (convolutionIrSelect) convolutionIrSelect.value = settings.irId || '';
+        if (convolutionDeleteBtn) convolutionDeleteBtn.style.display = settings.irId ? '' : 'none';
+        updateConvolutionStatus();
+    };
+
+    const renderImpulseResponses = async () => {
+        if (!convolutionIrSelect) return;
+        try {
+            impulseResponses = await db.getImpulseResponses();
+        } catch (e) {
+            console.warn('Failed to load impulse responses:', e);
+            impulseResponses = [];
+        }
+        convolutionIrSelect.innerHTML = '<option value="">None</option>';
+        for (const ir of impulseResponses) {
+            const option = document.createElement('option');
+            option.value = ir.id;
+            option.textContent = ir.name;
+            convolutionIrSelect.appendChild(option);
+        }
+        syncConvolutionControls();
+    };
+
+    if (convolutionToggle) {
+        convolutionToggle.addEventListener('change', async (e) => {
+            await audioContextManager.setConvolutionSettings({ enabled: e.target.checked });
+            syncConvolutionControls();
+        });
+    }
+
+    if (convolutionIrSelect) {
+        convolutionIrSelect.addEventListener('change', async () => {
+            const irId = convolutionIrSelect.value || null;
+            await audioContextManager.setConvolutionSettings({ irId, enabled: irId !== null });
+            syncConvolutionControls();
+        });
+    }
+
+    if (convolutionUploadBtn && convolutionFileInput) {
+        convolutionUploadBtn.addEventListener('click', () => convolutionFileInput.click());
+        convolutionFileInput.addEventListener('change', async () => {
+            const file = convolutionFileInput.files?.[0];
+            convolutionFileInput.value = '';
+            if (!file) return;
+            try {
+                const entry = await importImpulseResponse(file);
+                await audioContextManager.setConvolutionSettings({ irId: entry.id, enabled: true });
+                await renderImpulseResponses();
+            } catch (e) {
+                console.warn('Failed to import impulse response:', e);
+                updateConvolutionStatus(`Import failed: ${e.message}`);
+            }
+        });
+    }
+
+    if (convolutionDeleteBtn) {
+        convolutionDeleteBtn.addEventListener('click', async () => {
+            const irId = audioContextManager.getConvolutionSettings().irId;
+            const ir = impulseResponses.find((entry) => entry.id === irId);
+            if (!irId || !confirm(`Delete impulse response "${ir?.name || irId}"?`)) return;
+            await db.deleteImpulseResponse(irId);
+            await audioContextManager.setConvolutionSettings({ irId: null, enabled: false });
+            await renderImpulseResponses();
+        });
+    }
+
+    if (convolutionMix) {
+        convolutionMix.addEventListener('input', () => {
+            const mix = parseInt(convolutionMix.value, 10) / 100;
+            if (convolutionMixValue) convolutionMixValue.textContent = `${convolutionMix.value}% wet`;
+            void audioContextManager.setConvolutionSettings({ mix });
+        });
+    }
+
+    if (convolutionGain) {
+        convolutionGain.addEventListener('input', () => {
+            const gain = parseFloat(convolutionGain.value);
+            if (convolutionGainValue) convolutionGainValue.textContent = `${gain} dB`;
+            void audioContextManager.setConvolutionSettings({ gain });
+        });
+    }
+
+    if (convolutionNormalizeToggle) {
+        convolutionNormalizeToggle.addEventListener('change', (e) => {
+            void audioContextManager.setConvolutionSettings({ normalize: e.target.checked });
+        });
+    }
+
+    if (convolutionHeader) {
+        convolutionHeader.addEventListener('click', () => {
+            if (convolutionCollapse) convolutionCollapse.classList.toggle('collapsed');
+            if (convolutionContent)
+                convolutionContent.style.display = convolutionContent.style.display === 'none' ? 'flex' : 'none';
+        });
+    }
+
+    if (convolutionContent) {
+        void renderImpulseResponses();
+        window.addEventListener('convolution-changed', () => updateConvolutionStatus());
+        window.addEventListener('eq-profile-changed', syncConvolutionControls);
+    }
+
     // Initial render of saved profiles
     renderSavedProfiles();
 
@@ -5801,6 +6419,34 @@ export async function initializeSettings(scrobbler, player, api, ui) {
         });
     }
 
+    // Embed Synced Lyrics Toggle
+    const embedSyncedLyricsToggle = document.getElementById('embed-synced-lyrics-toggle');
+    if (embedSyncedLyricsToggle) {
+        embedSyncedLyricsToggle.checked = lyricsSettings.shouldEmbedSyncedLyrics();
+        embedSyncedLyricsToggle.addEventListener('change', (e) => {
+            lyricsSettings.setEmbedSyncedLyrics(e.target.checked);
+        });
+    }
+
+    // Embed MusicBrainz IDs Toggle
+    const embedMbidsToggle = document.getElementById('embed-musicbrainz-ids-toggle');
+    if (embedMbidsToggle) {
+        embedMbidsToggle.checked = musicBrainzSettings.shouldEmbedIds();
+        embedMbidsToggle.addEventListener('change', (e) => {
+            musicBrainzSettings.setEmbedIds(e.target.checked);
+        });
+    }
+
+    // Local Catalog Matching Toggle
+    const localCatalogMatchingToggle = document.getElementById('local-catalog-matching-toggle');
+    if (localCatalogMatchingToggle) {
+        localCatalogMatchingToggle.checked = localMatchSettings.isEnabled();
+        localCatalogMatchingToggle.addEventListener('change', (e) => {
+            localMatchSettings.setEnabled(e.target.checked);
+            if (e.target.checked) window.dispatchEvent(new CustomEvent('local-catalog-matching-enabled'));
+        });
+    }
+
     // Romaji Lyrics Toggle
     const romajiLyricsToggle = document.getElementById('romaji-lyrics-toggle');
     if (romajiLyricsToggle) {
@@ -6609,6 +7255,42 @@ export async function initializeSettings(scrobbler, player, api, ui) {
         }
     });
 
+    // Full Backup
+    document.getElementById('export-backup-btn')?.addEventListener('click', async (e) => {
+        const button = e.currentTarget;
+        button.disabled = true;
+        try {
+            await exportBackup();
+        } catch (err) {
+            console.error('Backup failed:', err);
+            alert('Failed to create the backup: ' + err.message);
+        } finally {
+            button.disabled = false;
+        }
+    });
+
+    const restoreBackupInput = document.getElementById('restore-backup-input');
+    document.getElementById('restore-backup-btn')?.addEventListener('click', () => {
+        restoreBackupInput.click();
+    });
+
+    restoreBackupInput?.addEventListener('change', async (e) => {
+        const file = e.target.files[0];
+        e.target.value = '';
+        if (!file) return;
+
+        try {
+            const backup = await readBackupFile(file);
+            if (await openBackupRestore(backup)) {
+                alert('Backup restored successfully!');
+                window.location.reload();
+            }
+        } catch (err) {
+            console.error('Restore failed:', err);
+            alert('Failed to read the backup: ' + err.message);
+        }
+    });
+
     // Backup & Restore
     document.getElementById('export-library-btn')?.addEventListener('click', async () => {
         const data = await db.exportData();
@@ -6789,6 +7471,72 @@ export async function initializeSettings(scrobbler, player, api, ui) {
         });
     }
 
+    // WebDAV Sync
+    const webDavSyncBtn = document.getElementById('webdav-sync-btn');
+    const webDavSyncModal = document.getElementById('webdav-sync-modal');
+    if (webDavSyncBtn && webDavSyncModal) {
+        const urlInput = document.getElementById('webdav-sync-url');
+        const usernameInput = document.getElementById('webdav-sync-username');
+        const passwordInput = document.getElementById('webdav-sync-password');
+        const errorText = document.getElementById('webdav-sync-error');
+        const saveBtn = document.getElementById('webdav-sync-save');
+        const disconnectBtn = document.getElementById('webdav-sync-disconnect');
+
+        const config = syncBackendSettings.getWebDavConfig();
+        if (config) document.getElementById('webdav-sync-status').textContent = `Syncing to ${config.url}`;
+
+        webDavSyncBtn.addEventListener('click', () => {
+            const current = syncBackendSettings.getWebDavConfig();
+            urlInput.value = current?.url || '';
+            usernameInput.value = current?.username || '';
+            passwordInput.value = current?.password || '';
+            errorText.textContent = '';
+            disconnectBtn.style.display = current ? '' : 'none';
+            webDavSyncModal.classList.add('active');
+        });
+
+        const closeWebDavSyncModal = () => {
+            webDavSyncModal.classList.remove('active');
+            passwordInput.value = '';
+        };
+
+        document.getElementById('webdav-sync-cancel').addEventListener('click', closeWebDavSyncModal);
+        webDavSyncModal.querySelector('.modal-overlay').addEventListener('click', closeWebDavSyncModal);
+
+        saveBtn.addEventListener('click', async () => {
+            const url = urlInput.value.trim();
+            if (!url) {
+                errorText.textContent = 'Enter the URL of the folder to sync to.';
+                return;
+            }
+            const newConfig = { url, username: usernameInput.value.trim(), password: passwordInput.value };
+
+            // Check the folder can be read before switching, so a typo doesn't stop syncing altogether
+            saveBtn.disabled = true;
+            errorText.textContent = '';
+            try {
+                await createWebDavBackend(newConfig).load();
+            } catch (error) {
+                console.error('WebDAV connection failed:', error);
+                errorText.textContent = `Could not connect: ${error.message}`;
+                return;
+            } finally {
+                saveBtn.disabled = false;
+            }
+
+            syncBackendSettings.setWebDavConfig(newConfig);
+            alert('WebDAV sync saved. Reloading...');
+            window.location.reload();
+        });
+
+        disconnectBtn.addEventListener('click', () => {
+            if (!confirm('Stop syncing with WebDAV? The files on the server are kept.')) return;
+            syncBackendSettings.setWebDavConfig(null);
+            alert('WebDAV sync turned off. Reloading...');
+            window.location.reload();
+        });
+    }
+
     // PWA Auto-Update Toggle
     const pwaAutoUpdateToggle = document.getElementById('pwa-auto-update-toggle');
     if (pwaAutoUpdateToggle) {
@@ -6807,6 +7555,137 @@ export async function initializeSettings(scrobbler, player, api, ui) {
         });
     }
 
+    // Cloud Library Encryption
+    const cloudEncryptionBtn = document.getElementById('cloud-encryption-btn');
+    if (cloudEncryptionBtn) {
+        const statusText = document.getElementById('cloud-encryption-status');
+        const defaultStatus = statusText.textContent;
+
+        const renderCloudEncryptionUI = () => {
+            const syncUser = syncManager.getSyncUser();
+            cloudEncryptionBtn.disabled = !syncUser;
+            if (!syncUser) {
+                statusText.textContent = defaultStatus;
+                cloudEncryptionBtn.textContent = 'Turn On';
+            } else if (syncManager.isEncryptionLocked()) {
+                statusText.textContent = 'Your cloud library is encrypted. Enter the passphrase to sync this device.';
+                cloudEncryptionBtn.textContent = 'Unlock';
+            } else if (syncManager.isEncrypted()) {
+                statusText.textContent = 'Your library, history and playlists are encrypted before they are synced.';
+                cloudEncryptionBtn.textContent = 'Turn Off';
+            } else {
+                statusText.textContent = defaultStatus;
+                cloudEncryptionBtn.textContent = 'Turn On';
+            }
+        };
+
+        const updateCloudEncryptionUI = async () => {
+            if (syncManager.getSyncUser()) await syncManager.getUserData();
+            renderCloudEncryptionUI();
+        };
+
+        cloudEncryptionBtn.addEventListener('click', async () => {
+            if (syncManager.isEncryptionLocked()) {
+                await openCloudEncryption('unlock');
+            } else if (syncManager.isEncrypted()) {
+                if (!confirm('Store your cloud library unencrypted again?')) return;
+                cloudEncryptionBtn.disabled = true;
+                try {
+                    await syncManager.disableEncryption();
+                } catch (error) {
+                    console.error('Failed to turn off encryption:', error);
+                    alert('Failed to turn off encryption: ' + error.message);
+                }
+            } else {
+                await openCloudEncryption('enable');
+            }
+            await updateCloudEncryptionUI();
+        });
+
+        window.addEventListener('cloud-encryption-locked', renderCloudEncryptionUI);
+        authManager.onAuthStateChanged(() => updateCloudEncryptionUI());
+        updateCloudEncryptionUI();
+    }
+
+    // Settings Sync
+    const settingsSyncToggle = document.getElementById('settings-sync-toggle');
+    if (settingsSyncToggle) {
+        const statusItem = document.getElementById('settings-sync-status-item');
+        const statusText = document.getElementById('settings-sync-status');
+        const categoriesContainer = document.getElementById('settings-sync-categories');
+
+        const updateSettingsSyncUI = (message) => {
+            const enabled = settingsSyncSettings.isEnabled();
+            statusItem.style.display = enabled ? '' : 'none';
+            categoriesContainer.style.display = enabled ? '' : 'none';
+            if (message) {
+                statusText.textContent = message;
+                return;
+            }
+            const { syncedAt } = settingsSyncSettings.getState();
+            if (!syncManager.getSyncUser()) {
+                statusText.textContent = 'Sign in to sync settings';
+            } else if (!syncManager.supports('settings')) {
+                statusText.textContent = 'Settings can only be synced to a WebDAV folder for now';
+            } else {
+                statusText.textContent = syncedAt ? new Date(syncedAt).toLocaleString() : 'Never';
+            }
+        };
+
+        const runSettingsSync = async () => {
+            updateSettingsSyncUI('Syncing...');
+            try {
+                const result = await syncSettings();
+                if (result?.status === 'outdated') {
+                    updateSettingsSyncUI('Settings were synced by a newer version of the app. Update to sync again.');
+                    return;
+                }
+                updateSettingsSyncUI();
+            } catch (error) {
+                console.error('Settings sync failed:', error);
+                updateSettingsSyncUI('Sync failed, please try again');
+            }
+        };
+
+        SYNC_CATEGORIES.forEach((category) => {
+            const item = document.createElement('div');
+            item.className = 'setting-item';
+            const info = document.createElement('div');
+            info.className = 'info';
+            const label = document.createElement('span');
+            label.className = 'label';
+            label.textContent = category.label;
+            info.appendChild(label);
+
+            const toggle = document.createElement('label');
+            toggle.className = 'toggle-switch';
+            const input = document.createElement('input');
+            input.type = 'checkbox';
+            input.checked = settingsSyncSettings.isCategoryEnabled(category.id);
+            input.addEventListener('change', (e) => {
+                settingsSyncSettings.setCategoryEnabled(category.id, e.target.checked);
+                if (e.target.checked) runSettingsSync();
+            });
+            const slider = document.createElement('span');
+            slider.className = 'slider';
+            toggle.append(input, slider);
+
+            item.append(info, toggle);
+            categoriesContainer.appendChild(item);
+        });
+
+        settingsSyncToggle.checked = settingsSyncSettings.isEnabled();
+        settingsSyncToggle.addEventListener('change', (e) => {
+            settingsSyncSettings.setEnabled(e.target.checked);
+            updateSettingsSyncUI();
+            if (e.target.checked) runSettingsSync();
+        });
+        document.getElementById('settings-sync-now-btn')?.addEventListener('click', runSettingsSync);
+        window.addEventListener('settings-synced', () => updateSettingsSyncUI());
+        authManager.onAuthStateChanged(() => updateSettingsSyncUI());
+        updateSettingsSyncUI();
+    }
+
     // Reset Local Data Button
     const resetLocalDataBtn = document.getElementById('reset-local-data-btn');
     if (resetLocalDataBtn) {
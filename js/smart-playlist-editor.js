@@ -0,0 +1,208 @@
+// js/smart-playlist-editor.js
+// Modal for creating and editing smart playlists. Rules are edited as rows of field, operator and value;
+// the number of matching tracks is previewed while editing.
+
+import { db } from './db.js';
+import { syncManager } from './accounts/pocketbase.js';
+import { showNotification } from './downloads.js';
+import { navigate } from './router.js';
+import { escapeHtml } from './utils.js';
+import {
+    QUALITY_LABELS,
+    SMART_PLAYLIST_FIELDS,
+    SMART_PLAYLIST_OPERATORS,
+    SMART_PLAYLIST_SORTS,
+    createSmartPlaylistContext,
+    evaluateSmartPlaylist,
+    getSmartPlaylistLibrary,
+} from './smart-playlists.js';
+
+const DEFAULT_RULE = { field: 'liked', operator: 'inLast', value: 30 };
+
+let closeSmartPlaylistEditor = null;
+
+function createOptions(entries, selected) {
+    return entries
+        .map(
+            ([value, label]) =>
+                `<option value="${value}" ${value === selected ? 'selected' : ''}>${escapeHtml(label)}</option>`
+        )
+        .join('');
+}
+
+function createValueInputs(rule) {
+    const field = SMART_PLAYLIST_FIELDS[rule.field];
+    if (field.options) {
+        const value = field.options.includes(rule.value) ? rule.value : field.options[1];
+        return `<select data-rule-value>${createOptions(
+            field.options.map((option) => [option, QUALITY_LABELS[option] || option]),
+            value
+        )}</select>`;
+    }
+    if (rule.operator === 'between') {
+        const [from, to] = Array.isArray(rule.value) ? rule.value : ['', ''];
+        return `<input type="number" class="template-input" data-rule-value="from" placeholder="From" value="${from ?? ''}" />
+            <input type="number" class="template-input" data-rule-value="to" placeholder="To" value="${to ?? ''}" />`;
+    }
+    const isText = field.operators.includes('contains');
+    const value = rule.value ?? '';
+    return `<input type="${isText ? 'text' : 'number'}" class="template-input" data-rule-value
+        value="${escapeHtml(String(value))}" ${field.unit ? 'min="0"' : 'step="any"'} />${
+            field.unit ? `<span class="smart-playlist-rule-unit">${field.unit}</span>` : ''
+        }`;
+}
+
+function createRuleRow(rule) {
+    const field = SMART_PLAYLIST_FIELDS[rule.field];
+    const row = document.createElement('div');
+    row.className = 'smart-playlist-rule';
+    row.innerHTML = `
+        <select data-rule-field>${createOptions(
+            Object.entries(SMART_PLAYLIST_FIELDS).map(([key, { label }]) => [key, label]),
+            rule.field
+        )}</select>
+        <select data-rule-operator>${createOptions(
+            field.operators.map((operator) => [operator, SMART_PLAYLIST_OPERATORS[operator]]),
+            rule.operator
+        )}</select>
+        ${createValueInputs(rule)}
+        <button type="button" class="btn-icon smart-playlist-remove-rule" title="Remove rule">&times;</button>
+    `;
+    return row;
+}
+
+function readRule(row) {
+    const field = row.querySelector('[data-rule-field]').value;
+    const operator = row.querySelector('[data-rule-operator]').value;
+    if (operator === 'between') {
+        const read = (name) => {
+            const value = row.querySelector(`[data-rule-value="${name}"]`)?.value;
+            return value === '' || value === undefined ? null : Number(value);
+        };
+        return { field, operator, value: [read('from'), read('to')] };
+    }
+    const input = row.querySelector('[data-rule-value]');
+    const isText = SMART_PLAYLIST_FIELDS[field].operators.includes('contains');
+    const isOption = !!SMART_PLAYLIST_FIELDS[field].options;
+    return { field, operator, value: isText || isOption ? input.value : Number(input.value) || 0 };
+}
+
+/**
+ * Open the editor for a new smart playlist or an existing one
+ * @param {import('./smart-playlists.js').SmartPlaylist|null} [playlist]
+ */
+export async function openSmartPlaylistEditor(playlist = null) {
+    const modal = document.getElementById('smart-playlist-modal');
+    if (!modal) return;
+
+    closeSmartPlaylistEditor?.();
+
+    const rulesContainer = modal.querySelector('#smart-playlist-rules');
+    const nameInput = modal.querySelector('#smart-playlist-name-input');
+    const descriptionInput = modal.querySelector('#smart-playlist-description-input');
+    const matchSelect = modal.querySelector('#smart-playlist-match-select');
+    const sortSelect = modal.querySelector('#smart-playlist-sort-select');
+    const limitInput = modal.querySelector('#smart-playlist-limit-input');
+    const preview = modal.querySelector('#smart-playlist-preview');
+
+    modal.querySelector('#smart-playlist-modal-title').textContent = playlist
+        ? 'Edit Smart Playlist'
+        : 'New Smart Playlist';
+    nameInput.value = playlist?.name || '';
+    descriptionInput.value = playlist?.description || '';
+    matchSelect.value = playlist?.match === 'any' ? 'any' : 'all';
+    sortSelect.innerHTML = `<option value="">None</option>${createOptions(
+        Object.entries(SMART_PLAYLIST_SORTS),
+        playlist?.sort || ''
+    )}`;
+    limitInput.value = playlist?.limit || '';
+    rulesContainer.innerHTML = '';
+    (playlist ? playlist.rules : [DEFAULT_RULE]).forEach((rule) => rulesContainer.appendChild(createRuleRow(rule)));
+
+    // Loaded once per opening; rules are cheap to evaluate so the preview updates on every change
+    const library = await getSmartPlaylistLibrary();
+    const context = createSmartPlaylistContext(library);
+
+    const readForm = () => ({
+        name: nameInput.value.trim(),
+        description: descriptionInput.value.trim(),
+        match: matchSelect.value,
+        rules: [...rulesContainer.querySelectorAll('.smart-playlist-rule')].map(readRule),
+        sort: sortSelect.value || null,
+        limit: parseInt(limitInput.value, 10) > 0 ? parseInt(limitInput.value, 10) : null,
+    });
+
+    const updatePreview = () => {
+        const count = evaluateSmartPlaylist(readForm(), library.tracks, context).length;
+        preview.textContent = `${count} matching track${count === 1 ? '' : 's'}`;
+    };
+
+    const close = () => {
+        modal.classList.remove('active');
+        modal.removeEventListener('click', handleClick);
+        modal.removeEventListener('change', handleChange);
+        modal.removeEventListener('input', updatePreview);
+        closeSmartPlaylistEditor = null;
+    };
+
+    const save = async () => {
+        const values = readForm();
+        if (!values.name) {
+            showNotification('Please enter a playlist name.');
+            nameInput.focus();
+            return;
+        }
+
+        const saved = playlist
+            ? await db.updateSmartPlaylist({ ...playlist, ...values })
+            : await db.createSmartPlaylist(values);
+        close();
+        await syncManager.syncSmartPlaylist(saved, playlist ? 'update' : 'create');
+        if (!playlist) navigate(`/smartplaylist/${saved.id}`);
+    };
+
+    const handleChange = (e) => {
+        const row = e.target.closest('.smart-playlist-rule');
+        // Changing the field or operator changes which inputs the row needs
+        if (row && (e.target.matches('[data-rule-field]') || e.target.matches('[data-rule-operator]'))) {
+            const rule = readRule(row);
+            const field = SMART_PLAYLIST_FIELDS[rule.field];
+            if (!field.operators.includes(rule.operator)) rule.operator = field.operators[0];
+            if (e.target.matches('[data-rule-field]')) rule.value = field.operators.includes('contains') ? '' : 0;
+            row.replaceWith(createRuleRow(rule));
+        }
+        updatePreview();
+    };
+
+    const handleClick = (e) => {
+        if (e.target.classList.contains('modal-overlay') || e.target.id === 'smart-playlist-cancel') {
+            close();
+        } else if (e.target.id === 'smart-playlist-add-rule') {
+            rulesContainer.appendChild(createRuleRow(DEFAULT_RULE));
+            updatePreview();
+        } else if (e.target.closest('.smart-playlist-remove-rule')) {
+            e.target.closest('.smart-playlist-rule').remove();
+            updatePreview();
+        } else if (e.target.id === 'smart-playlist-save') {
+            save().catch((error) => {
+                console.error('Failed to save smart playlist:', error);
+                showNotification('Failed to save smart playlist.');
+            });
+        }
+    };
+
+    closeSmartPlaylistEditor = close;
+    modal.addEventListener('click', handleClick);
+    modal.addEventListener('change', handleChange);
+    modal.addEventListener('input', updatePreview);
+    updatePreview();
+    modal.classList.add('active');
+    nameInput.focus();
+}
+
+export async function deleteSmartPlaylist(id) {
+    if (!confirm('Are you sure you want to delete this smart playlist?')) return false;
+    await db.deleteSmartPlaylist(id);
+    await syncManager.syncSmartPlaylist({ id }, 'delete');
+    return true;
+}
@@ -0,0 +1,252 @@
+// js/smart-playlists.js
+// Smart playlists are saved rules instead of saved tracks. They are evaluated against every track the
+// library knows about (liked tracks, history and user playlists) together with the play counts and
+// artist affinity recorded by the listening tracker, so their contents follow the user's listening.
+// Only the rules are stored and synced; tracks are computed whenever a playlist is opened or changes.
+
+import { db } from './db.js';
+import { listeningTracker } from './listening-tracker.js';
+import { QUALITY_PRIORITY, deriveTrackQuality } from './utils.js';
+
+const DAY_MS = 24 * 60 * 60 * 1000;
+
+export const SMART_PLAYLIST_OPERATORS = {
+    gt: 'more than',
+    lt: 'less than',
+    inLast: 'in the last',
+    notInLast: 'not in the last',
+    is: 'is',
+    isNot: 'is not',
+    between: 'between',
+    contains: 'contains',
+    notContains: 'does not contain',
+};
+
+/** Fields rules can test, with the operators each one supports */
+export const SMART_PLAYLIST_FIELDS = {
+    liked: { label: 'Liked', operators: ['inLast', 'notInLast'], unit: 'days' },
+    lastPlayed: { label: 'Last played', operators: ['inLast', 'notInLast'], unit: 'days' },
+    playCount: { label: 'Play count', operators: ['gt', 'lt'] },
+    artistAffinity: { label: 'Artist affinity', operators: ['gt', 'lt'] },
+    quality: { label: 'Quality', operators: ['is', 'isNot'], options: QUALITY_PRIORITY },
+    year: { label: 'Year', operators: ['between'] },
+    artist: { label: 'Artist', operators: ['contains', 'notContains'] },
+    album: { label: 'Album', operators: ['contains', 'notContains'] },
+    title: { label: 'Title', operators: ['contains', 'notContains'] },
+};
+
+export const QUALITY_LABELS = {
+    DOLBY_ATMOS: 'Dolby Atmos',
+    HI_RES_LOSSLESS: 'Hi-Res Lossless',
+    LOSSLESS: 'Lossless',
+    HIGH: 'High',
+    LOW: 'Low',
+};
+
+export const SMART_PLAYLIST_SORTS = {
+    lastPlayed: 'Recently played',
+    playCount: 'Most played',
+    liked: 'Recently liked',
+    artistAffinity: 'Favorite artists first',
+    year: 'Newest first',
+    title: 'Title',
+};
+
+/**
+ * @typedef {object} SmartPlaylistRule
+ * @property {keyof SMART_PLAYLIST_FIELDS} field
+ * @property {keyof SMART_PLAYLIST_OPERATORS} operator
+ * @property {string|number|number[]} value - Days, a count, a quality, `[from, to]` years or text
+ */
+
+/**
+ * @typedef {object} SmartPlaylist
+ * @property {string} id
+ * @property {string} name
+ * @property {string} description
+ * @property {'all'|'any'} match - Whether tracks have to match all rules or any of them
+ * @property {SmartPlaylistRule[]} rules
+ * @property {keyof SMART_PLAYLIST_SORTS} sort
+ * @property {number|null} limit
+ * @property {number} createdAt
+ * @property {number} updatedAt
+ */
+
+function getReleaseYear(track) {
+    const releaseDate = track.album?.releaseDate || track.streamStartDate;
+    if (!releaseDate) return null;
+    const year = new Date(releaseDate).getFullYear();
+    return Number.isNaN(year) ? null : year;
+}
+
+function getArtists(track) {
+    const artists = track.artists?.length ? track.artists : track.artist ? [track.artist] : [];
+    return artists.filter((artist) => artist && (artist.id || artist.name));
+}
+
+/**
+ * Build what rules are evaluated against
+ * @param {{favorites: object[], history: object[]}} library
+ * @param {{getTrackSignal: Function, getArtistAffinity: Function}} [tracker]
+ * @param {number} [now]
+ */
+export function createSmartPlaylistContext(
+    { favorites = [], history = [] },
+    tracker = listeningTracker,
+    now = Date.now()
+) {
+    const likedAt = new Map(favorites.map((track) => [String(track.id), track.addedAt || 0]));
+    const playedAt = new Map();
+    for (const entry of history) {
+        const id = String(entry.id);
+        playedAt.set(id, Math.max(playedAt.get(id) || 0, entry.timestamp || 0));
+    }
+
+    return {
+        now,
+        likedAt,
+        playedAt,
+        getTrackSignal: (id) => tracker.getTrackSignal(id),
+        getArtistAffinity: (id) => tracker.getArtistAffinity(id),
+    };
+}
+
+/** The values of every rule field for one track */
+export function getTrackFacts(track, context) {
+    const id = String(track.id);
+    const signal = context.getTrackSignal(track.id);
+    const lastPlayed = Math.max(signal?.lastPlayed || 0, context.playedAt.get(id) || 0) || null;
+    const artists = getArtists(track);
+    const affinities = artists.filter((artist) => artist.id).map((artist) => context.getArtistAffinity(artist.id));
+
+    return {
+        liked: context.likedAt.has(id) ? context.likedAt.get(id) : null,
+        lastPlayed,
+        // Tracks played before the tracker existed are still known to have been played once
+        playCount: signal?.playCount || (lastPlayed ? 1 : 0),
+        artistAffinity: affinities.length ? Math.max(...affinities) : 0,
+        quality: deriveTrackQuality(track),
+        year: getReleaseYear(track),
+        artist: artists.map((artist) => artist.name || '').join(', '),
+        album: track.album?.title || '',
+        title: track.title || '',
+    };
+}
+
+/**
+ * Whether a track's facts satisfy a rule
+ * @param {SmartPlaylistRule} rule
+ * @param {ReturnType<typeof getTrackFacts>} facts
+ * @param {number} now
+ */
+export function matchesRule(rule, facts, now) {
+    const value = facts[rule.field];
+    switch (rule.operator) {
+        case 'gt':
+            return value !== null && value > Number(rule.value);
+        case 'lt':
+            return value !== null && value < Number(rule.value);
+        case 'inLast':
+            return value !== null && value >= now - Number(rule.value) * DAY_MS;
+        case 'notInLast':
+            return value === null || value < now - Number(rule.value) * DAY_MS;
+        case 'is':
+            return value === rule.value;
+        case 'isNot':
+            return value !== rule.value;
+        case 'between': {
+            const [from, to] = Array.isArray(rule.value) ? rule.value.map((v) => (v === '' ? null : v)) : [];
+            if (value === null) return false;
+            return (from == null || value >= Number(from)) && (to == null || value <= Number(to));
+        }
+        case 'contains':
+            return String(value).toLowerCase().includes(String(rule.value).trim().toLowerCase());
+        case 'notContains':
+            return !String(value).toLowerCase().includes(String(rule.value).trim().toLowerCase());
+        default:
+            return false;
+    }
+}
+
+function compareFacts(sort, a, b) {
+    if (sort === 'title') return a.title.localeCompare(b.title);
+    // Highest first, tracks without a value last
+    return (b[sort] ?? -Infinity) - (a[sort] ?? -Infinity);
+}
+
+/**
+ * The tracks of a smart playlist, sorted and limited
+ * @param {SmartPlaylist} playlist
+ * @param {object[]} tracks - Candidate tracks
+ * @param {ReturnType<typeof createSmartPlaylistContext>} context
+ */
+export function evaluateSmartPlaylist(playlist, tracks, context) {
+    const rules = (playlist.rules || []).filter((rule) => SMART_PLAYLIST_FIELDS[rule.field]);
+    const matchAll = playlist.match !== 'any';
+
+    const matched = [];
+    for (const track of tracks) {
+        const facts = getTrackFacts(track, context);
+        const test = (rule) => matchesRule(rule, facts, context.now);
+        if (rules.length === 0 || (matchAll ? rules.every(test) : rules.some(test))) {
+            matched.push({ track, facts });
+        }
+    }
+
+    if (playlist.sort && SMART_PLAYLIST_SORTS[playlist.sort]) {
+        matched.sort((a, b) => compareFacts(playlist.sort, a.facts, b.facts));
+    }
+    const result = matched.map(({ track }) => track);
+    return playlist.limit > 0 ? result.slice(0, playlist.limit) : result;
+}
+
+/** Human readable form of a rule, e.g. "Last played not in the last 90 days" */
+export function describeRule(rule) {
+    const field = SMART_PLAYLIST_FIELDS[rule.field];
+    if (!field) return '';
+    const operator = SMART_PLAYLIST_OPERATORS[rule.operator] || rule.operator;
+
+    let value = rule.value;
+    if (rule.operator === 'between') {
+        const [from, to] = Array.isArray(rule.value) ? rule.value : [];
+        value = `${from || '…'} and ${to || '…'}`;
+    } else if (field.options) {
+        value = QUALITY_LABELS[rule.value] || rule.value;
+    } else if (field.unit) {
+        value = `${rule.value} ${field.unit}`;
+    } else if (field.operators.includes('contains')) {
+        value = `"${rule.value}"`;
+    }
+    return `${field.label} ${operator} ${value}`;
+}
+
+export function describeSmartPlaylist(playlist) {
+    const rules = (playlist.rules || []).map(describeRule).filter(Boolean);
+    if (rules.length === 0) return 'All tracks in your library';
+    return rules.join(playlist.match === 'any' ? ' or ' : ', ');
+}
+
+/** Every track the library knows about, with the data needed to build a context */
+export async function getSmartPlaylistLibrary() {
+    const [favorites, history, playlists] = await Promise.all([
+        db.getFavorites('track'),
+        db.getHistory(),
+        db.getPlaylists(true),
+    ]);
+
+    const byId = new Map();
+    for (const track of [...favorites, ...history, ...playlists.flatMap((playlist) => playlist.tracks || [])]) {
+        if (!track?.id || track.type === 'video' || byId.has(String(track.id))) continue;
+        byId.set(String(track.id), track);
+    }
+    return { tracks: [...byId.values()], favorites, history };
+}
+
+/**
+ * Evaluate a saved smart playlist against the current library
+ * @param {SmartPlaylist} playlist
+ */
+export async function getSmartPlaylistTracks(playlist) {
+    const library = await getSmartPlaylistLibrary();
+    return evaluateSmartPlaylist(playlist, library.tracks, createSmartPlaylistContext(library));
+}
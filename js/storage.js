@@ -538,6 +538,46 @@ export const gaplessPlaybackSettings = {
     },
 };
 
+export const crossfadeSettings = {
+    STORAGE_KEY: 'crossfade-duration',
+    ALBUM_AWARE_KEY: 'crossfade-album-aware',
+    MIN_DURATION: 0,
+    MAX_DURATION: 12,
+
+    getDuration() {
+        try {
+            const val = parseFloat(localStorage.getItem(this.STORAGE_KEY));
+            if (isNaN(val)) return 0;
+            return Math.max(this.MIN_DURATION, Math.min(this.MAX_DURATION, val));
+        } catch {
+            return 0;
+        }
+    },
+
+    setDuration(seconds) {
+        const val = parseFloat(seconds);
+        const clamped = Math.max(this.MIN_DURATION, Math.min(this.MAX_DURATION, isNaN(val) ? 0 : val));
+        localStorage.setItem(this.STORAGE_KEY, clamped.toString());
+    },
+
+    isEnabled() {
+        return this.getDuration() > 0;
+    },
+
+    isAlbumAware() {
+        try {
+            const val = localStorage.getItem(this.ALBUM_AWARE_KEY);
+            return val === null ? true : val === 'true';
+        } catch {
+            return true;
+        }
+    },
+
+    setAlbumAware(enabled) {
+        localStorage.setItem(this.ALBUM_AWARE_KEY, enabled ? 'true' : 'false');
+    },
+};
+
 export const fullscreenCoverClickSettings = {
     STORAGE_KEY: 'fullscreen-cover-click-action',
 
@@ -568,6 +608,63 @@ export const lyricsSettings = {
     setDownloadLyrics(enabled) {
         localStorage.setItem(this.DOWNLOAD_WITH_TRACKS, enabled ? 'true' : 'false');
     },
+
+    EMBED_SYNCED: 'lyrics-embed-synced',
+
+    shouldEmbedSyncedLyrics() {
+        try {
+            // Default to true if not set
+            return localStorage.getItem(this.EMBED_SYNCED) !== 'false';
+        } catch {
+            return true;
+        }
+    },
+
+    setEmbedSyncedLyrics(enabled) {
+        localStorage.setItem(this.EMBED_SYNCED, enabled ? 'true' : 'false');
+    },
+};
+
+export const musicBrainzSettings = {
+    EMBED_IDS_KEY: 'download-embed-musicbrainz-ids',
+
+    shouldEmbedIds() {
+        try {
+            return localStorage.getItem(this.EMBED_IDS_KEY) === 'true';
+        } catch {
+            return false;
+        }
+    },
+
+    setEmbedIds(enabled) {
+        localStorage.setItem(this.EMBED_IDS_KEY, enabled ? 'true' : 'false');
+    },
+};
+
+export const localMatchSettings = {
+    STORAGE_KEY: 'local-library-catalog-matching',
+
+    isEnabled() {
+        try {
+            // Off until the user agrees to file names being looked up
+            return localStorage.getItem(this.STORAGE_KEY) === 'true';
+        } catch {
+            return false;
+        }
+    },
+
+    /** Whether the user was asked or chose in settings */
+    isDecided() {
+        try {
+            return localStorage.getItem(this.STORAGE_KEY) !== null;
+        } catch {
+            return true;
+        }
+    },
+
+    setEnabled(enabled) {
+        localStorage.setItem(this.STORAGE_KEY, enabled ? 'true' : 'false');
+    },
 };
 
 export const backgroundSettings = {
@@ -720,6 +817,7 @@ export const artistBannerSettings = {
 export const replayGainSettings = {
     STORAGE_KEY_MODE: 'replay-gain-mode', // 'off', 'track', 'album'
     STORAGE_KEY_PREAMP: 'replay-gain-preamp',
+    STORAGE_KEY_ANALYSIS: 'replay-gain-analysis',
     getMode() {
         return localStorage.getItem(this.STORAGE_KEY_MODE) || 'track';
     },
@@ -733,10 +831,23 @@ export const replayGainSettings = {
     setPreamp(db) {
         localStorage.setItem(this.STORAGE_KEY_PREAMP, db);
     },
+    // Measure EBU R128 loudness for tracks that ship without ReplayGain tags. Off unless turned on, since
+    // each analysis decodes the whole track on this device.
+    isAnalysisEnabled() {
+        try {
+            return localStorage.getItem(this.STORAGE_KEY_ANALYSIS) === 'true';
+        } catch {
+            return false;
+        }
+    },
+    setAnalysisEnabled(enabled) {
+        localStorage.setItem(this.STORAGE_KEY_ANALYSIS, enabled ? 'true' : 'false');
+    },
 };
 
 export const downloadQualitySettings = {
     STORAGE_KEY: 'download-quality',
+    ASK_KEY: 'download-quality-ask',
     getQuality() {
         try {
             const stored = localStorage.getItem(this.STORAGE_KEY) || 'HI_RES_LOSSLESS';
@@ -761,6 +872,151 @@ export const downloadQualitySettings = {
     setQuality(quality) {
         localStorage.setItem(this.STORAGE_KEY, quality);
     },
+    /** Whether each download asks which quality or transcoding profile to use */
+    shouldAsk() {
+        try {
+            return localStorage.getItem(this.ASK_KEY) === 'true';
+        } catch {
+            return false;
+        }
+    },
+    setAsk(enabled) {
+        localStorage.setItem(this.ASK_KEY, enabled ? 'true' : 'false');
+    },
+};
+
+export const offlineSettings = {
+    KEEP_PLAYLISTS_KEY: 'offline-keep-playlists',
+    MAX_BYTES_KEY: 'offline-max-bytes',
+    QUALITY_KEY: 'offline-quality',
+    SAVE_DOWNLOADS_KEY: 'offline-save-downloads',
+    DEFAULT_MAX_BYTES: 4 * 1024 * 1024 * 1024,
+
+    /**
+     * IDs of user playlists whose tracks are kept in the offline library
+     * @returns {string[]}
+     */
+    getKeptPlaylists() {
+        try {
+            const stored = JSON.parse(localStorage.getItem(this.KEEP_PLAYLISTS_KEY));
+            return Array.isArray(stored) ? stored : [];
+        } catch {
+            return [];
+        }
+    },
+
+    isPlaylistKept(playlistId) {
+        return this.getKeptPlaylists().includes(playlistId);
+    },
+
+    setPlaylistKept(playlistId, kept) {
+        const playlists = this.getKeptPlaylists().filter((id) => id !== playlistId);
+        if (kept) playlists.push(playlistId);
+        try {
+            localStorage.setItem(this.KEEP_PLAYLISTS_KEY, JSON.stringify(playlists));
+        } catch {
+            // QuotaExceededError - storage full
+        }
+    },
+
+    clearKeptPlaylists() {
+        localStorage.removeItem(this.KEEP_PLAYLISTS_KEY);
+    },
+
+    /**
+     * Storage budget for offline tracks in bytes (0 = only limited by the browser quota)
+     */
+    getMaxBytes() {
+        try {
+            const val = parseInt(localStorage.getItem(this.MAX_BYTES_KEY), 10);
+            return Number.isFinite(val) && val >= 0 ? val : this.DEFAULT_MAX_BYTES;
+        } catch {
+            return this.DEFAULT_MAX_BYTES;
+        }
+    },
+
+    setMaxBytes(bytes) {
+        localStorage.setItem(this.MAX_BYTES_KEY, String(Math.max(0, Math.round(bytes))));
+    },
+
+    getQuality() {
+        try {
+            return localStorage.getItem(this.QUALITY_KEY) || 'LOSSLESS';
+        } catch {
+            return 'LOSSLESS';
+        }
+    },
+
+    setQuality(quality) {
+        localStorage.setItem(this.QUALITY_KEY, quality);
+    },
+
+    /**
+     * Also keep single-track and bulk downloads in the offline library
+     */
+    shouldSaveDownloads() {
+        try {
+            return localStorage.getItem(this.SAVE_DOWNLOADS_KEY) === 'true';
+        } catch {
+            return false;
+        }
+    },
+
+    setSaveDownloads(enabled) {
+        localStorage.setItem(this.SAVE_DOWNLOADS_KEY, enabled ? 'true' : 'false');
+    },
+};
+
+export const downloadQueueSettings = {
+    CONCURRENCY_KEY: 'download-queue-concurrency',
+    MAX_RETRIES_KEY: 'download-queue-max-retries',
+    PAUSED_KEY: 'download-queue-paused',
+    DEFAULT_CONCURRENCY: 2,
+    DEFAULT_MAX_RETRIES: 3,
+
+    /**
+     * Number of tracks downloaded in parallel (1-6)
+     */
+    getConcurrency() {
+        try {
+            const val = parseInt(localStorage.getItem(this.CONCURRENCY_KEY), 10);
+            return Number.isFinite(val) ? Math.min(6, Math.max(1, val)) : this.DEFAULT_CONCURRENCY;
+        } catch {
+            return this.DEFAULT_CONCURRENCY;
+        }
+    },
+
+    setConcurrency(value) {
+        localStorage.setItem(this.CONCURRENCY_KEY, String(Math.min(6, Math.max(1, Math.round(value)))));
+    },
+
+    /**
+     * Automatic retries per track before it is marked as failed
+     */
+    getMaxRetries() {
+        try {
+            const val = parseInt(localStorage.getItem(this.MAX_RETRIES_KEY), 10);
+            return Number.isFinite(val) && val >= 0 ? Math.min(10, val) : this.DEFAULT_MAX_RETRIES;
+        } catch {
+            return this.DEFAULT_MAX_RETRIES;
+        }
+    },
+
+    setMaxRetries(value) {
+        localStorage.setItem(this.MAX_RETRIES_KEY, String(Math.min(10, Math.max(0, Math.round(value)))));
+    },
+
+    isPaused() {
+        try {
+            return localStorage.getItem(this.PAUSED_KEY) === 'true';
+        } catch {
+            return false;
+        }
+    },
+
+    setPaused(paused) {
+        localStorage.setItem(this.PAUSED_KEY, paused ? 'true' : 'false');
+    },
 };
 
 export const preferDolbyAtmosSettings = {
@@ -1116,6 +1372,7 @@ export const equalizerSettings = {
     BAND_TYPES_KEY: 'equalizer-band-types',
     BAND_QS_KEY: 'equalizer-band-qs',
     BAND_CHANNELS_KEY: 'equalizer-band-channels',
+    BAND_SLOPES_KEY: 'equalizer-band-slopes',
     PRESET_KEY: 'equalizer-preset',
     CUSTOM_PRESETS_KEY: 'equalizer-custom-presets',
     BAND_COUNT_KEY: 'equalizer-band-count',
@@ -1514,6 +1771,33 @@ export const equalizerSettings = {
         }
     },
 
+    // High/low-pass slopes in dB/octave (12, 24 or 48); ignored for other filter types
+    getBandSlopes(bandCount) {
+        const count = bandCount || this.getBandCount();
+        try {
+            const stored = localStorage.getItem(this.BAND_SLOPES_KEY);
+            if (stored) {
+                const slopes = JSON.parse(stored);
+                if (Array.isArray(slopes) && slopes.length === count) {
+                    return slopes;
+                }
+            }
+        } catch {
+            /* ignore */
+        }
+        return new Array(count).fill(12);
+    },
+
+    setBandSlopes(slopes) {
+        try {
+            if (Array.isArray(slopes) && slopes.length >= this.MIN_BANDS && slopes.length <= this.MAX_BANDS) {
+                localStorage.setItem(this.BAND_SLOPES_KEY, JSON.stringify(slopes));
+            }
+        } catch (e) {
+            console.warn('[EQ] Failed to save band slopes:', e);
+        }
+    },
+
     /**
      * Interpolate gains array to match target band count
      */
@@ -2005,6 +2289,292 @@ export const binauralDspSettings = {
     },
 };
 
+export const dynamicsSettings = {
+    STORAGE_KEY: 'dynamics-processing',
+
+    /**
+     * Compressor / limiter parameters; missing values fall back to DYNAMICS_DEFAULTS
+     */
+    get() {
+        try {
+            return JSON.parse(localStorage.getItem(this.STORAGE_KEY)) || {};
+        } catch {
+            return {};
+        }
+    },
+
+    set(settings) {
+        try {
+            localStorage.setItem(this.STORAGE_KEY, JSON.stringify(settings));
+        } catch {
+            // QuotaExceededError - storage full
+        }
+    },
+};
+
+export const convolutionSettings = {
+    STORAGE_KEY: 'convolution-settings',
+
+    /**
+     * Convolution stage state (enabled, IR id, wet/dry mix, gain, normalize); see CONVOLUTION_DEFAULTS
+     */
+    get() {
+        try {
+            return JSON.parse(localStorage.getItem(this.STORAGE_KEY)) || {};
+        } catch {
+            return {};
+        }
+    },
+
+    set(settings) {
+        try {
+            localStorage.setItem(this.STORAGE_KEY, JSON.stringify(settings));
+        } catch {
+            // QuotaExceededError - storage full
+        }
+    },
+};
+
+// Named EQ profiles bound to audio output devices. A profile is a snapshot of the raw equalizer / graphic EQ /
+// binaural / dynamics / convolution storage values, so applying one is just writing them back.
+export const eqDeviceProfileSettings = {
+    PROFILES_KEY: 'eq-device-profiles',
+    BINDINGS_KEY: 'eq-device-bindings',
+    FALLBACK_KEY: 'eq-device-fallback',
+    ACTIVE_PROFILE_KEY: 'eq-device-active-profile',
+    OUTPUT_DEVICE_KEY: 'audio-output-device',
+    AUTO_SWITCH_KEY: 'eq-device-auto-switch',
+    // EQ panel mode; decides whether the parametric or the graphic EQ chain is active
+    EQ_MODE_KEY: 'eq-active-mode',
+
+    getSnapshotKeys() {
+        const eq = equalizerSettings;
+        return [
+            eq.ENABLED_KEY,
+            eq.GAINS_KEY,
+            eq.BAND_TYPES_KEY,
+            eq.BAND_QS_KEY,
+            eq.BAND_CHANNELS_KEY,
+            eq.BAND_SLOPES_KEY,
+            eq.PRESET_KEY,
+            eq.BAND_COUNT_KEY,
+            eq.FREQ_MIN_KEY,
+            eq.FREQ_MAX_KEY,
+            eq.PREAMP_KEY,
+            eq.CUSTOM_FREQUENCIES_KEY,
+            eq.GEQ_ENABLED_KEY,
+            eq.GEQ_GAINS_KEY,
+            eq.GEQ_PREAMP_KEY,
+            eq.GEQ_BAND_COUNT_KEY,
+            eq.GEQ_FREQ_RANGE_KEY,
+            binauralDspSettings.STORAGE_KEY,
+            dynamicsSettings.STORAGE_KEY,
+            convolutionSettings.STORAGE_KEY,
+            this.EQ_MODE_KEY,
+        ];
+    },
+
+    /**
+     * Capture the current EQ, graphic EQ and binaural state
+     * @returns {Object<string, string|null>} Raw storage values keyed by storage key
+     */
+    captureSnapshot() {
+        const snapshot = {};
+        for (const key of this.getSnapshotKeys()) {
+            try {
+                snapshot[key] = localStorage.getItem(key);
+            } catch {
+                snapshot[key] = null;
+            }
+        }
+        return snapshot;
+    },
+
+    /**
+     * Write a snapshot back to storage. Keys missing from the snapshot (older profiles) are left alone.
+     */
+    applySnapshot(snapshot) {
+        if (!snapshot || typeof snapshot !== 'object') return;
+        for (const key of this.getSnapshotKeys()) {
+            if (!(key in snapshot)) continue;
+            try {
+                if (snapshot[key] === null) {
+                    localStorage.removeItem(key);
+                } else {
+                    localStorage.setItem(key, snapshot[key]);
+                }
+            } catch {
+                // QuotaExceededError - storage full
+            }
+        }
+    },
+
+    getProfiles() {
+        try {
+            const stored = localStorage.getItem(this.PROFILES_KEY);
+            return stored ? JSON.parse(stored) : {};
+        } catch {
+            return {};
+        }
+    },
+
+    _setProfiles(profiles) {
+        try {
+            localStorage.setItem(this.PROFILES_KEY, JSON.stringify(profiles));
+            return true;
+        } catch (e) {
+            console.warn('[EQ Profiles] Failed to save profiles:', e);
+            return false;
+        }
+    },
+
+    /**
+     * Save a named profile
+     * @returns {string|false} Profile ID
+     */
+    saveProfile(name, snapshot, profileId = null) {
+        const profiles = this.getProfiles();
+        const id = profileId || 'eqprofile_' + Date.now();
+        profiles[id] = { id, name: name || 'Unnamed', snapshot, updatedAt: Date.now() };
+        return this._setProfiles(profiles) ? id : false;
+    },
+
+    updateProfileSnapshot(profileId, snapshot) {
+        const profiles = this.getProfiles();
+        if (!profiles[profileId]) return false;
+        profiles[profileId] = { ...profiles[profileId], snapshot, updatedAt: Date.now() };
+        return this._setProfiles(profiles);
+    },
+
+    deleteProfile(profileId) {
+        const profiles = this.getProfiles();
+        if (!profiles[profileId]) return false;
+        delete profiles[profileId];
+        const bindings = this.getBindings();
+        for (const [deviceId, binding] of Object.entries(bindings)) {
+            if (binding.profileId === profileId) delete bindings[deviceId];
+        }
+        this._setBindings(bindings);
+        if (this.getActiveProfile() === profileId) this.setActiveProfile(null);
+        return this._setProfiles(profiles);
+    },
+
+    getActiveProfile() {
+        try {
+            return localStorage.getItem(this.ACTIVE_PROFILE_KEY) || null;
+        } catch {
+            return null;
+        }
+    },
+
+    setActiveProfile(profileId) {
+        try {
+            if (profileId) {
+                localStorage.setItem(this.ACTIVE_PROFILE_KEY, profileId);
+            } else {
+                localStorage.removeItem(this.ACTIVE_PROFILE_KEY);
+            }
+        } catch {
+            // QuotaExceededError - storage full
+        }
+    },
+
+    /**
+     * @returns {Object<string, {profileId: string, label: string}>} Bindings keyed by device ID
+     */
+    getBindings() {
+        try {
+            const stored = localStorage.getItem(this.BINDINGS_KEY);
+            return stored ? JSON.parse(stored) : {};
+        } catch {
+            return {};
+        }
+    },
+
+    _setBindings(bindings) {
+        try {
+            localStorage.setItem(this.BINDINGS_KEY, JSON.stringify(bindings));
+        } catch {
+            // QuotaExceededError - storage full
+        }
+    },
+
+    /**
+     * Bind a profile to an output device, or unbind it when profileId is null
+     * @param {{deviceId: string, label?: string}} device
+     */
+    bindDevice(device, profileId) {
+        if (!device?.deviceId) return;
+        const bindings = this.getBindings();
+        if (profileId) {
+            bindings[device.deviceId] = { profileId, label: device.label || '' };
+        } else {
+            delete bindings[device.deviceId];
+        }
+        this._setBindings(bindings);
+    },
+
+    /**
+     * State restored on outputs without a bound profile
+     */
+    getFallbackSnapshot() {
+        try {
+            const stored = localStorage.getItem(this.FALLBACK_KEY);
+            return stored ? JSON.parse(stored) : null;
+        } catch {
+            return null;
+        }
+    },
+
+    setFallbackSnapshot(snapshot) {
+        try {
+            if (snapshot) {
+                localStorage.setItem(this.FALLBACK_KEY, JSON.stringify(snapshot));
+            } else {
+                localStorage.removeItem(this.FALLBACK_KEY);
+            }
+        } catch {
+            // QuotaExceededError - storage full
+        }
+    },
+
+    /**
+     * Sink ID picked in settings; empty string means the system default output
+     */
+    getOutputDevice() {
+        try {
+            return localStorage.getItem(this.OUTPUT_DEVICE_KEY) || '';
+        } catch {
+            return '';
+        }
+    },
+
+    setOutputDevice(deviceId) {
+        try {
+            if (deviceId && deviceId !== 'default') {
+                localStorage.setItem(this.OUTPUT_DEVICE_KEY, deviceId);
+            } else {
+                localStorage.removeItem(this.OUTPUT_DEVICE_KEY);
+            }
+        } catch {
+            // QuotaExceededError - storage full
+        }
+    },
+
+    isAutoSwitchEnabled() {
+        try {
+            // Enabled by default
+            return localStorage.getItem(this.AUTO_SWITCH_KEY) !== 'false';
+        } catch {
+            return true;
+        }
+    },
+
+    setAutoSwitchEnabled(enabled) {
+        localStorage.setItem(this.AUTO_SWITCH_KEY, enabled ? 'true' : 'false');
+    },
+};
+
 export const exponentialVolumeSettings = {
     STORAGE_KEY: 'exponential-volume-enabled',
 
@@ -2455,6 +3025,90 @@ export const analyticsSettings = {
     },
 };
 
+export const settingsSyncSettings = {
+    ENABLED_KEY: 'settings-sync-enabled',
+    DISABLED_CATEGORIES_KEY: 'settings-sync-disabled-categories',
+    STATE_KEY: 'settings-sync-state',
+
+    isEnabled() {
+        try {
+            return localStorage.getItem(this.ENABLED_KEY) === 'true';
+        } catch {
+            return false;
+        }
+    },
+
+    setEnabled(enabled) {
+        localStorage.setItem(this.ENABLED_KEY, enabled ? 'true' : 'false');
+    },
+
+    // Every category is synced unless it was turned off
+    isCategoryEnabled(category) {
+        return !this.getDisabledCategories().includes(category);
+    },
+
+    getDisabledCategories() {
+        try {
+            return JSON.parse(localStorage.getItem(this.DISABLED_CATEGORIES_KEY)) || [];
+        } catch {
+            return [];
+        }
+    },
+
+    setCategoryEnabled(category, enabled) {
+        const disabled = this.getDisabledCategories().filter((c) => c !== category);
+        if (!enabled) disabled.push(category);
+        localStorage.setItem(this.DISABLED_CATEGORIES_KEY, JSON.stringify(disabled));
+    },
+
+    // Values and change times as of the last sync, see settings-sync.js
+    getState() {
+        try {
+            return JSON.parse(localStorage.getItem(this.STATE_KEY)) || { entries: {}, revision: 0 };
+        } catch {
+            return { entries: {}, revision: 0 };
+        }
+    },
+
+    setState(state) {
+        localStorage.setItem(this.STATE_KEY, JSON.stringify(state));
+    },
+};
+
+export const syncBackendSettings = {
+    // Never synced or backed up, see settings-sync.js. The password is kept as plain text; the WebDAV dialog says so
+    WEBDAV_URL_KEY: 'webdav-sync-url',
+    WEBDAV_USERNAME_KEY: 'webdav-sync-username',
+    WEBDAV_PASSWORD_KEY: 'webdav-sync-password',
+
+    // Syncs through the Monochrome account unless a WebDAV folder is set
+    getWebDavConfig() {
+        try {
+            const url = localStorage.getItem(this.WEBDAV_URL_KEY);
+            if (!url) return null;
+            return {
+                url,
+                username: localStorage.getItem(this.WEBDAV_USERNAME_KEY) || '',
+                password: localStorage.getItem(this.WEBDAV_PASSWORD_KEY) || '',
+            };
+        } catch {
+            return null;
+        }
+    },
+
+    setWebDavConfig(config) {
+        if (!config) {
+            localStorage.removeItem(this.WEBDAV_URL_KEY);
+            localStorage.removeItem(this.WEBDAV_USERNAME_KEY);
+            localStorage.removeItem(this.WEBDAV_PASSWORD_KEY);
+            return;
+        }
+        localStorage.setItem(this.WEBDAV_URL_KEY, config.url);
+        localStorage.setItem(this.WEBDAV_USERNAME_KEY, config.username || '');
+        localStorage.setItem(this.WEBDAV_PASSWORD_KEY, config.password || '');
+    },
+};
+
 export const sidebarSectionSettings = {
     SHOW_HOME_KEY: 'sidebar-show-home',
     SHOW_LIBRARY_KEY: 'sidebar-show-library',
@@ -3226,6 +3880,14 @@ export const modalSettings = {
             'playlist-modal',
             'folder-modal',
             'playlist-select-modal',
+            'download-profile-modal',
+            'tag-editor-modal',
+            'smart-playlist-modal',
+            'import-review-modal',
+            'playlist-history-modal',
+            'backup-restore-modal',
+            'cloud-encryption-modal',
+            'webdav-sync-modal',
             'shortcuts-modal',
             'missing-tracks-modal',
             'sleep-timer-modal',
@@ -3259,6 +3921,14 @@ export const modalSettings = {
             'playlist-modal',
             'folder-modal',
             'playlist-select-modal',
+            'download-profile-modal',
+            'tag-editor-modal',
+            'smart-playlist-modal',
+            'import-review-modal',
+            'playlist-history-modal',
+            'backup-restore-modal',
+            'cloud-encryption-modal',
+            'webdav-sync-modal',
             'shortcuts-modal',
             'missing-tracks-modal',
             'sleep-timer-modal',
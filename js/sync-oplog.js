@@ -0,0 +1,175 @@
+// js/sync-oplog.js
+// Edits to playlists and the library are recorded as small operations (add, remove, move) stamped by a
+// logical clock. Before anything is written to the cloud, the queued operations are replayed on top of the
+// freshest cloud copy, so edits made on another device are kept instead of being overwritten. Replaying the
+// same operations on the same data always gives the same result.
+
+import { diffPlaylistTracks, getEntryKey } from './playlist-history.js';
+
+const TIME_DIGITS = 9;
+const COUNTER_DIGITS = 4;
+
+/** Target of the operations on a user playlist's tracks */
+export function playlistTarget(playlistId) {
+    return `playlist:${playlistId}`;
+}
+
+/** Target of the operations on one library section, e.g. "library:tracks" */
+export function libraryTarget(type) {
+    return `library:${type === 'mix' ? 'mixes' : `${type}s`}`;
+}
+
+export function createClock(device) {
+    return { time: 0, counter: 0, device: String(device) };
+}
+
+/** Advance the clock for a local event. Stamps keep increasing even if the system time goes back. */
+export function tickClock(clock, now = Date.now()) {
+    if (now > clock.time) return { ...clock, time: now, counter: 0 };
+    return { ...clock, counter: clock.counter + 1 };
+}
+
+/** Stamps sort as plain strings in the order of their clocks, ties broken by device */
+export function formatStamp({ time, counter, device }) {
+    return [
+        time.toString(36).padStart(TIME_DIGITS, '0'),
+        counter.toString(36).padStart(COUNTER_DIGITS, '0'),
+        device,
+    ].join('-');
+}
+
+export function compareOps(a, b) {
+    if (a.stamp !== b.stamp) return a.stamp < b.stamp ? -1 : 1;
+    if (a.id === b.id) return 0;
+    return a.id < b.id ? -1 : 1;
+}
+
+/** Union of operation logs from several devices, without duplicates, in replay order */
+export function mergeOpLogs(...logs) {
+    const byId = new Map();
+    logs.flat().forEach((op) => byId.set(op.id, op));
+    return [...byId.values()].sort(compareOps);
+}
+
+/**
+ * The operations that turn one version of a playlist's tracks into another
+ * @param {string} target
+ * @param {Array} before
+ * @param {Array} after
+ * @param {() => string} nextStamp - Returns a new, larger stamp on every call
+ */
+export function diffToOps(target, before = [], after = [], nextStamp) {
+    const { removed, moved } = diffPlaylistTracks(before, after);
+    const beforeKeys = new Set(before.map(getEntryKey));
+    const movedKeys = new Set(moved.map(getEntryKey));
+    const createOp = (type, key, fields = {}) => {
+        const stamp = nextStamp();
+        return { id: `${stamp}:${type}:${key}`, target, type, key, stamp, ...fields };
+    };
+
+    const ops = removed.map((track) => createOp('remove', getEntryKey(track)));
+    after.forEach((track, index) => {
+        const key = getEntryKey(track);
+        const anchor = index > 0 ? getEntryKey(after[index - 1]) : null;
+        if (!beforeKeys.has(key)) ops.push(createOp('add', key, { item: track, after: anchor }));
+        else if (movedKeys.has(key)) ops.push(createOp('move', key, { after: anchor }));
+    });
+    return ops;
+}
+
+// Where an entry goes: right after its anchor, first when there is no anchor, last when the anchor is gone
+function insertAfter(entries, entry, anchor) {
+    if (anchor === null || anchor === undefined) {
+        entries.unshift(entry);
+        return;
+    }
+    const index = entries.findIndex((e) => e.key === anchor);
+    if (index === -1) entries.push(entry);
+    else entries.splice(index + 1, 0, entry);
+}
+
+function replay(entries, ops) {
+    const result = [...entries];
+    [...ops].sort(compareOps).forEach((op) => {
+        const index = result.findIndex((e) => e.key === op.key);
+        if (op.type === 'add') {
+            if (index === -1) insertAfter(result, { key: op.key, item: op.item }, op.after);
+        } else if (op.type === 'remove') {
+            if (index !== -1) result.splice(index, 1);
+        } else if (op.type === 'move') {
+            if (index === -1 || op.after === op.key) return;
+            const [entry] = result.splice(index, 1);
+            insertAfter(result, entry, op.after);
+        }
+    });
+    return result;
+}
+
+/** Replay operations on a playlist's tracks. An added track that is already there is left in place. */
+export function applyPlaylistOps(tracks = [], ops = []) {
+    const entries = tracks.map((track) => ({ key: getEntryKey(track), item: track }));
+    return replay(entries, ops).map((entry) => entry.item);
+}
+
+/** Replay operations on one library section, an object keyed by item id */
+export function applyLibraryOps(items = {}, ops = []) {
+    const entries = Object.entries(items).map(([key, item]) => ({ key, item }));
+    return Object.fromEntries(replay(entries, ops).map((entry) => [entry.key, entry.item]));
+}
+
+/**
+ * Replay queued operations on the cloud copy of the library and playlists
+ * @param {{library?: object, userPlaylists?: object}} data - As loaded from the sync backend
+ * @param {Array} ops
+ * @param {Object<string, object>} [localPlaylists] - Records for playlists the cloud doesn't have yet
+ * @returns {{library: object, userPlaylists: object, changed: Set<string>}} Changed fields are "library"
+ *   and/or "userPlaylists"
+ */
+export function rebaseOnCloud(data, ops, localPlaylists = {}) {
+    const library = { ...(data.library || {}) };
+    const userPlaylists = { ...(data.userPlaylists || {}) };
+    const changed = new Set();
+
+    const byTarget = new Map();
+    ops.forEach((op) => {
+        if (!byTarget.has(op.target)) byTarget.set(op.target, []);
+        byTarget.get(op.target).push(op);
+    });
+
+    for (const [target, targetOps] of byTarget) {
+        const [kind, ...rest] = target.split(':');
+        const id = rest.join(':');
+        if (kind === 'library') {
+            library[id] = applyLibraryOps(library[id] || {}, targetOps);
+            changed.add('library');
+        } else if (kind === 'playlist') {
+            const cloud = userPlaylists[id];
+            const local = localPlaylists[id];
+            // The local copy already contains these edits when the cloud has never seen the playlist
+            if (!cloud && !local) continue;
+            const tracks = cloud ? applyPlaylistOps(cloud.tracks || [], targetOps) : local.tracks || [];
+            userPlaylists[id] = { ...(cloud || {}), ...(local || {}), tracks, numberOfTracks: tracks.length };
+            changed.add('userPlaylists');
+        }
+    }
+
+    return { library, userPlaylists, changed };
+}
+
+/**
+ * Load the cloud copy, replay the queued operations on it and save what changed
+ * @param {{load: () => Promise<object>, save: (fields: object) => Promise<object>}} transport
+ * @param {Array} ops
+ * @param {Object<string, object>} [localPlaylists]
+ */
+export async function pushOps(transport, ops, localPlaylists = {}) {
+    const data = await transport.load();
+    const merged = rebaseOnCloud(data || {}, ops, localPlaylists);
+    const fields = {};
+    merged.changed.forEach((field) => {
+        fields[field] = merged[field];
+    });
+    if (merged.changed.size > 0) await transport.save(fields);
+    return merged;
+}
+
@@ -0,0 +1,255 @@
+// js/tag-editor.js
+// Tag editor for files in the local media folder. Several files can be edited at once: a field whose
+// value differs between the selected files is shown as "Multiple values" and is only written if the
+// user types something into it. Retagged files are written back through their File System Access
+// handles and re-read into the Library > Local list.
+
+import { db } from './db.js';
+import { getMimeType } from './utils.js';
+import { showNotification } from './downloads.js';
+import { indexLocalFile } from './local-library.js';
+
+/** Fields the editor shows, keyed by their name in TagLibMetadata */
+export const EDITABLE_TAG_FIELDS = ['title', 'artist', 'albumTitle', 'trackNumber', 'discNumber', 'isrc', 'lyrics'];
+
+const NUMBER_FIELDS = new Set(['trackNumber', 'discNumber']);
+const MULTIPLE_VALUES = 'Multiple values';
+
+let closeTagEditor = null;
+
+/** Normalize a tag value to the string shown in the form */
+export function formatTagValue(field, value) {
+    if (value == null) return '';
+    if (field === 'artist') {
+        return (Array.isArray(value) ? value : String(value).split(';'))
+            .map((name) => name.trim())
+            .filter(Boolean)
+            .join('; ');
+    }
+    return String(value).trim();
+}
+
+/**
+ * Work out what to show for each field across the files being edited
+ * @param {object[]} tagList - Tags of each file
+ * @returns {Record<string, {value: string, mixed: boolean}>}
+ */
+export function getCommonTagValues(tagList) {
+    const common = {};
+    for (const field of EDITABLE_TAG_FIELDS) {
+        const values = new Set(tagList.map((tags) => formatTagValue(field, tags?.[field])));
+        common[field] = values.size === 1 ? { value: [...values][0], mixed: false } : { value: '', mixed: true };
+    }
+    return common;
+}
+
+/**
+ * Turn the submitted form into the tags to write. Fields left as they were are skipped. A field the
+ * user emptied is set to null, which the tag writers take as removing it from the file, since a
+ * missing value keeps whatever the file had.
+ * @param {Record<string, string>} values - Form values keyed by field
+ * @param {Record<string, {value: string, mixed: boolean}>} common - What the form was opened with
+ * @returns {object}
+ */
+export function buildTagChanges(values, common) {
+    const changes = {};
+    for (const field of EDITABLE_TAG_FIELDS) {
+        const value = formatTagValue(field, values[field]);
+        // A "Multiple values" field opens empty, so leaving it empty never removes anything
+        if (value === common[field]?.value) continue;
+        if (!value) {
+            changes[field] = null;
+            continue;
+        }
+
+        if (NUMBER_FIELDS.has(field)) {
+            const number = parseInt(value, 10);
+            if (number > 0) changes[field] = number;
+        } else if (field === 'artist') {
+            changes.artist = value.split(';').map((name) => name.trim());
+        } else {
+            changes[field] = value;
+        }
+    }
+    return changes;
+}
+
+async function requestWritePermission(handle) {
+    if (typeof handle?.queryPermission !== 'function') return true;
+    if ((await handle.queryPermission({ mode: 'readwrite' })) === 'granted') return true;
+    return (await handle.requestPermission({ mode: 'readwrite' })) === 'granted';
+}
+
+async function readCover(input) {
+    const file = input.files?.[0];
+    if (!file) return null;
+    const data = new Uint8Array(await file.arrayBuffer());
+    return { data, type: file.type || getMimeType(data) };
+}
+
+/**
+ * Retag one local track and return its re-read metadata
+ * @param {object} track - Entry of `window.localFilesCache`
+ * @param {object} changes
+ * @param {import('./metadata.js')} metadataModule
+ */
+async function retagTrack(track, changes, { readEditableTags, writeEditableTags, readTrackMetadata }) {
+    const handle = track.fileHandle;
+    const file = await handle.getFile();
+    const existing = (await readEditableTags(file)) || {};
+
+    // TagLib derives the album artist from the artists and drops track/disc totals unless told otherwise
+    const tags = { ...changes };
+    if (tags.artist && existing.albumArtist) tags.albumArtist = existing.albumArtist;
+    if (tags.trackNumber && existing.totalTracks) tags.totalTracks = existing.totalTracks;
+    if (tags.discNumber && existing.totalDiscs) tags.totalDiscs = existing.totalDiscs;
+    if (tags.cover) tags.cover = { data: tags.cover.data.slice(), type: tags.cover.type };
+
+    const retagged = await writeEditableTags(file, tags);
+    const writable = await handle.createWritable();
+    try {
+        await writable.write(retagged);
+        await writable.close();
+    } catch (error) {
+        await writable.abort().catch(() => {});
+        throw error;
+    }
+
+    // Files from the library index are re-indexed so the next scan does not parse them again
+    if (track.localRootId) {
+        return await indexLocalFile(track.localRootId, track.localPath, handle, readEditableTags);
+    }
+
+    const metadata = await readTrackMetadata(await handle.getFile());
+    metadata.id = track.id;
+    metadata.fileHandle = handle;
+    return metadata;
+}
+
+function fillForm(modal, common, count) {
+    modal.querySelector('#tag-editor-subtitle').textContent =
+        count === 1 ? 'Editing 1 file' : `Editing ${count} files. Fields left blank keep their current values.`;
+
+    modal.querySelectorAll('[data-tag-field]').forEach((input) => {
+        const { value, mixed } = common[input.dataset.tagField];
+        input.value = value;
+        input.placeholder = mixed ? MULTIPLE_VALUES : input.dataset.placeholder;
+    });
+
+    const coverInput = modal.querySelector('#tag-editor-cover-input');
+    coverInput.value = '';
+    modal.querySelector('#tag-editor-cover-name').textContent = 'Keep current cover';
+}
+
+/**
+ * Open the tag editor for one or more local tracks
+ * @param {object[]} tracks - Entries of `window.localFilesCache`
+ */
+export async function openTagEditor(tracks) {
+    const modal = document.getElementById('tag-editor-modal');
+    // Tracks split from a CUE sheet share one file, so their tags cannot be edited separately
+    const editable = tracks.filter((track) => track?.isLocal && track.fileHandle && track.cueStart === undefined);
+    if (!modal || editable.length === 0) {
+        showNotification('Only files in your local media folder can be edited');
+        return;
+    }
+
+    closeTagEditor?.();
+
+    const metadataModule = await import('./metadata.js');
+    const tagList = await Promise.all(
+        editable.map(async (track) => {
+            try {
+                return await metadataModule.readEditableTags(await track.fileHandle.getFile());
+            } catch (e) {
+                console.warn('Could not read tags of', track.fileHandle.name, e);
+                return null;
+            }
+        })
+    );
+    const common = getCommonTagValues(tagList);
+    fillForm(modal, common, editable.length);
+
+    const saveBtn = modal.querySelector('#tag-editor-save');
+    const coverInput = modal.querySelector('#tag-editor-cover-input');
+
+    const close = () => {
+        modal.classList.remove('active');
+        modal.removeEventListener('click', handleClick);
+        coverInput.removeEventListener('change', handleCoverChange);
+        closeTagEditor = null;
+    };
+
+    const handleCoverChange = () => {
+        const file = coverInput.files?.[0];
+        modal.querySelector('#tag-editor-cover-name').textContent = file ? file.name : 'Keep current cover';
+    };
+
+    const save = async () => {
+        const folderHandle = await db.getSetting('local_folder_handle');
+        if (!(await requestWritePermission(folderHandle || editable[0].fileHandle))) {
+            showNotification('Write access to the folder was denied');
+            return;
+        }
+
+        const values = {};
+        modal.querySelectorAll('[data-tag-field]').forEach((input) => {
+            values[input.dataset.tagField] = input.value;
+        });
+        const changes = buildTagChanges(values, common);
+        const cover = await readCover(coverInput);
+        if (cover) changes.cover = cover;
+
+        if (Object.keys(changes).length === 0) {
+            close();
+            return;
+        }
+
+        saveBtn.disabled = true;
+        saveBtn.textContent = 'Saving...';
+
+        let failed = 0;
+        for (const track of editable) {
+            try {
+                const updated = await retagTrack(track, changes, metadataModule);
+                const cache = window.localFilesCache || [];
+                const index = cache.findIndex((t) => t.id === track.id);
+                if (index !== -1) cache[index] = updated;
+            } catch (e) {
+                console.error('Failed to write tags to', track.fileHandle.name, e);
+                failed++;
+            }
+        }
+
+        saveBtn.disabled = false;
+        saveBtn.textContent = 'Save';
+        close();
+
+        const saved = editable.length - failed;
+        showNotification(
+            failed
+                ? `Saved tags for ${saved} of ${editable.length} files`
+                : `Saved tags for ${saved} file${saved === 1 ? '' : 's'}`
+        );
+        window.dispatchEvent(new CustomEvent('local-files-retagged'));
+    };
+
+    const handleClick = (e) => {
+        if (e.target.classList.contains('modal-overlay') || e.target.id === 'tag-editor-cancel') {
+            close();
+        } else if (e.target.id === 'tag-editor-cover-btn') {
+            coverInput.click();
+        } else if (e.target.id === 'tag-editor-save' && !saveBtn.disabled) {
+            save().catch((error) => {
+                console.error('Tag editor save failed:', error);
+                saveBtn.disabled = false;
+                saveBtn.textContent = 'Save';
+            });
+        }
+    };
+
+    closeTagEditor = close;
+    modal.addEventListener('click', handleClick);
+    coverInput.addEventListener('change', handleCoverChange);
+    modal.classList.add('active');
+}
@@ -41,10 +41,26 @@ export interface TagLibMetadata {
     explicit?: boolean;
     lyrics?: string;
     upc?: string;
+    musicBrainz?: {
+        recordingId?: string;
+        releaseId?: string;
+        artistIds?: string[];
+    };
     stik?: Mp4Stik;
     extra?: Record<string, string>;
 }
 
+/** Property keys of the fields that are removed from a file when they are written as null */
+export const REMOVABLE_TAG_KEYS = {
+    title: 'TITLE',
+    artist: 'ARTIST',
+    albumTitle: 'ALBUM',
+    trackNumber: 'TRACKNUMBER',
+    discNumber: 'DISCNUMBER',
+    isrc: 'ISRC',
+    lyrics: 'LYRICS',
+} as const;
+
 export enum Mp4Stik {
     HomeVideo = 0,
     Normal = 1,
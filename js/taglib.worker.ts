@@ -7,6 +7,7 @@ import { Variant } from '!/@dantheman827/taglib-ts/src/toolkit/variant.js';
 import { doTimed, doTimedAsync } from './doTimed';
 import {
     Mp4Stik,
+    REMOVABLE_TAG_KEYS,
     type _AddMetadataMessage,
     type _GetMetadataMessage,
     type AddMetadataMessage,
@@ -57,6 +58,7 @@ export async function addMetadataToAudio(message: _AddMetadataMessage): Promise<
         upc,
         explicit,
         lyrics,
+        musicBrainz,
         stik = Mp4Stik.Normal,
         extra,
         returnType = 'uint8array',
@@ -89,6 +91,11 @@ export async function addMetadataToAudio(message: _AddMetadataMessage): Promise<
     doTimed('Tagging file', () => {
         const props = ref.properties();
 
+        // An empty list makes setProperties remove the key
+        for (const [field, key] of Object.entries(REMOVABLE_TAG_KEYS)) {
+            if (message[field as keyof typeof REMOVABLE_TAG_KEYS] === null) props.replace(key, []);
+        }
+
         if (title) props.replace('TITLE', [title]);
         if (artistArray.length)
             props.replace('ARTIST', supportsMultiValuedArtist ? artistArray : [artistArray.join('; ')]);
@@ -143,6 +150,10 @@ export async function addMetadataToAudio(message: _AddMetadataMessage): Promise<
         if (upc) props.replace('UPC', [upc]);
         if (lyrics) props.replace('LYRICS', [lyrics.replace(/\r/g, '').replace(/\n/g, '\r\n')]);
 
+        if (musicBrainz?.recordingId) props.replace('MUSICBRAINZ_TRACKID', [musicBrainz.recordingId]);
+        if (musicBrainz?.releaseId) props.replace('MUSICBRAINZ_ALBUMID', [musicBrainz.releaseId]);
+        if (musicBrainz?.artistIds?.length) props.replace('MUSICBRAINZ_ARTISTID', musicBrainz.artistIds);
+
         if (explicit !== undefined) {
             if (isMp4) {
                 // rtng is a byte item - must be set directly on the Mp4Tag
@@ -249,6 +260,15 @@ export async function getMetadataFromAudio(message: _GetMetadataMessage): Promis
     data.lyrics = props.get('LYRICS')?.[0] || undefined;
     data.releaseDate = props.get('DATE')?.[0] || undefined;
 
+    const recordingId = props.get('MUSICBRAINZ_TRACKID')?.[0];
+    if (recordingId) {
+        data.musicBrainz = {
+            recordingId,
+            releaseId: props.get('MUSICBRAINZ_ALBUMID')?.[0] || undefined,
+            artistIds: props.get('MUSICBRAINZ_ARTISTID') || undefined,
+        };
+    }
+
     const replayGain: TagLibMetadata['replayGain'] = {};
     const albumGain = props.get('REPLAYGAIN_ALBUM_GAIN')?.[0];
     const albumPeak = props.get('REPLAYGAIN_ALBUM_PEAK')?.[0];
@@ -0,0 +1,75 @@
+import { expect, test, describe, beforeEach, vi } from 'vitest';
+import { authApi } from '../accounts/authApi.js';
+import { syncManager } from '../accounts/pocketbase.js';
+import { createAccountBackend } from '../accounts/sync-backend.js';
+
+vi.mock('../accounts/auth.js', () => ({
+    authManager: { user: { $id: 'user-1' }, onAuthStateChanged: vi.fn() },
+}));
+
+vi.mock('../accounts/authApi.js', () => ({
+    authApi: vi.fn(),
+}));
+
+vi.mock('pocketbase', () => ({
+    default: class {
+        autoCancellation() {}
+    },
+}));
+
+// Stands in for /api/sync, which keeps only the fields it has columns for
+function mockSyncServer() {
+    const cloud = { library: {}, history: [], userPlaylists: {}, userFolders: {}, encryption: null };
+    authApi.mockImplementation(async (_path, options = {}) => {
+        if (options.method === 'PATCH') {
+            const fields = JSON.parse(options.body);
+            Object.keys(cloud).forEach((field) => {
+                if (field in fields) cloud[field] = fields[field];
+            });
+        }
+        return structuredClone(cloud);
+    });
+    return cloud;
+}
+
+const patchedFields = () =>
+    authApi.mock.calls
+        .filter(([, options]) => options?.method === 'PATCH')
+        .map(([, options]) => JSON.parse(options.body));
+
+describe('sync-backend.js account', () => {
+    beforeEach(() => {
+        authApi.mockReset();
+        syncManager.useBackend(createAccountBackend());
+    });
+
+    test('the account backend loads and saves through /api/sync', async () => {
+        const cloud = mockSyncServer();
+        const backend = createAccountBackend();
+
+        await backend.save({ history: [{ id: 1, timestamp: 5 }] });
+        expect(authApi).toHaveBeenCalledWith('/api/sync', expect.objectContaining({ method: 'PATCH' }));
+        expect((await backend.load()).history).toEqual(cloud.history);
+    });
+
+    test('smart playlists are not sent to an account, which has nowhere to keep them', async () => {
+        mockSyncServer();
+        expect(syncManager.supports('userFolders')).toBe(true);
+        expect(syncManager.supports('smartPlaylists')).toBe(false);
+
+        await syncManager.syncSmartPlaylist({ id: 's1', name: 'Loud', rules: [] }, 'create');
+        expect(patchedFields()).toEqual([]);
+
+        await syncManager.syncUserFolder({ id: 'f1', name: 'Folder', createdAt: 1, updatedAt: 1 }, 'create');
+        expect(patchedFields()).toHaveLength(1);
+        expect(Object.keys(patchedFields()[0])).toEqual(['userFolders']);
+    });
+
+    test('settings are not sent to an account either', async () => {
+        mockSyncServer();
+        expect(syncManager.supports('settings')).toBe(false);
+
+        expect(await syncManager.saveSettings({ version: 1, revision: 1, entries: {} })).toBeFalsy();
+        expect(patchedFields()).toEqual([]);
+    });
+});
@@ -31,6 +31,7 @@ vi.mock('../cache.js', () => ({
 
 vi.mock('../dash-downloader.ts', () => ({ DashDownloader: class {} }));
 vi.mock('../hls-downloader.js', () => ({ HlsDownloader: class {} }));
+vi.mock('../resumable-download.js', () => ({ downloadWithRanges: vi.fn(async () => null) }));
 vi.mock('../proxy-utils.js', () => ({ getProxyUrl: vi.fn((url) => url), wrapTidalUrl: vi.fn((url) => url) }));
 vi.mock('../ffmpeg.js', () => ({ loadFfmpeg: vi.fn(), FfmpegError: class extends Error {}, ffmpeg: vi.fn() }));
 vi.mock('../download-utils.ts', () => ({ triggerDownload: vi.fn(), applyAudioPostProcessing: vi.fn() }));
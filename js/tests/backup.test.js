@@ -0,0 +1,135 @@
+import { expect, test, describe, beforeEach, afterEach } from 'vitest';
+import {
+    BACKUP_VERSION,
+    collectBackup,
+    createBackupArchive,
+    diffBackup,
+    getKeySection,
+    migrateBackup,
+    readBackupFile,
+    restoreBackup,
+} from '../backup.js';
+import { db } from '../db.js';
+
+const backupOf = (sections, extra = {}) => ({ version: BACKUP_VERSION, createdAt: null, sections, ...extra });
+
+describe('backup.js', () => {
+    beforeEach(() => {
+        localStorage.clear();
+    });
+
+    afterEach(() => {
+        localStorage.clear();
+    });
+
+    test('keys are sorted into sections and secrets are left out', () => {
+        expect(getKeySection('equalizer-gains')).toBe('equalizer');
+        expect(getKeySection('monochrome-theme')).toBe('themes');
+        expect(getKeySection('blocked-artists')).toBe('blocklists');
+        expect(getKeySection('lyrics-offset-123')).toBe('lyricsOffsets');
+        expect(getKeySection('monochrome-user-api-instances-v1')).toBe('instances');
+        expect(getKeySection('playback-quality')).toBe('settings');
+        expect(getKeySection('listenbrainz-token')).toBeNull();
+        expect(getKeySection('lastfm-session')).toBeNull();
+        expect(getKeySection('monochrome-api-instances-v9')).toBeNull();
+        expect(getKeySection('webdav-sync-url')).toBeNull();
+        expect(getKeySection('webdav-sync-username')).toBeNull();
+        expect(getKeySection('webdav-sync-password')).toBeNull();
+    });
+
+    test('the diff lists what a restore would add, change and remove', () => {
+        const current = backupOf({
+            library: { favorites_tracks: [{ id: 1 }, { id: 2, title: 'Old' }], history_tracks: [] },
+            equalizer: { 'equalizer-gains': '[0]', 'equalizer-preset': 'flat' },
+        });
+        const backup = backupOf({
+            library: { favorites_tracks: [{ id: 2, title: 'New' }, { id: 3 }] },
+            equalizer: { 'equalizer-gains': '[0]', 'equalizer-enabled': 'true' },
+        });
+
+        const [library, equalizer] = diffBackup(current, backup);
+        expect(library).toMatchObject({ id: 'library' });
+        expect(library.added).toEqual(['favorites_tracks/3']);
+        expect(library.changed).toEqual(['favorites_tracks/2']);
+        expect(library.removed).toEqual(['favorites_tracks/1']);
+        expect(equalizer.added).toEqual(['equalizer-enabled']);
+        expect(equalizer.changed).toEqual([]);
+        expect(equalizer.removed).toEqual(['equalizer-preset']);
+    });
+
+    test('exports of the old buttons are migrated', () => {
+        const library = migrateBackup({ version: 0, data: { favorites_tracks: [{ id: 1 }], user_playlists: [] } });
+        expect(library.version).toBe(BACKUP_VERSION);
+        expect(library.sections.library.favorites_tracks).toEqual([{ id: 1 }]);
+        expect(library.sections.library.history_tracks).toEqual([]);
+
+        const settings = migrateBackup({
+            version: 0,
+            data: { 'monochrome-theme': 'dark', 'monochrome-listening-data': { tracks: {} } },
+        });
+        expect(settings.partial).toBe(true);
+        expect(settings.sections).toEqual({
+            themes: { 'monochrome-theme': 'dark' },
+            listening: { 'monochrome-listening-data': '{"tracks":{}}' },
+        });
+
+        expect(() => migrateBackup({ version: BACKUP_VERSION + 1, sections: {} })).toThrow();
+    });
+
+    test('archives read back to the same backup', async () => {
+        const backup = backupOf(
+            {
+                library: { favorites_albums: [{ id: 7, title: 'Album' }] },
+                themes: { 'monochrome-theme': 'ocean' },
+                lyricsOffsets: { 'lyrics-offset-1': '250' },
+            },
+            { createdAt: '2026-01-02T03:04:05.000Z', origin: 'https://music.example.com' }
+        );
+
+        const archive = await createBackupArchive(backup);
+        const restored = await readBackupFile(archive);
+        expect(restored.sections).toEqual(backup.sections);
+        expect(restored.origin).toBe('https://music.example.com');
+
+        const legacy = new Blob([JSON.stringify({ 'monochrome-theme': 'dark' })]);
+        expect((await readBackupFile(legacy)).sections).toEqual({ themes: { 'monochrome-theme': 'dark' } });
+    });
+
+    test('speaker EQ profiles and custom legacy EQ presets survive a backup', async () => {
+        const profiles = { 'Desk speakers': { gains: [1.5, -2, 0], preamp: -3 }, Car: { gains: [3], preamp: 0 } };
+        const presets = JSON.stringify({ Warm: { gains: [2, 1, 0, -1] } });
+        await db.saveSetting('speaker-eq-profiles', profiles);
+        localStorage.setItem('monochrome-legacy-geq-custom-presets', presets);
+
+        const archive = await createBackupArchive(await collectBackup());
+        await db.deleteSetting('speaker-eq-profiles');
+        localStorage.removeItem('monochrome-legacy-geq-custom-presets');
+
+        await restoreBackup(await readBackupFile(archive), ['equalizer']);
+        expect(await db.getSetting('speaker-eq-profiles')).toEqual(profiles);
+        expect(localStorage.getItem('monochrome-legacy-geq-custom-presets')).toBe(presets);
+
+        await db.deleteSetting('speaker-eq-profiles');
+    });
+
+    test('restoring a section leaves the others alone', async () => {
+        localStorage.setItem('equalizer-gains', '[1]');
+        localStorage.setItem('equalizer-preset', 'rock');
+        localStorage.setItem('monochrome-theme', 'light');
+        localStorage.setItem('listenbrainz-token', 'secret');
+
+        const backup = backupOf({
+            equalizer: { 'equalizer-gains': '[2]' },
+            themes: { 'monochrome-theme': 'dark' },
+        });
+        await restoreBackup(backup, ['equalizer']);
+
+        expect(localStorage.getItem('equalizer-gains')).toBe('[2]');
+        expect(localStorage.getItem('equalizer-preset')).toBeNull();
+        expect(localStorage.getItem('monochrome-theme')).toBe('light');
+        expect(localStorage.getItem('listenbrainz-token')).toBe('secret');
+
+        await restoreBackup({ ...backup, partial: true, sections: { equalizer: {} } }, ['equalizer']);
+        expect(localStorage.getItem('equalizer-gains')).toBe('[2]');
+    });
+});
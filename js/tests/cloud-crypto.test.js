@@ -0,0 +1,134 @@
+import { expect, test, describe } from 'vitest';
+import {
+    createEncryptedTransport,
+    EncryptionInfoMissingError,
+    EncryptionLockedError,
+} from '../accounts/cloud-crypto.js';
+
+function createMemoryTransport(initial) {
+    let data = structuredClone(initial);
+    return {
+        load: async () => structuredClone(data),
+        save: async (fields) => {
+            data = { ...data, ...structuredClone(fields) };
+            return structuredClone(data);
+        },
+    };
+}
+
+function createMemoryKeyStore() {
+    let keys = null;
+    return {
+        load: async () => keys,
+        save: async (value) => {
+            keys = value;
+        },
+        clear: async () => {
+            keys = null;
+        },
+    };
+}
+
+const cloudData = () => ({
+    library: { tracks: { 42: { id: 42, title: 'Secret Song' } }, albums: {} },
+    history: [{ id: 42, title: 'Secret Song', timestamp: 1000 }],
+    userPlaylists: {
+        p1: { id: 'p1', name: 'Private Mix', tracks: [{ id: 42, title: 'Secret Song' }] },
+        p2: { id: 'p2', name: 'Shared Mix', isPublic: true, tracks: [{ id: 7, title: 'Open Song' }] },
+    },
+    userFolders: { f1: { id: 'f1', name: 'Folder', playlists: ['p1'] } },
+    settings: { version: 1, revision: 3, entries: { 'monochrome-theme': { value: 'dark', updatedAt: 1 } } },
+});
+
+describe('cloud-crypto.js', () => {
+    test('only public playlists are readable in the cloud copy', async () => {
+        const remote = createMemoryTransport(cloudData());
+        const phone = createEncryptedTransport(remote, createMemoryKeyStore());
+
+        await phone.enable('correct horse battery staple');
+
+        const stored = JSON.stringify(await remote.load());
+        expect(stored).not.toContain('Secret Song');
+        expect(stored).not.toContain('Private Mix');
+        expect(stored).not.toContain('monochrome-theme');
+        expect(stored).toContain('Shared Mix');
+        expect(Object.keys((await remote.load()).library.tracks)).not.toContain('42');
+
+        expect(await phone.load()).toEqual({ ...cloudData(), encryption: (await remote.load()).encryption });
+    });
+
+    test('another device syncs once the passphrase is entered', async () => {
+        const remote = createMemoryTransport(cloudData());
+        const phone = createEncryptedTransport(remote, createMemoryKeyStore());
+        await phone.enable('correct horse battery staple');
+
+        const laptop = createEncryptedTransport(remote, createMemoryKeyStore());
+        await expect(laptop.load()).rejects.toThrow(EncryptionLockedError);
+        expect(laptop.isLocked()).toBe(true);
+        await expect(laptop.unlock('wrong passphrase')).rejects.toThrow('Wrong passphrase');
+
+        await laptop.unlock('correct horse battery staple');
+        const data = await laptop.load();
+        const before = (await remote.load()).library.tracks;
+        data.library.tracks[5] = { id: 5, title: 'Added on laptop' };
+        await laptop.save({ library: data.library });
+
+        // Records that didn't change keep their ciphertext
+        const after = (await remote.load()).library.tracks;
+        Object.keys(before).forEach((id) => expect(after[id]).toEqual(before[id]));
+        const titles = Object.values((await phone.load()).library.tracks).map((t) => t.title);
+        expect(titles.sort()).toEqual(['Added on laptop', 'Secret Song']);
+    });
+
+    test('turning encryption off stores the library readable again', async () => {
+        const remote = createMemoryTransport(cloudData());
+        const phoneKeys = createMemoryKeyStore();
+        const phone = createEncryptedTransport(remote, phoneKeys);
+        await phone.enable('correct horse battery staple');
+
+        const laptop = createEncryptedTransport(remote, createMemoryKeyStore());
+        await laptop.unlock('correct horse battery staple');
+        await laptop.disable();
+
+        const stored = await remote.load();
+        expect(stored.encryption).toBeNull();
+        expect(stored.userPlaylists.p1.name).toBe('Private Mix');
+
+        await phone.load();
+        expect(phone.isEncrypted()).toBe(false);
+        expect(await phoneKeys.load()).toBeNull();
+    });
+
+    test('encryption is rolled back when the server does not store its settings', async () => {
+        const remote = createMemoryTransport(cloudData());
+        const dropsUnknownFields = {
+            load: remote.load,
+            save: async ({ encryption: _encryption, ...fields }) => remote.save(fields),
+        };
+        const phoneKeys = createMemoryKeyStore();
+        const phone = createEncryptedTransport(dropsUnknownFields, phoneKeys);
+
+        await expect(phone.enable('correct horse battery staple')).rejects.toThrow('left unencrypted');
+
+        expect(await remote.load()).toEqual(cloudData());
+        expect(await phoneKeys.load()).toBeNull();
+        expect(phone.isEncrypted()).toBe(false);
+        expect(await phone.load()).toEqual(cloudData());
+    });
+
+    test('encrypted records whose encryption settings went missing are not taken as turned off', async () => {
+        const remote = createMemoryTransport(cloudData());
+        const phoneKeys = createMemoryKeyStore();
+        const phone = createEncryptedTransport(remote, phoneKeys);
+        await phone.enable('correct horse battery staple');
+        const keys = await phoneKeys.load();
+
+        // e.g. a server or another client dropped the field
+        await remote.save({ encryption: null });
+
+        await expect(phone.load()).rejects.toThrow(EncryptionInfoMissingError);
+        await expect(phone.save({ history: [] })).rejects.toThrow(EncryptionInfoMissingError);
+        expect(await phoneKeys.load()).toBe(keys);
+        expect(JSON.stringify(await remote.load())).not.toContain('Secret Song');
+    });
+});
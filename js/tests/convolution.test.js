@@ -0,0 +1,87 @@
+import { expect, test, describe } from 'vitest';
+import {
+    parseWavInfo,
+    getImpulseResponseLayout,
+    getNormalizationScale,
+    sanitizeConvolutionSettings,
+    ImpulseResponseError,
+} from '../convolution.js';
+
+const writeTag = (view, offset, tag) => {
+    for (let i = 0; i < 4; i++) view.setUint8(offset + i, tag.charCodeAt(i));
+};
+
+// Minimal WAV with an optional chunk before 'fmt ' to check chunk skipping
+const makeWav = ({ channels = 2, sampleRate = 48000, bits = 24, formatTag = 1, frames = 480, extraChunk = 0 }) => {
+    const blockAlign = (channels * bits) / 8;
+    const dataSize = frames * blockAlign;
+    const extra = extraChunk ? 8 + extraChunk + (extraChunk % 2) : 0;
+    const buffer = new ArrayBuffer(12 + extra + 24 + 8 + dataSize);
+    const view = new DataView(buffer);
+    writeTag(view, 0, 'RIFF');
+    view.setUint32(4, buffer.byteLength - 8, true);
+    writeTag(view, 8, 'WAVE');
+    let offset = 12;
+    if (extraChunk) {
+        writeTag(view, offset, 'LIST');
+        view.setUint32(offset + 4, extraChunk, true);
+        offset += extra;
+    }
+    writeTag(view, offset, 'fmt ');
+    view.setUint32(offset + 4, 16, true);
+    view.setUint16(offset + 8, formatTag, true);
+    view.setUint16(offset + 10, channels, true);
+    view.setUint32(offset + 12, sampleRate, true);
+    view.setUint32(offset + 16, sampleRate * blockAlign, true);
+    view.setUint16(offset + 20, blockAlign, true);
+    view.setUint16(offset + 22, bits, true);
+    offset += 24;
+    writeTag(view, offset, 'data');
+    view.setUint32(offset + 4, dataSize, true);
+    return buffer;
+};
+
+describe('convolution.js', () => {
+    test('reads format and length from a WAV header', () => {
+        const info = parseWavInfo(makeWav({ channels: 4, sampleRate: 44100, bits: 32, formatTag: 3, frames: 44100 }));
+        expect(info).toMatchObject({ channels: 4, sampleRate: 44100, sampleFormat: 'float', frames: 44100 });
+        expect(info.duration).toBe(1);
+        expect(info.layout).toBe('true-stereo');
+    });
+
+    test('skips unrelated chunks, including odd-sized ones', () => {
+        const info = parseWavInfo(makeWav({ channels: 1, extraChunk: 7 }));
+        expect(info.layout).toBe('mono');
+        expect(info.frames).toBe(480);
+    });
+
+    test('rejects unsupported files', () => {
+        expect(() => parseWavInfo(new ArrayBuffer(64))).toThrow(ImpulseResponseError);
+        expect(() => parseWavInfo(makeWav({ channels: 6 }))).toThrow(/4-channel/);
+        expect(() => parseWavInfo(makeWav({ bits: 12 }))).toThrow(/Unsupported/);
+        expect(() => parseWavInfo(makeWav({ channels: 1, sampleRate: 8000, frames: 8000 * 30 }))).toThrow(/too long/);
+        expect(() => getImpulseResponseLayout(3)).toThrow(ImpulseResponseError);
+    });
+
+    test('normalization matches the Web Audio scale and halves it for true stereo', () => {
+        const impulse = new Float32Array(100);
+        impulse[0] = 1;
+        const mono = getNormalizationScale([impulse], 44100);
+        expect(mono).toBeCloseTo(0.00125 * Math.sqrt(100), 9);
+        const trueStereo = getNormalizationScale([impulse, impulse, impulse, impulse], 44100);
+        expect(trueStereo).toBeCloseTo(mono / 2, 9);
+        // Silent IRs fall back to the minimum power instead of dividing by zero
+        expect(Number.isFinite(getNormalizationScale([new Float32Array(10)], 48000))).toBe(true);
+    });
+
+    test('clamps settings and fills defaults', () => {
+        expect(sanitizeConvolutionSettings({ mix: 3, gain: -100, irId: '' })).toEqual({
+            enabled: false,
+            irId: null,
+            mix: 1,
+            gain: -24,
+            normalize: true,
+        });
+        expect(sanitizeConvolutionSettings({ enabled: true, irId: 'ir_1', normalize: false }).normalize).toBe(false);
+    });
+});
@@ -0,0 +1,270 @@
+import { expect, test, describe, beforeEach, afterEach, vi } from 'vitest';
+import { CrossfadeEngine } from '../crossfade.js';
+import { audioContextManager } from '../audio-context.js';
+import { TRANSITION_MODE } from '../transition-policy.js';
+import { REPEAT_MODE } from '../utils.js';
+
+vi.mock('../ModernSettings.js', () => ({
+    modernSettings: {},
+}));
+
+vi.mock('../icons.js', () => ({
+    SVG_ATMOS: () => '<svg>atmos</svg>',
+}));
+
+vi.mock('../audio-context.js', () => ({
+    audioContextManager: {
+        isReady: vi.fn(() => true),
+        attachCrossfadeSource: vi.fn(() => true),
+        detachCrossfadeSource: vi.fn(),
+        fadeSource: vi.fn(),
+        rampSourceGain: vi.fn(),
+    },
+}));
+
+vi.mock('../storage.js', () => ({
+    qualityBadgeSettings: { isEnabled: vi.fn(() => true) },
+    coverArtSizeSettings: { getSize: vi.fn(() => '1280') },
+    trackDateSettings: { useAlbumYear: vi.fn(() => false) },
+    contentBlockingSettings: { shouldHideTrack: vi.fn(() => false) },
+    gaplessPlaybackSettings: { isEnabled: vi.fn(() => true) },
+    crossfadeSettings: { getDuration: vi.fn(() => 5), isAlbumAware: vi.fn(() => true) },
+}));
+
+vi.mock('../proxy-utils.js', () => ({
+    getProxyUrl: vi.fn((url) => url),
+}));
+
+function createElement({ duration = 300, currentTime = 0 } = {}) {
+    const el = new EventTarget();
+    Object.assign(el, {
+        duration,
+        currentTime,
+        paused: false,
+        seeking: false,
+        ended: false,
+        playbackRate: 1,
+        preservesPitch: true,
+        volume: 1,
+        play: vi.fn(async () => {
+            el.paused = false;
+        }),
+        pause: vi.fn(() => {
+            el.paused = true;
+        }),
+        load: vi.fn(),
+        removeAttribute: vi.fn(),
+    });
+    return el;
+}
+
+// Just what CrossfadeEngine reads from the player, with Player's handling of CUE bounds
+function createPlayer({ current, next, audio, mode = TRANSITION_MODE.CROSSFADE, reason = 'policy' }) {
+    return {
+        audio,
+        activeElement: audio,
+        currentTrack: current,
+        repeatMode: REPEAT_MODE.OFF,
+        preloadCache: new Map(),
+        transitionPolicy: {
+            decide: () => ({ mode, crossfadeDuration: mode === TRANSITION_MODE.CROSSFADE ? 5 : 0, reason }),
+        },
+        getNextTrack: () => next,
+        computeEffectiveVolume: () => 1,
+        getTrackBounds(el) {
+            return { start: this.currentTrack?.cueStart || 0, end: this.currentTrack?.cueEnd || el.duration };
+        },
+        hasReachedTrackEnd(el) {
+            return this.currentTrack?.cueEnd > 0 && el.currentTime >= this.currentTrack.cueEnd;
+        },
+    };
+}
+
+const image = new File(['audio'], 'album.flac', { type: 'audio/flac' });
+const cueTrack = (id, cueStart, cueEnd) => ({ id, isLocal: true, file: image, cueStart, cueEnd });
+
+describe('crossfade.js', () => {
+    beforeEach(() => {
+        vi.useFakeTimers();
+    });
+
+    afterEach(() => {
+        vi.useRealTimers();
+        vi.clearAllMocks();
+    });
+
+    test('starts fading in the next track within the crossfade duration of the end', async () => {
+        const next = { id: 'b', isLocal: true, file: image };
+        const audio = createElement({ duration: 300, currentTime: 290 });
+        const engine = new CrossfadeEngine(createPlayer({ current: { id: 'a' }, next, audio }));
+        engine.element = createElement();
+
+        engine.check();
+        expect(engine.isActive()).toBe(false);
+
+        audio.currentTime = 294;
+        engine.check();
+        expect(engine.isActive()).toBe(true);
+        await vi.advanceTimersByTimeAsync(900);
+        expect(engine.element.play).not.toHaveBeenCalled();
+
+        audio.currentTime = 295;
+        await vi.advanceTimersByTimeAsync(100);
+        expect(engine.element.play).toHaveBeenCalled();
+        expect(engine.element.src).toMatch(/^blob:/);
+
+        const [outgoing, incoming] = audioContextManager.fadeSource.mock.calls;
+        expect(outgoing[0]).toBe(audio);
+        expect(outgoing[1][0]).toBe(1);
+        expect(outgoing[2]).toBe(5);
+        expect(incoming[0]).toBe(engine.element);
+        expect(incoming[1][0]).toBe(0);
+        expect(incoming[2]).toBe(5);
+    });
+
+    test('cancelling a fade releases the secondary element and restores the main one', async () => {
+        const next = { id: 'b', isLocal: true, file: image };
+        const audio = createElement({ duration: 300, currentTime: 295 });
+        const engine = new CrossfadeEngine(createPlayer({ current: { id: 'a' }, next, audio }));
+        engine.element = createElement();
+        const revokeObjectURL = vi.spyOn(URL, 'revokeObjectURL');
+
+        engine.check();
+        await vi.advanceTimersByTimeAsync(0);
+        const objectUrl = engine.element.src;
+
+        engine.cancel();
+        expect(engine.isActive()).toBe(false);
+        expect(engine.element.pause).toHaveBeenCalled();
+        expect(engine.element.removeAttribute).toHaveBeenCalledWith('src');
+        expect(engine.element.load).toHaveBeenCalled();
+        expect(revokeObjectURL).toHaveBeenCalledWith(objectUrl);
+        expect(audioContextManager.detachCrossfadeSource).toHaveBeenCalled();
+        expect(audioContextManager.rampSourceGain).toHaveBeenCalledWith(audio, 1, 0.03);
+
+        revokeObjectURL.mockRestore();
+    });
+
+    test('hands the running fade over at the position the next track has reached', async () => {
+        const next = { id: 'b', isLocal: true, file: image };
+        const audio = createElement({ duration: 300, currentTime: 295 });
+        const engine = new CrossfadeEngine(createPlayer({ current: { id: 'a' }, next, audio }));
+        engine.element = createElement();
+
+        // Nothing to hand over before the fade started
+        engine.check();
+        expect(engine.takeHandoff(next, audio)).toBeNull();
+        expect(engine.isActive()).toBe(false);
+
+        engine.check();
+        await vi.advanceTimersByTimeAsync(0);
+        engine.element.currentTime = 4.75;
+
+        // Another track was picked in the meantime
+        expect(engine.takeHandoff({ id: 'c' }, audio)).toBeNull();
+        expect(engine.isActive()).toBe(false);
+
+        engine.check();
+        await vi.advanceTimersByTimeAsync(0);
+        engine.element.currentTime = 4.75;
+        expect(engine.takeHandoff(next, audio)).toBe(4.75);
+        expect(engine.isActive()).toBe(true);
+    });
+
+    test('tracks split from a CUE sheet fade at their cue end and hand over relative to their cue start', async () => {
+        // Track 2 of a 600 s image ends at 200 s, where track 3 begins
+        const current = cueTrack('cue-2', 100, 200);
+        const next = cueTrack('cue-3', 200, 320);
+        const audio = createElement({ duration: 600, currentTime: 150 });
+        const player = createPlayer({ current, next, audio });
+        const engine = new CrossfadeEngine(player);
+        engine.element = createElement();
+
+        // 50 s before the cue end is too early, even though the image goes on for much longer
+        engine.check();
+        expect(engine.isActive()).toBe(false);
+
+        audio.currentTime = 196;
+        engine.check();
+        expect(engine.isActive()).toBe(true);
+
+        await vi.advanceTimersByTimeAsync(1000);
+        expect(engine.element.currentTime).toBe(200);
+        expect(engine.element.play).toHaveBeenCalled();
+        const [, , fadeDuration] = audioContextManager.fadeSource.mock.calls[0];
+        expect(fadeDuration).toBe(4);
+
+        // Pausing at the cue end is how the track ends, so the fade keeps going
+        audio.currentTime = 200;
+        audio.pause();
+        audio.dispatchEvent(new Event('pause'));
+        expect(engine.isActive()).toBe(true);
+
+        engine.element.currentTime = 203.5;
+        expect(engine.takeHandoff(next, audio)).toBe(3.5);
+    });
+
+    test('album runs kept gapless load the next track ahead and start it at full level as the current one ends', () => {
+        const current = { id: 'a-1', isLocal: true, file: image };
+        const next = { id: 'a-2', isLocal: true, file: image };
+        const audio = createElement({ duration: 300, currentTime: 280 });
+        const player = createPlayer({ current, next, audio, mode: TRANSITION_MODE.GAPLESS, reason: 'album' });
+        const engine = new CrossfadeEngine(player);
+        engine.element = createElement();
+
+        engine.check();
+        expect(engine.isActive()).toBe(false);
+
+        audio.currentTime = 295;
+        engine.check();
+        expect(engine.isActive()).toBe(true);
+        expect(engine.element.src).toMatch(/^blob:/);
+        expect(engine.element.play).not.toHaveBeenCalled();
+        expect(audioContextManager.rampSourceGain).toHaveBeenCalledWith(engine.element, 1, 0);
+
+        audio.currentTime = 300;
+        audio.paused = true;
+        audio.ended = true;
+        audio.dispatchEvent(new Event('ended'));
+        expect(engine.element.play).toHaveBeenCalledTimes(1);
+        expect(audioContextManager.rampSourceGain).toHaveBeenCalledWith(audio, 0, 0);
+        expect(audioContextManager.fadeSource).not.toHaveBeenCalled();
+
+        engine.element.currentTime = 0.25;
+        expect(engine.takeHandoff(next, audio)).toBe(0.25);
+        expect(engine.element.play).toHaveBeenCalledTimes(1);
+    });
+
+    test('other gapless transitions are left to the player', () => {
+        const next = { id: 'b', isLocal: true, file: image };
+        const audio = createElement({ duration: 300, currentTime: 298 });
+        const player = createPlayer({ current: { id: 'a' }, next, audio, mode: TRANSITION_MODE.GAPLESS });
+        const engine = new CrossfadeEngine(player);
+        engine.element = createElement();
+
+        engine.check();
+        expect(engine.isActive()).toBe(false);
+        expect(engine.element.src).toBeUndefined();
+    });
+
+    test('a gapless transition nothing claims is released and the main element restored', async () => {
+        const next = { id: 'a-2', isLocal: true, file: image };
+        const audio = createElement({ duration: 300, currentTime: 295 });
+        const current = { id: 'a-1' };
+        const player = createPlayer({ current, next, audio, mode: TRANSITION_MODE.GAPLESS, reason: 'album' });
+        const engine = new CrossfadeEngine(player);
+        engine.element = createElement();
+
+        engine.check();
+        audio.currentTime = 300;
+        audio.ended = true;
+        audio.dispatchEvent(new Event('ended'));
+        expect(engine.element.play).toHaveBeenCalled();
+
+        // e.g. the next track failed to resolve, or playback was stopped
+        await vi.advanceTimersByTimeAsync(8000);
+        expect(engine.isActive()).toBe(false);
+        expect(engine.element.pause).toHaveBeenCalled();
+        expect(audioContextManager.rampSourceGain).toHaveBeenCalledWith(audio, 1, 0.03);
+    });
+});
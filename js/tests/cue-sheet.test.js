@@ -0,0 +1,101 @@
+import { expect, test, describe } from 'vitest';
+import { decodeCueSheet, findCueImagePath, getCueTracks, parseCueSheet, parseCueTime } from '../cue-sheet.js';
+import { parseFlacCueSheetBlock } from '../metadata.flac.js';
+
+const SHEET = `REM GENRE Electronic
+REM DATE 2001
+PERFORMER "Daft Punk"
+TITLE "Discovery"
+FILE "Daft Punk - Discovery.wav" WAVE
+  TRACK 01 AUDIO
+    TITLE "One More Time"
+    ISRC GBDUW0000053
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE "Aerodynamic"
+    PERFORMER "Daft Punk feat. Nobody"
+    INDEX 00 05:19:70
+    INDEX 01 05:20:30
+  TRACK 03 AUDIO
+    TITLE "Digital Love"
+    INDEX 01 08:52:00
+`;
+
+describe('cue-sheet.js', () => {
+    test('parses album and track fields', () => {
+        const cue = parseCueSheet(SHEET.replace(/\n/g, '\r\n'));
+
+        expect(cue.title).toBe('Discovery');
+        expect(cue.performer).toBe('Daft Punk');
+        expect(cue.date).toBe('2001');
+        expect(cue.genre).toBe('Electronic');
+        expect(cue.files).toHaveLength(1);
+        expect(cue.files[0].name).toBe('Daft Punk - Discovery.wav');
+        expect(cue.files[0].tracks.map((track) => track.title)).toEqual([
+            'One More Time',
+            'Aerodynamic',
+            'Digital Love',
+        ]);
+        expect(cue.files[0].tracks[0].isrc).toBe('GBDUW0000053');
+        expect(cue.files[0].tracks[1].performer).toBe('Daft Punk feat. Nobody');
+        expect(parseCueTime('05:20:30')).toBeCloseTo(320.4);
+        expect(parseCueTime('bad')).toBeNull();
+    });
+
+    test('splits the image at INDEX 01 and ends the last track with the file', () => {
+        const tracks = getCueTracks(parseCueSheet(SHEET), 960);
+
+        expect(tracks.map((track) => [track.number, track.start, track.end])).toEqual([
+            [1, 0, 320.4],
+            [2, 320.4, 532],
+            [3, 532, 960],
+        ]);
+        expect(getCueTracks(parseCueSheet(SHEET))[2].end).toBeNull();
+    });
+
+    test('finds the image next to the sheet, also after it was re-encoded', () => {
+        const cue = parseCueSheet(SHEET);
+        const paths = new Set(['Discovery/Daft Punk - Discovery.flac', 'Other/Daft Punk - Discovery.wav']);
+
+        expect(findCueImagePath(cue, 'Discovery/Discovery.cue', paths)).toBe('Discovery/Daft Punk - Discovery.flac');
+        expect(findCueImagePath(cue, 'Missing/Discovery.cue', paths)).toBeNull();
+
+        const renamed = new Set(['Discovery.flac']);
+        expect(findCueImagePath(cue, 'Discovery.cue', renamed)).toBe('Discovery.flac');
+    });
+
+    test('falls back to Windows-1252 for sheets that are not UTF-8', () => {
+        const bytes = new Uint8Array([0x54, 0x49, 0x54, 0x4c, 0x45, 0x20, 0x22, 0x42, 0x6a, 0xf6, 0x72, 0x6b, 0x22]);
+        expect(parseCueSheet(decodeCueSheet(bytes.buffer)).title).toBe('Björk');
+    });
+
+    test('reads FLAC CUESHEET blocks', () => {
+        const trackSize = (indexCount) => 36 + indexCount * 12;
+        const block = new Uint8Array(396 + trackSize(1) * 2 + trackSize(0));
+        const view = new DataView(block.buffer);
+        view.setUint8(395, 3);
+
+        let pos = 396;
+        const writeTrack = (offset, number, isrc, indexOffsets) => {
+            view.setUint32(pos + 4, offset);
+            view.setUint8(pos + 8, number);
+            block.set(new TextEncoder().encode(isrc), pos + 9);
+            view.setUint8(pos + 35, indexOffsets.length);
+            pos += 36;
+            for (const indexOffset of indexOffsets) {
+                view.setUint32(pos + 4, indexOffset);
+                view.setUint8(pos + 8, 1);
+                pos += 12;
+            }
+        };
+        writeTrack(0, 1, 'GBDUW0000053', [0]);
+        writeTrack(441000, 2, '', [44100]);
+        writeTrack(882000, 170, '', []);
+
+        const cue = parseFlacCueSheetBlock(view, 44100, 'image.flac');
+        expect(cue.files[0].tracks).toEqual([
+            { number: 1, title: null, performer: null, isrc: 'GBDUW0000053', start: 0 },
+            { number: 2, title: null, performer: null, isrc: null, start: 11 },
+        ]);
+    });
+});
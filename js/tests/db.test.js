@@ -87,6 +87,70 @@ describe('MusicDatabase', () => {
         expect(deleted).toBeUndefined();
     });
 
+    test('playlist snapshots: changes are recorded and can be restored', async () => {
+        const playlist = await db.createPlaylist('History', [
+            { id: 'a', title: 'A' },
+            { id: 'b', title: 'B' },
+        ]);
+        await db.addTrackToPlaylist(playlist.id, { id: 'c', title: 'C' });
+        await db.updatePlaylistTracks(playlist.id, []);
+
+        let snapshots = await db.getPlaylistSnapshots(playlist.id);
+        expect(snapshots.map((s) => s.action)).toEqual(['edit', 'add', 'create']);
+        expect(snapshots[1].tracks.map((t) => t.id)).toEqual(['a', 'b', 'c']);
+
+        const named = await db.savePlaylistSnapshot(playlist.id, 'Before cleanup');
+        expect(named.name).toBe('Before cleanup');
+
+        const restored = await db.restorePlaylistSnapshot(snapshots[1].id);
+        expect(restored.tracks.map((t) => t.id)).toEqual(['a', 'b', 'c']);
+
+        snapshots = await db.getPlaylistSnapshots(playlist.id);
+        expect(snapshots.map((s) => s.action)).toEqual(['restore', 'manual', 'edit', 'add', 'create']);
+
+        await db.deletePlaylist(playlist.id);
+        expect(await db.getPlaylistSnapshots(playlist.id)).toEqual([]);
+    });
+
+    test('sync queue: edits are queued as operations until pushed', async () => {
+        db.isSyncActive = () => true;
+        const playlist = await db.createPlaylist('Queued', [{ id: 'a', title: 'A' }]);
+        await db.addTrackToPlaylist(playlist.id, { id: 'b', title: 'B' });
+        await db.updatePlaylistTracks(playlist.id, [{ id: 'b', title: 'B' }], 'reorder');
+        await db.toggleFavorite('album', { id: 'al1', title: 'Album' });
+
+        const ops = await db.getQueuedSyncOps();
+        expect(ops.map((op) => `${op.type} ${op.key}`)).toEqual([
+            'add track:a',
+            'add track:b',
+            'remove track:a',
+            'add al1',
+        ]);
+        expect(ops[3].target).toBe('library:albums');
+
+        // Tracks written by sync are already in the cloud
+        await db.updatePlaylistTracks(playlist.id, [], 'sync');
+        expect((await db.getQueuedSyncOps(`playlist:${playlist.id}`)).length).toBe(3);
+
+        await db.removeQueuedSyncOps(ops.slice(0, 2).map((op) => op.id));
+        await db.deletePlaylist(playlist.id);
+        expect((await db.getQueuedSyncOps()).map((op) => op.key)).toEqual(['al1']);
+    });
+
+    test('sync queue: nothing is queued while not syncing, and overflowing it asks for a full push', async () => {
+        await db.toggleFavorite('album', { id: 'al1', title: 'Album' });
+        expect(await db.getQueuedSyncOps()).toEqual([]);
+
+        db.isSyncActive = () => true;
+        const tracks = Array.from({ length: 5001 }, (_, i) => ({ id: `t${i}`, title: `Track ${i}` }));
+        await db.createPlaylist('Huge', tracks);
+        expect(await db.getQueuedSyncOps()).toEqual([]);
+        expect(await db.hasSyncQueueOverflowed()).toBe(true);
+
+        await db.setSyncQueueOverflowed(false);
+        expect(await db.hasSyncQueueOverflowed()).toBe(false);
+    });
+
     test('pinned items management', async () => {
         const album = { id: 'album1', title: 'Album 1', type: 'album' };
 
@@ -104,4 +168,70 @@ describe('MusicDatabase', () => {
         expect(pinned.some((p) => p.id === 'a4')).toBe(true);
         expect(pinned.some((p) => p.id === 'album1')).toBe(false);
     });
+
+    test('impulse responses: save, list and delete', async () => {
+        const data = new Uint8Array([1, 2, 3, 4]).buffer;
+        const saved = await db.saveImpulseResponse('Room', data, { channels: 2, sampleRate: 48000 });
+        expect(saved.id).toMatch(/^ir_/);
+
+        const list = await db.getImpulseResponses();
+        expect(list.length).toBe(1);
+        expect(list[0].name).toBe('Room');
+        expect(list[0].channels).toBe(2);
+
+        const loaded = await db.getImpulseResponse(saved.id);
+        expect(new Uint8Array(loaded.data)).toEqual(new Uint8Array([1, 2, 3, 4]));
+
+        await db.deleteImpulseResponse(saved.id);
+        expect(await db.getImpulseResponses()).toEqual([]);
+    });
+
+    test('download queue: jobs, items and staged blobs are removed together', async () => {
+        await db.saveDownloadJob({ id: 'job1', name: 'Album', createdAt: 1 });
+        await db.saveDownloadItems([
+            { id: 'job1_1', jobId: 'job1', order: 1 },
+            { id: 'job1_0', jobId: 'job1', order: 0 },
+            { id: 'job2_0', jobId: 'job2', order: 0 },
+        ]);
+        await db.saveDownloadBlob('job1_0', new Blob(['audio']));
+
+        const items = await db.getDownloadItems('job1');
+        expect(items.map((item) => item.id)).toEqual(['job1_0', 'job1_1']);
+        expect(await (await db.getDownloadBlob('job1_0')).text()).toBe('audio');
+
+        await db.deleteDownloadJob('job1');
+        expect(await db.getDownloadJobs()).toEqual([]);
+        expect(await db.getDownloadItems('job1')).toEqual([]);
+        expect(await db.getDownloadBlob('job1_0')).toBeUndefined();
+        expect((await db.getDownloadItems('job2')).length).toBe(1);
+    });
+
+    test('download checkpoints: deleting a checkpoint drops its chunks', async () => {
+        await db.saveDownloadCheckpoint({ key: 'track_1', fingerprint: 'bytes:10', completed: [0, 1], updatedAt: 1 });
+        await db.saveDownloadChunk('track_1#0', new Blob(['abc']));
+        await db.saveDownloadChunk('track_1#1', new Blob(['def']));
+        await db.saveDownloadChunk('track_10#0', new Blob(['other']));
+
+        expect((await db.getDownloadCheckpoint('track_1')).completed).toEqual([0, 1]);
+        expect(await (await db.getDownloadChunk('track_1#1')).text()).toBe('def');
+
+        await db.deleteDownloadCheckpoint('track_1');
+        expect(await db.getDownloadCheckpoint('track_1')).toBeUndefined();
+        expect(await db.getDownloadChunk('track_1#0')).toBeUndefined();
+        expect(await (await db.getDownloadChunk('track_10#0')).text()).toBe('other');
+    });
+
+    test('local library: entries are listed per root folder', async () => {
+        await db.saveLocalLibraryEntries([
+            { key: 'main:a.flac', rootId: 'main', path: 'a.flac', size: 1 },
+            { key: 'main:b/c.mp3', rootId: 'main', path: 'b/c.mp3', size: 2 },
+            { key: 'root-1:d.ogg', rootId: 'root-1', path: 'd.ogg', size: 3 },
+        ]);
+
+        expect((await db.getLocalLibraryEntries('main')).map((entry) => entry.path)).toEqual(['a.flac', 'b/c.mp3']);
+        expect((await db.getLocalLibraryEntries()).length).toBe(3);
+
+        await db.deleteLocalLibraryEntries(['main:a.flac']);
+        expect((await db.getLocalLibraryEntries('main')).map((entry) => entry.path)).toEqual(['b/c.mp3']);
+    });
 });
@@ -0,0 +1,90 @@
+import { expect, test, describe } from 'vitest';
+import {
+    getRetryDelay,
+    selectRunnableItems,
+    getNextRetryAt,
+    summarizeItems,
+    isJobComplete,
+    createFileChannel,
+    getJobOutput,
+} from '../download-queue.js';
+
+const item = (id, status, extra = {}) => ({ id, status, nextAttemptAt: 0, ...extra });
+
+describe('download-queue.js', () => {
+    test('backs off exponentially up to a cap, longer when rate limited', () => {
+        expect(getRetryDelay(1)).toBe(5000);
+        expect(getRetryDelay(2)).toBe(10000);
+        expect(getRetryDelay(4)).toBe(40000);
+        expect(getRetryDelay(20)).toBe(5 * 60 * 1000);
+        expect(getRetryDelay(1, true)).toBe(60000);
+    });
+
+    test('starts items oldest job first and skips paused jobs and pending retries', () => {
+        const jobs = [
+            { id: 'paused', status: 'paused' },
+            { id: 'a', status: 'queued' },
+            { id: 'b', status: 'queued' },
+        ];
+        const itemsByJob = new Map([
+            ['paused', [item('p0', 'queued')]],
+            [
+                'a',
+                [
+                    item('a0', 'done'),
+                    item('a1', 'retrying', { nextAttemptAt: 2000 }),
+                    item('a2', 'queued'),
+                    item('a3', 'paused'),
+                    item('a4', 'retrying', { nextAttemptAt: 500 }),
+                ],
+            ],
+            ['b', [item('b0', 'queued'), item('b1', 'queued')]],
+        ]);
+
+        const picked = selectRunnableItems(jobs, itemsByJob, 3, 1000).map((i) => i.id);
+        expect(picked).toEqual(['a2', 'a4', 'b0']);
+        expect(selectRunnableItems(jobs, itemsByJob, 0, 1000)).toEqual([]);
+        expect(getNextRetryAt(jobs, itemsByJob)).toBe(500);
+    });
+
+    test('a job is complete once releases are expanded and no track is left to try', () => {
+        const items = [item('0', 'done', { size: 10 }), item('1', 'failed')];
+        expect(isJobComplete({ pendingReleases: [] }, items)).toBe(true);
+        expect(isJobComplete({ pendingReleases: [{ id: 1 }] }, items)).toBe(false);
+        expect(isJobComplete({}, [...items, item('2', 'paused')])).toBe(false);
+
+        const summary = summarizeItems([...items, item('2', 'retrying'), item('3', 'active')]);
+        expect(summary).toEqual({ total: 4, done: 1, failed: 1, active: 1, paused: 0, waiting: 1, bytes: 10 });
+    });
+
+    test('streaming writers get each track as it finishes, others have it staged', async () => {
+        expect(getJobOutput({ streaming: 'files' })).toBe('files');
+        expect(getJobOutput({ streaming: 'archive' })).toBe('archive');
+        expect(getJobOutput({})).toBe('staged');
+
+        const channel = createFileChannel();
+        const written = [];
+        const writing = (async () => {
+            for await (const file of channel) written.push(file.name);
+        })();
+
+        await channel.push([{ name: 'Album/01 - One.flac' }, { name: 'Album/01 - One.lrc' }]);
+        // The first track is out before the second one has even finished
+        expect(written).toEqual(['Album/01 - One.flac', 'Album/01 - One.lrc']);
+
+        await channel.push([{ name: 'Album/02 - Two.flac' }]);
+        channel.close();
+        await writing;
+        expect(written).toHaveLength(3);
+    });
+
+    test('a failed output rejects the tracks waiting for it', async () => {
+        const channel = createFileChannel();
+        const waiting = channel.push([{ name: 'Album/01 - One.flac' }]);
+        channel.fail(new Error('Disk full'));
+
+        await expect(waiting).rejects.toThrow('Disk full');
+        await expect(channel[Symbol.asyncIterator]().next()).rejects.toThrow('Disk full');
+        await expect(channel.push([{ name: 'Album/02 - Two.flac' }])).rejects.toThrow('Disk full');
+    });
+});
@@ -0,0 +1,103 @@
+import { expect, test, describe, beforeEach, afterEach, vi } from 'vitest';
+import { chooseDownloadQuality } from '../downloads.js';
+import { downloadQualitySettings } from '../storage.js';
+
+vi.mock('../storage.js', () => ({
+    downloadQualitySettings: { shouldAsk: vi.fn(() => true) },
+    lyricsSettings: {},
+    playlistSettings: {},
+    offlineSettings: {},
+    losslessContainerSettings: {},
+    qualityBadgeSettings: { isEnabled: vi.fn(() => true) },
+    coverArtSizeSettings: { getSize: vi.fn(() => '1280') },
+    trackDateSettings: { useAlbumYear: vi.fn(() => false) },
+}));
+
+vi.mock('../ModernSettings.js', () => ({
+    modernSettings: {},
+    BulkDownloadMethod: {},
+}));
+
+vi.mock('../db.js', () => ({ db: {} }));
+vi.mock('../offline-library.js', () => ({ offlineLibrary: {} }));
+vi.mock('../side-panel.js', () => ({ sidePanelManager: {} }));
+vi.mock('../music-api.js', () => ({ MusicAPI: {} }));
+vi.mock('../lyrics.js', () => ({ LyricsManager: {} }));
+vi.mock('../download-queue.js', () => ({
+    createFileChannel: vi.fn(),
+    downloadQueue: { init: vi.fn(() => Promise.resolve()), getJobs: vi.fn(() => []) },
+    summarizeItems: vi.fn(),
+}));
+
+const listedProfiles = () =>
+    [...document.querySelectorAll('#download-profile-list .download-profile-option')].map((option) => ({
+        quality: option.dataset.quality,
+        name: option.querySelector('span').textContent,
+    }));
+
+const pick = (quality) =>
+    document.querySelector(`#download-profile-list [data-quality="${quality}"] span`).dispatchEvent(
+        new MouseEvent('click', { bubbles: true })
+    );
+
+describe('downloads.js', () => {
+    beforeEach(() => {
+        document.body.innerHTML = `
+            <div id="download-profile-modal" class="modal">
+                <div class="modal-overlay"></div>
+                <div id="download-profile-list"></div>
+                <button id="download-profile-cancel">Cancel</button>
+            </div>
+        `;
+    });
+
+    afterEach(() => {
+        document.body.innerHTML = '';
+        vi.clearAllMocks();
+    });
+
+    describe('chooseDownloadQuality', () => {
+        test('offers the default quality, the lossless qualities and the transcoding profiles', async () => {
+            const choice = chooseDownloadQuality('HIGH');
+
+            expect(document.getElementById('download-profile-modal').classList.contains('active')).toBe(true);
+            expect(listedProfiles()).toEqual([
+                { quality: 'HIGH', name: 'AAC 320kbps' },
+                { quality: 'HI_RES_LOSSLESS', name: 'Hi-Res Lossless' },
+                { quality: 'LOSSLESS', name: 'Lossless' },
+                { quality: 'FFMPEG_OPUS_160', name: 'Opus 160k' },
+                { quality: 'FFMPEG_MP3_V0', name: 'MP3 V0' },
+                { quality: 'FFMPEG_AAC_256', name: 'AAC 256k' },
+            ]);
+
+            pick('FFMPEG_MP3_V0');
+            expect(await choice).toBe('FFMPEG_MP3_V0');
+            expect(document.getElementById('download-profile-modal').classList.contains('active')).toBe(false);
+        });
+
+        test('lists a default that is also a profile once', async () => {
+            const choice = chooseDownloadQuality('LOSSLESS');
+            expect(listedProfiles().filter((profile) => profile.quality === 'LOSSLESS')).toHaveLength(1);
+            expect(listedProfiles()[0]).toEqual({ quality: 'LOSSLESS', name: 'Lossless' });
+
+            document.getElementById('download-profile-cancel').click();
+            expect(await choice).toBeNull();
+        });
+
+        test('a picker that is still open is dismissed by the next one', async () => {
+            const first = chooseDownloadQuality('LOSSLESS');
+            const second = chooseDownloadQuality('HI_RES_LOSSLESS');
+            expect(await first).toBeNull();
+
+            pick('FFMPEG_OPUS_160');
+            expect(await second).toBe('FFMPEG_OPUS_160');
+        });
+
+        test('uses the default quality without asking when the picker is turned off', async () => {
+            downloadQualitySettings.shouldAsk.mockReturnValueOnce(false);
+
+            expect(await chooseDownloadQuality('LOSSLESS')).toBe('LOSSLESS');
+            expect(document.getElementById('download-profile-modal').classList.contains('active')).toBe(false);
+        });
+    });
+});
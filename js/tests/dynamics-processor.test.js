@@ -0,0 +1,91 @@
+import { expect, test, describe } from 'vitest';
+import { DynamicsProcessor, compressorGainDb, sanitizeDynamicsSettings } from '../dynamics-processor.js';
+
+const SAMPLE_RATE = 48000;
+const BLOCK = 128;
+
+const sine = (seconds, frequency, amplitude) => {
+    const data = new Float32Array(Math.round(SAMPLE_RATE * seconds));
+    for (let i = 0; i < data.length; i++) {
+        data[i] = amplitude * Math.sin((2 * Math.PI * frequency * i) / SAMPLE_RATE);
+    }
+    return data;
+};
+
+const run = (processor, channels) => {
+    const outputs = channels.map((data) => new Float32Array(data.length));
+    for (let start = 0; start < channels[0].length; start += BLOCK) {
+        processor.process(
+            channels.map((data) => data.subarray(start, start + BLOCK)),
+            outputs.map((data) => data.subarray(start, start + BLOCK))
+        );
+    }
+    return outputs;
+};
+
+const peakDb = (data, from = 0) => {
+    let peak = 0;
+    for (let i = from; i < data.length; i++) peak = Math.max(peak, Math.abs(data[i]));
+    return 20 * Math.log10(peak);
+};
+
+describe('dynamics-processor.js', () => {
+    test('static curve applies the ratio above threshold with a soft knee', () => {
+        expect(compressorGainDb(-30, -20, 4, 0)).toBe(0);
+        expect(compressorGainDb(0, -20, 4, 0)).toBeCloseTo(-15, 6);
+        // Knee midpoint gets a quarter of the knee's worth of reduction
+        expect(compressorGainDb(-20, -20, 4, 6)).toBeCloseTo(-0.5625, 6);
+        expect(compressorGainDb(-17, -20, 4, 6)).toBeCloseTo(compressorGainDb(-17, -20, 4, 0), 6);
+    });
+
+    test('clamps settings to supported ranges', () => {
+        const settings = sanitizeDynamicsSettings({ ratio: 100, ceiling: 3, lookahead: 'x', limiterEnabled: true });
+        expect(settings.ratio).toBe(20);
+        expect(settings.ceiling).toBe(0);
+        expect(settings.lookahead).toBe(5);
+        expect(settings.compressorEnabled).toBe(false);
+        expect(settings.limiterEnabled).toBe(true);
+    });
+
+    test('limiter keeps peaks under the ceiling, including sudden transients', () => {
+        const signal = sine(0.5, 440, 0.3);
+        for (let i = 12000; i < 12010; i++) signal[i] = i % 2 ? -1.8 : 1.8;
+
+        const processor = new DynamicsProcessor(SAMPLE_RATE, { limiterEnabled: true, ceiling: -1 });
+        const [output] = run(processor, [signal]);
+        expect(peakDb(output)).toBeLessThanOrEqual(-1 + 1e-4);
+        expect(processor.takeMeter().limiter).toBeGreaterThan(6);
+    });
+
+    test('limiter is transparent below the ceiling apart from its latency', () => {
+        const signal = sine(0.1, 1000, 0.5);
+        const processor = new DynamicsProcessor(SAMPLE_RATE, { limiterEnabled: true, lookahead: 5 });
+        const [output] = run(processor, [signal]);
+        const latency = processor.getLatency();
+        expect(latency).toBe(240);
+        for (let i = latency; i < signal.length; i += 97) {
+            expect(output[i]).toBeCloseTo(signal[i - latency], 6);
+        }
+        expect(processor.takeMeter().limiter).toBe(0);
+    });
+
+    test('compressor settles near the static curve and reports its reduction', () => {
+        const processor = new DynamicsProcessor(SAMPLE_RATE, {
+            compressorEnabled: true,
+            threshold: -20,
+            ratio: 4,
+            knee: 0,
+            attack: 1,
+            release: 500,
+        });
+        const [output] = run(processor, [sine(1, 1000, 1)]);
+        expect(peakDb(output, SAMPLE_RATE / 2)).toBeCloseTo(-15, 0);
+        expect(processor.takeMeter().compressor).toBeGreaterThan(14);
+    });
+
+    test('passes audio through untouched when both stages are off', () => {
+        const signal = sine(0.01, 440, 1.5);
+        const [output] = run(new DynamicsProcessor(SAMPLE_RATE), [signal]);
+        expect(Array.from(output)).toEqual(Array.from(signal));
+    });
+});
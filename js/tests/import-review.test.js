@@ -0,0 +1,83 @@
+import { expect, test, describe } from 'vitest';
+import { applyImportReview, createImportMatch } from '../playlist-importer.js';
+import { addSearchResults, chooseMatch, needsReview } from '../import-review.js';
+
+const track = (id, title, artist, extra = {}) => ({
+    id,
+    title,
+    artists: [{ name: artist }],
+    album: { title: 'Album' },
+    ...extra,
+});
+
+describe('import-review.js', () => {
+    test('records the score of the pick and the ranked alternatives', () => {
+        const source = { title: 'Hurt', artist: 'Johnny Cash', album: 'American IV' };
+        const cover = track(1, 'Hurt', 'Nine Inch Nails');
+        const original = track(2, 'Hurt', 'Johnny Cash', { album: { title: 'American IV: The Man Comes Around' } });
+
+        const entry = createImportMatch(source, cover, [cover, original, track(3, 'Other', 'Someone')]);
+        expect(entry.match).toBe(cover);
+        expect(entry.score).toBe(0.5);
+        expect(entry.candidates.map((candidate) => candidate.track.id)).toEqual([2, 1, 3]);
+        expect(entry.candidates[0].score).toBeGreaterThan(0.9);
+        expect(needsReview(entry)).toBe(true);
+
+        const isrcEntry = createImportMatch({ ...source, isrc: 'US-SM1-02-00001' }, original, [
+            track(4, 'Hurt', 'Johnny Cash', { isrc: 'USSM10200001' }),
+        ]);
+        expect(isrcEntry.candidates[0]).toMatchObject({ score: 1 });
+        expect(isrcEntry.candidates).toHaveLength(2);
+    });
+
+    test('swaps, accepts and searches matches', () => {
+        const source = { title: 'Hurt', artist: 'Johnny Cash' };
+        const cover = track(1, 'Hurt', 'Nine Inch Nails');
+        const entry = createImportMatch(source, cover, [cover]);
+
+        chooseMatch(entry, null);
+        expect(entry).toMatchObject({ match: null, score: 0, reviewed: true });
+        expect(needsReview(entry)).toBe(false);
+
+        expect(addSearchResults(entry, [])).toBe(false);
+        expect(addSearchResults(entry, [track(2, 'Hurt', 'Johnny Cash'), cover])).toBe(true);
+        expect(entry.match.id).toBe(2);
+        expect(entry.score).toBe(0.9);
+        expect(entry.candidates.map((candidate) => candidate.track.id)).toEqual([2, 1]);
+
+        const missing = createImportMatch(source, null);
+        expect(needsReview(missing)).toBe(true);
+    });
+
+    test('rebuilds tracks, playlists and missing items from the review', () => {
+        const first = track(1, 'One', 'A');
+        const second = track(2, 'Two', 'B');
+        const matches = [
+            createImportMatch({ title: 'One', artist: 'A', playlistName: 'Mix', isFavorite: true }, first, [first]),
+            createImportMatch({ title: 'Two', artist: 'B', playlistName: 'Mix' }, second, [second]),
+        ];
+        const result = {
+            tracks: [first, second],
+            albums: [],
+            artists: [],
+            missingItems: [{ type: 'album', title: 'Lost', artist: 'C' }],
+            playlists: { Mix: [first, second] },
+            matches,
+            stats: { totalItems: 3, tracksFound: 2, missingCount: 1, playlistCount: 1 },
+        };
+
+        chooseMatch(matches[1], null);
+        const reviewed = applyImportReview(result);
+        expect(reviewed.tracks).toEqual([{ ...first, isFavorite: true }]);
+        expect(reviewed.playlists.Mix.map((t) => t.id)).toEqual([1]);
+        expect(reviewed.missingItems).toEqual([
+            { type: 'album', title: 'Lost', artist: 'C' },
+            { type: 'track', title: 'Two', artist: 'B', album: undefined },
+        ]);
+        expect(reviewed.stats).toMatchObject({ tracksFound: 1, missingCount: 2 });
+
+        const simple = applyImportReview({ tracks: [first, second], missingTracks: [], matches });
+        expect(simple.missingTracks).toHaveLength(1);
+        expect(simple.playlists).toBeUndefined();
+    });
+});
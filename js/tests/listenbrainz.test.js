@@ -0,0 +1,61 @@
+import { expect, test, describe, afterEach, vi } from 'vitest';
+import { createMbidLookupQueue, lookupMbids } from '../listenbrainz.js';
+
+const albumTrack = (title, album = 'Hurry Up, We Are Dreaming') => ({
+    title,
+    artist: { name: 'M83' },
+    album: { title: album },
+});
+
+describe('listenbrainz.js', () => {
+    afterEach(() => {
+        vi.unstubAllGlobals();
+    });
+
+    test('MBID lookups of a download job are spaced out and made once per recording', async () => {
+        const startedAt = [];
+        const lookup = vi.fn(async (track) => {
+            startedAt.push(Date.now());
+            return { recording_mbid: `mbid-${track.title}` };
+        });
+        const queue = createMbidLookupQueue({ interval: 50, lookup });
+
+        const again = albumTrack('Midnight City');
+        const results = await Promise.all([
+            queue.lookup(albumTrack('Midnight City')),
+            queue.lookup(albumTrack('Wait')),
+            queue.lookup(again),
+            queue.lookup(albumTrack('Reunion')),
+        ]);
+
+        expect(lookup.mock.calls.length).toBe(3);
+        expect(results.map((mbids) => mbids.recording_mbid)).toEqual([
+            'mbid-Midnight City',
+            'mbid-Wait',
+            'mbid-Midnight City',
+            'mbid-Reunion',
+        ]);
+        expect(again.mbids.recording_mbid).toBe('mbid-Midnight City');
+        for (let i = 1; i < startedAt.length; i++) {
+            expect(startedAt[i] - startedAt[i - 1]).toBeGreaterThan(40);
+        }
+
+        // The same title on another release is another lookup
+        await queue.lookup(albumTrack('Midnight City', 'Midnight City (Remixes)'));
+        expect(lookup.mock.calls.length).toBe(4);
+    });
+
+    test('a rate limited MBID lookup waits and retries instead of giving up', async () => {
+        const fetchMock = vi
+            .fn()
+            .mockResolvedValueOnce(new Response(null, { status: 429, headers: { 'Retry-After': '0' } }))
+            .mockResolvedValueOnce(Response.json({ recording_mbid: 'mbid-1', release_mbid: 'release-1' }));
+        vi.stubGlobal('fetch', fetchMock);
+
+        const mbids = await lookupMbids(albumTrack('Midnight City'), { apiUrl: 'https://lb.test', token: '' });
+
+        expect(fetchMock.mock.calls.length).toBe(2);
+        expect(mbids.recording_mbid).toBe('mbid-1');
+        expect(mbids.release_mbid).toBe('release-1');
+    });
+});
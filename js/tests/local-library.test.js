@@ -0,0 +1,94 @@
+import { expect, test, describe } from 'vitest';
+import {
+    createCueTrackEntry,
+    createLocalLibraryEntry,
+    groupLocalTracks,
+    isEntryCurrent,
+    isLocalAudioFile,
+    searchLocalTracks,
+    toLocalTrack,
+} from '../local-library.js';
+
+const file = (name, size = 100, lastModified = 1) => ({ name, size, lastModified });
+
+const track = (path, tags) => toLocalTrack(createLocalLibraryEntry('main', path, file(path.split('/').pop()), tags));
+
+describe('local-library.js', () => {
+    test('only reparses files whose size or modification time changed', () => {
+        const entry = createLocalLibraryEntry('main', 'a/b.flac', file('b.flac', 100, 5), { title: 'B' });
+
+        expect(entry.key).toBe('main:a/b.flac');
+        expect(isEntryCurrent(entry, file('b.flac', 100, 5))).toBe(true);
+        expect(isEntryCurrent(entry, file('b.flac', 101, 5))).toBe(false);
+        expect(isEntryCurrent(entry, file('b.flac', 100, 6))).toBe(false);
+        expect(isEntryCurrent(undefined, file('b.flac'))).toBe(false);
+        expect(isLocalAudioFile('Song.OPUS')).toBe(true);
+        expect(isLocalAudioFile('cover.jpg')).toBe(false);
+    });
+
+    test('builds tracks in the shape of readTrackMetadata', () => {
+        const result = track('x/01 Intro.flac', {
+            artist: 'A; B',
+            albumTitle: 'Album',
+            trackNumber: 1,
+            musicBrainz: { recordingId: 'rec' },
+        });
+
+        expect(result.title).toBe('01 Intro');
+        expect(result.artists).toEqual([{ name: 'A' }, { name: 'B' }]);
+        expect(result.artist).toEqual({ name: 'A' });
+        expect(result.album.title).toBe('Album');
+        expect(result.album.artist).toEqual({ name: 'A' });
+        expect(result.isLocal).toBe(true);
+        expect(result.mbids.recording_mbid).toBe('rec');
+    });
+
+    test('groups by album and artist and searches across fields', () => {
+        const tracks = [
+            track('2.flac', { title: 'Two', artist: 'A', albumTitle: 'First', trackNumber: 2 }),
+            track('1.flac', { title: 'One', artist: 'A', albumTitle: 'First', trackNumber: 1 }),
+            track('3.flac', { title: 'Three', artist: 'B', albumArtist: 'A', albumTitle: 'Second' }),
+            track('4.flac', { title: 'Four', artist: 'C', albumTitle: 'Other' }),
+        ];
+
+        const albums = groupLocalTracks(tracks, 'album');
+        expect(albums.map((group) => group.title)).toEqual(['First', 'Other', 'Second']);
+        expect(albums[0].tracks.map((t) => t.title)).toEqual(['One', 'Two']);
+
+        const artists = groupLocalTracks(tracks, 'artist');
+        expect(artists.map((group) => [group.title, group.subtitle])).toEqual([
+            ['A', '2 albums'],
+            ['C', '1 album'],
+        ]);
+
+        expect(searchLocalTracks(tracks, 'a first').map((t) => t.title)).toEqual(['Two', 'One']);
+        expect(searchLocalTracks(tracks, 'three b').map((t) => t.title)).toEqual(['Three']);
+        expect(searchLocalTracks(tracks, '')).toBe(tracks);
+    });
+
+    test('splits CUE images into tracks that bound their part of the file', () => {
+        const image = file('image.flac', 100, 5);
+        const cue = { title: 'Album', performer: 'Band', date: '1999', files: [] };
+        const entry = createCueTrackEntry(
+            'main',
+            'a/image.flac',
+            image,
+            { albumTitle: 'Tagged', duration: 600 },
+            cue,
+            { number: 2, title: 'Second', performer: null, isrc: 'X', start: 200, end: 410 },
+            7
+        );
+        const result = toLocalTrack(entry);
+
+        expect(entry.key).toBe('main:a/image.flac#02');
+        expect(result.title).toBe('Second');
+        expect(result.artist).toEqual({ name: 'Band' });
+        expect(result.album.title).toBe('Album');
+        expect(result.duration).toBe(210);
+        expect([result.cueStart, result.cueEnd]).toEqual([200, 410]);
+        expect(result.localPath).toBe('a/image.flac');
+        expect(isEntryCurrent(entry, image, { lastModified: 7 })).toBe(true);
+        expect(isEntryCurrent(entry, image, { lastModified: 8 })).toBe(false);
+        expect(isEntryCurrent(entry, image)).toBe(false);
+    });
+});
@@ -0,0 +1,76 @@
+import { expect, test, describe, vi } from 'vitest';
+import { getCatalogTrack, matchLocalTrack, needsMatch, scoreCatalogMatch } from '../local-matcher.js';
+
+const localTrack = (overrides = {}) => ({
+    id: 'local-main:a.flac',
+    title: 'Midnight City',
+    artists: [{ name: 'M83' }],
+    album: { title: "Hurry Up, We're Dreaming" },
+    duration: 244,
+    isLocal: true,
+    ...overrides,
+});
+
+const catalogTrack = (overrides = {}) => ({
+    id: 101,
+    title: 'Midnight City',
+    artists: [{ id: 1, name: 'M83' }],
+    album: { id: 5, title: "Hurry Up, We're Dreaming" },
+    duration: 243,
+    isrc: 'FR6V81141061',
+    ...overrides,
+});
+
+describe('local-matcher.js', () => {
+    test('scores matches by ISRC, metadata and duration', () => {
+        expect(scoreCatalogMatch(localTrack({ isrc: 'FR-6V8-11-41061' }), catalogTrack())).toBe(1);
+        expect(scoreCatalogMatch(localTrack(), catalogTrack())).toBe(1);
+        expect(scoreCatalogMatch(localTrack({ album: { title: 'Unknown Album' } }), catalogTrack())).toBe(1);
+        expect(scoreCatalogMatch(localTrack(), catalogTrack({ duration: 400 }))).toBeLessThan(0.75);
+        expect(scoreCatalogMatch(localTrack(), catalogTrack({ artists: [{ name: 'Other' }] }))).toBeLessThan(0.75);
+    });
+
+    test('looks up embedded ISRCs before searching', async () => {
+        const api = {
+            searchTracksByIsrc: vi.fn().mockResolvedValue({ items: [catalogTrack({ id: 7 })] }),
+            searchTracks: vi.fn(),
+        };
+
+        const match = await matchLocalTrack(localTrack({ isrc: 'FR6V81141061' }), api);
+
+        expect(match).toMatchObject({ id: 7, confidence: 1, method: 'isrc' });
+        expect(match.track.album.id).toBe(5);
+        expect(api.searchTracks).not.toHaveBeenCalled();
+    });
+
+    test('falls back to a fuzzy search', async () => {
+        const api = {
+            searchTracksByIsrc: vi.fn().mockResolvedValue({ items: [] }),
+            searchTracks: vi.fn().mockResolvedValue({
+                items: [catalogTrack({ id: 1, title: 'Outro' }), catalogTrack({ id: 2, isrc: null })],
+            }),
+        };
+
+        const match = await matchLocalTrack(localTrack({ isrc: 'XX0000000000' }), api);
+
+        expect(api.searchTracks).toHaveBeenCalledWith('"Midnight City" M83');
+        expect(match).toMatchObject({ id: 2, method: 'search' });
+        expect(match.confidence).toBeGreaterThan(0.9);
+    });
+
+    test('only uses confident matches in place of local tracks', () => {
+        const matched = localTrack({ catalogMatch: { id: 2, confidence: 0.9, track: catalogTrack({ id: 2 }) } });
+        const weak = localTrack({ catalogMatch: { id: 3, confidence: 0.6, track: catalogTrack({ id: 3 }) } });
+
+        expect(getCatalogTrack(matched).id).toBe(2);
+        expect(getCatalogTrack(weak)).toBeNull();
+        expect(getCatalogTrack(localTrack())).toBeNull();
+        expect(getCatalogTrack(catalogTrack()).id).toBe(101);
+
+        const now = Date.now();
+        expect(needsMatch({})).toBe(true);
+        expect(needsMatch({ match: { id: 2, matchedAt: 0 } }, now)).toBe(false);
+        expect(needsMatch({ match: { id: null, matchedAt: now - 1000 } }, now)).toBe(false);
+        expect(needsMatch({ match: { id: null, matchedAt: 0 } }, now)).toBe(true);
+    });
+});
@@ -0,0 +1,85 @@
+import { expect, test, describe, afterEach, vi } from 'vitest';
+import { loudnessAnalyzer } from '../loudness-analyzer.js';
+import { db } from '../db.js';
+
+vi.mock('../db.js', () => {
+    const entries = new Map();
+    return {
+        db: {
+            getLoudnessAnalysis: vi.fn(async (id) => entries.get(String(id))),
+            saveLoudnessAnalysis: vi.fn(async (id, analysis) => {
+                const entry = { ...analysis, id: String(id), analyzedAt: Date.now() };
+                entries.set(String(id), entry);
+                return entry;
+            }),
+        },
+    };
+});
+
+vi.mock('../proxy-utils.js', () => ({
+    getProxyUrl: vi.fn((url) => url),
+}));
+
+/** A 16-bit stereo WAV file of a 997 Hz tone, or of silence */
+function createWavFile(seconds, amplitude, sampleRate = 48000) {
+    const frames = Math.round(seconds * sampleRate);
+    const view = new DataView(new ArrayBuffer(44 + frames * 4));
+    const writeString = (offset, text) => [...text].forEach((c, i) => view.setUint8(offset + i, c.charCodeAt(0)));
+    writeString(0, 'RIFF');
+    view.setUint32(4, 36 + frames * 4, true);
+    writeString(8, 'WAVE');
+    writeString(12, 'fmt ');
+    view.setUint32(16, 16, true);
+    view.setUint16(20, 1, true);
+    view.setUint16(22, 2, true);
+    view.setUint32(24, sampleRate, true);
+    view.setUint32(28, sampleRate * 4, true);
+    view.setUint16(32, 4, true);
+    view.setUint16(34, 16, true);
+    writeString(36, 'data');
+    view.setUint32(40, frames * 4, true);
+    for (let i = 0; i < frames; i++) {
+        const sample = Math.round(amplitude * 32767 * Math.sin((2 * Math.PI * 997 * i) / sampleRate));
+        view.setInt16(44 + i * 4, sample, true);
+        view.setInt16(46 + i * 4, sample, true);
+    }
+    return new File([view.buffer], 'track.wav', { type: 'audio/wav' });
+}
+
+describe('loudness-analyzer.js', () => {
+    afterEach(() => {
+        vi.clearAllMocks();
+    });
+
+    test('measures a track fed to the worker in slices', async () => {
+        // 25 s spans several slices; the EBU reference tone at -20 dBFS on both channels reads -20 LUFS
+        const file = createWavFile(25, Math.pow(10, -20 / 20));
+        const entry = await loudnessAnalyzer.analyze({ id: 'tone', duration: 25 }, { file });
+
+        expect(entry.integratedLoudness).toBeCloseTo(-20, 0);
+        expect(entry.trackReplayGain).toBeCloseTo(2, 0);
+    });
+
+    test('silent tracks are remembered as having no gain instead of being analyzed again', async () => {
+        const file = createWavFile(2, 0);
+        const arrayBuffer = vi.spyOn(file, 'arrayBuffer');
+
+        expect(await loudnessAnalyzer.analyze({ id: 'silence', duration: 2 }, { file })).toBeNull();
+        expect(db.saveLoudnessAnalysis.mock.calls[0][1].noGain).toBe(true);
+        expect(await loudnessAnalyzer.getReplayGain('silence')).toBeNull();
+
+        expect(await loudnessAnalyzer.analyze({ id: 'silence', duration: 2 }, { file })).toBeNull();
+        expect(arrayBuffer).toHaveBeenCalledTimes(1);
+    });
+
+    test('tracks that are too long or of unknown length are not decoded', async () => {
+        const file = createWavFile(1, 0.1);
+        const arrayBuffer = vi.spyOn(file, 'arrayBuffer');
+
+        await expect(loudnessAnalyzer.analyze({ id: 'mix', duration: 3 * 3600 }, { file })).rejects.toThrow(
+            'too long'
+        );
+        await expect(loudnessAnalyzer.analyze({ id: 'unknown' }, { file })).rejects.toThrow('unknown length');
+        expect(arrayBuffer).not.toHaveBeenCalled();
+    });
+});
@@ -0,0 +1,57 @@
+import { expect, test, describe } from 'vitest';
+import { LoudnessMeter, loudnessToReplayGain } from '../loudness-meter.js';
+
+const sine = (sampleRate, seconds, frequency, amplitude, phase = 0) => {
+    const data = new Float32Array(Math.round(sampleRate * seconds));
+    for (let i = 0; i < data.length; i++) {
+        data[i] = amplitude * Math.sin((2 * Math.PI * frequency * i) / sampleRate + phase);
+    }
+    return data;
+};
+
+describe('loudness-meter.js', () => {
+    test('measures the EBU Tech 3341 reference tone at -23 LUFS', () => {
+        for (const sampleRate of [44100, 48000]) {
+            const tone = sine(sampleRate, 5, 997, Math.pow(10, -23 / 20));
+            const meter = new LoudnessMeter(sampleRate, 2);
+            meter.process([tone, tone]);
+            expect(meter.getResult().integratedLoudness).toBeCloseTo(-23, 1);
+        }
+    });
+
+    test('gives the same result when fed in chunks', () => {
+        const tone = sine(48000, 3, 440, 0.25);
+        const whole = new LoudnessMeter(48000, 1);
+        whole.process([tone]);
+
+        const chunked = new LoudnessMeter(48000, 1);
+        for (let start = 0; start < tone.length; start += 4096) {
+            chunked.process([tone.subarray(start, start + 4096)]);
+        }
+        expect(chunked.getResult().integratedLoudness).toBeCloseTo(whole.getResult().integratedLoudness, 6);
+    });
+
+    test('detects inter-sample peaks above the sample peak', () => {
+        // A quarter-rate sine sampled at 45 degrees never hits its true crest
+        const tone = sine(48000, 1, 12000, 1, Math.PI / 4);
+        const meter = new LoudnessMeter(48000, 1);
+        meter.process([tone]);
+        const { samplePeak, truePeak } = meter.getResult();
+        expect(samplePeak).toBeCloseTo(Math.SQRT1_2, 3);
+        expect(truePeak).toBeGreaterThan(0.95);
+    });
+
+    test('gates out silence', () => {
+        const meter = new LoudnessMeter(48000, 2);
+        const silence = new Float32Array(48000);
+        meter.process([silence, silence]);
+        expect(meter.getResult().integratedLoudness).toBe(-Infinity);
+        expect(loudnessToReplayGain(meter.getResult())).toBeNull();
+    });
+
+    test('converts loudness to ReplayGain 2.0 values', () => {
+        const rg = loudnessToReplayGain({ integratedLoudness: -9.5, truePeak: 1.12 });
+        expect(rg.trackReplayGain).toBe(-8.5);
+        expect(rg.trackPeakAmplitude).toBe(1.12);
+    });
+});
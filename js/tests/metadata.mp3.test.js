@@ -0,0 +1,58 @@
+import { expect, test, describe } from 'vitest';
+import { addID3v2Frames, createSYLTFrame, readSynchsafeInteger32 } from '../metadata.mp3.js';
+
+function createID3v24Tag(frames, padding) {
+    const framesLength = frames.reduce((acc, f) => acc + f.length, 0);
+    const tag = new Uint8Array(10 + framesLength + padding);
+    tag.set([0x49, 0x44, 0x33, 0x04, 0x00, 0x00]);
+    const size = framesLength + padding;
+    tag.set([(size >> 21) & 0x7f, (size >> 14) & 0x7f, (size >> 7) & 0x7f, size & 0x7f], 6);
+    let offset = 10;
+    for (const frame of frames) {
+        tag.set(frame, offset);
+        offset += frame.length;
+    }
+    return tag;
+}
+
+function listFrames(bytes) {
+    const view = new DataView(bytes.buffer);
+    const end = 10 + readSynchsafeInteger32(view, 6);
+    const frames = [];
+    let offset = 10;
+    while (offset + 10 <= end && bytes[offset] !== 0) {
+        const size = readSynchsafeInteger32(view, offset + 4);
+        frames.push({ id: String.fromCharCode(...bytes.slice(offset, offset + 4)), offset, size });
+        offset += 10 + size;
+    }
+    return frames;
+}
+
+describe('metadata.mp3.js', () => {
+    test('creates SYLT frames with millisecond timestamps', () => {
+        const frame = createSYLTFrame([{ time: 1.5, text: 'Hi' }], 4);
+        const view = new DataView(frame.buffer);
+
+        expect(String.fromCharCode(...frame.slice(0, 4))).toBe('SYLT');
+        expect(readSynchsafeInteger32(view, 4)).toBe(frame.length - 10);
+        expect(frame[10]).toBe(0x03); // UTF-8
+        expect(frame[14]).toBe(0x02); // Milliseconds
+        expect(frame[15]).toBe(0x01); // Lyrics
+        expect(String.fromCharCode(...frame.slice(17, 19))).toBe('Hi');
+        expect(view.getUint32(20, false)).toBe(1500);
+    });
+
+    test('adds frames to an existing tag before its padding', async () => {
+        const title = new Uint8Array([0x54, 0x49, 0x54, 0x32, 0, 0, 0, 2, 0, 0, 0x03, 0x41]);
+        const audio = new Uint8Array([0xff, 0xfb, 0x90, 0x00, 1, 2, 3]);
+        const blob = new Blob([createID3v24Tag([title], 16), audio]);
+
+        const result = await addID3v2Frames(blob, (version) => [createSYLTFrame([{ time: 0, text: 'a' }], version)]);
+        const bytes = new Uint8Array(await result.arrayBuffer());
+        const frames = listFrames(bytes);
+
+        expect(frames.map((frame) => frame.id)).toEqual(['TIT2', 'SYLT']);
+        expect(bytes.slice(bytes.length - audio.length)).toEqual(audio);
+        expect(bytes.length).toBe(blob.size + 10 + frames[1].size);
+    });
+});
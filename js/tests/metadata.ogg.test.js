@@ -0,0 +1,79 @@
+import { expect, test, describe } from 'vitest';
+import {
+    createOggPages,
+    oggCrc32,
+    parseOggPage,
+    readOggMetadata,
+    writeOggMetadata,
+    createVorbisCommentPacket,
+} from '../metadata.ogg.js';
+
+const ascii = (text) => Array.from(text, (c) => c.charCodeAt(0));
+
+function createVorbisFile() {
+    const identification = new Uint8Array(30);
+    identification.set([0x01, ...ascii('vorbis')]);
+    new DataView(identification.buffer).setUint32(12, 44100, true);
+    const comments = createVorbisCommentPacket('vorbis', [['TITLE', 'Old title'], ['GENRE', 'Ambient']], 'test');
+    const setup = new Uint8Array([0x05, ...ascii('vorbis'), 1, 2, 3]);
+
+    const [firstPage] = createOggPages([identification], 7, 0);
+    firstPage[5] = 0x02; // beginning of stream
+    new DataView(firstPage.buffer).setUint32(22, 0, true);
+    new DataView(firstPage.buffer).setUint32(22, oggCrc32(firstPage), true);
+
+    const headerPages = createOggPages([comments, setup], 7, 1);
+    const [audioPage] = createOggPages([new Uint8Array(100).fill(9)], 7, headerPages.length + 1);
+    const view = new DataView(audioPage.buffer);
+    view.setUint32(6, 441000, true); // 10 seconds at 44.1 kHz
+    view.setUint32(22, 0, true);
+    view.setUint32(22, oggCrc32(audioPage), true);
+
+    return new Blob([firstPage, ...headerPages, audioPage], { type: 'audio/ogg' });
+}
+
+function readPages(bytes) {
+    const pages = [];
+    for (let offset = 0; offset < bytes.length; ) {
+        const page = parseOggPage(bytes, offset);
+        const copy = bytes.slice(offset, offset + page.size);
+        new DataView(copy.buffer).setUint32(22, 0, true);
+        pages.push({ ...page, crcValid: oggCrc32(copy) === new DataView(bytes.buffer).getUint32(offset + 22, true) });
+        offset += page.size;
+    }
+    return pages;
+}
+
+describe('metadata.ogg.js', () => {
+    test('computes the Ogg CRC', () => {
+        expect(oggCrc32(new Uint8Array(ascii('OggS')))).toBe(0x5fb0a94f);
+    });
+
+    test('rewrites Vorbis comments with a cover, keeping other comments and valid pages', async () => {
+        const cover = { data: new Uint8Array(70000).fill(7), type: 'image/png' };
+        const tagged = await writeOggMetadata(createVorbisFile(), {
+            title: 'New title',
+            artist: ['A', 'B'],
+            writeArtistsSeparately: true,
+            trackNumber: 3,
+            cover,
+        });
+
+        const pages = readPages(new Uint8Array(await tagged.arrayBuffer()));
+        expect(pages.every((page) => page.crcValid)).toBe(true);
+        expect(pages.map((page) => page.sequence)).toEqual(pages.map((_, i) => i));
+        expect(pages.length).toBeGreaterThan(3);
+
+        const data = await readOggMetadata(tagged);
+        expect(data.title).toBe('New title');
+        expect(data.artist).toBe('A; B');
+        expect(data.trackNumber).toBe(3);
+        expect(data.cover.type).toBe('image/png');
+        expect(data.cover.data).toEqual(cover.data);
+        expect(data.duration).toBe(10);
+
+        const cleared = await readOggMetadata(await writeOggMetadata(tagged, { title: null }));
+        expect(cleared.title).toBeUndefined();
+        expect(cleared.artist).toBe('A; B');
+    });
+});
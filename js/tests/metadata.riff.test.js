@@ -0,0 +1,62 @@
+import { expect, test, describe } from 'vitest';
+import { readRiffMetadata, writeRiffMetadata } from '../metadata.riff.js';
+
+function createWavFile(seconds) {
+    const dataSize = 44100 * 4 * seconds;
+    const buffer = new ArrayBuffer(44 + dataSize);
+    const view = new DataView(buffer);
+    const writeId = (offset, id) => [...id].forEach((c, i) => view.setUint8(offset + i, c.charCodeAt(0)));
+
+    writeId(0, 'RIFF');
+    view.setUint32(4, 36 + dataSize, true);
+    writeId(8, 'WAVE');
+    writeId(12, 'fmt ');
+    view.setUint32(16, 16, true);
+    view.setUint16(20, 1, true); // PCM
+    view.setUint16(22, 2, true);
+    view.setUint32(24, 44100, true);
+    view.setUint32(28, 44100 * 4, true);
+    view.setUint16(32, 4, true);
+    view.setUint16(34, 16, true);
+    writeId(36, 'data');
+    view.setUint32(40, dataSize, true);
+    new Uint8Array(buffer, 44).fill(5);
+
+    return new Blob([buffer], { type: 'audio/wav' });
+}
+
+describe('metadata.riff.js', () => {
+    test('writes INFO and ID3 chunks to a WAV file and reads them back', async () => {
+        const wav = createWavFile(1);
+        const cover = { data: new Uint8Array([1, 2, 3, 4]), type: 'image/jpeg' };
+        const tagged = await writeRiffMetadata(wav, {
+            title: 'Title',
+            artist: 'Artist',
+            albumTitle: 'Album',
+            trackNumber: 2,
+            totalTracks: 9,
+            cover,
+        });
+
+        const view = new DataView(await tagged.arrayBuffer());
+        expect(view.getUint32(4, true)).toBe(tagged.size - 8);
+
+        const data = await readRiffMetadata(tagged);
+        expect(data.title).toBe('Title');
+        expect(data.artist).toBe('Artist');
+        expect(data.albumTitle).toBe('Album');
+        expect(data.trackNumber).toBe(2);
+        expect(data.totalTracks).toBe(9);
+        expect(data.cover.data).toEqual(cover.data);
+        expect(data.duration).toBe(1);
+
+        // Rewriting replaces the tag chunks instead of adding more
+        const retagged = await writeRiffMetadata(tagged, { title: 'Other' });
+        expect(retagged.size).toBe(tagged.size);
+        expect((await readRiffMetadata(retagged)).artist).toBe('Artist');
+
+        const cleared = await readRiffMetadata(await writeRiffMetadata(retagged, { artist: null }));
+        expect(cleared.artist).toBeUndefined();
+        expect(cleared.title).toBe('Other');
+    });
+});
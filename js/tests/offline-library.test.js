@@ -0,0 +1,65 @@
+import { expect, test, describe } from 'vitest';
+import {
+    getOfflineKey,
+    isOfflineCapable,
+    pickOfflineEntry,
+    selectEvictions,
+    planPlaylistSync,
+} from '../offline-library.js';
+
+const entry = (trackId, quality, extra = {}) => ({
+    key: getOfflineKey(trackId, quality),
+    trackId,
+    quality,
+    size: 10,
+    playlists: [],
+    addedAt: 0,
+    lastUsedAt: 0,
+    ...extra,
+});
+
+describe('offline-library.js', () => {
+    test('prefers the requested quality, then the best stored copy', () => {
+        const entries = [entry('1', 'HIGH'), entry('1', 'HI_RES_LOSSLESS'), entry('1', 'FFMPEG_MP3_320')];
+        expect(pickOfflineEntry(entries, 'HIGH').quality).toBe('HIGH');
+        expect(pickOfflineEntry(entries, 'LOSSLESS').quality).toBe('HI_RES_LOSSLESS');
+        expect(pickOfflineEntry([], 'LOSSLESS')).toBeNull();
+    });
+
+    test('only catalog audio can be stored offline', () => {
+        expect(isOfflineCapable({ id: 123 })).toBe(true);
+        expect(isOfflineCapable({ id: 1, isLocal: true })).toBe(false);
+        expect(isOfflineCapable({ id: 1, type: 'video' })).toBe(false);
+        expect(isOfflineCapable({ id: 'podcast_9' })).toBe(false);
+        expect(isOfflineCapable({ id: 'tracker-x' })).toBe(false);
+    });
+
+    test('evicts least recently used unpinned tracks first', () => {
+        const entries = [
+            entry('old', 'LOSSLESS', { lastUsedAt: 1 }),
+            entry('pinned', 'LOSSLESS', { lastUsedAt: 0, playlists: ['p1'] }),
+            entry('recent', 'LOSSLESS', { lastUsedAt: 50 }),
+            entry('middle', 'LOSSLESS', { lastUsedAt: 10 }),
+        ];
+        const { entries: evicted, bytes } = selectEvictions(entries, 15);
+        expect(evicted.map((e) => e.trackId)).toEqual(['old', 'middle']);
+        expect(bytes).toBe(20);
+
+        const all = selectEvictions(entries, 1000, new Set([getOfflineKey('recent', 'LOSSLESS')]));
+        expect(all.entries.map((e) => e.trackId)).toEqual(['old', 'middle']);
+        expect(all.bytes).toBe(20);
+    });
+
+    test('plans downloads, pins and releases for a playlist sync', () => {
+        const entries = [
+            entry('1', 'LOSSLESS', { playlists: ['p1'] }),
+            entry('2', 'HIGH'),
+            entry('3', 'LOSSLESS', { playlists: ['p1', 'p2'] }),
+        ];
+        const tracks = [{ id: 1 }, { id: 2 }, { id: 4 }, { id: 5, isLocal: true }];
+        const plan = planPlaylistSync(entries, 'p1', tracks);
+        expect(plan.download.map((t) => t.id)).toEqual([4]);
+        expect(plan.attach.map((e) => e.trackId)).toEqual(['2']);
+        expect(plan.release.map((e) => e.trackId)).toEqual(['3']);
+    });
+});
@@ -0,0 +1,39 @@
+import { expect, test, describe } from 'vitest';
+import { resolveOutputDevice, findBoundProfileId } from '../output-devices.js';
+
+const DEVICES = [
+    { deviceId: 'default', groupId: 'g-hp', label: 'Default - Headphones (USB DAC)' },
+    { deviceId: 'communications', groupId: 'g-spk', label: 'Communications - Speakers (Realtek)' },
+    { deviceId: 'spk', groupId: 'g-spk', label: 'Speakers (Realtek)' },
+    { deviceId: 'hp', groupId: 'g-hp', label: 'Headphones (USB DAC)' },
+];
+
+describe('output-devices.js', () => {
+    describe('resolveOutputDevice', () => {
+        test('resolves the default alias to the physical device', () => {
+            expect(resolveOutputDevice(DEVICES, '').deviceId).toBe('hp');
+            expect(resolveOutputDevice(DEVICES, 'default').deviceId).toBe('hp');
+        });
+
+        test('returns a selected device, or the default once it is gone', () => {
+            expect(resolveOutputDevice(DEVICES, 'spk').deviceId).toBe('spk');
+            expect(resolveOutputDevice(DEVICES, 'unplugged').deviceId).toBe('hp');
+        });
+
+        test('uses the first device when there is no default alias', () => {
+            expect(resolveOutputDevice(DEVICES.slice(2), '').deviceId).toBe('spk');
+            expect(resolveOutputDevice([], '')).toBeNull();
+        });
+    });
+
+    describe('findBoundProfileId', () => {
+        const bindings = { hp: { profileId: 'p1', label: 'Headphones (USB DAC)' } };
+
+        test('matches by device ID, then by label', () => {
+            expect(findBoundProfileId(bindings, DEVICES[3])).toBe('p1');
+            expect(findBoundProfileId(bindings, { deviceId: 'new-id', label: 'Headphones (USB DAC)' })).toBe('p1');
+            expect(findBoundProfileId(bindings, DEVICES[2])).toBeNull();
+            expect(findBoundProfileId(bindings, null)).toBeNull();
+        });
+    });
+});
@@ -0,0 +1,98 @@
+import { expect, test, describe } from 'vitest';
+import {
+    butterworthQs,
+    getFilterStages,
+    biquadStageResponseDb,
+    parseEqualizerAPO,
+    formatEqualizerAPO,
+} from '../parametric-eq.js';
+
+const responseDb = (f, band, sr = 48000) =>
+    getFilterStages(band).reduce((sum, stage) => sum + biquadStageResponseDb(f, stage, sr), 0);
+
+const APO_CONFIG = `Preamp: -6.5 dB
+Filter 1: ON PK Fc 100 Hz Gain 3.0 dB Q 1.41
+Channel: L
+Filter 2: ON HPQ Fc 30 Hz Q 0.54
+Filter 3: ON HPQ Fc 30 Hz Q 1.31
+Filter 4: ON LSC Fc 105 Hz Gain 4.5 dB Q 0.71
+Channel: R
+Filter 5: ON NO Fc 60 Hz Q 10.00
+Channel: all
+Filter 6: ON LPQ Fc 18000 Hz Q 0.71
+Filter 7: ON BP Fc 1000 Hz Q 1.41
+Filter 8: ON AP Fc 500 Hz Q 0.70`;
+
+describe('parametric-eq.js', () => {
+    describe('filter stages', () => {
+        test('builds Butterworth cascades for steep slopes', () => {
+            expect(butterworthQs(12)).toHaveLength(1);
+            expect(butterworthQs(24).map((q) => q.toFixed(4))).toEqual(['0.5412', '1.3066']);
+            expect(getFilterStages({ type: 'highpass', freq: 80, q: 2, slope: 48 })).toHaveLength(4);
+            expect(getFilterStages({ type: 'highpass', freq: 80, q: 2, slope: 12 })[0].q).toBe(2);
+        });
+
+        test('drops gain on types that have none', () => {
+            expect(getFilterStages({ type: 'notch', freq: 60, q: 10, gain: 6 })[0].gain).toBe(0);
+            expect(getFilterStages({ type: 'peaking', freq: 60, q: 1, gain: 6 })[0].gain).toBe(6);
+        });
+
+        test('magnitude responses follow each filter type', () => {
+            const hp = { type: 'highpass', freq: 1000, q: Math.SQRT1_2, slope: 24 };
+            expect(responseDb(1000, hp)).toBeCloseTo(-3.01, 1);
+            expect(responseDb(500, { ...hp, slope: 48 })).toBeCloseTo(-48, 0);
+            expect(responseDb(10000, { type: 'lowpass', freq: 1000, q: Math.SQRT1_2 })).toBeLessThan(-35);
+            expect(responseDb(60, { type: 'notch', freq: 60, q: 10 })).toBeLessThan(-60);
+            expect(responseDb(1000, { type: 'bandpass', freq: 1000, q: 1 })).toBeCloseTo(0, 3);
+            expect(responseDb(3000, { type: 'allpass', freq: 1000, q: 1 })).toBe(0);
+        });
+    });
+
+    describe('Equalizer APO', () => {
+        test('parses filter types, channels and slopes', () => {
+            const { preamp, bands } = parseEqualizerAPO(APO_CONFIG);
+            expect(preamp).toBe(-6.5);
+            expect(bands.map((b) => b.type)).toEqual([
+                'peaking',
+                'highpass',
+                'lowshelf',
+                'notch',
+                'lowpass',
+                'bandpass',
+                'allpass',
+            ]);
+            expect(bands.map((b) => b.channel)).toEqual([
+                'stereo',
+                'left',
+                'left',
+                'right',
+                'stereo',
+                'stereo',
+                'stereo',
+            ]);
+            expect(bands[1].slope).toBe(24);
+            expect(bands[3].gain).toBe(0);
+        });
+
+        test('round-trips a config through export', () => {
+            const { preamp, bands } = parseEqualizerAPO(APO_CONFIG);
+            expect(formatEqualizerAPO(preamp, bands)).toBe(APO_CONFIG);
+        });
+
+        test('skips filters for channels a stereo output does not have', () => {
+            const { bands } = parseEqualizerAPO(
+                'Channel: C\nFilter: ON PK Fc 1000 Hz Gain 3 dB Q 1\nChannel: L R\nFilter: ON HP Fc 20 Hz'
+            );
+            expect(bands).toHaveLength(1);
+            expect(bands[0]).toMatchObject({ type: 'highpass', channel: 'stereo', q: Math.SQRT1_2 });
+        });
+
+        test('accepts bandwidth in octaves and shelf slope notation', () => {
+            const { bands } = parseEqualizerAPO(
+                'Filter 1: ON PK Fc 1000 Hz Gain -2 dB BW Oct 1\nFilter 2: ON LS 12dB Fc 105.5 Hz Gain 4 dB'
+            );
+            expect(bands[0].q).toBeCloseTo(1.414, 3);
+            expect(bands[1]).toMatchObject({ type: 'lowshelf', freq: 105.5, gain: 4 });
+        });
+    });
+});
@@ -18,7 +18,11 @@ vi.mock('../storage.js', () => ({
         getQueue: vi.fn(() => null),
         saveQueue: vi.fn(),
     },
-    replayGainSettings: { getMode: vi.fn(() => 'off'), getPreamp: vi.fn(() => 0) },
+    replayGainSettings: {
+        getMode: vi.fn(() => 'off'),
+        getPreamp: vi.fn(() => 0),
+        isAnalysisEnabled: vi.fn(() => false),
+    },
     trackDateSettings: { useAlbumYear: vi.fn(() => true) },
     exponentialVolumeSettings: { applyCurve: vi.fn((v) => v) },
     audioEffectsSettings: {
@@ -44,12 +48,18 @@ vi.mock('../storage.js', () => ({
     lastFMStorage: { isEnabled: vi.fn(() => false) },
     nowPlayingSettings: { getMode: vi.fn(() => 'cover') },
     gaplessPlaybackSettings: { isEnabled: vi.fn(() => true) },
+    crossfadeSettings: {
+        getDuration: vi.fn(() => 0),
+        isEnabled: vi.fn(() => false),
+        isAlbumAware: vi.fn(() => true),
+    },
 }));
 
 vi.mock('../db.js', () => ({
     db: {
         get: vi.fn(),
         put: vi.fn(),
+        getLoudnessAnalysis: vi.fn(async () => undefined),
     },
 }));
 
@@ -192,4 +202,44 @@ describe('Player', () => {
         player.setPlaybackSpeed(0);
         expect(audioEffectsSettings.setSpeed).toHaveBeenCalledWith(0.01);
     });
+
+    test('playNext asks the transition policy how to move on to the next track', async () => {
+        player = new Player(audioElement, api);
+        const album = { id: 9, title: 'Album' };
+        player.queue = [
+            { id: 1, trackNumber: 1, album },
+            { id: 2, trackNumber: 2, album },
+            { id: 3, trackNumber: 1, album: { id: 10, title: 'Other album' } },
+        ];
+        player.currentQueueIndex = 0;
+        player.currentTrack = player.queue[0];
+        const playTrackFromQueue = vi.spyOn(player, 'playTrackFromQueue').mockResolvedValue();
+
+        await player.playNext();
+        expect(playTrackFromQueue.mock.calls[0][3].transition).toMatchObject({ mode: 'gapless', reason: 'album' });
+
+        player.currentTrack = player.queue[1];
+        await player.playNext();
+        expect(playTrackFromQueue.mock.calls[1][3].transition).toMatchObject({ mode: 'gapless', reason: 'policy' });
+    });
+
+    test('tracks split from a CUE sheet are bounded to their part of the file', () => {
+        player = new Player(audioElement, api);
+        player.currentTrack = { id: 'local-main:image.flac#02', isLocal: true, cueStart: 200, cueEnd: 410 };
+        const el = { currentTime: 250, duration: 600 };
+
+        expect(player.getPosition(el)).toEqual({ currentTime: 50, duration: 210 });
+        expect(player.hasReachedTrackEnd(el)).toBe(false);
+
+        player.seekTo(300, el);
+        expect(el.currentTime).toBe(410);
+        expect(player.hasReachedTrackEnd(el)).toBe(true);
+
+        player.seekTo(-5, el);
+        expect(el.currentTime).toBe(200);
+
+        player.currentTrack = { id: 1 };
+        expect(player.getPosition(el)).toEqual({ currentTime: 200, duration: 600 });
+        expect(player.hasReachedTrackEnd(el)).toBe(false);
+    });
 });
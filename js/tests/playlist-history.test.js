@@ -0,0 +1,40 @@
+import { expect, test, describe } from 'vitest';
+import { describePlaylistDiff, diffPlaylistTracks, haveSameTracks, withDiffs } from '../playlist-history.js';
+
+const tracks = (...ids) => ids.map((id) => ({ id, title: `Track ${id}` }));
+const ids = (list) => list.map((t) => t.id);
+
+describe('playlist-history.js', () => {
+    test('finds added, removed and moved tracks', () => {
+        const diff = diffPlaylistTracks(tracks(1, 2, 3, 4, 5), tracks(2, 3, 1, 4, 6));
+        expect(ids(diff.added)).toEqual([6]);
+        expect(ids(diff.removed)).toEqual([5]);
+        expect(ids(diff.moved)).toEqual([1]);
+        expect(describePlaylistDiff(diff)).toBe('+1 · −1 · 1 moved');
+
+        const reversed = diffPlaylistTracks(tracks(1, 2, 3), tracks(3, 2, 1));
+        expect(reversed.moved).toHaveLength(2);
+
+        expect(describePlaylistDiff(diffPlaylistTracks(tracks(1, 2), tracks(1, 2)))).toBe('No changes');
+    });
+
+    test('tells tracks and videos with the same id apart', () => {
+        const before = [{ id: 7, type: 'track' }];
+        const after = [{ id: 7, type: 'video' }];
+        expect(haveSameTracks(before, after)).toBe(false);
+        expect(diffPlaylistTracks(before, after).moved).toHaveLength(0);
+        expect(diffPlaylistTracks(before, after).added).toHaveLength(1);
+        expect(haveSameTracks(tracks(1, 2), tracks(1, 2))).toBe(true);
+    });
+
+    test('diffs every snapshot against the one before it', () => {
+        const history = withDiffs([
+            { id: 'c', tracks: tracks(2, 3) },
+            { id: 'b', tracks: tracks(1, 2, 3) },
+            { id: 'a', tracks: tracks(1, 2) },
+        ]);
+        expect(ids(history[0].diff.removed)).toEqual([1]);
+        expect(ids(history[1].diff.added)).toEqual([3]);
+        expect(history[2].diff).toBeNull();
+    });
+});
@@ -0,0 +1,91 @@
+import { expect, test, describe, beforeAll, afterAll } from 'vitest';
+import PocketBase from 'pocketbase';
+import { createPocketBaseTransport } from '../accounts/pocketbase-transport.js';
+import { diffToOps, formatStamp, libraryTarget, playlistTarget, pushOps, tickClock } from '../sync-oplog.js';
+
+// Runs against a local PocketBase with database/pb_schema.json imported, see database/README.md
+const POCKETBASE_URL = import.meta.env.VITE_POCKETBASE_TEST_URL;
+
+const tracks = (...ids) => ids.map((id) => ({ id, title: `Track ${id}` }));
+const ids = (list) => list.map((t) => t.id);
+
+function createDevice(name) {
+    let clock = { time: 1000, counter: 0, device: name };
+    return () => {
+        clock = tickClock(clock, 1000);
+        return formatStamp(clock);
+    };
+}
+
+describe.skipIf(!POCKETBASE_URL)('pocketbase-transport.js', () => {
+    let pb;
+    let owner;
+
+    beforeAll(async () => {
+        pb = new PocketBase(POCKETBASE_URL);
+        const { VITE_POCKETBASE_TEST_EMAIL, VITE_POCKETBASE_TEST_PASSWORD } = import.meta.env;
+        await pb.collection('_superusers').authWithPassword(VITE_POCKETBASE_TEST_EMAIL, VITE_POCKETBASE_TEST_PASSWORD);
+        const name = `sync-test-${Date.now()}`;
+        owner = await pb.collection('app_users').create({ better_auth_id: name, email: `${name}@example.com` });
+    });
+
+    afterAll(async () => {
+        if (owner) await pb.collection('app_users').delete(owner.id);
+    });
+
+    test("two devices editing the same playlist and library keep each other's changes", async () => {
+        const transport = createPocketBaseTransport(pb, owner.id);
+        await transport.save({
+            library: { tracks: { 1: { id: 1 } } },
+            userPlaylists: { p1: { id: 'p1', name: 'Shared', tracks: tracks(1, 2, 3) } },
+        });
+
+        const phone = createDevice('phone');
+        const laptop = createDevice('laptop');
+        const fromPhone = [
+            ...diffToOps(playlistTarget('p1'), tracks(1, 2, 3), tracks(1, 2, 3, 4), phone),
+            { id: 'phone-remove', target: libraryTarget('track'), type: 'remove', key: '1', stamp: phone() },
+        ];
+        const fromLaptop = [
+            ...diffToOps(playlistTarget('p1'), tracks(1, 2, 3), tracks(3, 1), laptop),
+            {
+                id: 'laptop-add',
+                target: libraryTarget('track'),
+                type: 'add',
+                key: '5',
+                item: { id: 5 },
+                stamp: laptop(),
+            },
+        ];
+
+        await pushOps(transport, fromLaptop);
+        await pushOps(transport, fromPhone);
+
+        const saved = await transport.load();
+        expect(ids(saved.userPlaylists.p1.tracks)).toEqual([3, 4, 1]);
+        expect(saved.userPlaylists.p1.name).toBe('Shared');
+        expect(Object.keys(saved.library.tracks)).toEqual(['5']);
+    });
+
+    test('smart playlists keep their rules, sort and limit', async () => {
+        const transport = createPocketBaseTransport(pb, owner.id);
+        const rules = [{ field: 'playCount', operator: 'gt', value: 5 }];
+        await transport.save({
+            smartPlaylists: {
+                s1: { id: 's1', name: 'Heavy rotation', match: 'any', rules, sort: 'playCount', limit: 25 },
+                s2: { id: 's2', name: 'Everything', match: 'all', rules: [], sort: null, limit: null },
+            },
+        });
+
+        let saved = await transport.load();
+        expect(saved.smartPlaylists.s1).toMatchObject({ id: 's1', name: 'Heavy rotation', match: 'any' });
+        expect(saved.smartPlaylists.s1.rules).toEqual(rules);
+        expect(saved.smartPlaylists.s1.sort).toBe('playCount');
+        expect(saved.smartPlaylists.s1.limit).toBe(25);
+        expect(saved.smartPlaylists.s2.limit).toBeNull();
+        expect(saved.smartPlaylists.s2.createdAt).toBeGreaterThan(0);
+
+        saved = await transport.save({ smartPlaylists: { s2: saved.smartPlaylists.s2 } });
+        expect(Object.keys(saved.smartPlaylists)).toEqual(['s2']);
+    });
+});
@@ -0,0 +1,135 @@
+import { expect, test, describe, afterEach, vi } from 'vitest';
+import {
+    downloadWithRanges,
+    parseContentRange,
+    planRanges,
+    isRetryableStatus,
+    RANGE_CHUNK_BYTES,
+} from '../resumable-download.js';
+
+// Checkpoints outlive a failed attempt, as they would in IndexedDB
+vi.mock('../db.js', () => {
+    const checkpoints = new Map();
+    const chunks = new Map();
+    return {
+        db: {
+            getDownloadCheckpoints: async () => [...checkpoints.values()],
+            getDownloadCheckpoint: async (key) => checkpoints.get(key),
+            saveDownloadCheckpoint: async (checkpoint) => checkpoints.set(checkpoint.key, checkpoint),
+            deleteDownloadCheckpoint: async (key) => {
+                checkpoints.delete(key);
+                [...chunks.keys()].filter((id) => id.startsWith(`${key}#`)).forEach((id) => chunks.delete(id));
+            },
+            getDownloadChunk: async (id) => chunks.get(id),
+            saveDownloadChunk: async (id, blob) => chunks.set(id, blob),
+        },
+    };
+});
+
+const FILE = Uint8Array.from({ length: RANGE_CHUNK_BYTES * 2 + 1000 }, (_, i) => (i * 31) % 251);
+
+/**
+ * Serves FILE, answering Range requests with 206 or, once `ignoreRanges` is set, with the whole file.
+ * The connection drops when the range starting at `dropAt` is requested.
+ */
+function createServer() {
+    const server = {
+        ignoreRanges: false,
+        dropAt: null,
+        onDrop: null,
+        requested: [],
+        fetch: vi.fn(async (_url, { headers = {} } = {}) => {
+            const [start, end] = /bytes=(\d+)-(\d+)/.exec(headers.Range).slice(1).map(Number);
+            if (start === server.dropAt) {
+                server.onDrop?.();
+                throw new TypeError('Failed to fetch');
+            }
+            if (end > 0) server.requested.push(start);
+            if (server.ignoreRanges && end > 0) {
+                return new Response(FILE.slice(), { status: 200, headers: { 'Content-Type': 'audio/flac' } });
+            }
+            return new Response(FILE.slice(start, end + 1), {
+                status: 206,
+                headers: { 'Content-Type': 'audio/flac', 'Content-Range': `bytes ${start}-${end}/${FILE.length}` },
+            });
+        }),
+    };
+    return server;
+}
+
+/** Start a download that is cut off after its first range, the way closing the tab or going offline would */
+async function interrupt(server, checkpointKey) {
+    const controller = new AbortController();
+    server.dropAt = RANGE_CHUNK_BYTES;
+    server.onDrop = () => controller.abort();
+
+    const download = downloadWithRanges('https://cdn.test/track.flac', { checkpointKey, signal: controller.signal });
+    await expect(download).rejects.toThrow();
+    expect(server.requested).toEqual([0]);
+
+    server.dropAt = null;
+    server.requested = [];
+}
+
+async function expectSameBytes(blob) {
+    const bytes = new Uint8Array(await blob.arrayBuffer());
+    expect(bytes.length).toBe(FILE.length);
+    expect(bytes.every((byte, i) => byte === FILE[i])).toBe(true);
+}
+
+describe('resumable-download.js', () => {
+    test('parses Content-Range headers', () => {
+        expect(parseContentRange('bytes 0-0/1234')).toEqual({ start: 0, end: 0, total: 1234 });
+        expect(parseContentRange('bytes 100-199/*')).toEqual({ start: 100, end: 199, total: null });
+        expect(parseContentRange(null)).toBeNull();
+        expect(parseContentRange('items 0-1/2')).toBeNull();
+    });
+
+    test('plans inclusive ranges covering the whole file', () => {
+        expect(planRanges(10, 4)).toEqual([
+            { index: 0, start: 0, end: 3 },
+            { index: 1, start: 4, end: 7 },
+            { index: 2, start: 8, end: 9 },
+        ]);
+        expect(planRanges(8, 4).at(-1)).toEqual({ index: 1, start: 4, end: 7 });
+        expect(planRanges(0, 4)).toEqual([]);
+    });
+
+    test('retries only transient HTTP errors', () => {
+        expect(isRetryableStatus(503)).toBe(true);
+        expect(isRetryableStatus(429)).toBe(true);
+        expect(isRetryableStatus(408)).toBe(true);
+        expect(isRetryableStatus(404)).toBe(false);
+        expect(isRetryableStatus(403)).toBe(false);
+    });
+
+    describe('downloadWithRanges', () => {
+        afterEach(() => {
+            vi.unstubAllGlobals();
+        });
+
+        test('an interrupted download resumes with the ranges it is missing', async () => {
+            const server = createServer();
+            vi.stubGlobal('fetch', server.fetch);
+            await interrupt(server, 'resume-206');
+
+            const blob = await downloadWithRanges('https://cdn.test/track.flac', { checkpointKey: 'resume-206' });
+
+            expect(server.requested).toEqual([RANGE_CHUNK_BYTES, RANGE_CHUNK_BYTES * 2]);
+            expect(blob.type).toBe('audio/flac');
+            await expectSameBytes(blob);
+        });
+
+        test('a resumed download takes the whole file when the range is answered with a 200', async () => {
+            const server = createServer();
+            vi.stubGlobal('fetch', server.fetch);
+            await interrupt(server, 'resume-200');
+
+            server.ignoreRanges = true;
+            const blob = await downloadWithRanges('https://cdn.test/track.flac', { checkpointKey: 'resume-200' });
+
+            expect(server.requested).toEqual([RANGE_CHUNK_BYTES]);
+            await expectSameBytes(blob);
+        });
+    });
+});
@@ -0,0 +1,66 @@
+import { expect, test, describe } from 'vitest';
+import { getSyncableKeys, isSecretKey, mergeSettingsEntries, recordChanges } from '../settings-sync.js';
+import {
+    equalizerSettings,
+    lastFMStorage,
+    listenBrainzSettings,
+    syncBackendSettings,
+    themeManager,
+} from '../storage.js';
+
+describe('settings-sync.js', () => {
+    test('tokens and API keys are never synced', () => {
+        const keys = getSyncableKeys();
+        expect(keys).toContain(equalizerSettings.ENABLED_KEY);
+        expect(keys).toContain(themeManager.STORAGE_KEY);
+        expect(keys).not.toContain(lastFMStorage.CUSTOM_API_KEY);
+        expect(keys).not.toContain(lastFMStorage.CUSTOM_API_SECRET);
+        expect(keys).not.toContain(listenBrainzSettings.TOKEN_KEY);
+        expect(keys.some(isSecretKey)).toBe(false);
+    });
+
+    test('the WebDAV folder and its login never leave the device', () => {
+        expect(isSecretKey(syncBackendSettings.WEBDAV_URL_KEY)).toBe(true);
+        expect(isSecretKey(syncBackendSettings.WEBDAV_USERNAME_KEY)).toBe(true);
+        expect(isSecretKey(syncBackendSettings.WEBDAV_PASSWORD_KEY)).toBe(true);
+    });
+
+    test('only the chosen categories are synced', () => {
+        const keys = getSyncableKeys(['appearance']);
+        expect(keys).toContain(themeManager.STORAGE_KEY);
+        expect(keys).not.toContain(equalizerSettings.ENABLED_KEY);
+    });
+
+    test('changed values are stamped, new ones defer to the cloud', () => {
+        const entries = recordChanges({ a: { value: '1', updatedAt: 5 } }, { a: '1', b: '2' }, 10);
+        expect(entries).toEqual({ a: { value: '1', updatedAt: 5 }, b: { value: '2', updatedAt: 0 } });
+        expect(recordChanges(entries, { a: '3' }, 20).a).toEqual({ value: '3', updatedAt: 20 });
+    });
+
+    test('the latest change of each key wins', () => {
+        const local = {
+            theme: { value: 'dark', updatedAt: 20 },
+            eq: { value: 'flat', updatedAt: 0 },
+            font: { value: 'Inter', updatedAt: 5 },
+            unset: { value: null, updatedAt: 0 },
+        };
+        const remote = {
+            theme: { value: 'light', updatedAt: 10 },
+            eq: { value: 'bass', updatedAt: 3 },
+            other: { value: 'x', updatedAt: 1 },
+        };
+
+        const { entries, apply, push } = mergeSettingsEntries(local, remote);
+        expect(apply).toEqual({ eq: 'bass' });
+        expect(push).toBe(true);
+        expect(entries.theme.value).toBe('dark');
+        expect(entries.font.value).toBe('Inter');
+        expect(entries.other.value).toBe('x');
+        expect(entries).not.toHaveProperty('unset');
+
+        // Merging again finds nothing to do
+        const again = mergeSettingsEntries({ ...entries, eq: { value: 'bass', updatedAt: 3 } }, entries);
+        expect(again.apply).toEqual({});
+        expect(again.push).toBe(false);
+    });
+});
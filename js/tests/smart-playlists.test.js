@@ -0,0 +1,119 @@
+import { expect, test, describe } from 'vitest';
+import {
+    createSmartPlaylistContext,
+    describeSmartPlaylist,
+    evaluateSmartPlaylist,
+    getTrackFacts,
+} from '../smart-playlists.js';
+
+const DAY = 24 * 60 * 60 * 1000;
+const NOW = 1_000 * DAY;
+
+const track = (id, overrides = {}) => ({
+    id,
+    title: `Track ${id}`,
+    artists: [{ id: `artist-${id}`, name: `Artist ${id}` }],
+    album: { title: `Album ${id}`, releaseDate: '2010-05-01' },
+    ...overrides,
+});
+
+const tracker = (signals = {}, affinities = {}) => ({
+    getTrackSignal: (id) => signals[id] || null,
+    getArtistAffinity: (id) => affinities[id] || 0,
+});
+
+const titles = (tracks) => tracks.map((t) => t.title);
+
+describe('smart-playlists.js', () => {
+    test('combines likes, history and listening signals into facts', () => {
+        const context = createSmartPlaylistContext(
+            {
+                favorites: [{ id: 1, addedAt: NOW - DAY }],
+                history: [
+                    { id: 2, timestamp: NOW - 3 * DAY },
+                    { id: 2, timestamp: NOW - 2 * DAY },
+                ],
+            },
+            tracker({ 1: { playCount: 12, lastPlayed: NOW - 100 * DAY } }, { 'artist-1': 2.5 }),
+            NOW
+        );
+
+        const liked = getTrackFacts(track(1, { audioQuality: 'HI_RES' }), context);
+        expect(liked).toMatchObject({
+            liked: NOW - DAY,
+            playCount: 12,
+            lastPlayed: NOW - 100 * DAY,
+            artistAffinity: 2.5,
+            quality: 'HI_RES_LOSSLESS',
+            year: 2010,
+        });
+
+        const played = getTrackFacts(track(2), context);
+        expect(played).toMatchObject({ liked: null, playCount: 1, lastPlayed: NOW - 2 * DAY, quality: null });
+    });
+
+    test('filters with all or any of the rules', () => {
+        const tracks = [track(1), track(2, { album: { title: 'Other', releaseDate: '1995-01-01' } }), track(3)];
+        const context = createSmartPlaylistContext(
+            { favorites: [{ id: 1, addedAt: NOW - 5 * DAY }, { id: 3, addedAt: NOW - 60 * DAY }] },
+            tracker({
+                1: { playCount: 3, lastPlayed: NOW - DAY },
+                2: { playCount: 20, lastPlayed: NOW - 200 * DAY },
+                3: { playCount: 15, lastPlayed: NOW - 10 * DAY },
+            }),
+            NOW
+        );
+
+        const likedRecently = { rules: [{ field: 'liked', operator: 'inLast', value: 30 }] };
+        expect(titles(evaluateSmartPlaylist(likedRecently, tracks, context))).toEqual(['Track 1']);
+
+        const forgotten = {
+            match: 'all',
+            rules: [
+                { field: 'playCount', operator: 'gt', value: 10 },
+                { field: 'lastPlayed', operator: 'notInLast', value: 90 },
+            ],
+        };
+        expect(titles(evaluateSmartPlaylist(forgotten, tracks, context))).toEqual(['Track 2']);
+
+        const either = { ...forgotten, match: 'any' };
+        expect(titles(evaluateSmartPlaylist(either, tracks, context))).toEqual(['Track 2', 'Track 3']);
+
+        const nineties = { rules: [{ field: 'year', operator: 'between', value: [1990, 1999] }] };
+        expect(titles(evaluateSmartPlaylist(nineties, tracks, context))).toEqual(['Track 2']);
+
+        const openEnded = { rules: [{ field: 'year', operator: 'between', value: [2000, null] }] };
+        expect(titles(evaluateSmartPlaylist(openEnded, tracks, context))).toEqual(['Track 1', 'Track 3']);
+    });
+
+    test('sorts and limits the matches', () => {
+        const tracks = [track(1), track(2), track(3)];
+        const context = createSmartPlaylistContext(
+            { favorites: [] },
+            tracker({ 1: { playCount: 2 }, 2: { playCount: 9 }, 3: { playCount: 5 } }, { 'artist-2': -1 }),
+            NOW
+        );
+
+        const mostPlayed = { rules: [], sort: 'playCount', limit: 2 };
+        expect(titles(evaluateSmartPlaylist(mostPlayed, tracks, context))).toEqual(['Track 2', 'Track 3']);
+
+        const disliked = { rules: [{ field: 'artistAffinity', operator: 'lt', value: 0 }] };
+        expect(titles(evaluateSmartPlaylist(disliked, tracks, context))).toEqual(['Track 2']);
+
+        const byArtist = { rules: [{ field: 'artist', operator: 'contains', value: ' artist 3 ' }] };
+        expect(titles(evaluateSmartPlaylist(byArtist, tracks, context))).toEqual(['Track 3']);
+    });
+
+    test('describes rules for the playlist header', () => {
+        expect(
+            describeSmartPlaylist({
+                match: 'any',
+                rules: [
+                    { field: 'liked', operator: 'inLast', value: 30 },
+                    { field: 'quality', operator: 'is', value: 'HI_RES_LOSSLESS' },
+                ],
+            })
+        ).toBe('Liked in the last 30 days or Quality is Hi-Res Lossless');
+        expect(describeSmartPlaylist({ rules: [] })).toBe('All tracks in your library');
+    });
+});
@@ -7,6 +7,11 @@ import {
     gaplessPlaybackSettings,
     exponentialVolumeSettings,
     audioEffectsSettings,
+    equalizerSettings,
+    binauralDspSettings,
+    eqDeviceProfileSettings,
+    localMatchSettings,
+    replayGainSettings,
 } from '../storage.js';
 
 describe('storage.js', () => {
@@ -122,4 +127,53 @@ describe('storage.js', () => {
             expect(audioEffectsSettings.getSpeed()).toBe(1.0);
         });
     });
+
+    describe('eqDeviceProfileSettings', () => {
+        test('snapshots restore EQ, graphic EQ and binaural state', () => {
+            equalizerSettings.setPreamp(-4);
+            equalizerSettings.setGraphicEqEnabled(true);
+            binauralDspSettings.setEnabled(true);
+            const snapshot = eqDeviceProfileSettings.captureSnapshot();
+
+            equalizerSettings.setPreamp(2);
+            equalizerSettings.setGraphicEqEnabled(false);
+            binauralDspSettings.setEnabled(false);
+            eqDeviceProfileSettings.applySnapshot(snapshot);
+
+            expect(equalizerSettings.getPreamp()).toBe(-4);
+            expect(equalizerSettings.isGraphicEqEnabled()).toBe(true);
+            expect(binauralDspSettings.isEnabled()).toBe(true);
+        });
+
+        test('deleting a profile drops its bindings', () => {
+            const id = eqDeviceProfileSettings.saveProfile('Headphones', {});
+            eqDeviceProfileSettings.bindDevice({ deviceId: 'abc', label: 'USB DAC' }, id);
+            eqDeviceProfileSettings.setActiveProfile(id);
+            expect(eqDeviceProfileSettings.getBindings().abc.profileId).toBe(id);
+
+            eqDeviceProfileSettings.deleteProfile(id);
+            expect(eqDeviceProfileSettings.getBindings()).toEqual({});
+            expect(eqDeviceProfileSettings.getActiveProfile()).toBeNull();
+        });
+    });
+
+    describe('replayGainSettings', () => {
+        test('loudness analysis is off until turned on', () => {
+            expect(replayGainSettings.isAnalysisEnabled()).toBe(false);
+            replayGainSettings.setAnalysisEnabled(true);
+            expect(replayGainSettings.isAnalysisEnabled()).toBe(true);
+        });
+    });
+
+    describe('localMatchSettings', () => {
+        test('is off and undecided until the user chooses', () => {
+            expect(localMatchSettings.isEnabled()).toBe(false);
+            expect(localMatchSettings.isDecided()).toBe(false);
+
+            localMatchSettings.setEnabled(false);
+            expect(localMatchSettings.isDecided()).toBe(true);
+            localMatchSettings.setEnabled(true);
+            expect(localMatchSettings.isEnabled()).toBe(true);
+        });
+    });
 });
@@ -0,0 +1,111 @@
+import { expect, test, describe } from 'vitest';
+import {
+    applyPlaylistOps,
+    createClock,
+    diffToOps,
+    formatStamp,
+    libraryTarget,
+    mergeOpLogs,
+    playlistTarget,
+    pushOps,
+    tickClock,
+} from '../sync-oplog.js';
+
+const tracks = (...ids) => ids.map((id) => ({ id, title: `Track ${id}` }));
+const ids = (list) => list.map((t) => t.id);
+
+function createDevice(name, start = 1000) {
+    let clock = { ...createClock(name), time: start };
+    return () => {
+        clock = tickClock(clock, start);
+        return formatStamp(clock);
+    };
+}
+
+function createMemoryTransport(initial) {
+    let data = structuredClone(initial);
+    return {
+        load: async () => structuredClone(data),
+        save: async (fields) => {
+            data = { ...data, ...structuredClone(fields) };
+            return structuredClone(data);
+        },
+    };
+}
+
+describe('sync-oplog.js', () => {
+    test('stamps keep increasing when the system time goes back', () => {
+        let clock = tickClock(createClock('a'), 5000);
+        const first = formatStamp(clock);
+        clock = tickClock(clock, 4000);
+        expect(formatStamp(clock) > first).toBe(true);
+        expect(formatStamp(tickClock(clock, 6000)) > formatStamp(clock)).toBe(true);
+    });
+
+    test('replaying the operations of a change reproduces it', () => {
+        const target = playlistTarget('p1');
+        const cases = [
+            [tracks(1, 2, 3), tracks(2, 1, 3)],
+            [tracks(1, 2, 3, 4, 5), tracks(5, 3, 6, 1)],
+            [tracks(), tracks(1, 2)],
+            [tracks(1, 2, 3), tracks(3, 2, 1)],
+        ];
+        for (const [before, after] of cases) {
+            const ops = diffToOps(target, before, after, createDevice('a'));
+            expect(ids(applyPlaylistOps(before, ops))).toEqual(ids(after));
+        }
+    });
+
+    test('edits from two devices are both kept, whatever order they arrive in', () => {
+        const target = playlistTarget('p1');
+        const base = tracks(1, 2, 3);
+        const fromPhone = diffToOps(target, base, tracks(1, 2, 3, 4), createDevice('phone'));
+        const fromLaptop = diffToOps(target, base, tracks(3, 1), createDevice('laptop'));
+
+        const merged = applyPlaylistOps(base, mergeOpLogs(fromPhone, fromLaptop));
+        expect(ids(merged)).toEqual(ids(applyPlaylistOps(base, mergeOpLogs(fromLaptop, fromPhone))));
+        expect(ids(merged).sort()).toEqual([1, 3, 4]);
+
+        // Pushed one after the other, each device rebases on what the other already saved
+        const pushedInTurn = applyPlaylistOps(applyPlaylistOps(base, fromLaptop), fromPhone);
+        expect(ids(pushedInTurn)).toEqual([3, 4, 1]);
+    });
+
+    test('queued operations are replayed on the latest cloud copy', async () => {
+        const transport = createMemoryTransport({
+            library: { tracks: { 1: { id: 1 }, 2: { id: 2 } } },
+            userPlaylists: { p1: { id: 'p1', name: 'Mix', tracks: tracks(1, 2, 3) } },
+        });
+        const nextStamp = createDevice('a');
+        const removeStamp = nextStamp();
+        const ops = [
+            ...diffToOps(playlistTarget('p1'), tracks(1, 2), tracks(2, 1, 9), nextStamp),
+            { id: 'r', target: libraryTarget('track'), type: 'remove', key: '2', stamp: removeStamp },
+            {
+                id: 'a',
+                target: libraryTarget('album'),
+                type: 'add',
+                key: '7',
+                item: { id: 7 },
+                after: null,
+                stamp: nextStamp(),
+            },
+            ...diffToOps(playlistTarget('p2'), [], tracks(5), nextStamp),
+        ];
+        const local = {
+            p1: { id: 'p1', name: 'Renamed', tracks: tracks(2, 1, 9) },
+            p2: { id: 'p2', tracks: tracks(5) },
+        };
+
+        const merged = await pushOps(transport, ops, local);
+        const saved = await transport.load();
+
+        // Track 3 was added on another device and survives the push
+        expect(ids(saved.userPlaylists.p1.tracks)).toEqual([2, 1, 9, 3]);
+        expect(saved.userPlaylists.p1.name).toBe('Renamed');
+        expect(ids(saved.userPlaylists.p2.tracks)).toEqual([5]);
+        expect(Object.keys(saved.library.tracks)).toEqual(['1']);
+        expect(Object.keys(saved.library.albums)).toEqual(['7']);
+        expect([...merged.changed].sort()).toEqual(['library', 'userPlaylists']);
+    });
+});
@@ -0,0 +1,48 @@
+import { expect, test, describe, vi } from 'vitest';
+
+vi.mock('../downloads.js', () => ({
+    showNotification: vi.fn(),
+}));
+
+import { buildTagChanges, getCommonTagValues } from '../tag-editor.js';
+
+describe('tag editor', () => {
+    test('marks fields that differ between files as mixed', () => {
+        const common = getCommonTagValues([
+            { title: 'One', artist: ['A', 'B'], albumTitle: 'Album', trackNumber: 1 },
+            { title: 'Two', artist: 'A; B', albumTitle: 'Album', trackNumber: 2 },
+        ]);
+
+        expect(common.title).toEqual({ value: '', mixed: true });
+        expect(common.artist).toEqual({ value: 'A; B', mixed: false });
+        expect(common.albumTitle).toEqual({ value: 'Album', mixed: false });
+        expect(common.trackNumber.mixed).toBe(true);
+        expect(common.isrc).toEqual({ value: '', mixed: false });
+    });
+
+    test('only writes fields that were changed', () => {
+        const common = getCommonTagValues([
+            { title: 'One', artist: 'A', albumTitle: 'Album', discNumber: 1 },
+            { title: 'Two', artist: 'A', albumTitle: 'Album', discNumber: 1 },
+        ]);
+        const changes = buildTagChanges(
+            { title: '', artist: 'A;  C ', albumTitle: 'Album', trackNumber: '', discNumber: '2', isrc: 'x' },
+            common
+        );
+
+        expect(changes).toEqual({ artist: ['A', 'C'], discNumber: 2, isrc: 'x' });
+    });
+
+    test('removes fields that were emptied', () => {
+        const common = getCommonTagValues([
+            { title: 'One', artist: 'A', albumTitle: 'Album', isrc: 'x', lyrics: 'La' },
+            { title: 'Two', artist: 'A', albumTitle: 'Album', isrc: 'x', lyrics: 'La' },
+        ]);
+        const changes = buildTagChanges(
+            { title: '', artist: ' ', albumTitle: 'Album', trackNumber: '', discNumber: '', isrc: '', lyrics: '' },
+            common
+        );
+
+        expect(changes).toEqual({ artist: null, isrc: null, lyrics: null });
+    });
+});
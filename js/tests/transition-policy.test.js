@@ -0,0 +1,93 @@
+import { expect, test, describe, vi, beforeEach } from 'vitest';
+import { TransitionPolicy, TRANSITION_MODE, isSequentialAlbumTransition } from '../transition-policy.js';
+import { crossfadeSettings } from '../storage.js';
+
+vi.mock('../ModernSettings.js', () => ({
+    modernSettings: {},
+}));
+
+vi.mock('../icons.js', () => ({
+    SVG_ATMOS: () => '<svg>atmos</svg>',
+}));
+
+vi.mock('../storage.js', () => ({
+    qualityBadgeSettings: { isEnabled: vi.fn(() => true) },
+    coverArtSizeSettings: { getSize: vi.fn(() => '1280') },
+    trackDateSettings: { useAlbumYear: vi.fn(() => false) },
+    crossfadeSettings: {
+        getDuration: vi.fn(() => 6),
+        isAlbumAware: vi.fn(() => true),
+    },
+}));
+
+const albumTrack = (trackNumber, volumeNumber = 1, album = { id: 42, numberOfTracks: 4 }) => ({
+    id: `${volumeNumber}-${trackNumber}`,
+    trackNumber,
+    volumeNumber,
+    album,
+});
+
+describe('transition-policy.js', () => {
+    beforeEach(() => {
+        vi.clearAllMocks();
+    });
+
+    describe('isSequentialAlbumTransition', () => {
+        test('detects the next track on the same disc', () => {
+            expect(isSequentialAlbumTransition(albumTrack(1), albumTrack(2))).toBe(true);
+            expect(isSequentialAlbumTransition(albumTrack(1), albumTrack(3))).toBe(false);
+            expect(isSequentialAlbumTransition(albumTrack(2), albumTrack(1))).toBe(false);
+        });
+
+        test('rejects tracks from different albums', () => {
+            const other = albumTrack(2, 1, { id: 7 });
+            expect(isSequentialAlbumTransition(albumTrack(1), other)).toBe(false);
+        });
+
+        test('crosses disc boundaries only from the last track of a disc', () => {
+            const counts = new Map([['id:42', new Map([[1, 2]])]]);
+            expect(isSequentialAlbumTransition(albumTrack(2, 1), albumTrack(1, 2), counts)).toBe(true);
+            expect(isSequentialAlbumTransition(albumTrack(1, 1), albumTrack(1, 2), counts)).toBe(false);
+            expect(isSequentialAlbumTransition(albumTrack(2, 1), albumTrack(2, 2), counts)).toBe(false);
+        });
+
+        test('matches local files by album artist and title', () => {
+            const album = { title: 'Live at Pompeii', artist: { name: 'Band' } };
+            const a = { id: 'local-1', isLocal: true, trackNumber: 3, album };
+            const b = { id: 'local-2', isLocal: true, trackNumber: 4, album: { ...album, title: 'live at pompeii ' } };
+            expect(isSequentialAlbumTransition(a, b)).toBe(true);
+        });
+    });
+
+    describe('TransitionPolicy', () => {
+        test('keeps album runs gapless and crossfades everything else', () => {
+            const policy = new TransitionPolicy();
+            const album = policy.decide(albumTrack(1), albumTrack(2));
+            expect(album.mode).toBe(TRANSITION_MODE.GAPLESS);
+            expect(album.reason).toBe('album');
+
+            const shuffled = policy.decide(albumTrack(1), albumTrack(3));
+            expect(shuffled.mode).toBe(TRANSITION_MODE.CROSSFADE);
+            expect(shuffled.crossfadeDuration).toBe(6);
+        });
+
+        test('per-queue options override user settings', () => {
+            const policy = new TransitionPolicy({ crossfadeDuration: 0, albumAware: false });
+            expect(policy.decide(albumTrack(1), albumTrack(3)).mode).toBe(TRANSITION_MODE.GAPLESS);
+
+            const always = new TransitionPolicy({ crossfadeDuration: 3, albumAware: false });
+            expect(always.decide(albumTrack(1), albumTrack(2)).mode).toBe(TRANSITION_MODE.CROSSFADE);
+            expect(crossfadeSettings.getDuration).not.toHaveBeenCalled();
+        });
+
+        test('indexAlbums records disc sizes for complete albums only', async () => {
+            const policy = new TransitionPolicy();
+            await policy.indexAlbums([albumTrack(1, 1), albumTrack(2, 1), albumTrack(1, 2), albumTrack(2, 2)]);
+            expect(policy.discTrackCounts.get('id:42').get(1)).toBe(2);
+
+            const partial = new TransitionPolicy();
+            await partial.indexAlbums([albumTrack(1, 1), albumTrack(2, 1)]);
+            expect(partial.discTrackCounts.has('id:42')).toBe(false);
+        });
+    });
+});
@@ -0,0 +1,145 @@
+import { expect, test, describe } from 'vitest';
+import { createWebDavBackend, SyncConflictError } from '../accounts/sync-backend.js';
+import { diffToOps, formatStamp, libraryTarget, playlistTarget, pushOps, tickClock } from '../sync-oplog.js';
+
+// Runs against an in-memory stand-in, or a real server (e.g. `rclone serve webdav`) when VITE_WEBDAV_TEST_URL is set
+const WEBDAV_URL = import.meta.env.VITE_WEBDAV_TEST_URL;
+
+const tracks = (...ids) => ids.map((id) => ({ id, title: `Track ${id}` }));
+const ids = (list) => list.map((t) => t.id);
+
+function createDevice(name) {
+    let clock = { time: 1000, counter: 0, device: name };
+    return () => {
+        clock = tickClock(clock, 1000);
+        return formatStamp(clock);
+    };
+}
+
+/** Just enough of WebDAV: GET, PUT and MKCOL, with ETags and conditional PUTs */
+function createFakeWebDav({ getEtags = true, putEtags = true } = {}) {
+    const files = new Map();
+    const folders = new Set(['http://dav.test/']);
+    let version = 0;
+
+    return async (url, { method = 'GET', headers = {}, body } = {}) => {
+        const parent = url.slice(0, url.lastIndexOf('/', url.length - 2) + 1);
+        const file = files.get(url);
+        if (method === 'GET') {
+            if (!file) return new Response(null, { status: 404 });
+            return new Response(file.body, { status: 200, headers: getEtags ? { ETag: file.etag } : {} });
+        }
+        if (method === 'MKCOL') {
+            if (folders.has(url)) return new Response(null, { status: 405 });
+            folders.add(url);
+            return new Response(null, { status: 201 });
+        }
+        if (method === 'PUT') {
+            if (!folders.has(parent)) return new Response(null, { status: 409 });
+            if (headers['If-Match'] && headers['If-Match'] !== file?.etag) return new Response(null, { status: 412 });
+            if (headers['If-None-Match'] === '*' && file) return new Response(null, { status: 412 });
+            const etag = `"${++version}"`;
+            files.set(url, { body, etag });
+            return new Response(null, { status: file ? 204 : 201, headers: putEtags ? { ETag: etag } : {} });
+        }
+        return new Response(null, { status: 405 });
+    };
+}
+
+function connect() {
+    if (WEBDAV_URL) {
+        const folder = `${WEBDAV_URL.replace(/\/$/, '')}/sync-test-${Date.now()}/`;
+        const { VITE_WEBDAV_TEST_USERNAME: username, VITE_WEBDAV_TEST_PASSWORD: password } = import.meta.env;
+        return () => createWebDavBackend({ url: folder, username, password });
+    }
+    const fetchImpl = createFakeWebDav();
+    return () => createWebDavBackend({ url: 'http://dav.test/monochrome' }, fetchImpl);
+}
+
+describe('sync-backend.js WebDAV', () => {
+    test('an empty folder loads as an empty library', async () => {
+        const backend = connect()();
+        const data = await backend.load();
+
+        expect(data.library).toEqual({});
+        expect(data.history).toEqual([]);
+        expect(data.userPlaylists).toEqual({});
+        expect(data.encryption).toBeNull();
+    });
+
+    test("two devices editing the same playlist and library keep each other's changes", async () => {
+        const device = connect();
+        await device().save({
+            library: { tracks: { 1: { id: 1 } } },
+            userPlaylists: { p1: { id: 'p1', name: 'Shared', tracks: tracks(1, 2, 3) } },
+        });
+
+        const phone = createDevice('phone');
+        const laptop = createDevice('laptop');
+        const fromPhone = [
+            ...diffToOps(playlistTarget('p1'), tracks(1, 2, 3), tracks(1, 2, 3, 4), phone),
+            { id: 'phone-remove', target: libraryTarget('track'), type: 'remove', key: '1', stamp: phone() },
+        ];
+        const fromLaptop = [
+            ...diffToOps(playlistTarget('p1'), tracks(1, 2, 3), tracks(3, 1), laptop),
+            {
+                id: 'laptop-add',
+                target: libraryTarget('track'),
+                type: 'add',
+                key: '5',
+                item: { id: 5 },
+                stamp: laptop(),
+            },
+        ];
+
+        await pushOps(device(), fromLaptop);
+        await pushOps(device(), fromPhone);
+
+        const saved = await device().load();
+        expect(ids(saved.userPlaylists.p1.tracks)).toEqual([3, 4, 1]);
+        expect(saved.userPlaylists.p1.name).toBe('Shared');
+        expect(Object.keys(saved.library.tracks)).toEqual(['5']);
+    });
+
+    test('saving over a copy changed by another device is refused', async () => {
+        const device = connect();
+        const phone = device();
+        const laptop = device();
+        await phone.load();
+        await laptop.load();
+
+        await laptop.save({ history: [{ id: 1, timestamp: 2000 }] });
+        await expect(phone.save({ history: [{ id: 2, timestamp: 1000 }] })).rejects.toThrow(SyncConflictError);
+
+        // After loading again it goes through
+        await phone.load();
+        const saved = await phone.save({ history: [{ id: 2, timestamp: 1000 }, { id: 1, timestamp: 2000 }] });
+        expect(saved.history).toHaveLength(2);
+    });
+
+    test('a document whose version the server did not name is never saved over blindly', async () => {
+        const url = 'http://dav.test/monochrome';
+        const server = createFakeWebDav({ putEtags: false });
+        let loadsFail = false;
+        const flaky = (path, options = {}) =>
+            loadsFail && !options.method ? Promise.reject(new TypeError('Failed to fetch')) : server(path, options);
+        const phone = createWebDavBackend({ url }, flaky);
+        const laptop = createWebDavBackend({ url }, server);
+        await phone.load();
+
+        // Saved without an ETag, and the connection dropped before the new version could be loaded
+        loadsFail = true;
+        await expect(phone.save({ history: [{ id: 1, timestamp: 1000 }] })).rejects.toThrow(TypeError);
+        loadsFail = false;
+
+        await laptop.load();
+        await laptop.save({ history: [{ id: 2, timestamp: 2000 }] });
+        await expect(phone.save({ history: [] })).rejects.toThrow(SyncConflictError);
+        expect((await phone.load()).history).toEqual([{ id: 2, timestamp: 2000 }]);
+
+        // A server that never sends ETags can't be saved to safely at all
+        const device = createWebDavBackend({ url }, createFakeWebDav({ getEtags: false, putEtags: false }));
+        await device.save({ history: [{ id: 1, timestamp: 1000 }] });
+        await expect(device.save({ history: [] })).rejects.toThrow(SyncConflictError);
+    });
+});
@@ -0,0 +1,128 @@
+// js/transition-policy.js
+// Decides how the player moves from one queue item to the next.
+// Consecutive tracks of the same release (live albums, concept albums, DJ mixes) keep their
+// authored, gapless transitions; everything else follows the user's crossfade setting.
+
+import { computeDiscInfo, getTrackDiscNumber } from './utils.js';
+import { crossfadeSettings } from './storage.js';
+
+export const TRANSITION_MODE = {
+    GAPLESS: 'gapless',
+    CROSSFADE: 'crossfade',
+};
+
+/**
+ * Stable key identifying the release a track belongs to.
+ * Catalog tracks use the album ID; local files fall back to album artist + title.
+ */
+export function getAlbumKey(track) {
+    const album = track?.album;
+    if (!album) return null;
+    if (album.id) return `id:${album.id}`;
+
+    const title = album.title?.trim().toLowerCase();
+    if (!title) return null;
+    const artist = (album.artist?.name || track.artist?.name || '').trim().toLowerCase();
+    return `local:${artist}|${title}`;
+}
+
+function getTrackNumber(track) {
+    const parsed = parseInt(track?.trackNumber, 10);
+    return parsed > 0 ? parsed : null;
+}
+
+/**
+ * Check whether `to` directly follows `from` on the same release:
+ * the next track number on the same disc, or the first track of the next disc.
+ * @param {object} from
+ * @param {object} to
+ * @param {Map<string, Map<number, number>>} [discTrackCounts] - Tracks per disc, keyed by album key
+ * @returns {boolean}
+ */
+export function isSequentialAlbumTransition(from, to, discTrackCounts = null) {
+    const albumKey = getAlbumKey(from);
+    if (!albumKey || albumKey !== getAlbumKey(to)) return false;
+
+    const fromNumber = getTrackNumber(from);
+    const toNumber = getTrackNumber(to);
+    if (!fromNumber || !toNumber) return false;
+
+    const fromDisc = getTrackDiscNumber(from) || 1;
+    const toDisc = getTrackDiscNumber(to) || 1;
+
+    if (fromDisc === toDisc) {
+        return toNumber === fromNumber + 1;
+    }
+
+    if (toDisc !== fromDisc + 1 || toNumber !== 1) return false;
+
+    const tracksOnDisc = discTrackCounts?.get(albumKey)?.get(fromDisc) ?? from.album?.numberOfTracksOnDisc;
+    return !tracksOnDisc || fromNumber === tracksOnDisc;
+}
+
+/**
+ * Per-queue transition policy consulted by the player and the crossfade engine.
+ * Options left as null follow the user's settings.
+ */
+export class TransitionPolicy {
+    /**
+     * @param {object} [options]
+     * @param {number|null} [options.crossfadeDuration] - Overlap in seconds
+     * @param {boolean|null} [options.albumAware] - Keep same-album runs gapless
+     */
+    constructor({ crossfadeDuration = null, albumAware = null } = {}) {
+        this.crossfadeDuration = crossfadeDuration;
+        this.albumAware = albumAware;
+        this.discTrackCounts = new Map();
+    }
+
+    /**
+     * Record per-disc track counts for every complete album present in the queue,
+     * so disc boundaries can be recognised even when the payload has no disc totals.
+     * @param {Array<object>} tracks
+     */
+    async indexAlbums(tracks) {
+        const groups = new Map();
+        for (const track of tracks || []) {
+            const key = getAlbumKey(track);
+            if (!key || this.discTrackCounts.has(key)) continue;
+            if (!groups.has(key)) groups.set(key, []);
+            groups.get(key).push(track);
+        }
+
+        for (const [key, albumTracks] of groups) {
+            const expected = albumTracks[0].album?.numberOfTracks;
+            if (!expected || albumTracks.length !== expected) continue;
+            const { tracksPerDisc } = await computeDiscInfo(albumTracks);
+            this.discTrackCounts.set(key, tracksPerDisc);
+        }
+    }
+
+    getCrossfadeDuration() {
+        return this.crossfadeDuration ?? crossfadeSettings.getDuration();
+    }
+
+    isAlbumAware() {
+        return this.albumAware ?? crossfadeSettings.isAlbumAware();
+    }
+
+    /**
+     * Decide how to transition between two tracks
+     * @returns {{mode: string, crossfadeDuration: number, reason: 'album'|'policy'|'boundary'}}
+     */
+    decide(from, to) {
+        if (!from || !to) {
+            return { mode: TRANSITION_MODE.GAPLESS, crossfadeDuration: 0, reason: 'boundary' };
+        }
+
+        if (this.isAlbumAware() && isSequentialAlbumTransition(from, to, this.discTrackCounts)) {
+            return { mode: TRANSITION_MODE.GAPLESS, crossfadeDuration: 0, reason: 'album' };
+        }
+
+        const duration = this.getCrossfadeDuration();
+        if (duration > 0) {
+            return { mode: TRANSITION_MODE.CROSSFADE, crossfadeDuration: duration, reason: 'policy' };
+        }
+        return { mode: TRANSITION_MODE.GAPLESS, crossfadeDuration: 0, reason: 'policy' };
+    }
+}
@@ -16,6 +16,7 @@ import {
     decodeHtml,
     getShareUrl,
     createModal,
+    debounce,
 } from './utils.js';
 import { openLyricsPanel, renderLyricsInFullscreen, clearFullscreenLyricsSync } from './lyrics.js';
 import {
@@ -32,6 +33,10 @@ import {
     artistBannerSettings,
 } from './storage.js';
 import { db } from './db.js';
+import { offlineLibrary } from './offline-library.js';
+import { MAIN_ROOT_ID, getLocalRoots, groupLocalTracks, searchLocalTracks } from './local-library.js';
+import { getCatalogTrack } from './local-matcher.js';
+import { describeSmartPlaylist, getSmartPlaylistTracks } from './smart-playlists.js';
 import { getVibrantColorFromImage } from './vibrant-color.js';
 import { syncManager } from './accounts/pocketbase.js';
 import { authManager } from './accounts/auth.js';
@@ -96,12 +101,15 @@ import {
     SVG_SQUARE_PEN,
     SVG_SHARE,
     SVG_UPLOAD,
+    SVG_HISTORY,
     SVG_SHUFFLE,
     SVG_VIDEO,
     SVG_LEFT_ARROW,
     SVG_RIGHT_ARROW,
     SVG_CLOCK,
     SVG_CHECKBOX,
+    SVG_CHECK,
+    SVG_SPARKLES,
 } from './icons.js';
 
 const AOTY_BASE = 'https://aoty.prigoana.pw';
@@ -393,6 +401,25 @@ export class UIRenderer {
             .join('');
     }
 
+    async renderSmartPlaylistsNav() {
+        const list = document.getElementById('smart-playlists-list');
+        if (!list) return;
+
+        const playlists = await db.getSmartPlaylists();
+        list.innerHTML = playlists
+            .map(
+                (playlist) => `
+                <li class="nav-item">
+                    <a href="/smartplaylist/${playlist.id}" title="${escapeHtml(playlist.name)}">
+                        ${SVG_SPARKLES(24)}
+                        <span class="smart-playlist-name">${escapeHtml(playlist.name)}</span>
+                    </a>
+                </li>
+            `
+            )
+            .join('');
+    }
+
     async setCurrentTrack(track) {
         this.currentTrack = track;
         await this.updateGlobalTheme();
@@ -408,6 +435,8 @@ export class UIRenderer {
             const isLocal = track.isLocal;
             const isTracker = track.isTracker || (track.id && String(track.id).startsWith('tracker-'));
             const shouldHideLikes = isLocal || isTracker;
+            // Local files matched to the catalog can be added to playlists and show lyrics
+            const isUnmatchedLocal = isLocal && !getCatalogTrack(track);
 
             if (likeBtn) {
                 if (shouldHideLikes) {
@@ -419,7 +448,7 @@ export class UIRenderer {
             }
 
             if (addPlaylistBtn) {
-                if (isLocal) {
+                if (isUnmatchedLocal) {
                     addPlaylistBtn.style.setProperty('display', 'none', 'important');
                 } else {
                     addPlaylistBtn.style.removeProperty('display');
@@ -427,7 +456,7 @@ export class UIRenderer {
                 }
             }
             if (mobileAddPlaylistBtn) {
-                if (isLocal) {
+                if (isUnmatchedLocal) {
                     mobileAddPlaylistBtn.style.setProperty('display', 'none', 'important');
                 } else {
                     mobileAddPlaylistBtn.style.removeProperty('display');
@@ -435,7 +464,7 @@ export class UIRenderer {
                 }
             }
             if (lyricsBtn) {
-                if (isLocal) lyricsBtn.style.display = 'none';
+                if (isUnmatchedLocal) lyricsBtn.style.display = 'none';
                 else lyricsBtn.style.removeProperty('display');
             }
 
@@ -2169,7 +2198,7 @@ export class UIRenderer {
             if (!isNaN(activeEl.duration)) {
                 progressFill.style.width = `${position * 100}%`;
                 if (currentTimeEl) {
-                    currentTimeEl.textContent = formatTime(position * activeEl.duration);
+                    currentTimeEl.textContent = formatTime(position * this.player.getPosition().duration);
                 }
             }
         };
@@ -2231,7 +2260,7 @@ export class UIRenderer {
             if (isFsSeeking) {
                 const activeEl = this.player.activeElement;
                 if (!isNaN(activeEl.duration)) {
-                    activeEl.currentTime = lastFsSeekPosition * activeEl.duration;
+                    this.player.seekTo(lastFsSeekPosition * this.player.getPosition().duration);
                     if (wasFsPlaying) activeEl.play();
                 }
                 isFsSeeking = false;
@@ -2242,7 +2271,7 @@ export class UIRenderer {
             if (isFsSeeking) {
                 const activeEl = this.player.activeElement;
                 if (!isNaN(activeEl.duration)) {
-                    activeEl.currentTime = lastFsSeekPosition * activeEl.duration;
+                    this.player.seekTo(lastFsSeekPosition * this.player.getPosition().duration);
                     if (wasFsPlaying) activeEl.play();
                 }
                 isFsSeeking = false;
@@ -2387,9 +2416,7 @@ export class UIRenderer {
         const update = () => {
             if (document.getElementById('fullscreen-cover-overlay').style.display === 'none') return;
 
-            const activeEl = this.player.activeElement;
-            const duration = activeEl.duration || 0;
-            const current = activeEl.currentTime || 0;
+            const { currentTime: current, duration } = this.player.getPosition();
 
             if (duration > 0) {
                 // Only update progress if not currently seeking (user is dragging)
@@ -2832,10 +2859,12 @@ export class UIRenderer {
 
         const introDiv = document.getElementById('local-files-intro');
         const headerDiv = document.getElementById('local-files-header');
+        const toolbarDiv = document.getElementById('local-files-toolbar');
         const listContainer = document.getElementById('local-files-list');
         const selectBtnText = document.getElementById('select-local-folder-text');
 
-        const handle = await db.getSetting('local_folder_handle');
+        const roots = await getLocalRoots();
+        const handle = roots.find((root) => root.id === MAIN_ROOT_ID)?.handle || roots[0]?.handle;
         if (handle) {
             if (selectBtnText) selectBtnText.textContent = `Load "${handle.name}"`;
 
@@ -2845,12 +2874,18 @@ export class UIRenderer {
                     headerDiv.style.display = 'flex';
                     headerDiv.querySelector('h3').textContent = `Local Files (${window.localFilesCache.length})`;
                 }
+                if (toolbarDiv) {
+                    toolbarDiv.style.display = 'flex';
+                    this.renderLocalRoots(roots);
+                    this.setupLocalFilesControls(container);
+                }
                 if (listContainer) {
-                    await this.renderListWithTracks(listContainer, window.localFilesCache, true);
+                    await this.renderLocalFilesList(listContainer);
                 }
             } else {
                 if (introDiv) introDiv.style.display = 'block';
                 if (headerDiv) headerDiv.style.display = 'none';
+                if (toolbarDiv) toolbarDiv.style.display = 'none';
                 if (listContainer) listContainer.innerHTML = '';
                 // Kick off a background scan when there is a saved folder handle but
                 // the cache hasn't been populated yet (e.g. first visit after a page
@@ -2863,10 +2898,103 @@ export class UIRenderer {
             if (selectBtnText) selectBtnText.textContent = 'Select Music Folder';
             if (introDiv) introDiv.style.display = 'block';
             if (headerDiv) headerDiv.style.display = 'none';
+            if (toolbarDiv) toolbarDiv.style.display = 'none';
             if (listContainer) listContainer.innerHTML = '';
         }
     }
 
+    renderLocalRoots(roots) {
+        const rootsList = document.getElementById('local-roots-list');
+        if (!rootsList) return;
+
+        rootsList.innerHTML = roots
+            .map(
+                (root) => `
+                <span class="local-root-chip" title="${escapeHtml(root.name)}">
+                    ${escapeHtml(root.name)}
+                    ${
+                        root.id === MAIN_ROOT_ID
+                            ? ''
+                            : `<button class="local-root-remove" data-root-id="${escapeHtml(root.id)}" title="Remove folder">&times;</button>`
+                    }
+                </span>
+            `
+            )
+            .join('');
+    }
+
+    setupLocalFilesControls(container) {
+        const viewSelect = document.getElementById('local-files-view');
+        const searchInput = document.getElementById('local-files-search');
+        if (!viewSelect || !searchInput || viewSelect.dataset.bound) return;
+        viewSelect.dataset.bound = 'true';
+
+        this.setupSearchClearButton(searchInput);
+        this.localFilesView = { mode: viewSelect.value, group: null };
+
+        viewSelect.addEventListener('change', () => {
+            this.localFilesView = { mode: viewSelect.value, group: null };
+            this.renderLocalFiles(container);
+        });
+        searchInput.addEventListener('input', debounce(() => this.renderLocalFiles(container), 200));
+    }
+
+    async renderLocalFilesList(listContainer) {
+        const query = document.getElementById('local-files-search')?.value || '';
+        const { mode, group: groupKey } = this.localFilesView || { mode: 'tracks', group: null };
+        const tracks = searchLocalTracks(window.localFilesCache, query);
+
+        if (mode === 'tracks') {
+            if (tracks.length) {
+                await this.renderListWithTracks(listContainer, tracks, true);
+            } else {
+                listContainer.innerHTML = createPlaceholder('No local files match your search.');
+            }
+            return;
+        }
+
+        const groups = groupLocalTracks(tracks, mode === 'artists' ? 'artist' : 'album');
+        const group = groupKey && groups.find((g) => g.key === groupKey);
+
+        if (group) {
+            listContainer.innerHTML = `
+                <div class="local-group-header">
+                    <button class="btn-secondary local-group-back">&larr; ${mode === 'artists' ? 'Artists' : 'Albums'}</button>
+                    <h4>${escapeHtml(group.title)}</h4>
+                    <span>${escapeHtml(group.subtitle)}</span>
+                </div>
+                <div class="local-group-tracks"></div>
+            `;
+            listContainer.querySelector('.local-group-back').addEventListener('click', () => {
+                this.localFilesView.group = null;
+                this.renderLocalFilesList(listContainer);
+            });
+            await this.renderListWithTracks(listContainer.querySelector('.local-group-tracks'), group.tracks, true);
+            return;
+        }
+
+        listContainer.innerHTML = groups.length
+            ? `<div class="local-group-grid">${groups
+                  .map(
+                      (g) => `
+                <button class="local-group-card" data-group-key="${escapeHtml(g.key)}">
+                    <img src="${escapeHtml(g.cover)}" alt="" loading="lazy" />
+                    <span class="title">${escapeHtml(g.title)}</span>
+                    <span class="subtitle">${escapeHtml(g.subtitle)} &middot; ${g.tracks.length} tracks</span>
+                </button>
+            `
+                  )
+                  .join('')}</div>`
+            : createPlaceholder('No local files match your search.');
+
+        listContainer.querySelectorAll('.local-group-card').forEach((card) => {
+            card.addEventListener('click', () => {
+                this.localFilesView.group = card.dataset.groupKey;
+                this.renderLocalFilesList(listContainer);
+            });
+        });
+    }
+
     async renderHomePage() {
         if (this.renderLock) return;
         this.renderLock = true;
@@ -4412,6 +4540,12 @@ export class UIRenderer {
 
         this.setupSearchTabsLazyLoad();
 
+        // Local files are searched in the index right away, independently of the API search
+        const localTracks = searchLocalTracks(window.localFilesCache || [], query);
+        const localTab = document.getElementById('search-local-tab');
+        if (localTab) localTab.style.display = localTracks.length ? '' : 'none';
+        await this.renderListWithTracks(document.getElementById('search-local-container'), localTracks, true);
+
         try {
             const provider = this.api.getCurrentProvider();
             const results = await this.api.search(query, { signal, provider, enrichArtists: false });
@@ -5539,6 +5673,86 @@ export class UIRenderer {
         }
     }
 
+    async renderSmartPlaylistPage(playlistId) {
+        // Refreshes after library changes keep the current content instead of flashing skeletons
+        const isRefresh = this.currentPage === 'smart-playlist';
+        await this.showPage('smart-playlist');
+
+        const imageEl = document.getElementById('smart-playlist-detail-image');
+        const collageEl = document.getElementById('smart-playlist-detail-collage');
+        const titleEl = document.getElementById('smart-playlist-detail-title');
+        const metaEl = document.getElementById('smart-playlist-detail-meta');
+        const descEl = document.getElementById('smart-playlist-detail-description');
+        const tracklistContainer = document.getElementById('smart-playlist-detail-tracklist');
+
+        if (!isRefresh) {
+            imageEl.src = '';
+            imageEl.style.display = 'block';
+            imageEl.style.backgroundColor = 'var(--muted)';
+            collageEl.style.display = 'none';
+            titleEl.innerHTML = '<div class="skeleton" style="height: 48px; width: 300px; max-width: 90%;"></div>';
+            metaEl.innerHTML = '<div class="skeleton" style="height: 16px; width: 200px; max-width: 80%;"></div>';
+            descEl.innerHTML = '<div class="skeleton" style="height: 16px; width: 100%;"></div>';
+            tracklistContainer.innerHTML = `${TRACKLIST_HEADER_WITH_LIKE_COL_HTML}${this.createSkeletonTracks(10, true)}`;
+        }
+
+        try {
+            const playlist = await db.getSmartPlaylist(playlistId);
+            if (!playlist) throw new Error('Smart playlist not found');
+            const tracks = await getSmartPlaylistTracks(playlist);
+
+            const covers = [...new Set(tracks.map((t) => t.album?.cover).filter(Boolean))].slice(0, 4);
+            if (covers.length > 0) {
+                imageEl.style.display = 'none';
+                collageEl.style.display = 'grid';
+                collageEl.innerHTML = [0, 1, 2, 3]
+                    .map(
+                        (i) =>
+                            `<img crossorigin="anonymous" referrerpolicy="no-referrer" src="${this.api.getCoverUrl(covers[i % covers.length])}">`
+                    )
+                    .join('');
+            } else {
+                imageEl.src = '/assets/appicon.png';
+                imageEl.style.display = 'block';
+                collageEl.style.display = 'none';
+            }
+            this.setPageBackground(null);
+            this.resetVibrantColor();
+
+            titleEl.textContent = playlist.name;
+            this.adjustTitleFontSize(titleEl, playlist.name);
+            metaEl.textContent = `${tracks.length} tracks • ${formatDuration(calculateTotalDuration(tracks))}`;
+            descEl.textContent = playlist.description || describeSmartPlaylist(playlist);
+            descEl.title = describeSmartPlaylist(playlist);
+
+            if (tracks.length > 0) {
+                tracklistContainer.innerHTML = TRACKLIST_HEADER_WITH_LIKE_COL_HTML;
+                await this.renderListWithTracks(tracklistContainer, tracks, true, true, false, true);
+            } else {
+                tracklistContainer.innerHTML = createPlaceholder('No tracks in your library match these rules yet.');
+            }
+
+            document.getElementById('play-smart-playlist-btn').onclick = () => {
+                if (tracks.length === 0) return;
+                this.player.setQueue(tracks, 0);
+                this.player.playTrackFromQueue();
+            };
+            document.getElementById('shuffle-smart-playlist-btn').onclick = () => {
+                if (tracks.length === 0) return;
+                this.player.setQueue([...tracks].sort(() => Math.random() - 0.5), 0);
+                this.player.playTrackFromQueue();
+            };
+
+            document.title = `${playlist.name} - Monochrome`;
+        } catch (error) {
+            console.error('Failed to load smart playlist:', error);
+            titleEl.textContent = '';
+            metaEl.textContent = '';
+            descEl.textContent = '';
+            tracklistContainer.innerHTML = createPlaceholder('Smart playlist not found.');
+        }
+    }
+
     async renderMixPage(mixId, provider = null) {
         await this.showPage('mix');
 
@@ -6627,6 +6841,8 @@ export class UIRenderer {
             'share-playlist-btn',
             'sort-playlist-btn',
             'export-playlist-btn',
+            'history-playlist-btn',
+            'offline-playlist-btn',
         ].forEach((id) => {
             const btn = actionsDiv.querySelector(`#${id}`);
             if (btn) btn.remove();
@@ -6693,6 +6909,44 @@ export class UIRenderer {
             };
         }
 
+        // Keep Offline (Owned Only): re-synced in the background whenever the playlist changes
+        if (this._offlineStatusHandler) {
+            window.removeEventListener('offline-library-changed', this._offlineStatusHandler);
+            this._offlineStatusHandler = null;
+        }
+        if (isOwned) {
+            const playlistId = playlist.id || playlist.uuid;
+            const offlineBtn = document.createElement('button');
+            offlineBtn.id = 'offline-playlist-btn';
+            offlineBtn.className = 'btn-secondary';
+
+            const renderOfflineBtn = () => {
+                const kept = offlineLibrary.isPlaylistKeptOffline(playlistId);
+                const status = offlineLibrary.getSyncStatus(playlistId);
+                let label = 'Keep Offline';
+                if (kept) label = status?.syncing ? `Saving ${status.done}/${status.total}` : 'Offline';
+                offlineBtn.title =
+                    status?.error ||
+                    (kept ? 'Stop keeping this playlist offline' : 'Save this playlist for offline playback');
+                offlineBtn.innerHTML = `${kept ? SVG_CHECK(20) : SVG_DOWNLOAD(20)}<span>${label}</span>`;
+            };
+            renderOfflineBtn();
+
+            offlineBtn.onclick = async () => {
+                const keep = !offlineLibrary.isPlaylistKeptOffline(playlistId);
+                if (!keep && !confirm('Remove the offline copies of this playlist?')) return;
+                await offlineLibrary.setPlaylistKeepOffline(playlistId, keep);
+                renderOfflineBtn();
+            };
+
+            this._offlineStatusHandler = (e) => {
+                if (!offlineBtn.isConnected) return;
+                if (!e.detail?.playlistId || e.detail.playlistId === playlistId) renderOfflineBtn();
+            };
+            window.addEventListener('offline-library-changed', this._offlineStatusHandler);
+            fragment.appendChild(offlineBtn);
+        }
+
         // Edit/Delete (Owned Only)
         if (isOwned) {
             const editBtn = document.createElement('button');
@@ -6709,6 +6963,13 @@ export class UIRenderer {
             exportBtn.innerHTML = `${SVG_UPLOAD(20)}<span>Export</span>`;
             fragment.appendChild(exportBtn);
 
+            const historyBtn = document.createElement('button');
+            historyBtn.id = 'history-playlist-btn';
+            historyBtn.className = 'btn-secondary';
+            historyBtn.title = 'Show earlier versions of this playlist';
+            historyBtn.innerHTML = `${SVG_HISTORY(20)}<span>History</span>`;
+            fragment.appendChild(historyBtn);
+
             const deleteBtn = document.createElement('button');
             deleteBtn.id = 'delete-playlist-btn';
             deleteBtn.className = 'btn-secondary danger';
@@ -6857,7 +7118,7 @@ export class UIRenderer {
                 tracks.splice(0, tracks.length, ...newTracks);
 
                 // Save to DB
-                const updatedPlaylist = await db.updatePlaylistTracks(playlistId, newTracks);
+                const updatedPlaylist = await db.updatePlaylistTracks(playlistId, newTracks, 'reorder');
                 syncManager.syncUserPlaylist(updatedPlaylist, 'update');
 
                 draggedElement = null;